@@ -0,0 +1,699 @@
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use bytes::Bytes;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::s3::S3Client;
+use crate::vfs::{ArchiveEntry, ArchiveIndex, EntryType, JsonEntryHandler};
+
+use super::ArchiveHandler;
+
+/// Handler for JSON and NDJSON files - treats them as virtual directories
+///
+/// Arrays of records (and NDJSON lines) are exposed as `records/N` files and
+/// `columns/KEY` files; top-level objects expose one file per key.
+pub struct JsonHandler;
+
+/// File holding the schema, next to the top-level entries
+const SCHEMA_FILE: &str = "_schema.txt";
+
+/// Byte range of a top-level value within the document
+#[derive(Debug, PartialEq)]
+struct Span {
+    /// Key of the value when the document is an object
+    key: Option<String>,
+    offset: usize,
+    length: usize,
+}
+
+/// Types observed for a top-level record field
+#[derive(Debug)]
+struct FieldInfo {
+    name: String,
+    types: Vec<&'static str>,
+    /// Number of records that contain this field
+    present: usize,
+}
+
+impl JsonHandler {
+    pub fn new() -> Self {
+        JsonHandler
+    }
+
+    /// Check whether a key names a newline-delimited JSON file
+    fn is_json_lines(key: &str) -> bool {
+        let key_lower = key.to_lowercase();
+        key_lower.ends_with(".jsonl") || key_lower.ends_with(".ndjson")
+    }
+
+    /// Find the span of every non-blank line
+    fn line_spans(data: &[u8]) -> Vec<Span> {
+        let mut spans = Vec::new();
+        let mut offset = 0;
+
+        for line in data.split(|b| *b == b'\n') {
+            let trimmed = line.trim_ascii();
+            if !trimmed.is_empty() {
+                let leading = line.len() - line.trim_ascii_start().len();
+                spans.push(Span {
+                    key: None,
+                    offset: offset + leading,
+                    length: trimmed.len(),
+                });
+            }
+            offset += line.len() + 1;
+        }
+
+        spans
+    }
+
+    /// Find the spans of the elements of a top-level array or the values of a
+    /// top-level object. The document must already be known to be valid JSON.
+    fn top_level_spans(data: &[u8]) -> Result<Vec<Span>> {
+        let mut pos = Self::skip_whitespace(data, 0);
+        let is_object = match data.get(pos) {
+            Some(b'[') => false,
+            Some(b'{') => true,
+            _ => return Err(anyhow!("Top-level JSON value must be an array or object")),
+        };
+        pos += 1;
+
+        let mut spans = Vec::new();
+        loop {
+            pos = Self::skip_whitespace(data, pos);
+            match data.get(pos) {
+                Some(b']') | Some(b'}') => break,
+                Some(b',') => {
+                    pos += 1;
+                    continue;
+                }
+                None => return Err(anyhow!("Unexpected end of JSON document")),
+                _ => {}
+            }
+
+            let key = if is_object {
+                let key_end = Self::value_end(data, pos, true);
+                let key: String = serde_json::from_slice(&data[pos..key_end])
+                    .context("Invalid object key in JSON document")?;
+                pos = Self::skip_whitespace(data, key_end);
+                if data.get(pos) != Some(&b':') {
+                    return Err(anyhow!("Expected ':' after object key {key:?}"));
+                }
+                pos = Self::skip_whitespace(data, pos + 1);
+                Some(key)
+            } else {
+                None
+            };
+
+            let end = Self::value_end(data, pos, false);
+            let value = data[pos..end].trim_ascii_end();
+            spans.push(Span {
+                key,
+                offset: pos,
+                length: value.len(),
+            });
+            pos = end;
+        }
+
+        Ok(spans)
+    }
+
+    /// Advance past any whitespace
+    fn skip_whitespace(data: &[u8], mut pos: usize) -> usize {
+        while pos < data.len() && data[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        pos
+    }
+
+    /// Find where the value starting at `start` ends: the first `,`, `]`, or `}`
+    /// that isn't nested inside it (or the first `:` when scanning an object key)
+    fn value_end(data: &[u8], start: usize, is_key: bool) -> usize {
+        let mut depth = 0usize;
+        let mut in_string = false;
+        let mut escaped = false;
+        let mut pos = start;
+
+        while pos < data.len() {
+            let b = data[pos];
+            if in_string {
+                if escaped {
+                    escaped = false;
+                } else if b == b'\\' {
+                    escaped = true;
+                } else if b == b'"' {
+                    in_string = false;
+                }
+            } else {
+                match b {
+                    b'"' => in_string = true,
+                    b'[' | b'{' => depth += 1,
+                    b']' | b'}' if depth == 0 => break,
+                    b']' | b'}' => depth -= 1,
+                    b',' if depth == 0 => break,
+                    b':' if depth == 0 && is_key => break,
+                    _ => {}
+                }
+            }
+            pos += 1;
+        }
+
+        pos
+    }
+
+    /// Parse every record in the document
+    fn parse_records(data: &[u8], json_lines: bool) -> Result<Vec<Value>> {
+        if json_lines {
+            Self::line_spans(data)
+                .iter()
+                .enumerate()
+                .map(|(i, span)| {
+                    serde_json::from_slice(&data[span.offset..span.offset + span.length])
+                        .with_context(|| format!("Invalid JSON on record {i}"))
+                })
+                .collect()
+        } else {
+            match serde_json::from_slice(data).context("Invalid JSON document")? {
+                Value::Array(records) => Ok(records),
+                _ => Err(anyhow!("JSON document is not an array of records")),
+            }
+        }
+    }
+
+    /// Name of a JSON value's type for schema display
+    fn type_name(value: &Value) -> &'static str {
+        match value {
+            Value::Null => "null",
+            Value::Bool(_) => "boolean",
+            Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+        }
+    }
+
+    /// Render a value as a single line for column files
+    /// Strings are printed bare so columns pipe cleanly into sort/uniq
+    fn render_cell(value: Option<&Value>) -> String {
+        match value {
+            Some(Value::String(s)) => s.clone(),
+            Some(v) => v.to_string(),
+            None => "null".to_string(),
+        }
+    }
+
+    /// Infer top-level fields and their types across all records
+    fn infer_fields(records: &[Value]) -> Vec<FieldInfo> {
+        let mut fields: Vec<FieldInfo> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+
+        for record in records {
+            let Value::Object(map) = record else {
+                continue;
+            };
+            for (name, value) in map {
+                let idx = *positions.entry(name.clone()).or_insert_with(|| {
+                    fields.push(FieldInfo {
+                        name: name.clone(),
+                        types: Vec::new(),
+                        present: 0,
+                    });
+                    fields.len() - 1
+                });
+
+                let field = &mut fields[idx];
+                let type_name = Self::type_name(value);
+                if !field.types.contains(&type_name) {
+                    field.types.push(type_name);
+                }
+                field.present += 1;
+            }
+        }
+
+        fields
+    }
+
+    /// Render one column: the value of a field in every record, one per line
+    fn render_column(records: &[Value], name: &str) -> String {
+        let mut output = String::new();
+        for record in records {
+            output.push_str(&Self::render_cell(record.get(name)));
+            output.push('\n');
+        }
+        output
+    }
+
+    /// Where the rendered column of a field is kept in the index metadata
+    fn column_metadata_key(name: &str) -> String {
+        format!("column:{name}")
+    }
+
+    /// A path under `dir` for the file of `key` that no entry has taken yet
+    /// File names can't contain `/`, so it becomes `_`, and names that would be empty or
+    /// `.`/`..` get a leading `_`. When two keys still end up with the same name (e.g.
+    /// "a/b" and "a_b", or a key "_schema.txt"), the later one gets a "~N" suffix.
+    fn unique_path(entries: &HashMap<String, ArchiveEntry>, dir: &str, key: &str) -> String {
+        let mut name = key.replace('/', "_");
+        if matches!(name.as_str(), "" | "." | "..") {
+            name.insert(0, '_');
+        }
+        let path = |name: &str| match dir {
+            "" => name.to_string(),
+            dir => format!("{dir}/{name}"),
+        };
+        let taken = |path: &str| entries.contains_key(path) || path == SCHEMA_FILE;
+
+        let mut candidate = path(&name);
+        let mut n = 2;
+        while taken(&candidate) {
+            candidate = path(&format!("{name}~{n}"));
+            n += 1;
+        }
+        candidate
+    }
+
+    /// Build human-readable schema output
+    fn render_schema(
+        format: &str,
+        count_label: &str,
+        count: usize,
+        fields: &[FieldInfo],
+    ) -> String {
+        let mut output = String::new();
+
+        output.push_str("JSON Schema\n");
+        output.push_str("===========\n\n");
+        output.push_str(&format!("Format: {format}\n"));
+        output.push_str(&format!("{count_label}: {count}\n\n"));
+
+        output.push_str("Fields:\n");
+        output.push_str("-------\n");
+        for field in fields {
+            let optional = if field.present < count {
+                " (optional)"
+            } else {
+                ""
+            };
+            output.push_str(&format!(
+                "  {} : {}{}\n",
+                field.name,
+                field.types.join(" | "),
+                optional
+            ));
+        }
+
+        output
+    }
+
+    /// Add an entry for the value of each key of a top-level object
+    fn add_object_entries(
+        entries: &mut HashMap<String, ArchiveEntry>,
+        data: &[u8],
+        object: &serde_json::Map<String, Value>,
+    ) -> Result<String> {
+        for span in Self::top_level_spans(data)? {
+            let key = span.key.unwrap_or_default();
+            let name = Self::unique_path(entries, "", &key);
+            entries.insert(
+                name.clone(),
+                ArchiveEntry::json_virtual(
+                    name,
+                    span.length as u64,
+                    false, // is_dir
+                    JsonEntryHandler::Value {
+                        offset: span.offset as u64,
+                        length: span.length as u64,
+                    },
+                ),
+            );
+        }
+
+        let fields: Vec<FieldInfo> = object
+            .iter()
+            .map(|(name, value)| FieldInfo {
+                name: name.clone(),
+                types: vec![Self::type_name(value)],
+                present: 1,
+            })
+            .collect();
+
+        Ok(Self::render_schema("JSON object", "Keys", 1, &fields))
+    }
+
+    /// Add records/ and columns/ entries for an array of records
+    /// Columns are rendered now, into `metadata`, while the records are parsed anyway.
+    fn add_record_entries(
+        entries: &mut HashMap<String, ArchiveEntry>,
+        metadata: &mut HashMap<String, String>,
+        spans: &[Span],
+        records: &[Value],
+        format: &str,
+    ) -> String {
+        for dir in ["records", "columns"] {
+            entries.insert(
+                dir.to_string(),
+                ArchiveEntry::json_virtual(
+                    dir.to_string(),
+                    0,
+                    true,                     // is_dir
+                    JsonEntryHandler::Schema, // Placeholder handler (not used for directories)
+                ),
+            );
+        }
+
+        for (i, span) in spans.iter().enumerate() {
+            let path = format!("records/{i}");
+            entries.insert(
+                path.clone(),
+                ArchiveEntry::json_virtual(
+                    path,
+                    span.length as u64,
+                    false, // is_dir
+                    JsonEntryHandler::Value {
+                        offset: span.offset as u64,
+                        length: span.length as u64,
+                    },
+                ),
+            );
+        }
+
+        let fields = Self::infer_fields(records);
+        for field in &fields {
+            let column = Self::render_column(records, &field.name);
+            let path = Self::unique_path(entries, "columns", &field.name);
+            entries.insert(
+                path.clone(),
+                ArchiveEntry::json_virtual(
+                    path,
+                    column.len() as u64,
+                    false, // is_dir
+                    JsonEntryHandler::Column {
+                        name: field.name.clone(),
+                    },
+                ),
+            );
+            metadata.insert(Self::column_metadata_key(&field.name), column);
+        }
+
+        let mut schema = Self::render_schema(format, "Records", records.len(), &fields);
+        if records.iter().any(|r| !r.is_object()) {
+            schema.push_str("\nNote: some records are not objects and have no fields\n");
+        }
+        schema
+    }
+
+    /// Render a single record or object value, fetched with a ranged read
+    async fn render_value(
+        &self,
+        s3_client: &Arc<S3Client>,
+        bucket: &str,
+        key: &str,
+        offset: u64,
+        length: u64,
+    ) -> Result<Bytes> {
+        let data = s3_client
+            .get_object_range(bucket, key, offset, length)
+            .await?;
+        let value: Value = serde_json::from_slice(&data).context("Invalid JSON value")?;
+
+        let mut output = serde_json::to_string_pretty(&value)?;
+        output.push('\n');
+
+        Ok(Bytes::from(output))
+    }
+}
+
+impl Default for JsonHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl ArchiveHandler for JsonHandler {
+    #[tracing::instrument(level = "debug", skip(self, s3_client))]
+    async fn build_index(
+        &self,
+        s3_client: &Arc<S3Client>,
+        bucket: &str,
+        key: &str,
+    ) -> Result<ArchiveIndex> {
+        // JSON has no index structure, so the whole document must be read once
+        let data = s3_client.get_object(bucket, key).await?;
+
+        let mut entries = HashMap::new();
+        let mut metadata = HashMap::new();
+        let schema = if Self::is_json_lines(key) {
+            let spans = Self::line_spans(&data);
+            let records = Self::parse_records(&data, true)?;
+            Self::add_record_entries(&mut entries, &mut metadata, &spans, &records, "JSON Lines")
+        } else {
+            match serde_json::from_slice(&data).context("Invalid JSON document")? {
+                Value::Array(records) => {
+                    let spans = Self::top_level_spans(&data)?;
+                    Self::add_record_entries(
+                        &mut entries,
+                        &mut metadata,
+                        &spans,
+                        &records,
+                        "JSON array",
+                    )
+                }
+                Value::Object(object) => Self::add_object_entries(&mut entries, &data, &object)?,
+                _ => return Err(anyhow!("Top-level JSON value must be an array or object")),
+            }
+        };
+
+        entries.insert(
+            SCHEMA_FILE.to_string(),
+            ArchiveEntry::json_virtual(
+                SCHEMA_FILE.to_string(),
+                schema.len() as u64,
+                false, // is_dir
+                JsonEntryHandler::Schema,
+            ),
+        );
+        metadata.insert("schema".to_string(), schema);
+
+        Ok(ArchiveIndex {
+            entries,
+            metadata,
+            #[cfg(feature = "parquet")]
+            parquet_store: None,
+            #[cfg(feature = "parquet")]
+            parquet_metadata: None,
+        })
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, s3_client, index))]
+    async fn extract_file(
+        &self,
+        s3_client: &Arc<S3Client>,
+        bucket: &str,
+        key: &str,
+        index: &ArchiveIndex,
+        file_path: &str,
+    ) -> Result<Bytes> {
+        let entry = index
+            .entries
+            .get(file_path)
+            .ok_or_else(|| anyhow!("File not found in JSON archive: {file_path}"))?;
+
+        if entry.is_dir {
+            return Err(anyhow!("Cannot extract directory: {file_path}"));
+        }
+
+        match &entry.entry_type {
+            EntryType::JsonVirtual { handler } => match handler {
+                JsonEntryHandler::Schema => {
+                    let schema = index
+                        .metadata
+                        .get("schema")
+                        .ok_or_else(|| anyhow!("Schema not found in index metadata"))?;
+                    Ok(Bytes::from(schema.clone()))
+                }
+                JsonEntryHandler::Column { name } => {
+                    let column = index
+                        .metadata
+                        .get(&Self::column_metadata_key(name))
+                        .ok_or_else(|| anyhow!("Column {name} not found in index metadata"))?;
+                    Ok(Bytes::from(column.clone()))
+                }
+                JsonEntryHandler::Value { offset, length } => {
+                    self.render_value(s3_client, bucket, key, *offset, *length)
+                        .await
+                }
+            },
+            _ => Err(anyhow!("Invalid entry type for JSON handler")),
+        }
+    }
+
+    fn list_entries<'a>(&self, index: &'a ArchiveIndex, path: &str) -> Vec<&'a ArchiveEntry> {
+        let normalized_path = if path.is_empty() || path == "/" {
+            ""
+        } else {
+            path.trim_start_matches('/').trim_end_matches('/')
+        };
+
+        let search_prefix = if normalized_path.is_empty() {
+            String::new()
+        } else {
+            format!("{normalized_path}/")
+        };
+
+        let mut result: Vec<&ArchiveEntry> = index
+            .entries
+            .iter()
+            .filter_map(|(entry_path, entry)| {
+                let relative = entry_path.strip_prefix(&search_prefix)?;
+                // Only direct children; every directory has an explicit entry
+                (!relative.is_empty() && !relative.contains('/')).then_some(entry)
+            })
+            .collect();
+
+        // Directories first, then files; records sort numerically
+        result.sort_by(|a, b| {
+            b.is_dir.cmp(&a.is_dir).then_with(|| {
+                let a_num = a
+                    .path
+                    .rsplit('/')
+                    .next()
+                    .and_then(|n| n.parse::<u64>().ok());
+                let b_num = b
+                    .path
+                    .rsplit('/')
+                    .next()
+                    .and_then(|n| n.parse::<u64>().ok());
+                match (a_num, b_num) {
+                    (Some(a_num), Some(b_num)) => a_num.cmp(&b_num),
+                    _ => a.path.cmp(&b.path),
+                }
+            })
+        });
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span_text<'a>(data: &'a [u8], span: &Span) -> &'a str {
+        std::str::from_utf8(&data[span.offset..span.offset + span.length]).unwrap()
+    }
+
+    #[test]
+    fn test_line_spans() {
+        let data = b"{\"a\":1}\n\n  {\"a\":2}  \r\n{\"a\":3}";
+        let spans = JsonHandler::line_spans(data);
+        assert_eq!(spans.len(), 3);
+        assert_eq!(span_text(data, &spans[0]), "{\"a\":1}");
+        assert_eq!(span_text(data, &spans[1]), "{\"a\":2}");
+        assert_eq!(span_text(data, &spans[2]), "{\"a\":3}");
+    }
+
+    #[test]
+    fn test_top_level_array_spans() {
+        let data = br#" [ {"s": "a,]}\"b", "n": [1, 2]}, 42 ,"x" ] "#;
+        let spans = JsonHandler::top_level_spans(data).unwrap();
+        assert_eq!(spans.len(), 3);
+        assert_eq!(
+            span_text(data, &spans[0]),
+            r#"{"s": "a,]}\"b", "n": [1, 2]}"#
+        );
+        assert_eq!(span_text(data, &spans[1]), "42");
+        assert_eq!(span_text(data, &spans[2]), "\"x\"");
+        assert!(spans.iter().all(|s| s.key.is_none()));
+    }
+
+    #[test]
+    fn test_top_level_object_spans() {
+        let data = br#"{"na:me": "v", "nested" : {"k": [1, {"z": null}]}, "e": []}"#;
+        let spans = JsonHandler::top_level_spans(data).unwrap();
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans[0].key.as_deref(), Some("na:me"));
+        assert_eq!(span_text(data, &spans[0]), "\"v\"");
+        assert_eq!(spans[1].key.as_deref(), Some("nested"));
+        assert_eq!(span_text(data, &spans[1]), r#"{"k": [1, {"z": null}]}"#);
+        assert_eq!(span_text(data, &spans[2]), "[]");
+
+        assert!(JsonHandler::top_level_spans(b"42").is_err());
+    }
+
+    #[test]
+    fn test_infer_fields() {
+        let records: Vec<Value> = serde_json::from_str(
+            r#"[{"id": 1, "name": "a"}, {"id": 2.5, "tags": []}, {"id": 3, "name": null}]"#,
+        )
+        .unwrap();
+        let fields = JsonHandler::infer_fields(&records);
+
+        assert_eq!(fields.len(), 3);
+        assert_eq!(fields[0].name, "id");
+        assert_eq!(fields[0].types, vec!["integer", "number"]);
+        assert_eq!(fields[0].present, 3);
+        assert_eq!(fields[1].name, "name");
+        assert_eq!(fields[1].types, vec!["string", "null"]);
+        assert_eq!(fields[2].present, 1);
+
+        assert_eq!(JsonHandler::render_column(&records, "id"), "1\n2.5\n3\n");
+        // Missing and null values both render as null
+        assert_eq!(
+            JsonHandler::render_column(&records, "name"),
+            "a\nnull\nnull\n"
+        );
+
+        let schema = JsonHandler::render_schema("JSON array", "Records", 3, &fields);
+        assert!(schema.contains("Records: 3"));
+        assert!(schema.contains("  id : integer | number\n"));
+        assert!(schema.contains("  tags : array (optional)\n"));
+    }
+
+    #[test]
+    fn test_colliding_names() {
+        let data = br#"{"a/b": 1, "a_b": 2, "_schema.txt": 3, "": 4, "..": 5}"#;
+        let object = serde_json::from_slice(data).unwrap();
+        let mut entries = HashMap::new();
+        JsonHandler::add_object_entries(&mut entries, data, &object).unwrap();
+        let mut names: Vec<&str> = entries.keys().map(String::as_str).collect();
+        names.sort();
+        assert_eq!(names, ["_", "_..", "_schema.txt~2", "a_b", "a_b~2"]);
+
+        let records: Vec<Value> = serde_json::from_str(r#"[{"a/b": 1, "a_b": 2}]"#).unwrap();
+        let spans = JsonHandler::top_level_spans(br#"[{"a/b": 1, "a_b": 2}]"#).unwrap();
+        let mut entries = HashMap::new();
+        let mut metadata = HashMap::new();
+        JsonHandler::add_record_entries(&mut entries, &mut metadata, &spans, &records, "JSON");
+        assert!(entries.contains_key("columns/a_b") && entries.contains_key("columns/a_b~2"));
+        assert_eq!(metadata["column:a/b"], "1\n");
+        assert_eq!(metadata["column:a_b"], "2\n");
+    }
+
+    #[tokio::test]
+    async fn test_columns_rendered_with_index() {
+        let mock = crate::s3::MockS3Client::new().with_object(
+            "b",
+            "people.json",
+            r#"[{"name": "ann"}, {"name": "bo"}]"#,
+        );
+        let client = Arc::new(mock.client());
+        let handler = JsonHandler::new();
+        let index = handler
+            .build_index(&client, "b", "people.json")
+            .await
+            .unwrap();
+
+        // Reading a column doesn't fetch the document again
+        client.delete_object("b", "people.json").await.unwrap();
+        let column = handler
+            .extract_file(&client, "b", "people.json", &index, "columns/name")
+            .await
+            .unwrap();
+        assert_eq!(column, "ann\nbo\n");
+        assert_eq!(index.entries["columns/name"].size, 7);
+    }
+}
@@ -1,16 +1,114 @@
+pub mod json;
 pub mod tar;
+pub mod xlsx;
 pub mod zip;
 
 #[cfg(feature = "parquet")]
 pub mod parquet;
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use async_trait::async_trait;
 use bytes::Bytes;
 use std::sync::Arc;
 
 use crate::s3::S3Client;
-use crate::vfs::{ArchiveEntry, ArchiveIndex};
+use crate::vfs::{ArchiveEntry, ArchiveIndex, ArchiveType};
+
+/// Number of rows shown from tabular virtual files when no range is given
+pub const DEFAULT_ROW_LIMIT: usize = 100;
+
+/// Window of rows to read from a tabular virtual file (e.g. Parquet columns)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RowRange {
+    /// Rows to skip from the start
+    pub skip: usize,
+    /// Maximum number of rows to return
+    pub limit: usize,
+}
+
+impl Default for RowRange {
+    fn default() -> Self {
+        RowRange {
+            skip: 0,
+            limit: DEFAULT_ROW_LIMIT,
+        }
+    }
+}
+
+/// Default largest decompressed size of a single extracted file (1 GiB)
+pub const DEFAULT_MAX_EXTRACT_SIZE: u64 = 1 << 30;
+
+/// Default largest compression ratio (1000:1) before a file is taken for a zip bomb
+pub const DEFAULT_MAX_COMPRESSION_RATIO: u64 = 1000;
+
+/// Safety limits for decompressing files out of archives (zip bomb protection)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtractLimits {
+    /// Largest decompressed size of one file (None = unlimited)
+    pub max_size: Option<u64>,
+    /// Largest ratio of decompressed to compressed size (None = unlimited)
+    pub max_ratio: Option<u64>,
+}
+
+impl Default for ExtractLimits {
+    fn default() -> Self {
+        ExtractLimits {
+            max_size: Some(DEFAULT_MAX_EXTRACT_SIZE),
+            max_ratio: Some(DEFAULT_MAX_COMPRESSION_RATIO),
+        }
+    }
+}
+
+impl ExtractLimits {
+    /// No limits, for `--no-limits` on data known to be safe
+    pub const UNLIMITED: ExtractLimits = ExtractLimits {
+        max_size: None,
+        max_ratio: None,
+    };
+
+    /// Refuse a file whose declared sizes break the limits, before reading it
+    pub fn check(&self, size: u64, compressed_size: u64) -> Result<()> {
+        if let Some(max_size) = self.max_size
+            && size > max_size
+        {
+            return Err(anyhow!(
+                "File too large to extract safely: {size} bytes exceeds {max_size} byte limit \
+                 (see `set max_extract_size`, or --no-limits)"
+            ));
+        }
+        if let Some(max_ratio) = self.max_ratio
+            && compressed_size > 0
+            && size / compressed_size > max_ratio
+        {
+            return Err(anyhow!(
+                "Suspicious compression ratio detected ({:.0}:1). File may be a zip bomb \
+                 (see `set max_compression_ratio`, or --no-limits).",
+                size as f64 / compressed_size as f64
+            ));
+        }
+        Ok(())
+    }
+}
+
+tokio::task_local! {
+    /// Limits overridden for one command (`--no-limits`), in place of the client's
+    static EXTRACT_LIMITS: ExtractLimits;
+}
+
+/// Run `f` with `limits` in place of the client's for every extraction it makes
+///
+/// The override is scoped to the task, so it never outlives the command that asked for
+/// it or reaches background jobs and other commands sharing the client.
+pub async fn with_extract_limits<F: Future>(limits: ExtractLimits, f: F) -> F::Output {
+    EXTRACT_LIMITS.scope(limits, f).await
+}
+
+/// The limits extraction is held to: a command's override, else the client's
+pub fn extract_limits(s3_client: &S3Client) -> ExtractLimits {
+    EXTRACT_LIMITS
+        .try_with(|limits| *limits)
+        .unwrap_or_else(|_| s3_client.extract_limits())
+}
 
 /// Trait for handling different archive formats
 #[async_trait]
@@ -38,5 +136,204 @@ pub trait ArchiveHandler: Send + Sync {
     fn list_entries<'a>(&self, index: &'a ArchiveIndex, path: &str) -> Vec<&'a ArchiveEntry>;
 }
 
+/// Bytes read from the start of an object to sniff its format
+pub const SNIFF_LEN: u64 = 512;
+
+/// A format s3sh recognizes: how to detect it and which handler reads it
+pub struct ArchiveFormat {
+    pub archive_type: ArchiveType,
+    /// Lowercase file name suffixes; the longest match wins, so `.tar.gz` beats `.gz`
+    pub extensions: &'static [&'static str],
+    /// Content types objects in this format are stored with
+    pub mime_types: &'static [&'static str],
+    /// Whether the first bytes of an object are this format's signature
+    pub sniff: Option<fn(&[u8]) -> bool>,
+    /// Creates the handler that indexes and reads the format (None = detected but
+    /// can't be navigated, like plain .gz/.bz2)
+    pub handler: Option<fn() -> Box<dyn ArchiveHandler>>,
+}
+
+impl ArchiveFormat {
+    /// Whether the format can be opened like a directory
+    pub fn is_navigable(&self) -> bool {
+        self.handler.is_some()
+    }
+}
+
+/// Every supported format; new formats only need an entry here (and an ArchiveType)
+/// Sniffers are tried in order, so looser signatures (JSON) come last.
+pub static FORMATS: &[ArchiveFormat] = &[
+    ArchiveFormat {
+        archive_type: ArchiveType::Zip,
+        extensions: &[".zip"],
+        mime_types: &["application/zip", "application/x-zip-compressed"],
+        sniff: Some(|bytes| bytes.starts_with(b"PK\x03\x04") || bytes.starts_with(b"PK\x05\x06")),
+        handler: Some(|| Box::new(zip::ZipHandler::new())),
+    },
+    // Xlsx files are zips, so they're only recognized by name or content type
+    ArchiveFormat {
+        archive_type: ArchiveType::Xlsx,
+        extensions: &[".xlsx"],
+        mime_types: &["application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"],
+        sniff: None,
+        handler: Some(|| Box::new(xlsx::XlsxHandler::new())),
+    },
+    #[cfg(feature = "parquet")]
+    ArchiveFormat {
+        archive_type: ArchiveType::Parquet,
+        extensions: &[".parquet"],
+        mime_types: &["application/vnd.apache.parquet", "application/x-parquet"],
+        sniff: Some(|bytes| bytes.starts_with(b"PAR1")),
+        handler: Some(|| Box::new(ParquetHandler::new())),
+    },
+    ArchiveFormat {
+        archive_type: ArchiveType::Tar,
+        extensions: &[".tar"],
+        mime_types: &["application/x-tar"],
+        sniff: Some(|bytes| bytes.get(257..262) == Some(b"ustar")),
+        handler: Some(|| Box::new(tar::TarHandler::new(ArchiveType::Tar))),
+    },
+    // Compressed tars look like plain .gz/.bz2 until decompressed
+    ArchiveFormat {
+        archive_type: ArchiveType::TarGz,
+        extensions: &[".tar.gz", ".tgz"],
+        mime_types: &["application/x-gtar", "application/x-compressed-tar"],
+        sniff: None,
+        handler: Some(|| Box::new(tar::TarHandler::new(ArchiveType::TarGz))),
+    },
+    ArchiveFormat {
+        archive_type: ArchiveType::TarBz2,
+        extensions: &[".tar.bz2", ".tbz2"],
+        mime_types: &["application/x-bzip-compressed-tar"],
+        sniff: None,
+        handler: Some(|| Box::new(tar::TarHandler::new(ArchiveType::TarBz2))),
+    },
+    ArchiveFormat {
+        archive_type: ArchiveType::Gz,
+        extensions: &[".gz"],
+        mime_types: &["application/gzip", "application/x-gzip"],
+        sniff: Some(|bytes| bytes.starts_with(&[0x1f, 0x8b])),
+        handler: None,
+    },
+    ArchiveFormat {
+        archive_type: ArchiveType::Bz2,
+        extensions: &[".bz2"],
+        mime_types: &["application/x-bzip2"],
+        sniff: Some(|bytes| bytes.starts_with(b"BZh")),
+        handler: None,
+    },
+    ArchiveFormat {
+        archive_type: ArchiveType::Json,
+        extensions: &[".json", ".jsonl", ".ndjson"],
+        mime_types: &["application/json", "application/x-ndjson"],
+        sniff: Some(|bytes| {
+            matches!(
+                bytes.iter().find(|b| !b.is_ascii_whitespace()),
+                Some(b'{' | b'[')
+            )
+        }),
+        handler: Some(|| Box::new(json::JsonHandler::new())),
+    },
+];
+
+/// The registry entry for an archive type
+pub fn format_for(archive_type: &ArchiveType) -> &'static ArchiveFormat {
+    FORMATS
+        .iter()
+        .find(|format| format.archive_type == *archive_type)
+        .expect("every archive type has a registered format")
+}
+
+/// The format a file name's extension names
+pub fn format_for_name(path: &str) -> Option<&'static ArchiveFormat> {
+    let path = path.to_lowercase();
+    FORMATS
+        .iter()
+        .filter_map(|format| {
+            let ext = format.extensions.iter().find(|ext| path.ends_with(*ext))?;
+            Some((ext.len(), format))
+        })
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, format)| format)
+}
+
+/// The format an object's content type names (parameters like `; charset=` are ignored)
+pub fn format_for_mime(content_type: &str) -> Option<&'static ArchiveFormat> {
+    let mime = content_type.split(';').next()?.trim().to_lowercase();
+    FORMATS
+        .iter()
+        .find(|format| format.mime_types.contains(&mime.as_str()))
+}
+
+/// The format whose signature the first bytes of an object match
+pub fn sniff(bytes: &[u8]) -> Option<&'static ArchiveFormat> {
+    FORMATS
+        .iter()
+        .find(|format| format.sniff.is_some_and(|sniff| sniff(bytes)))
+}
+
+/// Handler for an archive type, or None for types that can't be navigated (plain .gz/.bz2)
+pub fn handler_for(archive_type: &ArchiveType) -> Option<Box<dyn ArchiveHandler>> {
+    format_for(archive_type).handler.map(|new| new())
+}
+
 #[cfg(feature = "parquet")]
-pub use parquet::ParquetHandler;
+pub use parquet::{ExportFormat, ParquetHandler};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_limits() {
+        let limits = ExtractLimits::default();
+        assert!(limits.check(1 << 20, 1 << 12).is_ok());
+        assert!(limits.check(2 << 30, 1 << 30).is_err());
+        assert!(limits.check(10 << 20, 1 << 10).is_err());
+
+        let ratio_off = ExtractLimits {
+            max_ratio: None,
+            ..limits
+        };
+        assert!(ratio_off.check(10 << 20, 1 << 10).is_ok());
+        assert!(ExtractLimits::UNLIMITED.check(u64::MAX, 1).is_ok());
+    }
+
+    #[test]
+    fn test_format_for_name() {
+        let detect = |path| format_for_name(path).map(|f| f.archive_type.clone());
+        assert_eq!(detect("a/b.TAR.GZ"), Some(ArchiveType::TarGz));
+        assert_eq!(detect("b.tgz"), Some(ArchiveType::TarGz));
+        assert_eq!(detect("b.gz"), Some(ArchiveType::Gz));
+        assert_eq!(detect("b.tar.bz2"), Some(ArchiveType::TarBz2));
+        assert_eq!(detect("b.ndjson"), Some(ArchiveType::Json));
+        assert_eq!(detect("b.txt"), None);
+        assert!(!format_for(&ArchiveType::Gz).is_navigable());
+        assert!(format_for(&ArchiveType::Zip).is_navigable());
+    }
+
+    #[test]
+    fn test_format_for_mime() {
+        let detect = |mime| format_for_mime(mime).map(|f| f.archive_type.clone());
+        assert_eq!(detect("application/zip"), Some(ArchiveType::Zip));
+        assert_eq!(
+            detect("Application/JSON; charset=utf-8"),
+            Some(ArchiveType::Json)
+        );
+        assert_eq!(detect("text/plain"), None);
+    }
+
+    #[test]
+    fn test_sniff() {
+        let detect = |bytes: &[u8]| sniff(bytes).map(|f| f.archive_type.clone());
+        assert_eq!(detect(b"PK\x03\x04rest"), Some(ArchiveType::Zip));
+        assert_eq!(detect(&[0x1f, 0x8b, 8, 0]), Some(ArchiveType::Gz));
+        assert_eq!(detect(b"  \n[1, 2]"), Some(ArchiveType::Json));
+        assert_eq!(detect(b"hello"), None);
+
+        let mut tar = vec![0u8; 512];
+        tar[..5].copy_from_slice(b"a.txt");
+        tar[257..262].copy_from_slice(b"ustar");
+        assert_eq!(detect(&tar), Some(ArchiveType::Tar));
+    }
+}
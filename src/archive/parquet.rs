@@ -1,35 +1,61 @@
 use anyhow::{Context, Result, anyhow};
 use async_trait::async_trait;
 use bytes::Bytes;
-use futures::stream::StreamExt;
+use futures::stream::{BoxStream, StreamExt};
 use std::collections::HashMap;
 use std::sync::Arc;
 
 use arrow_array::cast::{
-    as_boolean_array, as_generic_binary_array, as_primitive_array, as_string_array,
+    AsArray, as_boolean_array, as_generic_binary_array, as_primitive_array, as_string_array,
 };
 use arrow_array::types::*;
 use arrow_array::*;
 use arrow_schema::{DataType, Schema};
 use chrono;
 use object_store::aws::AmazonS3Builder;
+use object_store::http::HttpBuilder;
 use object_store::{ObjectStore, path::Path as ObjectPath};
 use parquet::arrow::{
     ParquetRecordBatchStreamBuilder, ProjectionMask,
-    async_reader::{AsyncFileReader, ParquetObjectReader},
+    arrow_reader::{ArrowReaderMetadata, ArrowReaderOptions},
+    async_reader::{AsyncFileReader, ParquetObjectReader, ParquetRecordBatchStream},
 };
 use parquet::file::metadata::ParquetMetaData;
+use parquet::file::statistics::Statistics;
 
 use aws_credential_types::provider::ProvideCredentials;
+use datafusion::arrow::{csv, json};
+use datafusion::execution::object_store::ObjectStoreUrl;
+use datafusion::prelude::{ParquetReadOptions, SessionContext};
 
 use crate::s3::S3Client;
+use crate::s3::http::is_http_origin;
 use crate::vfs::{ArchiveEntry, ArchiveIndex, EntryType, ParquetEntryHandler};
 
-use super::ArchiveHandler;
+use super::{ArchiveHandler, DEFAULT_ROW_LIMIT, RowRange};
 
 /// Handler for Parquet files - treats them as virtual directories
 pub struct ParquetHandler;
 
+/// Formats `cat --format` can convert a Parquet file to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    /// Newline-delimited JSON, one object per row
+    Json,
+}
+
+impl ExportFormat {
+    /// Parse a format name (csv, json)
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "csv" => Some(Self::Csv),
+            "json" | "jsonl" | "ndjson" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
 // Timeout constants for operations
 const METADATA_READ_TIMEOUT_SECS: u64 = 30; // Timeout for reading Parquet footer metadata
 const DATA_READ_TIMEOUT_SECS: u64 = 60; // Timeout for reading column data
@@ -39,53 +65,65 @@ impl ParquetHandler {
         ParquetHandler
     }
 
-    /// Load AWS config once to be reused across operations
-    /// This prevents duplicate credential loading
-    async fn load_aws_config() -> aws_config::SdkConfig {
-        aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await
-    }
-
-    /// Create an object_store S3 client from pre-loaded AWS config
-    /// Accepts config as parameter to avoid redundant credential loading
+    /// Create an object_store S3 client that talks to the same service as `s3_client`
+    /// Honors the active provider's endpoint, anonymous mode, and addressing style
     async fn create_object_store(
-        config: &aws_config::SdkConfig,
+        s3_client: &S3Client,
         bucket: &str,
     ) -> Result<Arc<dyn ObjectStore>> {
-        // Config is passed in to avoid duplicate aws_config::load_defaults() calls
-
-        // Extract region with proper fallback chain
-        let region = config
-            .region()
-            .map(|r| r.as_ref().to_string())
-            .or_else(|| std::env::var("AWS_DEFAULT_REGION").ok())
-            .unwrap_or_else(|| {
-                eprintln!(
-                    "Warning: No AWS region configured. Using us-west-2 as fallback. \
-                          Set AWS_DEFAULT_REGION or configure region in ~/.aws/config"
-                );
-                "us-west-2".to_string()
-            });
+        // Plain HTTP(S) files are read with range requests against the origin
+        if is_http_origin(bucket) {
+            let store = HttpBuilder::new()
+                .with_url(bucket)
+                .build()
+                .context("Failed to create object_store HTTP client")?;
+            return Ok(Arc::new(store));
+        }
 
-        // Get credentials from aws-config - this supports the full AWS credential provider chain
-        // including ~/.aws/credentials, environment variables, IAM roles, etc.
-        let credentials = config
-            .credentials_provider()
-            .ok_or_else(|| anyhow!("No credentials provider found in AWS config"))?
-            .provide_credentials()
-            .await
-            .context("Failed to load AWS credentials")?;
+        // Buckets mounted from another provider use that provider's endpoint and keys
+        let mounted = s3_client.mounted_client(bucket);
+        let s3_client = mounted.as_deref().unwrap_or(s3_client);
+        let provider = s3_client.provider_config();
+
+        let region = match provider.and_then(|p| p.default_region.clone()) {
+            Some(region) => region,
+            None => s3_client.bucket_region(bucket).await,
+        };
 
-        // Build object_store S3 client with explicit credentials
         // Note: Timeouts are managed at the operation level using tokio::time::timeout
         let mut builder = AmazonS3Builder::new()
             .with_bucket_name(bucket)
-            .with_region(&region)
-            .with_access_key_id(credentials.access_key_id())
-            .with_secret_access_key(credentials.secret_access_key());
+            .with_region(&region);
+
+        if let Some(p) = provider
+            && let Some(endpoint) = &p.endpoint_url
+        {
+            builder = builder
+                .with_endpoint(endpoint)
+                .with_allow_http(endpoint.starts_with("http://"))
+                .with_virtual_hosted_style_request(!p.force_path_style);
+        }
 
-        // Add session token if present (for temporary credentials)
-        if let Some(token) = credentials.session_token() {
-            builder = builder.with_token(token);
+        if provider.is_some_and(|p| p.anonymous) {
+            builder = builder.with_skip_signature(true);
+        } else {
+            // Same credential chain the provider uses (~/.aws/credentials, env vars, IAM roles, etc.)
+            let config = s3_client.sdk_config().await;
+            let credentials = config
+                .credentials_provider()
+                .ok_or_else(|| anyhow!("No credentials provider found in AWS config"))?
+                .provide_credentials()
+                .await
+                .context("Failed to load AWS credentials")?;
+
+            builder = builder
+                .with_access_key_id(credentials.access_key_id())
+                .with_secret_access_key(credentials.secret_access_key());
+
+            // Add session token if present (for temporary credentials)
+            if let Some(token) = credentials.session_token() {
+                builder = builder.with_token(token);
+            }
         }
 
         let store = builder
@@ -96,10 +134,7 @@ impl ParquetHandler {
     }
 
     /// Read Parquet metadata (footer) from S3 using cached object store
-    async fn read_metadata(
-        store: &Arc<dyn ObjectStore>,
-        key: &str,
-    ) -> Result<(Arc<ParquetMetaData>, Arc<Schema>)> {
+    async fn read_metadata(store: &Arc<dyn ObjectStore>, key: &str) -> Result<ArrowReaderMetadata> {
         // Create object path
         let object_path = ObjectPath::from(key);
 
@@ -116,14 +151,20 @@ impl ParquetHandler {
         .context("Timeout reading Parquet metadata - operation took longer than 30 seconds")?
         .context("Failed to read Parquet metadata")?;
 
-        // Extract Arrow schema
-        let schema = parquet::arrow::parquet_to_arrow_schema(
-            metadata.file_metadata().schema_descr(),
-            metadata.file_metadata().key_value_metadata(),
-        )
-        .context("Failed to convert Parquet schema to Arrow")?;
+        // Derive the Arrow schema once alongside the footer
+        ArrowReaderMetadata::try_new(metadata, ArrowReaderOptions::new())
+            .context("Failed to convert Parquet schema to Arrow")
+    }
+
+    /// Get the footer metadata and Arrow schema cached in the index
+    /// Reading virtual files never re-fetches the footer from S3
+    fn cached_metadata(index: &ArchiveIndex) -> Result<(Arc<ParquetMetaData>, Arc<Schema>)> {
+        let cached = index
+            .parquet_metadata
+            .as_ref()
+            .ok_or_else(|| anyhow!("No Parquet metadata cached in index"))?;
 
-        Ok((metadata, Arc::new(schema)))
+        Ok((Arc::clone(cached.metadata()), Arc::clone(cached.schema())))
     }
 
     /// Check if field is a nested type (struct, list, map)
@@ -143,7 +184,8 @@ impl ParquetHandler {
             DataType::Int64 | DataType::UInt64 | DataType::Float64 => 20,
             DataType::Utf8 | DataType::Binary => 50, // Average string length
             DataType::Boolean => 5,
-            _ => 20, // Default
+            DataType::List(_) | DataType::Map(_, _) => 100, // Rendered as JSON-ish values
+            _ => 20,                                        // Default
         }
     }
 
@@ -168,9 +210,24 @@ impl ParquetHandler {
                 format!("TIMESTAMP({:?}, {})", unit, tz_str)
             }
             DataType::Date32 => "DATE".to_string(),
-            DataType::Struct(_) => "STRUCT".to_string(),
-            DataType::List(_) => "LIST".to_string(),
-            DataType::Map(_, _) => "MAP".to_string(),
+            DataType::Struct(fields) => {
+                let children: Vec<String> = fields
+                    .iter()
+                    .map(|f| format!("{}: {}", f.name(), Self::format_data_type(f.data_type())))
+                    .collect();
+                format!("STRUCT<{}>", children.join(", "))
+            }
+            DataType::List(item) | DataType::LargeList(item) => {
+                format!("LIST<{}>", Self::format_data_type(item.data_type()))
+            }
+            DataType::Map(entries, _) => match entries.data_type() {
+                DataType::Struct(kv) if kv.len() == 2 => format!(
+                    "MAP<{}, {}>",
+                    Self::format_data_type(kv[0].data_type()),
+                    Self::format_data_type(kv[1].data_type())
+                ),
+                _ => "MAP".to_string(),
+            },
             _ => format!("{:?}", dt),
         }
     }
@@ -196,39 +253,94 @@ impl ParquetHandler {
         Ok(())
     }
 
+    /// Add _head.csv virtual file
+    fn add_head_entry(entries: &mut HashMap<String, ArchiveEntry>, schema: &Schema) -> Result<()> {
+        // Estimate size: header plus rows of all flat columns
+        let row_size: usize = schema
+            .fields()
+            .iter()
+            .filter(|f| !Self::is_nested_type(f))
+            .map(|f| Self::estimate_field_size(f))
+            .sum();
+        let estimated_size = (DEFAULT_ROW_LIMIT + 1) * row_size;
+
+        entries.insert(
+            "_head.csv".to_string(),
+            ArchiveEntry::parquet_virtual(
+                "_head.csv".to_string(),
+                estimated_size as u64,
+                false, // is_dir
+                ParquetEntryHandler::Head,
+            ),
+        );
+
+        Ok(())
+    }
+
     /// Add column data entries under columns/
+    /// Struct columns become directories with one entry per child field
     fn add_column_entries(
         entries: &mut HashMap<String, ArchiveEntry>,
         schema: &Schema,
     ) -> Result<()> {
-        // Only handle flat schemas (defer nested types)
         for (i, field) in schema.fields().iter().enumerate() {
-            // Skip nested types
-            if Self::is_nested_type(field) {
-                continue;
-            }
-
-            let column_name = field.name();
-            let path = format!("columns/{}", column_name);
+            let path = format!("columns/{}", field.name());
+            Self::add_field_entries(entries, i, field, path, Vec::new());
+        }
 
-            // Estimate size: 100 rows * avg field size
-            let estimated_size = 100 * Self::estimate_field_size(field);
+        Ok(())
+    }
 
+    /// Add the entry for one field, recursing into struct children
+    fn add_field_entries(
+        entries: &mut HashMap<String, ArchiveEntry>,
+        column_index: usize,
+        field: &arrow_schema::Field,
+        path: String,
+        field_path: Vec<usize>,
+    ) {
+        if let DataType::Struct(children) = field.data_type() {
             entries.insert(
                 path.clone(),
                 ArchiveEntry::parquet_virtual(
-                    path,
-                    estimated_size as u64,
-                    false, // is_dir
-                    ParquetEntryHandler::ColumnData {
-                        column_index: i,
-                        column_name: column_name.to_string(),
-                    },
+                    path.clone(),
+                    0,
+                    true,                        // is_dir
+                    ParquetEntryHandler::Schema, // Placeholder handler (not used for directories)
                 ),
             );
+
+            for (child_idx, child) in children.iter().enumerate() {
+                let mut child_path = field_path.clone();
+                child_path.push(child_idx);
+                Self::add_field_entries(
+                    entries,
+                    column_index,
+                    child,
+                    format!("{}/{}", path, child.name()),
+                    child_path,
+                );
+            }
+            return;
         }
 
-        Ok(())
+        // Estimate size: 100 rows * avg field size
+        let estimated_size = 100 * Self::estimate_field_size(field);
+        let column_name = path.trim_start_matches("columns/").to_string();
+
+        entries.insert(
+            path.clone(),
+            ArchiveEntry::parquet_virtual(
+                path,
+                estimated_size as u64,
+                false, // is_dir
+                ParquetEntryHandler::ColumnData {
+                    column_index,
+                    column_name,
+                    field_path,
+                },
+            ),
+        );
     }
 
     /// Add statistics entries under stats/
@@ -265,21 +377,274 @@ impl ParquetHandler {
         Ok(())
     }
 
-    /// Render schema as human-readable text
-    async fn render_schema(&self, index: &ArchiveIndex) -> Result<Bytes> {
-        // Get cached object store
-        let store = index
-            .parquet_store
-            .as_ref()
-            .ok_or_else(|| anyhow!("No object store cached in index"))?;
+    /// Directory path for a row group, zero-padded so listings sort numerically
+    fn row_group_dir(row_group: usize, num_row_groups: usize) -> String {
+        let width = num_row_groups.saturating_sub(1).to_string().len().max(3);
+        format!("row_groups/{row_group:0width$}")
+    }
 
-        let key = index
-            .metadata
-            .get("key")
-            .ok_or_else(|| anyhow!("Key not found in index metadata"))?;
+    /// Add the row_groups/ hierarchy: one directory per row group with an
+    /// _info.txt overview and a file per column chunk
+    fn add_row_group_entries(
+        entries: &mut HashMap<String, ArchiveEntry>,
+        metadata: &ParquetMetaData,
+    ) -> Result<()> {
+        let num_row_groups = metadata.num_row_groups();
 
-        // Read metadata using cached store
-        let (_metadata, schema) = Self::read_metadata(store, key).await?;
+        entries.insert(
+            "row_groups".to_string(),
+            ArchiveEntry::parquet_virtual(
+                "row_groups".to_string(),
+                0,
+                true,                        // is_dir
+                ParquetEntryHandler::Schema, // Placeholder handler (not used for directories)
+            ),
+        );
+        entries.insert(
+            "row_groups/_summary.txt".to_string(),
+            ArchiveEntry::parquet_virtual(
+                "row_groups/_summary.txt".to_string(),
+                (200 + num_row_groups * 60) as u64,
+                false, // is_dir
+                ParquetEntryHandler::RowGroupSummary,
+            ),
+        );
+
+        for (rg_idx, row_group) in metadata.row_groups().iter().enumerate() {
+            let dir = Self::row_group_dir(rg_idx, num_row_groups);
+
+            // Directory and chunk sizes are the on-disk (compressed) sizes,
+            // so `ls -l` shows how data is spread across row groups
+            entries.insert(
+                dir.clone(),
+                ArchiveEntry::parquet_virtual(
+                    dir.clone(),
+                    row_group.compressed_size() as u64,
+                    true,                        // is_dir
+                    ParquetEntryHandler::Schema, // Placeholder handler (not used for directories)
+                ),
+            );
+
+            let info_path = format!("{dir}/_info.txt");
+            entries.insert(
+                info_path.clone(),
+                ArchiveEntry::parquet_virtual(
+                    info_path,
+                    (300 + row_group.num_columns() * 80) as u64,
+                    false, // is_dir
+                    ParquetEntryHandler::RowGroupInfo { row_group: rg_idx },
+                ),
+            );
+
+            for (col_idx, chunk) in row_group.columns().iter().enumerate() {
+                let column_name = chunk.column_path().string();
+                let path = format!("{dir}/{column_name}");
+                entries.insert(
+                    path.clone(),
+                    ArchiveEntry::parquet_virtual(
+                        path,
+                        chunk.compressed_size() as u64,
+                        false, // is_dir
+                        ParquetEntryHandler::RowGroupColumn {
+                            row_group: rg_idx,
+                            column_index: col_idx,
+                            column_name,
+                        },
+                    ),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Format typed min/max statistics as strings
+    fn format_min_max(stats: &Statistics) -> (String, String) {
+        fn pair<T: ToString>(min: Option<&T>, max: Option<&T>) -> (String, String) {
+            let fmt = |v: Option<&T>| {
+                v.map(|v| v.to_string())
+                    .unwrap_or_else(|| "<not available>".to_string())
+            };
+            (fmt(min), fmt(max))
+        }
+
+        match stats {
+            Statistics::Boolean(s) => pair(s.min_opt(), s.max_opt()),
+            Statistics::Int32(s) => pair(s.min_opt(), s.max_opt()),
+            Statistics::Int64(s) => pair(s.min_opt(), s.max_opt()),
+            Statistics::Int96(s) => pair(s.min_opt(), s.max_opt()),
+            Statistics::Float(s) => pair(s.min_opt(), s.max_opt()),
+            Statistics::Double(s) => pair(s.min_opt(), s.max_opt()),
+            Statistics::ByteArray(s) => (
+                Self::format_stat_value(s.min_opt().map(|v| v.data())),
+                Self::format_stat_value(s.max_opt().map(|v| v.data())),
+            ),
+            Statistics::FixedLenByteArray(s) => (
+                Self::format_stat_value(s.min_opt().map(|v| v.data())),
+                Self::format_stat_value(s.max_opt().map(|v| v.data())),
+            ),
+        }
+    }
+
+    /// Render an overview of all row groups, to spot skew at a glance
+    async fn render_row_group_summary(&self, index: &ArchiveIndex) -> Result<Bytes> {
+        let (metadata, _schema) = Self::cached_metadata(index)?;
+        let row_groups = metadata.row_groups();
+
+        let mut output = String::new();
+        output.push_str("Row Groups\n");
+        output.push_str("==========\n\n");
+        output.push_str(&format!("Count: {}\n", row_groups.len()));
+
+        let row_counts: Vec<i64> = row_groups.iter().map(|rg| rg.num_rows()).collect();
+        let total_rows: i64 = row_counts.iter().sum();
+        if let (Some(min), Some(max)) = (row_counts.iter().min(), row_counts.iter().max()) {
+            output.push_str(&format!(
+                "Rows: {} total, {} min, {} max, {} avg\n",
+                total_rows,
+                min,
+                max,
+                total_rows / row_groups.len() as i64
+            ));
+        }
+        output.push('\n');
+
+        output.push_str(&format!(
+            "{:<8} {:>12} {:>12} {:>14} {:>7}\n",
+            "GROUP", "ROWS", "COMPRESSED", "UNCOMPRESSED", "ROWS %"
+        ));
+        for (rg_idx, row_group) in row_groups.iter().enumerate() {
+            let row_pct = if total_rows > 0 {
+                row_group.num_rows() as f64 / total_rows as f64 * 100.0
+            } else {
+                0.0
+            };
+            output.push_str(&format!(
+                "{:<8} {:>12} {:>12} {:>14} {:>6.1}%\n",
+                Self::row_group_dir(rg_idx, row_groups.len()).trim_start_matches("row_groups/"),
+                row_group.num_rows(),
+                humansize::format_size(row_group.compressed_size() as u64, humansize::BINARY),
+                humansize::format_size(row_group.total_byte_size() as u64, humansize::BINARY),
+                row_pct
+            ));
+        }
+
+        Ok(Bytes::from(output))
+    }
+
+    /// Render an overview of one row group with a line per column chunk
+    async fn render_row_group_info(&self, index: &ArchiveIndex, row_group: usize) -> Result<Bytes> {
+        let (metadata, _schema) = Self::cached_metadata(index)?;
+        let rg = metadata
+            .row_groups()
+            .get(row_group)
+            .ok_or_else(|| anyhow!("Row group {} not found", row_group))?;
+
+        let mut output = String::new();
+        let title = format!("Row Group {}", row_group);
+        output.push_str(&format!("{}\n{}\n\n", title, "=".repeat(title.len())));
+        output.push_str(&format!("Rows: {}\n", rg.num_rows()));
+        output.push_str(&format!(
+            "Compressed Size: {}\n",
+            humansize::format_size(rg.compressed_size() as u64, humansize::BINARY)
+        ));
+        output.push_str(&format!(
+            "Uncompressed Size: {}\n",
+            humansize::format_size(rg.total_byte_size() as u64, humansize::BINARY)
+        ));
+        if let Some(offset) = rg.file_offset() {
+            output.push_str(&format!("File Offset: {}\n", offset));
+        }
+        output.push('\n');
+
+        output.push_str("Columns:\n");
+        output.push_str("--------\n");
+        for chunk in rg.columns() {
+            let nulls = chunk
+                .statistics()
+                .and_then(|s| s.null_count_opt())
+                .map(|n| n.to_string())
+                .unwrap_or_else(|| "-".to_string());
+            output.push_str(&format!(
+                "  {} : {} compressed, {} uncompressed, {} nulls, {}\n",
+                chunk.column_path().string(),
+                humansize::format_size(chunk.compressed_size() as u64, humansize::BINARY),
+                humansize::format_size(chunk.uncompressed_size() as u64, humansize::BINARY),
+                nulls,
+                chunk.compression()
+            ));
+        }
+
+        Ok(Bytes::from(output))
+    }
+
+    /// Render details and statistics for one column chunk
+    async fn render_row_group_column(
+        &self,
+        index: &ArchiveIndex,
+        row_group: usize,
+        column_index: usize,
+        column_name: &str,
+    ) -> Result<Bytes> {
+        let (metadata, _schema) = Self::cached_metadata(index)?;
+        let chunk = metadata
+            .row_groups()
+            .get(row_group)
+            .and_then(|rg| rg.columns().get(column_index))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Column {} not found in row group {}",
+                    column_name,
+                    row_group
+                )
+            })?;
+
+        let mut output = String::new();
+        let title = format!("Column: {} (row group {})", column_name, row_group);
+        output.push_str(&format!("{}\n{}\n\n", title, "=".repeat(title.len())));
+
+        let encodings: Vec<String> = chunk.encodings().map(|e| format!("{:?}", e)).collect();
+        output.push_str(&format!("Physical Type: {}\n", chunk.column_type()));
+        output.push_str(&format!("Compression: {}\n", chunk.compression()));
+        output.push_str(&format!("Encodings: {}\n", encodings.join(", ")));
+        output.push_str(&format!("Values: {}\n", chunk.num_values()));
+        output.push_str(&format!(
+            "Compressed Size: {}\n",
+            humansize::format_size(chunk.compressed_size() as u64, humansize::BINARY)
+        ));
+        output.push_str(&format!(
+            "Uncompressed Size: {}\n",
+            humansize::format_size(chunk.uncompressed_size() as u64, humansize::BINARY)
+        ));
+        if let Some(offset) = chunk.dictionary_page_offset() {
+            output.push_str(&format!("Dictionary Page Offset: {}\n", offset));
+        }
+        output.push_str(&format!("Data Page Offset: {}\n", chunk.data_page_offset()));
+        output.push('\n');
+
+        output.push_str("Statistics:\n");
+        output.push_str("-----------\n");
+        match chunk.statistics() {
+            Some(stats) => {
+                let (min, max) = Self::format_min_max(stats);
+                output.push_str(&format!("  Min Value: {}\n", min));
+                output.push_str(&format!("  Max Value: {}\n", max));
+                if let Some(nulls) = stats.null_count_opt() {
+                    output.push_str(&format!("  Null Count: {}\n", nulls));
+                }
+                if let Some(distinct) = stats.distinct_count_opt() {
+                    output.push_str(&format!("  Distinct Count: {}\n", distinct));
+                }
+            }
+            None => output.push_str("  <not available>\n"),
+        }
+
+        Ok(Bytes::from(output))
+    }
+
+    /// Render schema as human-readable text
+    async fn render_schema(&self, index: &ArchiveIndex) -> Result<Bytes> {
+        let (_metadata, schema) = Self::cached_metadata(index)?;
 
         // Build human-readable output
         let mut output = String::new();
@@ -340,19 +705,7 @@ impl ParquetHandler {
         column_index: usize,
         column_name: &str,
     ) -> Result<Bytes> {
-        // Get cached object store
-        let store = index
-            .parquet_store
-            .as_ref()
-            .ok_or_else(|| anyhow!("No object store cached in index"))?;
-
-        let key = index
-            .metadata
-            .get("key")
-            .ok_or_else(|| anyhow!("Key not found in index metadata"))?;
-
-        // Read metadata using cached store
-        let (metadata, schema) = Self::read_metadata(store, key).await?;
+        let (metadata, schema) = Self::cached_metadata(index)?;
 
         let mut output = String::new();
 
@@ -378,8 +731,15 @@ impl ParquetHandler {
         let mut total_rows = 0u64;
 
         // Iterate through row groups
+        // Column chunks are indexed by leaf, which differs from the top-level index
+        // once a nested column appears earlier in the schema
+        let schema_descr = metadata.file_metadata().schema_descr();
+        let leaf_index = (0..schema_descr.num_columns())
+            .find(|&leaf| schema_descr.get_column_root_idx(leaf) == column_index)
+            .unwrap_or(column_index);
+
         for (rg_idx, row_group) in metadata.row_groups().iter().enumerate() {
-            if let Some(column_chunk) = row_group.columns().get(column_index)
+            if let Some(column_chunk) = row_group.columns().get(leaf_index)
                 && let Some(stats) = column_chunk.statistics()
             {
                 // Collect stats
@@ -504,19 +864,104 @@ impl ParquetHandler {
                     Ok(format!("<invalid date: {} ms>", millis))
                 }
             }
+            DataType::List(_)
+            | DataType::LargeList(_)
+            | DataType::Map(_, _)
+            | DataType::Struct(_) => Self::format_nested_value(array, index),
             _ => Ok(format!("<unsupported type: {:?}>", array.data_type())),
         }
     }
 
-    /// Read and render column data
-    async fn render_column_data(
-        &self,
-        index: &ArchiveIndex,
-        column_index: usize,
-        _column_name: &str,
-    ) -> Result<Bytes> {
-        const DEFAULT_ROW_LIMIT: usize = 100;
+    /// Format a value inside a list, map, or struct as JSON-ish text
+    /// Strings are quoted and NULLs become null so the result reads like JSON
+    fn format_nested_value(array: &Arc<dyn Array>, index: usize) -> Result<String> {
+        if array.is_null(index) {
+            return Ok("null".to_string());
+        }
+
+        match array.data_type() {
+            DataType::Utf8 => Ok(serde_json::to_string(as_string_array(array).value(index))?),
+            DataType::List(_) => {
+                let values = array.as_list::<i32>().value(index);
+                Self::format_nested_list(&values)
+            }
+            DataType::LargeList(_) => {
+                let values = array.as_list::<i64>().value(index);
+                Self::format_nested_list(&values)
+            }
+            DataType::Map(_, _) => {
+                let entries = array.as_map().value(index);
+                let keys = entries.column(0);
+                let values = entries.column(1);
+                let mut parts = Vec::with_capacity(entries.len());
+                for i in 0..entries.len() {
+                    parts.push(format!(
+                        "{}: {}",
+                        Self::format_nested_value(keys, i)?,
+                        Self::format_nested_value(values, i)?
+                    ));
+                }
+                Ok(format!("{{{}}}", parts.join(", ")))
+            }
+            DataType::Struct(fields) => {
+                let struct_array = array.as_struct();
+                let mut parts = Vec::with_capacity(fields.len());
+                for (field, column) in fields.iter().zip(struct_array.columns()) {
+                    parts.push(format!(
+                        "{}: {}",
+                        serde_json::to_string(field.name())?,
+                        Self::format_nested_value(column, index)?
+                    ));
+                }
+                Ok(format!("{{{}}}", parts.join(", ")))
+            }
+            DataType::Timestamp(_, _) | DataType::Date32 | DataType::Date64 => {
+                Ok(format!("\"{}\"", Self::format_array_value(array, index)?))
+            }
+            _ => Self::format_array_value(array, index),
+        }
+    }
 
+    /// Format every element of a list as JSON-ish text
+    fn format_nested_list(values: &Arc<dyn Array>) -> Result<String> {
+        let parts = (0..values.len())
+            .map(|i| Self::format_nested_value(values, i))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(format!("[{}]", parts.join(", ")))
+    }
+
+    /// Walk from a top-level column into a struct child, returning every level on the way
+    /// A value is NULL if it or any enclosing struct is NULL
+    fn resolve_field_path(column: &Arc<dyn Array>, field_path: &[usize]) -> Result<Vec<ArrayRef>> {
+        let mut levels = vec![Arc::clone(column)];
+        for &child_idx in field_path {
+            let parent = levels.last().expect("levels starts non-empty");
+            let child = parent
+                .as_struct_opt()
+                .and_then(|s| s.columns().get(child_idx))
+                .ok_or_else(|| anyhow!("Nested field {} not found", child_idx))?;
+            levels.push(Arc::clone(child));
+        }
+        Ok(levels)
+    }
+
+    /// Quote a CSV field if it contains a delimiter, quote, or newline
+    fn csv_escape(value: &str) -> String {
+        if value.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    }
+
+    /// Open a projected record batch stream over the file, reusing the cached footer
+    /// The offset and limit are pushed into the reader so skipped pages aren't decoded
+    fn open_stream(
+        index: &ArchiveIndex,
+        column_indices: Option<Vec<usize>>,
+        skip: usize,
+        limit: Option<usize>,
+    ) -> Result<ParquetRecordBatchStream<ParquetObjectReader>> {
         // Get cached object store
         let store = index
             .parquet_store
@@ -533,54 +978,272 @@ impl ParquetHandler {
         // Create Parquet reader with path (API changed in 57.x)
         let reader = ParquetObjectReader::new(Arc::clone(store), object_path);
 
-        // Build stream with column projection
-        let builder = ParquetRecordBatchStreamBuilder::new(reader)
-            .await
-            .context("Failed to create Parquet stream builder")?;
-
-        // Create projection mask for single column
-        let mask = ProjectionMask::roots(builder.parquet_schema(), vec![column_index]);
+        // Reuse the cached footer instead of fetching it again
+        let cached = index
+            .parquet_metadata
+            .clone()
+            .ok_or_else(|| anyhow!("No Parquet metadata cached in index"))?;
+        let builder = ParquetRecordBatchStreamBuilder::new_with_metadata(reader, cached);
+
+        // Default to every flat column (nested types are skipped, as in _head.csv)
+        let column_indices = column_indices.unwrap_or_else(|| {
+            builder
+                .schema()
+                .fields()
+                .iter()
+                .enumerate()
+                .filter(|(_, field)| !Self::is_nested_type(field))
+                .map(|(i, _)| i)
+                .collect()
+        });
+        let mask = ProjectionMask::roots(builder.parquet_schema(), column_indices);
 
-        let builder = builder
+        let mut builder = builder
             .with_projection(mask)
-            .with_batch_size(DEFAULT_ROW_LIMIT);
+            .with_batch_size(limit.unwrap_or(8192).clamp(1, 8192))
+            .with_offset(skip);
+        if let Some(limit) = limit {
+            builder = builder.with_limit(limit);
+        }
 
-        let mut stream = builder.build().context("Failed to build Parquet stream")?;
+        builder.build().context("Failed to build Parquet stream")
+    }
 
-        // Read first batch with timeout
-        let batch_result = tokio::time::timeout(
+    /// Wait for the next batch of a stream with a per-batch timeout
+    async fn next_batch(
+        stream: &mut ParquetRecordBatchStream<ParquetObjectReader>,
+    ) -> Result<Option<RecordBatch>> {
+        // Each batch gets its own timeout so large reads can still complete
+        let next = tokio::time::timeout(
             std::time::Duration::from_secs(DATA_READ_TIMEOUT_SECS),
             stream.next(),
         )
         .await
-        .context("Timeout reading column data - operation took longer than 60 seconds")?
-        .ok_or_else(|| anyhow!("No data in Parquet file - stream is empty"))?;
+        .context("Timeout reading column data - operation took longer than 60 seconds")?;
 
-        let batch = batch_result.map_err(|e| {
+        next.transpose().map_err(|e| {
             anyhow!(
                 "Failed to read batch from Parquet stream: {}. \
                     This may indicate a permission issue or file format problem.",
                 e
             )
-        })?;
+        })
+    }
+
+    /// Read a window of rows for the given top-level columns with a single projected read
+    async fn read_rows(
+        index: &ArchiveIndex,
+        column_indices: Option<Vec<usize>>,
+        range: RowRange,
+    ) -> Result<Vec<RecordBatch>> {
+        let mut stream = Self::open_stream(index, column_indices, range.skip, Some(range.limit))?;
+
+        let mut batches = Vec::new();
+        while let Some(batch) = Self::next_batch(&mut stream).await? {
+            batches.push(batch);
+        }
+
+        Ok(batches)
+    }
+
+    /// Convert the whole file (or its first `limit` rows) to CSV or JSON Lines
+    /// Batches are encoded as they arrive so output can be piped without buffering the file
+    /// CSV only carries flat columns; JSON includes nested columns as objects and arrays
+    pub fn export(
+        &self,
+        index: &ArchiveIndex,
+        format: ExportFormat,
+        limit: Option<usize>,
+    ) -> Result<BoxStream<'static, Result<Bytes>>> {
+        let column_indices = match format {
+            ExportFormat::Csv => None,
+            ExportFormat::Json => {
+                let (_metadata, schema) = Self::cached_metadata(index)?;
+                Some((0..schema.fields().len()).collect())
+            }
+        };
+        let stream = Self::open_stream(index, column_indices, 0, limit)?;
+
+        let encoded =
+            futures::stream::try_unfold((stream, true), move |(mut stream, first)| async move {
+                let Some(batch) = Self::next_batch(&mut stream).await? else {
+                    return Ok(None);
+                };
+                let bytes = Self::encode_batch(&batch, format, first)?;
+                Ok(Some((bytes, (stream, false))))
+            });
+
+        Ok(encoded.boxed())
+    }
+
+    /// Encode one record batch, writing the CSV header only for the first batch
+    fn encode_batch(batch: &RecordBatch, format: ExportFormat, first: bool) -> Result<Bytes> {
+        let mut buf = Vec::new();
+        match format {
+            ExportFormat::Csv => {
+                let mut writer = csv::WriterBuilder::new().with_header(first).build(&mut buf);
+                writer.write(batch).context("Failed to encode CSV")?;
+            }
+            ExportFormat::Json => {
+                let mut writer = json::LineDelimitedWriter::new(&mut buf);
+                writer.write(batch).context("Failed to encode JSON")?;
+                writer.finish().context("Failed to encode JSON")?;
+            }
+        }
+        Ok(Bytes::from(buf))
+    }
 
-        // Extract column
-        let column = batch.column(0).clone(); // First column (we projected only one)
+    /// Render a window of rows of all flat columns as CSV
+    async fn render_head(&self, index: &ArchiveIndex, range: RowRange) -> Result<Bytes> {
+        let batches = Self::read_rows(index, None, range).await?;
 
-        // Format as text (one value per line)
         let mut output = String::new();
+        let Some(first) = batches.first() else {
+            return Ok(Bytes::from(output));
+        };
+
+        // Header row
+        let header: Vec<String> = first
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| Self::csv_escape(f.name()))
+            .collect();
+        output.push_str(&header.join(","));
+        output.push('\n');
 
-        // Get actual number of rows (might be less than limit)
-        let num_rows = column.len().min(DEFAULT_ROW_LIMIT);
+        for batch in &batches {
+            for row_idx in 0..batch.num_rows() {
+                let mut row = Vec::with_capacity(batch.num_columns());
+                for column in batch.columns() {
+                    // NULLs are empty fields in CSV
+                    let value = if column.is_null(row_idx) {
+                        String::new()
+                    } else {
+                        Self::format_array_value(column, row_idx)?
+                    };
+                    row.push(Self::csv_escape(&value));
+                }
+                output.push_str(&row.join(","));
+                output.push('\n');
+            }
+        }
 
-        for row_idx in 0..num_rows {
-            let value_str = Self::format_array_value(&column, row_idx)?;
-            output.push_str(&value_str);
-            output.push('\n');
+        Ok(Bytes::from(output))
+    }
+
+    /// Read and render a window of column data, one value per line
+    async fn render_column_data(
+        &self,
+        index: &ArchiveIndex,
+        column_index: usize,
+        field_path: &[usize],
+        range: RowRange,
+    ) -> Result<Bytes> {
+        let batches = Self::read_rows(index, Some(vec![column_index]), range).await?;
+
+        // An empty window (--rows 0, or --skip past the end) isn't an empty file
+        if batches.is_empty() && range.skip == 0 && range.limit > 0 {
+            return Err(anyhow!("No data in Parquet file - stream is empty"));
+        }
+
+        let mut output = String::new();
+        for batch in &batches {
+            // First column (we projected only one), then down into struct children
+            let levels = Self::resolve_field_path(batch.column(0), field_path)?;
+            let column = levels.last().expect("levels starts non-empty");
+            for row_idx in 0..column.len() {
+                if levels.iter().any(|level| level.is_null(row_idx)) {
+                    output.push_str("<NULL>");
+                } else {
+                    output.push_str(&Self::format_array_value(column, row_idx)?);
+                }
+                output.push('\n');
+            }
         }
 
         Ok(Bytes::from(output))
     }
+
+    /// Extract a window of rows from a columns/ file or _head.csv
+    pub async fn extract_rows(
+        &self,
+        index: &ArchiveIndex,
+        file_path: &str,
+        range: RowRange,
+    ) -> Result<Bytes> {
+        let entry = index
+            .entries
+            .get(file_path)
+            .ok_or_else(|| anyhow!("File not found in Parquet archive: {}", file_path))?;
+
+        match &entry.entry_type {
+            EntryType::ParquetVirtual {
+                handler:
+                    ParquetEntryHandler::ColumnData {
+                        column_index,
+                        field_path,
+                        ..
+                    },
+            } => {
+                self.render_column_data(index, *column_index, field_path, range)
+                    .await
+            }
+            EntryType::ParquetVirtual {
+                handler: ParquetEntryHandler::Head,
+            } => self.render_head(index, range).await,
+            _ => Err(anyhow!(
+                "Row ranges only apply to columns/ files and _head.csv: {}",
+                file_path
+            )),
+        }
+    }
+}
+
+impl ParquetHandler {
+    /// Run a SQL query against the Parquet file with DataFusion.
+    ///
+    /// The file is registered as the table `this`. Queries go through the object store
+    /// cached in the index, so projection and predicate pushdown only fetch the
+    /// column chunks and row groups the query actually needs.
+    pub async fn query(&self, index: &ArchiveIndex, sql: &str) -> Result<Vec<RecordBatch>> {
+        let store = index
+            .parquet_store
+            .as_ref()
+            .ok_or_else(|| anyhow!("No object store cached in index"))?;
+
+        let bucket = index
+            .metadata
+            .get("bucket")
+            .ok_or_else(|| anyhow!("Bucket not found in index metadata"))?;
+        let key = index
+            .metadata
+            .get("key")
+            .ok_or_else(|| anyhow!("Key not found in index metadata"))?;
+
+        let ctx = SessionContext::new();
+        let base_url = if is_http_origin(bucket) {
+            bucket.clone()
+        } else {
+            format!("s3://{bucket}")
+        };
+        let store_url =
+            ObjectStoreUrl::parse(&base_url).context("Invalid bucket name for object store URL")?;
+        ctx.register_object_store(store_url.as_ref(), Arc::clone(store));
+
+        ctx.register_parquet(
+            "this",
+            format!("{base_url}/{key}"),
+            ParquetReadOptions::default(),
+        )
+        .await
+        .context("Failed to register Parquet file as table")?;
+
+        let df = ctx.sql(sql).await.context("Failed to plan query")?;
+        let batches = df.collect().await.context("Failed to execute query")?;
+
+        Ok(batches)
+    }
 }
 
 impl Default for ParquetHandler {
@@ -591,6 +1254,7 @@ impl Default for ParquetHandler {
 
 #[async_trait]
 impl ArchiveHandler for ParquetHandler {
+    #[tracing::instrument(level = "debug", skip(self, s3_client))]
     async fn build_index(
         &self,
         s3_client: &Arc<S3Client>,
@@ -603,17 +1267,19 @@ impl ArchiveHandler for ParquetHandler {
             .await
             .context("Failed to verify Parquet file exists")?;
 
-        // Load AWS config once and create object store - this will be cached
-        let config = Self::load_aws_config().await;
-        let store = Self::create_object_store(&config, bucket).await?;
+        // Create object store against the active provider - this will be cached
+        let store = Self::create_object_store(s3_client, bucket).await?;
 
         // Read Parquet metadata using the store
-        let (metadata, schema) = Self::read_metadata(&store, key).await?;
+        let reader_metadata = Self::read_metadata(&store, key).await?;
+        let metadata = Arc::clone(reader_metadata.metadata());
+        let schema = Arc::clone(reader_metadata.schema());
 
         let mut entries = HashMap::new();
 
         // Create virtual files and directories from schema
         Self::add_schema_entry(&mut entries, &schema)?;
+        Self::add_head_entry(&mut entries, &schema)?;
 
         // Add explicit directory entries for columns/ and stats/
         // These must be added BEFORE the child entries to avoid issues with list_entries
@@ -638,6 +1304,7 @@ impl ArchiveHandler for ParquetHandler {
 
         Self::add_column_entries(&mut entries, &schema)?;
         Self::add_stats_entries(&mut entries, &schema)?;
+        Self::add_row_group_entries(&mut entries, &metadata)?;
 
         // Store metadata in index for later use
         let mut metadata_map = HashMap::new();
@@ -656,9 +1323,11 @@ impl ArchiveHandler for ParquetHandler {
             entries,
             metadata: metadata_map,
             parquet_store: Some(store),
+            parquet_metadata: Some(reader_metadata),
         })
     }
 
+    #[tracing::instrument(level = "debug", skip(self, _s3_client, index))]
     async fn extract_file(
         &self,
         _s3_client: &Arc<S3Client>,
@@ -681,6 +1350,7 @@ impl ArchiveHandler for ParquetHandler {
         match &entry.entry_type {
             EntryType::ParquetVirtual { handler } => match handler {
                 ParquetEntryHandler::Schema => self.render_schema(index).await,
+                ParquetEntryHandler::Head => self.render_head(index, RowRange::default()).await,
                 ParquetEntryHandler::ColumnStats {
                     column_index,
                     column_name,
@@ -689,10 +1359,23 @@ impl ArchiveHandler for ParquetHandler {
                         .await
                 }
                 ParquetEntryHandler::ColumnData {
+                    column_index,
+                    field_path,
+                    ..
+                } => {
+                    self.render_column_data(index, *column_index, field_path, RowRange::default())
+                        .await
+                }
+                ParquetEntryHandler::RowGroupSummary => self.render_row_group_summary(index).await,
+                ParquetEntryHandler::RowGroupInfo { row_group } => {
+                    self.render_row_group_info(index, *row_group).await
+                }
+                ParquetEntryHandler::RowGroupColumn {
+                    row_group,
                     column_index,
                     column_name,
                 } => {
-                    self.render_column_data(index, *column_index, column_name)
+                    self.render_row_group_column(index, *row_group, *column_index, column_name)
                         .await
                 }
             },
@@ -777,3 +1460,211 @@ impl ArchiveHandler for ParquetHandler {
         result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_escape() {
+        assert_eq!(ParquetHandler::csv_escape("plain"), "plain");
+        assert_eq!(ParquetHandler::csv_escape("a,b"), "\"a,b\"");
+        assert_eq!(
+            ParquetHandler::csv_escape("say \"hi\""),
+            "\"say \"\"hi\"\"\""
+        );
+        assert_eq!(ParquetHandler::csv_escape("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn test_format_nested_value() {
+        use arrow_array::builder::{Int32Builder, ListBuilder, StringBuilder};
+        use arrow_schema::Field;
+
+        let mut list = ListBuilder::new(Int32Builder::new());
+        list.values().append_value(1);
+        list.values().append_null();
+        list.values().append_value(3);
+        list.append(true);
+        list.append(false);
+        let list: ArrayRef = Arc::new(list.finish());
+
+        assert_eq!(
+            ParquetHandler::format_array_value(&list, 0).unwrap(),
+            "[1, null, 3]"
+        );
+        assert_eq!(
+            ParquetHandler::format_array_value(&list, 1).unwrap(),
+            "<NULL>"
+        );
+
+        let mut names = StringBuilder::new();
+        names.append_value("Ada \"Countess\"");
+        let structs: ArrayRef = Arc::new(StructArray::from(vec![
+            (
+                Arc::new(Field::new("name", DataType::Utf8, true)),
+                Arc::new(names.finish()) as ArrayRef,
+            ),
+            (
+                Arc::new(Field::new("age", DataType::Int32, true)),
+                Arc::new(Int32Array::from(vec![36])) as ArrayRef,
+            ),
+        ]));
+
+        assert_eq!(
+            ParquetHandler::format_array_value(&structs, 0).unwrap(),
+            r#"{"name": "Ada \"Countess\"", "age": 36}"#
+        );
+
+        let levels = ParquetHandler::resolve_field_path(&structs, &[1]).unwrap();
+        assert_eq!(levels.len(), 2);
+        assert_eq!(
+            ParquetHandler::format_array_value(&levels[1], 0).unwrap(),
+            "36"
+        );
+    }
+
+    #[test]
+    fn test_encode_batch() {
+        let batch = RecordBatch::try_from_iter(vec![
+            ("id", Arc::new(Int32Array::from(vec![1, 2])) as ArrayRef),
+            (
+                "name",
+                Arc::new(StringArray::from(vec![Some("a,b"), None])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+
+        let csv = ParquetHandler::encode_batch(&batch, ExportFormat::Csv, true).unwrap();
+        assert_eq!(&csv[..], b"id,name\n1,\"a,b\"\n2,\n");
+
+        // Later batches continue the same table without repeating the header
+        let csv = ParquetHandler::encode_batch(&batch, ExportFormat::Csv, false).unwrap();
+        assert!(csv.starts_with(b"1,"));
+
+        let json = ParquetHandler::encode_batch(&batch, ExportFormat::Json, true).unwrap();
+        assert_eq!(&json[..], b"{\"id\":1,\"name\":\"a,b\"}\n{\"id\":2}\n");
+
+        assert_eq!(ExportFormat::parse("CSV"), Some(ExportFormat::Csv));
+        assert_eq!(ExportFormat::parse("ndjson"), Some(ExportFormat::Json));
+        assert_eq!(ExportFormat::parse("xml"), None);
+    }
+
+    #[test]
+    fn test_row_group_dir() {
+        assert_eq!(ParquetHandler::row_group_dir(0, 1), "row_groups/000");
+        assert_eq!(ParquetHandler::row_group_dir(42, 100), "row_groups/042");
+        assert_eq!(ParquetHandler::row_group_dir(7, 1000), "row_groups/007");
+        assert_eq!(ParquetHandler::row_group_dir(7, 1001), "row_groups/0007");
+    }
+
+    #[tokio::test]
+    async fn test_query() {
+        let batch = RecordBatch::try_from_iter(vec![
+            (
+                "city",
+                Arc::new(StringArray::from(vec!["Oslo", "Lima", "Oslo"])) as ArrayRef,
+            ),
+            (
+                "temp",
+                Arc::new(Int32Array::from(vec![3, 19, 5])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+        let mut file = Vec::new();
+        let mut writer =
+            parquet::arrow::ArrowWriter::try_new(&mut file, batch.schema(), None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let store = object_store::memory::InMemory::new();
+        store
+            .put(
+                &ObjectPath::from("weather.parquet"),
+                Bytes::from(file).into(),
+            )
+            .await
+            .unwrap();
+        let index = ArchiveIndex {
+            entries: HashMap::new(),
+            metadata: HashMap::from([
+                ("bucket".to_string(), "b".to_string()),
+                ("key".to_string(), "weather.parquet".to_string()),
+            ]),
+            parquet_store: Some(Arc::new(store)),
+            parquet_metadata: None,
+        };
+
+        let batches = ParquetHandler::new()
+            .query(
+                &index,
+                "SELECT city, sum(temp) AS total FROM this GROUP BY city ORDER BY city",
+            )
+            .await
+            .unwrap();
+        let table = datafusion::arrow::util::pretty::pretty_format_batches(&batches)
+            .unwrap()
+            .to_string();
+        assert_eq!(
+            table,
+            "+------+-------+\n\
+             | city | total |\n\
+             +------+-------+\n\
+             | Lima | 19    |\n\
+             | Oslo | 8     |\n\
+             +------+-------+"
+        );
+
+        assert!(
+            ParquetHandler::new()
+                .query(&index, "SELECT nope FROM this")
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_row_windows() {
+        let batch = RecordBatch::try_from_iter(vec![(
+            "id",
+            Arc::new(Int32Array::from((0..10).collect::<Vec<_>>())) as ArrayRef,
+        )])
+        .unwrap();
+        let mut file = Vec::new();
+        let mut writer =
+            parquet::arrow::ArrowWriter::try_new(&mut file, batch.schema(), None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let store: Arc<dyn ObjectStore> = Arc::new(object_store::memory::InMemory::new());
+        store
+            .put(&ObjectPath::from("ids.parquet"), Bytes::from(file).into())
+            .await
+            .unwrap();
+        let reader_metadata = ParquetHandler::read_metadata(&store, "ids.parquet")
+            .await
+            .unwrap();
+        let mut entries = HashMap::new();
+        ParquetHandler::add_head_entry(&mut entries, reader_metadata.schema()).unwrap();
+        ParquetHandler::add_column_entries(&mut entries, reader_metadata.schema()).unwrap();
+        let index = ArchiveIndex {
+            entries,
+            metadata: HashMap::from([("key".to_string(), "ids.parquet".to_string())]),
+            parquet_store: Some(store),
+            parquet_metadata: Some(reader_metadata),
+        };
+
+        let rows = async |path: &str, skip: usize, limit: usize| {
+            let bytes = ParquetHandler::new()
+                .extract_rows(&index, path, RowRange { skip, limit })
+                .await
+                .unwrap();
+            String::from_utf8(bytes.to_vec()).unwrap()
+        };
+        assert_eq!(rows("columns/id", 2, 3).await, "2\n3\n4\n");
+        assert_eq!(rows("_head.csv", 8, 5).await, "id\n8\n9\n");
+        assert_eq!(rows("columns/id", 0, 0).await, "");
+        assert_eq!(rows("columns/id", 20, 5).await, "");
+        assert_eq!(rows("_head.csv", 20, 5).await, "");
+    }
+}
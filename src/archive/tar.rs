@@ -2,13 +2,15 @@ use anyhow::{Context, Result, anyhow};
 use async_compression::tokio::bufread::{BzDecoder, GzipDecoder};
 use async_trait::async_trait;
 use bytes::Bytes;
+use futures::TryStreamExt;
 use std::collections::HashMap;
 use std::io::Read;
 use std::sync::Arc;
 use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio_util::io::StreamReader;
 
 use crate::s3::{S3Client, S3Stream};
-use crate::vfs::{ArchiveEntry, ArchiveIndex, ArchiveType};
+use crate::vfs::{ArchiveEntry, ArchiveIndex, ArchiveType, EntryAttrs};
 
 use super::ArchiveHandler;
 
@@ -26,17 +28,18 @@ impl TarHandler {
 
 #[async_trait]
 impl ArchiveHandler for TarHandler {
+    #[tracing::instrument(level = "debug", skip(self, s3_client))]
     async fn build_index(
         &self,
         s3_client: &Arc<S3Client>,
         bucket: &str,
         key: &str,
     ) -> Result<ArchiveIndex> {
-        // Get streaming byte stream from S3
-        let byte_stream = s3_client.get_object_stream(bucket, key).await?;
+        // Stream the object from S3, as concurrent ranged GETs when it's large
+        let chunks = s3_client.get_object_chunks(bucket, key, None).await?;
 
-        // Convert ByteStream to AsyncRead
-        let reader = byte_stream.into_async_read();
+        // Convert the chunk stream to AsyncRead
+        let reader = StreamReader::new(chunks.map_err(std::io::Error::other));
 
         // Wrap reader based on archive type
         let mut entries = match self.archive_type {
@@ -115,9 +118,12 @@ impl ArchiveHandler for TarHandler {
             metadata: std::collections::HashMap::new(),
             #[cfg(feature = "parquet")]
             parquet_store: None,
+            #[cfg(feature = "parquet")]
+            parquet_metadata: None,
         })
     }
 
+    #[tracing::instrument(level = "debug", skip(self, s3_client, index))]
     async fn extract_file(
         &self,
         s3_client: &Arc<S3Client>,
@@ -148,6 +154,12 @@ impl ArchiveHandler for TarHandler {
             crate::vfs::EntryType::ZipEntry { .. } => {
                 unreachable!("Tar archives should never contain ZipEntry entries")
             }
+            crate::vfs::EntryType::XlsxSheet { .. } => {
+                unreachable!("Tar archives should never contain XlsxSheet entries")
+            }
+            crate::vfs::EntryType::JsonVirtual { .. } => {
+                unreachable!("Tar archives should never contain JsonVirtual entries")
+            }
             #[cfg(feature = "parquet")]
             crate::vfs::EntryType::ParquetVirtual { .. } => {
                 unreachable!("Tar archives should never contain ParquetVirtual entries")
@@ -355,10 +367,21 @@ async fn stream_list_tar<R: AsyncRead + Unpin>(mut r: R) -> Result<HashMap<Strin
         let typeflag = header[156] as char;
         let is_dir = typeflag == '5' || path.ends_with('/');
 
+        // Ownership and permissions; user/group names are only set by ustar archives
+        let attrs = EntryAttrs {
+            mtime: parse_octal_u64(&header[136..148]).and_then(|t| i64::try_from(t).ok()),
+            mode: parse_octal_u64(&header[100..108]).map(|mode| (mode & 0o7777) as u32),
+            uid: parse_octal_u64(&header[108..116]),
+            gid: parse_octal_u64(&header[116..124]),
+            user: Some(parse_cstr(&header[265..297])).filter(|name| !name.is_empty()),
+            group: Some(parse_cstr(&header[297..329])).filter(|name| !name.is_empty()),
+            symlink: typeflag == '2',
+        };
+
         // Store the entry
         entries.insert(
             path.clone(),
-            ArchiveEntry::physical(path, current_offset, size, is_dir),
+            ArchiveEntry::physical(path, current_offset, size, is_dir).with_attrs(attrs),
         );
 
         // Update offset for next entry (512-byte header + padded data)
@@ -446,6 +469,8 @@ mod tests {
             metadata: HashMap::new(),
             #[cfg(feature = "parquet")]
             parquet_store: None,
+            #[cfg(feature = "parquet")]
+            parquet_metadata: None,
         };
 
         let handler = TarHandler::new(ArchiveType::Tar);
@@ -475,6 +500,8 @@ mod tests {
             metadata: HashMap::new(),
             #[cfg(feature = "parquet")]
             parquet_store: None,
+            #[cfg(feature = "parquet")]
+            parquet_metadata: None,
         };
 
         let handler = TarHandler::new(ArchiveType::Tar);
@@ -544,6 +571,8 @@ mod tests {
             metadata: HashMap::new(),
             #[cfg(feature = "parquet")]
             parquet_store: None,
+            #[cfg(feature = "parquet")]
+            parquet_metadata: None,
         };
 
         let handler = TarHandler::new(ArchiveType::Tar);
@@ -0,0 +1,503 @@
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use bytes::Bytes;
+use quick_xml::Reader;
+use quick_xml::events::{BytesRef, BytesStart, Event};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::s3::S3Client;
+use crate::vfs::{ArchiveEntry, ArchiveIndex, EntryType};
+
+use super::ArchiveHandler;
+use super::zip::ZipHandler;
+
+/// Number of rows rendered in a sheet preview
+const PREVIEW_ROWS: usize = 100;
+
+/// Cells wider than this are truncated in the preview
+const MAX_CELL_WIDTH: usize = 40;
+
+/// Columns a worksheet can have (A to XFD)
+const MAX_COLUMNS: usize = 16_384;
+
+/// Workbook parts every .xlsx file contains
+const WORKBOOK_PART: &str = "xl/workbook.xml";
+const WORKBOOK_RELS_PART: &str = "xl/_rels/workbook.xml.rels";
+const SHARED_STRINGS_PART: &str = "xl/sharedStrings.xml";
+
+/// Handler for Excel workbooks - presents each sheet as a rendered table
+///
+/// An .xlsx file is a zip of XML parts, so the index is the zip central directory
+/// plus one virtual entry per sheet, and parts are read through `ZipHandler`.
+pub struct XlsxHandler {
+    zip: ZipHandler,
+}
+
+impl XlsxHandler {
+    pub fn new() -> Self {
+        XlsxHandler {
+            zip: ZipHandler::new(),
+        }
+    }
+
+    /// Read an attribute value by name
+    fn attribute(element: &BytesStart, name: &[u8]) -> Result<Option<String>> {
+        match element.try_get_attribute(name)? {
+            Some(attr) => Ok(Some(attr.unescape_value()?.into_owned())),
+            None => Ok(None),
+        }
+    }
+
+    /// Resolve an entity or character reference inside text content
+    fn resolve_ref(reference: &BytesRef) -> Result<String> {
+        if let Some(ch) = reference.resolve_char_ref()? {
+            return Ok(ch.to_string());
+        }
+        let name = reference.decode()?;
+        Ok(quick_xml::escape::resolve_xml_entity(&name)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("&{name};")))
+    }
+
+    /// Parse sheet names and relationship ids from xl/workbook.xml, in workbook order
+    fn parse_workbook(xml: &[u8]) -> Result<Vec<(String, String)>> {
+        let mut reader = Reader::from_reader(xml);
+        let mut sheets = Vec::new();
+
+        loop {
+            match reader.read_event()? {
+                Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"sheet" => {
+                    let name = Self::attribute(&e, b"name")?
+                        .ok_or_else(|| anyhow!("Sheet without a name in workbook"))?;
+                    let rel_id = Self::attribute(&e, b"r:id")?
+                        .ok_or_else(|| anyhow!("Sheet {name} has no relationship id"))?;
+                    sheets.push((name, rel_id));
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        Ok(sheets)
+    }
+
+    /// Parse relationship ids to part paths from xl/_rels/workbook.xml.rels
+    fn parse_relationships(xml: &[u8]) -> Result<HashMap<String, String>> {
+        let mut reader = Reader::from_reader(xml);
+        let mut targets = HashMap::new();
+
+        loop {
+            match reader.read_event()? {
+                Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"Relationship" => {
+                    if let (Some(id), Some(target)) =
+                        (Self::attribute(&e, b"Id")?, Self::attribute(&e, b"Target")?)
+                    {
+                        // Targets are relative to xl/ unless they are absolute
+                        let path = match target.strip_prefix('/') {
+                            Some(absolute) => absolute.to_string(),
+                            None => format!("xl/{target}"),
+                        };
+                        targets.insert(id, path);
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        Ok(targets)
+    }
+
+    /// Parse the shared string table; rich text runs are concatenated
+    fn parse_shared_strings(xml: &[u8]) -> Result<Vec<String>> {
+        let mut reader = Reader::from_reader(xml);
+        let mut strings = Vec::new();
+        let mut current = String::new();
+        let mut in_text = false;
+        // Phonetic runs also use <t>, but aren't part of the displayed value
+        let mut in_phonetic = false;
+
+        loop {
+            match reader.read_event()? {
+                Event::Start(e) => match e.local_name().as_ref() {
+                    b"si" => current.clear(),
+                    b"rPh" => in_phonetic = true,
+                    b"t" if !in_phonetic => in_text = true,
+                    _ => {}
+                },
+                Event::End(e) => match e.local_name().as_ref() {
+                    b"si" => strings.push(std::mem::take(&mut current)),
+                    b"rPh" => in_phonetic = false,
+                    b"t" => in_text = false,
+                    _ => {}
+                },
+                Event::Empty(e) if e.local_name().as_ref() == b"si" => strings.push(String::new()),
+                Event::Text(e) if in_text => current.push_str(&e.decode()?),
+                Event::CData(e) if in_text => current.push_str(&e.decode()?),
+                Event::GeneralRef(e) if in_text => current.push_str(&Self::resolve_ref(&e)?),
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        Ok(strings)
+    }
+
+    /// Convert a cell reference like "AB12" to a zero-based column index
+    /// None if there's no column, or it's past XFD, the last one Excel allows.
+    fn column_index(cell_ref: &str) -> Option<usize> {
+        let letters: Vec<u8> = cell_ref
+            .bytes()
+            .take_while(|b| b.is_ascii_alphabetic())
+            .collect();
+        // Three letters reach past XFD; more would overflow
+        if letters.is_empty() || letters.len() > 3 {
+            return None;
+        }
+
+        let number = letters.iter().fold(0usize, |acc, b| {
+            acc * 26 + (b.to_ascii_uppercase() - b'A' + 1) as usize
+        });
+        (number <= MAX_COLUMNS).then(|| number - 1)
+    }
+
+    /// Parse a worksheet, keeping the first `max_rows` rows
+    /// Returns the rows and the total number of rows in the sheet
+    fn parse_sheet(
+        xml: &[u8],
+        shared_strings: &[String],
+        max_rows: usize,
+    ) -> Result<(Vec<Vec<String>>, usize)> {
+        let mut reader = Reader::from_reader(xml);
+        let mut rows = Vec::new();
+        let mut total_rows = 0;
+
+        let mut row: Vec<String> = Vec::new();
+        // None for a cell that's skipped, having no valid column
+        let mut cell_col = None;
+        let mut cell_type: Option<String> = None;
+        let mut cell_text = String::new();
+        let mut in_value = false;
+        let mut in_phonetic = false;
+
+        loop {
+            match reader.read_event()? {
+                Event::Start(e) => match e.local_name().as_ref() {
+                    b"row" => row.clear(),
+                    b"c" => {
+                        cell_col = match Self::attribute(&e, b"r")? {
+                            Some(r) => Self::column_index(&r),
+                            None => Some(row.len()).filter(|col| *col < MAX_COLUMNS),
+                        };
+                        cell_type = Self::attribute(&e, b"t")?;
+                        cell_text.clear();
+                    }
+                    b"rPh" => in_phonetic = true,
+                    b"v" => in_value = true,
+                    b"t" if !in_phonetic => in_value = true,
+                    _ => {}
+                },
+                Event::End(e) => match e.local_name().as_ref() {
+                    b"v" | b"t" => in_value = false,
+                    b"rPh" => in_phonetic = false,
+                    b"c" => {
+                        let value = match cell_type.as_deref() {
+                            Some("s") => cell_text
+                                .trim()
+                                .parse::<usize>()
+                                .ok()
+                                .and_then(|i| shared_strings.get(i))
+                                .cloned()
+                                .unwrap_or_default(),
+                            Some("b") => match cell_text.trim() {
+                                "1" => "TRUE".to_string(),
+                                _ => "FALSE".to_string(),
+                            },
+                            _ => std::mem::take(&mut cell_text),
+                        };
+                        if let Some(col) = cell_col {
+                            if row.len() <= col {
+                                row.resize(col + 1, String::new());
+                            }
+                            row[col] = value;
+                        }
+                    }
+                    b"row" => {
+                        total_rows += 1;
+                        if rows.len() < max_rows {
+                            rows.push(std::mem::take(&mut row));
+                        }
+                    }
+                    _ => {}
+                },
+                Event::Empty(e) if e.local_name().as_ref() == b"row" => {
+                    total_rows += 1;
+                    if rows.len() < max_rows {
+                        rows.push(Vec::new());
+                    }
+                }
+                Event::Text(e) if in_value => cell_text.push_str(&e.decode()?),
+                Event::CData(e) if in_value => cell_text.push_str(&e.decode()?),
+                Event::GeneralRef(e) if in_value => cell_text.push_str(&Self::resolve_ref(&e)?),
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        Ok((rows, total_rows))
+    }
+
+    /// Render rows as an aligned text table, treating the first row as a header
+    fn render_table(rows: &[Vec<String>], total_rows: usize) -> String {
+        if rows.is_empty() {
+            return "(empty sheet)\n".to_string();
+        }
+
+        let cells: Vec<Vec<String>> = rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| {
+                        // Keep each row on one line
+                        let cell = cell.replace(['\n', '\r', '\t'], " ");
+                        if cell.chars().count() > MAX_CELL_WIDTH {
+                            let truncated: String = cell.chars().take(MAX_CELL_WIDTH - 1).collect();
+                            format!("{truncated}…")
+                        } else {
+                            cell
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let num_cols = cells.iter().map(Vec::len).max().unwrap_or(0);
+        let mut widths = vec![0; num_cols];
+        for row in &cells {
+            for (i, cell) in row.iter().enumerate() {
+                widths[i] = widths[i].max(cell.chars().count());
+            }
+        }
+
+        let mut output = String::new();
+        for (row_num, row) in cells.iter().enumerate() {
+            let line: Vec<String> = (0..num_cols)
+                .map(|i| {
+                    let cell = row.get(i).map(String::as_str).unwrap_or("");
+                    format!("{cell:<width$}", width = widths[i])
+                })
+                .collect();
+            output.push_str(line.join(" | ").trim_end());
+            output.push('\n');
+
+            if row_num == 0 {
+                let rule: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+                output.push_str(&rule.join("-+-"));
+                output.push('\n');
+            }
+        }
+
+        if total_rows > rows.len() {
+            output.push_str(&format!("\n... ({} more rows)\n", total_rows - rows.len()));
+        }
+
+        output
+    }
+}
+
+impl Default for XlsxHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl ArchiveHandler for XlsxHandler {
+    #[tracing::instrument(level = "debug", skip(self, s3_client))]
+    async fn build_index(
+        &self,
+        s3_client: &Arc<S3Client>,
+        bucket: &str,
+        key: &str,
+    ) -> Result<ArchiveIndex> {
+        let mut index = self.zip.build_index(s3_client, bucket, key).await?;
+
+        let workbook = self
+            .zip
+            .extract_file(s3_client, bucket, key, &index, WORKBOOK_PART)
+            .await
+            .context("Not an Excel workbook: missing xl/workbook.xml")?;
+        let rels = self
+            .zip
+            .extract_file(s3_client, bucket, key, &index, WORKBOOK_RELS_PART)
+            .await
+            .context("Not an Excel workbook: missing workbook relationships")?;
+
+        let sheets = Self::parse_workbook(&workbook)?;
+        let targets = Self::parse_relationships(&rels)?;
+
+        for (position, (name, rel_id)) in sheets.into_iter().enumerate() {
+            let part = targets
+                .get(&rel_id)
+                .ok_or_else(|| anyhow!("Sheet {name} refers to unknown relationship {rel_id}"))?;
+
+            // The sheet XML size is the best estimate available without parsing it
+            let size = index.entries.get(part).map(|e| e.size).unwrap_or(0);
+
+            // Sheet names become file names, so they can't contain path separators
+            let path = name.replace('/', "_");
+            index.entries.insert(
+                path.clone(),
+                ArchiveEntry::xlsx_sheet(path, size, part.clone(), position),
+            );
+        }
+
+        Ok(index)
+    }
+
+    #[tracing::instrument(level = "debug", skip(self, s3_client, index))]
+    async fn extract_file(
+        &self,
+        s3_client: &Arc<S3Client>,
+        bucket: &str,
+        key: &str,
+        index: &ArchiveIndex,
+        file_path: &str,
+    ) -> Result<Bytes> {
+        let entry = index
+            .entries
+            .get(file_path)
+            .ok_or_else(|| anyhow!("Sheet not found in workbook: {file_path}"))?;
+
+        let part = match &entry.entry_type {
+            EntryType::XlsxSheet { part, .. } => part,
+            _ => return Err(anyhow!("Sheet not found in workbook: {file_path}")),
+        };
+
+        let shared_strings = if index.entries.contains_key(SHARED_STRINGS_PART) {
+            let xml = self
+                .zip
+                .extract_file(s3_client, bucket, key, index, SHARED_STRINGS_PART)
+                .await?;
+            Self::parse_shared_strings(&xml)?
+        } else {
+            Vec::new()
+        };
+
+        let xml = self
+            .zip
+            .extract_file(s3_client, bucket, key, index, part)
+            .await?;
+        let (rows, total_rows) = Self::parse_sheet(&xml, &shared_strings, PREVIEW_ROWS)?;
+
+        Ok(Bytes::from(Self::render_table(&rows, total_rows)))
+    }
+
+    fn list_entries<'a>(&self, index: &'a ArchiveIndex, path: &str) -> Vec<&'a ArchiveEntry> {
+        // Sheets only live at the root; the underlying XML parts stay hidden
+        if !path.trim_matches('/').is_empty() {
+            return Vec::new();
+        }
+
+        let mut sheets: Vec<(usize, &ArchiveEntry)> = index
+            .entries
+            .values()
+            .filter_map(|entry| match &entry.entry_type {
+                EntryType::XlsxSheet { position, .. } => Some((*position, entry)),
+                _ => None,
+            })
+            .collect();
+
+        // Keep the workbook's tab order
+        sheets.sort_by_key(|(position, _)| *position);
+        sheets.into_iter().map(|(_, entry)| entry).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_index() {
+        assert_eq!(XlsxHandler::column_index("A1"), Some(0));
+        assert_eq!(XlsxHandler::column_index("Z9"), Some(25));
+        assert_eq!(XlsxHandler::column_index("AA10"), Some(26));
+        assert_eq!(XlsxHandler::column_index("AB1"), Some(27));
+        assert_eq!(XlsxHandler::column_index("12"), None);
+        assert_eq!(XlsxHandler::column_index("XFD1"), Some(16_383));
+        // Past the last column, or long enough to overflow
+        assert_eq!(XlsxHandler::column_index("XFE1"), None);
+        assert_eq!(XlsxHandler::column_index("ZZZZZZ1"), None);
+        assert_eq!(XlsxHandler::column_index("ZZZZZZZZZZZZZZ1"), None);
+    }
+
+    #[test]
+    fn test_parse_workbook_and_relationships() {
+        let workbook = br#"<?xml version="1.0"?>
+<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"
+    xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+  <sheets>
+    <sheet name="Sales &amp; Costs" sheetId="1" r:id="rId1"/>
+    <sheet name="Notes" sheetId="2" r:id="rId2"/>
+  </sheets>
+</workbook>"#;
+        let sheets = XlsxHandler::parse_workbook(workbook).unwrap();
+        assert_eq!(
+            sheets,
+            vec![
+                ("Sales & Costs".to_string(), "rId1".to_string()),
+                ("Notes".to_string(), "rId2".to_string())
+            ]
+        );
+
+        let rels = br#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rId1" Type="worksheet" Target="worksheets/sheet1.xml"/>
+  <Relationship Id="rId2" Type="worksheet" Target="/xl/worksheets/sheet2.xml"/>
+</Relationships>"#;
+        let targets = XlsxHandler::parse_relationships(rels).unwrap();
+        assert_eq!(targets["rId1"], "xl/worksheets/sheet1.xml");
+        assert_eq!(targets["rId2"], "xl/worksheets/sheet2.xml");
+    }
+
+    #[test]
+    fn test_parse_shared_strings() {
+        let xml = br#"<sst count="3" uniqueCount="3">
+  <si><t>name</t></si>
+  <si><r><t>Fish </t></r><r><t>&amp; Chips</t></r><rPh><t>ignored</t></rPh></si>
+  <si/>
+</sst>"#;
+        let strings = XlsxHandler::parse_shared_strings(xml).unwrap();
+        assert_eq!(strings, vec!["name", "Fish & Chips", ""]);
+    }
+
+    #[test]
+    fn test_parse_sheet_and_render() {
+        let shared = vec!["name".to_string(), "qty".to_string(), "apple".to_string()];
+        let xml = br#"<worksheet><sheetData>
+  <row r="1"><c r="A1" t="s"><v>0</v></c><c r="B1" t="s"><v>1</v></c></row>
+  <row r="2"><c r="A2" t="s"><v>2</v></c><c r="B2"><v>12</v></c><c r="D2" t="b"><v>1</v></c></row>
+  <row r="3"><c r="A3" t="inlineStr"><is><t>pear</t></is></c></row>
+</sheetData></worksheet>"#;
+
+        let (rows, total) = XlsxHandler::parse_sheet(xml, &shared, 2).unwrap();
+        assert_eq!(total, 3);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0], vec!["name", "qty"]);
+        assert_eq!(rows[1], vec!["apple", "12", "", "TRUE"]);
+
+        let table = XlsxHandler::render_table(&rows, total);
+        assert_eq!(
+            table,
+            "name  | qty |  |\n------+-----+--+-----\napple | 12  |  | TRUE\n\n... (1 more rows)\n"
+        );
+
+        // Cells past the last column are skipped rather than padded out to
+        let xml = br#"<worksheet><sheetData>
+  <row r="1"><c r="A1"><v>1</v></c><c r="ZZZZZZ1"><v>2</v></c><c r="ZZZZZZZZZZZZZZ1"><v>3</v></c></row>
+</sheetData></worksheet>"#;
+        let (rows, _) = XlsxHandler::parse_sheet(xml, &shared, 10).unwrap();
+        assert_eq!(rows, vec![vec!["1"]]);
+    }
+}
@@ -7,9 +7,9 @@ use std::io::Read;
 use std::sync::Arc;
 
 use crate::s3::{S3Client, S3Stream};
-use crate::vfs::{ArchiveEntry, ArchiveIndex, EntryType};
+use crate::vfs::{ArchiveEntry, ArchiveIndex, EntryAttrs, EntryType};
 
-use super::ArchiveHandler;
+use super::{ArchiveHandler, extract_limits};
 
 /// Maximum size to read for the End of Central Directory search.
 /// Must be at least 65535 (max comment) + 22 (EOCD) = 65557 bytes.
@@ -28,11 +28,30 @@ const LOCAL_HEADER_MIN_SIZE: usize = 30;
 const COMPRESSION_STORED: u16 = 0;
 const COMPRESSION_DEFLATE: u16 = 8;
 
-/// Maximum allowed decompressed size (1GB) to prevent zip bombs
-const MAX_DECOMPRESSED_SIZE: u64 = 1024 * 1024 * 1024;
+/// Name of a compression method, as listed by `ls -l` and `stat`
+pub fn method_name(method: u16) -> String {
+    match method {
+        COMPRESSION_STORED => "stored",
+        COMPRESSION_DEFLATE => "deflate",
+        9 => "deflate64",
+        12 => "bzip2",
+        14 => "lzma",
+        93 => "zstd",
+        95 => "xz",
+        98 => "ppmd",
+        other => return format!("method {other}"),
+    }
+    .to_string()
+}
 
-/// Maximum compression ratio allowed (1000:1) to detect zip bombs
-const MAX_COMPRESSION_RATIO: u64 = 1000;
+/// How much smaller compression made a file, e.g. "75%" for one packed to a quarter
+pub fn space_saved(size: u64, compressed_size: u64) -> String {
+    if size == 0 {
+        return "0%".to_string();
+    }
+    let saved = 100.0 * (1.0 - compressed_size as f64 / size as f64);
+    format!("{saved:.0}%")
+}
 
 /// ZIP signatures
 const EOCD_SIGNATURE: [u8; 4] = [0x50, 0x4b, 0x05, 0x06];
@@ -44,6 +63,49 @@ const FLAG_ENCRYPTED: u16 = 0x0001;
 const FLAG_DATA_DESCRIPTOR: u16 = 0x0008;
 const FLAG_UTF8_FILENAME: u16 = 0x0800;
 
+/// "Version made by" host for Unix, whose external attributes hold the file mode
+const HOST_UNIX: u16 = 3;
+
+/// File type bits of a Unix mode, and the type of a symbolic link
+const S_IFMT: u32 = 0o170000;
+const S_IFLNK: u32 = 0o120000;
+
+/// Extra field holding the modification time in UTC ("UT")
+const EXTENDED_TIMESTAMP_ID: u16 = 0x5455;
+
+/// Convert an MS-DOS date and time (local time, 2-second precision) to Unix seconds
+/// The time zone isn't recorded, so the result is the wall-clock time read as UTC.
+fn dos_to_unix_time(date: u16, time: u16) -> Option<i64> {
+    let date = chrono::NaiveDate::from_ymd_opt(
+        1980 + i32::from(date >> 9),
+        u32::from((date >> 5) & 0xf),
+        u32::from(date & 0x1f),
+    )?;
+    let time = chrono::NaiveTime::from_hms_opt(
+        u32::from(time >> 11),
+        u32::from((time >> 5) & 0x3f),
+        u32::from(time & 0x1f) * 2,
+    )?;
+    Some(date.and_time(time).and_utc().timestamp())
+}
+
+/// Modification time from the extended timestamp extra field, if the entry has one
+fn extended_mtime(mut extra: &[u8]) -> Option<i64> {
+    while extra.len() >= 4 {
+        let id = read_u16_le(extra, 0);
+        let len = read_u16_le(extra, 2) as usize;
+        let field = extra.get(4..4 + len)?;
+        // Flag bit 0 says the modification time follows
+        if id == EXTENDED_TIMESTAMP_ID && field.len() >= 5 && field[0] & 1 != 0 {
+            return Some(i64::from(i32::from_le_bytes([
+                field[1], field[2], field[3], field[4],
+            ])));
+        }
+        extra = &extra[4 + len..];
+    }
+    None
+}
+
 /// Read a little-endian u16 from a byte slice at the given offset
 fn read_u16_le(data: &[u8], offset: usize) -> u16 {
     u16::from_le_bytes([data[offset], data[offset + 1]])
@@ -70,6 +132,7 @@ struct EocdInfo {
 
 #[async_trait]
 impl ArchiveHandler for ZipHandler {
+    #[tracing::instrument(level = "debug", skip(self, s3_client))]
     async fn build_index(
         &self,
         s3_client: &Arc<S3Client>,
@@ -121,9 +184,12 @@ impl ArchiveHandler for ZipHandler {
             metadata: std::collections::HashMap::new(),
             #[cfg(feature = "parquet")]
             parquet_store: None,
+            #[cfg(feature = "parquet")]
+            parquet_metadata: None,
         })
     }
 
+    #[tracing::instrument(level = "debug", skip(self, s3_client, index))]
     async fn extract_file(
         &self,
         s3_client: &Arc<S3Client>,
@@ -163,22 +229,9 @@ impl ArchiveHandler for ZipHandler {
                 }
             };
 
-        // Zip bomb protection: check decompressed size limit
-        if entry.size > MAX_DECOMPRESSED_SIZE {
-            return Err(anyhow!(
-                "File too large to extract safely: {} bytes exceeds {} byte limit",
-                entry.size,
-                MAX_DECOMPRESSED_SIZE
-            ));
-        }
-
-        // Zip bomb protection: check compression ratio
-        if compressed_size > 0 && entry.size / compressed_size > MAX_COMPRESSION_RATIO {
-            return Err(anyhow!(
-                "Suspicious compression ratio detected ({:.0}:1). File may be a zip bomb.",
-                entry.size as f64 / compressed_size as f64
-            ));
-        }
+        // Zip bomb protection: check the declared size and compression ratio
+        let limits = extract_limits(s3_client);
+        limits.check(entry.size, compressed_size)?;
 
         // Create S3 stream for range requests
         let stream =
@@ -273,7 +326,9 @@ impl ArchiveHandler for ZipHandler {
             COMPRESSION_DEFLATE => {
                 let mut decoder = DeflateDecoder::new(&compressed_data[..]);
                 // Use bounded reads to prevent memory exhaustion from malicious input
-                let max_allowed = MAX_DECOMPRESSED_SIZE.min(entry.size);
+                let max_allowed = limits
+                    .max_size
+                    .map_or(entry.size, |max| max.min(entry.size));
                 let capacity = max_allowed as usize;
                 let mut decompressed = Vec::with_capacity(capacity);
                 let mut buffer = [0u8; 8192];
@@ -575,6 +630,21 @@ impl ZipHandler {
             // Determine if it's a directory (ends with /)
             let is_dir = filename.ends_with('/');
 
+            // Timestamps and (for archives made on Unix) permissions
+            let extra_start = pos + CDFH_MIN_SIZE + filename_len;
+            let extra = &data[extra_start..extra_start + extra_len];
+            let made_on_unix = read_u16_le(data, pos + 4) >> 8 == HOST_UNIX;
+            let file_mode = read_u32_le(data, pos + 38) >> 16;
+            let unix_mode = file_mode & 0o7777;
+            let attrs = EntryAttrs {
+                mtime: extended_mtime(extra).or_else(|| {
+                    dos_to_unix_time(read_u16_le(data, pos + 14), read_u16_le(data, pos + 12))
+                }),
+                mode: (made_on_unix && unix_mode != 0).then_some(unix_mode),
+                symlink: made_on_unix && file_mode & S_IFMT == S_IFLNK,
+                ..Default::default()
+            };
+
             // Create the entry
             let entry = ArchiveEntry::zip_entry(
                 filename.clone(),
@@ -584,7 +654,8 @@ impl ZipHandler {
                 compressed_size,
                 compression_method,
                 crc32,
-            );
+            )
+            .with_attrs(attrs);
 
             entries.insert(filename, entry);
 
@@ -649,6 +720,16 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_method_name_and_space_saved() {
+        assert_eq!(method_name(COMPRESSION_DEFLATE), "deflate");
+        assert_eq!(method_name(93), "zstd");
+        assert_eq!(method_name(42), "method 42");
+        assert_eq!(space_saved(1000, 250), "75%");
+        assert_eq!(space_saved(1000, 1000), "0%");
+        assert_eq!(space_saved(0, 0), "0%");
+    }
+
     #[test]
     fn test_crc32_empty() {
         assert_eq!(crc32fast::hash(&[]), 0x00000000);
@@ -742,6 +823,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_central_directory_attributes() {
+        let mut data = vec![0u8; 100];
+
+        data[0..4].copy_from_slice(&CDFH_SIGNATURE);
+        data[4..6].copy_from_slice(&0x031eu16.to_le_bytes()); // made on Unix
+        // 2024-03-15 13:45:30 in MS-DOS format
+        data[12..14].copy_from_slice(&((13u16 << 11) | (45 << 5) | 15).to_le_bytes());
+        data[14..16].copy_from_slice(&((44u16 << 9) | (3 << 5) | 15).to_le_bytes());
+        data[28..30].copy_from_slice(&6u16.to_le_bytes()); // filename length
+        data[38..42].copy_from_slice(&(0o100755u32 << 16).to_le_bytes()); // external attrs
+        data[46..52].copy_from_slice(b"run.sh");
+
+        let entries = ZipHandler::parse_central_directory(&data, 10000).unwrap();
+        let attrs = &entries.get("run.sh").unwrap().attrs;
+        assert_eq!(attrs.mode, Some(0o755));
+        assert_eq!(attrs.modified().as_deref(), Some("2024-03-15 13:45:30"));
+        assert_eq!(attrs.permissions(false).as_deref(), Some("-rwxr-xr-x"));
+        assert!(!attrs.symlink);
+
+        data[38..42].copy_from_slice(&(0o120777u32 << 16).to_le_bytes());
+        let entries = ZipHandler::parse_central_directory(&data, 10000).unwrap();
+        let attrs = &entries.get("run.sh").unwrap().attrs;
+        assert!(attrs.symlink);
+        assert_eq!(attrs.permissions(false).as_deref(), Some("lrwxrwxrwx"));
+
+        // The extended timestamp (UTC) wins over the DOS time
+        let mut extra = EXTENDED_TIMESTAMP_ID.to_le_bytes().to_vec();
+        extra.extend_from_slice(&5u16.to_le_bytes());
+        extra.push(1);
+        extra.extend_from_slice(&1_700_000_000i32.to_le_bytes());
+        assert_eq!(extended_mtime(&extra), Some(1_700_000_000));
+        assert_eq!(extended_mtime(&[]), None);
+        assert_eq!(dos_to_unix_time(0, 0), None);
+    }
+
     #[test]
     fn test_parse_central_directory_utf8_filename() {
         let mut data = vec![0u8; 100];
@@ -23,7 +23,9 @@ impl ArchiveCache {
     /// Get an archive index from the cache
     pub fn get(&self, key: &str) -> Option<Arc<ArchiveIndex>> {
         let mut cache = self.cache.write().ok()?;
-        cache.get(key).cloned()
+        let index = cache.get(key).cloned();
+        tracing::debug!(key, hit = index.is_some(), "archive index cache lookup");
+        index
     }
 
     /// Put an archive index into the cache
@@ -40,6 +42,43 @@ impl ArchiveCache {
         }
     }
 
+    /// Change the maximum number of entries, evicting the least recently used if shrinking
+    pub fn resize(&self, capacity: usize) {
+        if let (Ok(mut cache), Some(capacity)) = (self.cache.write(), NonZeroUsize::new(capacity)) {
+            cache.resize(capacity);
+        }
+    }
+
+    /// Drop every archive index for which `keep` returns false
+    pub fn retain(&self, keep: impl Fn(&ArchiveIndex) -> bool) {
+        if let Ok(mut cache) = self.cache.write() {
+            let stale: Vec<String> = cache
+                .iter()
+                .filter(|(_, index)| !keep(index))
+                .map(|(key, _)| key.clone())
+                .collect();
+            for key in stale {
+                cache.pop(&key);
+            }
+        }
+    }
+
+    /// Drop the archive indexes whose keys `stale` matches, returning how many
+    pub fn remove_where(&self, stale: impl Fn(&str) -> bool) -> usize {
+        let Ok(mut cache) = self.cache.write() else {
+            return 0;
+        };
+        let keys: Vec<String> = cache
+            .iter()
+            .filter(|(key, _)| stale(key))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &keys {
+            cache.pop(key);
+        }
+        keys.len()
+    }
+
     /// Get cache statistics
     pub fn len(&self) -> usize {
         self.cache.read().ok().map(|c| c.len()).unwrap_or(0)
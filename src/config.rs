@@ -0,0 +1,821 @@
+use anyhow::{Context, Result, anyhow};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use toml_edit::{DocumentMut, Item, Table, Value, value};
+
+use crate::providers::ProviderConfig;
+use crate::s3::connection::HttpOptions;
+use crate::s3::retry::RetryOn;
+use crate::shell::completion::MatchMode;
+use crate::shell::prompt;
+use crate::shell::settings::{
+    MIN_PART_SIZE, parse_attempts, parse_bandwidth, parse_bool, parse_compression_ratio,
+    parse_concurrency, parse_duration, parse_extract_size, parse_part_size, parse_read_ahead,
+    parse_size, parse_timeout,
+};
+use std::time::Duration;
+
+/// Top-level keys recognized in config.toml, with descriptions for the `config` command
+pub const KEYS: &[(&str, &str)] = &[
+    ("provider", "Provider used when --provider isn't given"),
+    ("region", "Default region for providers without a fixed one"),
+    ("color", "Colored output (on/off)"),
+    (
+        "prompt",
+        "Prompt template, e.g. \"{provider}:{path:2}> \" (see README)",
+    ),
+    ("history_size", "Maximum number of history entries kept"),
+    (
+        "history_dedup",
+        "Skip a history entry identical to the previous one (on/off)",
+    ),
+    ("cache_size", "Number of archive indexes kept in memory"),
+    (
+        "completion_ttl",
+        "How long tab completion reuses a directory listing, e.g. 30s",
+    ),
+    (
+        "listing_ttl",
+        "How long ls and tab completion reuse a directory listing (0 = always list)",
+    ),
+    (
+        "completion_mode",
+        "How tab completion matches names: prefix or fuzzy",
+    ),
+    ("confirm", "Ask before expensive operations (on/off)"),
+    (
+        "archive_threshold",
+        "Streaming archives above this size asks first",
+    ),
+    (
+        "timeout",
+        "Seconds before a command is abandoned (off = never)",
+    ),
+    (
+        "part_size",
+        "Large downloads are split into ranged GETs of this size",
+    ),
+    (
+        "part_concurrency",
+        "Ranged GETs in flight per download (1 = sequential)",
+    ),
+    (
+        "list_concurrency",
+        "Prefixes listed at once by recursive listings (sync, get -r, ls -R)",
+    ),
+    (
+        "read_ahead",
+        "Largest chunk archive reads fetch ahead, e.g. 16MiB (off = none)",
+    ),
+    (
+        "bandwidth",
+        "Limit on download and upload speed, e.g. 10MB/s (off = none)",
+    ),
+    (
+        "max_extract_size",
+        "Largest file decompressed out of an archive, e.g. 4GiB (off = none)",
+    ),
+    (
+        "max_compression_ratio",
+        "Larger compression ratios are refused as zip bombs (off = none)",
+    ),
+    (
+        "retry_attempts",
+        "Attempts per S3 call, including the first (1 = no retries)",
+    ),
+    (
+        "retry_backoff",
+        "Delay before the first retry, doubling after each one",
+    ),
+    (
+        "retry_on",
+        "Failures to retry: throttling, server, timeout, all, or none",
+    ),
+    (
+        "connect_timeout",
+        "Give up connecting after this long (e.g. 5s; startup only)",
+    ),
+    (
+        "read_timeout",
+        "Give up on a stalled response after this long (startup only)",
+    ),
+    (
+        "idle_timeout",
+        "Close pooled connections idle this long (startup only)",
+    ),
+    (
+        "max_idle_connections",
+        "Idle connections kept per host for HTTP(S) sources (startup only)",
+    ),
+    (
+        "max_concurrent_requests",
+        "S3 requests in flight at once (startup only)",
+    ),
+    (
+        "use_dualstack",
+        "Use dual-stack (IPv4/IPv6) AWS endpoints (on/off; startup only)",
+    ),
+    (
+        "use_fips",
+        "Use FIPS endpoints, e.g. in GovCloud (on/off; startup only)",
+    ),
+    (
+        "readonly",
+        "Refuse uploads, deletes, and other changes to S3 (on/off)",
+    ),
+];
+
+/// A `[mounts]` entry: buckets matching `pattern` are served by another provider
+#[derive(Debug, Clone, PartialEq)]
+pub struct MountConfig {
+    pub pattern: String,
+    pub provider: String,
+    pub profile: Option<String>,
+    pub region: Option<String>,
+}
+
+/// Persistent user configuration stored in `~/.config/s3sh/config.toml`
+/// The document is edited in place so comments and formatting survive a save.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    /// File the configuration was loaded from (None = in-memory only, never saved)
+    path: Option<PathBuf>,
+    doc: DocumentMut,
+}
+
+impl Config {
+    /// Default location: $S3SH_CONFIG, else $XDG_CONFIG_HOME/s3sh/config.toml,
+    /// else ~/.config/s3sh/config.toml
+    pub fn default_path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os("S3SH_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+        let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| dirs::home_dir().map(|home| home.join(".config")))?;
+        Some(config_dir.join("s3sh").join("config.toml"))
+    }
+
+    /// Load the configuration from the default location (a missing file is an empty config)
+    pub fn load() -> Result<Self> {
+        match Self::default_path() {
+            Some(path) => Self::load_from(path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Load the configuration from a specific file (a missing file is an empty config)
+    pub fn load_from(path: PathBuf) -> Result<Self> {
+        let doc = match std::fs::read_to_string(&path) {
+            Ok(contents) => Self::parse(&contents)
+                .with_context(|| format!("Invalid config file {}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => DocumentMut::new(),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read {}", path.display()));
+            }
+        };
+
+        Ok(Config {
+            path: Some(path),
+            doc,
+        })
+    }
+
+    /// Parse configuration text
+    fn parse(contents: &str) -> Result<DocumentMut> {
+        contents.parse::<DocumentMut>().map_err(|e| anyhow!("{e}"))
+    }
+
+    /// File the configuration is saved to, if any
+    pub fn path(&self) -> Option<&PathBuf> {
+        self.path.as_ref()
+    }
+
+    /// Write the configuration back to its file
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        std::fs::write(path, self.doc.to_string())
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Get a setting formatted for display, or None if it isn't set
+    pub fn get(&self, key: &str) -> Result<Option<String>> {
+        Self::check_key(key)?;
+        Ok(self.doc.get(key).and_then(Item::as_value).map(|v| match v {
+            Value::String(s) => s.value().clone(),
+            Value::Boolean(b) => if *b.value() { "on" } else { "off" }.to_string(),
+            other => other.to_string().trim().to_string(),
+        }))
+    }
+
+    /// Validate and store a setting
+    pub fn set(&mut self, key: &str, raw: &str) -> Result<()> {
+        Self::check_key(key)?;
+        self.doc[key] = match key {
+            "color" | "confirm" | "history_dedup" | "readonly" | "use_dualstack" | "use_fips" => {
+                value(parse_bool(raw)?)
+            }
+            "history_size" | "cache_size" | "max_concurrent_requests" => {
+                let n: i64 = raw
+                    .parse()
+                    .ok()
+                    .filter(|n| *n > 0)
+                    .ok_or_else(|| anyhow!("{key} must be a positive number"))?;
+                value(n)
+            }
+            "archive_threshold" => {
+                parse_size(raw)?;
+                value(raw)
+            }
+            "timeout" => {
+                parse_timeout(raw)?;
+                value(raw)
+            }
+            "part_size" => {
+                parse_part_size(raw)?;
+                value(raw)
+            }
+            "part_concurrency" | "list_concurrency" => value(parse_concurrency(raw)? as i64),
+            "read_ahead" => {
+                parse_read_ahead(raw)?;
+                value(raw)
+            }
+            "bandwidth" => {
+                parse_bandwidth(raw)?;
+                value(raw)
+            }
+            "max_extract_size" => {
+                parse_extract_size(raw)?;
+                value(raw)
+            }
+            "max_compression_ratio" => {
+                parse_compression_ratio(raw)?;
+                value(raw)
+            }
+            "retry_attempts" => value(i64::from(parse_attempts(raw)?)),
+            "retry_backoff" | "completion_ttl" | "listing_ttl" | "connect_timeout"
+            | "read_timeout" | "idle_timeout" => {
+                parse_duration(raw)?;
+                value(raw)
+            }
+            "max_idle_connections" => {
+                let n: i64 = raw
+                    .parse()
+                    .ok()
+                    .filter(|n| *n >= 0)
+                    .ok_or_else(|| anyhow!("{key} must be a number"))?;
+                value(n)
+            }
+            "retry_on" => value(RetryOn::parse(raw)?.to_string()),
+            "completion_mode" => value(MatchMode::parse(raw)?.to_string()),
+            "prompt" => {
+                prompt::validate(raw)?;
+                value(raw)
+            }
+            _ => value(raw),
+        };
+        Ok(())
+    }
+
+    /// Remove a setting so the built-in default applies again
+    pub fn unset(&mut self, key: &str) -> Result<()> {
+        Self::check_key(key)?;
+        self.doc.remove(key);
+        Ok(())
+    }
+
+    fn check_key(key: &str) -> Result<()> {
+        if KEYS.iter().any(|(k, _)| *k == key) {
+            Ok(())
+        } else {
+            Err(anyhow!("Unknown config key: {key}"))
+        }
+    }
+
+    /// Provider used when --provider isn't given
+    pub fn provider(&self) -> Option<&str> {
+        self.doc.get("provider")?.as_str()
+    }
+
+    /// Default region
+    pub fn region(&self) -> Option<&str> {
+        self.doc.get("region")?.as_str()
+    }
+
+    /// Colored output
+    pub fn color(&self) -> Option<bool> {
+        self.bool_value("color")
+    }
+
+    /// Prompt template
+    pub fn prompt(&self) -> Option<&str> {
+        self.doc.get("prompt")?.as_str()
+    }
+
+    /// Maximum number of history entries
+    pub fn history_size(&self) -> Option<usize> {
+        self.count_value("history_size")
+    }
+
+    /// Skip consecutive duplicate history entries
+    pub fn history_dedup(&self) -> Option<bool> {
+        self.bool_value("history_dedup")
+    }
+
+    /// Number of archive indexes kept in memory
+    pub fn cache_size(&self) -> Option<usize> {
+        self.count_value("cache_size")
+    }
+
+    /// How long completion listings are reused
+    pub fn completion_ttl(&self) -> Option<Duration> {
+        self.duration_value("completion_ttl")
+    }
+
+    /// How long directory listings are reused
+    pub fn listing_ttl(&self) -> Option<Duration> {
+        self.duration_value("listing_ttl")
+    }
+
+    /// How tab completion matches names
+    pub fn completion_mode(&self) -> Option<MatchMode> {
+        MatchMode::parse(self.doc.get("completion_mode")?.as_str()?).ok()
+    }
+
+    /// Ask before expensive operations
+    pub fn confirm(&self) -> Option<bool> {
+        self.bool_value("confirm")
+    }
+
+    /// Archive size above which streaming asks first (a number of bytes or a size like "10GB")
+    pub fn archive_threshold(&self) -> Option<u64> {
+        match self.doc.get("archive_threshold")?.as_value()? {
+            Value::Integer(n) => u64::try_from(*n.value()).ok(),
+            Value::String(s) => parse_size(s.value()).ok(),
+            _ => None,
+        }
+    }
+
+    /// Command timeout: Some(None) when explicitly turned off
+    pub fn timeout(&self) -> Option<Option<Duration>> {
+        match self.doc.get("timeout")?.as_value()? {
+            Value::Integer(n) => Some(
+                u64::try_from(*n.value())
+                    .ok()
+                    .filter(|n| *n > 0)
+                    .map(Duration::from_secs),
+            ),
+            Value::String(s) => parse_timeout(s.value()).ok(),
+            _ => None,
+        }
+    }
+
+    /// Part size for ranged downloads (a number of bytes or a size like "16MiB")
+    pub fn part_size(&self) -> Option<u64> {
+        match self.doc.get("part_size")?.as_value()? {
+            Value::Integer(n) => u64::try_from(*n.value())
+                .ok()
+                .filter(|n| *n >= MIN_PART_SIZE),
+            Value::String(s) => parse_part_size(s.value()).ok(),
+            _ => None,
+        }
+    }
+
+    /// Ranged GETs in flight per download
+    pub fn part_concurrency(&self) -> Option<usize> {
+        self.count_value("part_concurrency")
+    }
+
+    /// Prefixes listed at once by recursive listings
+    pub fn list_concurrency(&self) -> Option<usize> {
+        self.count_value("list_concurrency")
+    }
+
+    /// Largest read-ahead chunk for streamed archive reads (0 = off)
+    pub fn read_ahead(&self) -> Option<u64> {
+        match self.doc.get("read_ahead")?.as_value()? {
+            Value::Integer(n) => u64::try_from(*n.value()).ok(),
+            Value::String(s) => parse_read_ahead(s.value()).ok(),
+            _ => None,
+        }
+    }
+
+    /// Transfer bandwidth limit: Some(None) when explicitly turned off
+    pub fn bandwidth(&self) -> Option<Option<u64>> {
+        match self.doc.get("bandwidth")?.as_value()? {
+            Value::Integer(n) => Some(u64::try_from(*n.value()).ok().filter(|n| *n > 0)),
+            Value::String(s) => parse_bandwidth(s.value()).ok(),
+            _ => None,
+        }
+    }
+
+    /// Largest file decompressed out of an archive: Some(None) when explicitly turned off
+    pub fn max_extract_size(&self) -> Option<Option<u64>> {
+        match self.doc.get("max_extract_size")?.as_value()? {
+            Value::Integer(n) => Some(u64::try_from(*n.value()).ok().filter(|n| *n > 0)),
+            Value::String(s) => parse_extract_size(s.value()).ok(),
+            _ => None,
+        }
+    }
+
+    /// Largest compression ratio accepted: Some(None) when explicitly turned off
+    pub fn max_compression_ratio(&self) -> Option<Option<u64>> {
+        match self.doc.get("max_compression_ratio")?.as_value()? {
+            Value::Integer(n) => Some(u64::try_from(*n.value()).ok().filter(|n| *n > 0)),
+            Value::String(s) => parse_compression_ratio(s.value()).ok(),
+            _ => None,
+        }
+    }
+
+    /// Attempts per S3 call
+    pub fn retry_attempts(&self) -> Option<u32> {
+        self.count_value("retry_attempts")
+            .and_then(|n| u32::try_from(n).ok())
+    }
+
+    /// Delay before the first retry (seconds, or a string like "500ms")
+    pub fn retry_backoff(&self) -> Option<Duration> {
+        self.duration_value("retry_backoff")
+    }
+
+    /// Failures that are retried
+    pub fn retry_on(&self) -> Option<RetryOn> {
+        RetryOn::parse(self.doc.get("retry_on")?.as_str()?).ok()
+    }
+
+    /// Refuse changes to S3
+    pub fn read_only(&self) -> Option<bool> {
+        self.bool_value("readonly")
+    }
+
+    /// Apply the connection settings (timeouts, pooling, dual-stack, FIPS) to a
+    /// provider's configuration
+    pub fn apply_to_provider(&self, provider: &mut ProviderConfig) {
+        provider.http = self.http_options();
+        if let Some(dualstack) = self.use_dualstack() {
+            provider.use_dualstack = dualstack;
+        }
+        if let Some(fips) = self.use_fips() {
+            provider.use_fips = fips;
+        }
+    }
+
+    /// Use dual-stack endpoints
+    pub fn use_dualstack(&self) -> Option<bool> {
+        self.bool_value("use_dualstack")
+    }
+
+    /// Use FIPS endpoints
+    pub fn use_fips(&self) -> Option<bool> {
+        self.bool_value("use_fips")
+    }
+
+    /// Connection timeouts, pooling, and the cap on S3 requests in flight
+    pub fn http_options(&self) -> HttpOptions {
+        HttpOptions {
+            connect_timeout: self.duration_value("connect_timeout"),
+            read_timeout: self.duration_value("read_timeout"),
+            idle_timeout: self.duration_value("idle_timeout"),
+            max_idle_connections: self
+                .doc
+                .get("max_idle_connections")
+                .and_then(Item::as_integer)
+                .and_then(|n| usize::try_from(n).ok()),
+            max_concurrent_requests: self.count_value("max_concurrent_requests"),
+        }
+    }
+
+    /// Durations may be written as seconds or strings like "500ms"
+    fn duration_value(&self, key: &str) -> Option<Duration> {
+        match self.doc.get(key)?.as_value()? {
+            Value::Integer(n) => u64::try_from(*n.value()).ok().map(Duration::from_secs),
+            Value::Float(f) => parse_duration(&f.value().to_string()).ok(),
+            Value::String(s) => parse_duration(s.value()).ok(),
+            _ => None,
+        }
+    }
+
+    /// Booleans may be written as TOML booleans or on/off strings
+    fn bool_value(&self, key: &str) -> Option<bool> {
+        match self.doc.get(key)?.as_value()? {
+            Value::Boolean(b) => Some(*b.value()),
+            Value::String(s) => parse_bool(s.value()).ok(),
+            _ => None,
+        }
+    }
+
+    fn count_value(&self, key: &str) -> Option<usize> {
+        usize::try_from(self.doc.get(key)?.as_integer()?)
+            .ok()
+            .filter(|n| *n > 0)
+    }
+
+    /// Price overrides from the `[pricing.<provider>]` table (integers or floats)
+    pub fn pricing(&self, provider: &str) -> BTreeMap<String, f64> {
+        self.doc
+            .get("pricing")
+            .and_then(|pricing| pricing.get(provider))
+            .and_then(Item::as_table_like)
+            .map(|table| {
+                table
+                    .iter()
+                    .filter_map(|(key, item)| {
+                        let price = match item.as_value()? {
+                            Value::Float(f) => *f.value(),
+                            Value::Integer(n) => *n.value() as f64,
+                            _ => return None,
+                        };
+                        Some((key.to_string(), price))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Mounts from the `[mounts]` table, in file order
+    /// An entry is a provider name, or a table with `provider` and optionally `profile`
+    /// and `region`; entries without a provider are skipped.
+    pub fn mounts(&self) -> Vec<MountConfig> {
+        let Some(table) = self.doc.get("mounts").and_then(Item::as_table_like) else {
+            return Vec::new();
+        };
+        table
+            .iter()
+            .filter_map(|(pattern, item)| {
+                let mount = match item.as_str() {
+                    Some(provider) => MountConfig {
+                        pattern: pattern.to_string(),
+                        provider: provider.to_string(),
+                        profile: None,
+                        region: None,
+                    },
+                    None => {
+                        let entry = item.as_table_like()?;
+                        let field = |key| entry.get(key).and_then(Item::as_str).map(String::from);
+                        MountConfig {
+                            pattern: pattern.to_string(),
+                            provider: field("provider")?,
+                            profile: field("profile"),
+                            region: field("region"),
+                        }
+                    }
+                };
+                Some(mount)
+            })
+            .collect()
+    }
+
+    /// S3 Inventory locations from the `[inventory]` table: bucket to s3:// URL
+    pub fn inventories(&self) -> BTreeMap<String, String> {
+        self.doc
+            .get("inventory")
+            .and_then(Item::as_table_like)
+            .map(|table| {
+                table
+                    .iter()
+                    .filter_map(|(bucket, item)| {
+                        Some((bucket.to_string(), item.as_str()?.to_string()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Listing colors from the `[theme]` table: a file kind (`dir`, `archive`, `data`,
+    /// `image`, `symlink`, `file`) or `*.ext` pattern to a style like "bold red"
+    pub fn theme(&self) -> BTreeMap<String, String> {
+        self.doc
+            .get("theme")
+            .and_then(Item::as_table_like)
+            .map(|table| {
+                table
+                    .iter()
+                    .filter_map(|(key, item)| Some((key.to_string(), item.as_str()?.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// All aliases from the `[aliases]` table, sorted by name
+    pub fn aliases(&self) -> BTreeMap<String, String> {
+        self.doc
+            .get("aliases")
+            .and_then(Item::as_table_like)
+            .map(|table| {
+                table
+                    .iter()
+                    .filter_map(|(name, item)| Some((name.to_string(), item.as_str()?.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Look up a single alias
+    pub fn alias(&self, name: &str) -> Option<&str> {
+        self.doc.get("aliases")?.get(name)?.as_str()
+    }
+
+    /// Define or replace an alias
+    pub fn set_alias(&mut self, name: &str, expansion: &str) {
+        let aliases = self
+            .doc
+            .entry("aliases")
+            .or_insert_with(|| Item::Table(Table::new()));
+        aliases[name] = value(expansion);
+    }
+
+    /// Remove an alias, returning whether it existed
+    pub fn remove_alias(&mut self, name: &str) -> bool {
+        self.doc
+            .get_mut("aliases")
+            .and_then(Item::as_table_like_mut)
+            .and_then(|table| table.remove(name))
+            .is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aliases_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("s3sh").join("config.toml");
+
+        let mut config = Config::load_from(path.clone()).unwrap();
+        assert!(config.aliases().is_empty());
+
+        config.set_alias("ll", "ls -l");
+        config.set_alias("lj", "ls | grep .json");
+        config.save().unwrap();
+
+        let mut config = Config::load_from(path.clone()).unwrap();
+        assert_eq!(config.alias("ll"), Some("ls -l"));
+        assert_eq!(config.aliases().len(), 2);
+
+        assert!(config.remove_alias("ll"));
+        assert!(!config.remove_alias("ll"));
+        assert_eq!(config.alias("ll"), None);
+    }
+
+    #[test]
+    fn test_settings() {
+        let mut config = Config::default();
+        assert_eq!(config.get("provider").unwrap(), None);
+
+        config.set("provider", "sourcecoop").unwrap();
+        config.set("color", "off").unwrap();
+        config.set("history_size", "5000").unwrap();
+        config.set("archive_threshold", "10GB").unwrap();
+        config.set("timeout", "120").unwrap();
+        config.set("part_size", "16MiB").unwrap();
+        config.set("part_concurrency", "4").unwrap();
+        config.set("bandwidth", "5MB/s").unwrap();
+        config.set("read_ahead", "off").unwrap();
+        config.set("list_concurrency", "16").unwrap();
+        config.set("readonly", "on").unwrap();
+        config.set("use_fips", "on").unwrap();
+        config.set("retry_attempts", "5").unwrap();
+        config.set("retry_backoff", "200ms").unwrap();
+        config.set("retry_on", "server,throttling").unwrap();
+        assert_eq!(config.provider(), Some("sourcecoop"));
+        assert_eq!(config.color(), Some(false));
+        assert_eq!(config.history_size(), Some(5000));
+        assert_eq!(config.archive_threshold(), Some(10_000_000_000));
+        assert_eq!(config.timeout(), Some(Some(Duration::from_secs(120))));
+        assert_eq!(config.part_size(), Some(16 << 20));
+        assert_eq!(config.part_concurrency(), Some(4));
+        assert_eq!(config.bandwidth(), Some(Some(5_000_000)));
+        assert_eq!(config.read_ahead(), Some(0));
+        config.set("max_extract_size", "off").unwrap();
+        config.set("max_compression_ratio", "5000").unwrap();
+        assert_eq!(config.max_extract_size(), Some(None));
+        assert_eq!(config.max_compression_ratio(), Some(Some(5000)));
+        assert_eq!(config.list_concurrency(), Some(16));
+        assert_eq!(config.read_only(), Some(true));
+        assert_eq!(config.use_fips(), Some(true));
+        assert_eq!(config.use_dualstack(), None);
+        assert_eq!(config.retry_attempts(), Some(5));
+        assert_eq!(config.retry_backoff(), Some(Duration::from_millis(200)));
+        assert_eq!(
+            config.get("retry_on").unwrap().as_deref(),
+            Some("throttling,server")
+        );
+        assert!(config.set("retry_on", "often").is_err());
+        config.set("completion_mode", "FUZZY").unwrap();
+        assert_eq!(config.completion_mode(), Some(MatchMode::Fuzzy));
+        assert!(config.set("completion_mode", "glob").is_err());
+        config.set("completion_ttl", "30s").unwrap();
+        assert_eq!(config.completion_ttl(), Some(Duration::from_secs(30)));
+
+        config.set("connect_timeout", "5s").unwrap();
+        config.set("max_idle_connections", "0").unwrap();
+        config.set("max_concurrent_requests", "4").unwrap();
+        let http = config.http_options();
+        assert_eq!(http.connect_timeout, Some(Duration::from_secs(5)));
+        assert_eq!(http.read_timeout, None);
+        assert_eq!(http.max_idle_connections, Some(0));
+        assert_eq!(http.max_concurrent_requests, Some(4));
+        assert!(config.set("max_concurrent_requests", "0").is_err());
+        assert_eq!(config.get("color").unwrap().as_deref(), Some("off"));
+        assert_eq!(config.get("history_size").unwrap().as_deref(), Some("5000"));
+
+        assert!(config.set("cache_size", "0").is_err());
+        assert!(config.set("confirm", "maybe").is_err());
+        assert!(config.set("part_size", "1KB").is_err());
+        assert!(config.set("nope", "1").is_err());
+
+        config.unset("color").unwrap();
+        assert_eq!(config.color(), None);
+
+        // Hand-written files may use plain TOML types
+        let config = Config {
+            path: None,
+            doc: Config::parse("confirm = false\narchive_threshold = 1000\ntimeout = 0\n").unwrap(),
+        };
+        assert_eq!(config.confirm(), Some(false));
+        assert_eq!(config.archive_threshold(), Some(1000));
+        assert_eq!(config.timeout(), Some(None));
+    }
+
+    #[test]
+    fn test_pricing() {
+        let config = Config {
+            path: None,
+            doc: Config::parse("[pricing.aws]\ntransfer_per_gb = 0.02\nread_per_1000 = 0\n")
+                .unwrap(),
+        };
+        let pricing = config.pricing("aws");
+        assert_eq!(pricing.len(), 2);
+        assert_eq!(pricing["transfer_per_gb"], 0.02);
+        assert_eq!(pricing["read_per_1000"], 0.0);
+        assert!(config.pricing("r2").is_empty());
+    }
+
+    #[test]
+    fn test_inventories() {
+        let config = Config {
+            path: None,
+            doc: Config::parse(
+                "[inventory]\n\"my.bucket\" = \"s3://inv/my.bucket/daily/\"\nother = 1\n",
+            )
+            .unwrap(),
+        };
+        let inventories = config.inventories();
+        assert_eq!(inventories.len(), 1);
+        assert_eq!(inventories["my.bucket"], "s3://inv/my.bucket/daily/");
+    }
+
+    #[test]
+    fn test_mounts() {
+        let config = Config {
+            path: None,
+            doc: Config::parse(concat!(
+                "[mounts]\n",
+                "public-data = \"sourcecoop\"\n",
+                "\"work-*\" = { provider = \"aws\", profile = \"work\" }\n",
+                "broken = { profile = \"work\" }\n",
+            ))
+            .unwrap(),
+        };
+        assert_eq!(
+            config.mounts(),
+            vec![
+                MountConfig {
+                    pattern: "public-data".to_string(),
+                    provider: "sourcecoop".to_string(),
+                    profile: None,
+                    region: None,
+                },
+                MountConfig {
+                    pattern: "work-*".to_string(),
+                    provider: "aws".to_string(),
+                    profile: Some("work".to_string()),
+                    region: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_save_keeps_comments() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "# my settings\n[aliases]\nll = \"ls -l\"\n").unwrap();
+
+        let mut config = Config::load_from(path.clone()).unwrap();
+        config.set_alias("la", "ls -R");
+        config.save().unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.starts_with("# my settings\n"));
+        assert!(contents.contains("la = \"ls -R\""));
+    }
+}
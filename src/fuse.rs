@@ -0,0 +1,417 @@
+//! Read-only FUSE mount of the virtual filesystem (`s3sh mount`).
+//!
+//! Paths map to inodes the first time a directory listing shows them. Listings come
+//! from a [`Session`], so buckets, prefixes, archives, and the virtual files inside
+//! Parquet and JSON objects look the same as they do in the shell. Objects are read
+//! with ranged GETs; files inside archives are extracted whole when opened.
+
+use anyhow::{Context, Result, anyhow};
+use bytes::Bytes;
+use fuser::{
+    BackgroundSession, Config, Errno, FileAttr, FileHandle, FileType, Filesystem, FopenFlags,
+    Generation, INodeNo, LockOwner, MountOption, OpenFlags, ReplyAttr, ReplyData, ReplyDirectory,
+    ReplyEmpty, ReplyEntry, ReplyOpen, Request,
+};
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::archive::format_for_name;
+use crate::session::{Entry, EntryKind, Session};
+use crate::vfs::VfsNode;
+
+/// How long the kernel may cache attributes and names
+const ATTR_TTL: Duration = Duration::from_secs(60);
+
+/// Directory listings are fetched again after this long
+const LISTING_TTL: Duration = Duration::from_secs(60);
+
+/// Mount `session`'s working directory at `mountpoint` until the returned session is dropped
+pub fn mount(session: Session, mountpoint: &Path) -> Result<BackgroundSession> {
+    let metadata = std::fs::metadata(mountpoint)
+        .with_context(|| format!("Mount point not found: {}", mountpoint.display()))?;
+    if !metadata.is_dir() {
+        return Err(anyhow!(
+            "Mount point is not a directory: {}",
+            mountpoint.display()
+        ));
+    }
+    // Files are owned by whoever owns the mount point
+    let owner = (metadata.uid(), metadata.gid());
+    let fs = S3Filesystem::new(session, tokio::runtime::Handle::current(), owner);
+    let mut config = Config::default();
+    config.mount_options.extend([
+        MountOption::RO,
+        MountOption::FSName("s3sh".to_string()),
+        MountOption::Subtype("s3sh".to_string()),
+    ]);
+    // Listings and reads block on S3, so one slow request shouldn't stall the mount
+    config.n_threads = Some(4);
+    fuser::spawn_mount(fs, mountpoint, &config)
+        .with_context(|| format!("Failed to mount at {}", mountpoint.display()))
+}
+
+struct S3Filesystem {
+    session: Session,
+    runtime: tokio::runtime::Handle,
+    inodes: Mutex<Inodes>,
+    handles: Mutex<HashMap<u64, OpenFile>>,
+    next_handle: AtomicU64,
+    uid: u32,
+    gid: u32,
+}
+
+/// Paths seen so far, their attributes, and the listings they came from
+#[derive(Default)]
+struct Inodes {
+    /// Path of inode N at index N - 1
+    paths: Vec<String>,
+    by_path: HashMap<String, u64>,
+    attrs: HashMap<u64, FileAttr>,
+    listings: HashMap<u64, (Instant, Vec<(String, u64)>)>,
+}
+
+impl Inodes {
+    /// The inode for a path, assigning the next one the first time it's seen
+    fn ino(&mut self, path: &str) -> u64 {
+        if let Some(&ino) = self.by_path.get(path) {
+            return ino;
+        }
+        self.paths.push(path.to_string());
+        let ino = self.paths.len() as u64;
+        self.by_path.insert(path.to_string(), ino);
+        ino
+    }
+
+    fn path(&self, ino: u64) -> Option<&str> {
+        self.paths
+            .get(ino.checked_sub(1)? as usize)
+            .map(String::as_str)
+    }
+}
+
+/// What an open file handle reads from
+enum OpenFile {
+    /// An S3 object, read range by range
+    Object {
+        bucket: String,
+        key: String,
+        size: u64,
+    },
+    /// A file extracted from an archive
+    Bytes(Bytes),
+}
+
+impl S3Filesystem {
+    fn new(session: Session, runtime: tokio::runtime::Handle, (uid, gid): (u32, u32)) -> Self {
+        let mut inodes = Inodes::default();
+        let root = inodes.ino(&session.cwd().path());
+        let fs = S3Filesystem {
+            session,
+            runtime,
+            inodes: Mutex::new(inodes),
+            handles: Mutex::new(HashMap::new()),
+            next_handle: AtomicU64::new(1),
+            uid,
+            gid,
+        };
+        let attr = fs.attr(root, FileType::Directory, 0, None);
+        fs.inodes.lock().unwrap().attrs.insert(root, attr);
+        fs
+    }
+
+    fn attr(&self, ino: u64, kind: FileType, size: u64, modified: Option<&str>) -> FileAttr {
+        let time = modified.and_then(parse_time).unwrap_or(UNIX_EPOCH);
+        let dir = kind == FileType::Directory;
+        FileAttr {
+            ino: INodeNo(ino),
+            size,
+            blocks: size.div_ceil(512),
+            atime: time,
+            mtime: time,
+            ctime: time,
+            crtime: time,
+            kind,
+            perm: if dir { 0o555 } else { 0o444 },
+            nlink: if dir { 2 } else { 1 },
+            uid: self.uid,
+            gid: self.gid,
+            rdev: 0,
+            flags: 0,
+            blksize: 512,
+        }
+    }
+
+    /// The children of a directory inode, listing it if the last listing has expired
+    fn children(&self, ino: u64) -> Result<Vec<(String, u64)>, Errno> {
+        let path = {
+            let inodes = self.inodes.lock().unwrap();
+            if let Some((listed, children)) = inodes.listings.get(&ino)
+                && listed.elapsed() < LISTING_TTL
+            {
+                return Ok(children.clone());
+            }
+            inodes.path(ino).ok_or(Errno::ENOENT)?.to_string()
+        };
+
+        let entries = self.runtime.block_on(self.session.ls(&path)).map_err(|e| {
+            tracing::debug!("fuse: listing {path} failed: {e:#}");
+            Errno::EIO
+        })?;
+
+        let mut inodes = self.inodes.lock().unwrap();
+        let children: Vec<(String, u64)> = entries
+            .into_iter()
+            .map(|entry| {
+                let child = inodes.ino(&entry.path);
+                let (kind, size) = file_type(&entry);
+                let attr = self.attr(child, kind, size, entry.modified.as_deref());
+                inodes.attrs.insert(child, attr);
+                (entry.name, child)
+            })
+            .collect();
+        inodes
+            .listings
+            .insert(ino, (Instant::now(), children.clone()));
+        Ok(children)
+    }
+
+    /// Work out what to read for a file inode
+    fn open_file(&self, ino: u64) -> Result<(OpenFile, FopenFlags), Errno> {
+        let path = self
+            .inodes
+            .lock()
+            .unwrap()
+            .path(ino)
+            .ok_or(Errno::ENOENT)?
+            .to_string();
+        let node = self
+            .runtime
+            .block_on(self.session.resolve(&path))
+            .map_err(|_| Errno::ENOENT)?;
+        match node {
+            VfsNode::Object {
+                bucket, key, size, ..
+            } => Ok((OpenFile::Object { bucket, key, size }, FopenFlags::empty())),
+            VfsNode::ArchiveEntry { is_dir: false, .. } => {
+                let bytes = self
+                    .runtime
+                    .block_on(self.session.read(&path))
+                    .map_err(|e| {
+                        tracing::debug!("fuse: reading {path} failed: {e:#}");
+                        Errno::EIO
+                    })?;
+                // Virtual files (Parquet columns, JSON schemas) only have estimated
+                // sizes, so reads go to EOF instead of stopping at the listed size
+                Ok((OpenFile::Bytes(bytes), FopenFlags::FOPEN_DIRECT_IO))
+            }
+            _ => Err(Errno::EISDIR),
+        }
+    }
+}
+
+impl Filesystem for S3Filesystem {
+    fn lookup(&self, _req: &Request, parent: INodeNo, name: &OsStr, reply: ReplyEntry) {
+        let children = match self.children(parent.0) {
+            Ok(children) => children,
+            Err(e) => return reply.error(e),
+        };
+        let found = children
+            .iter()
+            .find(|(child, _)| OsStr::new(child) == name)
+            .and_then(|(_, ino)| self.inodes.lock().unwrap().attrs.get(ino).copied());
+        match found {
+            Some(attr) => reply.entry(&ATTR_TTL, &attr, Generation(0)),
+            None => reply.error(Errno::ENOENT),
+        }
+    }
+
+    fn getattr(&self, _req: &Request, ino: INodeNo, _fh: Option<FileHandle>, reply: ReplyAttr) {
+        match self.inodes.lock().unwrap().attrs.get(&ino.0) {
+            Some(attr) => reply.attr(&ATTR_TTL, attr),
+            None => reply.error(Errno::ENOENT),
+        }
+    }
+
+    fn open(&self, _req: &Request, ino: INodeNo, _flags: OpenFlags, reply: ReplyOpen) {
+        match self.open_file(ino.0) {
+            Ok((file, flags)) => {
+                let fh = self.next_handle.fetch_add(1, Ordering::Relaxed);
+                self.handles.lock().unwrap().insert(fh, file);
+                reply.opened(FileHandle(fh), flags);
+            }
+            Err(e) => reply.error(e),
+        }
+    }
+
+    fn read(
+        &self,
+        _req: &Request,
+        _ino: INodeNo,
+        fh: FileHandle,
+        offset: u64,
+        size: u32,
+        _flags: OpenFlags,
+        _lock_owner: Option<LockOwner>,
+        reply: ReplyData,
+    ) {
+        let range = {
+            let handles = self.handles.lock().unwrap();
+            match handles.get(&fh.0) {
+                None => return reply.error(Errno::EBADF),
+                Some(OpenFile::Bytes(bytes)) => {
+                    let start = (offset as usize).min(bytes.len());
+                    let end = (start + size as usize).min(bytes.len());
+                    return reply.data(&bytes[start..end]);
+                }
+                Some(OpenFile::Object {
+                    bucket,
+                    key,
+                    size: object_size,
+                    ..
+                }) => {
+                    let length = (size as u64).min(object_size.saturating_sub(offset));
+                    (bucket.clone(), key.clone(), length)
+                }
+            }
+        };
+        let (bucket, key, length) = range;
+        if length == 0 {
+            return reply.data(&[]);
+        }
+        match self.runtime.block_on(
+            self.session
+                .client()
+                .get_object_range(&bucket, &key, offset, length),
+        ) {
+            Ok(bytes) => reply.data(&bytes),
+            Err(e) => {
+                tracing::debug!("fuse: reading s3://{bucket}/{key} failed: {e:#}");
+                reply.error(Errno::EIO)
+            }
+        }
+    }
+
+    fn release(
+        &self,
+        _req: &Request,
+        _ino: INodeNo,
+        fh: FileHandle,
+        _flags: OpenFlags,
+        _lock_owner: Option<LockOwner>,
+        _flush: bool,
+        reply: ReplyEmpty,
+    ) {
+        self.handles.lock().unwrap().remove(&fh.0);
+        reply.ok();
+    }
+
+    fn readdir(
+        &self,
+        _req: &Request,
+        ino: INodeNo,
+        _fh: FileHandle,
+        offset: u64,
+        mut reply: ReplyDirectory,
+    ) {
+        let children = match self.children(ino.0) {
+            Ok(children) => children,
+            Err(e) => return reply.error(e),
+        };
+        let kinds: Vec<FileType> = {
+            let inodes = self.inodes.lock().unwrap();
+            children
+                .iter()
+                .map(|(_, child)| {
+                    inodes
+                        .attrs
+                        .get(child)
+                        .map_or(FileType::RegularFile, |a| a.kind)
+                })
+                .collect()
+        };
+        let entries = [
+            (ino.0, FileType::Directory, "."),
+            (ino.0, FileType::Directory, ".."),
+        ]
+        .into_iter()
+        .chain(
+            children
+                .iter()
+                .zip(kinds)
+                .map(|((name, child), kind)| (*child, kind, name.as_str())),
+        );
+        for (i, (child, kind, name)) in entries.enumerate().skip(offset as usize) {
+            // The offset given is where the next readdir call resumes
+            if reply.add(INodeNo(child), (i + 1) as u64, kind, name) {
+                break;
+            }
+        }
+        reply.ok();
+    }
+}
+
+/// How a listed entry appears in the mount; archives that can be opened are directories
+fn file_type(entry: &Entry) -> (FileType, u64) {
+    match entry.kind {
+        EntryKind::Bucket | EntryKind::Directory => (FileType::Directory, 0),
+        EntryKind::File if format_for_name(&entry.name).is_some_and(|f| f.is_navigable()) => {
+            (FileType::Directory, 0)
+        }
+        EntryKind::File => (FileType::RegularFile, entry.size.unwrap_or(0)),
+    }
+}
+
+/// S3 times are RFC 3339; archive entry times are "2024-01-31 12:00:00" in UTC
+fn parse_time(text: &str) -> Option<SystemTime> {
+    let time = chrono::DateTime::parse_from_rfc3339(text)
+        .map(|t| t.to_utc())
+        .or_else(|_| {
+            chrono::NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S").map(|t| t.and_utc())
+        })
+        .ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(u64::try_from(time.timestamp()).ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, kind: EntryKind, size: Option<u64>) -> Entry {
+        Entry {
+            name: name.to_string(),
+            path: format!("/bucket/{name}"),
+            kind,
+            size,
+            modified: None,
+        }
+    }
+
+    #[test]
+    fn test_file_type_archives_are_directories() {
+        assert_eq!(
+            file_type(&entry("logs.tar.gz", EntryKind::File, Some(1024))),
+            (FileType::Directory, 0)
+        );
+        assert_eq!(
+            file_type(&entry("notes.txt", EntryKind::File, Some(12))),
+            (FileType::RegularFile, 12)
+        );
+        assert_eq!(
+            file_type(&entry("data", EntryKind::Directory, None)),
+            (FileType::Directory, 0)
+        );
+    }
+
+    #[test]
+    fn test_parse_time() {
+        let expected = UNIX_EPOCH + Duration::from_secs(1_706_702_400);
+        assert_eq!(parse_time("2024-01-31T12:00:00Z"), Some(expected));
+        assert_eq!(parse_time("2024-01-31 12:00:00"), Some(expected));
+        assert_eq!(parse_time("yesterday"), None);
+    }
+}
@@ -1,7 +1,17 @@
 pub mod archive;
 pub mod cache;
+pub mod config;
+#[cfg(all(feature = "fuse", unix))]
+pub mod fuse;
+pub mod logging;
 pub mod providers;
 pub mod s3;
+pub mod serve;
+pub mod session;
 pub mod shell;
+#[cfg(feature = "tui")]
+pub mod tui;
 pub mod ui;
 pub mod vfs;
+
+pub use session::Session;
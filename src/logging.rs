@@ -0,0 +1,71 @@
+//! Debug logging with `tracing`.
+//!
+//! Nothing is logged unless `RUST_LOG` is set or `--verbose` is given. `-v` logs s3sh's
+//! own events (requests, ranges, cache hits, timings); `-vv` adds the AWS SDK's.
+//! Instrumented functions log their duration when they return.
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::sync::Mutex;
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
+
+/// Filter used when RUST_LOG isn't set, by number of -v flags
+fn default_filter(verbosity: u8) -> &'static str {
+    match verbosity {
+        0 => "off",
+        1 => "s3sh=debug",
+        2 => "s3sh=trace,aws_sdk_s3=debug,aws_smithy_runtime=debug",
+        _ => "trace",
+    }
+}
+
+/// Install the global subscriber, writing to stderr or appending to `log_file`
+/// RUST_LOG (e.g. `RUST_LOG=s3sh::s3=trace`) takes precedence over `verbosity`;
+/// a log file on its own logs at `-v` level.
+pub fn init(verbosity: u8, log_file: Option<&Path>) -> Result<()> {
+    let verbosity = if log_file.is_some() {
+        verbosity.max(1)
+    } else {
+        verbosity
+    };
+    let filter = match std::env::var("RUST_LOG") {
+        Ok(directives) if !directives.is_empty() => EnvFilter::try_new(&directives)
+            .with_context(|| format!("Invalid RUST_LOG: {directives}"))?,
+        _ => EnvFilter::new(default_filter(verbosity)),
+    };
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(FmtSpan::CLOSE);
+
+    match log_file {
+        Some(path) => {
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .with_context(|| format!("Cannot open log file {}", path.display()))?;
+            builder
+                .with_ansi(false)
+                .with_writer(Mutex::new(file))
+                .init();
+        }
+        None => builder.with_writer(std::io::stderr).init(),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_filter() {
+        assert_eq!(default_filter(0), "off");
+        assert_eq!(default_filter(1), "s3sh=debug");
+        assert!(default_filter(2).contains("aws_sdk_s3=debug"));
+        for verbosity in 0..4 {
+            assert!(EnvFilter::try_new(default_filter(verbosity)).is_ok());
+        }
+    }
+}
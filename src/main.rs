@@ -1,32 +1,143 @@
 use anyhow::Result;
-use clap::Parser;
+use clap::{CommandFactory, Parser};
 use colored::*;
 use rustyline::Editor;
+use rustyline::config::Configurer;
 use rustyline::error::ReadlineError;
+use std::io::Write;
 use std::sync::Arc;
 
-use s3sh::{providers, s3, shell};
+use s3sh::{config, providers, s3, shell, ui};
 
 #[derive(Parser, Debug)]
 #[command(name = "s3sh")]
 #[command(about = "The S3 Shell - Navigate S3 buckets like a Unix shell", long_about = None)]
 struct Args {
-    /// S3 provider to use (aws, sourcecoop)
-    #[arg(short, long, default_value = "aws")]
-    provider: String,
+    /// S3 provider to use [default: aws, or `provider` from config.toml]
+    #[arg(short, long, value_parser = provider_names())]
+    provider: Option<String>,
+
+    /// Region to use, overriding the provider's and config.toml's default
+    #[arg(long)]
+    region: Option<String>,
+
+    /// Custom S3 endpoint (MinIO, LocalStack, ...); implies path-style addressing
+    #[arg(long, value_name = "URL")]
+    endpoint_url: Option<String>,
+
+    /// Send unsigned requests (for public buckets)
+    #[arg(long)]
+    anonymous: bool,
+
+    /// Named AWS profile to load credentials and region from
+    #[arg(long, conflicts_with = "anonymous")]
+    profile: Option<String>,
+
+    /// Disable colored output
+    #[arg(long)]
+    no_color: bool,
 
     /// List available providers and exit
     #[arg(long)]
     list_providers: bool,
 
-    /// S3 URL to start in (e.g. s3://bucket/prefix)
-    #[arg(value_name = "S3_URL")]
+    /// Skip confirmation prompts (useful for scripts)
+    #[arg(short = 'y', long)]
+    yes: bool,
+
+    /// Refuse uploads, deletes, and other changes to S3 (same as `set readonly on`)
+    #[arg(long)]
+    read_only: bool,
+
+    /// Log debug information to stderr (-vv includes the AWS SDK); RUST_LOG overrides
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Append debug logs to this file instead of stderr
+    #[arg(long, value_name = "PATH")]
+    log_file: Option<std::path::PathBuf>,
+
+    /// Run a single command line (pipes allowed) and exit with its status
+    #[arg(short = 'c', value_name = "COMMAND")]
+    command: Option<String>,
+
+    /// Browse a built-in in-memory sandbox instead of a provider (no credentials needed)
+    #[cfg(feature = "test-util")]
+    #[arg(long)]
+    demo: bool,
+
+    /// Browse in a full-screen, two-pane terminal UI instead of the prompt
+    #[cfg(feature = "tui")]
+    #[arg(long, conflicts_with = "command")]
+    tui: bool,
+
+    /// Location to start in (s3://bucket/prefix, an archive, or an HTTP(S) archive URL)
+    #[arg(value_name = "URL")]
     url: Option<String>,
+
+    #[command(subcommand)]
+    subcommand: Option<Subcommand>,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Subcommand {
+    /// Print a completion script for s3sh's flags and subcommands
+    /// e.g. `s3sh completions bash > ~/.local/share/bash-completion/completions/s3sh`
+    Completions {
+        /// Shell to generate the script for
+        shell: clap_complete::Shell,
+    },
+    /// Serve a read-only web UI for browsing and previewing files until Ctrl-C
+    Serve {
+        /// Port to listen on
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+        /// Address to listen on; use 0.0.0.0 to share with other machines
+        #[arg(long, default_value = "127.0.0.1")]
+        bind: std::net::IpAddr,
+        /// Location to serve (s3://bucket/prefix or an archive) [default: all buckets]
+        #[arg(value_name = "PATH")]
+        path: Option<String>,
+    },
+    /// Mount the virtual filesystem read-only with FUSE until Ctrl-C
+    #[cfg(all(feature = "fuse", unix))]
+    Mount {
+        /// Empty directory to mount on
+        mountpoint: std::path::PathBuf,
+        /// Location to mount (s3://bucket/prefix or an archive) [default: all buckets]
+        #[arg(value_name = "PATH")]
+        path: Option<String>,
+    },
+}
+
+impl Args {
+    /// A client for the in-memory sandbox, when --demo asks for it
+    #[cfg(feature = "test-util")]
+    fn demo_client(&self) -> Option<s3::S3Client> {
+        self.demo.then(|| s3::MockS3Client::demo().client())
+    }
+
+    #[cfg(not(feature = "test-util"))]
+    fn demo_client(&self) -> Option<s3::S3Client> {
+        None
+    }
+
+    /// Whether s3sh runs its prompt (not a -c command, the TUI, or a subcommand)
+    fn interactive(&self) -> bool {
+        #[cfg(feature = "tui")]
+        if self.tui {
+            return false;
+        }
+        self.command.is_none() && self.subcommand.is_none()
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
+    if let Err(e) = s3sh::logging::init(args.verbose, args.log_file.as_deref()) {
+        eprintln!("{} {:#}", "Warning:".yellow().bold(), e);
+    }
 
     // Handle --list-providers
     if args.list_providers {
@@ -34,84 +145,168 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if let Some(Subcommand::Completions { shell }) = args.subcommand {
+        // Buffered so a closed pipe (e.g. `| head`) isn't a panic
+        let mut script = Vec::new();
+        clap_complete::generate(shell, &mut Args::command(), "s3sh", &mut script);
+        let _ = std::io::stdout().write_all(&script);
+        return Ok(());
+    }
+
+    // Load user configuration; a broken file shouldn't keep the shell from starting
+    let config = config::Config::load().unwrap_or_else(|e| {
+        eprintln!("{} {:#}", "Warning:".yellow().bold(), e);
+        config::Config::default()
+    });
+    if args.no_color {
+        ui::disable_color();
+    } else {
+        ui::set_color(config.color());
+    }
+
     // Initialize provider registry
     let registry = providers::ProviderRegistry::new();
 
-    // Get the requested provider
+    // Get the requested provider (command line, then config file, then AWS)
+    let provider_name = args
+        .provider
+        .clone()
+        .or_else(|| config.provider().map(String::from))
+        .unwrap_or_else(|| "aws".to_string());
     let provider = registry
-        .get(&args.provider)
-        .ok_or_else(|| anyhow::anyhow!("Unknown provider: {}", args.provider))?;
+        .get(&provider_name)
+        .ok_or_else(|| anyhow::anyhow!("Unknown provider: {provider_name}"))?;
 
     // Create provider configuration first to check for custom endpoints
-    let provider_config = provider.build_config().await?;
-
-    // Print welcome message with provider info
-    println!("{}", "=".repeat(60).cyan());
-    println!("{}", "  s3sh - The S3 Shell".bold().cyan());
-    println!("{}", "  Navigate S3 buckets like a Unix shell".cyan());
-    println!("{}", "=".repeat(60).cyan());
-    println!(
-        "Provider: {} ({})",
-        provider.name().bold(),
-        provider.description()
-    );
-    if let Some(endpoint) = &provider_config.endpoint_url {
-        println!("Endpoint: {}", endpoint.bold());
+    let mut provider_config = provider.build_config().await?;
+    if provider_config.default_region.is_none() {
+        provider_config.default_region = config.region().map(String::from);
     }
-    println!();
-    println!("Type 'help' for available commands or 'exit' to quit");
-    println!();
+    config.apply_to_provider(&mut provider_config);
+    apply_overrides(&args, &mut provider_config);
 
-    // Create S3 client from provider
-    let (client, region, disable_cross_region) =
-        match providers::create_s3_client(provider_config).await {
-            Ok(result) => result,
-            Err(e) => {
-                eprintln!(
-                    "{} Failed to initialize S3 client: {}",
-                    "Error:".red().bold(),
-                    e
-                );
-                if args.provider == "aws" {
-                    eprintln!("Make sure you have valid AWS credentials configured.");
-                }
-                std::process::exit(1);
-            }
-        };
+    let demo_client = args.demo_client();
 
-    // Wrap client in S3Client wrapper
-    let s3_client = Arc::new(s3::S3Client::from_client_with_options(
-        client,
-        region,
-        disable_cross_region,
-    ));
+    // Print welcome message with provider info (not in -c mode, where stdout is the output)
+    if args.interactive() {
+        println!("{}", "=".repeat(60).cyan());
+        println!("{}", "  s3sh - The S3 Shell".bold().cyan());
+        println!("{}", "  Navigate S3 buckets like a Unix shell".cyan());
+        println!("{}", "=".repeat(60).cyan());
+        if demo_client.is_some() {
+            println!(
+                "Provider: {} (in-memory sandbox; try `cd demo-bucket`)",
+                "demo".bold()
+            );
+        } else {
+            println!(
+                "Provider: {} ({})",
+                provider.name().bold(),
+                provider.description()
+            );
+        }
+        if demo_client.is_none()
+            && let Some(endpoint) = &provider_config.endpoint_url
+        {
+            println!("Endpoint: {}", endpoint.bold());
+        }
+        println!();
+        println!("Type 'help' for available commands or 'exit' to quit");
+        println!();
+    }
+
+    // Create S3 client from provider (or the sandbox for --demo)
+    let s3_client = match demo_client {
+        Some(client) => Ok(client),
+        None => s3::S3Client::from_provider(provider.name(), provider_config).await,
+    };
+    let s3_client = match s3_client {
+        Ok(client) => Arc::new(client),
+        Err(e) => {
+            eprintln!(
+                "{} Failed to initialize S3 client: {}",
+                "Error:".red().bold(),
+                e
+            );
+            if provider_name == "aws" {
+                eprintln!("Make sure you have valid AWS credentials configured.");
+            }
+            std::process::exit(1);
+        }
+    };
 
     // Initialize shell state with the client
     let mut state = shell::ShellState::with_client(s3_client).await?;
+    state.set_config(config);
+    if let Err(e) = state.mount_buckets().await {
+        eprintln!("{} {:#}", "Warning:".yellow().bold(), e);
+    }
+    if args.yes {
+        state.settings_mut().confirm = false;
+    }
+    if args.read_only {
+        state.settings_mut().read_only = true;
+    }
 
-    // Navigate to initial URL if provided
-    if let Some(url) = &args.url {
-        let path = url
-            .strip_prefix("s3://")
-            .unwrap_or(url)
-            .trim_end_matches('/');
-        if !path.is_empty() {
-            let cd_path = format!("/{path}");
-            if let Err(e) = state.execute(&format!("cd {cd_path}")).await {
-                eprintln!(
-                    "{} Failed to navigate to {}: {}",
-                    "Error:".red().bold(),
-                    url,
-                    e
-                );
-                std::process::exit(1);
-            }
+    match &args.subcommand {
+        Some(Subcommand::Completions { .. }) | None => {}
+        Some(Subcommand::Serve { port, bind, path }) => {
+            return serve(&state, (*bind, *port).into(), path.as_deref()).await;
+        }
+        #[cfg(all(feature = "fuse", unix))]
+        Some(Subcommand::Mount { mountpoint, path }) => {
+            return mount(&state, mountpoint, path.as_deref()).await;
         }
     }
 
+    #[cfg(feature = "tui")]
+    if args.tui {
+        let mut session = s3sh::Session::new(Arc::clone(state.s3_client()));
+        if let Some(cd_path) = args.url.as_deref().and_then(start_path) {
+            session.cd(&cd_path).await?;
+        }
+        return s3sh::tui::run(session).await;
+    }
+
+    // Navigate to initial URL if provided (cd resolves prefixes and archives)
+    if let Some(url) = &args.url
+        && let Some(cd_path) = start_path(url)
+        && let Err(e) = state
+            .run_command("cd", &[cd_path], &mut shell::Output::stdout())
+            .await
+    {
+        eprintln!(
+            "{} Failed to navigate to {}: {}",
+            "Error:".red().bold(),
+            url,
+            e
+        );
+        std::process::exit(1);
+    }
+
+    // Non-interactive mode: run one line and exit with its status (`$?` after the line;
+    // a failing pipeline such as grep with no matches fails the line like sh would)
+    if let Some(line) = &args.command {
+        if let Err(e) = state.execute(line).await
+            && e.to_string() != "exit"
+        {
+            eprintln!("{} {}", "Error:".red().bold(), e);
+        }
+        let code = state.last_status();
+        // Output piped to e.g. `head` may already be closed; that's not a failure
+        let _ = std::io::stdout().flush();
+        std::process::exit(code);
+    }
+
     // Create readline editor with tab completion
     let completer = shell::ShellCompleter::new(state.completion_cache().clone());
-    let mut rl = Editor::new()?;
+    let history = state.history();
+    let mut rl = Editor::with_config(
+        rustyline::Config::builder()
+            .max_history_size(history.max_size())?
+            .history_ignore_dups(history.ignore_dups())?
+            .build(),
+    )?;
     rl.set_helper(Some(completer));
 
     // Load history if available
@@ -123,16 +318,69 @@ async fn main() -> Result<()> {
     if let Some(path) = &history_file {
         let _ = rl.load_history(path);
     }
+    for entry in rl.history().iter() {
+        state.history_mut().add(entry);
+    }
 
     // REPL loop
     loop {
+        report_finished_jobs(&mut state);
+        // `config history_size` and `config history_dedup` apply without a restart
+        let _ = rl.set_max_history_size(state.history().max_size());
+        let _ = rl.set_history_ignore_dups(state.history().ignore_dups());
         let prompt = state.prompt();
 
         match rl.readline(&prompt) {
             Ok(line) => {
+                // !!, !N and friends are replaced before the line is recorded, like bash
+                let line = match state.history().expand(&line) {
+                    Ok(Some(expanded)) => {
+                        println!("{expanded}");
+                        expanded
+                    }
+                    Ok(None) => line,
+                    Err(e) => {
+                        eprintln!("{} {}", "Error:".red().bold(), e);
+                        continue;
+                    }
+                };
                 let _ = rl.add_history_entry(line.as_str());
+                state.history_mut().add(&line);
+                // Appended as each line is entered, like bash's histappend, so concurrent
+                // sessions don't overwrite each other and a crash loses nothing
+                if let Some(path) = &history_file {
+                    let _ = rl.append_history(path);
+                }
 
-                match state.execute(&line).await {
+                let Some(mut result) = execute_interruptible(&mut state, &line).await else {
+                    // The terminal has already echoed ^C; `$?` reports 128 + SIGINT like sh
+                    state.set_last_status(130);
+                    println!();
+                    continue;
+                };
+
+                // Offer to pick up refreshed credentials instead of restarting the shell
+                if let Err(e) = &result
+                    && s3::is_expired_credentials_error(e)
+                {
+                    eprintln!("{} {}", "Error:".red().bold(), e);
+                    if ui::confirm(
+                        "Credentials expired — reload? (runs SSO login / re-reads credentials file)",
+                    )
+                    .unwrap_or(false)
+                    {
+                        result = match state.reload_credentials().await {
+                            Ok(()) => execute_interruptible(&mut state, &line)
+                                .await
+                                .unwrap_or_else(|| Err(anyhow::anyhow!("Interrupted"))),
+                            Err(e) => Err(e),
+                        };
+                    } else {
+                        continue;
+                    }
+                }
+
+                match result {
                     Ok(_) => {}
                     Err(e) => {
                         if e.to_string() == "exit" {
@@ -157,15 +405,129 @@ async fn main() -> Result<()> {
         }
     }
 
-    // Save history
-    if let Some(path) = &history_file {
-        let _ = rl.save_history(path);
+    println!("Goodbye!");
+    Ok(())
+}
+
+/// Run a command line, abandoning it if Ctrl-C arrives first
+/// Dropping the future cancels the command at its next await point: its S3 requests are
+/// dropped, the shell process after a `|` is killed, and a `> FILE` keeps what was written
+/// so far. Background jobs keep running. Caches are only updated between awaits, so none
+/// is left half-written.
+async fn execute_interruptible(state: &mut shell::ShellState, line: &str) -> Option<Result<()>> {
+    tokio::select! {
+        result = state.execute(line) => Some(result),
+        _ = tokio::signal::ctrl_c() => None,
     }
+}
 
-    println!("Goodbye!");
+/// Print the output of background jobs that finished since the last prompt, like sh's
+/// "Done" notices
+fn report_finished_jobs(state: &mut shell::ShellState) {
+    for (job, result) in state.jobs_mut().take_finished() {
+        if let Ok(output) = job.output() {
+            let _ = std::io::stdout().write_all(&output);
+        }
+        match result {
+            Ok(()) => println!("[{}] Done  {}", job.id, job.line),
+            Err(e) => println!("[{}] {}  {}: {e}", job.id, "Exit 1".red(), job.line),
+        }
+    }
+}
+
+/// Apply connection flags from the command line on top of the provider's configuration
+fn apply_overrides(args: &Args, config: &mut providers::ProviderConfig) {
+    if let Some(region) = &args.region {
+        config.default_region = Some(region.clone());
+    }
+    if let Some(endpoint) = &args.endpoint_url {
+        // Same treatment as AWS_ENDPOINT_URL: S3-compatible services rarely support
+        // virtual-hosted buckets or region discovery
+        config.endpoint_url = Some(endpoint.clone());
+        config.force_path_style = true;
+        config.disable_cross_region = true;
+    }
+    if args.anonymous {
+        config.anonymous = true;
+        config.profile = None;
+        config.static_credentials = None;
+    }
+    if let Some(profile) = &args.profile {
+        config.profile = Some(profile.clone());
+    }
+}
+
+/// Serve the web UI for `path` until Ctrl-C
+async fn serve(
+    state: &shell::ShellState,
+    addr: std::net::SocketAddr,
+    path: Option<&str>,
+) -> Result<()> {
+    let mut session = s3sh::Session::new(Arc::clone(state.s3_client()));
+    if let Some(cd_path) = path.and_then(start_path) {
+        session.cd(&cd_path).await?;
+    }
+    let source = session.cwd().path();
+
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to listen on {addr}: {e}"))?;
+    eprintln!(
+        "Serving {} at {} (read-only); Ctrl-C to stop",
+        source.bold(),
+        format!("http://{addr}/").cyan()
+    );
+    tokio::select! {
+        result = s3sh::serve::serve(session, listener) => result,
+        _ = tokio::signal::ctrl_c() => Ok(()),
+    }
+}
+
+/// Serve a read-only FUSE mount of `path` until Ctrl-C, then unmount
+#[cfg(all(feature = "fuse", unix))]
+async fn mount(
+    state: &shell::ShellState,
+    mountpoint: &std::path::Path,
+    path: Option<&str>,
+) -> Result<()> {
+    let mut session = s3sh::Session::new(Arc::clone(state.s3_client()));
+    if let Some(cd_path) = path.and_then(start_path) {
+        session.cd(&cd_path).await?;
+    }
+    let source = session.cwd().path();
+
+    let mount = s3sh::fuse::mount(session, mountpoint)?;
+    eprintln!(
+        "Mounted {} at {} (read-only); Ctrl-C to unmount",
+        source.bold(),
+        mountpoint.display()
+    );
+    tokio::signal::ctrl_c().await?;
+    mount.umount_and_join()?;
     Ok(())
 }
 
+/// Turn a start location (s3://bucket/prefix, bucket/prefix, or an HTTP(S) URL) into a cd target
+fn start_path(url: &str) -> Option<String> {
+    if url.starts_with("https://") || url.starts_with("http://") {
+        return Some(url.to_string());
+    }
+
+    let path = url.strip_prefix("s3://").unwrap_or(url).trim_matches('/');
+    (!path.is_empty()).then(|| format!("/{path}"))
+}
+
+/// Accepts the registered provider names, which shell completion offers too
+fn provider_names() -> clap::builder::PossibleValuesParser {
+    let registry = providers::ProviderRegistry::new();
+    clap::builder::PossibleValuesParser::new(
+        registry
+            .list()
+            .into_iter()
+            .map(|name| clap::builder::PossibleValue::new(name.to_string())),
+    )
+}
+
 fn print_available_providers() {
     let registry = providers::ProviderRegistry::new();
     println!("Available S3 providers:");
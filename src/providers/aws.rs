@@ -1,5 +1,7 @@
-use super::{Provider, ProviderConfig};
+use super::{HttpOptions, Provider, ProviderConfig};
 use anyhow::Result;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
 
 /// AWS S3 provider (default)
 pub struct AwsProvider;
@@ -40,14 +42,114 @@ impl Provider for AwsProvider {
             // Disable cross-region support when using custom endpoints
             // as S3-compatible services may not support region discovery
             disable_cross_region: has_custom_endpoint,
+            profile: None,
+            static_credentials: None,
+            relaxed_checksums: false,
+            use_dualstack: false,
+            use_fips: false,
+            http: HttpOptions::default(),
         })
     }
 }
 
+/// Path of the shared AWS config file, honoring AWS_CONFIG_FILE like the SDK does
+fn config_file_path() -> Option<PathBuf> {
+    std::env::var_os("AWS_CONFIG_FILE")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".aws").join("config")))
+}
+
+/// Path of the shared credentials file, honoring AWS_SHARED_CREDENTIALS_FILE
+fn credentials_file_path() -> Option<PathBuf> {
+    std::env::var_os("AWS_SHARED_CREDENTIALS_FILE")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".aws").join("credentials")))
+}
+
+/// List profile names from the shared AWS config and credentials files
+pub fn list_profiles() -> Vec<String> {
+    let mut profiles = BTreeSet::new();
+    if let Some(contents) = config_file_path().and_then(|p| std::fs::read_to_string(p).ok()) {
+        profiles.extend(parse_profile_names(&contents, true));
+    }
+    if let Some(contents) = credentials_file_path().and_then(|p| std::fs::read_to_string(p).ok()) {
+        profiles.extend(parse_profile_names(&contents, false));
+    }
+
+    profiles.into_iter().collect()
+}
+
+/// Check whether a profile signs in through IAM Identity Center (SSO)
+pub fn profile_uses_sso(profile: &str) -> bool {
+    config_file_path()
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .is_some_and(|contents| section_uses_sso(&contents, profile))
+}
+
+/// Check a config file's section for a profile for sso_session/sso_start_url keys
+fn section_uses_sso(contents: &str, profile: &str) -> bool {
+    let mut in_profile = false;
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_profile =
+                parse_profile_names(line, true).first().map(String::as_str) == Some(profile);
+            continue;
+        }
+        if in_profile {
+            let key = line.split('=').next().unwrap_or("").trim();
+            if key == "sso_session" || key == "sso_start_url" {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Extract profile names from INI section headers
+/// The config file uses `[profile NAME]` (except `[default]`); the credentials file uses `[NAME]`
+fn parse_profile_names(contents: &str, is_config: bool) -> Vec<String> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let section = line.trim().strip_prefix('[')?.strip_suffix(']')?.trim();
+            if !is_config || section == "default" {
+                return Some(section.to_string());
+            }
+            // Skip other config sections like [sso-session ...] and [services ...]
+            section
+                .strip_prefix("profile")
+                .filter(|rest| rest.starts_with(char::is_whitespace))
+                .map(|rest| rest.trim().to_string())
+        })
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_profile_names() {
+        let config = "[default]\nregion = us-west-2\n\n[profile dev]\nsso_session = corp\n\n\
+                      [sso-session corp]\nsso_region = us-east-1\n[profile  prod ]\n";
+        assert_eq!(
+            parse_profile_names(config, true),
+            vec!["default", "dev", "prod"]
+        );
+
+        assert!(section_uses_sso(config, "dev"));
+        assert!(!section_uses_sso(config, "default"));
+        assert!(!section_uses_sso(config, "prod"));
+
+        let credentials = "[default]\naws_access_key_id = x\n[ci]\naws_access_key_id = y\n";
+        assert_eq!(
+            parse_profile_names(credentials, false),
+            vec!["default", "ci"]
+        );
+    }
+
     #[tokio::test]
     async fn test_aws_provider_config_default() {
         // Ensure AWS_ENDPOINT_URL is not set for this test
@@ -61,10 +163,10 @@ mod tests {
 
         let config = provider.build_config().await.unwrap();
         assert_eq!(config.endpoint_url, None);
-        assert_eq!(config.anonymous, false);
-        assert_eq!(config.force_path_style, false);
+        assert!(!config.anonymous);
+        assert!(!config.force_path_style);
         assert_eq!(config.default_region, None);
-        assert_eq!(config.disable_cross_region, false);
+        assert!(!config.disable_cross_region);
     }
 
     #[tokio::test]
@@ -81,8 +183,8 @@ mod tests {
             config.endpoint_url,
             Some("https://play.minio.io:9000".to_string())
         );
-        assert_eq!(config.force_path_style, true);
-        assert_eq!(config.disable_cross_region, true);
+        assert!(config.force_path_style);
+        assert!(config.disable_cross_region);
 
         // Clean up
         unsafe {
@@ -0,0 +1,182 @@
+use super::{HttpOptions, Provider, ProviderConfig};
+use anyhow::{Result, anyhow};
+
+/// S3-compatible vendors with a built-in preset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Vendor {
+    /// Cloudflare R2
+    R2,
+    /// Backblaze B2
+    B2,
+    /// Wasabi
+    Wasabi,
+    /// DigitalOcean Spaces
+    Spaces,
+}
+
+impl Vendor {
+    /// Environment variable holding the account ID or region the endpoint is built from
+    fn parameter_var(self) -> &'static str {
+        match self {
+            Vendor::R2 => "R2_ACCOUNT_ID",
+            Vendor::B2 => "B2_REGION",
+            Vendor::Wasabi => "WASABI_REGION",
+            Vendor::Spaces => "SPACES_REGION",
+        }
+    }
+}
+
+/// Preset for an S3-compatible vendor (endpoint pattern, addressing, and region quirks)
+/// Credentials come from the standard AWS chain (AWS_ACCESS_KEY_ID/AWS_SECRET_ACCESS_KEY or a profile)
+pub struct CompatibleProvider {
+    vendor: Vendor,
+}
+
+impl CompatibleProvider {
+    pub fn new(vendor: Vendor) -> Self {
+        Self { vendor }
+    }
+
+    /// Build the configuration from the vendor parameter (account ID or region)
+    fn config_with(&self, parameter: Option<&str>) -> Result<ProviderConfig> {
+        let parameter = parameter.map(str::trim).filter(|p| !p.is_empty());
+
+        // (endpoint, signing region)
+        let (endpoint, region) = match self.vendor {
+            Vendor::R2 => {
+                let account = parameter.ok_or_else(|| {
+                    anyhow!("Set R2_ACCOUNT_ID to your Cloudflare account ID to use R2")
+                })?;
+                // R2 ignores the region but the signer needs one; "auto" is what Cloudflare documents
+                (
+                    format!("https://{account}.r2.cloudflarestorage.com"),
+                    "auto".to_string(),
+                )
+            }
+            Vendor::B2 => {
+                let region = parameter.ok_or_else(|| {
+                    anyhow!("Set B2_REGION to your bucket's region (e.g. us-west-004) to use B2")
+                })?;
+                (
+                    format!("https://s3.{region}.backblazeb2.com"),
+                    region.to_string(),
+                )
+            }
+            Vendor::Wasabi => {
+                let region = parameter.unwrap_or("us-east-1");
+                (
+                    format!("https://s3.{region}.wasabisys.com"),
+                    region.to_string(),
+                )
+            }
+            Vendor::Spaces => {
+                // Spaces regions are datacenter slugs (nyc3, fra1, ...); requests are signed
+                // for us-east-1 regardless of the endpoint
+                let region = parameter.unwrap_or("nyc3");
+                (
+                    format!("https://{region}.digitaloceanspaces.com"),
+                    "us-east-1".to_string(),
+                )
+            }
+        };
+
+        Ok(ProviderConfig {
+            endpoint_url: Some(endpoint),
+            force_path_style: true,
+            anonymous: false,
+            default_region: Some(region),
+            disable_cross_region: true,
+            profile: None,
+            static_credentials: None,
+            relaxed_checksums: false,
+            use_dualstack: false,
+            use_fips: false,
+            http: HttpOptions::default(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Provider for CompatibleProvider {
+    fn name(&self) -> &str {
+        match self.vendor {
+            Vendor::R2 => "r2",
+            Vendor::B2 => "b2",
+            Vendor::Wasabi => "wasabi",
+            Vendor::Spaces => "spaces",
+        }
+    }
+
+    fn description(&self) -> &str {
+        match self.vendor {
+            Vendor::R2 => "Cloudflare R2 (needs R2_ACCOUNT_ID)",
+            Vendor::B2 => "Backblaze B2 (needs B2_REGION, e.g. us-west-004)",
+            Vendor::Wasabi => "Wasabi (WASABI_REGION, default us-east-1)",
+            Vendor::Spaces => "DigitalOcean Spaces (SPACES_REGION, default nyc3)",
+        }
+    }
+
+    async fn build_config(&self) -> Result<ProviderConfig> {
+        let parameter = std::env::var(self.vendor.parameter_var()).ok();
+        self.config_with(parameter.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compatible_provider_configs() {
+        let r2 = CompatibleProvider::new(Vendor::R2)
+            .config_with(Some("abc123"))
+            .unwrap();
+        assert_eq!(
+            r2.endpoint_url.as_deref(),
+            Some("https://abc123.r2.cloudflarestorage.com")
+        );
+        assert_eq!(r2.default_region.as_deref(), Some("auto"));
+        assert!(r2.force_path_style);
+        assert!(r2.disable_cross_region);
+        assert!(!r2.anonymous);
+
+        let b2 = CompatibleProvider::new(Vendor::B2)
+            .config_with(Some("us-west-004"))
+            .unwrap();
+        assert_eq!(
+            b2.endpoint_url.as_deref(),
+            Some("https://s3.us-west-004.backblazeb2.com")
+        );
+
+        let wasabi = CompatibleProvider::new(Vendor::Wasabi)
+            .config_with(None)
+            .unwrap();
+        assert_eq!(
+            wasabi.endpoint_url.as_deref(),
+            Some("https://s3.us-east-1.wasabisys.com")
+        );
+
+        let spaces = CompatibleProvider::new(Vendor::Spaces)
+            .config_with(Some("fra1"))
+            .unwrap();
+        assert_eq!(
+            spaces.endpoint_url.as_deref(),
+            Some("https://fra1.digitaloceanspaces.com")
+        );
+        assert_eq!(spaces.default_region.as_deref(), Some("us-east-1"));
+    }
+
+    #[test]
+    fn test_compatible_provider_requires_parameter() {
+        assert!(
+            CompatibleProvider::new(Vendor::R2)
+                .config_with(None)
+                .is_err()
+        );
+        assert!(
+            CompatibleProvider::new(Vendor::B2)
+                .config_with(Some(" "))
+                .is_err()
+        );
+    }
+}
@@ -1,11 +1,18 @@
 mod aws;
+mod compatible;
+mod gcs;
 mod sourcecoop;
 
-pub use aws::AwsProvider;
+pub use aws::{AwsProvider, list_profiles, profile_uses_sso};
+pub use compatible::{CompatibleProvider, Vendor};
+pub use gcs::GcsProvider;
 pub use sourcecoop::SourceCoopProvider;
 
+pub use crate::s3::connection::HttpOptions;
+
 use anyhow::Result;
 use aws_sdk_s3::Client;
+use aws_smithy_runtime_api::client::http::SharedHttpClient;
 use std::collections::HashMap;
 
 /// Configuration for creating an S3 client
@@ -21,6 +28,19 @@ pub struct ProviderConfig {
     pub default_region: Option<String>,
     /// Disable cross-region bucket support (for custom endpoints that don't support it)
     pub disable_cross_region: bool,
+    /// Named AWS profile to load credentials and region from (None = default chain)
+    pub profile: Option<String>,
+    /// Fixed access key ID and secret to use instead of the credential chain (e.g. GCS HMAC keys)
+    pub static_credentials: Option<(String, String)>,
+    /// Only send and validate checksums when an operation requires them
+    /// (for services that reject the SDK's flexible-checksum headers)
+    pub relaxed_checksums: bool,
+    /// Use dual-stack (IPv4 and IPv6) endpoints
+    pub use_dualstack: bool,
+    /// Use FIPS 140-2 endpoints (e.g. for GovCloud)
+    pub use_fips: bool,
+    /// Timeouts, connection pooling, and the cap on requests in flight
+    pub http: HttpOptions,
 }
 
 /// Trait for S3 provider implementations
@@ -37,43 +57,114 @@ pub trait Provider: Send + Sync {
     async fn build_config(&self) -> Result<ProviderConfig>;
 }
 
-/// Factory function to create S3Client from provider configuration
-/// Returns (client, region, disable_cross_region)
-pub async fn create_s3_client(config: ProviderConfig) -> Result<(Client, String, bool)> {
-    let mut sdk_config = aws_config::defaults(aws_config::BehaviorVersion::latest());
+/// Load the AWS SDK configuration (credential chain, region) for a provider
+/// Every AWS client the shell creates goes through here so they agree on profile and credentials
+pub async fn load_sdk_config(config: &ProviderConfig) -> aws_config::SdkConfig {
+    let mut loader = aws_config::defaults(aws_config::BehaviorVersion::latest());
 
     // Handle anonymous access
     if config.anonymous {
-        sdk_config = sdk_config.no_credentials();
+        loader = loader.no_credentials();
+    }
+
+    if let Some(profile) = &config.profile {
+        loader = loader.profile_name(profile);
+    }
+
+    // Both pick among AWS's own endpoints, so a custom endpoint overrides them; left
+    // off, the SDK still honors AWS_USE_DUALSTACK_ENDPOINT and AWS_USE_FIPS_ENDPOINT
+    if config.endpoint_url.is_none() {
+        if config.use_dualstack {
+            loader = loader.use_dual_stack(true);
+        }
+        if config.use_fips {
+            loader = loader.use_fips(true);
+        }
+    }
+
+    // Fixed keys are kept while anonymous so `set anonymous off` can go back to them
+    if let Some((access_key_id, secret)) = config
+        .static_credentials
+        .as_ref()
+        .filter(|_| !config.anonymous)
+    {
+        loader = loader.credentials_provider(aws_sdk_s3::config::Credentials::new(
+            access_key_id,
+            secret,
+            None,
+            None,
+            "s3sh-static",
+        ));
     }
 
-    let base_config = sdk_config.load().await;
+    loader.load().await
+}
+
+/// Factory function to create S3Client from provider configuration
+/// Returns (client, region, disable_cross_region)
+pub async fn create_s3_client(config: ProviderConfig) -> Result<(Client, String, bool)> {
+    let base_config = load_sdk_config(&config).await;
 
     // Determine default region
     let default_region = config
         .default_region
+        .clone()
         .or_else(|| base_config.region().map(|r| r.as_ref().to_string()))
         .unwrap_or_else(|| "us-west-2".to_string());
 
-    // Build S3-specific config
-    let mut s3_config_builder = aws_sdk_s3::config::Builder::from(&base_config);
+    let s3_config = s3_config(
+        Some(&config),
+        &base_config,
+        &default_region,
+        config.http.http_client(),
+    );
+    let client = Client::from_conf(s3_config);
+
+    Ok((client, default_region, config.disable_cross_region))
+}
 
-    // Set the region explicitly to ensure provider-specified region is used
-    s3_config_builder =
-        s3_config_builder.region(aws_sdk_s3::config::Region::new(default_region.clone()));
+/// SDK settings for an S3 client in `region`, applying a provider's endpoint, timeouts,
+/// and checksum behavior if there is one
+/// Regional clients pass in the default client's HTTP client, so every region shares its
+/// connection pool and cap on requests in flight.
+pub fn s3_config(
+    config: Option<&ProviderConfig>,
+    base_config: &aws_config::SdkConfig,
+    region: &str,
+    http_client: Option<SharedHttpClient>,
+) -> aws_sdk_s3::Config {
+    let mut builder = aws_sdk_s3::config::Builder::from(base_config)
+        .region(aws_sdk_s3::config::Region::new(region.to_string()));
+    if let Some(http_client) = http_client {
+        builder = builder.http_client(http_client);
+    }
+    let Some(config) = config else {
+        return builder.build();
+    };
 
-    if let Some(endpoint) = config.endpoint_url {
-        s3_config_builder = s3_config_builder.endpoint_url(endpoint);
+    if let Some(endpoint) = &config.endpoint_url {
+        builder = builder.endpoint_url(endpoint);
     }
 
     if config.force_path_style {
-        s3_config_builder = s3_config_builder.force_path_style(true);
+        builder = builder.force_path_style(true);
     }
 
-    let s3_config = s3_config_builder.build();
-    let client = Client::from_conf(s3_config);
+    if let Some(timeouts) = config.http.timeout_config(base_config.timeout_config()) {
+        builder = builder.timeout_config(timeouts);
+    }
 
-    Ok((client, default_region, config.disable_cross_region))
+    if config.relaxed_checksums {
+        builder = builder
+            .request_checksum_calculation(
+                aws_sdk_s3::config::RequestChecksumCalculation::WhenRequired,
+            )
+            .response_checksum_validation(
+                aws_sdk_s3::config::ResponseChecksumValidation::WhenRequired,
+            );
+    }
+
+    builder.build()
 }
 
 /// Registry of available providers
@@ -91,6 +182,10 @@ impl ProviderRegistry {
         // Register built-in providers
         registry.register(Box::new(aws::AwsProvider::new()));
         registry.register(Box::new(sourcecoop::SourceCoopProvider::new()));
+        registry.register(Box::new(gcs::GcsProvider::new()));
+        for vendor in [Vendor::R2, Vendor::B2, Vendor::Wasabi, Vendor::Spaces] {
+            registry.register(Box::new(CompatibleProvider::new(vendor)));
+        }
 
         registry
     }
@@ -155,6 +250,12 @@ mod tests {
                 anonymous: false,
                 default_region: None,
                 disable_cross_region: false,
+                profile: None,
+                static_credentials: None,
+                relaxed_checksums: false,
+                use_dualstack: false,
+                use_fips: false,
+                http: HttpOptions::default(),
             })
         }
     }
@@ -167,6 +268,12 @@ mod tests {
             anonymous: false,
             default_region: Some("us-east-1".to_string()),
             disable_cross_region: false,
+            profile: None,
+            static_credentials: None,
+            relaxed_checksums: false,
+            use_dualstack: false,
+            use_fips: false,
+            http: HttpOptions::default(),
         };
 
         let result = create_s3_client(config).await;
@@ -174,7 +281,7 @@ mod tests {
 
         let (_client, region, disable_cross_region) = result.unwrap();
         assert_eq!(region, "us-east-1");
-        assert_eq!(disable_cross_region, false);
+        assert!(!disable_cross_region);
     }
 
     #[tokio::test]
@@ -185,6 +292,12 @@ mod tests {
             anonymous: true,
             default_region: Some("us-west-2".to_string()),
             disable_cross_region: false,
+            profile: None,
+            static_credentials: None,
+            relaxed_checksums: false,
+            use_dualstack: false,
+            use_fips: false,
+            http: HttpOptions::default(),
         };
 
         let result = create_s3_client(config).await;
@@ -192,7 +305,7 @@ mod tests {
 
         let (_, region, disable_cross_region) = result.unwrap();
         assert_eq!(region, "us-west-2");
-        assert_eq!(disable_cross_region, false);
+        assert!(!disable_cross_region);
     }
 
     #[tokio::test]
@@ -203,6 +316,12 @@ mod tests {
             anonymous: false,
             default_region: Some("custom-region".to_string()),
             disable_cross_region: false,
+            profile: None,
+            static_credentials: None,
+            relaxed_checksums: false,
+            use_dualstack: false,
+            use_fips: false,
+            http: HttpOptions::default(),
         };
 
         let result = create_s3_client(config).await;
@@ -220,6 +339,12 @@ mod tests {
             anonymous: true,
             default_region: Some("us-west-2".to_string()),
             disable_cross_region: true,
+            profile: None,
+            static_credentials: None,
+            relaxed_checksums: false,
+            use_dualstack: false,
+            use_fips: false,
+            http: HttpOptions::default(),
         };
 
         let result = create_s3_client(config).await;
@@ -227,7 +352,33 @@ mod tests {
 
         let (_, region, disable_cross_region) = result.unwrap();
         assert_eq!(region, "us-west-2");
-        assert_eq!(disable_cross_region, true);
+        assert!(disable_cross_region);
+    }
+
+    #[tokio::test]
+    async fn test_load_sdk_config_endpoint_variants() {
+        let mut config = ProviderConfig {
+            endpoint_url: None,
+            force_path_style: false,
+            anonymous: true,
+            default_region: Some("us-gov-west-1".to_string()),
+            disable_cross_region: false,
+            profile: None,
+            static_credentials: None,
+            relaxed_checksums: false,
+            use_dualstack: true,
+            use_fips: true,
+            http: HttpOptions::default(),
+        };
+        let sdk_config = load_sdk_config(&config).await;
+        assert_eq!(sdk_config.use_dual_stack(), Some(true));
+        assert_eq!(sdk_config.use_fips(), Some(true));
+
+        // A custom endpoint can't be combined with either
+        config.endpoint_url = Some("https://s3.custom.com".to_string());
+        let sdk_config = load_sdk_config(&config).await;
+        assert_ne!(sdk_config.use_dual_stack(), Some(true));
+        assert_ne!(sdk_config.use_fips(), Some(true));
     }
 
     #[tokio::test]
@@ -238,6 +389,12 @@ mod tests {
             anonymous: false,
             default_region: None, // No default region set
             disable_cross_region: false,
+            profile: None,
+            static_credentials: None,
+            relaxed_checksums: false,
+            use_dualstack: false,
+            use_fips: false,
+            http: HttpOptions::default(),
         };
 
         let result = create_s3_client(config).await;
@@ -248,6 +405,59 @@ mod tests {
         assert!(!region.is_empty());
     }
 
+    #[test]
+    fn test_s3_config_for_regional_clients() {
+        use aws_sdk_s3::config::{RequestChecksumCalculation, ResponseChecksumValidation};
+        use std::time::Duration;
+
+        let config = ProviderConfig {
+            endpoint_url: None,
+            force_path_style: true,
+            anonymous: true,
+            default_region: Some("us-east-1".to_string()),
+            disable_cross_region: false,
+            profile: None,
+            static_credentials: None,
+            relaxed_checksums: true,
+            use_dualstack: false,
+            use_fips: false,
+            http: HttpOptions {
+                read_timeout: Some(Duration::from_secs(7)),
+                max_concurrent_requests: Some(4),
+                ..HttpOptions::default()
+            },
+        };
+        let base_config = aws_config::SdkConfig::builder().build();
+        let default = s3_config(
+            Some(&config),
+            &base_config,
+            "us-east-1",
+            config.http.http_client(),
+        );
+
+        // A client for another region keeps the provider's settings and HTTP client
+        let regional = s3_config(
+            Some(&config),
+            &base_config,
+            "eu-west-1",
+            default.http_client(),
+        );
+        assert_eq!(regional.region().unwrap().as_ref(), "eu-west-1");
+        assert_eq!(
+            regional.timeout_config().unwrap().read_timeout(),
+            Some(Duration::from_secs(7))
+        );
+        assert_eq!(
+            regional.request_checksum_calculation(),
+            Some(&RequestChecksumCalculation::WhenRequired)
+        );
+        assert_eq!(
+            regional.response_checksum_validation(),
+            Some(&ResponseChecksumValidation::WhenRequired)
+        );
+        assert!(regional.http_client().is_some());
+    }
+
     #[test]
     fn test_provider_registry_new() {
         let registry = ProviderRegistry::new();
@@ -256,11 +466,15 @@ mod tests {
         assert!(registry.get("aws").is_some());
         assert!(registry.get("sourcecoop").is_some());
 
-        // Verify list contains both providers
+        // Verify list contains AWS, Source Coop, and the S3-compatible presets
         let providers = registry.list();
-        assert_eq!(providers.len(), 2);
+        assert_eq!(providers.len(), 7);
         assert!(providers.contains(&"aws"));
         assert!(providers.contains(&"sourcecoop"));
+        assert!(providers.contains(&"gcs"));
+        for preset in ["r2", "b2", "wasabi", "spaces"] {
+            assert!(providers.contains(&preset));
+        }
     }
 
     #[test]
@@ -332,6 +546,6 @@ mod tests {
         // Default should behave the same as new()
         assert!(registry.get("aws").is_some());
         assert!(registry.get("sourcecoop").is_some());
-        assert_eq!(registry.list().len(), 2);
+        assert_eq!(registry.list().len(), 7);
     }
 }
@@ -1,4 +1,4 @@
-use super::{Provider, ProviderConfig};
+use super::{HttpOptions, Provider, ProviderConfig};
 use anyhow::Result;
 
 /// Source Cooperative provider for public geospatial data
@@ -33,6 +33,12 @@ impl Provider for SourceCoopProvider {
             anonymous: true,
             default_region: Some("us-west-2".to_string()),
             disable_cross_region: true,
+            profile: None,
+            static_credentials: None,
+            relaxed_checksums: false,
+            use_dualstack: false,
+            use_fips: false,
+            http: HttpOptions::default(),
         })
     }
 }
@@ -55,9 +61,9 @@ mod tests {
             config.endpoint_url,
             Some("https://data.source.coop".to_string())
         );
-        assert_eq!(config.anonymous, true);
-        assert_eq!(config.force_path_style, true);
+        assert!(config.anonymous);
+        assert!(config.force_path_style);
         assert_eq!(config.default_region, Some("us-west-2".to_string()));
-        assert_eq!(config.disable_cross_region, true);
+        assert!(config.disable_cross_region);
     }
 }
@@ -1,11 +1,79 @@
 use anyhow::{Context, Result};
 use aws_sdk_s3::Client;
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use aws_sdk_s3::primitives::event_stream::EventReceiver;
+use aws_sdk_s3::types::error::SelectObjectContentEventStreamError;
+use aws_sdk_s3::types::{
+    BucketLocationConstraint, CompressionType, CreateBucketConfiguration, CsvInput, CsvOutput,
+    ExpressionType, FileHeaderInfo, InputSerialization, JsonInput, JsonOutput, JsonType,
+    OutputSerialization, ParquetInput, SelectObjectContentEventStream,
+};
 use bytes::Bytes;
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use futures::stream::{self, BoxStream, FuturesUnordered, StreamExt, TryStreamExt};
+use lru::LruCache;
+use std::collections::{BTreeMap, HashMap};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::Instant;
+use tracing::debug;
 
-use super::metrics::S3Metrics;
+use super::http::{HttpSource, is_http_origin};
+use super::inventory::InventoryStore;
+use super::listing::ListingCache;
+use super::metrics::{MetricsInterceptor, S3Metrics};
+use super::retry::{RetryPolicy, RetryPolicyClassifier, RetryPolicyInterceptor};
+use super::transfer::{BandwidthLimiter, TransferOptions};
+use super::walker::PrefixWalker;
+use crate::archive::ExtractLimits;
+use crate::providers::ProviderConfig;
+
+/// HEAD results kept for listed objects
+const HEAD_CACHE_SIZE: usize = 1000;
+
+/// HEAD requests in flight when fetching metadata for a listing
+const HEAD_CONCURRENCY: usize = 16;
+
+/// HEAD results by (bucket, key, ETag)
+type HeadCache = Mutex<LruCache<(String, String, String), ObjectMetadata>>;
+
+/// Largest object a single CopyObject can copy (5 GiB)
+const MAX_COPY_SIZE: u64 = 5 << 30;
+
+/// Set an object's content type, storage class, system headers, and encryption on a
+/// CopyObject (with REPLACE) or PutObject request, which would otherwise reset them
+macro_rules! carry_over_headers {
+    ($req:expr, $current:expr) => {{
+        let current: &ObjectMetadata = $current;
+        let headers = &current.headers;
+        let mut req = $req
+            .set_content_type(current.content_type.clone())
+            .set_content_encoding(headers.content_encoding.clone())
+            .set_cache_control(headers.cache_control.clone())
+            .set_content_disposition(headers.content_disposition.clone())
+            .set_content_language(headers.content_language.clone())
+            .set_expires(headers.expires.as_deref().and_then(|expires| {
+                aws_sdk_s3::primitives::DateTime::from_str(
+                    expires,
+                    aws_sdk_s3::primitives::DateTimeFormat::HttpDate,
+                )
+                .ok()
+            }))
+            .set_website_redirect_location(headers.website_redirect_location.clone())
+            .set_server_side_encryption(
+                headers
+                    .server_side_encryption
+                    .as_deref()
+                    .map(aws_sdk_s3::types::ServerSideEncryption::from),
+            )
+            .set_ssekms_key_id(headers.sse_kms_key_id.clone())
+            .set_bucket_key_enabled(headers.bucket_key_enabled);
+        if let Some(class) = &current.storage_class {
+            req = req.storage_class(aws_sdk_s3::types::StorageClass::from(class.as_str()));
+        }
+        req
+    }};
+}
 
 /// Wrapper around AWS S3 client with cross-region support
 pub struct S3Client {
@@ -13,10 +81,57 @@ pub struct S3Client {
     default_region: String,
     /// Cache of region-specific clients
     regional_clients: Arc<RwLock<HashMap<String, Client>>>,
+    /// Cache of bucket name to region, so each bucket is located only once
+    bucket_regions: Arc<RwLock<HashMap<String, String>>>,
+    /// Client for HTTP(S) URLs, which stand in as bucket = origin, key = path
+    http: HttpSource,
     /// Disable cross-region bucket support (for custom endpoints)
     disable_cross_region: bool,
     /// Optional metrics collector
     metrics: Option<Arc<S3Metrics>>,
+    /// Name of the provider the client was built from
+    provider_name: Option<String>,
+    /// Provider settings the client was built from (endpoint, anonymous, etc.)
+    provider_config: Option<ProviderConfig>,
+    /// How large downloads are split into concurrent ranged GETs
+    transfer: RwLock<TransferOptions>,
+    /// Paces downloads and uploads to `TransferOptions::bandwidth`
+    bandwidth: Arc<BandwidthLimiter>,
+    /// Refuse uploads, deletes, and other changes
+    read_only: AtomicBool,
+    /// How failed calls are retried, shared with the SDK clients' interceptors
+    retry: Arc<RwLock<RetryPolicy>>,
+    /// Metadata of listed objects; an object with the same ETag has the same content,
+    /// so entries don't go stale
+    head_cache: HeadCache,
+    /// Recent '/'-delimited listings, reused for the listing TTL
+    listings: ListingCache,
+    /// Zip bomb limits for files decompressed out of archives
+    extract_limits: RwLock<ExtractLimits>,
+    /// S3 Inventory reports that recursive summaries of configured buckets are read from
+    inventory: InventoryStore,
+    /// Buckets served through other providers' clients, checked in order
+    mounts: RwLock<Vec<Mount>>,
+}
+
+/// Buckets whose names match `pattern` (exact, or with `*` wildcards) are sent to
+/// `client` instead, so one shell can browse buckets from several providers
+#[derive(Clone)]
+pub struct Mount {
+    pub pattern: String,
+    pub client: Arc<S3Client>,
+}
+
+impl Mount {
+    /// Whether the mount serves this bucket
+    pub fn matches(&self, bucket: &str) -> bool {
+        wildcard_match(&self.pattern, bucket)
+    }
+
+    /// Whether the pattern names a single bucket, which is listed at / as is
+    fn is_exact(&self) -> bool {
+        !self.pattern.contains('*')
+    }
 }
 
 impl S3Client {
@@ -27,14 +142,29 @@ impl S3Client {
             .region()
             .map(|r| r.as_ref().to_string())
             .unwrap_or_else(|| "us-west-2".to_string());
-        let client = Client::new(&config);
+        let metrics = S3Metrics::new();
+        let retry = Arc::new(RwLock::new(RetryPolicy::default()));
+        let client = instrument(Client::new(&config), Some(&metrics), &retry);
 
         Ok(S3Client {
             default_client: client,
             default_region,
             regional_clients: Arc::new(RwLock::new(HashMap::new())),
+            bucket_regions: Arc::new(RwLock::new(HashMap::new())),
+            http: HttpSource::new(),
             disable_cross_region: false,
-            metrics: None,
+            metrics: Some(metrics),
+            provider_name: None,
+            provider_config: None,
+            transfer: RwLock::new(TransferOptions::default()),
+            bandwidth: Arc::default(),
+            read_only: AtomicBool::new(false),
+            retry,
+            head_cache: Mutex::new(LruCache::new(NonZeroUsize::new(HEAD_CACHE_SIZE).unwrap())),
+            listings: ListingCache::default(),
+            extract_limits: RwLock::new(ExtractLimits::default()),
+            inventory: InventoryStore::default(),
+            mounts: RwLock::default(),
         })
     }
 
@@ -49,12 +179,26 @@ impl S3Client {
         region: String,
         disable_cross_region: bool,
     ) -> Self {
+        let retry = Arc::new(RwLock::new(RetryPolicy::default()));
         S3Client {
-            default_client: client,
+            default_client: instrument(client, None, &retry),
             default_region: region,
             regional_clients: Arc::new(RwLock::new(HashMap::new())),
+            bucket_regions: Arc::new(RwLock::new(HashMap::new())),
+            http: HttpSource::new(),
             disable_cross_region,
             metrics: None,
+            provider_name: None,
+            provider_config: None,
+            transfer: RwLock::new(TransferOptions::default()),
+            bandwidth: Arc::default(),
+            read_only: AtomicBool::new(false),
+            retry,
+            head_cache: Mutex::new(LruCache::new(NonZeroUsize::new(HEAD_CACHE_SIZE).unwrap())),
+            listings: ListingCache::default(),
+            extract_limits: RwLock::new(ExtractLimits::default()),
+            inventory: InventoryStore::default(),
+            mounts: RwLock::default(),
         }
     }
 
@@ -64,21 +208,187 @@ impl S3Client {
         region: String,
         disable_cross_region: bool,
         metrics: Option<Arc<S3Metrics>>,
+    ) -> Self {
+        let retry = Arc::new(RwLock::new(RetryPolicy::default()));
+        Self::from_parts(client, region, disable_cross_region, metrics, retry)
+    }
+
+    fn from_parts(
+        client: Client,
+        region: String,
+        disable_cross_region: bool,
+        metrics: Option<Arc<S3Metrics>>,
+        retry: Arc<RwLock<RetryPolicy>>,
     ) -> Self {
         S3Client {
-            default_client: client,
+            default_client: instrument(client, metrics.as_ref(), &retry),
             default_region: region,
             regional_clients: Arc::new(RwLock::new(HashMap::new())),
+            bucket_regions: Arc::new(RwLock::new(HashMap::new())),
+            http: HttpSource::new(),
             disable_cross_region,
             metrics,
+            provider_name: None,
+            provider_config: None,
+            transfer: RwLock::new(TransferOptions::default()),
+            bandwidth: Arc::default(),
+            read_only: AtomicBool::new(false),
+            retry,
+            head_cache: Mutex::new(LruCache::new(NonZeroUsize::new(HEAD_CACHE_SIZE).unwrap())),
+            listings: ListingCache::default(),
+            extract_limits: RwLock::new(ExtractLimits::default()),
+            inventory: InventoryStore::default(),
+            mounts: RwLock::default(),
         }
     }
 
+    /// Create a client from a provider's configuration
+    pub async fn from_provider(name: &str, config: ProviderConfig) -> Result<Self> {
+        let (client, region, disable_cross_region) =
+            crate::providers::create_s3_client(config.clone()).await?;
+        // Metrics let the shell report progress when a command times out
+        Ok(Self::from_client_with_metrics(
+            client,
+            region,
+            disable_cross_region,
+            Some(S3Metrics::new()),
+        )
+        .with_provider(name, config))
+    }
+
+    /// Attach the provider (name and configuration) used to build this client
+    /// Other clients (e.g. object_store for Parquet, STS) use it to reach the same endpoint
+    pub fn with_provider(mut self, name: &str, config: ProviderConfig) -> Self {
+        self.http = HttpSource::with_options(&config.http);
+        self.provider_name = Some(name.to_string());
+        self.provider_config = Some(config);
+        self
+    }
+
     /// Get the metrics collector if enabled
     pub fn metrics(&self) -> Option<&Arc<S3Metrics>> {
         self.metrics.as_ref()
     }
 
+    /// Get the current settings for parallel ranged downloads
+    pub fn transfer_options(&self) -> TransferOptions {
+        *self.transfer.read().unwrap()
+    }
+
+    /// Change how large downloads are split into concurrent ranged GETs
+    pub fn set_transfer_options(&self, options: TransferOptions) {
+        self.bandwidth.set_rate(options.bandwidth);
+        *self.transfer.write().unwrap() = options;
+    }
+
+    /// Get the current retry policy
+    pub fn retry_policy(&self) -> RetryPolicy {
+        *self.retry.read().unwrap()
+    }
+
+    /// Change how failed calls are retried, including by clients already created
+    pub fn set_retry_policy(&self, policy: RetryPolicy) {
+        *self.retry.write().unwrap() = policy;
+    }
+
+    /// Set where each bucket's S3 Inventory reports are delivered (bucket to s3:// URL)
+    pub fn set_inventory_locations(&self, locations: BTreeMap<String, String>) {
+        self.inventory.set_locations(locations);
+    }
+
+    /// Get the limits on files decompressed out of archives
+    pub fn extract_limits(&self) -> ExtractLimits {
+        *self.extract_limits.read().unwrap()
+    }
+
+    /// Change the limits on files decompressed out of archives
+    pub fn set_extract_limits(&self, limits: ExtractLimits) {
+        *self.extract_limits.write().unwrap() = limits;
+    }
+
+    /// Whether changes to S3 are refused
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::Relaxed)
+    }
+
+    /// Refuse (or allow again) uploads, deletes, and other changes
+    pub fn set_read_only(&self, read_only: bool) {
+        self.read_only.store(read_only, Ordering::Relaxed);
+    }
+
+    /// Fail if the client is read-only; every method that changes S3 calls this first,
+    /// and commands call it up front so they fail before doing any work
+    pub fn check_writable(&self, action: &str, bucket: &str, key: &str) -> Result<()> {
+        if self.is_read_only() {
+            return Err(anyhow::anyhow!(
+                "Read-only mode: not allowed to {action} s3://{bucket}/{key} (`set readonly off` to allow changes)"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Build a client for another provider to mount buckets from
+    /// It shares this client's metrics and retry policy, so its requests count toward
+    /// the running command and follow `set retry_*`.
+    pub async fn mount(&self, name: &str, config: ProviderConfig) -> Result<Self> {
+        let (client, region, disable_cross_region) =
+            crate::providers::create_s3_client(config.clone()).await?;
+        Ok(Self::from_parts(
+            client,
+            region,
+            disable_cross_region,
+            self.metrics.clone(),
+            Arc::clone(&self.retry),
+        )
+        .with_provider(name, config))
+    }
+
+    /// Replace the mount table
+    pub fn set_mounts(&self, mounts: Vec<Mount>) {
+        *self.mounts.write().unwrap() = mounts;
+    }
+
+    /// The mount table, in the order buckets are matched against it
+    pub fn mounts(&self) -> Vec<Mount> {
+        self.mounts.read().unwrap().clone()
+    }
+
+    /// The client of the first mount serving a bucket, if any
+    pub fn mounted_client(&self, bucket: &str) -> Option<Arc<S3Client>> {
+        if is_http_origin(bucket) {
+            return None;
+        }
+        self.mounts
+            .read()
+            .unwrap()
+            .iter()
+            .find(|mount| mount.matches(bucket))
+            .map(|mount| Arc::clone(&mount.client))
+    }
+
+    /// Get the provider name, if the client was built from a provider
+    pub fn provider_name(&self) -> Option<&str> {
+        self.provider_name.as_deref()
+    }
+
+    /// Get the provider configuration, if the client was built from one
+    pub fn provider_config(&self) -> Option<&ProviderConfig> {
+        self.provider_config.as_ref()
+    }
+
+    /// Load the AWS SDK configuration matching this client's provider (profile, anonymous)
+    pub async fn sdk_config(&self) -> aws_config::SdkConfig {
+        match &self.provider_config {
+            Some(config) => crate::providers::load_sdk_config(config).await,
+            None => aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await,
+        }
+    }
+
+    /// Get the default region
+    pub fn region(&self) -> &str {
+        &self.default_region
+    }
+
     /// Get or create a client for a specific region
     async fn get_regional_client(&self, region: &str) -> Result<Client> {
         // Check if we already have a client for this region
@@ -90,12 +400,19 @@ impl S3Client {
         }
 
         // Create a new client for this region
-        let config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
-        let region_provider = aws_sdk_s3::config::Region::new(region.to_string());
-        let s3_config = aws_sdk_s3::config::Builder::from(&config)
-            .region(region_provider)
-            .build();
-        let client = Client::from_conf(s3_config);
+        debug!(region, "creating regional client");
+        let config = self.sdk_config().await;
+        let s3_config = crate::providers::s3_config(
+            self.provider_config.as_ref(),
+            &config,
+            region,
+            self.default_client.config().http_client(),
+        );
+        let client = instrument(
+            Client::from_conf(s3_config),
+            self.metrics.as_ref(),
+            &self.retry,
+        );
 
         // Cache it
         {
@@ -106,9 +423,25 @@ impl S3Client {
         Ok(client)
     }
 
-    /// Get the region of a bucket by making a head_bucket request
+    /// Get the region of a bucket, caching the answer for the rest of the session
     async fn get_bucket_region(&self, bucket: &str) -> Result<String> {
-        // Try with default client first
+        if let Some(region) = self.bucket_regions.read().unwrap().get(bucket) {
+            tracing::trace!(bucket, region, "bucket region cache hit");
+            return Ok(region.clone());
+        }
+
+        let region = self.lookup_bucket_region(bucket).await?;
+        debug!(bucket, region, "resolved bucket region");
+        self.bucket_regions
+            .write()
+            .unwrap()
+            .insert(bucket.to_string(), region.clone());
+        Ok(region)
+    }
+
+    /// Ask S3 where a bucket lives
+    #[tracing::instrument(level = "debug", skip(self))]
+    async fn lookup_bucket_region(&self, bucket: &str) -> Result<String> {
         match self
             .default_client
             .head_bucket()
@@ -117,68 +450,110 @@ impl S3Client {
             .await
         {
             Ok(resp) => {
-                // Extract region from response headers
-                if let Some(region) = resp.bucket_region() {
-                    return Ok(region.to_string());
-                }
-                Ok(self.default_region.clone())
+                return Ok(resp
+                    .bucket_region()
+                    .unwrap_or(&self.default_region)
+                    .to_string());
             }
             Err(e) => {
-                // Check if error contains region information
-                let error_msg = format!("{e:?}");
-                if error_msg.contains("PermanentRedirect") || error_msg.contains("301") {
-                    // Try to extract region from error
-                    // AWS returns the region in the error for redirects
-                    // For now, we'll try common regions
-                    for region in [
-                        "us-east-1",
-                        "us-west-1",
-                        "us-west-2",
-                        "eu-west-1",
-                        "ap-southeast-1",
-                    ] {
-                        let client = self.get_regional_client(region).await?;
-                        if let Ok(resp) = client.head_bucket().bucket(bucket).send().await {
-                            if let Some(bucket_region) = resp.bucket_region() {
-                                return Ok(bucket_region.to_string());
-                            }
-                            return Ok(region.to_string());
-                        }
-                    }
+                // S3 names the bucket's region in a header even on 301/403 responses
+                if let Some(region) = e
+                    .raw_response()
+                    .and_then(|resp| resp.headers().get("x-amz-bucket-region"))
+                {
+                    return Ok(region.to_string());
                 }
-                Err(anyhow::anyhow!(
-                    "Failed to determine bucket region: {bucket}"
-                ))
             }
         }
+
+        // Fall back to GetBucketLocation (e.g. when HEAD is blocked by a proxy)
+        let resp = self
+            .default_client
+            .get_bucket_location()
+            .bucket(bucket)
+            .send()
+            .await
+            .context(format!("Failed to determine bucket region: {bucket}"))?;
+
+        Ok(region_from_location(
+            resp.location_constraint().map(|c| c.as_str()),
+        ))
+    }
+
+    /// Get the region to use for a bucket, falling back to the default region
+    /// Custom endpoints (cross-region disabled) always use the default region
+    pub async fn bucket_region(&self, bucket: &str) -> String {
+        if let Some(client) = self.mounted_client(bucket) {
+            return Box::pin(client.bucket_region(bucket)).await;
+        }
+        if self.disable_cross_region {
+            return self.default_region.clone();
+        }
+
+        self.get_bucket_region(bucket)
+            .await
+            .unwrap_or_else(|_| self.default_region.clone())
     }
 
     /// Get the appropriate client for a bucket (handles cross-region)
     async fn get_client_for_bucket(&self, bucket: &str) -> Result<Client> {
+        if let Some(client) = self.mounted_client(bucket) {
+            return Box::pin(client.get_client_for_bucket(bucket)).await;
+        }
+
         // If cross-region is disabled (e.g., custom endpoints), always use default client
         if self.disable_cross_region {
             return Ok(self.default_client.clone());
         }
 
-        // Try default client first
-        match self
-            .default_client
-            .head_bucket()
-            .bucket(bucket)
-            .send()
-            .await
-        {
-            Ok(_) => Ok(self.default_client.clone()),
-            Err(_) => {
-                // Get the bucket's region and return appropriate client
-                let region = self.get_bucket_region(bucket).await?;
-                self.get_regional_client(&region).await
-            }
+        // Unknown regions fall through to the default client, which reports the real error
+        let region = match self.get_bucket_region(bucket).await {
+            Ok(region) => region,
+            Err(_) => return Ok(self.default_client.clone()),
+        };
+        if region == self.default_region {
+            return Ok(self.default_client.clone());
         }
+        self.get_regional_client(&region).await
     }
 
-    /// List all S3 buckets
+    /// List all S3 buckets, with those of mounted providers merged in
+    /// Buckets matching a mount pattern are listed through the mount, and exact mounts
+    /// are listed by name since anonymous providers often can't list buckets.
+    #[tracing::instrument(level = "debug", skip(self))]
     pub async fn list_buckets(&self) -> Result<Vec<BucketInfo>> {
+        let mounts = self.mounts();
+        let mut buckets = self.list_own_buckets().await?;
+        if mounts.is_empty() {
+            return Ok(buckets);
+        }
+
+        buckets.retain(|b| !mounts.iter().any(|mount| mount.matches(&b.name)));
+        for mount in &mounts {
+            if mount.is_exact() {
+                buckets.push(BucketInfo {
+                    name: mount.pattern.clone(),
+                    creation_date: None,
+                });
+                continue;
+            }
+            match Box::pin(mount.client.list_own_buckets()).await {
+                Ok(mounted) => {
+                    buckets.extend(mounted.into_iter().filter(|b| mount.matches(&b.name)))
+                }
+                Err(e) => debug!(
+                    pattern = mount.pattern,
+                    "listing mounted buckets failed: {e:#}"
+                ),
+            }
+        }
+        buckets.sort_by(|a, b| a.name.cmp(&b.name));
+        buckets.dedup_by(|a, b| a.name == b.name);
+        Ok(buckets)
+    }
+
+    /// List the buckets this client's own provider has
+    async fn list_own_buckets(&self) -> Result<Vec<BucketInfo>> {
         let resp = self
             .default_client
             .list_buckets()
@@ -200,15 +575,196 @@ impl S3Client {
         Ok(buckets)
     }
 
-    /// List objects in a bucket with a given prefix and delimiter
+    /// Create a bucket, in `region` or else the default region
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn create_bucket(&self, bucket: &str, region: Option<&str>) -> Result<()> {
+        self.check_writable("create", bucket, "")?;
+        if let Some(client) = self.mounted_client(bucket) {
+            return Box::pin(client.create_bucket(bucket, region)).await;
+        }
+        let region = region.unwrap_or(&self.default_region);
+        let client = if self.disable_cross_region || region == self.default_region {
+            self.default_client.clone()
+        } else {
+            self.get_regional_client(region).await?
+        };
+
+        let mut request = client.create_bucket().bucket(bucket);
+        // us-east-1 is the one region that must not be named as a location constraint
+        if region != "us-east-1" {
+            request = request.create_bucket_configuration(
+                CreateBucketConfiguration::builder()
+                    .location_constraint(BucketLocationConstraint::from(region))
+                    .build(),
+            );
+        }
+        request
+            .send()
+            .await
+            .context(format!("Failed to create bucket {bucket}"))?;
+
+        self.bucket_regions
+            .write()
+            .unwrap()
+            .insert(bucket.to_string(), region.to_string());
+        Ok(())
+    }
+
+    /// Delete an (empty) bucket
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn delete_bucket(&self, bucket: &str) -> Result<()> {
+        self.check_writable("delete", bucket, "")?;
+        let client = self.get_client_for_bucket(bucket).await?;
+        client
+            .delete_bucket()
+            .bucket(bucket)
+            .send()
+            .await
+            .context(format!("Failed to delete bucket {bucket}"))?;
+
+        self.bucket_regions.write().unwrap().remove(bucket);
+        self.listings.invalidate(bucket, "");
+        Ok(())
+    }
+
+    /// Get a bucket's versioning status ("Enabled" or "Suspended"), None if never enabled
+    pub async fn get_bucket_versioning(&self, bucket: &str) -> Result<Option<String>> {
+        let client = self.get_client_for_bucket(bucket).await?;
+        let resp = client
+            .get_bucket_versioning()
+            .bucket(bucket)
+            .send()
+            .await
+            .context(format!("Failed to get versioning for {bucket}"))?;
+        Ok(resp.status().map(|status| status.as_str().to_string()))
+    }
+
+    /// Describe a bucket's default encryption ("aws:kms (key ...)"), None if not configured
+    pub async fn get_bucket_encryption(&self, bucket: &str) -> Result<Option<String>> {
+        let client = self.get_client_for_bucket(bucket).await?;
+        let resp = match client.get_bucket_encryption().bucket(bucket).send().await {
+            Ok(resp) => resp,
+            Err(e) if e.code() == Some("ServerSideEncryptionConfigurationNotFoundError") => {
+                return Ok(None);
+            }
+            Err(e) => {
+                return Err(e).context(format!("Failed to get encryption for {bucket}"));
+            }
+        };
+
+        let rules = resp
+            .server_side_encryption_configuration()
+            .map(|config| config.rules())
+            .unwrap_or_default();
+        let described: Vec<String> = rules
+            .iter()
+            .filter_map(|rule| {
+                let default = rule.apply_server_side_encryption_by_default()?;
+                let mut description = default.sse_algorithm().as_str().to_string();
+                if let Some(key) = default.kms_master_key_id() {
+                    description.push_str(&format!(" (key {key})"));
+                }
+                if rule.bucket_key_enabled() == Some(true) {
+                    description.push_str(", bucket key");
+                }
+                Some(description)
+            })
+            .collect();
+        Ok((!described.is_empty()).then(|| described.join("; ")))
+    }
+
+    /// Get a bucket's public access block settings, None if the bucket has none
+    pub async fn get_public_access_block(&self, bucket: &str) -> Result<Option<PublicAccessBlock>> {
+        let client = self.get_client_for_bucket(bucket).await?;
+        let resp = match client.get_public_access_block().bucket(bucket).send().await {
+            Ok(resp) => resp,
+            Err(e) if e.code() == Some("NoSuchPublicAccessBlockConfiguration") => return Ok(None),
+            Err(e) => {
+                return Err(e).context(format!("Failed to get public access block for {bucket}"));
+            }
+        };
+
+        Ok(resp
+            .public_access_block_configuration()
+            .map(|config| PublicAccessBlock {
+                block_public_acls: config.block_public_acls().unwrap_or(false),
+                ignore_public_acls: config.ignore_public_acls().unwrap_or(false),
+                block_public_policy: config.block_public_policy().unwrap_or(false),
+                restrict_public_buckets: config.restrict_public_buckets().unwrap_or(false),
+            }))
+    }
+
+    /// List objects in a bucket with a given prefix and delimiter (the first page)
+    #[tracing::instrument(level = "debug", skip(self))]
     pub async fn list_objects(
         &self,
         bucket: &str,
         prefix: &str,
         delimiter: Option<&str>,
     ) -> Result<ListObjectsResult> {
+        // Only directory listings are cached; they're what browsing repeats
+        let cacheable = delimiter == Some("/");
+        if cacheable && let Some(result) = self.listings.get(bucket, prefix) {
+            return Ok(result);
+        }
+        let result = self
+            .list_objects_page(bucket, prefix, delimiter, None)
+            .await?
+            .0;
+        if cacheable {
+            self.listings.put(bucket, prefix, &result);
+        }
+        Ok(result)
+    }
+
+    /// Recent directory listings, shared by `ls` and tab completion
+    pub fn listing_cache(&self) -> &ListingCache {
+        &self.listings
+    }
+
+    /// List the first page of objects along with each object's owner (FetchOwner)
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_objects_with_owner(
+        &self,
+        bucket: &str,
+        prefix: &str,
+        delimiter: Option<&str>,
+    ) -> Result<ListObjectsResult> {
+        Ok(self
+            .fetch_objects_page(bucket, prefix, delimiter, None, true)
+            .await?
+            .0)
+    }
+
+    /// List one page of objects, returning the token for the next page if there is one
+    pub async fn list_objects_page(
+        &self,
+        bucket: &str,
+        prefix: &str,
+        delimiter: Option<&str>,
+        continuation_token: Option<String>,
+    ) -> Result<(ListObjectsResult, Option<String>)> {
+        self.fetch_objects_page(bucket, prefix, delimiter, continuation_token, false)
+            .await
+    }
+
+    async fn fetch_objects_page(
+        &self,
+        bucket: &str,
+        prefix: &str,
+        delimiter: Option<&str>,
+        continuation_token: Option<String>,
+        fetch_owner: bool,
+    ) -> Result<(ListObjectsResult, Option<String>)> {
         let client = self.get_client_for_bucket(bucket).await?;
-        let mut req = client.list_objects_v2().bucket(bucket);
+        let mut req = client
+            .list_objects_v2()
+            .bucket(bucket)
+            .set_continuation_token(continuation_token);
+
+        if fetch_owner {
+            req = req.fetch_owner(true);
+        }
 
         if !prefix.is_empty() {
             req = req.prefix(prefix);
@@ -239,70 +795,525 @@ impl S3Client {
                 last_modified: obj
                     .last_modified()
                     .and_then(|d| d.fmt(aws_sdk_s3::primitives::DateTimeFormat::DateTime).ok()),
+                etag: obj.e_tag().map(String::from),
+                storage_class: obj.storage_class().map(|c| c.as_str().to_string()),
+                // Newer buckets return only the canonical ID
+                owner: obj
+                    .owner()
+                    .and_then(|owner| owner.display_name().or(owner.id()))
+                    .map(String::from),
             })
             .collect();
 
-        Ok(ListObjectsResult { prefixes, objects })
+        let next_token = resp
+            .next_continuation_token()
+            .filter(|_| resp.is_truncated() == Some(true))
+            .map(String::from);
+        Ok((ListObjectsResult { prefixes, objects }, next_token))
+    }
+
+    /// Whether any object exists under a prefix (including a directory marker)
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn prefix_exists(&self, bucket: &str, prefix: &str) -> Result<bool> {
+        let client = self.get_client_for_bucket(bucket).await?;
+        let resp = client
+            .list_objects_v2()
+            .bucket(bucket)
+            .prefix(prefix)
+            .max_keys(1)
+            .send()
+            .await
+            .context(format!("Failed to list objects in bucket: {bucket}"))?;
+        Ok(resp.key_count().unwrap_or(0) > 0 || !resp.contents().is_empty())
+    }
+
+    /// List every object under a prefix, sorted by key
+    /// Sibling prefixes are listed concurrently (see [`PrefixWalker`]).
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_objects_recursive(
+        &self,
+        bucket: &str,
+        prefix: &str,
+    ) -> Result<Vec<ObjectInfo>> {
+        PrefixWalker::new(self, bucket).collect(prefix).await
+    }
+
+    /// Count the objects under a prefix (recursively) and total their size
+    /// Only objects accepted by `include` are counted. Buckets with an inventory
+    /// configured are counted from its latest report, falling back to listing.
+    pub async fn summarize_prefix(
+        &self,
+        bucket: &str,
+        prefix: &str,
+        include: impl Fn(&ObjectInfo) -> bool,
+    ) -> Result<(usize, u64)> {
+        match self.inventory.get(self, bucket).await {
+            Ok(Some(inventory)) => {
+                debug!(
+                    bucket,
+                    snapshot = inventory.snapshot,
+                    "summarizing from inventory"
+                );
+                return Ok(inventory
+                    .under(prefix)
+                    .iter()
+                    .filter(|obj| include(obj))
+                    .fold((0, 0), |(count, bytes), obj| (count + 1, bytes + obj.size)));
+            }
+            Ok(None) => {}
+            Err(e) => debug!(bucket, "inventory unavailable, listing instead: {e:#}"),
+        }
+
+        PrefixWalker::new(self, bucket)
+            .walk(prefix)
+            .try_fold((0, 0), |(count, bytes), objects| {
+                let summary = objects
+                    .iter()
+                    .filter(|obj| include(obj))
+                    .fold((count, bytes), |(count, bytes), obj| {
+                        (count + 1, bytes + obj.size)
+                    });
+                async move { Ok(summary) }
+            })
+            .await
+    }
+
+    /// List object versions (and delete markers) under a prefix, following pagination
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn list_object_versions(
+        &self,
+        bucket: &str,
+        prefix: &str,
+        delimiter: Option<&str>,
+    ) -> Result<ListVersionsResult> {
+        let client = self.get_client_for_bucket(bucket).await?;
+        let mut result = ListVersionsResult {
+            prefixes: Vec::new(),
+            versions: Vec::new(),
+        };
+        let mut key_marker: Option<String> = None;
+        let mut version_id_marker: Option<String> = None;
+
+        loop {
+            let mut req = client
+                .list_object_versions()
+                .bucket(bucket)
+                .set_key_marker(key_marker.take())
+                .set_version_id_marker(version_id_marker.take());
+
+            if !prefix.is_empty() {
+                req = req.prefix(prefix);
+            }
+
+            if let Some(delim) = delimiter {
+                req = req.delimiter(delim);
+            }
+
+            let resp = req.send().await.context(format!(
+                "Failed to list object versions in bucket: {bucket}"
+            ))?;
+
+            result.prefixes.extend(
+                resp.common_prefixes()
+                    .iter()
+                    .filter_map(|p| p.prefix())
+                    .map(String::from),
+            );
+
+            result.versions.extend(resp.versions().iter().map(|v| {
+                ObjectVersionInfo {
+                    key: v.key().unwrap_or("").to_string(),
+                    version_id: v.version_id().unwrap_or("null").to_string(),
+                    size: v.size().unwrap_or(0) as u64,
+                    last_modified: v
+                        .last_modified()
+                        .and_then(|d| d.fmt(aws_sdk_s3::primitives::DateTimeFormat::DateTime).ok()),
+                    is_latest: v.is_latest().unwrap_or(false),
+                    is_delete_marker: false,
+                }
+            }));
+
+            result
+                .versions
+                .extend(resp.delete_markers().iter().map(|m| ObjectVersionInfo {
+                    key: m.key().unwrap_or("").to_string(),
+                    version_id: m.version_id().unwrap_or("null").to_string(),
+                    size: 0,
+                    last_modified:
+                        m.last_modified().and_then(|d| {
+                            d.fmt(aws_sdk_s3::primitives::DateTimeFormat::DateTime).ok()
+                        }),
+                    is_latest: m.is_latest().unwrap_or(false),
+                    is_delete_marker: true,
+                }));
+
+            if !resp.is_truncated().unwrap_or(false) {
+                break;
+            }
+            key_marker = resp.next_key_marker().map(String::from);
+            version_id_marker = resp.next_version_id_marker().map(String::from);
+            if key_marker.is_none() && version_id_marker.is_none() {
+                break;
+            }
+        }
+
+        // Group versions of a key together, newest first (delete markers come back separately)
+        result.versions.sort_by(|a, b| {
+            a.key
+                .cmp(&b.key)
+                .then_with(|| b.last_modified.cmp(&a.last_modified))
+        });
+        result.prefixes.dedup();
+
+        Ok(result)
     }
 
     /// Get an object's metadata
     pub async fn head_object(&self, bucket: &str, key: &str) -> Result<ObjectMetadata> {
+        self.head_object_version(bucket, key, None).await
+    }
+
+    /// Get the metadata of listed objects, in order (None where the HEAD failed)
+    /// Objects seen before with the same ETag come from a cache; the rest are fetched
+    /// concurrently.
+    pub async fn head_listed_objects(
+        &self,
+        bucket: &str,
+        objects: &[&ObjectInfo],
+    ) -> Vec<Option<ObjectMetadata>> {
+        let cache_key = |obj: &ObjectInfo| {
+            obj.etag
+                .clone()
+                .map(|etag| (bucket.to_string(), obj.key.clone(), etag))
+        };
+
+        let mut results: Vec<Option<ObjectMetadata>> = {
+            let mut cache = self.head_cache.lock().unwrap();
+            objects
+                .iter()
+                .map(|obj| cache_key(obj).and_then(|k| cache.get(&k).cloned()))
+                .collect()
+        };
+        let mut missing = results
+            .iter()
+            .enumerate()
+            .filter(|(_, result)| result.is_none())
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>()
+            .into_iter();
+        debug!(
+            objects = objects.len(),
+            misses = missing.len(),
+            "HEAD for listing"
+        );
+
+        // Keep up to HEAD_CONCURRENCY requests in flight, starting another as each finishes
+        let head = |i: usize| async move { (i, self.head_object(bucket, &objects[i].key).await) };
+        let mut in_flight = FuturesUnordered::new();
+        in_flight.extend(missing.by_ref().take(HEAD_CONCURRENCY).map(head));
+        while let Some((i, result)) = in_flight.next().await {
+            if let Some(i) = missing.next() {
+                in_flight.push(head(i));
+            }
+            if let Ok(metadata) = result {
+                if let Some(key) = cache_key(objects[i]) {
+                    self.head_cache.lock().unwrap().put(key, metadata.clone());
+                }
+                results[i] = Some(metadata);
+            }
+        }
+        results
+    }
+
+    /// Get the metadata of a specific object version (None = latest)
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn head_object_version(
+        &self,
+        bucket: &str,
+        key: &str,
+        version_id: Option<&str>,
+    ) -> Result<ObjectMetadata> {
+        if is_http_origin(bucket) {
+            return self.http.head(bucket, key).await;
+        }
+
         let client = self.get_client_for_bucket(bucket).await?;
         let resp = client
             .head_object()
             .bucket(bucket)
             .key(key)
+            .set_version_id(version_id.map(String::from))
             .send()
             .await
             .context(format!("Failed to get metadata for s3://{bucket}/{key}"))?;
 
         Ok(ObjectMetadata {
             size: resp.content_length().unwrap_or(0) as u64,
+            last_modified: resp
+                .last_modified()
+                .and_then(|d| d.fmt(aws_sdk_s3::primitives::DateTimeFormat::DateTime).ok()),
+            content_type: resp.content_type().map(String::from),
+            etag: resp.e_tag().map(String::from),
+            version_id: resp.version_id().map(String::from),
+            storage_class: resp.storage_class().map(|c| c.as_str().to_string()),
+            user_metadata: resp
+                .metadata()
+                .map(|m| m.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+                .unwrap_or_default(),
+            headers: ObjectHeaders {
+                content_encoding: resp.content_encoding().map(String::from),
+                cache_control: resp.cache_control().map(String::from),
+                content_disposition: resp.content_disposition().map(String::from),
+                content_language: resp.content_language().map(String::from),
+                expires: resp.expires_string().map(String::from),
+                website_redirect_location: resp.website_redirect_location().map(String::from),
+                server_side_encryption: resp
+                    .server_side_encryption()
+                    .map(|sse| sse.as_str().to_string()),
+                sse_kms_key_id: resp.ssekms_key_id().map(String::from),
+                bucket_key_enabled: resp.bucket_key_enabled(),
+            },
         })
     }
 
-    /// Get an entire object's contents
-    pub async fn get_object(&self, bucket: &str, key: &str) -> Result<Bytes> {
+    /// Get an object's tag set, sorted by key
+    pub async fn get_object_tags(&self, bucket: &str, key: &str) -> Result<Vec<(String, String)>> {
         let client = self.get_client_for_bucket(bucket).await?;
         let resp = client
-            .get_object()
+            .get_object_tagging()
             .bucket(bucket)
             .key(key)
             .send()
             .await
-            .context(format!("Failed to get object s3://{bucket}/{key}"))?;
+            .context(format!("Failed to get tags for s3://{bucket}/{key}"))?;
 
-        let bytes = resp
-            .body
-            .collect()
-            .await
-            .context("Failed to read object body")?
-            .into_bytes();
+        let mut tags: Vec<(String, String)> = resp
+            .tag_set()
+            .iter()
+            .map(|t| (t.key().to_string(), t.value().to_string()))
+            .collect();
+        tags.sort();
 
-        Ok(bytes)
+        Ok(tags)
     }
 
-    /// Get a range of bytes from an object (for streaming archives)
-    pub async fn get_object_range(
+    /// Replace an object's user metadata (x-amz-meta-*) by copying it onto itself
+    /// REPLACE resets everything else set at upload, so the content type, storage class,
+    /// system headers (Content-Encoding, Cache-Control, etc.), and SSE-S3/SSE-KMS
+    /// encryption are read with a HEAD and set again. The copy is conditional on the ETag
+    /// the HEAD saw. Objects over 5 GiB are refused, as CopyObject can't copy them.
+    #[tracing::instrument(level = "debug", skip(self, metadata))]
+    pub async fn replace_user_metadata(
         &self,
         bucket: &str,
         key: &str,
-        offset: u64,
-        length: u64,
-    ) -> Result<Bytes> {
+        metadata: &BTreeMap<String, String>,
+    ) -> Result<()> {
+        self.check_writable("update metadata of", bucket, key)?;
+        let current = self.head_object(bucket, key).await?;
+        if current.size > MAX_COPY_SIZE {
+            return Err(anyhow::anyhow!(
+                "s3://{bucket}/{key} is {}, over the 5 GiB a single copy can update metadata of",
+                humansize::format_size(current.size, humansize::BINARY)
+            ));
+        }
+        let client = self.get_client_for_bucket(bucket).await?;
+
+        let req = client
+            .copy_object()
+            .bucket(bucket)
+            .key(key)
+            .copy_source(format!("{bucket}/{}", encode_copy_source(key)))
+            .set_copy_source_if_match(current.etag.clone())
+            .metadata_directive(aws_sdk_s3::types::MetadataDirective::Replace)
+            .set_metadata(Some(metadata.clone().into_iter().collect()));
+
+        carry_over_headers!(req, &current)
+            .send()
+            .await
+            .map_err(|e| {
+                if e.raw_response().map(|resp| resp.status().as_u16()) == Some(412) {
+                    anyhow::anyhow!("s3://{bucket}/{key} changed while its metadata was updated")
+                } else {
+                    anyhow::Error::new(e)
+                        .context(format!("Failed to update metadata for s3://{bucket}/{key}"))
+                }
+            })?;
+        self.listings.invalidate(bucket, key);
+
+        Ok(())
+    }
+
+    /// Overwrite an object's content, keeping its user metadata and everything
+    /// `replace_user_metadata` carries over (content type, storage class, system headers,
+    /// and encryption)
+    /// The write is conditional on the ETag in `current`, so a concurrent change isn't clobbered.
+    #[tracing::instrument(level = "debug", skip(self, body, current), fields(len = body.len()))]
+    pub async fn replace_object_content(
+        &self,
+        bucket: &str,
+        key: &str,
+        body: Bytes,
+        current: &ObjectMetadata,
+    ) -> Result<()> {
+        self.check_writable("overwrite", bucket, key)?;
+        let client = self.get_client_for_bucket(bucket).await?;
+
+        let req = client
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(body.into())
+            .set_metadata(Some(current.user_metadata.clone().into_iter().collect()))
+            .set_if_match(current.etag.clone());
+
+        carry_over_headers!(req, current)
+            .send()
+            .await
+            .map_err(|e| {
+                if e.raw_response().map(|resp| resp.status().as_u16()) == Some(412) {
+                    anyhow::anyhow!(
+                        "s3://{bucket}/{key} changed while it was being edited; not uploaded"
+                    )
+                } else {
+                    anyhow::Error::new(e).context(format!("Failed to upload s3://{bucket}/{key}"))
+                }
+            })?;
+        self.listings.invalidate(bucket, key);
+
+        Ok(())
+    }
+
+    /// Upload an in-memory body as an object, replacing any existing one
+    #[tracing::instrument(level = "debug", skip(self, body), fields(len = body.len()))]
+    pub async fn put_object(&self, bucket: &str, key: &str, body: Bytes) -> Result<()> {
+        self.check_writable("upload to", bucket, key)?;
+        let size = body.len() as u64;
+        let client = self.get_client_for_bucket(bucket).await?;
+        let start = Instant::now();
+        client
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(body.into())
+            .send()
+            .await
+            .context(format!("Failed to upload s3://{bucket}/{key}"))?;
+        self.listings.invalidate(bucket, key);
+
+        if let Some(metrics) = &self.metrics {
+            metrics.record_upload(size, start.elapsed());
+        }
+        Ok(())
+    }
+
+    /// Upload a local file, streaming it from disk (single PUT, so at most 5 GB)
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn upload_file(&self, bucket: &str, key: &str, path: &std::path::Path) -> Result<()> {
+        self.check_writable("upload to", bucket, key)?;
+        let body = aws_sdk_s3::primitives::ByteStream::from_path(path)
+            .await
+            .context(format!("Failed to read {}", path.display()))?;
+        let size = body.size_hint().0;
+        let bandwidth = Arc::clone(&self.bandwidth);
+        let body = body.map(move |body| bandwidth.throttle_body(body));
+        let client = self.get_client_for_bucket(bucket).await?;
         let start = Instant::now();
+        client
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(body)
+            .send()
+            .await
+            .context(format!("Failed to upload s3://{bucket}/{key}"))?;
+        self.listings.invalidate(bucket, key);
+
+        if let Some(metrics) = &self.metrics {
+            metrics.record_upload(size, start.elapsed());
+        }
+        Ok(())
+    }
 
+    /// Download an object to a local file, creating parent directories as needed
+    /// Large objects are fetched as concurrent ranged GETs; pass the size when
+    /// it's already known to skip a HEAD. Returns the number of bytes written.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn download_file(
+        &self,
+        bucket: &str,
+        key: &str,
+        size: Option<u64>,
+        path: &std::path::Path,
+    ) -> Result<u64> {
+        use tokio::io::AsyncWriteExt;
+
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir)
+                .await
+                .context(format!("Failed to create {}", dir.display()))?;
+        }
+
+        let mut body = self.get_object_chunks(bucket, key, size).await?;
+        let mut file = tokio::fs::File::create(path)
+            .await
+            .context(format!("Failed to create {}", path.display()))?;
+        let mut written = 0;
+        while let Some(chunk) = body.try_next().await? {
+            file.write_all(&chunk)
+                .await
+                .context(format!("Failed to write {}", path.display()))?;
+            written += chunk.len() as u64;
+        }
+        file.flush()
+            .await
+            .context(format!("Failed to write {}", path.display()))?;
+
+        Ok(written)
+    }
+
+    /// Delete an object
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn delete_object(&self, bucket: &str, key: &str) -> Result<()> {
+        self.check_writable("delete", bucket, key)?;
         let client = self.get_client_for_bucket(bucket).await?;
-        let range = format!("bytes={}-{}", offset, offset + length - 1);
+        client
+            .delete_object()
+            .bucket(bucket)
+            .key(key)
+            .send()
+            .await
+            .context(format!("Failed to delete s3://{bucket}/{key}"))?;
+        self.listings.invalidate(bucket, key);
+        Ok(())
+    }
 
+    /// Get an entire object's contents
+    pub async fn get_object(&self, bucket: &str, key: &str) -> Result<Bytes> {
+        self.get_object_version(bucket, key, None).await
+    }
+
+    /// Get the contents of a specific object version (None = latest)
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_object_version(
+        &self,
+        bucket: &str,
+        key: &str,
+        version_id: Option<&str>,
+    ) -> Result<Bytes> {
+        if is_http_origin(bucket) {
+            return self.http.get(bucket, key).await;
+        }
+
+        let client = self.get_client_for_bucket(bucket).await?;
         let resp = client
             .get_object()
             .bucket(bucket)
             .key(key)
-            .range(range)
+            .set_version_id(version_id.map(String::from))
             .send()
             .await
-            .context(format!("Failed to get object range s3://{bucket}/{key}"))?;
+            .context(format!("Failed to get object s3://{bucket}/{key}"))?;
 
         let bytes = resp
             .body
@@ -311,6 +1322,65 @@ impl S3Client {
             .context("Failed to read object body")?
             .into_bytes();
 
+        Ok(bytes)
+    }
+
+    /// Get a range of bytes from an object (for streaming archives)
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_object_range(
+        &self,
+        bucket: &str,
+        key: &str,
+        offset: u64,
+        length: u64,
+    ) -> Result<Bytes> {
+        self.get_object_part(bucket, key, offset, length, None)
+            .await
+    }
+
+    /// Read a byte range of an object, failing if its ETag is no longer `if_match`
+    pub(crate) async fn get_object_part(
+        &self,
+        bucket: &str,
+        key: &str,
+        offset: u64,
+        length: u64,
+        if_match: Option<&str>,
+    ) -> Result<Bytes> {
+        let start = Instant::now();
+
+        let bytes = if is_http_origin(bucket) {
+            self.http.get_range(bucket, key, offset, length).await?
+        } else {
+            let client = self.get_client_for_bucket(bucket).await?;
+            let range = format!("bytes={}-{}", offset, offset + length - 1);
+
+            let resp = client
+                .get_object()
+                .bucket(bucket)
+                .key(key)
+                .range(range)
+                .set_if_match(if_match.map(String::from))
+                .send()
+                .await
+                .map_err(|e| {
+                    if e.raw_response().map(|resp| resp.status().as_u16()) == Some(412) {
+                        anyhow::anyhow!(
+                            "s3://{bucket}/{key} changed while it was being downloaded; try again"
+                        )
+                    } else {
+                        anyhow::Error::new(e)
+                            .context(format!("Failed to get object range s3://{bucket}/{key}"))
+                    }
+                })?;
+
+            resp.body
+                .collect()
+                .await
+                .context("Failed to read object body")?
+                .into_bytes()
+        };
+
         // Record metrics if enabled
         if let Some(metrics) = &self.metrics {
             metrics.record_request(bytes.len() as u64, start.elapsed(), offset, length);
@@ -325,17 +1395,226 @@ impl S3Client {
         bucket: &str,
         key: &str,
     ) -> Result<aws_sdk_s3::primitives::ByteStream> {
+        self.get_object_version_stream(bucket, key, None).await
+    }
+
+    /// Get the streaming ByteStream of a specific object version (None = latest)
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_object_version_stream(
+        &self,
+        bucket: &str,
+        key: &str,
+        version_id: Option<&str>,
+    ) -> Result<aws_sdk_s3::primitives::ByteStream> {
+        // HTTP bodies are buffered; only full-scan formats (tar) stream whole objects
+        if is_http_origin(bucket) {
+            let bytes = self.http.get(bucket, key).await?;
+            return Ok(aws_sdk_s3::primitives::ByteStream::from(bytes));
+        }
+
         let client = self.get_client_for_bucket(bucket).await?;
         let resp = client
             .get_object()
             .bucket(bucket)
             .key(key)
+            .set_version_id(version_id.map(String::from))
             .send()
             .await
             .context(format!("Failed to get object stream s3://{bucket}/{key}"))?;
 
         Ok(resp.body)
     }
+
+    /// Stream an object's contents in order, splitting large objects into
+    /// concurrent ranged GETs (see [`TransferOptions`]). Pass the size when it's
+    /// already known; otherwise the object is HEADed first. Split downloads always
+    /// HEAD, and fail if the object changes before every part is read.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_object_chunks<'a>(
+        &'a self,
+        bucket: &'a str,
+        key: &'a str,
+        size: Option<u64>,
+    ) -> Result<BoxStream<'a, Result<Bytes>>> {
+        let mut head = None;
+        let size = match size {
+            Some(size) => size,
+            None => head.insert(self.head_object(bucket, key).await?).size,
+        };
+
+        let options = self.transfer_options();
+        if !options.is_parallel(size) {
+            let body = self.get_object_stream(bucket, key).await?;
+            return Ok(self.body_chunks(body));
+        }
+
+        // The parts must all come from the same object, so each is pinned to the ETag a
+        // HEAD saw (which also corrects a size listed before the object changed)
+        let head = match head {
+            Some(head) => head,
+            None => self.head_object(bucket, key).await?,
+        };
+        let size = head.size;
+        let etag = head.etag;
+        let parts = options.parts(size);
+        debug!(
+            size,
+            parts = parts.len(),
+            concurrency = options.concurrency,
+            "downloading as ranged GETs"
+        );
+
+        // `buffered` keeps parts in order while up to `concurrency` are in flight;
+        // each part waits for its share of the bandwidth limit before it's requested
+        Ok(stream::iter(parts)
+            .map(move |(offset, length)| {
+                let etag = etag.clone();
+                async move {
+                    self.bandwidth.throttle(length).await;
+                    self.get_object_part(bucket, key, offset, length, etag.as_deref())
+                        .await
+                }
+            })
+            .buffered(options.concurrency)
+            .boxed())
+    }
+
+    /// Stream a specific object version's contents (a single GET)
+    pub async fn get_object_version_chunks(
+        &self,
+        bucket: &str,
+        key: &str,
+        version_id: &str,
+    ) -> Result<BoxStream<'static, Result<Bytes>>> {
+        let body = self
+            .get_object_version_stream(bucket, key, Some(version_id))
+            .await?;
+        Ok(self.body_chunks(body))
+    }
+
+    /// Adapt a response body to a stream of chunks, counting them in the metrics
+    /// and pacing them to the bandwidth limit
+    fn body_chunks(
+        &self,
+        body: aws_sdk_s3::primitives::ByteStream,
+    ) -> BoxStream<'static, Result<Bytes>> {
+        let metrics = self.metrics.clone();
+        let body = body.map({
+            let bandwidth = Arc::clone(&self.bandwidth);
+            move |body| bandwidth.throttle_body(body)
+        });
+        stream::try_unfold(body, |mut body| async move {
+            let chunk = body
+                .try_next()
+                .await
+                .context("Failed to read object body")?;
+            Ok(chunk.map(|chunk| (chunk, body)))
+        })
+        .inspect_ok(move |chunk| {
+            if let Some(metrics) = &metrics {
+                metrics.record_bytes(chunk.len() as u64);
+            }
+        })
+        .boxed()
+    }
+
+    /// Build a time-limited GET URL for an object, usable without credentials
+    /// HTTP sources are already plain URLs and are returned as-is.
+    pub async fn presign_get_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        expires_in: std::time::Duration,
+    ) -> Result<String> {
+        if is_http_origin(bucket) {
+            return Ok(format!("{bucket}/{key}"));
+        }
+
+        let presigning = aws_sdk_s3::presigning::PresigningConfig::expires_in(expires_in)
+            .context("Invalid presigned URL expiry")?;
+        let client = self.get_client_for_bucket(bucket).await?;
+        let request = client
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .presigned(presigning)
+            .await
+            .context(format!("Failed to presign s3://{bucket}/{key}"))?;
+
+        Ok(request.uri().to_string())
+    }
+
+    /// Run an S3 Select query against an object, returning a stream of result events
+    #[tracing::instrument(level = "debug", skip(self, request))]
+    pub async fn select_object_content(
+        &self,
+        bucket: &str,
+        key: &str,
+        request: &SelectRequest,
+    ) -> Result<SelectStream> {
+        let client = self.get_client_for_bucket(bucket).await?;
+
+        let mut input = InputSerialization::builder();
+        let csv_input = || {
+            CsvInput::builder().file_header_info(if request.csv_header {
+                FileHeaderInfo::Use
+            } else {
+                FileHeaderInfo::None
+            })
+        };
+        input = match request.input_format {
+            SelectFormat::Csv => input.csv(csv_input().build()),
+            SelectFormat::Tsv => input.csv(csv_input().field_delimiter("\t").build()),
+            SelectFormat::Json => {
+                input.json(JsonInput::builder().r#type(JsonType::Document).build())
+            }
+            SelectFormat::JsonLines => {
+                input.json(JsonInput::builder().r#type(JsonType::Lines).build())
+            }
+            SelectFormat::Parquet => input.parquet(ParquetInput::builder().build()),
+        };
+
+        // Parquet objects handle compression internally
+        if request.input_format != SelectFormat::Parquet {
+            let key_lower = key.to_lowercase();
+            if key_lower.ends_with(".gz") {
+                input = input.compression_type(CompressionType::Gzip);
+            } else if key_lower.ends_with(".bz2") {
+                input = input.compression_type(CompressionType::Bzip2);
+            }
+        }
+
+        let output = if request.output_json {
+            OutputSerialization::builder()
+                .json(JsonOutput::builder().record_delimiter("\n").build())
+                .build()
+        } else {
+            // TSV in, TSV out
+            let delimiter = match request.input_format {
+                SelectFormat::Tsv => "\t",
+                _ => ",",
+            };
+            OutputSerialization::builder()
+                .csv(CsvOutput::builder().field_delimiter(delimiter).build())
+                .build()
+        };
+
+        let resp = client
+            .select_object_content()
+            .bucket(bucket)
+            .key(key)
+            .expression(&request.expression)
+            .expression_type(ExpressionType::Sql)
+            .input_serialization(input.build())
+            .output_serialization(output)
+            .send()
+            .await
+            .context(format!("Failed to run S3 Select on s3://{bucket}/{key}"))?;
+
+        Ok(SelectStream {
+            receiver: resp.payload,
+        })
+    }
 }
 
 /// Information about an S3 bucket
@@ -345,6 +1624,25 @@ pub struct BucketInfo {
     pub creation_date: Option<String>,
 }
 
+/// A bucket's public access block settings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PublicAccessBlock {
+    pub block_public_acls: bool,
+    pub ignore_public_acls: bool,
+    pub block_public_policy: bool,
+    pub restrict_public_buckets: bool,
+}
+
+impl PublicAccessBlock {
+    /// Whether all four settings are on, which blocks all public access
+    pub fn blocks_all(&self) -> bool {
+        self.block_public_acls
+            && self.ignore_public_acls
+            && self.block_public_policy
+            && self.restrict_public_buckets
+    }
+}
+
 /// Result of listing objects in a bucket
 #[derive(Debug, Clone)]
 pub struct ListObjectsResult {
@@ -358,10 +1656,478 @@ pub struct ObjectInfo {
     pub key: String,
     pub size: u64,
     pub last_modified: Option<String>,
+    pub etag: Option<String>,
+    /// Storage class as listed (STANDARD, GLACIER, ...)
+    pub storage_class: Option<String>,
+    /// Owner display name, or canonical ID; only present when the listing fetched owners
+    pub owner: Option<String>,
+}
+
+impl ObjectInfo {
+    /// The storage class, which listings may leave out for STANDARD
+    pub fn effective_storage_class(&self) -> &str {
+        self.storage_class.as_deref().unwrap_or("STANDARD")
+    }
+}
+
+/// Result of listing object versions in a bucket
+#[derive(Debug, Clone)]
+pub struct ListVersionsResult {
+    pub prefixes: Vec<String>,
+    pub versions: Vec<ObjectVersionInfo>,
+}
+
+/// Information about one version of an S3 object
+#[derive(Debug, Clone)]
+pub struct ObjectVersionInfo {
+    pub key: String,
+    /// "null" for objects written before versioning was enabled
+    pub version_id: String,
+    pub size: u64,
+    pub last_modified: Option<String>,
+    pub is_latest: bool,
+    pub is_delete_marker: bool,
 }
 
 /// Metadata about an S3 object
 #[derive(Debug, Clone)]
 pub struct ObjectMetadata {
     pub size: u64,
+    pub last_modified: Option<String>,
+    pub content_type: Option<String>,
+    pub etag: Option<String>,
+    /// None when the bucket is not versioned
+    pub version_id: Option<String>,
+    /// None for STANDARD
+    pub storage_class: Option<String>,
+    /// User metadata (x-amz-meta-*), keys without the prefix
+    pub user_metadata: BTreeMap<String, String>,
+    /// System headers and encryption, kept when the object is rewritten
+    pub headers: ObjectHeaders,
+}
+
+/// Headers set when an object was uploaded, which rewriting it (a copy with REPLACE, or
+/// a PUT) would reset unless they're sent again
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ObjectHeaders {
+    pub content_encoding: Option<String>,
+    pub cache_control: Option<String>,
+    pub content_disposition: Option<String>,
+    pub content_language: Option<String>,
+    /// Expires, as sent
+    pub expires: Option<String>,
+    pub website_redirect_location: Option<String>,
+    /// "AES256", "aws:kms" or "aws:kms:dsse"
+    pub server_side_encryption: Option<String>,
+    pub sse_kms_key_id: Option<String>,
+    pub bucket_key_enabled: Option<bool>,
+}
+
+/// Apply the retry policy to a client, and count every API call it sends in the metrics
+/// if there are any
+fn instrument(
+    client: Client,
+    metrics: Option<&Arc<S3Metrics>>,
+    retry: &Arc<RwLock<RetryPolicy>>,
+) -> Client {
+    let mut config = client
+        .config()
+        .to_builder()
+        .interceptor(RetryPolicyInterceptor::new(Arc::clone(retry)))
+        .retry_classifier(RetryPolicyClassifier::new(Arc::clone(retry)));
+    if let Some(metrics) = metrics {
+        config = config.interceptor(MetricsInterceptor::new(Arc::clone(metrics)));
+    }
+    Client::from_conf(config.build())
+}
+
+/// Match a bucket name against a pattern where `*` matches any run of characters
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    // Without a `*` the whole name must match
+    let Some(last) = parts.pop() else {
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+/// Map a GetBucketLocation constraint to a region name
+/// us-east-1 buckets report no constraint, and very old EU buckets report "EU"
+fn region_from_location(constraint: Option<&str>) -> String {
+    match constraint {
+        None | Some("") => "us-east-1".to_string(),
+        Some("EU") => "eu-west-1".to_string(),
+        Some(region) => region.to_string(),
+    }
+}
+
+/// Check whether an error was caused by expired temporary or SSO credentials
+/// The error code only shows up in the SDK error's Debug output, so both forms are searched.
+pub fn is_expired_credentials_error(err: &anyhow::Error) -> bool {
+    const MARKERS: &[&str] = &[
+        "expiredtoken",
+        "tokenrefreshrequired",
+        "token has expired",
+        "token is expired",
+        "session has expired",
+    ];
+    err.chain().any(|cause| {
+        let text = format!("{cause} {cause:?}").to_lowercase();
+        MARKERS.iter().any(|marker| text.contains(marker))
+    })
+}
+
+/// Percent-encode a key for the CopySource header, keeping '/' separators
+fn encode_copy_source(key: &str) -> String {
+    let mut encoded = String::with_capacity(key.len());
+    for byte in key.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// Input format of an object queried with S3 Select
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectFormat {
+    Csv,
+    /// CSV with tabs between fields
+    Tsv,
+    /// A single JSON document
+    Json,
+    /// Newline-delimited JSON records
+    JsonLines,
+    Parquet,
+}
+
+impl SelectFormat {
+    /// Parse a format name as given on the command line
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "csv" => Some(SelectFormat::Csv),
+            "tsv" => Some(SelectFormat::Tsv),
+            "json" => Some(SelectFormat::Json),
+            "jsonl" | "ndjson" | "lines" => Some(SelectFormat::JsonLines),
+            "parquet" => Some(SelectFormat::Parquet),
+            _ => None,
+        }
+    }
+
+    /// Detect the format from an object key, ignoring .gz/.bz2 suffixes
+    pub fn from_key(key: &str) -> Option<Self> {
+        let key_lower = key.to_lowercase();
+        let base = key_lower
+            .strip_suffix(".gz")
+            .or_else(|| key_lower.strip_suffix(".bz2"))
+            .unwrap_or(&key_lower);
+        let extension = base.rsplit_once('.')?.1;
+        Self::parse(extension)
+    }
+}
+
+/// Parameters for an S3 Select query
+#[derive(Debug, Clone)]
+pub struct SelectRequest {
+    /// SQL expression to evaluate
+    pub expression: String,
+    /// Format of the queried object
+    pub input_format: SelectFormat,
+    /// Whether CSV (or TSV) input has a header row (enables column names in SQL)
+    pub csv_header: bool,
+    /// Emit JSON records instead of CSV rows
+    pub output_json: bool,
+}
+
+/// Event decoded from an S3 Select response stream
+#[derive(Debug, Clone)]
+pub enum SelectEvent {
+    /// A chunk of result records
+    Records(Bytes),
+    /// Final byte counts for the query
+    Stats {
+        bytes_scanned: u64,
+        bytes_processed: u64,
+        bytes_returned: u64,
+    },
+    /// End of the result stream
+    End,
+}
+
+/// Streaming results of an S3 Select query
+pub struct SelectStream {
+    receiver: EventReceiver<SelectObjectContentEventStream, SelectObjectContentEventStreamError>,
+}
+
+impl SelectStream {
+    /// Receive the next meaningful event, skipping progress and keep-alive messages
+    pub async fn next_event(&mut self) -> Result<Option<SelectEvent>> {
+        loop {
+            let event = self
+                .receiver
+                .recv()
+                .await
+                .context("Failed to read S3 Select response")?;
+
+            match event {
+                None => return Ok(None),
+                Some(SelectObjectContentEventStream::Records(records)) => {
+                    if let Some(payload) = records.payload() {
+                        return Ok(Some(SelectEvent::Records(Bytes::copy_from_slice(
+                            payload.as_ref(),
+                        ))));
+                    }
+                }
+                Some(SelectObjectContentEventStream::Stats(stats)) => {
+                    if let Some(details) = stats.details() {
+                        return Ok(Some(SelectEvent::Stats {
+                            bytes_scanned: details.bytes_scanned().unwrap_or(0) as u64,
+                            bytes_processed: details.bytes_processed().unwrap_or(0) as u64,
+                            bytes_returned: details.bytes_returned().unwrap_or(0) as u64,
+                        }));
+                    }
+                }
+                Some(SelectObjectContentEventStream::End(_)) => {
+                    return Ok(Some(SelectEvent::End));
+                }
+                // Continuation and progress events carry no result data
+                Some(_) => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_region_from_location() {
+        assert_eq!(region_from_location(None), "us-east-1");
+        assert_eq!(region_from_location(Some("")), "us-east-1");
+        assert_eq!(region_from_location(Some("EU")), "eu-west-1");
+        assert_eq!(region_from_location(Some("ap-south-1")), "ap-south-1");
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("public-data", "public-data"));
+        assert!(!wildcard_match("public-data", "public-data2"));
+        assert!(wildcard_match("work-*", "work-logs"));
+        assert!(wildcard_match("work-*", "work-"));
+        assert!(!wildcard_match("work-*", "my-work-logs"));
+        assert!(wildcard_match("*-logs", "prod-logs"));
+        assert!(wildcard_match("a*b*c", "a-b-b-c"));
+        assert!(!wildcard_match("ab*ba", "aba"));
+        assert!(wildcard_match("*", "anything"));
+    }
+
+    #[test]
+    fn test_is_expired_credentials_error() {
+        let expired = anyhow::anyhow!("ExpiredToken: The provided token has expired.")
+            .context("Failed to list S3 buckets");
+        assert!(is_expired_credentials_error(&expired));
+
+        let sso = anyhow::anyhow!("the SSO session has expired or is invalid");
+        assert!(is_expired_credentials_error(&sso));
+
+        let other = anyhow::anyhow!("NoSuchBucket").context("Failed to list objects");
+        assert!(!is_expired_credentials_error(&other));
+    }
+
+    #[test]
+    fn test_select_format_from_key() {
+        assert_eq!(SelectFormat::from_key("data.csv"), Some(SelectFormat::Csv));
+        assert_eq!(
+            SelectFormat::from_key("logs/2024/events.ndjson.gz"),
+            Some(SelectFormat::JsonLines)
+        );
+        assert_eq!(
+            SelectFormat::from_key("dump.JSON.bz2"),
+            Some(SelectFormat::Json)
+        );
+        assert_eq!(
+            SelectFormat::from_key("part-0.parquet"),
+            Some(SelectFormat::Parquet)
+        );
+        assert_eq!(
+            SelectFormat::from_key("export.tsv.gz"),
+            Some(SelectFormat::Tsv)
+        );
+        assert_eq!(SelectFormat::parse("TSV"), Some(SelectFormat::Tsv));
+        assert_eq!(SelectFormat::parse("csv"), Some(SelectFormat::Csv));
+        assert_eq!(SelectFormat::from_key("archive.tar.gz"), None);
+        assert_eq!(SelectFormat::from_key("README"), None);
+    }
+
+    #[test]
+    fn test_encode_copy_source() {
+        assert_eq!(encode_copy_source("logs/2024/app.log"), "logs/2024/app.log");
+        assert_eq!(
+            encode_copy_source("reports/Q1 summary+final.csv"),
+            "reports/Q1%20summary%2Bfinal.csv"
+        );
+        assert_eq!(encode_copy_source("café.txt"), "caf%C3%A9.txt");
+    }
+
+    #[tokio::test]
+    async fn test_rewrites_keep_headers() {
+        let mock = crate::s3::MockS3Client::new()
+            .with_object("b", "page.html.gz", "<html>")
+            .with_headers(
+                "b",
+                "page.html.gz",
+                &[
+                    ("content-encoding", "gzip"),
+                    ("cache-control", "max-age=60"),
+                    ("content-disposition", "inline"),
+                    ("content-language", "en"),
+                    ("expires", "Wed, 21 Oct 2026 07:28:00 GMT"),
+                    ("x-amz-website-redirect-location", "/other.html"),
+                    ("x-amz-server-side-encryption", "aws:kms"),
+                    ("x-amz-server-side-encryption-aws-kms-key-id", "key-1"),
+                    ("x-amz-server-side-encryption-bucket-key-enabled", "true"),
+                ],
+            );
+        let client = mock.client();
+        let before = client.head_object("b", "page.html.gz").await.unwrap();
+        assert_eq!(before.headers.content_encoding.as_deref(), Some("gzip"));
+        assert_eq!(before.headers.bucket_key_enabled, Some(true));
+
+        let metadata = BTreeMap::from([("owner".to_string(), "web".to_string())]);
+        client
+            .replace_user_metadata("b", "page.html.gz", &metadata)
+            .await
+            .unwrap();
+        let after = client.head_object("b", "page.html.gz").await.unwrap();
+        assert_eq!(after.user_metadata, metadata);
+        assert_eq!(after.headers, before.headers);
+
+        client
+            .replace_object_content("b", "page.html.gz", Bytes::from("<p>"), &after)
+            .await
+            .unwrap();
+        let edited = client.head_object("b", "page.html.gz").await.unwrap();
+        assert_eq!(edited.size, 3);
+        assert_eq!(edited.user_metadata, metadata);
+        assert_eq!(edited.headers, before.headers);
+    }
+
+    #[tokio::test]
+    async fn test_ranged_parts_pinned_to_etag() {
+        let mock = crate::s3::MockS3Client::new().with_object("b", "k", "0123456789abcdefghij");
+        let client = mock.client();
+        client.set_transfer_options(TransferOptions {
+            part_size: 4,
+            concurrency: 2,
+            ..TransferOptions::default()
+        });
+
+        // A stale listed size is corrected by the HEAD
+        let chunks: Vec<Bytes> = client
+            .get_object_chunks("b", "k", Some(8))
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(chunks.concat(), b"0123456789abcdefghij");
+
+        // Parts requested after the object changes are refused
+        let mut chunks = client.get_object_chunks("b", "k", Some(20)).await.unwrap();
+        assert_eq!(chunks.next().await.unwrap().unwrap(), "0123");
+        let _ = mock.with_object("b", "k", "ABCDEFGHIJKLMNOPQRST");
+        let err = chunks.try_collect::<Vec<_>>().await.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("changed while it was being downloaded")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_only_refuses_writes() {
+        let mock = crate::s3::MockS3Client::new().with_object("b", "k", "original");
+        let client = mock.client();
+        client.set_read_only(true);
+        let current = client.head_object("b", "k").await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let local = dir.path().join("upload.txt");
+        std::fs::write(&local, "new").unwrap();
+        mock.take_requests();
+
+        let metadata = BTreeMap::from([("owner".to_string(), "me".to_string())]);
+        let results = [
+            client.put_object("b", "new", Bytes::from("new")).await,
+            client.upload_file("b", "new", &local).await,
+            client.delete_object("b", "k").await,
+            client.replace_user_metadata("b", "k", &metadata).await,
+            client
+                .replace_object_content("b", "k", Bytes::from("new"), &current)
+                .await,
+        ];
+        for result in results {
+            assert!(
+                result
+                    .unwrap_err()
+                    .to_string()
+                    .starts_with("Read-only mode")
+            );
+        }
+
+        // Nothing reached S3, let alone a write
+        assert_eq!(mock.take_requests(), []);
+        assert_eq!(mock.object("b", "k").unwrap(), "original");
+        assert!(mock.object("b", "new").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_head_cache_follows_etag() {
+        let mock = crate::s3::MockS3Client::new()
+            .with_object("b", "a.txt", "a")
+            .with_object("b", "b.txt", "b");
+        let client = mock.client();
+        let heads = |mock: &crate::s3::MockS3Client| -> Vec<String> {
+            mock.take_requests()
+                .into_iter()
+                .filter(|request| request.method == "HEAD")
+                .map(|request| request.key)
+                .collect()
+        };
+        let head_listing = async || {
+            let listing = client.list_objects("b", "", None).await.unwrap();
+            let objects: Vec<&ObjectInfo> = listing.objects.iter().collect();
+            client
+                .head_listed_objects("b", &objects)
+                .await
+                .into_iter()
+                .map(|metadata| metadata.unwrap().size)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(head_listing().await, [1, 1]);
+        assert_eq!(heads(&mock), ["a.txt", "b.txt"]);
+
+        // Unchanged objects are served from the cache
+        assert_eq!(head_listing().await, [1, 1]);
+        assert!(heads(&mock).is_empty());
+
+        // A new ETag means the object changed, so it's fetched again
+        let _ = mock.clone().with_object("b", "b.txt", "changed");
+        assert_eq!(head_listing().await, [1, 7]);
+        assert_eq!(heads(&mock), ["b.txt"]);
+    }
 }
@@ -0,0 +1,190 @@
+//! HTTP connection tuning for the SDK client: timeouts, connection pooling, and a cap
+//! on requests in flight.
+//!
+//! The cap is a semaphore wrapped around the SDK's HTTP connector. A permit is held
+//! until the response body has been read or dropped, so streamed downloads count
+//! for as long as they're transferring.
+
+use aws_sdk_s3::config::timeout::TimeoutConfig;
+use aws_sdk_s3::primitives::SdkBody;
+use aws_smithy_http_client::proxy::ProxyConfig;
+use aws_smithy_http_client::tls::{self, rustls_provider::CryptoMode};
+use aws_smithy_http_client::{Builder, ConnectorBuilder};
+use aws_smithy_runtime_api::client::http::{
+    HttpConnector, HttpConnectorFuture, SharedHttpClient, SharedHttpConnector, http_client_fn,
+};
+use aws_smithy_runtime_api::client::orchestrator::HttpRequest;
+use aws_smithy_runtime_api::client::result::ConnectorError;
+use bytes::Bytes;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Connection settings for S3 and HTTP(S) sources (None = the client's default)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HttpOptions {
+    /// Give up on establishing a connection after this long
+    pub connect_timeout: Option<Duration>,
+    /// Give up when a response stalls (no bytes) for this long
+    pub read_timeout: Option<Duration>,
+    /// Close pooled connections that have been idle this long
+    pub idle_timeout: Option<Duration>,
+    /// Idle connections kept per host (HTTP(S) sources only; the SDK doesn't expose it)
+    pub max_idle_connections: Option<usize>,
+    /// S3 requests in flight at once, across all commands
+    pub max_concurrent_requests: Option<usize>,
+}
+
+impl HttpOptions {
+    /// The SDK timeout configuration, keeping `base`'s values for anything not set
+    pub fn timeout_config(&self, base: Option<&TimeoutConfig>) -> Option<TimeoutConfig> {
+        if self.connect_timeout.is_none() && self.read_timeout.is_none() {
+            return None;
+        }
+        let mut builder = base.map(TimeoutConfig::to_builder).unwrap_or_default();
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.read_timeout {
+            builder = builder.read_timeout(timeout);
+        }
+        Some(builder.build())
+    }
+
+    /// An SDK HTTP client honoring the pool and concurrency settings
+    /// None when neither is set, so the SDK's default client is used.
+    pub fn http_client(&self) -> Option<SharedHttpClient> {
+        if self.idle_timeout.is_none() && self.max_concurrent_requests.is_none() {
+            return None;
+        }
+        let client = default_client(self.idle_timeout);
+        Some(match self.max_concurrent_requests {
+            Some(limit) => limit_requests(client, limit),
+            None => client,
+        })
+    }
+
+    /// Apply the settings to a reqwest client for HTTP(S) sources
+    pub fn apply_to(&self, mut builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.read_timeout {
+            builder = builder.read_timeout(timeout);
+        }
+        if let Some(timeout) = self.idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if let Some(max) = self.max_idle_connections {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        builder
+    }
+}
+
+/// The SDK's default HTTPS client (rustls, proxies from the environment), with an idle timeout
+fn default_client(idle_timeout: Option<Duration>) -> SharedHttpClient {
+    Builder::new().build_with_connector_fn(move |settings, runtime_components| {
+        let mut builder =
+            ConnectorBuilder::default().tls_provider(tls::Provider::Rustls(CryptoMode::AwsLc));
+        builder.set_connector_settings(settings.cloned());
+        if let Some(components) = runtime_components {
+            builder.set_sleep_impl(components.sleep_impl());
+        }
+        builder.set_proxy_config(Some(ProxyConfig::from_env()));
+        if let Some(timeout) = idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        builder.build()
+    })
+}
+
+/// Wrap a client so at most `limit` requests are in flight at once
+fn limit_requests(client: SharedHttpClient, limit: usize) -> SharedHttpClient {
+    let permits = Arc::new(Semaphore::new(limit.max(1)));
+    http_client_fn(move |settings, components| {
+        use aws_smithy_runtime_api::client::http::HttpClient;
+        SharedHttpConnector::new(LimitedConnector {
+            inner: client.http_connector(settings, components),
+            permits: Arc::clone(&permits),
+        })
+    })
+}
+
+#[derive(Debug)]
+struct LimitedConnector {
+    inner: SharedHttpConnector,
+    permits: Arc<Semaphore>,
+}
+
+impl HttpConnector for LimitedConnector {
+    fn call(&self, request: HttpRequest) -> HttpConnectorFuture {
+        let inner = self.inner.clone();
+        let permits = Arc::clone(&self.permits);
+        HttpConnectorFuture::new(async move {
+            let permit = permits
+                .acquire_owned()
+                .await
+                .map_err(|e| ConnectorError::other(e.into(), None))?;
+            let mut response = inner.call(request).await?;
+            let body = response.take_body();
+            *response.body_mut() = SdkBody::from_body_1_x(PermitBody {
+                body,
+                _permit: permit,
+            });
+            Ok(response)
+        })
+    }
+}
+
+/// Response body that returns its request's permit once it's read or dropped
+struct PermitBody {
+    body: SdkBody,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl http_body::Body for PermitBody {
+    type Data = Bytes;
+    type Error = <SdkBody as http_body::Body>::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<http_body::Frame<Bytes>, Self::Error>>> {
+        Pin::new(&mut self.get_mut().body).poll_frame(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.body.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeout_config() {
+        assert!(HttpOptions::default().timeout_config(None).is_none());
+        assert!(HttpOptions::default().http_client().is_none());
+
+        let base = TimeoutConfig::builder()
+            .connect_timeout(Duration::from_secs(3))
+            .operation_timeout(Duration::from_secs(60))
+            .build();
+        let options = HttpOptions {
+            read_timeout: Some(Duration::from_secs(30)),
+            ..Default::default()
+        };
+        let config = options.timeout_config(Some(&base)).unwrap();
+        assert_eq!(config.read_timeout(), Some(Duration::from_secs(30)));
+        assert_eq!(config.connect_timeout(), Some(Duration::from_secs(3)));
+        assert_eq!(config.operation_timeout(), Some(Duration::from_secs(60)));
+    }
+}
@@ -0,0 +1,170 @@
+use anyhow::{Context, Result, anyhow};
+use bytes::Bytes;
+use reqwest::StatusCode;
+use reqwest::header::{CONTENT_RANGE, CONTENT_TYPE, ETAG, LAST_MODIFIED, RANGE};
+use std::collections::BTreeMap;
+
+use super::client::ObjectMetadata;
+use super::connection::HttpOptions;
+
+/// Check whether a "bucket" is really the origin of an HTTP(S) URL
+/// HTTP sources reuse the bucket/key plumbing with bucket = "https://host" and key = the path.
+pub fn is_http_origin(bucket: &str) -> bool {
+    bucket.starts_with("https://") || bucket.starts_with("http://")
+}
+
+/// Split an HTTP(S) URL into (origin, path) so it can stand in for (bucket, key)
+pub fn split_url(url: &str) -> Option<(String, String)> {
+    let scheme_len = if url.starts_with("https://") {
+        "https://".len()
+    } else if url.starts_with("http://") {
+        "http://".len()
+    } else {
+        return None;
+    };
+
+    let (host, path) = url[scheme_len..].split_once('/')?;
+    let path = path.trim_end_matches('/');
+    if host.is_empty() || path.is_empty() {
+        return None;
+    }
+    Some((format!("{}{host}", &url[..scheme_len]), path.to_string()))
+}
+
+/// Client for files served over plain HTTP(S) by servers that honor Range requests
+#[derive(Clone, Default)]
+pub struct HttpSource {
+    client: reqwest::Client,
+}
+
+impl HttpSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Client with the given timeouts and pool settings
+    pub fn with_options(options: &HttpOptions) -> Self {
+        let client = options
+            .apply_to(reqwest::Client::builder())
+            .build()
+            .unwrap_or_default();
+        HttpSource { client }
+    }
+
+    /// Fetch size and headers with a one-byte range request
+    /// Unlike HEAD this also proves the server supports ranges, which archive indexing relies on.
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn head(&self, origin: &str, path: &str) -> Result<ObjectMetadata> {
+        let url = format!("{origin}/{path}");
+        let resp = self
+            .client
+            .get(&url)
+            .header(RANGE, "bytes=0-0")
+            .send()
+            .await
+            .context(format!("Failed to fetch {url}"))?
+            .error_for_status()
+            .context(format!("Failed to fetch {url}"))?;
+
+        if resp.status() != StatusCode::PARTIAL_CONTENT {
+            return Err(anyhow!("{url} does not support Range requests"));
+        }
+
+        let header = |name| {
+            resp.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(String::from)
+        };
+        let size = header(CONTENT_RANGE)
+            .as_deref()
+            .and_then(parse_content_range_size)
+            .ok_or_else(|| anyhow!("{url} did not report its size in Content-Range"))?;
+
+        Ok(ObjectMetadata {
+            size,
+            last_modified: header(LAST_MODIFIED),
+            content_type: header(CONTENT_TYPE),
+            etag: header(ETAG),
+            version_id: None,
+            storage_class: None,
+            user_metadata: BTreeMap::new(),
+            headers: Default::default(),
+        })
+    }
+
+    /// Read the whole file
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get(&self, origin: &str, path: &str) -> Result<Bytes> {
+        let url = format!("{origin}/{path}");
+        self.client
+            .get(&url)
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status())
+            .context(format!("Failed to fetch {url}"))?
+            .bytes()
+            .await
+            .context("Failed to read response body")
+    }
+
+    /// Read `length` bytes starting at `offset`
+    #[tracing::instrument(level = "debug", skip(self))]
+    pub async fn get_range(
+        &self,
+        origin: &str,
+        path: &str,
+        offset: u64,
+        length: u64,
+    ) -> Result<Bytes> {
+        let url = format!("{origin}/{path}");
+        let resp = self
+            .client
+            .get(&url)
+            .header(RANGE, format!("bytes={}-{}", offset, offset + length - 1))
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status())
+            .context(format!("Failed to fetch range of {url}"))?;
+
+        // A 200 means the server ignored the range and is sending the whole file
+        if resp.status() != StatusCode::PARTIAL_CONTENT {
+            return Err(anyhow!("{url} does not support Range requests"));
+        }
+
+        resp.bytes().await.context("Failed to read response body")
+    }
+}
+
+/// Parse the total size out of a `Content-Range: bytes 0-0/12345` header
+fn parse_content_range_size(value: &str) -> Option<u64> {
+    value.rsplit_once('/')?.1.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_url() {
+        assert_eq!(
+            split_url("https://example.com/data/archive.zip"),
+            Some((
+                "https://example.com".to_string(),
+                "data/archive.zip".to_string()
+            ))
+        );
+        assert_eq!(
+            split_url("http://localhost:8080/a.parquet"),
+            Some(("http://localhost:8080".to_string(), "a.parquet".to_string()))
+        );
+        assert_eq!(split_url("https://example.com/"), None);
+        assert_eq!(split_url("/bucket/key"), None);
+    }
+
+    #[test]
+    fn test_parse_content_range_size() {
+        assert_eq!(parse_content_range_size("bytes 0-0/12345"), Some(12345));
+        assert_eq!(parse_content_range_size("bytes 0-0/*"), None);
+    }
+}
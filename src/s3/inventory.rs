@@ -0,0 +1,383 @@
+//! S3 Inventory reports for `ls -R --summarize`.
+//!
+//! A bucket with an inventory configured in the `[inventory]` config table is summarized
+//! from its latest inventory report (CSV, or Parquet with the `parquet` feature) instead
+//! of listing every prefix. Reports are at most a day old, so nothing else reads them:
+//! listings, and anything that acts on the objects, go to S3 live.
+
+use anyhow::{Context, Result, anyhow};
+use async_compression::tokio::bufread::GzipDecoder;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+use super::S3Client;
+use super::client::ObjectInfo;
+
+/// Every object in a bucket as of an inventory report, sorted by key
+#[derive(Debug)]
+pub struct Inventory {
+    /// The report's dated folder, e.g. "2024-01-01T01-00Z"
+    pub snapshot: String,
+    objects: Vec<ObjectInfo>,
+}
+
+impl Inventory {
+    /// The objects whose keys start with `prefix`
+    pub fn under(&self, prefix: &str) -> &[ObjectInfo] {
+        let start = self
+            .objects
+            .partition_point(|obj| obj.key.as_str() < prefix);
+        let len = self.objects[start..].partition_point(|obj| obj.key.starts_with(prefix));
+        &self.objects[start..start + len]
+    }
+}
+
+/// Where each bucket's inventory lives, and the reports loaded so far this session
+#[derive(Debug, Default)]
+pub struct InventoryStore {
+    /// Bucket name to s3://destination/prefix/ (or a manifest.json)
+    locations: RwLock<BTreeMap<String, String>>,
+    /// Reports by location, so changing a bucket's location loads the new one
+    loaded: tokio::sync::Mutex<HashMap<String, Arc<Inventory>>>,
+}
+
+impl InventoryStore {
+    /// Replace the configured locations
+    pub fn set_locations(&self, locations: BTreeMap<String, String>) {
+        *self.locations.write().unwrap() = locations;
+    }
+
+    /// The bucket's inventory, loading it on first use; None if it has none configured
+    pub async fn get(&self, client: &S3Client, bucket: &str) -> Result<Option<Arc<Inventory>>> {
+        let Some(location) = self.locations.read().unwrap().get(bucket).cloned() else {
+            return Ok(None);
+        };
+        let mut loaded = self.loaded.lock().await;
+        if let Some(inventory) = loaded.get(&location) {
+            return Ok(Some(Arc::clone(inventory)));
+        }
+        let inventory = Arc::new(load(client, &location).await?);
+        loaded.insert(location, Arc::clone(&inventory));
+        Ok(Some(inventory))
+    }
+}
+
+/// Read the newest report under an inventory location
+async fn load(client: &S3Client, location: &str) -> Result<Inventory> {
+    let path = location
+        .strip_prefix("s3://")
+        .ok_or_else(|| anyhow!("Inventory location must be an s3:// URL: {location}"))?;
+    let (bucket, prefix) = path.split_once('/').unwrap_or((path, ""));
+
+    let manifest_key = if prefix.ends_with("manifest.json") {
+        prefix.to_string()
+    } else {
+        latest_manifest(client, bucket, prefix).await?
+    };
+    let manifest: serde_json::Value =
+        serde_json::from_slice(&client.get_object(bucket, &manifest_key).await?)
+            .with_context(|| format!("Invalid inventory manifest s3://{bucket}/{manifest_key}"))?;
+    let snapshot = manifest_key
+        .trim_end_matches("manifest.json")
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+
+    let format = manifest["fileFormat"].as_str().unwrap_or_default();
+    let files: Vec<&str> = manifest["files"]
+        .as_array()
+        .map(|files| files.iter().filter_map(|f| f["key"].as_str()).collect())
+        .unwrap_or_default();
+
+    let mut objects = Vec::new();
+    match format {
+        "CSV" => {
+            let schema = manifest["fileSchema"]
+                .as_str()
+                .ok_or_else(|| anyhow!("Inventory manifest has no fileSchema"))?;
+            let columns = CsvColumns::new(schema)?;
+            for key in files {
+                read_csv(client, bucket, key, &columns, &mut objects).await?;
+            }
+        }
+        #[cfg(feature = "parquet")]
+        "Parquet" => {
+            for key in files {
+                read_parquet(client, bucket, key, &mut objects).await?;
+            }
+        }
+        other => return Err(anyhow!("Unsupported inventory format: {other}")),
+    }
+
+    objects.sort_by(|a, b| a.key.cmp(&b.key));
+    Ok(Inventory { snapshot, objects })
+}
+
+/// The manifest in the newest dated folder that has one (the newest may still be written)
+async fn latest_manifest(client: &S3Client, bucket: &str, prefix: &str) -> Result<String> {
+    let prefix = if prefix.is_empty() || prefix.ends_with('/') {
+        prefix.to_string()
+    } else {
+        format!("{prefix}/")
+    };
+    let mut folders = Vec::new();
+    let mut token = None;
+    loop {
+        let (listing, next) = client
+            .list_objects_page(bucket, &prefix, Some("/"), token)
+            .await?;
+        // Reports are in folders named for their date, e.g. 2024-01-01T01-00Z/
+        folders.extend(
+            listing
+                .prefixes
+                .into_iter()
+                .filter(|p| p[prefix.len()..].starts_with(|c: char| c.is_ascii_digit())),
+        );
+        match next {
+            Some(next) => token = Some(next),
+            None => break,
+        }
+    }
+    folders.sort();
+
+    for folder in folders.iter().rev().take(3) {
+        let key = format!("{folder}manifest.json");
+        if client.prefix_exists(bucket, &key).await? {
+            return Ok(key);
+        }
+    }
+    Err(anyhow!("No inventory reports in s3://{bucket}/{prefix}"))
+}
+
+/// Positions of the fields an [`ObjectInfo`] needs in the manifest's CSV schema
+#[derive(Debug, PartialEq)]
+struct CsvColumns {
+    key: usize,
+    size: Option<usize>,
+    last_modified: Option<usize>,
+    etag: Option<usize>,
+    storage_class: Option<usize>,
+    is_latest: Option<usize>,
+    is_delete_marker: Option<usize>,
+}
+
+impl CsvColumns {
+    fn new(schema: &str) -> Result<Self> {
+        let names: Vec<&str> = schema.split(',').map(str::trim).collect();
+        let find = |name: &str| names.iter().position(|n| *n == name);
+        Ok(CsvColumns {
+            key: find("Key").ok_or_else(|| anyhow!("Inventory schema has no Key column"))?,
+            size: find("Size"),
+            last_modified: find("LastModifiedDate"),
+            etag: find("ETag"),
+            storage_class: find("StorageClass"),
+            is_latest: find("IsLatest"),
+            is_delete_marker: find("IsDeleteMarker"),
+        })
+    }
+
+    /// An object from one CSV record, or None for noncurrent versions and delete markers
+    fn object(&self, fields: &[String]) -> Option<ObjectInfo> {
+        let field = |i: Option<usize>| i.and_then(|i| fields.get(i)).filter(|f| !f.is_empty());
+        if field(self.is_latest).is_some_and(|f| f != "true")
+            || field(self.is_delete_marker).is_some_and(|f| f == "true")
+        {
+            return None;
+        }
+        Some(ObjectInfo {
+            key: percent_decode(fields.get(self.key)?),
+            size: field(self.size).and_then(|f| f.parse().ok()).unwrap_or(0),
+            last_modified: field(self.last_modified).cloned(),
+            etag: field(self.etag).cloned(),
+            storage_class: field(self.storage_class).cloned(),
+            owner: None,
+        })
+    }
+}
+
+/// Stream one gzipped CSV report file into `objects`
+async fn read_csv(
+    client: &S3Client,
+    bucket: &str,
+    key: &str,
+    columns: &CsvColumns,
+    objects: &mut Vec<ObjectInfo>,
+) -> Result<()> {
+    let body = client.get_object_stream(bucket, key).await?;
+    let mut lines = BufReader::new(GzipDecoder::new(body.into_async_read())).lines();
+    while let Some(line) = lines
+        .next_line()
+        .await
+        .with_context(|| format!("Failed to read inventory file s3://{bucket}/{key}"))?
+    {
+        objects.extend(columns.object(&split_csv_line(&line)));
+    }
+    Ok(())
+}
+
+/// Read one Parquet report file into `objects`
+#[cfg(feature = "parquet")]
+async fn read_parquet(
+    client: &S3Client,
+    bucket: &str,
+    key: &str,
+    objects: &mut Vec<ObjectInfo>,
+) -> Result<()> {
+    use arrow_array::{
+        Array, BooleanArray, Int64Array, RecordBatch, StringArray, TimestampMillisecondArray,
+    };
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    fn column<'a, T: 'static>(batch: &'a RecordBatch, name: &str) -> Option<&'a T> {
+        batch.column_by_name(name)?.as_any().downcast_ref::<T>()
+    }
+
+    let bytes = client.get_object(bucket, key).await?;
+    let reader = ParquetRecordBatchReaderBuilder::try_new(bytes)
+        .and_then(|builder| builder.build())
+        .with_context(|| format!("Invalid inventory file s3://{bucket}/{key}"))?;
+    for batch in reader {
+        let batch = batch?;
+        let keys = column::<StringArray>(&batch, "key")
+            .ok_or_else(|| anyhow!("Inventory file has no key column"))?;
+        let sizes = column::<Int64Array>(&batch, "size");
+        let modified = column::<TimestampMillisecondArray>(&batch, "last_modified_date");
+        let etags = column::<StringArray>(&batch, "e_tag");
+        let classes = column::<StringArray>(&batch, "storage_class");
+        let is_latest = column::<BooleanArray>(&batch, "is_latest");
+        let is_delete_marker = column::<BooleanArray>(&batch, "is_delete_marker");
+
+        for row in 0..batch.num_rows() {
+            if is_latest.is_some_and(|c| c.is_valid(row) && !c.value(row))
+                || is_delete_marker.is_some_and(|c| c.is_valid(row) && c.value(row))
+            {
+                continue;
+            }
+            let string = |c: Option<&StringArray>| {
+                c.filter(|c| c.is_valid(row))
+                    .map(|c| c.value(row).to_string())
+            };
+            objects.push(ObjectInfo {
+                key: keys.value(row).to_string(),
+                size: sizes
+                    .filter(|c| c.is_valid(row))
+                    .map_or(0, |c| c.value(row) as u64),
+                last_modified: modified
+                    .filter(|c| c.is_valid(row))
+                    .and_then(|c| chrono::DateTime::from_timestamp_millis(c.value(row)))
+                    .map(|t| t.format("%Y-%m-%dT%H:%M:%SZ").to_string()),
+                etag: string(etags),
+                storage_class: string(classes),
+                owner: None,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Split a CSV record whose fields may be quoted (with "" for a literal quote)
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Inventory CSV keys are URL-encoded
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%')
+            .then(|| s.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (_, Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+            (b'+', None) => decoded.push(b' '),
+            (byte, None) => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_records() {
+        assert_eq!(
+            split_csv_line(r#""bkt","a%2Fb+c.txt","12","2024-01-01T00:00:00.000Z","say ""hi""""#),
+            vec![
+                "bkt",
+                "a%2Fb+c.txt",
+                "12",
+                "2024-01-01T00:00:00.000Z",
+                "say \"hi\""
+            ]
+        );
+        assert_eq!(percent_decode("logs%2F2024+01%20x%zz"), "logs/2024 01 x%zz");
+
+        let columns =
+            CsvColumns::new("Bucket, Key, VersionId, IsLatest, IsDeleteMarker, Size").unwrap();
+        let record = |fields: &[&str]| {
+            columns.object(&fields.iter().map(|f| f.to_string()).collect::<Vec<_>>())
+        };
+        let obj = record(&["bkt", "logs/a.txt", "v1", "true", "false", "12"]).unwrap();
+        assert_eq!((obj.key.as_str(), obj.size), ("logs/a.txt", 12));
+        assert!(record(&["bkt", "logs/a.txt", "v0", "false", "false", "10"]).is_none());
+        assert!(record(&["bkt", "logs/b.txt", "v2", "true", "true", ""]).is_none());
+        assert!(CsvColumns::new("Bucket, Size").is_err());
+    }
+
+    #[test]
+    fn test_under() {
+        let inventory = Inventory {
+            snapshot: String::new(),
+            objects: ["a/1", "a/2", "ab", "b/1"]
+                .into_iter()
+                .map(|key| ObjectInfo {
+                    key: key.to_string(),
+                    size: 1,
+                    last_modified: None,
+                    etag: None,
+                    storage_class: None,
+                    owner: None,
+                })
+                .collect(),
+        };
+        let keys = |prefix| {
+            inventory
+                .under(prefix)
+                .iter()
+                .map(|o| o.key.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(keys("a/"), ["a/1", "a/2"]);
+        assert_eq!(keys(""), ["a/1", "a/2", "ab", "b/1"]);
+        assert!(keys("c").is_empty());
+    }
+}
@@ -0,0 +1,142 @@
+//! Short-lived cache of directory listings.
+//!
+//! `ls` and tab completion list the same few prefixes over and over while browsing.
+//! The first page of each '/'-delimited listing is kept for a short while, so going back
+//! and forth between directories doesn't re-issue ListObjects every time. Changes made
+//! through the client drop the listings they affect; `ls --no-cache` and `refresh` drop
+//! one explicitly.
+
+use lru::LruCache;
+use std::num::NonZeroUsize;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use super::client::ListObjectsResult;
+
+/// How long a listing is reused by default
+pub const DEFAULT_LISTING_TTL: Duration = Duration::from_secs(30);
+
+/// Listings kept at once
+const LISTING_CACHE_SIZE: usize = 256;
+
+/// Listings by (bucket, prefix), with when they were fetched
+pub struct ListingCache {
+    ttl: RwLock<Duration>,
+    listings: Mutex<LruCache<(String, String), (Instant, ListObjectsResult)>>,
+}
+
+impl Default for ListingCache {
+    fn default() -> Self {
+        ListingCache {
+            ttl: RwLock::new(DEFAULT_LISTING_TTL),
+            listings: Mutex::new(LruCache::new(
+                NonZeroUsize::new(LISTING_CACHE_SIZE).unwrap(),
+            )),
+        }
+    }
+}
+
+impl ListingCache {
+    /// How long listings are reused (zero = never)
+    pub fn ttl(&self) -> Duration {
+        *self.ttl.read().unwrap()
+    }
+
+    pub fn set_ttl(&self, ttl: Duration) {
+        *self.ttl.write().unwrap() = ttl;
+        if ttl.is_zero() {
+            self.clear();
+        }
+    }
+
+    /// A listing fetched less than the TTL ago
+    pub fn get(&self, bucket: &str, prefix: &str) -> Option<ListObjectsResult> {
+        let ttl = self.ttl();
+        let mut listings = self.listings.lock().unwrap();
+        let key = (bucket.to_string(), prefix.to_string());
+        match listings.get(&key) {
+            Some((fetched, result)) if fetched.elapsed() < ttl => Some(result.clone()),
+            Some(_) => {
+                listings.pop(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn put(&self, bucket: &str, prefix: &str, result: &ListObjectsResult) {
+        if self.ttl().is_zero() {
+            return;
+        }
+        self.listings.lock().unwrap().put(
+            (bucket.to_string(), prefix.to_string()),
+            (Instant::now(), result.clone()),
+        );
+    }
+
+    /// Forget one listing, so it's fetched again next time
+    pub fn remove(&self, bucket: &str, prefix: &str) {
+        self.listings
+            .lock()
+            .unwrap()
+            .pop(&(bucket.to_string(), prefix.to_string()));
+    }
+
+    /// Drop the listings a change to `key` makes stale: those of the prefixes containing
+    /// it and, when `key` is itself a prefix (or "" for the whole bucket), of everything
+    /// below it
+    pub fn invalidate(&self, bucket: &str, key: &str) {
+        let mut listings = self.listings.lock().unwrap();
+        let stale: Vec<_> = listings
+            .iter()
+            .map(|(entry, _)| entry)
+            .filter(|(b, prefix)| {
+                b == bucket
+                    && (key.starts_with(prefix.as_str())
+                        || ((key.is_empty() || key.ends_with('/')) && prefix.starts_with(key)))
+            })
+            .cloned()
+            .collect();
+        for entry in stale {
+            listings.pop(&entry);
+        }
+    }
+
+    pub fn clear(&self) {
+        self.listings.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_listing_cache() {
+        let cache = ListingCache::default();
+        let listing = ListObjectsResult {
+            prefixes: vec!["logs/2024/".to_string()],
+            objects: Vec::new(),
+        };
+        for prefix in ["", "logs/", "logs/2024/", "other/"] {
+            cache.put("b", prefix, &listing);
+        }
+        cache.put("c", "", &listing);
+        assert!(cache.get("b", "logs/").is_some());
+
+        cache.invalidate("b", "logs/app.log");
+        let cached = |bucket: &str, prefix: &str| cache.get(bucket, prefix).is_some();
+        assert!(!cached("b", "") && !cached("b", "logs/"));
+        assert!(cached("b", "logs/2024/") && cached("b", "other/") && cached("c", ""));
+
+        // A prefix takes everything below it along
+        cache.invalidate("b", "logs/");
+        assert!(!cached("b", "logs/2024/") && cached("b", "other/"));
+        cache.invalidate("b", "");
+        assert!(!cached("b", "other/") && cached("c", ""));
+
+        cache.set_ttl(Duration::ZERO);
+        cache.put("b", "other/", &listing);
+        assert!(!cached("b", "other/") && !cached("c", ""));
+    }
+}
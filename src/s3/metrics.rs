@@ -2,7 +2,17 @@
 //!
 //! This module provides thread-safe tracking of S3 request metrics including
 //! bytes transferred, request count, and timing information.
+//! [`MetricsInterceptor`] counts every API call the SDK sends, by operation,
+//! and how many of them were retries.
 
+use aws_sdk_s3::config::interceptors::{
+    BeforeDeserializationInterceptorContextRef, BeforeTransmitInterceptorContextRef,
+};
+use aws_sdk_s3::config::{ConfigBag, Intercept, RuntimeComponents};
+use aws_smithy_runtime_api::box_error::BoxError;
+use aws_smithy_runtime_api::client::orchestrator::Metadata;
+use aws_smithy_runtime_api::client::retries::RequestAttempts;
+use std::collections::BTreeMap;
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
@@ -26,8 +36,10 @@ pub struct RequestMetric {
 /// bytes transferred, and timing information.
 #[derive(Debug, Default)]
 pub struct S3Metrics {
-    /// Total bytes transferred
+    /// Total bytes downloaded
     total_bytes: AtomicU64,
+    /// Total bytes uploaded
+    uploaded_bytes: AtomicU64,
     /// Total number of requests
     request_count: AtomicUsize,
     /// Total time spent in requests (nanoseconds)
@@ -36,6 +48,62 @@ pub struct S3Metrics {
     requests: RwLock<Vec<RequestMetric>>,
     /// Start time of the operation
     operation_start: RwLock<Option<Instant>>,
+    /// API calls sent, by operation name (e.g. "ListObjectsV2")
+    operations: RwLock<BTreeMap<String, usize>>,
+    /// Attempts that were retries of a failed call
+    retries: AtomicUsize,
+}
+
+/// What a span of work (a command, a session) cost in S3 calls and bytes
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetricsSummary {
+    /// API calls by operation name; retries count as separate calls
+    pub operations: BTreeMap<String, usize>,
+    /// How many of those calls were retries
+    pub retries: usize,
+    /// Bytes downloaded
+    pub downloaded: u64,
+    /// Bytes uploaded
+    pub uploaded: u64,
+    /// Wall-clock time
+    pub elapsed: Duration,
+}
+
+impl MetricsSummary {
+    /// Total API calls
+    pub fn requests(&self) -> usize {
+        self.operations.values().sum()
+    }
+
+    /// Add another summary's counts to this one
+    pub fn add(&mut self, other: &MetricsSummary) {
+        for (operation, count) in &other.operations {
+            *self.operations.entry(operation.clone()).or_default() += count;
+        }
+        self.retries += other.retries;
+        self.downloaded += other.downloaded;
+        self.uploaded += other.uploaded;
+        self.elapsed += other.elapsed;
+    }
+
+    /// What was added since `start`, an earlier summary from the same collector
+    pub fn since(&self, start: &MetricsSummary) -> MetricsSummary {
+        let operations = self
+            .operations
+            .iter()
+            .filter_map(|(operation, count)| {
+                let before = start.operations.get(operation).copied().unwrap_or(0);
+                (*count > before).then(|| (operation.clone(), count - before))
+            })
+            .collect();
+        MetricsSummary {
+            operations,
+            retries: self.retries.saturating_sub(start.retries),
+            downloaded: self.downloaded.saturating_sub(start.downloaded),
+            uploaded: self.uploaded.saturating_sub(start.uploaded),
+            elapsed: self.elapsed.saturating_sub(start.elapsed),
+        }
+    }
 }
 
 impl S3Metrics {
@@ -66,11 +134,72 @@ impl S3Metrics {
         });
     }
 
-    /// Get total bytes transferred
+    /// Record bytes of a streamed body as they arrive
+    /// The request itself is neither counted nor timed, as it may never complete.
+    pub fn record_bytes(&self, bytes: u64) {
+        self.total_bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Record a completed upload
+    pub fn record_upload(&self, bytes: u64, duration: Duration) {
+        self.uploaded_bytes.fetch_add(bytes, Ordering::Relaxed);
+        self.request_count.fetch_add(1, Ordering::Relaxed);
+        self.total_request_time_ns
+            .fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// Record an API call that got a response
+    pub fn record_operation(&self, operation: &str) {
+        let mut operations = self.operations.write().unwrap();
+        match operations.get_mut(operation) {
+            Some(count) => *count += 1,
+            None => {
+                operations.insert(operation.to_string(), 1);
+            }
+        }
+    }
+
+    /// Record an attempt that retries a failed call
+    pub fn record_retry(&self) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Get the number of retried attempts
+    pub fn retries(&self) -> usize {
+        self.retries.load(Ordering::Relaxed)
+    }
+
+    /// Get the API calls sent, by operation name
+    pub fn operations(&self) -> BTreeMap<String, usize> {
+        self.operations.read().unwrap().clone()
+    }
+
+    /// Snapshot the calls, bytes, and time recorded since the last reset
+    pub fn summary(&self) -> MetricsSummary {
+        MetricsSummary {
+            operations: self.operations(),
+            retries: self.retries(),
+            downloaded: self.total_bytes(),
+            uploaded: self.uploaded_bytes(),
+            elapsed: self.operation_elapsed().unwrap_or_default(),
+        }
+    }
+
+    /// Get total bytes downloaded
     pub fn total_bytes(&self) -> u64 {
         self.total_bytes.load(Ordering::Relaxed)
     }
 
+    /// Get total bytes uploaded
+    pub fn uploaded_bytes(&self) -> u64 {
+        self.uploaded_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Get bytes moved in either direction
+    pub fn transferred_bytes(&self) -> u64 {
+        self.total_bytes() + self.uploaded_bytes()
+    }
+
     /// Get request count
     pub fn request_count(&self) -> usize {
         self.request_count.load(Ordering::Relaxed)
@@ -94,13 +223,62 @@ impl S3Metrics {
     /// Reset all metrics
     pub fn reset(&self) {
         self.total_bytes.store(0, Ordering::Relaxed);
+        self.uploaded_bytes.store(0, Ordering::Relaxed);
         self.request_count.store(0, Ordering::Relaxed);
         self.total_request_time_ns.store(0, Ordering::Relaxed);
         self.requests.write().unwrap().clear();
+        self.operations.write().unwrap().clear();
+        self.retries.store(0, Ordering::Relaxed);
         *self.operation_start.write().unwrap() = None;
     }
 }
 
+/// SDK interceptor that records each API call (including retries) in [`S3Metrics`]
+#[derive(Debug)]
+pub struct MetricsInterceptor {
+    metrics: Arc<S3Metrics>,
+}
+
+impl MetricsInterceptor {
+    pub fn new(metrics: Arc<S3Metrics>) -> Self {
+        MetricsInterceptor { metrics }
+    }
+}
+
+impl Intercept for MetricsInterceptor {
+    fn name(&self) -> &'static str {
+        "S3MetricsInterceptor"
+    }
+
+    // Counted once a response arrives: presigning never sends anything, and S3 bills
+    // error responses but not connections that failed
+    fn read_after_transmit(
+        &self,
+        _context: &BeforeDeserializationInterceptorContextRef<'_>,
+        _runtime_components: &RuntimeComponents,
+        cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        let operation = cfg.load::<Metadata>().map_or("Unknown", Metadata::name);
+        self.metrics.record_operation(operation);
+        Ok(())
+    }
+
+    fn read_before_attempt(
+        &self,
+        _context: &BeforeTransmitInterceptorContextRef<'_>,
+        _runtime_components: &RuntimeComponents,
+        cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        if cfg
+            .load::<RequestAttempts>()
+            .is_some_and(|attempts| attempts.attempts() > 1)
+        {
+            self.metrics.record_retry();
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -122,6 +300,54 @@ mod tests {
         assert_eq!(requests[1].bytes, 2000);
     }
 
+    #[test]
+    fn test_streamed_and_uploaded_bytes() {
+        let metrics = S3Metrics::new();
+
+        metrics.record_bytes(500);
+        metrics.record_bytes(500);
+        metrics.record_upload(2000, Duration::from_millis(20));
+
+        assert_eq!(metrics.total_bytes(), 1000);
+        assert_eq!(metrics.uploaded_bytes(), 2000);
+        assert_eq!(metrics.transferred_bytes(), 3000);
+        // Streamed chunks aren't requests of their own
+        assert_eq!(metrics.request_count(), 1);
+        assert!(metrics.requests().is_empty());
+    }
+
+    #[test]
+    fn test_summary() {
+        let metrics = S3Metrics::new();
+        metrics.record_operation("ListObjectsV2");
+        metrics.record_operation("ListObjectsV2");
+        metrics.record_operation("GetObject");
+        metrics.record_bytes(100);
+
+        let mut total = metrics.summary();
+        assert_eq!(total.requests(), 3);
+        assert_eq!(total.operations["ListObjectsV2"], 2);
+        assert_eq!(total.downloaded, 100);
+
+        metrics.reset();
+        metrics.record_operation("GetObject");
+        metrics.record_upload(50, Duration::from_millis(5));
+        total.add(&metrics.summary());
+        assert_eq!(total.requests(), 4);
+        assert_eq!(total.operations["GetObject"], 2);
+        assert_eq!(total.uploaded, 50);
+
+        // Counts since an earlier snapshot leave out what came before it
+        let start = metrics.summary();
+        metrics.record_operation("GetObject");
+        metrics.record_operation("HeadObject");
+        metrics.record_bytes(10);
+        let since = metrics.summary().since(&start);
+        assert_eq!(since.operations.len(), 2);
+        assert_eq!(since.operations["GetObject"], 1);
+        assert_eq!((since.downloaded, since.uploaded), (10, 0));
+    }
+
     #[test]
     fn test_metrics_reset() {
         let metrics = S3Metrics::new();
@@ -0,0 +1,856 @@
+//! In-memory S3 for tests and `s3sh --demo`.
+//!
+//! [`MockS3Client`] answers the SDK's HTTP requests from a map of buckets and objects,
+//! so the real [`S3Client`] (and every command and archive handler on top of it) runs
+//! unchanged without LocalStack or network access. It speaks enough of the S3 REST API
+//! for browsing and small edits: ListBuckets, ListObjectsV2, Head/Get (with ranges),
+//! Put, Copy, and Delete of objects, and Create/Head/Delete of buckets. Requests are
+//! recorded, so tests can check what was sent.
+
+use aws_sdk_s3::config::{BehaviorVersion, Credentials, Region};
+use aws_sdk_s3::primitives::SdkBody;
+use aws_smithy_runtime_api::client::http::{
+    HttpConnector, HttpConnectorFuture, SharedHttpConnector, http_client_fn,
+};
+use aws_smithy_runtime_api::client::orchestrator::{HttpRequest, HttpResponse};
+use aws_smithy_runtime_api::client::result::ConnectorError;
+use aws_smithy_runtime_api::http::StatusCode;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use http_body_util::BodyExt;
+use md5::{Digest, Md5};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+
+use super::S3Client;
+
+/// Region every mock bucket is in
+const REGION: &str = "us-east-1";
+
+/// Keys per ListObjectsV2 page when the request doesn't say
+const DEFAULT_MAX_KEYS: usize = 1000;
+
+/// An object held in memory
+#[derive(Debug, Clone)]
+struct MockObject {
+    body: Bytes,
+    etag: String,
+    content_type: String,
+    metadata: BTreeMap<String, String>,
+    /// System headers set at upload (see [`SYSTEM_HEADERS`])
+    headers: BTreeMap<String, String>,
+    storage_class: String,
+    last_modified: DateTime<Utc>,
+}
+
+/// Headers kept with an object like S3 does, and sent back on GET and HEAD
+const SYSTEM_HEADERS: &[&str] = &[
+    "cache-control",
+    "content-disposition",
+    "content-encoding",
+    "content-language",
+    "expires",
+    "x-amz-website-redirect-location",
+    "x-amz-server-side-encryption",
+    "x-amz-server-side-encryption-aws-kms-key-id",
+    "x-amz-server-side-encryption-bucket-key-enabled",
+];
+
+impl MockObject {
+    fn new(body: Bytes) -> Self {
+        MockObject {
+            etag: format!("\"{:x}\"", Md5::digest(&body)),
+            content_type: "application/octet-stream".to_string(),
+            metadata: BTreeMap::new(),
+            headers: BTreeMap::new(),
+            storage_class: "STANDARD".to_string(),
+            last_modified: Utc::now(),
+            body,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct MockBucket {
+    created: DateTime<Utc>,
+    objects: BTreeMap<String, MockObject>,
+}
+
+type Buckets = Arc<Mutex<BTreeMap<String, MockBucket>>>;
+
+/// A request the mock answered, for tests that check what was sent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockRequest {
+    pub method: String,
+    pub bucket: String,
+    pub key: String,
+    /// The Range header, e.g. "bytes=0-1023"
+    pub range: Option<String>,
+    /// The If-Match header
+    pub if_match: Option<String>,
+}
+
+type Requests = Arc<Mutex<Vec<MockRequest>>>;
+
+/// Buckets and objects kept in memory, served to an [`S3Client`]
+/// Clients made with [`client`](Self::client) share the store, so changes made through
+/// one are visible to the others and to later `with_*` calls.
+#[derive(Clone, Default)]
+pub struct MockS3Client {
+    buckets: Buckets,
+    requests: Requests,
+}
+
+impl MockS3Client {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an empty bucket
+    pub fn with_bucket(self, bucket: &str) -> Self {
+        self.buckets
+            .lock()
+            .unwrap()
+            .entry(bucket.to_string())
+            .or_insert_with(|| MockBucket {
+                created: Utc::now(),
+                ..Default::default()
+            });
+        self
+    }
+
+    /// Add an object, creating its bucket if needed
+    pub fn with_object(self, bucket: &str, key: &str, body: impl Into<Bytes>) -> Self {
+        let this = self.with_bucket(bucket);
+        this.buckets
+            .lock()
+            .unwrap()
+            .get_mut(bucket)
+            .expect("bucket was just added")
+            .objects
+            .insert(key.to_string(), MockObject::new(body.into()));
+        this
+    }
+
+    /// Set system headers (e.g. cache-control) on an object added before
+    pub fn with_headers(self, bucket: &str, key: &str, headers: &[(&str, &str)]) -> Self {
+        if let Some(object) = self
+            .buckets
+            .lock()
+            .unwrap()
+            .get_mut(bucket)
+            .and_then(|b| b.objects.get_mut(key))
+        {
+            for (name, value) in headers {
+                object.headers.insert(name.to_string(), value.to_string());
+            }
+        }
+        self
+    }
+
+    /// Move an object added before to another storage class (e.g. GLACIER)
+    pub fn with_storage_class(self, bucket: &str, key: &str, class: &str) -> Self {
+        if let Some(object) = self
+            .buckets
+            .lock()
+            .unwrap()
+            .get_mut(bucket)
+            .and_then(|b| b.objects.get_mut(key))
+        {
+            object.storage_class = class.to_string();
+        }
+        self
+    }
+
+    /// The contents of an object, if it exists
+    pub fn object(&self, bucket: &str, key: &str) -> Option<Bytes> {
+        let buckets = self.buckets.lock().unwrap();
+        Some(buckets.get(bucket)?.objects.get(key)?.body.clone())
+    }
+
+    /// The requests answered since the last call, oldest first
+    pub fn take_requests(&self) -> Vec<MockRequest> {
+        std::mem::take(&mut self.requests.lock().unwrap())
+    }
+
+    /// An [`S3Client`] backed by this store
+    pub fn client(&self) -> S3Client {
+        let connector = SharedHttpConnector::new(MockConnector {
+            buckets: Arc::clone(&self.buckets),
+            requests: Arc::clone(&self.requests),
+        });
+        let config = aws_sdk_s3::Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .region(Region::new(REGION))
+            .endpoint_url("http://s3.mock")
+            .force_path_style(true)
+            .credentials_provider(Credentials::new("mock", "mock", None, None, "mock"))
+            // Keep bodies plain rather than aws-chunked with checksum trailers
+            .request_checksum_calculation(
+                aws_sdk_s3::config::RequestChecksumCalculation::WhenRequired,
+            )
+            .response_checksum_validation(
+                aws_sdk_s3::config::ResponseChecksumValidation::WhenRequired,
+            )
+            .http_client(http_client_fn(move |_, _| connector.clone()))
+            .build();
+        S3Client::from_client_with_options(
+            aws_sdk_s3::Client::from_conf(config),
+            REGION.to_string(),
+            true,
+        )
+    }
+
+    /// The sandbox `s3sh --demo` browses: text, logs, JSON, and a tar.gz archive
+    pub fn demo() -> Self {
+        let backup = demo_archive(&[
+            ("app/config.yml", "listen: 0.0.0.0:8080\nlog_level: info\n"),
+            (
+                "app/server.log",
+                "2024-06-01 12:00:00 INFO started\n2024-06-01 12:00:05 ERROR upstream timed out\n",
+            ),
+            ("README", "Nightly backup of the demo app\n"),
+        ]);
+        Self::new()
+            .with_object(
+                "demo-bucket",
+                "README.md",
+                "# Demo bucket\n\nTry `ls`, `cd backups/nightly.tar.gz`, and `cat data/users.json`.\n",
+            )
+            .with_object(
+                "demo-bucket",
+                "data/users.json",
+                r#"{"users": [{"name": "ada", "admin": true}, {"name": "grace", "admin": false}]}"#,
+            )
+            .with_object(
+                "demo-bucket",
+                "logs/2024-06-01/app.log",
+                "12:00:00 INFO started\n12:00:05 ERROR upstream timed out\n12:00:06 INFO retrying\n",
+            )
+            .with_object(
+                "demo-bucket",
+                "logs/2024-06-02/app.log",
+                "12:00:00 INFO started\n",
+            )
+            .with_object("demo-bucket", "backups/nightly.tar.gz", backup)
+            .with_bucket("empty-bucket")
+    }
+}
+
+/// A gzipped tar of (path, contents)
+fn demo_archive(files: &[(&str, &str)]) -> Vec<u8> {
+    let gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    let mut tar = tar::Builder::new(gz);
+    for (path, contents) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(1_717_243_200);
+        header.set_cksum();
+        tar.append_data(&mut header, path, contents.as_bytes())
+            .expect("writing to memory can't fail");
+    }
+    tar.into_inner()
+        .and_then(|gz| gz.finish())
+        .expect("writing to memory can't fail")
+}
+
+#[derive(Debug)]
+struct MockConnector {
+    buckets: Buckets,
+    requests: Requests,
+}
+
+impl HttpConnector for MockConnector {
+    fn call(&self, mut request: HttpRequest) -> HttpConnectorFuture {
+        let buckets = Arc::clone(&self.buckets);
+        let requests = Arc::clone(&self.requests);
+        HttpConnectorFuture::new(async move {
+            let body = request
+                .take_body()
+                .collect()
+                .await
+                .map_err(|e| ConnectorError::other(e, None))?
+                .to_bytes();
+            Ok(handle(&buckets, &requests, &request, body).into_response())
+        })
+    }
+}
+
+/// A response before it's turned into the SDK's type
+struct Reply {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Bytes,
+}
+
+impl Reply {
+    fn ok(body: impl Into<Bytes>) -> Self {
+        Reply {
+            status: 200,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    fn xml(body: String) -> Self {
+        Reply::ok(format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n{body}"
+        ))
+        .header("content-type", "application/xml")
+    }
+
+    fn error(status: u16, code: &str, message: &str) -> Self {
+        let mut reply = Reply::xml(format!(
+            "<Error><Code>{code}</Code><Message>{}</Message></Error>",
+            xml_escape(message)
+        ));
+        reply.status = status;
+        reply
+    }
+
+    fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    fn into_response(self) -> HttpResponse {
+        let mut response = HttpResponse::new(
+            StatusCode::try_from(self.status).expect("valid status"),
+            SdkBody::from(self.body),
+        );
+        for (name, value) in self.headers {
+            response.headers_mut().insert(name, value);
+        }
+        response
+    }
+}
+
+fn handle(buckets: &Buckets, requests: &Requests, request: &HttpRequest, body: Bytes) -> Reply {
+    let uri = request.uri();
+    let path_and_query = uri
+        .find("://")
+        .and_then(|i| uri[i + 3..].find('/').map(|j| &uri[i + 3 + j..]))
+        .unwrap_or("/");
+    let (path, query) = path_and_query
+        .split_once('?')
+        .unwrap_or((path_and_query, ""));
+    let query: HashMap<String, String> = query
+        .split('&')
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            let (k, v) = p.split_once('=').unwrap_or((p, ""));
+            (decode(k), decode(v))
+        })
+        .collect();
+    let path = path.trim_start_matches('/');
+    let (bucket, key) = match path.split_once('/') {
+        Some((bucket, key)) => (decode(bucket), decode(key)),
+        None => (decode(path), String::new()),
+    };
+    let method = request.method();
+    let header = |name: &str| request.headers().get(name).map(String::from);
+    tracing::trace!(method, bucket, key, "mock S3 request");
+    requests.lock().unwrap().push(MockRequest {
+        method: method.to_string(),
+        bucket: bucket.clone(),
+        key: key.clone(),
+        range: header("range"),
+        if_match: header("if-match"),
+    });
+
+    let mut buckets = buckets.lock().unwrap();
+    if bucket.is_empty() {
+        return match method {
+            "GET" => list_buckets(&buckets),
+            _ => not_implemented(),
+        };
+    }
+
+    if key.is_empty() {
+        return match method {
+            "PUT" if query.is_empty() => {
+                if buckets.contains_key(&bucket) {
+                    return Reply::error(409, "BucketAlreadyOwnedByYou", &bucket);
+                }
+                buckets.insert(
+                    bucket,
+                    MockBucket {
+                        created: Utc::now(),
+                        ..Default::default()
+                    },
+                );
+                Reply::ok("")
+            }
+            _ => {
+                let Some(b) = buckets.get(&bucket) else {
+                    return Reply::error(404, "NoSuchBucket", &bucket);
+                };
+                match method {
+                    "HEAD" => Reply::ok("").header("x-amz-bucket-region", REGION),
+                    "GET" if query.contains_key("location") => {
+                        Reply::xml(format!("<LocationConstraint>{REGION}</LocationConstraint>"))
+                    }
+                    "GET" if query.contains_key("versioning") => {
+                        Reply::xml("<VersioningConfiguration/>".to_string())
+                    }
+                    "GET" if query.get("list-type").map(String::as_str) == Some("2") => {
+                        list_objects(&bucket, b, &query)
+                    }
+                    "DELETE" if query.is_empty() => {
+                        if !b.objects.is_empty() {
+                            return Reply::error(409, "BucketNotEmpty", &bucket);
+                        }
+                        buckets.remove(&bucket);
+                        Reply {
+                            status: 204,
+                            ..Reply::ok("")
+                        }
+                    }
+                    _ => not_implemented(),
+                }
+            }
+        };
+    }
+
+    // Copies read the source before the destination bucket is borrowed
+    let copy_source = header("x-amz-copy-source").map(|source| {
+        let source = decode(source.trim_start_matches('/'));
+        let (src_bucket, src_key) = source.split_once('/').unwrap_or((&source, ""));
+        buckets
+            .get(src_bucket)
+            .and_then(|b| b.objects.get(src_key))
+            .cloned()
+    });
+
+    let Some(b) = buckets.get_mut(&bucket) else {
+        return Reply::error(404, "NoSuchBucket", &bucket);
+    };
+    match method {
+        "GET" if query.contains_key("tagging") => match b.objects.get(&key) {
+            Some(_) => Reply::xml("<Tagging><TagSet></TagSet></Tagging>".to_string()),
+            None => Reply::error(404, "NoSuchKey", &key),
+        },
+        "GET" | "HEAD" if query.keys().all(|k| k == "x-id") => {
+            let Some(object) = b.objects.get(&key) else {
+                // HEAD errors have no body
+                return match method {
+                    "HEAD" => Reply {
+                        status: 404,
+                        ..Reply::ok("")
+                    },
+                    _ => Reply::error(404, "NoSuchKey", &key),
+                };
+            };
+            if let Some(expected) = header("if-match")
+                && object.etag != expected
+            {
+                return Reply::error(412, "PreconditionFailed", &key);
+            }
+            get_object(object, header("range").as_deref(), method == "HEAD")
+        }
+        "PUT" if query.keys().all(|k| k == "x-id") => {
+            if let Some(expected) = header("if-match")
+                && b.objects.get(&key).map(|o| &o.etag) != Some(&expected)
+            {
+                return Reply::error(412, "PreconditionFailed", &key);
+            }
+            let copied = copy_source.is_some();
+            let mut object = match copy_source {
+                Some(Some(source)) => source,
+                Some(None) => return Reply::error(404, "NoSuchKey", "copy source"),
+                None => MockObject::new(body),
+            };
+            // Copies go back to STANDARD too unless the request names a class
+            object.storage_class =
+                header("x-amz-storage-class").unwrap_or_else(|| "STANDARD".to_string());
+            if let Some(expected) = header("x-amz-copy-source-if-match")
+                && object.etag != expected
+            {
+                return Reply::error(412, "PreconditionFailed", "copy source");
+            }
+            // Plain PUTs take metadata from their headers; copies only with REPLACE
+            if !copied || header("x-amz-metadata-directive").as_deref() == Some("REPLACE") {
+                object.metadata = request
+                    .headers()
+                    .iter()
+                    .filter_map(|(name, value)| {
+                        let name = name.strip_prefix("x-amz-meta-")?;
+                        Some((name.to_string(), value.to_string()))
+                    })
+                    .collect();
+                if let Some(content_type) = header("content-type") {
+                    object.content_type = content_type;
+                }
+                object.headers = SYSTEM_HEADERS
+                    .iter()
+                    .filter_map(|name| Some((name.to_string(), header(name)?)))
+                    .collect();
+            }
+            object.last_modified = Utc::now();
+            let etag = object.etag.clone();
+            let result = format!(
+                "<CopyObjectResult><ETag>{}</ETag><LastModified>{}</LastModified></CopyObjectResult>",
+                xml_escape(&etag),
+                iso_time(&object.last_modified)
+            );
+            b.objects.insert(key, object);
+            if copied {
+                Reply::xml(result)
+            } else {
+                Reply::ok("").header("etag", etag)
+            }
+        }
+        "DELETE" if query.keys().all(|k| k == "x-id") => {
+            b.objects.remove(&key);
+            Reply {
+                status: 204,
+                ..Reply::ok("")
+            }
+        }
+        _ => not_implemented(),
+    }
+}
+
+fn not_implemented() -> Reply {
+    Reply::error(
+        501,
+        "NotImplemented",
+        "Not supported by the mock S3 backend",
+    )
+}
+
+fn list_buckets(buckets: &BTreeMap<String, MockBucket>) -> Reply {
+    let mut xml = String::from(
+        "<ListAllMyBucketsResult><Owner><ID>mock</ID><DisplayName>mock</DisplayName></Owner><Buckets>",
+    );
+    for (name, bucket) in buckets {
+        let _ = write!(
+            xml,
+            "<Bucket><Name>{}</Name><CreationDate>{}</CreationDate></Bucket>",
+            xml_escape(name),
+            iso_time(&bucket.created)
+        );
+    }
+    xml.push_str("</Buckets></ListAllMyBucketsResult>");
+    Reply::xml(xml)
+}
+
+/// One ListObjectsV2 page; the continuation token is the last key the page covered
+fn list_objects(name: &str, bucket: &MockBucket, query: &HashMap<String, String>) -> Reply {
+    let prefix = query.get("prefix").map(String::as_str).unwrap_or("");
+    let delimiter = query.get("delimiter").filter(|d| !d.is_empty());
+    let max_keys = query
+        .get("max-keys")
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(DEFAULT_MAX_KEYS);
+    let after = query
+        .get("continuation-token")
+        .or_else(|| query.get("start-after"));
+
+    let mut contents = String::new();
+    let mut prefixes: Vec<String> = Vec::new();
+    let mut count = 0;
+    let mut last_key: Option<&str> = None;
+    let mut truncated = false;
+    let keys = bucket
+        .objects
+        .iter()
+        .filter(|(key, _)| after.is_none_or(|after| key.as_str() > after.as_str()))
+        .filter(|(key, _)| key.starts_with(prefix));
+    for (key, object) in keys {
+        let common = delimiter.and_then(|d| {
+            let rest = &key[prefix.len()..];
+            rest.find(d.as_str())
+                .map(|i| format!("{prefix}{}", &rest[..i + d.len()]))
+        });
+        // Keys under a prefix already listed are covered by it
+        if let Some(common) = &common
+            && prefixes.last() == Some(common)
+        {
+            last_key = Some(key);
+            continue;
+        }
+        if count == max_keys {
+            truncated = true;
+            break;
+        }
+        count += 1;
+        last_key = Some(key);
+        match common {
+            Some(common) => prefixes.push(common),
+            None => {
+                let _ = write!(
+                    contents,
+                    "<Contents><Key>{}</Key><LastModified>{}</LastModified><ETag>{}</ETag>\
+                     <Size>{}</Size><StorageClass>{}</StorageClass></Contents>",
+                    xml_escape(key),
+                    iso_time(&object.last_modified),
+                    xml_escape(&object.etag),
+                    object.body.len(),
+                    object.storage_class
+                );
+            }
+        }
+    }
+
+    let mut xml = format!(
+        "<ListBucketResult><Name>{}</Name><Prefix>{}</Prefix><KeyCount>{count}</KeyCount>\
+         <MaxKeys>{max_keys}</MaxKeys><IsTruncated>{truncated}</IsTruncated>",
+        xml_escape(name),
+        xml_escape(prefix)
+    );
+    if let Some(delimiter) = delimiter {
+        let _ = write!(xml, "<Delimiter>{}</Delimiter>", xml_escape(delimiter));
+    }
+    if truncated && let Some(last) = last_key {
+        let _ = write!(
+            xml,
+            "<NextContinuationToken>{}</NextContinuationToken>",
+            xml_escape(last)
+        );
+    }
+    xml.push_str(&contents);
+    for common in &prefixes {
+        let _ = write!(
+            xml,
+            "<CommonPrefixes><Prefix>{}</Prefix></CommonPrefixes>",
+            xml_escape(common)
+        );
+    }
+    xml.push_str("</ListBucketResult>");
+    Reply::xml(xml)
+}
+
+/// GET or HEAD of an object, honoring a single `bytes=` range
+fn get_object(object: &MockObject, range: Option<&str>, head: bool) -> Reply {
+    let size = object.body.len() as u64;
+    let (status, start, end) = match range.and_then(|r| parse_range(r, size)) {
+        Some((start, end)) => (206, start, end),
+        None if range.is_some() => return Reply::error(416, "InvalidRange", "Invalid range"),
+        None => (200, 0, size),
+    };
+    let mut reply = Reply {
+        status,
+        headers: Vec::new(),
+        body: if head {
+            Bytes::new()
+        } else {
+            object.body.slice(start as usize..end as usize)
+        },
+    }
+    .header("content-length", (end - start).to_string())
+    .header("content-type", object.content_type.clone())
+    .header("etag", object.etag.clone())
+    .header(
+        "last-modified",
+        object
+            .last_modified
+            .format("%a, %d %b %Y %H:%M:%S GMT")
+            .to_string(),
+    )
+    .header("accept-ranges", "bytes");
+    // Like S3, only classes other than STANDARD are named
+    if object.storage_class != "STANDARD" {
+        reply = reply.header("x-amz-storage-class", object.storage_class.clone());
+    }
+    if status == 206 {
+        reply = reply.header("content-range", format!("bytes {start}-{}/{size}", end - 1));
+    }
+    for (name, value) in &object.metadata {
+        reply = reply.header(format!("x-amz-meta-{name}"), value.clone());
+    }
+    for (name, value) in &object.headers {
+        reply = reply.header(name.clone(), value.clone());
+    }
+    reply
+}
+
+/// Parse `bytes=a-b`, `bytes=a-`, or `bytes=-n` into a half-open range within `size`
+fn parse_range(range: &str, size: u64) -> Option<(u64, u64)> {
+    let (start, end) = range.strip_prefix("bytes=")?.split_once('-')?;
+    let (start, end) = match (start, end) {
+        ("", suffix) => (size.saturating_sub(suffix.parse().ok()?), size),
+        (start, "") => (start.parse().ok()?, size),
+        (start, end) => (
+            start.parse().ok()?,
+            end.parse::<u64>().ok()?.saturating_add(1),
+        ),
+    };
+    let end = end.min(size);
+    (start < end).then_some((start, end))
+}
+
+fn iso_time(time: &DateTime<Utc>) -> String {
+    time.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Decode %XX escapes in a URL path or query component
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%')
+            .then(|| text.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match hex {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::{EntryKind, Session};
+    use crate::shell::{Output, ShellState};
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-9", 100), Some((0, 10)));
+        assert_eq!(parse_range("bytes=90-", 100), Some((90, 100)));
+        assert_eq!(parse_range("bytes=-10", 100), Some((90, 100)));
+        assert_eq!(parse_range("bytes=95-200", 100), Some((95, 100)));
+        assert_eq!(parse_range("bytes=100-", 100), None);
+        assert_eq!(parse_range("items=0-1", 100), None);
+    }
+
+    #[tokio::test]
+    async fn test_objects_round_trip() {
+        let mock = MockS3Client::new().with_object("bucket", "dir/a.txt", "hello world");
+        let client = mock.client();
+
+        let buckets = client.list_buckets().await.unwrap();
+        assert_eq!(buckets.len(), 1);
+        assert_eq!(buckets[0].name, "bucket");
+
+        assert_eq!(
+            client
+                .head_object("bucket", "dir/a.txt")
+                .await
+                .unwrap()
+                .size,
+            11
+        );
+        assert_eq!(
+            client
+                .get_object_range("bucket", "dir/a.txt", 6, 5)
+                .await
+                .unwrap(),
+            "world"
+        );
+        assert!(client.get_object("bucket", "missing").await.is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let local = dir.path().join("b.txt");
+        std::fs::write(&local, "uploaded").unwrap();
+        client
+            .upload_file("bucket", "dir/b.txt", &local)
+            .await
+            .unwrap();
+        assert_eq!(mock.object("bucket", "dir/b.txt").unwrap(), "uploaded");
+
+        client.delete_object("bucket", "dir/a.txt").await.unwrap();
+        assert!(mock.object("bucket", "dir/a.txt").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_list_objects_pages_and_prefixes() {
+        let mut mock = MockS3Client::new();
+        for key in ["a/1", "a/2", "b/1", "c", "d"] {
+            mock = mock.with_object("bucket", key, key.to_string());
+        }
+        let client = mock.client();
+
+        let (listing, next) = client
+            .list_objects_page("bucket", "", Some("/"), None)
+            .await
+            .unwrap();
+        assert_eq!(listing.prefixes, vec!["a/", "b/"]);
+        let keys: Vec<_> = listing.objects.iter().map(|o| o.key.as_str()).collect();
+        assert_eq!(keys, vec!["c", "d"]);
+        assert!(next.is_none());
+
+        // Pages end on the last key they cover, skipping the rest of a common prefix
+        let buckets = mock.buckets.lock().unwrap();
+        let query = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+        let page = list_objects(
+            "bucket",
+            &buckets["bucket"],
+            &query(&[("delimiter", "/"), ("max-keys", "1")]),
+        );
+        let xml = String::from_utf8_lossy(&page.body).into_owned();
+        assert!(xml.contains("<CommonPrefixes><Prefix>a/</Prefix></CommonPrefixes>"));
+        assert!(xml.contains("<NextContinuationToken>a/2</NextContinuationToken>"));
+
+        let page = list_objects(
+            "bucket",
+            &buckets["bucket"],
+            &query(&[("continuation-token", "b/1"), ("max-keys", "5")]),
+        );
+        let xml = String::from_utf8_lossy(&page.body).into_owned();
+        assert!(xml.contains("<Key>c</Key>") && xml.contains("<Key>d</Key>"));
+        assert!(!xml.contains("<Key>b/1</Key>"));
+        assert!(xml.contains("<IsTruncated>false</IsTruncated>"));
+    }
+
+    #[tokio::test]
+    async fn test_session_reads_inside_archives() {
+        let session = Session::new(Arc::new(MockS3Client::demo().client()));
+
+        let entries = session
+            .ls("/demo-bucket/backups/nightly.tar.gz/app")
+            .await
+            .unwrap();
+        let mut names: Vec<_> = entries.iter().map(|e| e.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["config.yml", "server.log"]);
+        assert!(entries.iter().all(|e| e.kind == EntryKind::File));
+
+        let log = session
+            .read("/demo-bucket/backups/nightly.tar.gz/app/server.log")
+            .await
+            .unwrap();
+        assert!(String::from_utf8_lossy(&log).contains("ERROR upstream timed out"));
+    }
+
+    #[tokio::test]
+    async fn test_shell_commands() {
+        let mut state = ShellState::with_client(Arc::new(MockS3Client::demo().client()))
+            .await
+            .unwrap();
+        let mut out = Output::capture();
+        state
+            .execute_to("cd /demo-bucket/logs", &mut out)
+            .await
+            .unwrap();
+        state
+            .execute_to("cat 2024-06-02/app.log", &mut out)
+            .await
+            .unwrap();
+        assert_eq!(out.captured().unwrap(), b"12:00:00 INFO started\n");
+    }
+}
@@ -1,7 +1,26 @@
 pub mod client;
+pub mod connection;
+pub mod http;
+pub mod inventory;
+pub mod listing;
 pub mod metrics;
+#[cfg(any(test, feature = "test-util"))]
+pub mod mock;
+pub mod pricing;
+pub mod retry;
 pub mod stream;
+pub mod transfer;
+pub mod walker;
 
-pub use client::S3Client;
-pub use metrics::S3Metrics;
+pub use client::{
+    Mount, PublicAccessBlock, S3Client, SelectEvent, SelectFormat, SelectRequest,
+    is_expired_credentials_error,
+};
+pub use metrics::{MetricsSummary, S3Metrics};
+#[cfg(any(test, feature = "test-util"))]
+pub use mock::{MockRequest, MockS3Client};
+pub use pricing::{CostEstimate, Pricing};
+pub use retry::RetryPolicy;
 pub use stream::S3Stream;
+pub use transfer::TransferOptions;
+pub use walker::PrefixWalker;
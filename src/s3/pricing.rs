@@ -0,0 +1,185 @@
+//! Rough cost estimates for the S3 usage recorded in [`MetricsSummary`].
+//!
+//! Prices are public list prices for each provider's standard storage class and
+//! internet egress; free tiers, volume discounts, and in-region traffic aren't
+//! modeled. Any of them can be overridden in `[pricing.<provider>]` in config.toml.
+
+use anyhow::{Result, anyhow};
+
+use super::metrics::MetricsSummary;
+
+/// Price keys accepted in `[pricing.<provider>]`
+pub const KEYS: &[(&str, &str)] = &[
+    (
+        "write_per_1000",
+        "USD per 1,000 PUT, COPY, POST, and LIST requests",
+    ),
+    (
+        "read_per_1000",
+        "USD per 1,000 GET, HEAD, and other requests",
+    ),
+    ("transfer_per_gb", "USD per GB downloaded"),
+];
+
+/// Request and data transfer prices, in USD
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pricing {
+    /// Per 1,000 PUT, COPY, POST, and LIST requests
+    pub write_per_1000: f64,
+    /// Per 1,000 GET, HEAD, and other requests
+    pub read_per_1000: f64,
+    /// Per GB (10^9 bytes) downloaded
+    pub transfer_per_gb: f64,
+}
+
+/// Estimated cost of a [`MetricsSummary`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CostEstimate {
+    /// PUT, COPY, POST, and LIST requests
+    pub write_requests: usize,
+    /// GET, HEAD, and other requests
+    pub read_requests: usize,
+    /// Cost of all requests
+    pub requests: f64,
+    /// Cost of the bytes downloaded
+    pub transfer: f64,
+}
+
+impl CostEstimate {
+    pub fn total(&self) -> f64 {
+        self.requests + self.transfer
+    }
+}
+
+impl Pricing {
+    /// Built-in list prices for a provider (AWS us-east-1 prices for unknown ones)
+    pub fn for_provider(provider: &str) -> Pricing {
+        let (write_per_1000, read_per_1000, transfer_per_gb) = match provider {
+            // Class A / class B operations, free egress
+            "r2" => (0.0045, 0.00036, 0.0),
+            // Class C / class B transactions; uploads and deletes are free
+            "b2" => (0.004, 0.0004, 0.01),
+            "gcs" => (0.005, 0.0004, 0.12),
+            // No request or egress fees within fair use
+            "wasabi" | "spaces" => (0.0, 0.0, 0.0),
+            // Public datasets hosted for free
+            "sourcecoop" => (0.0, 0.0, 0.0),
+            _ => (0.005, 0.0004, 0.09),
+        };
+        Pricing {
+            write_per_1000,
+            read_per_1000,
+            transfer_per_gb,
+        }
+    }
+
+    /// Override one price by its key (see [`KEYS`])
+    pub fn set(&mut self, key: &str, price: f64) -> Result<()> {
+        if !price.is_finite() || price < 0.0 {
+            return Err(anyhow!("Invalid price for {key}: {price}"));
+        }
+        match key {
+            "write_per_1000" => self.write_per_1000 = price,
+            "read_per_1000" => self.read_per_1000 = price,
+            "transfer_per_gb" => self.transfer_per_gb = price,
+            _ => {
+                let known: Vec<&str> = KEYS.iter().map(|(key, _)| *key).collect();
+                return Err(anyhow!(
+                    "Unknown pricing key: {key} (expected {})",
+                    known.join(", ")
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Estimate what the calls and downloads in a summary cost
+    pub fn estimate(&self, summary: &MetricsSummary) -> CostEstimate {
+        let (mut write_requests, mut read_requests) = (0, 0);
+        for (operation, count) in &summary.operations {
+            match classify(operation) {
+                RequestTier::Write => write_requests += count,
+                RequestTier::Read => read_requests += count,
+                RequestTier::Free => {}
+            }
+        }
+
+        CostEstimate {
+            write_requests,
+            read_requests,
+            requests: write_requests as f64 / 1000.0 * self.write_per_1000
+                + read_requests as f64 / 1000.0 * self.read_per_1000,
+            transfer: summary.downloaded as f64 / 1e9 * self.transfer_per_gb,
+        }
+    }
+}
+
+/// How S3 bills a request
+#[derive(Debug, PartialEq)]
+enum RequestTier {
+    /// PUT, COPY, POST, and LIST
+    Write,
+    /// GET, HEAD, and everything else
+    Read,
+    /// DELETE and aborting uploads cost nothing
+    Free,
+}
+
+fn classify(operation: &str) -> RequestTier {
+    const WRITE_PREFIXES: &[&str] = &[
+        "Put", "Copy", "Post", "List", "Create", "Complete", "Upload", "Restore",
+    ];
+    if operation.starts_with("Delete") || operation == "AbortMultipartUpload" {
+        RequestTier::Free
+    } else if WRITE_PREFIXES
+        .iter()
+        .any(|prefix| operation.starts_with(prefix))
+    {
+        RequestTier::Write
+    } else {
+        RequestTier::Read
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify("ListObjectsV2"), RequestTier::Write);
+        assert_eq!(classify("PutObject"), RequestTier::Write);
+        assert_eq!(classify("UploadPart"), RequestTier::Write);
+        assert_eq!(classify("GetObject"), RequestTier::Read);
+        assert_eq!(classify("HeadObject"), RequestTier::Read);
+        assert_eq!(classify("SelectObjectContent"), RequestTier::Read);
+        assert_eq!(classify("DeleteObject"), RequestTier::Free);
+    }
+
+    #[test]
+    fn test_estimate() {
+        let summary = MetricsSummary {
+            operations: BTreeMap::from([
+                ("ListObjectsV2".to_string(), 2000),
+                ("GetObject".to_string(), 10_000),
+                ("DeleteObject".to_string(), 5),
+            ]),
+            downloaded: 2_000_000_000,
+            ..Default::default()
+        };
+
+        let estimate = Pricing::for_provider("aws").estimate(&summary);
+        assert_eq!(estimate.write_requests, 2000);
+        assert_eq!(estimate.read_requests, 10_000);
+        assert!((estimate.requests - (0.01 + 0.004)).abs() < 1e-9);
+        assert!((estimate.transfer - 0.18).abs() < 1e-9);
+
+        let mut pricing = Pricing::for_provider("r2");
+        assert_eq!(pricing.estimate(&summary).transfer, 0.0);
+        pricing.set("transfer_per_gb", 0.05).unwrap();
+        assert!((pricing.estimate(&summary).transfer - 0.1).abs() < 1e-9);
+        assert!(pricing.set("per_request", 1.0).is_err());
+        assert!(pricing.set("read_per_1000", -1.0).is_err());
+    }
+}
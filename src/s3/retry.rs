@@ -0,0 +1,275 @@
+//! Retry policy for S3 calls.
+//!
+//! The SDK's standard retry strategy does the retrying (exponential backoff with
+//! jitter); [`RetryPolicyInterceptor`] hands it the current [`RetryPolicy`] before
+//! each call, so `set retry_attempts` takes effect immediately, and
+//! [`RetryPolicyClassifier`] decides which failures are worth another attempt.
+
+use anyhow::{Result, anyhow};
+use aws_sdk_s3::config::interceptors::BeforeTransmitInterceptorContextMut;
+use aws_sdk_s3::config::retry::{ClassifyRetry, RetryAction, RetryConfig};
+use aws_sdk_s3::config::{ConfigBag, Intercept, RuntimeComponents};
+use aws_smithy_runtime_api::box_error::BoxError;
+use aws_smithy_runtime_api::client::interceptors::context::InterceptorContext;
+use aws_smithy_runtime_api::client::retries::classifiers::RetryClassifierPriority;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Default attempts per call, including the first (the SDK's default)
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+/// Default delay before the first retry; later ones double, up to [`MAX_BACKOFF`]
+pub const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Longest delay between two attempts
+pub const MAX_BACKOFF: Duration = Duration::from_secs(20);
+
+/// Kinds of failure that can be retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    /// SlowDown (503) and Too Many Requests (429)
+    Throttling,
+    /// Internal errors (500, 502, 504)
+    Server,
+    /// Timeouts and dropped connections
+    Timeout,
+}
+
+impl FailureKind {
+    const ALL: [FailureKind; 3] = [
+        FailureKind::Throttling,
+        FailureKind::Server,
+        FailureKind::Timeout,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            FailureKind::Throttling => "throttling",
+            FailureKind::Server => "server",
+            FailureKind::Timeout => "timeout",
+        }
+    }
+
+    /// Classify a failed attempt by its HTTP status (None = no response arrived)
+    fn classify(status: Option<u16>, timed_out: bool) -> Option<FailureKind> {
+        if timed_out {
+            return Some(FailureKind::Timeout);
+        }
+        match status? {
+            429 | 503 => Some(FailureKind::Throttling),
+            500 | 502 | 504 => Some(FailureKind::Server),
+            _ => None,
+        }
+    }
+}
+
+/// Which kinds of failure are retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryOn {
+    pub throttling: bool,
+    pub server: bool,
+    pub timeout: bool,
+}
+
+impl Default for RetryOn {
+    fn default() -> Self {
+        RetryOn {
+            throttling: true,
+            server: true,
+            timeout: true,
+        }
+    }
+}
+
+impl RetryOn {
+    /// Parse a comma-separated list like "throttling,timeout", or "all" / "none"
+    pub fn parse(value: &str) -> Result<RetryOn> {
+        let mut retry_on = RetryOn {
+            throttling: false,
+            server: false,
+            timeout: false,
+        };
+        for name in value.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            match name.to_lowercase().as_str() {
+                "all" => retry_on = RetryOn::default(),
+                "none" => {}
+                "throttling" => retry_on.throttling = true,
+                "server" => retry_on.server = true,
+                "timeout" => retry_on.timeout = true,
+                _ => {
+                    return Err(anyhow!(
+                        "Unknown failure kind: {name} (expected throttling, server, timeout, all, or none)"
+                    ));
+                }
+            }
+        }
+        Ok(retry_on)
+    }
+
+    pub fn contains(&self, kind: FailureKind) -> bool {
+        match kind {
+            FailureKind::Throttling => self.throttling,
+            FailureKind::Server => self.server,
+            FailureKind::Timeout => self.timeout,
+        }
+    }
+}
+
+impl fmt::Display for RetryOn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = FailureKind::ALL
+            .into_iter()
+            .filter(|kind| self.contains(*kind))
+            .map(FailureKind::name)
+            .collect();
+        if names.is_empty() {
+            write!(f, "none")
+        } else {
+            write!(f, "{}", names.join(","))
+        }
+    }
+}
+
+/// How failed S3 calls are retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts per call, including the first (1 = never retry)
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubling (with jitter) for each one after
+    pub initial_backoff: Duration,
+    /// Kinds of failure that are retried
+    pub retry_on: RetryOn,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            retry_on: RetryOn::default(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The SDK retry configuration for this policy
+    pub fn retry_config(&self) -> RetryConfig {
+        RetryConfig::standard()
+            .with_max_attempts(self.max_attempts.max(1))
+            .with_initial_backoff(self.initial_backoff)
+            .with_max_backoff(MAX_BACKOFF.max(self.initial_backoff))
+    }
+}
+
+/// SDK interceptor that applies the current [`RetryPolicy`] to each call
+#[derive(Debug)]
+pub struct RetryPolicyInterceptor {
+    policy: Arc<RwLock<RetryPolicy>>,
+}
+
+impl RetryPolicyInterceptor {
+    pub fn new(policy: Arc<RwLock<RetryPolicy>>) -> Self {
+        RetryPolicyInterceptor { policy }
+    }
+}
+
+impl Intercept for RetryPolicyInterceptor {
+    fn name(&self) -> &'static str {
+        "S3RetryPolicyInterceptor"
+    }
+
+    // The retry strategy reads its configuration from the bag on every decision
+    fn modify_before_retry_loop(
+        &self,
+        _context: &mut BeforeTransmitInterceptorContextMut<'_>,
+        _runtime_components: &RuntimeComponents,
+        cfg: &mut ConfigBag,
+    ) -> Result<(), BoxError> {
+        let config = self.policy.read().unwrap().retry_config();
+        cfg.interceptor_state().store_put(config);
+        Ok(())
+    }
+}
+
+/// Retry classifier enforcing [`RetryPolicy::retry_on`]
+/// It runs after the SDK's own classifiers, so it can veto their retries; failures it
+/// doesn't recognize are left to them.
+#[derive(Debug)]
+pub struct RetryPolicyClassifier {
+    policy: Arc<RwLock<RetryPolicy>>,
+}
+
+impl RetryPolicyClassifier {
+    pub fn new(policy: Arc<RwLock<RetryPolicy>>) -> Self {
+        RetryPolicyClassifier { policy }
+    }
+}
+
+impl ClassifyRetry for RetryPolicyClassifier {
+    fn classify_retry(&self, ctx: &InterceptorContext) -> RetryAction {
+        let Some(Err(error)) = ctx.output_or_error() else {
+            return RetryAction::NoActionIndicated;
+        };
+        let timed_out = error.is_timeout_error()
+            || error
+                .as_connector_error()
+                .is_some_and(|e| e.is_timeout() || e.is_io());
+        let status = ctx.response().map(|resp| resp.status().as_u16());
+
+        let Some(kind) = FailureKind::classify(status, timed_out) else {
+            return RetryAction::NoActionIndicated;
+        };
+        if !self.policy.read().unwrap().retry_on.contains(kind) {
+            return RetryAction::RetryForbidden;
+        }
+        match kind {
+            FailureKind::Throttling => RetryAction::throttling_error(),
+            FailureKind::Server => RetryAction::server_error(),
+            FailureKind::Timeout => RetryAction::transient_error(),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "S3RetryPolicyClassifier"
+    }
+
+    fn priority(&self) -> RetryClassifierPriority {
+        RetryClassifierPriority::run_after(RetryClassifierPriority::transient_error_classifier())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(
+            FailureKind::classify(Some(503), false),
+            Some(FailureKind::Throttling)
+        );
+        assert_eq!(
+            FailureKind::classify(Some(500), false),
+            Some(FailureKind::Server)
+        );
+        assert_eq!(
+            FailureKind::classify(None, true),
+            Some(FailureKind::Timeout)
+        );
+        assert_eq!(FailureKind::classify(Some(404), false), None);
+        assert_eq!(FailureKind::classify(None, false), None);
+    }
+
+    #[test]
+    fn test_retry_on() {
+        let retry_on = RetryOn::parse("throttling, timeout").unwrap();
+        assert!(retry_on.contains(FailureKind::Throttling));
+        assert!(!retry_on.contains(FailureKind::Server));
+        assert_eq!(retry_on.to_string(), "throttling,timeout");
+
+        assert_eq!(RetryOn::parse("all").unwrap(), RetryOn::default());
+        assert_eq!(RetryOn::parse("none").unwrap().to_string(), "none");
+        assert!(RetryOn::parse("sometimes").is_err());
+    }
+}
@@ -2,23 +2,37 @@ use anyhow::{Context, Result};
 use bytes::Bytes;
 use std::io::{Read, Seek, SeekFrom};
 use std::sync::Arc;
+use tokio::task::JoinHandle;
 
 use super::S3Client;
 
+/// Size of the first ranged GET of a sequential read
+const INITIAL_CHUNK_SIZE: u64 = 64 * 1024;
+
 /// A streaming reader for S3 objects that supports range requests
-/// This allows us to read specific parts of large files (like archives) without downloading everything
+/// This allows us to read specific parts of large files (like archives) without downloading everything.
+/// Sequential reads fetch the next chunk while the current one is consumed, doubling the
+/// chunk size each time up to the `read_ahead` transfer option.
 pub struct S3Stream {
     client: Arc<S3Client>,
     bucket: String,
     key: String,
     /// Total size of the object
     size: u64,
+    /// ETag from when the stream was opened; reads fail if the object is replaced
+    etag: Option<String>,
     /// Current position in the stream
     position: u64,
     /// Optional buffer for recently read data
     buffer: Option<Bytes>,
     /// Buffer position offset
     buffer_offset: u64,
+    /// Size of the next sequential fetch
+    chunk_size: u64,
+    /// Largest chunk to grow to (0 = fetch 64 KiB at a time, without read-ahead)
+    read_ahead: u64,
+    /// The chunk being fetched in the background, and where it starts
+    prefetch: Option<(u64, JoinHandle<Result<Bytes>>)>,
 }
 
 impl S3Stream {
@@ -26,15 +40,20 @@ impl S3Stream {
     pub async fn new(client: Arc<S3Client>, bucket: String, key: String) -> Result<Self> {
         // Get object size
         let metadata = client.head_object(&bucket, &key).await?;
+        let read_ahead = client.transfer_options().read_ahead;
 
         Ok(S3Stream {
             client,
             bucket,
             key,
             size: metadata.size,
+            etag: metadata.etag,
             position: 0,
             buffer: None,
             buffer_offset: 0,
+            chunk_size: INITIAL_CHUNK_SIZE,
+            read_ahead,
+            prefetch: None,
         })
     }
 
@@ -60,7 +79,13 @@ impl S3Stream {
         }
 
         self.client
-            .get_object_range(&self.bucket, &self.key, offset, length)
+            .get_object_part(
+                &self.bucket,
+                &self.key,
+                offset,
+                length,
+                self.etag.as_deref(),
+            )
             .await
     }
 
@@ -91,13 +116,8 @@ impl S3Stream {
         }
 
         // Need to fetch more data
-        // Fetch in chunks of 64KB or remaining size
-        let chunk_size = 65536u64;
-        let remaining = self.size - self.position;
-        let fetch_size = chunk_size.min(remaining).min(buf.len() as u64 * 2);
-
         let bytes = self
-            .read_range(self.position, fetch_size)
+            .fetch_chunk(self.position)
             .await
             .context("Failed to read from S3")?;
 
@@ -112,6 +132,39 @@ impl S3Stream {
         Ok(to_copy)
     }
 
+    /// Fetch the chunk starting at `offset`, then start fetching the one after it
+    /// A read elsewhere than the prefetched chunk (a seek) starts over from small chunks.
+    async fn fetch_chunk(&mut self, offset: u64) -> Result<Bytes> {
+        let bytes = match self.prefetch.take() {
+            Some((start, task)) if start == offset => {
+                task.await.context("Read-ahead task failed")??
+            }
+            prefetch => {
+                if let Some((_, task)) = prefetch {
+                    task.abort();
+                    self.chunk_size = INITIAL_CHUNK_SIZE;
+                }
+                let length = self.chunk_size.min(self.size - offset);
+                self.read_range(offset, length).await?
+            }
+        };
+
+        let next = offset + bytes.len() as u64;
+        if self.read_ahead > 0 && next < self.size {
+            self.chunk_size = (self.chunk_size * 2).min(self.read_ahead.max(INITIAL_CHUNK_SIZE));
+            let length = self.chunk_size.min(self.size - next);
+            let client = Arc::clone(&self.client);
+            let (bucket, key, etag) = (self.bucket.clone(), self.key.clone(), self.etag.clone());
+            let task = tokio::spawn(async move {
+                client
+                    .get_object_part(&bucket, &key, next, length, etag.as_deref())
+                    .await
+            });
+            self.prefetch = Some((next, task));
+        }
+        Ok(bytes)
+    }
+
     /// Create a synchronous reader wrapper
     pub fn into_sync_reader(self) -> SyncS3Reader {
         SyncS3Reader {
@@ -121,6 +174,14 @@ impl S3Stream {
     }
 }
 
+impl Drop for S3Stream {
+    fn drop(&mut self) {
+        if let Some((_, task)) = self.prefetch.take() {
+            task.abort();
+        }
+    }
+}
+
 /// Synchronous wrapper around S3Stream for use with sync Read trait
 pub struct SyncS3Reader {
     stream: Arc<tokio::sync::Mutex<S3Stream>>,
@@ -190,3 +251,129 @@ impl Clone for SyncS3Reader {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::s3::{MockS3Client, TransferOptions};
+
+    const SIZE: usize = 600 * 1024;
+    const KIB: u64 = 1024;
+
+    /// A stream over a SIZE-byte object with the given read-ahead, and the mock behind it
+    async fn open(read_ahead: u64) -> (MockS3Client, S3Stream, Vec<u8>) {
+        let body: Vec<u8> = (0..SIZE).map(|i| (i % 251) as u8).collect();
+        let mock = MockS3Client::new().with_object("b", "k", body.clone());
+        let client = mock.client();
+        client.set_transfer_options(TransferOptions {
+            read_ahead,
+            ..TransferOptions::default()
+        });
+        let stream = S3Stream::new(Arc::new(client), "b".to_string(), "k".to_string())
+            .await
+            .unwrap();
+        mock.take_requests();
+        (mock, stream, body)
+    }
+
+    /// Wait for the read-ahead to land, so the requests it sent are recorded
+    async fn settle(stream: &S3Stream) {
+        while stream
+            .prefetch
+            .as_ref()
+            .is_some_and(|(_, task)| !task.is_finished())
+        {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    /// The ranges requested since the last call, checking each is pinned to `etag`
+    fn ranges(mock: &MockS3Client, etag: &str) -> Vec<String> {
+        mock.take_requests()
+            .into_iter()
+            .map(|request| {
+                assert_eq!(request.if_match.as_deref(), Some(etag));
+                request.range.unwrap()
+            })
+            .collect()
+    }
+
+    fn range(offset: u64, length: u64) -> String {
+        format!("bytes={}-{}", offset, offset + length - 1)
+    }
+
+    #[tokio::test]
+    async fn test_fetch_chunk_reads_ahead() {
+        let (mock, mut stream, body) = open(256 * KIB).await;
+        let etag = stream.etag.clone().unwrap();
+
+        // Sequential reads are served by the read-ahead, which doubles up to read_ahead
+        let mut offset = 0;
+        for length in [64 * KIB, 128 * KIB, 256 * KIB] {
+            let bytes = stream.fetch_chunk(offset).await.unwrap();
+            assert_eq!(bytes, body[offset as usize..(offset + length) as usize]);
+            offset += length;
+            settle(&stream).await;
+        }
+        // The last chunk stops at the end of the object
+        let last = SIZE as u64 - offset;
+        assert_eq!(
+            ranges(&mock, &etag),
+            [
+                range(0, 64 * KIB),
+                range(64 * KIB, 128 * KIB),
+                range(192 * KIB, 256 * KIB),
+                range(offset, last),
+            ]
+        );
+        assert_eq!(stream.fetch_chunk(offset).await.unwrap().len() as u64, last);
+        assert!(stream.prefetch.is_none());
+
+        // A seek drops the read-ahead and starts over from small chunks
+        let (mock, mut stream, body) = open(256 * KIB).await;
+        stream.fetch_chunk(0).await.unwrap();
+        settle(&stream).await;
+        mock.take_requests();
+        let bytes = stream.fetch_chunk(300 * KIB).await.unwrap();
+        assert_eq!(bytes, body[300 * 1024..364 * 1024]);
+        settle(&stream).await;
+        assert_eq!(
+            ranges(&mock, &etag),
+            [range(300 * KIB, 64 * KIB), range(364 * KIB, 128 * KIB)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_chunk_without_read_ahead() {
+        let (mock, mut stream, _) = open(0).await;
+        let etag = stream.etag.clone().unwrap();
+        for offset in [0, 64 * KIB, 128 * KIB] {
+            stream.fetch_chunk(offset).await.unwrap();
+            assert!(stream.prefetch.is_none());
+        }
+        assert_eq!(
+            ranges(&mock, &etag),
+            [
+                range(0, 64 * KIB),
+                range(64 * KIB, 64 * KIB),
+                range(128 * KIB, 64 * KIB),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reads_pinned_to_etag() {
+        let (mock, mut stream, _) = open(256 * KIB).await;
+        stream.fetch_chunk(0).await.unwrap();
+        settle(&stream).await;
+
+        // The read-ahead was fetched before the change; the chunk after it isn't
+        let _ = mock.with_object("b", "k", vec![0u8; SIZE]);
+        stream.fetch_chunk(64 * KIB).await.unwrap();
+        let err = stream.fetch_chunk(192 * KIB).await.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("changed while it was being downloaded")
+        );
+    }
+}
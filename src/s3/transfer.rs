@@ -0,0 +1,233 @@
+//! Settings for downloading large objects as concurrent ranged GETs and streaming
+//! them with read-ahead, and the bandwidth limit shared by downloads and uploads.
+
+use aws_sdk_s3::primitives::SdkBody;
+use bytes::Bytes;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, ready};
+use std::time::{Duration, Instant};
+use tokio::time::Sleep;
+
+/// Default size of each ranged GET (8 MiB, the AWS CLI default)
+pub const DEFAULT_PART_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Default number of ranged GETs in flight
+pub const DEFAULT_CONCURRENCY: usize = 8;
+
+/// Default largest chunk a streamed read grows to while reading ahead (8 MiB)
+pub const DEFAULT_READ_AHEAD: u64 = 8 * 1024 * 1024;
+
+/// How large objects are split into parallel ranged downloads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferOptions {
+    /// Bytes fetched by each ranged GET; objects no larger than this use a single GET
+    pub part_size: u64,
+    /// Ranged GETs in flight at once (1 = sequential)
+    pub concurrency: usize,
+    /// Bytes per second across all transfers (None = unlimited)
+    pub bandwidth: Option<u64>,
+    /// Prefixes listed at once by recursive walks (see [`PrefixWalker`](super::PrefixWalker))
+    pub list_concurrency: usize,
+    /// Largest chunk streamed archive reads grow to while fetching the next
+    /// chunk ahead of time (0 = no read-ahead)
+    pub read_ahead: u64,
+}
+
+impl Default for TransferOptions {
+    fn default() -> Self {
+        TransferOptions {
+            part_size: DEFAULT_PART_SIZE,
+            concurrency: DEFAULT_CONCURRENCY,
+            bandwidth: None,
+            list_concurrency: super::walker::DEFAULT_LIST_CONCURRENCY,
+            read_ahead: DEFAULT_READ_AHEAD,
+        }
+    }
+}
+
+impl TransferOptions {
+    /// Whether an object of this size is worth splitting into ranged GETs
+    pub fn is_parallel(&self, size: u64) -> bool {
+        self.concurrency > 1 && size > self.part_size
+    }
+
+    /// (offset, length) of each part of an object, in order
+    pub fn parts(&self, size: u64) -> Vec<(u64, u64)> {
+        let part_size = self.part_size.max(1);
+        (0..size.div_ceil(part_size))
+            .map(|i| {
+                let offset = i * part_size;
+                (offset, part_size.min(size - offset))
+            })
+            .collect()
+    }
+}
+
+/// Token bucket pacing transfers to a bandwidth limit
+/// Up to a second's worth of bytes can go out at once; beyond that each transfer
+/// waits its turn. Transfers already running pick up a new rate on their next chunk.
+#[derive(Debug)]
+pub struct BandwidthLimiter {
+    state: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Bytes per second (None = unlimited)
+    rate: Option<u64>,
+    /// Bytes that may go out without waiting (negative = already owed)
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl Default for BandwidthLimiter {
+    fn default() -> Self {
+        BandwidthLimiter {
+            state: Mutex::new(Bucket {
+                rate: None,
+                tokens: 0.0,
+                refilled: Instant::now(),
+            }),
+        }
+    }
+}
+
+impl BandwidthLimiter {
+    pub fn set_rate(&self, rate: Option<u64>) {
+        let mut bucket = self.state.lock().unwrap();
+        if bucket.rate != rate {
+            // Start the new rate from an empty bucket rather than the old rate's debt
+            *bucket = Bucket {
+                rate,
+                tokens: 0.0,
+                refilled: Instant::now(),
+            };
+        }
+    }
+
+    /// Take `bytes` from the bucket, returning how long to wait before sending more
+    pub fn reserve(&self, bytes: u64) -> Duration {
+        self.reserve_at(bytes, Instant::now())
+    }
+
+    fn reserve_at(&self, bytes: u64, now: Instant) -> Duration {
+        let mut bucket = self.state.lock().unwrap();
+        let Some(rate) = bucket.rate else {
+            return Duration::ZERO;
+        };
+        let rate = rate.max(1) as f64;
+        let elapsed = now.saturating_duration_since(bucket.refilled).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(rate);
+        bucket.refilled = now;
+        bucket.tokens -= bytes as f64;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / rate)
+        }
+    }
+
+    /// Wait until `bytes` fit within the limit
+    pub async fn throttle(&self, bytes: u64) {
+        let wait = self.reserve(bytes);
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Pace a body (an upload or a streamed download) to the limit
+    pub fn throttle_body(self: &Arc<Self>, body: SdkBody) -> SdkBody {
+        SdkBody::from_body_1_x(ThrottledBody {
+            body,
+            limiter: Arc::clone(self),
+            delay: None,
+        })
+    }
+}
+
+/// Body that waits out the limiter's delay after each chunk it hands over
+struct ThrottledBody {
+    body: SdkBody,
+    limiter: Arc<BandwidthLimiter>,
+    delay: Option<Pin<Box<Sleep>>>,
+}
+
+impl http_body::Body for ThrottledBody {
+    type Data = Bytes;
+    type Error = <SdkBody as http_body::Body>::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<http_body::Frame<Bytes>, Self::Error>>> {
+        let this = self.get_mut();
+        if let Some(delay) = &mut this.delay {
+            ready!(delay.as_mut().poll(cx));
+            this.delay = None;
+        }
+        let frame = ready!(Pin::new(&mut this.body).poll_frame(cx));
+        if let Some(Ok(frame)) = &frame
+            && let Some(data) = frame.data_ref()
+        {
+            let wait = this.limiter.reserve(data.len() as u64);
+            if !wait.is_zero() {
+                this.delay = Some(Box::pin(tokio::time::sleep(wait)));
+            }
+        }
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.body.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parts() {
+        let options = TransferOptions {
+            part_size: 10,
+            concurrency: 4,
+            ..Default::default()
+        };
+        assert_eq!(options.parts(25), [(0, 10), (10, 10), (20, 5)]);
+        assert_eq!(options.parts(20), [(0, 10), (10, 10)]);
+        assert!(options.parts(0).is_empty());
+
+        assert!(options.is_parallel(11));
+        assert!(!options.is_parallel(10));
+        assert!(
+            !TransferOptions {
+                concurrency: 1,
+                ..options
+            }
+            .is_parallel(100)
+        );
+    }
+
+    #[test]
+    fn test_bandwidth_limiter() {
+        let limiter = BandwidthLimiter::default();
+        assert_eq!(limiter.reserve(1 << 30), Duration::ZERO);
+
+        limiter.set_rate(Some(100));
+        let start = limiter.state.lock().unwrap().refilled;
+        // The bucket starts empty, so the first bytes wait their full share
+        assert_eq!(limiter.reserve_at(50, start), Duration::from_millis(500));
+        // Reservations queue behind each other
+        assert_eq!(limiter.reserve_at(50, start), Duration::from_secs(1));
+        // Time pays off the debt, and an idle bucket holds at most a second's worth
+        let later = start + Duration::from_secs(5);
+        assert_eq!(limiter.reserve_at(100, later), Duration::ZERO);
+        assert_eq!(limiter.reserve_at(50, later), Duration::from_millis(500));
+    }
+}
@@ -0,0 +1,214 @@
+//! Concurrent recursive listing.
+//!
+//! A walk lists one level at a time (delimiter "/"), so sibling prefixes can be listed
+//! concurrently instead of paging through the whole tree in key order. Objects are
+//! yielded a page at a time as the listings arrive, in no particular order.
+
+use anyhow::Result;
+use futures::future::BoxFuture;
+use futures::stream::{self, BoxStream, FuturesUnordered, StreamExt, TryStreamExt};
+use std::collections::VecDeque;
+
+use super::S3Client;
+use super::client::{ListObjectsResult, ObjectInfo};
+
+/// Default number of prefixes listed at once
+pub const DEFAULT_LIST_CONCURRENCY: usize = 8;
+
+/// Walks every object below a prefix, listing up to `workers` prefixes at once
+#[derive(Clone, Copy)]
+pub struct PrefixWalker<'a> {
+    client: &'a S3Client,
+    bucket: &'a str,
+    workers: usize,
+}
+
+/// One page of one prefix's listing
+struct Page {
+    prefix: String,
+    continuation_token: Option<String>,
+}
+
+/// A listed page, and the token for the page after it
+type Listed = (ListObjectsResult, Page, Option<String>);
+
+struct Walk<'a> {
+    pending: VecDeque<Page>,
+    in_flight: FuturesUnordered<BoxFuture<'a, Result<Listed>>>,
+    failed: bool,
+}
+
+impl<'a> PrefixWalker<'a> {
+    /// A walker using the client's `list_concurrency` transfer option
+    pub fn new(client: &'a S3Client, bucket: &'a str) -> Self {
+        PrefixWalker {
+            client,
+            bucket,
+            workers: client.transfer_options().list_concurrency,
+        }
+    }
+
+    /// Set the number of prefixes listed at once (1 = one at a time)
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = workers;
+        self
+    }
+
+    /// Stream the objects below `prefix`, a page at a time
+    /// The stream ends after the first error.
+    pub fn walk(self, prefix: &str) -> BoxStream<'a, Result<Vec<ObjectInfo>>> {
+        let walk = Walk {
+            pending: VecDeque::from([Page {
+                prefix: prefix.to_string(),
+                continuation_token: None,
+            }]),
+            in_flight: FuturesUnordered::new(),
+            failed: false,
+        };
+        let workers = self.workers.max(1);
+
+        stream::unfold(walk, move |mut walk| async move {
+            if walk.failed {
+                return None;
+            }
+            while walk.in_flight.len() < workers
+                && let Some(page) = walk.pending.pop_front()
+            {
+                walk.in_flight.push(self.list(page));
+            }
+
+            match walk.in_flight.next().await? {
+                Ok((listing, page, next_token)) => {
+                    walk.pending
+                        .extend(listing.prefixes.into_iter().map(|prefix| Page {
+                            prefix,
+                            continuation_token: None,
+                        }));
+                    if next_token.is_some() {
+                        walk.pending.push_back(Page {
+                            continuation_token: next_token,
+                            ..page
+                        });
+                    }
+                    Some((Ok(listing.objects), walk))
+                }
+                Err(e) => {
+                    walk.failed = true;
+                    Some((Err(e), walk))
+                }
+            }
+        })
+        .boxed()
+    }
+
+    /// Every object below `prefix`, sorted by key
+    pub async fn collect(self, prefix: &str) -> Result<Vec<ObjectInfo>> {
+        let mut objects: Vec<ObjectInfo> = self.walk(prefix).try_concat().await?;
+        objects.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(objects)
+    }
+
+    fn list(&self, page: Page) -> BoxFuture<'a, Result<Listed>> {
+        let (client, bucket) = (self.client, self.bucket);
+        Box::pin(async move {
+            let (listing, next_token) = client
+                .list_objects_page(
+                    bucket,
+                    &page.prefix,
+                    Some("/"),
+                    page.continuation_token.clone(),
+                )
+                .await?;
+            Ok((listing, page, next_token))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::s3::MockS3Client;
+    use std::collections::BTreeMap;
+
+    /// Every object below `prefix` from one flat listing, paged in key order
+    async fn list_sequentially(client: &S3Client, prefix: &str) -> Vec<ObjectInfo> {
+        let mut objects = Vec::new();
+        let mut token = None;
+        loop {
+            let (listing, next) = client
+                .list_objects_page("b", prefix, None, token)
+                .await
+                .unwrap();
+            objects.extend(listing.objects);
+            match next {
+                Some(next) => token = Some(next),
+                None => return objects,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_walk_matches_sequential_listing() {
+        let mut mock = MockS3Client::new().with_object("b", "other.txt", "outside");
+        for (i, key) in [
+            "top.txt",
+            "a/1.txt",
+            "a/b/2.txt",
+            "a/b/c/3.txt",
+            "d/e/4.txt",
+        ]
+        .iter()
+        .enumerate()
+        {
+            mock = mock.with_object("b", &format!("data/{key}"), vec![b'x'; i + 1]);
+        }
+        // More keys than fit on one page
+        for i in 0..1100 {
+            mock = mock.with_object("b", &format!("data/a/b/many/{i:04}"), vec![b'y'; i % 7]);
+        }
+        let client = mock.client();
+
+        let expected = list_sequentially(&client, "data/").await;
+        assert_eq!(expected.len(), 1105);
+        let keys = |objects: &[ObjectInfo]| -> Vec<String> {
+            objects.iter().map(|obj| obj.key.clone()).collect()
+        };
+        for workers in [1, 4] {
+            let walked = PrefixWalker::new(&client, "b")
+                .workers(workers)
+                .collect("data/")
+                .await
+                .unwrap();
+            assert_eq!(keys(&walked), keys(&expected));
+        }
+
+        let totals = (
+            expected.len(),
+            expected.iter().map(|obj| obj.size).sum::<u64>(),
+        );
+        assert_eq!(
+            client
+                .summarize_prefix("b", "data/", |_| true)
+                .await
+                .unwrap(),
+            totals
+        );
+
+        // An inventory that can't be read falls back to listing
+        mock.take_requests();
+        client.set_inventory_locations(BTreeMap::from([(
+            "b".to_string(),
+            "s3://missing-inventory/b/daily/".to_string(),
+        )]));
+        assert_eq!(
+            client
+                .summarize_prefix("b", "data/", |_| true)
+                .await
+                .unwrap(),
+            totals
+        );
+        let requests = mock.take_requests();
+        assert!(requests.iter().any(|r| r.bucket == "missing-inventory"));
+        assert!(requests.iter().any(|r| r.bucket == "b"));
+    }
+}
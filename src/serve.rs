@@ -0,0 +1,308 @@
+//! Read-only web UI over the virtual filesystem (`s3sh serve`).
+//!
+//! URLs mirror shell paths under the served location: a path ending in '/' lists a
+//! bucket, prefix, or archive directory, and any other path shows a preview of the
+//! file's first bytes. Adding `?raw` streams the whole file instead. Parquet files
+//! list like directories, with their schema shown above the listing.
+
+use anyhow::Result;
+use bytes::Bytes;
+use futures::TryStreamExt;
+use http_body_util::combinators::BoxBody;
+use http_body_util::{BodyExt, Full, StreamBody};
+use hyper::body::{Frame, Incoming};
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use std::convert::Infallible;
+use std::fmt::Write;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio_util::io::ReaderStream;
+
+use crate::archive::format_for_name;
+use crate::session::{Entry, EntryKind, Session};
+use crate::shell::commands::xxd::hex_dump;
+use crate::ui::looks_binary;
+use crate::vfs::VfsNode;
+
+/// Bytes of a file shown on its preview page
+const PREVIEW_BYTES: u64 = 64 * 1024;
+
+type Body = BoxBody<Bytes, std::io::Error>;
+
+/// Answer requests on `listener` until the returned future is dropped
+/// Each connection is served on its own task; the session's archive cache is shared.
+pub async fn serve(session: Session, listener: TcpListener) -> Result<()> {
+    let session = Arc::new(session);
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let session = Arc::clone(&session);
+        tokio::spawn(async move {
+            let service = service_fn(move |req| {
+                let session = Arc::clone(&session);
+                async move { Ok::<_, Infallible>(handle(&session, req).await) }
+            });
+            if let Err(e) = hyper::server::conn::http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                tracing::debug!("serve: connection from {peer} failed: {e}");
+            }
+        });
+    }
+}
+
+async fn handle(session: &Session, req: Request<Incoming>) -> Response<Body> {
+    if req.method() != Method::GET && req.method() != Method::HEAD {
+        return error_page(StatusCode::METHOD_NOT_ALLOWED, "Only GET is supported");
+    }
+    let url_path = decode_path(req.uri().path());
+    // Paths are relative to the served location and can't climb out of it
+    if url_path.split('/').any(|segment| segment == "..") {
+        return error_page(StatusCode::BAD_REQUEST, "Paths may not contain '..'");
+    }
+    let rel = url_path.trim_start_matches('/');
+    let raw = req
+        .uri()
+        .query()
+        .is_some_and(|q| q.split('&').any(|p| p == "raw" || p.starts_with("raw=")));
+    tracing::debug!(path = rel, raw, "serve: request");
+
+    let result = if rel.is_empty() || rel.ends_with('/') {
+        directory_page(session, rel).await
+    } else if raw {
+        raw_file(session, rel).await
+    } else {
+        preview_page(session, rel).await
+    };
+    result.unwrap_or_else(|e| error_page(StatusCode::NOT_FOUND, &format!("{e:#}")))
+}
+
+/// A listing, with a Parquet file's schema above it
+async fn directory_page(session: &Session, rel: &str) -> Result<Response<Body>> {
+    let dir = if rel.is_empty() { "." } else { rel };
+    let entries = session.ls(dir).await?;
+
+    let mut html = header(&display_path(session, rel));
+    if !rel.is_empty() {
+        html.push_str("<p><a href=\"../\">..</a></p>\n");
+    }
+    if entries.iter().any(|e| e.name == "_schema.txt")
+        && let Ok(schema) = session.read(&format!("{rel}_schema.txt")).await
+    {
+        let _ = writeln!(
+            html,
+            "<h2>Schema</h2>\n<pre>{}</pre>",
+            escape(&String::from_utf8_lossy(&schema))
+        );
+    }
+    html.push_str("<table>\n<tr><th>Name</th><th>Size</th><th>Modified</th></tr>\n");
+    for entry in &entries {
+        let _ = writeln!(
+            html,
+            "<tr><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td></tr>",
+            encode_path(&href(entry)),
+            escape(&href(entry)),
+            entry
+                .size
+                .map(|size| humansize::format_size(size, humansize::BINARY))
+                .unwrap_or_default(),
+            escape(entry.modified.as_deref().unwrap_or("")),
+        );
+    }
+    html.push_str("</table>\n");
+    Ok(html_response(StatusCode::OK, html))
+}
+
+/// The start of a file as text or a hex dump, with a link to the whole file
+async fn preview_page(session: &Session, rel: &str) -> Result<Response<Body>> {
+    let bytes = session.read_head(rel, PREVIEW_BYTES).await?;
+    let name = rel.rsplit('/').next().unwrap_or(rel);
+
+    let mut html = header(&display_path(session, rel));
+    let _ = writeln!(
+        html,
+        "<p><a href=\"./\">Back</a> · <a href=\"{}?raw\">Download</a></p>",
+        encode_path(name)
+    );
+    if bytes.len() as u64 == PREVIEW_BYTES {
+        let _ = writeln!(
+            html,
+            "<p>Showing the first {}.</p>",
+            humansize::format_size(PREVIEW_BYTES, humansize::BINARY)
+        );
+    }
+    let text = if looks_binary(&bytes) {
+        hex_dump(0, &bytes).join("\n")
+    } else {
+        String::from_utf8_lossy(&bytes).into_owned()
+    };
+    let _ = writeln!(html, "<pre>{}</pre>", escape(&text));
+    Ok(html_response(StatusCode::OK, html))
+}
+
+/// The whole file; objects are streamed, archive entries are extracted first
+async fn raw_file(session: &Session, rel: &str) -> Result<Response<Body>> {
+    let (body, length) = match session.resolve(rel).await? {
+        VfsNode::Object {
+            bucket,
+            key,
+            version_id,
+            size,
+            ..
+        } => {
+            let stream = session
+                .client()
+                .get_object_version_stream(&bucket, &key, version_id.as_deref())
+                .await?;
+            let frames = ReaderStream::new(stream.into_async_read()).map_ok(Frame::data);
+            (StreamBody::new(frames).boxed(), size)
+        }
+        _ => {
+            let bytes = session.read(rel).await?;
+            let length = bytes.len() as u64;
+            (full(bytes), length)
+        }
+    };
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, "application/octet-stream")
+        .header(CONTENT_LENGTH, length)
+        .body(body)
+        .expect("static headers are valid"))
+}
+
+fn error_page(status: StatusCode, message: &str) -> Response<Body> {
+    let mut html = header(status.canonical_reason().unwrap_or("Error"));
+    let _ = writeln!(html, "<p>{}</p>", escape(message));
+    html_response(status, html)
+}
+
+fn header(title: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{0} - s3sh</title>\n\
+         <style>body{{font-family:sans-serif;margin:2em}}td,th{{padding:0 1em 0 0;text-align:left}}\
+         pre{{background:#f4f4f4;padding:1em;overflow:auto}}</style></head>\n\
+         <body>\n<h1>{0}</h1>\n",
+        escape(title)
+    )
+}
+
+fn html_response(status: StatusCode, html: String) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "text/html; charset=utf-8")
+        .body(full(Bytes::from(html)))
+        .expect("static headers are valid")
+}
+
+fn full(bytes: Bytes) -> Body {
+    Full::new(bytes).map_err(|never| match never {}).boxed()
+}
+
+/// The shell path a request refers to, for page titles
+fn display_path(session: &Session, rel: &str) -> String {
+    format!("{}/{rel}", session.cwd().path().trim_end_matches('/'))
+}
+
+/// Link target for a listed entry; anything that lists like a directory ends in '/'
+fn href(entry: &Entry) -> String {
+    let is_dir = match entry.kind {
+        EntryKind::Bucket | EntryKind::Directory => true,
+        EntryKind::File => format_for_name(&entry.name).is_some_and(|f| f.is_navigable()),
+    };
+    if is_dir {
+        format!("{}/", entry.name)
+    } else {
+        entry.name.clone()
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Percent-encode a path for a link, keeping '/' separators
+fn encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// Decode %XX escapes in a URL path ('+' is a literal plus in paths)
+fn decode_path(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%')
+            .then(|| path.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match hex {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode_path() {
+        assert_eq!(encode_path("logs/a b+c.txt"), "logs/a%20b%2Bc.txt");
+        assert_eq!(decode_path("logs/a%20b+c.txt"), "logs/a b+c.txt");
+        assert_eq!(decode_path(&encode_path("données/ü.csv")), "données/ü.csv");
+        assert_eq!(decode_path("100%"), "100%");
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape("<a href=\"x\">&'</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&#39;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_href() {
+        let entry = |name: &str, kind| Entry {
+            name: name.to_string(),
+            path: format!("/bucket/{name}"),
+            kind,
+            size: None,
+            modified: None,
+        };
+        assert_eq!(href(&entry("logs", EntryKind::Directory)), "logs/");
+        assert_eq!(href(&entry("backup.zip", EntryKind::File)), "backup.zip/");
+        assert_eq!(href(&entry("notes.txt", EntryKind::File)), "notes.txt");
+    }
+}
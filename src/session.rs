@@ -0,0 +1,279 @@
+//! Library access to the s3sh filesystem, without the shell.
+//!
+//! A [`Session`] resolves the same paths the shell does (buckets, prefixes, and the
+//! inside of archives) and returns structured results instead of printing them:
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! let session = s3sh::Session::connect("aws").await?;
+//! for entry in session.ls("/my-bucket/logs/archive.tar.gz").await? {
+//!     println!("{} {:?}", entry.path, entry.size);
+//! }
+//! let bytes = session.read("/my-bucket/logs/archive.tar.gz/app.log").await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use anyhow::{Result, anyhow};
+use bytes::Bytes;
+use std::sync::Arc;
+
+use crate::archive::handler_for;
+use crate::cache::ArchiveCache;
+use crate::providers::ProviderRegistry;
+use crate::s3::S3Client;
+use crate::vfs::{PathResolver, VfsNode};
+
+/// Archive indexes kept in memory, as in the shell
+const CACHE_CAPACITY: usize = 100;
+
+/// What a listed entry is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    Bucket,
+    /// A prefix, or a directory inside an archive
+    Directory,
+    File,
+}
+
+/// One item of a listing
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    /// Base name, without a trailing '/'
+    pub name: String,
+    /// Absolute path, usable with the other [`Session`] methods
+    pub path: String,
+    pub kind: EntryKind,
+    /// Size in bytes, for files
+    pub size: Option<u64>,
+    /// Last modification (or bucket creation) time, where known
+    pub modified: Option<String>,
+}
+
+/// A working directory and archive cache over one S3 client
+pub struct Session {
+    client: Arc<S3Client>,
+    cache: ArchiveCache,
+    cwd: VfsNode,
+}
+
+impl Session {
+    /// A session at the bucket list, using an existing client
+    pub fn new(client: Arc<S3Client>) -> Self {
+        Session {
+            client,
+            cache: ArchiveCache::new(CACHE_CAPACITY),
+            cwd: VfsNode::Root,
+        }
+    }
+
+    /// A session sharing the shell's client, archive cache, and working directory
+    pub(crate) fn from_parts(client: Arc<S3Client>, cache: ArchiveCache, cwd: VfsNode) -> Self {
+        Session { client, cache, cwd }
+    }
+
+    /// A session on a named provider ("aws", "gcs", ...), configured from the environment
+    pub async fn connect(provider_name: &str) -> Result<Self> {
+        let registry = ProviderRegistry::new();
+        let provider = registry
+            .get(provider_name)
+            .ok_or_else(|| anyhow!("Unknown provider: {provider_name}"))?;
+        let config = provider.build_config().await?;
+        let client = S3Client::from_provider(provider.name(), config).await?;
+        Ok(Self::new(Arc::new(client)))
+    }
+
+    pub fn client(&self) -> &Arc<S3Client> {
+        &self.client
+    }
+
+    /// The working directory relative paths are resolved against
+    pub fn cwd(&self) -> &VfsNode {
+        &self.cwd
+    }
+
+    /// Change the working directory; archives are opened like directories
+    pub async fn cd(&mut self, path: &str) -> Result<()> {
+        let node = self.resolver().resolve_dir(&self.cwd, path).await?;
+        if !node.is_navigable() {
+            return Err(anyhow!("Not a directory: {path}"));
+        }
+        self.cwd = node;
+        Ok(())
+    }
+
+    /// Resolve a path, absolute or relative to the working directory
+    /// An archive named by the last segment is returned as an object.
+    pub async fn resolve(&self, path: &str) -> Result<VfsNode> {
+        self.resolver().resolve(&self.cwd, path).await
+    }
+
+    /// The contents of a directory: the bucket list, a prefix, or a directory in an archive
+    pub async fn ls(&self, path: &str) -> Result<Vec<Entry>> {
+        let node = self.resolver().resolve_dir(&self.cwd, path).await?;
+        let base = node.path();
+        let child_path = |name: &str| format!("{}/{name}", base.trim_end_matches('/'));
+
+        match &node {
+            VfsNode::Root => Ok(self
+                .client
+                .list_buckets()
+                .await?
+                .into_iter()
+                .map(|bucket| Entry {
+                    path: child_path(&bucket.name),
+                    name: bucket.name,
+                    kind: EntryKind::Bucket,
+                    size: None,
+                    modified: bucket.creation_date,
+                })
+                .collect()),
+            VfsNode::Bucket { name: bucket } | VfsNode::Prefix { bucket, .. } => {
+                let prefix = match &node {
+                    VfsNode::Prefix { prefix, .. } => prefix.as_str(),
+                    _ => "",
+                };
+                let mut entries = Vec::new();
+                let mut token = None;
+                loop {
+                    let (listing, next) = self
+                        .client
+                        .list_objects_page(bucket, prefix, Some("/"), token)
+                        .await?;
+                    entries.extend(listing.prefixes.iter().map(|p| {
+                        let name = p[prefix.len()..].trim_end_matches('/').to_string();
+                        Entry {
+                            path: child_path(&name),
+                            name,
+                            kind: EntryKind::Directory,
+                            size: None,
+                            modified: None,
+                        }
+                    }));
+                    entries.extend(listing.objects.into_iter().filter_map(|obj| {
+                        // Skip the zero-byte marker for the prefix itself
+                        let name = obj.key.get(prefix.len()..)?.to_string();
+                        (!name.is_empty()).then(|| Entry {
+                            path: child_path(&name),
+                            name,
+                            kind: EntryKind::File,
+                            size: Some(obj.size),
+                            modified: obj.last_modified,
+                        })
+                    }));
+                    match next {
+                        Some(next) => token = Some(next),
+                        None => break,
+                    }
+                }
+                Ok(entries)
+            }
+            VfsNode::Archive { .. } | VfsNode::ArchiveEntry { is_dir: true, .. } => {
+                let (archive, dir) = match &node {
+                    VfsNode::ArchiveEntry { archive, path, .. } => {
+                        (archive.as_ref(), path.as_str())
+                    }
+                    _ => (&node, ""),
+                };
+                let VfsNode::Archive { archive_type, .. } = archive else {
+                    return Err(anyhow!("Not an archive"));
+                };
+                let index = self.resolver().index(archive).await?;
+                let handler = handler_for(archive_type)
+                    .ok_or_else(|| anyhow!("Archive type not yet supported"))?;
+                Ok(handler
+                    .list_entries(&index, dir)
+                    .into_iter()
+                    .map(|entry| {
+                        let name = entry
+                            .path
+                            .trim_end_matches('/')
+                            .rsplit('/')
+                            .next()
+                            .unwrap_or(&entry.path)
+                            .to_string();
+                        Entry {
+                            path: child_path(&name),
+                            name,
+                            kind: if entry.is_dir {
+                                EntryKind::Directory
+                            } else {
+                                EntryKind::File
+                            },
+                            size: (!entry.is_dir).then_some(entry.size),
+                            modified: entry.attrs.modified(),
+                        }
+                    })
+                    .collect())
+            }
+            _ => Err(anyhow!("Not a directory: {path}")),
+        }
+    }
+
+    /// The whole contents of an object or a file inside an archive
+    pub async fn read(&self, path: &str) -> Result<Bytes> {
+        match self.resolve(path).await? {
+            VfsNode::Object {
+                bucket,
+                key,
+                version_id,
+                ..
+            } => {
+                self.client
+                    .get_object_version(&bucket, &key, version_id.as_deref())
+                    .await
+            }
+            VfsNode::ArchiveEntry { is_dir: true, .. } => Err(anyhow!("Is a directory: {path}")),
+            VfsNode::ArchiveEntry {
+                archive,
+                path: entry,
+                ..
+            } => {
+                let index = self.resolver().index(&archive).await?;
+                let VfsNode::Archive {
+                    parent,
+                    archive_type,
+                    ..
+                } = archive.as_ref()
+                else {
+                    return Err(anyhow!("Not an archive"));
+                };
+                let VfsNode::Object { bucket, key, .. } = parent.as_ref() else {
+                    return Err(anyhow!("Reading files in nested archives isn't supported"));
+                };
+                let handler = handler_for(archive_type)
+                    .ok_or_else(|| anyhow!("Archive type not yet supported"))?;
+                handler
+                    .extract_file(&self.client, bucket, key, &index, &entry)
+                    .await
+            }
+            VfsNode::Prefix { .. } => Err(anyhow!("File not found: {path}")),
+            _ => Err(anyhow!("Is a directory: {path}")),
+        }
+    }
+
+    /// Up to `limit` bytes from the start of a file, for previews
+    /// Objects are fetched with a ranged GET; files inside archives are extracted whole.
+    pub async fn read_head(&self, path: &str, limit: u64) -> Result<Bytes> {
+        match self.resolve(path).await? {
+            VfsNode::Object {
+                bucket, key, size, ..
+            } => {
+                if size == 0 || limit == 0 {
+                    return Ok(Bytes::new());
+                }
+                self.client
+                    .get_object_range(&bucket, &key, 0, size.min(limit))
+                    .await
+            }
+            _ => {
+                let bytes = self.read(path).await?;
+                Ok(bytes.slice(..bytes.len().min(limit as usize)))
+            }
+        }
+    }
+
+    fn resolver(&self) -> PathResolver {
+        PathResolver::new(Arc::clone(&self.client), self.cache.clone())
+    }
+}
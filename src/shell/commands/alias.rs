@@ -0,0 +1,84 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+
+use super::output::{Output, print_line};
+use super::{Command, ShellState};
+
+pub struct AliasCommand;
+
+#[async_trait]
+impl Command for AliasCommand {
+    fn name(&self) -> &str {
+        "alias"
+    }
+
+    fn usage(&self) -> &str {
+        "alias [NAME[=VALUE]] - List, show, or define command aliases"
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        let [arg] = args else {
+            if !args.is_empty() {
+                return Err(anyhow!("Usage: {}", self.usage()));
+            }
+            for (name, expansion) in state.config().aliases() {
+                print_line!(out, "alias {name}='{expansion}'");
+            }
+            return Ok(());
+        };
+
+        let Some((name, expansion)) = arg.split_once('=') else {
+            let expansion = state
+                .config()
+                .alias(arg)
+                .ok_or_else(|| anyhow!("alias: {arg}: not found"))?;
+            print_line!(out, "alias {arg}='{expansion}'");
+            return Ok(());
+        };
+
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(anyhow!("alias: invalid name: '{name}'"));
+        }
+
+        let config = state.config_mut();
+        config.set_alias(name, expansion);
+        config.save()
+    }
+}
+
+pub struct UnaliasCommand;
+
+#[async_trait]
+impl Command for UnaliasCommand {
+    fn name(&self) -> &str {
+        "unalias"
+    }
+
+    fn usage(&self) -> &str {
+        "unalias NAME... - Remove command aliases"
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        _out: &mut Output,
+    ) -> Result<()> {
+        if args.is_empty() {
+            return Err(anyhow!("Usage: {}", self.usage()));
+        }
+
+        let config = state.config_mut();
+        for name in args {
+            if !config.remove_alias(name) {
+                return Err(anyhow!("unalias: {name}: not found"));
+            }
+        }
+        config.save()
+    }
+}
@@ -0,0 +1,274 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use colored::*;
+
+use super::output::{Output, print_line};
+use super::{Command, ShellState};
+use crate::ui::{confirm, create_spinner};
+use crate::vfs::VfsNode;
+
+/// Strip the leading '/' a bucket argument has after `s3://name` becomes a shell path
+fn bucket_arg(arg: &str) -> Result<&str> {
+    let name = arg.trim_start_matches('/').trim_end_matches('/');
+    if name.is_empty() || name.contains('/') {
+        return Err(anyhow!("Not a bucket name: {arg}"));
+    }
+    Ok(name)
+}
+
+/// Check a name against S3's bucket naming rules, so typos fail before any request
+fn validate_bucket_name(name: &str) -> Result<()> {
+    let valid = (3..=63).contains(&name.len())
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'.' || b == b'-')
+        && name.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && name.ends_with(|c: char| c.is_ascii_alphanumeric())
+        && !name.contains("..");
+    if !valid {
+        return Err(anyhow!(
+            "Invalid bucket name: {name} (3-63 lowercase letters, digits, dots, and hyphens)"
+        ));
+    }
+    Ok(())
+}
+
+pub struct MbCommand;
+
+#[async_trait]
+impl Command for MbCommand {
+    fn name(&self) -> &str {
+        "mb"
+    }
+
+    fn usage(&self) -> &str {
+        "mb [--region REGION] BUCKET - Create a bucket"
+    }
+
+    fn flags(&self) -> &[(&str, &str)] {
+        &[(
+            "--region REGION",
+            "Create the bucket in REGION instead of the shell's region",
+        )]
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        let mut region: Option<&String> = None;
+        let mut names = Vec::new();
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--region" => {
+                    region = Some(
+                        iter.next()
+                            .ok_or_else(|| anyhow!("--region requires a value"))?,
+                    )
+                }
+                _ => names.push(arg),
+            }
+        }
+        let [name] = names.as_slice() else {
+            return Err(anyhow!("Usage: {}", self.usage()));
+        };
+        let name = bucket_arg(name)?;
+        validate_bucket_name(name)?;
+
+        let client = state.s3_client();
+        let region = region.map(String::as_str);
+        client.create_bucket(name, region).await?;
+        // The bucket list at / is cached for completion
+        state.completion_cache().clear();
+
+        print_line!(
+            out,
+            "make_bucket: {name} ({})",
+            region.unwrap_or(client.region())
+        );
+        Ok(())
+    }
+}
+
+pub struct RbCommand;
+
+#[async_trait]
+impl Command for RbCommand {
+    fn name(&self) -> &str {
+        "rb"
+    }
+
+    fn usage(&self) -> &str {
+        "rb [-y|--yes] BUCKET - Delete an empty bucket"
+    }
+
+    fn flags(&self) -> &[(&str, &str)] {
+        &[("-y, --yes", "Delete without asking first")]
+    }
+
+    fn interactive(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        let yes = args.iter().any(|a| a == "-y" || a == "--yes");
+        let names: Vec<&String> = args
+            .iter()
+            .filter(|a| *a != "-y" && *a != "--yes")
+            .collect();
+        let [name] = names.as_slice() else {
+            return Err(anyhow!("Usage: {}", self.usage()));
+        };
+        let name = bucket_arg(name)?;
+
+        let client = state.s3_client();
+        client.check_writable("delete", name, "")?;
+
+        // S3 refuses to delete a bucket with objects in it; say so before asking
+        if client.prefix_exists(name, "").await? {
+            return Err(anyhow!(
+                "Bucket not empty: {name} (delete its objects first)"
+            ));
+        }
+        if !yes && !confirm(&format!("Delete bucket {name}?"))? {
+            print_line!(out, "Not deleted");
+            return Ok(());
+        }
+
+        client.delete_bucket(name).await?;
+        state.completion_cache().clear();
+
+        // Don't leave the shell inside a bucket that no longer exists
+        let inside = match state.current_node() {
+            VfsNode::Bucket { name: current } => current == name,
+            VfsNode::Prefix { bucket, .. } => bucket == name,
+            _ => false,
+        };
+        if inside {
+            state.change_dir(VfsNode::Root);
+        }
+
+        print_line!(out, "remove_bucket: {name}");
+        Ok(())
+    }
+}
+
+pub struct BucketCommand;
+
+#[async_trait]
+impl Command for BucketCommand {
+    fn name(&self) -> &str {
+        "bucket"
+    }
+
+    fn usage(&self) -> &str {
+        "bucket info [BUCKET] - Show a bucket's region, versioning, encryption, and public access block"
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        let name = match args {
+            [sub] if sub == "info" => match state.current_node() {
+                VfsNode::Bucket { name } => name.clone(),
+                VfsNode::Prefix { bucket, .. } => bucket.clone(),
+                _ => return Err(anyhow!("Not in a bucket; usage: {}", self.usage())),
+            },
+            [sub, name] if sub == "info" => bucket_arg(name)?.to_string(),
+            _ => return Err(anyhow!("Usage: {}", self.usage())),
+        };
+
+        let client = state.s3_client();
+        let spinner = create_spinner(&format!("Inspecting {name}..."));
+        let (versioning, encryption, public_access) = tokio::join!(
+            client.get_bucket_versioning(&name),
+            client.get_bucket_encryption(&name),
+            client.get_public_access_block(&name),
+        );
+        let region = client.bucket_region(&name).await;
+        spinner.finish_and_clear();
+
+        // Each setting needs its own permission, so one denial doesn't hide the rest
+        let unavailable = |e: anyhow::Error| format!("unavailable ({e:#})").dimmed().to_string();
+
+        print_line!(out, "{:<14} {name}", "Bucket:");
+        print_line!(out, "{:<14} {region}", "Region:");
+        let versioning = match versioning {
+            Ok(Some(status)) => status,
+            Ok(None) => "Never enabled".to_string(),
+            Err(e) => unavailable(e),
+        };
+        print_line!(out, "{:<14} {versioning}", "Versioning:");
+        let encryption = match encryption {
+            Ok(Some(description)) => description,
+            Ok(None) => "None".to_string(),
+            Err(e) => unavailable(e),
+        };
+        print_line!(out, "{:<14} {encryption}", "Encryption:");
+        match public_access {
+            Ok(Some(block)) => {
+                let summary = if block.blocks_all() {
+                    "all public access blocked".green().to_string()
+                } else {
+                    "partial".yellow().to_string()
+                };
+                print_line!(out, "{:<14} {summary}", "Public access:");
+                for (setting, on) in [
+                    ("BlockPublicAcls", block.block_public_acls),
+                    ("IgnorePublicAcls", block.ignore_public_acls),
+                    ("BlockPublicPolicy", block.block_public_policy),
+                    ("RestrictPublicBuckets", block.restrict_public_buckets),
+                ] {
+                    print_line!(out, "  {setting:<22} {}", if on { "on" } else { "off" });
+                }
+            }
+            Ok(None) => {
+                print_line!(
+                    out,
+                    "{:<14} {}",
+                    "Public access:",
+                    "no block configured".yellow()
+                );
+            }
+            Err(e) => print_line!(out, "{:<14} {}", "Public access:", unavailable(e)),
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_names() {
+        assert_eq!(bucket_arg("/my-bucket").unwrap(), "my-bucket");
+        assert_eq!(bucket_arg("my-bucket/").unwrap(), "my-bucket");
+        assert!(bucket_arg("/my-bucket/logs").is_err());
+
+        assert!(validate_bucket_name("my-bucket.logs-2024").is_ok());
+        for name in [
+            "ab",
+            "My-Bucket",
+            "-bucket",
+            "bucket-",
+            "a..b",
+            "under_score",
+        ] {
+            assert!(validate_bucket_name(name).is_err(), "{name}");
+        }
+    }
+}
@@ -1,16 +1,23 @@
 use anyhow::{Result, anyhow};
 use async_trait::async_trait;
-use std::sync::Arc;
+use bytes::Bytes;
+use futures::TryStreamExt;
+use futures::stream::BoxStream;
 
-use super::output::{print_line, print_str};
+use super::output::{Output, print_bytes, print_line, print_str};
 use super::{Command, ShellState};
-use crate::archive::ArchiveHandler;
+use crate::archive::{
+    DEFAULT_ROW_LIMIT, ExtractLimits, RowRange, handler_for, with_extract_limits,
+};
 #[cfg(feature = "parquet")]
-use crate::archive::ParquetHandler;
-use crate::archive::tar::TarHandler;
-use crate::archive::zip::ZipHandler;
+use crate::archive::{ExportFormat, ParquetHandler};
 use crate::ui::create_spinner;
-use crate::vfs::{ArchiveType, VfsNode, VirtualPath};
+#[cfg(feature = "parquet")]
+use crate::vfs::ArchiveType;
+use crate::vfs::VfsNode;
+
+/// Bytes of binary content shown as a hex dump
+const BINARY_PREVIEW_LEN: usize = 1024;
 
 pub struct CatCommand;
 
@@ -21,28 +28,134 @@ impl Command for CatCommand {
     }
 
     fn usage(&self) -> &str {
-        "cat FILE - Display file contents"
+        "cat [--rows N] [--skip N] [--format csv|json [--limit N]] [--version-id ID] [--no-limits] FILE - Display file contents"
+    }
+
+    fn flags(&self) -> &[(&str, &str)] {
+        &[
+            ("--rows N", "Show only N rows of a Parquet column file"),
+            ("--skip N", "Skip the first N rows of a Parquet column file"),
+            (
+                "--format csv|json",
+                "Print a Parquet file as CSV or JSON lines",
+            ),
+            ("--limit N", "With --format, stop after N rows"),
+            ("--version-id ID", "Read an older version of the object"),
+            (
+                "--no-limits",
+                "Lift the archive extraction size limits for this command",
+            ),
+        ]
     }
 
-    async fn execute(&self, state: &mut ShellState, args: &[String]) -> Result<()> {
-        if args.is_empty() {
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        // Skip the zip bomb limits for this command only, without touching the shared client
+        if let Some(pos) = args.iter().position(|arg| arg == "--no-limits") {
+            let mut args = args.to_vec();
+            args.remove(pos);
+            return with_extract_limits(ExtractLimits::UNLIMITED, self.execute(state, &args, out))
+                .await;
+        }
+
+        // --rows/--skip select a window of a Parquet column or _head.csv
+        // --format/--limit convert a whole Parquet file
+        let mut rows: Option<usize> = None;
+        let mut skip: Option<usize> = None;
+        let mut format: Option<&String> = None;
+        let mut limit: Option<usize> = None;
+        let mut version_id: Option<&String> = None;
+        let mut paths = Vec::new();
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--rows" => rows = Some(Self::parse_count(arg, iter.next())?),
+                "--skip" => skip = Some(Self::parse_count(arg, iter.next())?),
+                "--format" => {
+                    format = Some(
+                        iter.next()
+                            .ok_or_else(|| anyhow!("--format requires a value"))?,
+                    )
+                }
+                "--limit" => limit = Some(Self::parse_count(arg, iter.next())?),
+                "--version-id" => {
+                    version_id = Some(
+                        iter.next()
+                            .ok_or_else(|| anyhow!("--version-id requires a value"))?,
+                    )
+                }
+                _ => paths.push(arg),
+            }
+        }
+
+        let Some(path_str) = paths.first() else {
             return Err(anyhow!("Usage: cat FILE"));
+        };
+
+        if let Some(format) = format {
+            if rows.is_some() || skip.is_some() {
+                return Err(anyhow!("--format cannot be combined with --rows or --skip"));
+            }
+            return self.export(state, out, path_str, format, limit).await;
+        }
+        if limit.is_some() {
+            return Err(anyhow!("--limit only applies with --format"));
         }
 
-        let path_str = &args[0];
+        let row_range = (rows.is_some() || skip.is_some()).then(|| RowRange {
+            skip: skip.unwrap_or(0),
+            limit: rows.unwrap_or(DEFAULT_ROW_LIMIT),
+        });
 
         // Resolve the path to a node
-        let target_node = if path_str.starts_with('/') {
-            self.resolve_absolute(state, path_str).await?
-        } else {
-            self.resolve_relative(state, path_str).await?
-        };
+        let mut target_node = Self::resolve_file(state, path_str).await?;
+
+        if let Some(id) = version_id {
+            match &mut target_node {
+                VfsNode::Object { version_id, .. } => *version_id = Some(id.clone()),
+                _ => return Err(anyhow!("--version-id only applies to S3 objects")),
+            }
+        }
 
         // Read the file
         match &target_node {
-            VfsNode::Object { bucket, key, .. } => {
-                let bytes = state.s3_client().get_object(bucket, key).await?;
-                Self::display_bytes(&bytes)?;
+            VfsNode::Object { .. } if row_range.is_some() => {
+                return Err(anyhow!(
+                    "--rows and --skip only apply to Parquet column files"
+                ));
+            }
+
+            VfsNode::Object {
+                bucket,
+                key,
+                size,
+                version_id,
+            } => {
+                // Stream chunk by chunk so memory stays flat on multi-GB objects
+                let client = state.s3_client();
+                let (chunks, size) = match version_id {
+                    // Large objects come down as concurrent ranged GETs
+                    None => (
+                        client.get_object_chunks(bucket, key, Some(*size)).await?,
+                        *size,
+                    ),
+                    Some(id) => {
+                        let size = client
+                            .head_object_version(bucket, key, Some(id))
+                            .await?
+                            .size;
+                        (
+                            client.get_object_version_chunks(bucket, key, id).await?,
+                            size,
+                        )
+                    }
+                };
+                Self::display_stream(out, chunks, size).await?;
             }
 
             VfsNode::ArchiveEntry {
@@ -56,95 +169,53 @@ impl Command for CatCommand {
                 }
 
                 // Get bucket and key from archive
-                let (bucket, key, archive_type, index) = match archive.as_ref() {
+                let (bucket, key, archive_type) = match archive.as_ref() {
                     VfsNode::Archive {
                         parent,
                         archive_type,
-                        index,
-                    } => {
-                        let (b, k) = match parent.as_ref() {
-                            VfsNode::Object { bucket, key, .. } => (bucket, key),
-                            _ => return Err(anyhow!("Invalid archive parent")),
-                        };
-                        (b, k, archive_type, index)
-                    }
+                        ..
+                    } => match parent.as_ref() {
+                        VfsNode::Object { bucket, key, .. } => (bucket, key, archive_type),
+                        _ => return Err(anyhow!("Invalid archive parent")),
+                    },
                     _ => return Err(anyhow!("Not an archive")),
                 };
-
-                // Get or build index
-                let idx = if let Some(i) = index {
-                    Arc::clone(i)
-                } else {
-                    let cache_key = format!("s3://{bucket}/{key}");
-                    if let Some(cached) = state.cache().get(&cache_key) {
-                        cached
-                    } else {
-                        // Show spinner while building index
-                        let filename = key.split('/').next_back().unwrap_or(key);
-                        let spinner = create_spinner(&format!("Building index for {filename}..."));
-
-                        let built = match archive_type {
-                            ArchiveType::Zip => {
-                                let handler = ZipHandler::new();
-                                handler.build_index(state.s3_client(), bucket, key).await?
-                            }
-                            ArchiveType::Tar | ArchiveType::TarGz | ArchiveType::TarBz2 => {
-                                let handler = TarHandler::new(archive_type.clone());
-                                handler.build_index(state.s3_client(), bucket, key).await?
-                            }
-                            #[cfg(feature = "parquet")]
-                            ArchiveType::Parquet => {
-                                let handler = ParquetHandler::new();
-                                handler.build_index(state.s3_client(), bucket, key).await?
-                            }
-                            _ => {
-                                spinner.finish_and_clear();
-                                return Err(anyhow!("Archive type not yet supported"));
-                            }
-                        };
-
-                        spinner.finish_and_clear();
-
-                        let arc = Arc::new(built);
-                        state.cache().put(cache_key, Arc::clone(&arc));
-                        arc
-                    }
-                };
+                let idx = state.resolver().index(archive).await?;
+
+                #[cfg(feature = "parquet")]
+                let supports_rows = *archive_type == ArchiveType::Parquet;
+                #[cfg(not(feature = "parquet"))]
+                let supports_rows = false;
+                if row_range.is_some() && !supports_rows {
+                    return Err(anyhow!(
+                        "--rows and --skip only apply to Parquet column files"
+                    ));
+                }
 
                 // Show spinner while extracting file
                 let filename = file_path.split('/').next_back().unwrap_or(file_path);
                 let spinner = create_spinner(&format!("Extracting {filename}..."));
 
                 // Extract the file
-                let bytes = match archive_type {
-                    ArchiveType::Zip => {
-                        let handler = ZipHandler::new();
-                        handler
-                            .extract_file(state.s3_client(), bucket, key, &idx, file_path)
-                            .await?
-                    }
-                    ArchiveType::Tar | ArchiveType::TarGz | ArchiveType::TarBz2 => {
-                        let handler = TarHandler::new(archive_type.clone());
-                        handler
-                            .extract_file(state.s3_client(), bucket, key, &idx, file_path)
-                            .await?
-                    }
+                let bytes = match (archive_type, row_range) {
                     #[cfg(feature = "parquet")]
-                    ArchiveType::Parquet => {
-                        let handler = ParquetHandler::new();
-                        handler
-                            .extract_file(state.s3_client(), bucket, key, &idx, file_path)
-                            .await?
-                    }
-                    _ => {
-                        spinner.finish_and_clear();
-                        return Err(anyhow!("Archive type not yet supported"));
+                    (ArchiveType::Parquet, Some(range)) => {
+                        ParquetHandler::new()
+                            .extract_rows(&idx, file_path, range)
+                            .await
                     }
+                    _ => match handler_for(archive_type) {
+                        Some(handler) => {
+                            handler
+                                .extract_file(state.s3_client(), bucket, key, &idx, file_path)
+                                .await
+                        }
+                        None => Err(anyhow!("Archive type not yet supported")),
+                    },
                 };
-
                 spinner.finish_and_clear();
 
-                Self::display_bytes(&bytes)?;
+                Self::display_bytes(out, &bytes?)?;
             }
 
             _ => {
@@ -157,128 +228,235 @@ impl Command for CatCommand {
 }
 
 impl CatCommand {
-    /// Display file contents, handling both text and binary data.
-    /// For binary data, displays first 1KB as hex dump.
-    fn display_bytes(bytes: &[u8]) -> Result<()> {
-        match String::from_utf8(bytes.to_vec()) {
-            Ok(text) => print_str!("{text}"),
-            Err(_) => {
-                eprintln!("Warning: File contains binary data");
-                let display_len = bytes.len().min(1024);
-                for (i, byte) in bytes[..display_len].iter().enumerate() {
-                    if i % 16 == 0 {
-                        print_str!("\n{i:08x}: ");
-                    }
-                    print_str!("{byte:02x} ");
-                }
-                print_line!();
-                if bytes.len() > 1024 {
-                    eprintln!("... ({} more bytes)", bytes.len() - 1024);
-                }
-            }
+    /// Convert a Parquet file to CSV or JSON Lines, streaming batches to stdout
+    #[cfg(feature = "parquet")]
+    async fn export(
+        &self,
+        state: &mut ShellState,
+        out: &mut Output,
+        path_str: &str,
+        format: &str,
+        limit: Option<usize>,
+    ) -> Result<()> {
+        use futures::StreamExt;
+
+        let format = ExportFormat::parse(format)
+            .ok_or_else(|| anyhow!("Unknown format: {format} (expected csv or json)"))?;
+
+        let target_node = Self::resolve_file(state, path_str).await?;
+
+        let is_parquet = matches!(&target_node, VfsNode::Object { key, .. }
+            if ArchiveType::from_path(key) == Some(ArchiveType::Parquet));
+        if !is_parquet {
+            return Err(anyhow!("--format only applies to Parquet files"));
         }
-        Ok(())
-    }
 
-    /// Resolve absolute path to a VFS node
-    async fn resolve_absolute(&self, state: &ShellState, path: &str) -> Result<VfsNode> {
-        let vpath = VirtualPath::parse(path);
-        let segments = vpath.segments();
+        let archive = VfsNode::Archive {
+            parent: Box::new(target_node),
+            archive_type: ArchiveType::Parquet,
+            index: None,
+        };
+        let index = state.resolver().index(&archive).await?;
 
-        if segments.len() < 2 {
-            return Err(anyhow!("Invalid file path: {path}"));
+        let handler = ParquetHandler::new();
+        let mut stream = handler.export(&index, format, limit)?;
+        while let Some(chunk) = stream.next().await {
+            print_bytes!(out, &chunk?);
         }
 
-        let bucket = &segments[0];
-        let key = segments[1..].join("/");
+        Ok(())
+    }
 
-        // Get object metadata
-        let metadata = state.s3_client().head_object(bucket, &key).await?;
+    #[cfg(not(feature = "parquet"))]
+    async fn export(
+        &self,
+        _state: &mut ShellState,
+        _out: &mut Output,
+        _path_str: &str,
+        _format: &str,
+        _limit: Option<usize>,
+    ) -> Result<()> {
+        Err(anyhow!(
+            "--format requires Parquet support (build with --features parquet)"
+        ))
+    }
 
-        Ok(VfsNode::Object {
-            bucket: bucket.clone(),
-            key,
-            size: metadata.size,
-        })
+    /// Parse the numeric value of a --rows/--skip/--limit flag
+    fn parse_count(flag: &str, value: Option<&String>) -> Result<usize> {
+        value
+            .ok_or_else(|| anyhow!("{flag} requires a value"))?
+            .parse()
+            .map_err(|_| anyhow!("{flag} expects a number"))
     }
 
-    /// Resolve relative path to a VFS node
-    async fn resolve_relative(&self, state: &ShellState, path: &str) -> Result<VfsNode> {
-        let current = state.current_node();
-
-        match current {
-            VfsNode::Bucket { name } => {
-                let metadata = state.s3_client().head_object(name, path).await?;
-                Ok(VfsNode::Object {
-                    bucket: name.clone(),
-                    key: path.to_string(),
-                    size: metadata.size,
-                })
-            }
+    /// Display file contents, handling both text and binary data.
+    /// For binary data, displays first 1KB as hex dump.
+    fn display_bytes(out: &mut Output, bytes: &[u8]) -> Result<()> {
+        match String::from_utf8(bytes.to_vec()) {
+            Ok(text) => print_str!(out, "{text}"),
+            Err(_) => Self::display_binary(out, bytes, bytes.len() as u64)?,
+        }
+        Ok(())
+    }
 
-            VfsNode::Prefix { bucket, prefix } => {
-                let key = format!("{prefix}{path}");
-                let metadata = state.s3_client().head_object(bucket, &key).await?;
-                Ok(VfsNode::Object {
-                    bucket: bucket.clone(),
-                    key,
-                    size: metadata.size,
-                })
-            }
+    /// Print an object as it downloads; whether it's text is decided from the first chunk
+    async fn display_stream(
+        out: &mut Output,
+        mut chunks: BoxStream<'_, Result<Bytes>>,
+        size: u64,
+    ) -> Result<()> {
+        let Some(first) = chunks.try_next().await? else {
+            return Ok(());
+        };
 
-            VfsNode::Archive { index, .. } => {
-                // Resolve file at root of archive
-                let idx = if let Some(i) = index {
-                    Arc::clone(i)
-                } else {
-                    return Err(anyhow!("Archive index not available"));
-                };
+        if !is_text(&first) {
+            // Only the start is shown, so stop downloading once there's enough of it
+            let mut head = first.to_vec();
+            while head.len() < BINARY_PREVIEW_LEN
+                && let Some(chunk) = chunks.try_next().await?
+            {
+                head.extend_from_slice(&chunk);
+            }
+            return Self::display_binary(out, &head, size.max(head.len() as u64));
+        }
 
-                let entry = idx
-                    .find_entry(path)
-                    .ok_or_else(|| anyhow!("File not found in archive: {path}"))?;
+        // Returning on BrokenPipe drops the stream, ending the download early
+        print_bytes!(out, &first);
+        while let Some(chunk) = chunks.try_next().await? {
+            print_bytes!(out, &chunk);
+        }
+        Ok(())
+    }
 
-                Ok(VfsNode::ArchiveEntry {
-                    archive: Box::new(current.clone()),
-                    path: path.to_string(),
-                    size: entry.size,
-                    is_dir: entry.is_dir,
-                })
+    /// Hex dump the first bytes of binary content of `total` bytes
+    fn display_binary(out: &mut Output, bytes: &[u8], total: u64) -> Result<()> {
+        eprintln!("Warning: File contains binary data");
+        let display_len = bytes.len().min(BINARY_PREVIEW_LEN);
+        for (i, byte) in bytes[..display_len].iter().enumerate() {
+            if i % 16 == 0 {
+                print_str!(out, "\n{i:08x}: ");
             }
+            print_str!(out, "{byte:02x} ");
+        }
+        print_line!(out);
+        if total > display_len as u64 {
+            eprintln!(
+                "... ({} more bytes; use xxd --offset to see the rest)",
+                total - display_len as u64
+            );
+        }
+        Ok(())
+    }
 
-            VfsNode::ArchiveEntry {
-                archive,
-                path: current_path,
-                ..
-            } => {
-                // Resolve file relative to current path in archive
-                let idx = match archive.as_ref() {
-                    VfsNode::Archive { index: Some(i), .. } => Arc::clone(i),
-                    _ => return Err(anyhow!("Archive index not available")),
-                };
+    /// Resolve a path that must name a file: an object or an entry inside an archive
+    async fn resolve_file(state: &ShellState, path: &str) -> Result<VfsNode> {
+        match state.resolver().resolve(state.current_node(), path).await? {
+            node @ (VfsNode::Object { .. } | VfsNode::ArchiveEntry { .. }) => Ok(node),
+            // Names that aren't objects resolve to prefixes
+            VfsNode::Prefix { .. } => Err(anyhow!("File not found: {path}")),
+            _ => Err(anyhow!("Not a file: {path}")),
+        }
+    }
+}
 
-                // Build full path
-                let full_path = if current_path.is_empty() {
-                    path.to_string()
-                } else {
-                    format!("{}/{}", current_path.trim_end_matches('/'), path)
-                };
+/// Whether a chunk is UTF-8 text; a character split at the chunk's end still counts
+pub(super) fn is_text(chunk: &[u8]) -> bool {
+    match std::str::from_utf8(chunk) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none(),
+    }
+}
 
-                let entry = idx
-                    .find_entry(&full_path)
-                    .ok_or_else(|| anyhow!("File not found in archive: {path}"))?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::{ArchiveEntry, ArchiveIndex, ArchiveType};
+    use futures::StreamExt;
+
+    #[test]
+    fn test_is_text() {
+        assert!(is_text(b"hello\nworld\n"));
+        assert!(is_text("caf\u{e9}".as_bytes()));
+        // "é" cut in half by a chunk boundary
+        assert!(is_text(&"caf\u{e9}".as_bytes()[..4]));
+        assert!(!is_text(b"PK\x03\x04\xff\x00"));
+    }
 
-                Ok(VfsNode::ArchiveEntry {
-                    archive: archive.clone(),
-                    path: full_path,
-                    size: entry.size,
-                    is_dir: entry.is_dir,
-                })
-            }
+    #[tokio::test]
+    async fn test_display_stream() {
+        let chunks = futures::stream::iter(["abc", "def\n"].map(|s| Ok(Bytes::from(s)))).boxed();
+        let mut out = Output::capture();
+        CatCommand::display_stream(&mut out, chunks, 7)
+            .await
+            .unwrap();
+        assert_eq!(out.captured().unwrap(), b"abcdef\n");
+
+        let binary = vec![0xffu8; 3000];
+        let chunks = futures::stream::iter(
+            binary
+                .chunks(100)
+                .map(|c| Ok(Bytes::copy_from_slice(c)))
+                .collect::<Vec<_>>(),
+        )
+        .boxed();
+        let mut out = Output::capture();
+        CatCommand::display_stream(&mut out, chunks, 3000)
+            .await
+            .unwrap();
+        let dump = String::from_utf8(out.captured().unwrap().to_vec()).unwrap();
+        assert!(dump.starts_with("\n00000000: ff ff"));
+        assert_eq!(dump.matches("ff").count(), BINARY_PREVIEW_LEN);
+    }
 
-            _ => Err(anyhow!(
-                "Cannot resolve relative path from current location"
-            )),
-        }
+    #[tokio::test]
+    async fn test_no_limits_is_per_command() {
+        // A zip holding one stored file, with its index built by hand
+        let data = b"hello world";
+        let crc = crc32fast::hash(data);
+        let mut zip = b"PK\x03\x04\x14\x00\x00\x00\x00\x00\x00\x00\x00\x00".to_vec();
+        zip.extend(crc.to_le_bytes());
+        zip.extend((data.len() as u32).to_le_bytes());
+        zip.extend((data.len() as u32).to_le_bytes());
+        zip.extend(5u16.to_le_bytes());
+        zip.extend(0u16.to_le_bytes());
+        zip.extend(b"a.txt");
+        zip.extend(data);
+        let size = zip.len() as u64;
+        let mock = crate::s3::MockS3Client::new().with_object("b", "a.zip", zip);
+
+        let entry = ArchiveEntry::zip_entry("a.txt".to_string(), 11, false, 0, 11, 0, crc);
+        let mut state = ShellState::with_client(std::sync::Arc::new(mock.client()))
+            .await
+            .unwrap();
+        state.set_current_node(VfsNode::Archive {
+            parent: Box::new(VfsNode::Object {
+                bucket: "b".to_string(),
+                key: "a.zip".to_string(),
+                size,
+                version_id: None,
+            }),
+            archive_type: ArchiveType::Zip,
+            index: Some(std::sync::Arc::new(ArchiveIndex {
+                entries: [("a.txt".to_string(), entry)].into(),
+                metadata: Default::default(),
+                #[cfg(feature = "parquet")]
+                parquet_store: None,
+                #[cfg(feature = "parquet")]
+                parquet_metadata: None,
+            })),
+        });
+
+        let mut out = Output::capture();
+        state
+            .execute_to("set max_extract_size 4", &mut out)
+            .await
+            .unwrap();
+        let err = state
+            .execute_to("cat --no-limits a.txt && cat a.txt", &mut out)
+            .await
+            .unwrap_err();
+        assert_eq!(out.captured().unwrap(), b"hello world");
+        // The next command in the chain is held to the limit again
+        assert!(err.to_string().contains("too large"), "{err}");
     }
 }
@@ -0,0 +1,503 @@
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use md5::{Digest, Md5};
+use sha2::Sha256;
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+
+use super::output::{Output, print_line};
+use super::{Command, ShellState, resolve_object};
+use crate::shell::settings::parse_size;
+use crate::ui::create_spinner;
+
+/// Read size when hashing a local file
+const LOCAL_CHUNK: usize = 1 << 20;
+
+pub struct ChecksumCommand;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Algorithm {
+    Md5,
+    Sha256,
+    /// S3's ETag: the MD5 for single-part uploads, MD5-of-part-MD5s plus "-N" for multipart
+    Etag,
+}
+
+impl Algorithm {
+    fn parse(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "md5" => Ok(Self::Md5),
+            "sha256" => Ok(Self::Sha256),
+            "etag" => Ok(Self::Etag),
+            _ => Err(anyhow!(
+                "Unknown algorithm: {name} (expected md5, sha256 or etag)"
+            )),
+        }
+    }
+}
+
+/// Incremental digest over an object or file, fed chunk by chunk
+enum Hasher {
+    Md5(Md5),
+    Sha256(Sha256),
+    Etag {
+        /// None = single-part upload, i.e. the plain MD5
+        part_size: Option<u64>,
+        part: Md5,
+        part_len: u64,
+        /// Concatenated MD5s of the finished parts
+        part_digests: Vec<u8>,
+        parts: usize,
+    },
+}
+
+impl Hasher {
+    fn new(algorithm: Algorithm, part_size: Option<u64>) -> Self {
+        match algorithm {
+            Algorithm::Md5 => Self::Md5(Md5::new()),
+            Algorithm::Sha256 => Self::Sha256(Sha256::new()),
+            Algorithm::Etag => Self::Etag {
+                part_size,
+                part: Md5::new(),
+                part_len: 0,
+                part_digests: Vec::new(),
+                parts: 0,
+            },
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        match self {
+            Self::Md5(hasher) => hasher.update(data),
+            Self::Sha256(hasher) => hasher.update(data),
+            Self::Etag {
+                part_size: None,
+                part,
+                ..
+            } => part.update(data),
+            Self::Etag {
+                part_size: Some(part_size),
+                part,
+                part_len,
+                part_digests,
+                parts,
+            } => {
+                // Chunks don't line up with parts, so split them at part boundaries
+                while !data.is_empty() {
+                    let room = (*part_size - *part_len).min(data.len() as u64) as usize;
+                    part.update(&data[..room]);
+                    *part_len += room as u64;
+                    data = &data[room..];
+
+                    if *part_len == *part_size {
+                        part_digests.extend_from_slice(&part.finalize_reset());
+                        *part_len = 0;
+                        *parts += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    fn finalize(self) -> String {
+        match self {
+            Self::Md5(hasher) => to_hex(&hasher.finalize()),
+            Self::Sha256(hasher) => to_hex(&hasher.finalize()),
+            Self::Etag {
+                part_size: None,
+                part,
+                ..
+            } => to_hex(&part.finalize()),
+            Self::Etag {
+                part_size: Some(_),
+                part,
+                part_len,
+                mut part_digests,
+                mut parts,
+            } => {
+                if part_len > 0 || parts == 0 {
+                    part_digests.extend_from_slice(&part.finalize());
+                    parts += 1;
+                }
+                format!("{}-{parts}", to_hex(&Md5::digest(&part_digests)))
+            }
+        }
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Part sizes that could have produced a multipart ETag ("...-N") for an object of this size
+/// The common tool defaults come first, then the smallest whole-MiB size that yields N parts.
+fn candidate_part_sizes(size: u64, parts: u64) -> Vec<u64> {
+    const MIB: u64 = 1 << 20;
+    const COMMON: &[u64] = &[8, 16, 5, 15, 10, 32, 50, 64, 100, 128, 256, 512];
+    if parts == 0 {
+        return Vec::new();
+    }
+
+    let fits = |part_size: u64| size.div_ceil(part_size).max(1) == parts;
+    let smallest = size.div_ceil(parts).div_ceil(MIB).max(1) * MIB;
+
+    let mut candidates: Vec<u64> = Vec::new();
+    for part_size in COMMON.iter().map(|mib| mib * MIB).chain([smallest]) {
+        if fits(part_size) && !candidates.contains(&part_size) {
+            candidates.push(part_size);
+        }
+    }
+    if parts == 1 {
+        // Any part size at least as large as the object gives the same ETag
+        candidates.truncate(1);
+    }
+    candidates
+}
+
+/// Digest a local file once per part size, reading it a single time
+async fn hash_file(
+    path: &Path,
+    algorithm: Algorithm,
+    part_sizes: &[Option<u64>],
+) -> Result<Vec<String>> {
+    let mut hashers: Vec<Hasher> = part_sizes
+        .iter()
+        .map(|part_size| Hasher::new(algorithm, *part_size))
+        .collect();
+    let mut file = tokio::fs::File::open(path)
+        .await
+        .with_context(|| format!("Cannot open {}", path.display()))?;
+    let mut buf = vec![0u8; LOCAL_CHUNK];
+    loop {
+        let n = file
+            .read(&mut buf)
+            .await
+            .with_context(|| format!("Cannot read {}", path.display()))?;
+        if n == 0 {
+            break;
+        }
+        hashers
+            .iter_mut()
+            .for_each(|hasher| hasher.update(&buf[..n]));
+    }
+    Ok(hashers.into_iter().map(Hasher::finalize).collect())
+}
+
+/// Check whether a local file has the content behind an S3 ETag
+/// Returns None when that can't be told: the part size of a multipart upload can't be inferred.
+pub(crate) async fn local_file_matches_etag(
+    path: &Path,
+    size: u64,
+    etag: &str,
+) -> Result<Option<bool>> {
+    let etag = etag.trim_matches('"');
+    let part_sizes: Vec<Option<u64>> = match etag.rsplit_once('-') {
+        Some((_, parts)) => {
+            let Ok(parts) = parts.parse() else {
+                return Ok(None);
+            };
+            candidate_part_sizes(size, parts)
+                .into_iter()
+                .map(Some)
+                .collect()
+        }
+        None => vec![None],
+    };
+    if part_sizes.is_empty() {
+        return Ok(None);
+    }
+
+    let digests = hash_file(path, Algorithm::Etag, &part_sizes).await?;
+    Ok(Some(digests.iter().any(|digest| digest == etag)))
+}
+
+#[async_trait]
+impl Command for ChecksumCommand {
+    fn name(&self) -> &str {
+        "checksum"
+    }
+
+    fn usage(&self) -> &str {
+        "checksum [--algo md5|sha256|etag] [--part-size SIZE] [--local PATH] FILE - Compute an object's digest"
+    }
+
+    fn flags(&self) -> &[(&str, &str)] {
+        &[
+            (
+                "-a, --algo md5|sha256|etag",
+                "Digest to compute (default md5)",
+            ),
+            (
+                "--part-size SIZE",
+                "Part size for a multipart ETag (guessed when omitted)",
+            ),
+            (
+                "--local PATH",
+                "Compare against the same digest of a local file",
+            ),
+        ]
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        let mut algorithm = Algorithm::Md5;
+        let mut part_size: Option<u64> = None;
+        let mut local: Option<&String> = None;
+        let mut positional = Vec::new();
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--algo" | "-a" => {
+                    algorithm = Algorithm::parse(
+                        iter.next()
+                            .ok_or_else(|| anyhow!("--algo requires a value"))?,
+                    )?
+                }
+                "--part-size" => {
+                    let size = parse_size(
+                        iter.next()
+                            .ok_or_else(|| anyhow!("--part-size requires a value"))?,
+                    )?;
+                    if size == 0 {
+                        return Err(anyhow!("--part-size must be greater than zero"));
+                    }
+                    part_size = Some(size);
+                }
+                "--local" => {
+                    local = Some(
+                        iter.next()
+                            .ok_or_else(|| anyhow!("--local requires a value"))?,
+                    )
+                }
+                _ => positional.push(arg),
+            }
+        }
+
+        let [path] = positional.as_slice() else {
+            return Err(anyhow!("Usage: {}", self.usage()));
+        };
+        if part_size.is_some() && algorithm != Algorithm::Etag {
+            return Err(anyhow!("--part-size only applies to --algo etag"));
+        }
+
+        let (bucket, key, _) = resolve_object(state, path).await?;
+        let metadata = state.s3_client().head_object(&bucket, &key).await?;
+        let etag = metadata
+            .etag
+            .as_deref()
+            .map(|etag| etag.trim_matches('"').to_string());
+
+        // Without an explicit part size, reproduce the layout the ETag says was uploaded,
+        // trying each plausible part size in a single pass over the object
+        let mut part_sizes = vec![part_size];
+        if algorithm == Algorithm::Etag && part_size.is_none() {
+            let parts = etag
+                .as_deref()
+                .and_then(|etag| etag.rsplit_once('-'))
+                .and_then(|(_, parts)| parts.parse::<u64>().ok());
+            if let Some(parts) = parts {
+                part_sizes = candidate_part_sizes(metadata.size, parts)
+                    .into_iter()
+                    .map(Some)
+                    .collect();
+                if part_sizes.is_empty() {
+                    return Err(anyhow!(
+                        "Cannot infer the part size of a {parts}-part upload; use --part-size"
+                    ));
+                }
+            }
+        }
+
+        let spinner = create_spinner(&format!("Computing checksum of {path}..."));
+        let digests = async {
+            let mut hashers: Vec<Hasher> = part_sizes
+                .iter()
+                .map(|part_size| Hasher::new(algorithm, *part_size))
+                .collect();
+            // Ranged GETs count toward the bandwidth limit and metrics
+            let mut chunks = state
+                .s3_client()
+                .get_object_chunks(&bucket, &key, Some(metadata.size))
+                .await?;
+            while let Some(chunk) = chunks.try_next().await? {
+                hashers.iter_mut().for_each(|hasher| hasher.update(&chunk));
+            }
+            Ok::<_, anyhow::Error>(
+                hashers
+                    .into_iter()
+                    .map(Hasher::finalize)
+                    .collect::<Vec<_>>(),
+            )
+        }
+        .await;
+        spinner.finish_and_clear();
+        let digests = digests?;
+
+        // Prefer the part size that reproduces the ETag
+        let index = digests
+            .iter()
+            .position(|digest| Some(digest) == etag.as_ref())
+            .unwrap_or(0);
+        let (digest, part_size) = (&digests[index], part_sizes[index]);
+
+        print_line!(out, "{digest}  {path}");
+
+        if let Some(local) = local {
+            let local_digest = hash_file(local.as_ref(), algorithm, &[part_size])
+                .await?
+                .remove(0);
+
+            print_line!(out, "{local_digest}  {local}");
+            if local_digest != *digest {
+                return Err(anyhow!("Checksum mismatch: {local} differs from {path}"));
+            }
+            print_line!(out, "OK: {local} matches {path}");
+        } else if algorithm == Algorithm::Etag {
+            match etag {
+                Some(etag) if etag == *digest => print_line!(out, "OK: matches the S3 ETag"),
+                // SSE-KMS and SSE-C objects have ETags that aren't content digests
+                Some(etag) => {
+                    return Err(anyhow!(
+                        "ETag mismatch: S3 reports {etag} (objects encrypted with SSE-KMS or SSE-C never match)"
+                    ));
+                }
+                None => print_line!(out, "S3 did not return an ETag to compare against"),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(algorithm: Algorithm, part_size: Option<u64>, chunks: &[&[u8]]) -> String {
+        let mut hasher = Hasher::new(algorithm, part_size);
+        for chunk in chunks {
+            hasher.update(chunk);
+        }
+        hasher.finalize()
+    }
+
+    #[test]
+    fn test_digests() {
+        assert_eq!(
+            digest(Algorithm::Md5, None, &[b"hello ", b"world"]),
+            "5eb63bbbe01eeed093cb22bb8f5acdc3"
+        );
+        assert_eq!(
+            digest(Algorithm::Sha256, None, &[b"hello world"]),
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+        );
+        assert_eq!(
+            digest(Algorithm::Etag, None, &[b"hello world"]),
+            "5eb63bbbe01eeed093cb22bb8f5acdc3"
+        );
+    }
+
+    #[test]
+    fn test_multipart_etag() {
+        // Parts "hello", " worl", "d", fed in chunks that straddle the boundaries
+        let mut expected = Vec::new();
+        for part in [&b"hello"[..], b" worl", b"d"] {
+            expected.extend_from_slice(&Md5::digest(part));
+        }
+        let expected = format!("{}-3", to_hex(&Md5::digest(&expected)));
+
+        assert_eq!(
+            digest(Algorithm::Etag, Some(5), &[b"hel", b"lo wo", b"rld"]),
+            expected
+        );
+        assert_eq!(
+            digest(Algorithm::Etag, Some(5), &[b"hello world"]),
+            expected
+        );
+
+        // An empty multipart upload still has one part
+        assert!(digest(Algorithm::Etag, Some(5), &[]).ends_with("-1"));
+    }
+
+    #[test]
+    fn test_candidate_part_sizes() {
+        const MIB: u64 = 1 << 20;
+        assert_eq!(candidate_part_sizes(20 * MIB, 3), [8 * MIB, 7 * MIB]);
+        assert_eq!(
+            candidate_part_sizes(16 * MIB, 2),
+            [8 * MIB, 15 * MIB, 10 * MIB]
+        );
+        assert_eq!(candidate_part_sizes(100, 1), [8 * MIB]);
+        assert!(candidate_part_sizes(MIB, 5).is_empty());
+        assert!(candidate_part_sizes(MIB, 0).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_local_file_matches_etag() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hello.txt");
+        std::fs::write(&path, "hello world").unwrap();
+
+        let etag = "\"5eb63bbbe01eeed093cb22bb8f5acdc3\"";
+        assert_eq!(
+            local_file_matches_etag(&path, 11, etag).await.unwrap(),
+            Some(true)
+        );
+        assert_eq!(
+            local_file_matches_etag(&path, 11, "\"00000000000000000000000000000000\"")
+                .await
+                .unwrap(),
+            Some(false)
+        );
+        // 11 bytes can't have been uploaded in 3 whole-MiB parts
+        assert_eq!(
+            local_file_matches_etag(&path, 11, "abc-3").await.unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_object_read_in_ranged_parts() {
+        // Two and a half 1 MiB parts
+        let body: Vec<u8> = (0..5 << 19).map(|i| (i % 251) as u8).collect();
+        let md5 = format!("{:x}", Md5::digest(&body));
+        let mock = crate::s3::MockS3Client::new().with_object("b", "big.bin", body);
+        let mut state = ShellState::with_client(std::sync::Arc::new(mock.client()))
+            .await
+            .unwrap();
+        state
+            .execute_to("set part_size 1MiB", &mut Output::capture())
+            .await
+            .unwrap();
+        mock.take_requests();
+
+        let mut out = Output::capture();
+        state
+            .execute_to("checksum --algo etag /b/big.bin", &mut out)
+            .await
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out.captured().unwrap().to_vec()).unwrap(),
+            format!("{md5}  /b/big.bin\nOK: matches the S3 ETag\n")
+        );
+        let ranges: Vec<_> = mock
+            .take_requests()
+            .into_iter()
+            .filter_map(|request| request.range)
+            .collect();
+        assert_eq!(
+            ranges,
+            [
+                "bytes=0-1048575",
+                "bytes=1048576-2097151",
+                "bytes=2097152-2621439"
+            ]
+        );
+    }
+}
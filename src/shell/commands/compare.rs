@@ -0,0 +1,261 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::output::{Output, print_line};
+use super::{Command, ShellState};
+use crate::s3::S3Client;
+use crate::ui::create_spinner;
+use crate::vfs::VfsNode;
+
+pub struct CompareCommand;
+
+/// An object's size and ETag, keyed by its path relative to the compared prefix
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Entry {
+    pub(super) size: u64,
+    etag: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) enum Change {
+    OnlyInA,
+    OnlyInB,
+    SizeDiffers(u64, u64),
+    EtagDiffers,
+}
+
+#[async_trait]
+impl Command for CompareCommand {
+    fn name(&self) -> &str {
+        "compare"
+    }
+
+    fn usage(&self) -> &str {
+        "compare [-r] [-a] A B - Report keys only in one prefix, and objects whose size or ETag differ"
+    }
+
+    fn flags(&self) -> &[(&str, &str)] {
+        &[
+            (
+                "-r, --recursive",
+                "Compare everything under the prefixes, not just one level",
+            ),
+            ("-a, --all", "Also list keys that match"),
+        ]
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        let mut recursive = false;
+        let mut show_same = false;
+        let mut positional = Vec::new();
+        for arg in args {
+            match arg.as_str() {
+                "-r" | "-R" | "--recursive" => recursive = true,
+                "-a" | "--all" => show_same = true,
+                _ => positional.push(arg.as_str()),
+            }
+        }
+        let [a, b] = positional.as_slice() else {
+            return Err(anyhow!("Usage: {}", self.usage()));
+        };
+
+        let (bucket_a, prefix_a) = Self::resolve_prefix(state, a).await?;
+        let (bucket_b, prefix_b) = Self::resolve_prefix(state, b).await?;
+
+        let client = state.s3_client();
+        let spinner = create_spinner("Listing...");
+        let listed = tokio::try_join!(
+            list(client, &bucket_a, &prefix_a, recursive),
+            list(client, &bucket_b, &prefix_b, recursive),
+        );
+        spinner.finish_and_clear();
+        let (entries_a, entries_b) = listed?;
+
+        let changes = compare(&entries_a, &entries_b);
+        let mut counts = [0usize; 4];
+        for (rel, change) in &changes {
+            let (slot, line) = match change {
+                Change::OnlyInA => (0, format!("only in A: {rel}")),
+                Change::OnlyInB => (1, format!("only in B: {rel}")),
+                Change::SizeDiffers(size_a, size_b) => (
+                    2,
+                    format!(
+                        "differs:   {rel} ({} vs {})",
+                        humansize::format_size(*size_a, humansize::BINARY),
+                        humansize::format_size(*size_b, humansize::BINARY)
+                    ),
+                ),
+                Change::EtagDiffers => (2, format!("differs:   {rel} (ETag)")),
+            };
+            counts[slot] += 1;
+            print_line!(out, "{line}");
+        }
+
+        let changed: BTreeSet<&String> = changes.iter().map(|(rel, _)| rel).collect();
+        let same: Vec<&String> = entries_a
+            .keys()
+            .filter(|rel| !changed.contains(rel))
+            .collect();
+        counts[3] = same.len();
+        if show_same {
+            for rel in same {
+                print_line!(out, "same:      {rel}");
+            }
+        }
+
+        print_line!(
+            out,
+            "{} only in A, {} only in B, {} differ, {} identical",
+            counts[0],
+            counts[1],
+            counts[2],
+            counts[3]
+        );
+        Ok(())
+    }
+}
+
+impl CompareCommand {
+    /// Resolve a path to a bucket and a prefix ending in '/' (or empty, for a whole bucket)
+    pub(super) async fn resolve_prefix(state: &ShellState, path: &str) -> Result<(String, String)> {
+        match state
+            .resolver()
+            .resolve_dir(state.current_node(), path)
+            .await?
+        {
+            VfsNode::Bucket { name } => Ok((name, String::new())),
+            VfsNode::Prefix { bucket, prefix } => Ok((bucket, prefix)),
+            _ => Err(anyhow!("Not an S3 prefix: {path}")),
+        }
+    }
+}
+
+/// The objects below a prefix, every page of them; only the top level unless `recursive`
+pub(super) async fn list(
+    client: &S3Client,
+    bucket: &str,
+    prefix: &str,
+    recursive: bool,
+) -> Result<BTreeMap<String, Entry>> {
+    let objects = if recursive {
+        client.list_objects_recursive(bucket, prefix).await?
+    } else {
+        let mut objects = Vec::new();
+        let mut token = None;
+        loop {
+            let (listing, next) = client
+                .list_objects_page(bucket, prefix, Some("/"), token)
+                .await?;
+            objects.extend(listing.objects);
+            match next {
+                Some(next) => token = Some(next),
+                None => break,
+            }
+        }
+        objects
+    };
+
+    Ok(objects
+        .into_iter()
+        .filter_map(|obj| {
+            let rel = obj.key.strip_prefix(prefix)?;
+            // Zero-byte "folder" markers aren't files
+            (!rel.is_empty() && !rel.ends_with('/')).then(|| {
+                (
+                    rel.to_string(),
+                    Entry {
+                        size: obj.size,
+                        etag: obj.etag,
+                    },
+                )
+            })
+        })
+        .collect())
+}
+
+/// Every key that's missing on one side or differs, in key order
+/// Sizes are compared first; equal sizes fall back to the ETag when both sides have one.
+pub(super) fn compare(
+    a: &BTreeMap<String, Entry>,
+    b: &BTreeMap<String, Entry>,
+) -> Vec<(String, Change)> {
+    let mut changes: Vec<(String, Change)> = a
+        .iter()
+        .filter_map(|(rel, entry_a)| {
+            let change = match b.get(rel) {
+                None => Change::OnlyInA,
+                Some(entry_b) if entry_a.size != entry_b.size => {
+                    Change::SizeDiffers(entry_a.size, entry_b.size)
+                }
+                Some(Entry {
+                    etag: Some(etag_b), ..
+                }) if entry_a.etag.as_ref().is_some_and(|etag_a| etag_a != etag_b) => {
+                    Change::EtagDiffers
+                }
+                Some(_) => return None,
+            };
+            Some((rel.clone(), change))
+        })
+        .collect();
+    changes.extend(
+        b.keys()
+            .filter(|rel| !a.contains_key(*rel))
+            .map(|rel| (rel.clone(), Change::OnlyInB)),
+    );
+    changes.sort_by(|x, y| x.0.cmp(&y.0));
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(items: &[(&str, u64, &str)]) -> BTreeMap<String, Entry> {
+        items
+            .iter()
+            .map(|(rel, size, etag)| {
+                (
+                    rel.to_string(),
+                    Entry {
+                        size: *size,
+                        etag: (!etag.is_empty()).then(|| etag.to_string()),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compare() {
+        let a = entries(&[
+            ("a.txt", 1, "x"),
+            ("b.txt", 2, "x"),
+            ("c.txt", 3, "x"),
+            ("d.txt", 4, "x"),
+            ("e.txt", 5, ""),
+        ]);
+        let b = entries(&[
+            ("b.txt", 20, "x"),
+            ("c.txt", 3, "y"),
+            ("d.txt", 4, "x"),
+            ("e.txt", 5, "z"),
+            ("f.txt", 6, "x"),
+        ]);
+        assert_eq!(
+            compare(&a, &b),
+            vec![
+                ("a.txt".to_string(), Change::OnlyInA),
+                ("b.txt".to_string(), Change::SizeDiffers(2, 20)),
+                ("c.txt".to_string(), Change::EtagDiffers),
+                ("f.txt".to_string(), Change::OnlyInB),
+            ]
+        );
+        assert!(compare(&a, &a).is_empty());
+    }
+}
@@ -0,0 +1,63 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+
+use super::output::{Output, print_line};
+use super::{Command, ShellState};
+use crate::config::KEYS;
+
+pub struct ConfigCommand;
+
+#[async_trait]
+impl Command for ConfigCommand {
+    fn name(&self) -> &str {
+        "config"
+    }
+
+    fn usage(&self) -> &str {
+        "config [KEY [VALUE|--unset]] - Show or change saved defaults in config.toml"
+    }
+
+    fn flags(&self) -> &[(&str, &str)] {
+        &[("--unset", "Remove KEY from config.toml")]
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        match args {
+            [] => {
+                if let Some(path) = state.config().path() {
+                    print_line!(out, "# {}", path.display());
+                }
+                for (key, description) in KEYS {
+                    let value = state.config().get(key)?;
+                    print_line!(
+                        out,
+                        "{key:<17} = {:<12} # {description}",
+                        value.as_deref().unwrap_or("(default)")
+                    );
+                }
+                return Ok(());
+            }
+            [key] => {
+                let value = state.config().get(key)?;
+                print_line!(out, "{key} = {}", value.as_deref().unwrap_or("(default)"));
+                return Ok(());
+            }
+            [key, flag] if flag == "--unset" => state.config_mut().unset(key)?,
+            [key, value] => state.config_mut().set(key, value)?,
+            _ => return Err(anyhow!("Usage: {}", self.usage())),
+        }
+
+        state.config().save()?;
+        state.apply_config();
+
+        if matches!(args[0].as_str(), "provider" | "region" | "history_size") {
+            print_line!(out, "{} takes effect the next time s3sh starts", args[0]);
+        }
+        Ok(())
+    }
+}
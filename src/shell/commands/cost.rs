@@ -0,0 +1,102 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+
+use super::output::{Output, print_line};
+use super::{Command, ShellState};
+use crate::s3::Pricing;
+
+pub struct CostCommand;
+
+#[async_trait]
+impl Command for CostCommand {
+    fn name(&self) -> &str {
+        "cost"
+    }
+
+    fn usage(&self) -> &str {
+        "cost - Estimate the request and data transfer cost of this session"
+    }
+
+    fn records_metrics(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        if !args.is_empty() {
+            return Err(anyhow!("Usage: {}", self.usage()));
+        }
+
+        let provider = state
+            .s3_client()
+            .provider_name()
+            .unwrap_or("aws")
+            .to_string();
+        let mut pricing = Pricing::for_provider(&provider);
+        for (key, price) in state.config().pricing(&provider) {
+            pricing
+                .set(&key, price)
+                .map_err(|e| anyhow!("{e} in [pricing.{provider}]"))?;
+        }
+
+        let session = state.session_metrics();
+        let estimate = pricing.estimate(session);
+        print_line!(out, "Estimated cost this session ({provider} pricing):");
+        print_line!(
+            out,
+            "  PUT/COPY/POST/LIST {:>10} requests  {}",
+            estimate.write_requests,
+            format_usd(estimate.write_requests as f64 / 1000.0 * pricing.write_per_1000)
+        );
+        print_line!(
+            out,
+            "  GET/HEAD/other     {:>10} requests  {}",
+            estimate.read_requests,
+            format_usd(estimate.read_requests as f64 / 1000.0 * pricing.read_per_1000)
+        );
+        print_line!(
+            out,
+            "  Data transfer      {:>19}  {}",
+            humansize::format_size(session.downloaded, humansize::DECIMAL),
+            format_usd(estimate.transfer)
+        );
+        print_line!(out, "  {:<38}  {}", "Total", format_usd(estimate.total()));
+        print_line!(
+            out,
+            "Prices: ${} per 1,000 PUT/LIST, ${} per 1,000 GET, ${}/GB downloaded",
+            pricing.write_per_1000,
+            pricing.read_per_1000,
+            pricing.transfer_per_gb
+        );
+        print_line!(
+            out,
+            "List prices without free tiers; in-region transfer is free. Override in [pricing.{provider}] in config.toml."
+        );
+        Ok(())
+    }
+}
+
+/// Dollars, with enough decimals to show fractions of a cent
+fn format_usd(amount: f64) -> String {
+    if amount > 0.0 && amount < 0.01 {
+        format!("${amount:.6}")
+    } else {
+        format!("${amount:.2}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_usd() {
+        assert_eq!(format_usd(0.0), "$0.00");
+        assert_eq!(format_usd(0.0004), "$0.000400");
+        assert_eq!(format_usd(1.234), "$1.23");
+    }
+}
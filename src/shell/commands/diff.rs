@@ -0,0 +1,315 @@
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio_util::io::ReaderStream;
+
+use super::cat::is_text;
+use super::output::{Output, print_line, print_str};
+use super::{Command, ShellState};
+use crate::archive::handler_for;
+use crate::shell::diff::unified_diff;
+use crate::vfs::{ArchiveIndex, ArchiveType, VfsNode};
+
+/// Larger inputs are compared byte by byte instead of line by line
+const MAX_TEXT_DIFF: u64 = 32 * 1024 * 1024;
+
+pub struct DiffCommand;
+
+/// One side of the comparison
+enum Source {
+    Object {
+        bucket: String,
+        key: String,
+        size: u64,
+    },
+    Entry {
+        bucket: String,
+        key: String,
+        archive_type: ArchiveType,
+        index: Arc<ArchiveIndex>,
+        path: String,
+        size: u64,
+    },
+    Local {
+        path: PathBuf,
+        size: u64,
+    },
+}
+
+/// Where two byte streams first disagree
+#[derive(Debug, PartialEq)]
+enum Difference {
+    /// The bytes at this offset differ
+    Byte(u64),
+    /// One input ends after this many bytes while the other goes on
+    Eof { first_ends: bool, at: u64 },
+}
+
+#[async_trait]
+impl Command for DiffCommand {
+    fn name(&self) -> &str {
+        "diff"
+    }
+
+    fn usage(&self) -> &str {
+        "diff A B - Compare two objects, archive entries, or local files (file://PATH)"
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        let [a, b] = args else {
+            return Err(anyhow!("Usage: {}", self.usage()));
+        };
+
+        let source_a = Self::resolve(state, a).await?;
+        let source_b = Self::resolve(state, b).await?;
+        let mut stream_a = Self::open(state, &source_a).await?;
+        let mut stream_b = Self::open(state, &source_b).await?;
+
+        // The first chunk of each side decides between a text and a binary comparison
+        let first_a = stream_a.try_next().await?.unwrap_or_default();
+        let first_b = stream_b.try_next().await?.unwrap_or_default();
+        let text = source_a.size() <= MAX_TEXT_DIFF
+            && source_b.size() <= MAX_TEXT_DIFF
+            && is_text(&first_a)
+            && is_text(&first_b);
+
+        if text {
+            let text_a = collect_text(first_a, stream_a).await?;
+            let text_b = collect_text(first_b, stream_b).await?;
+            if let Some(diff) = unified_diff(&text_a, &text_b, a, b) {
+                print_str!(out, "{diff}");
+            }
+            return Ok(());
+        }
+
+        let stream_a = stream::once(async { Ok(first_a) }).chain(stream_a).boxed();
+        let stream_b = stream::once(async { Ok(first_b) }).chain(stream_b).boxed();
+        match first_difference(stream_a, stream_b).await? {
+            None => {}
+            Some(Difference::Byte(offset)) => {
+                print_line!(
+                    out,
+                    "Binary files {a} and {b} differ at byte offset {offset} (0x{offset:x})"
+                );
+            }
+            Some(Difference::Eof { first_ends, at }) => {
+                let shorter = if first_ends { a } else { b };
+                print_line!(
+                    out,
+                    "Binary files {a} and {b} differ: {shorter} ends after {at} bytes"
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl DiffCommand {
+    /// Work out what a path names: file:// paths are local, anything else is in S3
+    async fn resolve(state: &ShellState, path: &str) -> Result<Source> {
+        if let Some(local) = path.strip_prefix("file://") {
+            let path = PathBuf::from(local);
+            let metadata = tokio::fs::metadata(&path)
+                .await
+                .with_context(|| format!("Failed to read {local}"))?;
+            if metadata.is_dir() {
+                return Err(anyhow!("Is a directory: {local}"));
+            }
+            return Ok(Source::Local {
+                path,
+                size: metadata.len(),
+            });
+        }
+
+        let resolver = state.resolver();
+        match resolver.resolve(state.current_node(), path).await? {
+            VfsNode::Object {
+                bucket, key, size, ..
+            } => Ok(Source::Object { bucket, key, size }),
+            VfsNode::ArchiveEntry { is_dir: true, .. } => Err(anyhow!("Is a directory: {path}")),
+            VfsNode::ArchiveEntry {
+                archive,
+                path: entry,
+                size,
+                ..
+            } => {
+                let index = resolver.index(&archive).await?;
+                let VfsNode::Archive {
+                    parent,
+                    archive_type,
+                    ..
+                } = archive.as_ref()
+                else {
+                    return Err(anyhow!("Not an archive"));
+                };
+                let VfsNode::Object { bucket, key, .. } = parent.as_ref() else {
+                    return Err(anyhow!(
+                        "Comparing files in nested archives isn't supported"
+                    ));
+                };
+                Ok(Source::Entry {
+                    bucket: bucket.clone(),
+                    key: key.clone(),
+                    archive_type: archive_type.clone(),
+                    index,
+                    path: entry,
+                    size,
+                })
+            }
+            VfsNode::Prefix { .. } => Err(anyhow!("File not found: {path}")),
+            _ => Err(anyhow!("Not a file: {path}")),
+        }
+    }
+
+    /// Stream a source's bytes; archive entries are extracted whole
+    async fn open<'a>(
+        state: &'a ShellState,
+        source: &'a Source,
+    ) -> Result<BoxStream<'a, Result<Bytes>>> {
+        match source {
+            Source::Object { bucket, key, size } => {
+                state
+                    .s3_client()
+                    .get_object_chunks(bucket, key, Some(*size))
+                    .await
+            }
+            Source::Entry {
+                bucket,
+                key,
+                archive_type,
+                index,
+                path,
+                ..
+            } => {
+                let handler = handler_for(archive_type)
+                    .ok_or_else(|| anyhow!("Archive type not yet supported"))?;
+                let bytes = handler
+                    .extract_file(state.s3_client(), bucket, key, index, path)
+                    .await?;
+                Ok(stream::once(async { Ok(bytes) }).boxed())
+            }
+            Source::Local { path, .. } => {
+                let file = tokio::fs::File::open(path)
+                    .await
+                    .with_context(|| format!("Failed to open {}", path.display()))?;
+                Ok(ReaderStream::new(file).map_err(anyhow::Error::from).boxed())
+            }
+        }
+    }
+}
+
+impl Source {
+    fn size(&self) -> u64 {
+        match self {
+            Source::Object { size, .. }
+            | Source::Entry { size, .. }
+            | Source::Local { size, .. } => *size,
+        }
+    }
+}
+
+/// Read the rest of a text stream after its first chunk
+async fn collect_text(first: Bytes, mut rest: BoxStream<'_, Result<Bytes>>) -> Result<String> {
+    let mut bytes = first.to_vec();
+    while let Some(chunk) = rest.try_next().await? {
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Walk two streams in step, stopping at the first byte that differs
+async fn first_difference(
+    mut a: BoxStream<'_, Result<Bytes>>,
+    mut b: BoxStream<'_, Result<Bytes>>,
+) -> Result<Option<Difference>> {
+    let mut buf_a = Bytes::new();
+    let mut buf_b = Bytes::new();
+    let mut offset = 0u64;
+    loop {
+        // Refill whichever side has run dry; chunk boundaries needn't line up
+        while buf_a.is_empty() {
+            match a.try_next().await? {
+                Some(chunk) => buf_a = chunk,
+                None => break,
+            }
+        }
+        while buf_b.is_empty() {
+            match b.try_next().await? {
+                Some(chunk) => buf_b = chunk,
+                None => break,
+            }
+        }
+
+        match (buf_a.is_empty(), buf_b.is_empty()) {
+            (true, true) => return Ok(None),
+            (true, false) | (false, true) => {
+                return Ok(Some(Difference::Eof {
+                    first_ends: buf_a.is_empty(),
+                    at: offset,
+                }));
+            }
+            (false, false) => {}
+        }
+
+        let n = buf_a.len().min(buf_b.len());
+        if let Some(i) = (0..n).find(|&i| buf_a[i] != buf_b[i]) {
+            return Ok(Some(Difference::Byte(offset + i as u64)));
+        }
+        offset += n as u64;
+        buf_a = buf_a.slice(n..);
+        buf_b = buf_b.slice(n..);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunks(parts: &[&'static [u8]]) -> BoxStream<'static, Result<Bytes>> {
+        stream::iter(
+            parts
+                .iter()
+                .map(|part| Ok(Bytes::from_static(part)))
+                .collect::<Vec<_>>(),
+        )
+        .boxed()
+    }
+
+    #[tokio::test]
+    async fn test_first_difference() {
+        let diff = |a, b| first_difference(chunks(a), chunks(b));
+        assert_eq!(
+            diff(&[b"abc", b"def"], &[b"ab", b"", b"cdef"])
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            diff(&[b"abc", b"def"], &[b"abcdXf"]).await.unwrap(),
+            Some(Difference::Byte(4))
+        );
+        assert_eq!(
+            diff(&[b"abc"], &[b"ab", b"cd"]).await.unwrap(),
+            Some(Difference::Eof {
+                first_ends: true,
+                at: 3
+            })
+        );
+        assert_eq!(
+            diff(&[b"abcd"], &[]).await.unwrap(),
+            Some(Difference::Eof {
+                first_ends: false,
+                at: 0
+            })
+        );
+    }
+}
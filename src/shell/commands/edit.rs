@@ -0,0 +1,160 @@
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::io::Write;
+
+use super::output::{Output, print_line, print_str};
+use super::{Command, ShellState, resolve_object};
+use crate::shell::diff::unified_diff;
+use crate::ui::{confirm, create_spinner};
+
+pub struct EditCommand;
+
+#[async_trait]
+impl Command for EditCommand {
+    fn name(&self) -> &str {
+        "edit"
+    }
+
+    fn usage(&self) -> &str {
+        "edit [-y|--yes] [-n|--dry-run] FILE - Edit an object in $EDITOR and upload the changes"
+    }
+
+    fn flags(&self) -> &[(&str, &str)] {
+        &[
+            ("-y, --yes", "Upload without asking after showing the diff"),
+            ("-n, --dry-run", "Show the diff without uploading"),
+        ]
+    }
+
+    fn interactive(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        // -y/--yes uploads without asking after the diff; -n/--dry-run only shows it
+        let mut yes = false;
+        let mut dry_run = false;
+        let mut positional = Vec::new();
+        for arg in args {
+            match arg.as_str() {
+                "-y" | "--yes" => yes = true,
+                "-n" | "--dry-run" => dry_run = true,
+                _ => positional.push(arg),
+            }
+        }
+        let [path] = positional.as_slice() else {
+            return Err(anyhow!("Usage: {}", self.usage()));
+        };
+
+        let (bucket, key, _) = resolve_object(state, path).await?;
+        if !dry_run {
+            // Refuse before the user spends time editing
+            state.s3_client().check_writable("edit", &bucket, &key)?;
+        }
+        let metadata = state.s3_client().head_object(&bucket, &key).await?;
+
+        let spinner = create_spinner(&format!("Downloading {path}..."));
+        let original = state.s3_client().get_object(&bucket, &key).await;
+        spinner.finish_and_clear();
+        let original = original?;
+
+        // Keep the object's file name so the editor picks the right syntax mode
+        let file_name = key.rsplit('/').next().unwrap_or(&key);
+        let temp = tempfile::Builder::new()
+            .prefix("s3sh-")
+            .suffix(&format!("-{file_name}"))
+            .tempfile()
+            .context("Failed to create a temporary file")?;
+        std::fs::write(temp.path(), &original).context("Failed to write the temporary file")?;
+
+        let editor = editor();
+        let mut words = editor.split_whitespace();
+        let program = words.next().unwrap_or("vi");
+        let status = tokio::process::Command::new(program)
+            .args(words)
+            .arg(temp.path())
+            .status()
+            .await
+            .with_context(|| format!("Failed to launch editor: {editor}"))?;
+        if !status.success() {
+            return Err(anyhow!("Editor exited with {status}; nothing uploaded"));
+        }
+
+        let edited =
+            Bytes::from(std::fs::read(temp.path()).context("Failed to read back the edited file")?);
+        if edited == original {
+            print_line!(out, "No changes");
+            return Ok(());
+        }
+
+        match (std::str::from_utf8(&original), std::str::from_utf8(&edited)) {
+            (Ok(old), Ok(new)) => {
+                if let Some(diff) = unified_diff(old, new, path, &format!("{path} (edited)")) {
+                    print_str!(out, "{diff}");
+                }
+            }
+            _ => print_line!(
+                out,
+                "Binary content changed ({} -> {} bytes)",
+                original.len(),
+                edited.len()
+            ),
+        }
+        out.flush()?;
+
+        if dry_run {
+            print_line!(
+                out,
+                "(dry run) Not uploaded; edited copy kept at {}",
+                keep(temp)?.display()
+            );
+            return Ok(());
+        }
+
+        if !yes && !confirm(&format!("Upload changes to s3://{bucket}/{key}?"))? {
+            print_line!(
+                out,
+                "Not uploaded; edited copy kept at {}",
+                keep(temp)?.display()
+            );
+            return Ok(());
+        }
+
+        let spinner = create_spinner(&format!("Uploading {path}..."));
+        let result = state
+            .s3_client()
+            .replace_object_content(&bucket, &key, edited, &metadata)
+            .await;
+        spinner.finish_and_clear();
+        if let Err(e) = result {
+            // Don't lose the user's edits to a failed upload
+            let kept = keep(temp)?;
+            return Err(anyhow!("{e:#}; edited copy kept at {}", kept.display()));
+        }
+        state.completion_cache().invalidate(&bucket, &key);
+
+        print_line!(out, "Uploaded {path}");
+        Ok(())
+    }
+}
+
+/// The user's editor: $VISUAL, then $EDITOR, then the platform default
+fn editor() -> String {
+    std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string())
+}
+
+/// Keep the temporary file instead of deleting it
+fn keep(temp: tempfile::NamedTempFile) -> Result<std::path::PathBuf> {
+    let (_, path) = temp.keep().context("Failed to keep the edited copy")?;
+    Ok(path)
+}
@@ -0,0 +1,133 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+
+use super::output::{Output, print_line};
+use super::{Command, ShellState, resolve_object};
+
+/// Bytes fetched to identify an object (enough to reach the tar header magic at offset 257)
+const SNIFF_LEN: u64 = 512;
+
+pub struct FileCommand;
+
+#[async_trait]
+impl Command for FileCommand {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    fn usage(&self) -> &str {
+        "file FILE... - Identify objects from their Content-Type and first bytes"
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        if args.is_empty() {
+            return Err(anyhow!("Usage: {}", self.usage()));
+        }
+
+        for path in args {
+            let (bucket, key, _) = resolve_object(state, path).await?;
+            let metadata = state.s3_client().head_object(&bucket, &key).await?;
+
+            let description = if metadata.size == 0 {
+                "empty"
+            } else {
+                let head = state
+                    .s3_client()
+                    .get_object_range(&bucket, &key, 0, metadata.size.min(SNIFF_LEN))
+                    .await?;
+                Self::identify(&head)
+            };
+
+            print_line!(
+                out,
+                "{path}: {description} (Content-Type: {})",
+                metadata.content_type.as_deref().unwrap_or("-")
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl FileCommand {
+    /// Describe data from its leading bytes, like file(1) but for formats s3sh cares about
+    fn identify(head: &[u8]) -> &'static str {
+        const MAGIC: &[(&[u8], &str)] = &[
+            (b"\x1f\x8b", "gzip compressed data"),
+            (b"BZh", "bzip2 compressed data"),
+            (b"\x28\xb5\x2f\xfd", "Zstandard compressed data"),
+            (b"\xfd7zXZ\x00", "XZ compressed data"),
+            (b"PK\x03\x04", "Zip archive"),
+            (b"PK\x05\x06", "Zip archive (empty)"),
+            (b"PAR1", "Apache Parquet"),
+            (b"ORC", "Apache ORC"),
+            (b"Obj\x01", "Apache Avro container"),
+            (b"\x89PNG\r\n\x1a\n", "PNG image"),
+            (b"\xff\xd8\xff", "JPEG image"),
+            (b"GIF8", "GIF image"),
+            (b"%PDF-", "PDF document"),
+            (b"SQLite format 3\x00", "SQLite database"),
+        ];
+
+        if let Some((_, description)) = MAGIC.iter().find(|(magic, _)| head.starts_with(magic)) {
+            return description;
+        }
+
+        // POSIX tar archives carry "ustar" in the header block
+        if head.get(257..262) == Some(b"ustar".as_slice()) {
+            return "tar archive";
+        }
+
+        // A range can end mid-character, so only the last few bytes may be incomplete
+        let text = match std::str::from_utf8(head) {
+            Ok(text) => text,
+            Err(e) if e.error_len().is_none() => {
+                std::str::from_utf8(&head[..e.valid_up_to()]).unwrap_or_default()
+            }
+            Err(_) => return "data",
+        };
+        if text.contains('\0') {
+            return "data";
+        }
+
+        match text.trim_start().chars().next() {
+            Some('{') | Some('[') => "JSON text",
+            Some('<') => "XML/HTML text",
+            _ if text.is_ascii() => "ASCII text",
+            _ => "UTF-8 text",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identify() {
+        assert_eq!(
+            FileCommand::identify(b"\x1f\x8b\x08\x00rest"),
+            "gzip compressed data"
+        );
+        assert_eq!(FileCommand::identify(b"PK\x03\x04\x14\x00"), "Zip archive");
+        assert_eq!(FileCommand::identify(b"PAR1\x15\x04"), "Apache Parquet");
+        assert_eq!(FileCommand::identify(b"\x89PNG\r\n\x1a\n"), "PNG image");
+
+        let mut tar = vec![0u8; 512];
+        tar[..8].copy_from_slice(b"data.csv");
+        tar[257..262].copy_from_slice(b"ustar");
+        assert_eq!(FileCommand::identify(&tar), "tar archive");
+
+        assert_eq!(FileCommand::identify(b"  {\"a\": 1}\n"), "JSON text");
+        assert_eq!(FileCommand::identify(b"id,name\n1,x\n"), "ASCII text");
+        assert_eq!(FileCommand::identify("naïve\n".as_bytes()), "UTF-8 text");
+        // Truncated in the middle of a multi-byte character
+        assert_eq!(FileCommand::identify(&"é".as_bytes()[..1]), "ASCII text");
+        assert_eq!(FileCommand::identify(b"\x00\x01\x02\xff\xfe"), "data");
+    }
+}
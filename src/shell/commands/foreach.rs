@@ -0,0 +1,250 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use colored::Colorize;
+use futures::stream::{self, StreamExt};
+
+use super::ls::LsCommand;
+use super::output::{Output, print_bytes};
+use super::{Command, ShellState};
+use crate::session::Session;
+
+/// Placeholder in the command line for each matching path
+const PLACEHOLDER: &str = "{}";
+
+pub struct ForeachCommand;
+
+#[async_trait]
+impl Command for ForeachCommand {
+    fn name(&self) -> &str {
+        "foreach"
+    }
+
+    fn usage(&self) -> &str {
+        "foreach [-j N] PATTERN -- COMMAND - Run COMMAND once per entry matching PATTERN, with {} as its path"
+    }
+
+    fn flags(&self) -> &[(&str, &str)] {
+        &[(
+            "-j, --concurrency N",
+            "Run N at a time, in read-only mode (output still comes in order)",
+        )]
+    }
+
+    fn takes_command_line(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        let split = args
+            .iter()
+            .position(|arg| arg == "--")
+            .ok_or_else(|| anyhow!("Usage: {}", self.usage()))?;
+        let template = args[split + 1..].join(" ");
+
+        let mut concurrency = 1;
+        let mut patterns = Vec::new();
+        let mut iter = args[..split].iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "-j" | "--concurrency" => {
+                    concurrency = iter
+                        .next()
+                        .and_then(|n| n.parse().ok())
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| anyhow!("--concurrency requires a positive number"))?;
+                }
+                _ => patterns.push(arg),
+            }
+        }
+        let ([pattern], false) = (patterns.as_slice(), template.trim().is_empty()) else {
+            return Err(anyhow!("Usage: {}", self.usage()));
+        };
+
+        let matches = Self::expand(state, pattern).await?;
+        if matches.is_empty() {
+            return Err(anyhow!("No matches for {pattern}"));
+        }
+        let lines: Vec<String> = matches
+            .iter()
+            .map(|path| Self::command_line(&template, path))
+            .collect();
+
+        let total = lines.len();
+        let failed = if concurrency == 1 {
+            let mut failed = 0;
+            for line in &lines {
+                if let Err(e) = state.execute_line(line, out).await {
+                    if e.to_string() == "exit" {
+                        return Err(e);
+                    }
+                    eprintln!("{} {line}: {e}", "Error:".red().bold());
+                    failed += 1;
+                }
+            }
+            failed
+        } else {
+            // Each run gets its own shell so directories and statuses don't mix; S3 writes
+            // are refused, since nothing orders them
+            state.s3_client().set_read_only(true);
+            let runs: Vec<_> = lines
+                .into_iter()
+                .map(|line| {
+                    let mut worker = state.worker();
+                    async move {
+                        let mut buffer = Output::capture();
+                        let result = worker.execute_line(&line, &mut buffer).await;
+                        (line, buffer, result)
+                    }
+                })
+                .collect();
+            let mut results = stream::iter(runs).buffered(concurrency);
+            let mut failed = 0;
+            while let Some((line, buffer, result)) = results.next().await {
+                if let Some(bytes) = buffer.captured() {
+                    print_bytes!(out, bytes);
+                }
+                if let Err(e) = result {
+                    eprintln!("{} {line}: {e}", "Error:".red().bold());
+                    failed += 1;
+                }
+            }
+            state.s3_client().set_read_only(state.settings().read_only);
+            failed
+        };
+
+        match failed {
+            0 => Ok(()),
+            _ => Err(anyhow!("{failed} of {total} commands failed")),
+        }
+    }
+}
+
+impl ForeachCommand {
+    /// Paths of the entries matching a glob, in listing order
+    /// Wildcards are only allowed in the last segment, as with `ls`.
+    async fn expand(state: &ShellState, pattern: &str) -> Result<Vec<String>> {
+        let (dir, name_pattern) = match pattern.rsplit_once('/') {
+            Some(("", name)) => ("/", name),
+            Some((dir, name)) => (dir, name),
+            None => (".", pattern),
+        };
+        if dir.contains(['*', '?']) {
+            return Err(anyhow!(
+                "Wildcards are only supported in the last path segment"
+            ));
+        }
+
+        let session = Session::from_parts(
+            state.s3_client().clone(),
+            state.cache().clone(),
+            state.current_node().clone(),
+        );
+        let prefix = match pattern.rsplit_once('/') {
+            Some((dir, _)) => format!("{dir}/"),
+            None => String::new(),
+        };
+        Ok(session
+            .ls(dir)
+            .await?
+            .into_iter()
+            .filter(|entry| LsCommand::matches_pattern(&entry.name, name_pattern))
+            .map(|entry| format!("{prefix}{}", entry.name))
+            .collect())
+    }
+
+    /// The command line for one match: `{}` replaced by its path, or the path appended
+    fn command_line(template: &str, path: &str) -> String {
+        let path = escape(path);
+        if template.contains(PLACEHOLDER) {
+            template.replace(PLACEHOLDER, &path)
+        } else {
+            format!("{template} {path}")
+        }
+    }
+}
+
+/// Backslash-escape characters the shell would otherwise split or interpret
+/// Works both bare and inside double quotes.
+fn escape(path: &str) -> String {
+    let mut escaped = String::with_capacity(path.len());
+    for c in path.chars() {
+        if c.is_whitespace() || "\\'\"|>&$".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::s3::MockS3Client;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_command_line() {
+        assert_eq!(
+            ForeachCommand::command_line("zcat {} | wc -l", "logs/a.gz"),
+            "zcat logs/a.gz | wc -l"
+        );
+        assert_eq!(
+            ForeachCommand::command_line("stat", "my file's.txt"),
+            "stat my\\ file\\'s.txt"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_foreach() {
+        let mock = MockS3Client::new()
+            .with_object("bucket", "logs/a.log", "one\n")
+            .with_object("bucket", "logs/b.log", "two\n")
+            .with_object("bucket", "logs/notes.txt", "skip\n");
+        let mut state = ShellState::with_client(Arc::new(mock.client()))
+            .await
+            .unwrap();
+
+        for line in [
+            "foreach /bucket/logs/*.log -- cat {}",
+            "foreach -j 4 /bucket/logs/*.log -- cat",
+        ] {
+            let mut out = Output::capture();
+            state.execute_to(line, &mut out).await.unwrap();
+            assert_eq!(out.captured().unwrap(), b"one\ntwo\n");
+        }
+
+        state
+            .execute_to("cd /bucket/logs", &mut Output::capture())
+            .await
+            .unwrap();
+        let mut out = Output::capture();
+        state
+            .execute_to("foreach ?.log -- echo {} && echo done", &mut out)
+            .await
+            .unwrap();
+        assert_eq!(out.captured().unwrap(), b"a.log\nb.log\ndone\n");
+
+        // Writes are refused when running concurrently
+        let result = state
+            .execute_to(
+                "foreach -j 2 *.log -- cat {} | tee --remote {}.copy",
+                &mut Output::capture(),
+            )
+            .await;
+        assert!(result.is_err());
+        assert!(mock.object("bucket", "logs/a.log.copy").is_none());
+        assert!(!state.s3_client().is_read_only());
+
+        assert!(
+            state
+                .execute_to("foreach *.csv -- cat {}", &mut Output::capture())
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,417 @@
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::output::{Output, print_line};
+use super::{Command, ShellState, local_path_under, not_an_object, resolve_object};
+use crate::archive::{ExtractLimits, handler_for, with_extract_limits};
+use crate::ui::{create_progress_bar, create_spinner};
+use crate::vfs::{ArchiveIndex, ArchiveType, VfsNode, VirtualPath};
+
+/// Downloads running at once unless --concurrency says otherwise
+const DEFAULT_CONCURRENCY: usize = 8;
+
+pub struct GetCommand;
+
+/// What a `get` argument refers to
+enum Source {
+    /// S3 object or, with -r, everything below a prefix
+    S3 { bucket: String, key: String },
+    /// File or, with -r, directory inside the archive the shell is in
+    Archive {
+        bucket: String,
+        key: String,
+        archive_type: ArchiveType,
+        index: Arc<ArchiveIndex>,
+        path: String,
+    },
+}
+
+#[async_trait]
+impl Command for GetCommand {
+    fn name(&self) -> &str {
+        "get"
+    }
+
+    fn usage(&self) -> &str {
+        "get [-r] [--concurrency N] [--no-limits] PATH [LOCAL] - Download a file, or with -r a prefix or archive directory"
+    }
+
+    fn flags(&self) -> &[(&str, &str)] {
+        &[
+            ("-r, --recursive", "Download a prefix or archive directory"),
+            (
+                "-j, --concurrency N",
+                "Parallel downloads with -r (default 8)",
+            ),
+            (
+                "--no-limits",
+                "Lift the archive extraction size limits for this command",
+            ),
+        ]
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        // Skip the zip bomb limits for this command only, without touching the shared client
+        if let Some(pos) = args.iter().position(|arg| arg == "--no-limits") {
+            let mut args = args.to_vec();
+            args.remove(pos);
+            return with_extract_limits(ExtractLimits::UNLIMITED, self.execute(state, &args, out))
+                .await;
+        }
+
+        let mut recursive = false;
+        let mut concurrency = DEFAULT_CONCURRENCY;
+        let mut positional = Vec::new();
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "-r" | "-R" | "--recursive" => recursive = true,
+                "--concurrency" | "-j" => {
+                    concurrency = iter
+                        .next()
+                        .and_then(|n| n.parse().ok())
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| anyhow!("--concurrency requires a positive number"))?;
+                }
+                _ => positional.push(arg.as_str()),
+            }
+        }
+
+        let (path, local) = match positional.as_slice() {
+            [path] => (*path, None),
+            [path, local] => (*path, Some(Path::new(*local))),
+            _ => return Err(anyhow!("Usage: {}", self.usage())),
+        };
+
+        let source = Self::resolve(state, path, recursive).await?;
+        if recursive {
+            self.get_recursive(state, out, source, path, local, concurrency)
+                .await
+        } else {
+            self.get_file(state, out, source, path, local).await
+        }
+    }
+}
+
+impl GetCommand {
+    /// Work out whether PATH is in S3 or in the archive the shell is in
+    async fn resolve(state: &ShellState, path: &str, recursive: bool) -> Result<Source> {
+        let (archive, base) = match state.current_node() {
+            node @ VfsNode::Archive { .. } if !path.starts_with('/') => (node, ""),
+            VfsNode::ArchiveEntry {
+                archive,
+                path: base,
+                ..
+            } if !path.starts_with('/') => (archive.as_ref(), base.as_str()),
+            _ if recursive => {
+                // `get -r .` (or a bucket or prefix) downloads everything below it
+                let (bucket, key) =
+                    match state.resolver().resolve(state.current_node(), path).await? {
+                        VfsNode::Bucket { name } => (name, String::new()),
+                        VfsNode::Prefix { bucket, prefix } => (bucket, prefix),
+                        VfsNode::Object { bucket, key, .. } => (bucket, key),
+                        _ => return Err(not_an_object(path)),
+                    };
+                return Ok(Source::S3 { bucket, key });
+            }
+            _ => {
+                let (bucket, key, _) = resolve_object(state, path).await?;
+                return Ok(Source::S3 { bucket, key });
+            }
+        };
+
+        let VfsNode::Archive {
+            parent,
+            archive_type,
+            index: Some(index),
+        } = archive
+        else {
+            return Err(anyhow!("Archive index not available"));
+        };
+        let VfsNode::Object { bucket, key, .. } = parent.as_ref() else {
+            return Err(anyhow!("Downloading from nested archives isn't supported"));
+        };
+
+        let path = VirtualPath::parse(base).join(path).segments().join("/");
+        Ok(Source::Archive {
+            bucket: bucket.clone(),
+            key: key.clone(),
+            archive_type: archive_type.clone(),
+            index: Arc::clone(index),
+            path,
+        })
+    }
+
+    /// Download a single file
+    async fn get_file(
+        &self,
+        state: &ShellState,
+        out: &mut Output,
+        source: Source,
+        path: &str,
+        local: Option<&Path>,
+    ) -> Result<()> {
+        let name = match &source {
+            Source::S3 { key, .. } => key.rsplit('/').next().unwrap_or(key),
+            Source::Archive { path, .. } => path.rsplit('/').next().unwrap_or(path),
+        };
+        if name.is_empty() {
+            return Err(anyhow!("{path} is a directory; use get -r"));
+        }
+        let target = match local {
+            Some(local) if local.is_dir() => local.join(name),
+            Some(local) => local.to_path_buf(),
+            None => PathBuf::from(name),
+        };
+
+        let written = match &source {
+            Source::S3 { bucket, key } => {
+                let client = state.s3_client();
+                let size = client.head_object(bucket, key).await?.size;
+                let bar = create_progress_bar(
+                    &format!("Downloading {path}"),
+                    size,
+                    client.metrics(),
+                    out.is_terminal(),
+                );
+                let written = client.download_file(bucket, key, Some(size), &target).await;
+                bar.finish_and_clear();
+                written
+            }
+            Source::Archive {
+                bucket,
+                key,
+                archive_type,
+                index,
+                path: entry,
+            } => match index.find_entry(entry) {
+                Some(found) if found.is_dir => Err(anyhow!("{path} is a directory; use get -r")),
+                Some(_) => {
+                    let spinner = create_spinner(&format!("Extracting {path}..."));
+                    let written =
+                        Self::extract_to(state, bucket, key, archive_type, index, entry, &target)
+                            .await;
+                    spinner.finish_and_clear();
+                    written
+                }
+                None => Err(anyhow!("File not found in archive: {path}")),
+            },
+        };
+
+        print_line!(
+            out,
+            "Downloaded {path} to {} ({})",
+            target.display(),
+            humansize::format_size(written?, humansize::BINARY)
+        );
+        Ok(())
+    }
+
+    /// Download everything below a prefix or archive directory, keeping the hierarchy
+    async fn get_recursive(
+        &self,
+        state: &ShellState,
+        out: &mut Output,
+        source: Source,
+        path: &str,
+        local: Option<&Path>,
+        concurrency: usize,
+    ) -> Result<()> {
+        // Files below the source, relative to it
+        let spinner = create_spinner(&format!("Listing {path}..."));
+        let listing = match &source {
+            Source::S3 { bucket, key } => {
+                let prefix = match key.trim_end_matches('/') {
+                    "" => String::new(),
+                    key => format!("{key}/"),
+                };
+                state
+                    .s3_client()
+                    .list_objects_recursive(bucket, &prefix)
+                    .await
+                    .map(|objects| {
+                        objects
+                            .into_iter()
+                            .filter(|obj| !obj.key.ends_with('/'))
+                            .map(|obj| (obj.key[prefix.len()..].to_string(), Some(obj.size)))
+                            .collect::<Vec<_>>()
+                    })
+            }
+            Source::Archive { index, path, .. } => {
+                let prefix = match path.as_str() {
+                    "" => String::new(),
+                    path => format!("{path}/"),
+                };
+                Ok(index
+                    .entries
+                    .values()
+                    .filter(|entry| !entry.is_dir && entry.path.starts_with(&prefix))
+                    .map(|entry| (entry.path[prefix.len()..].to_string(), None))
+                    .collect())
+            }
+        };
+        spinner.finish_and_clear();
+        let mut files = listing?;
+        files.sort();
+        if files.is_empty() {
+            return Err(anyhow!("Nothing to download below {path}"));
+        }
+
+        // Default destination: a local directory named after the source
+        let root = match local {
+            Some(local) => local.to_path_buf(),
+            None => {
+                let name = match &source {
+                    Source::S3 { bucket, key } => key
+                        .trim_end_matches('/')
+                        .rsplit('/')
+                        .next()
+                        .filter(|name| !name.is_empty())
+                        .unwrap_or(bucket),
+                    Source::Archive { key, path, .. } => path
+                        .rsplit('/')
+                        .next()
+                        .filter(|name| !name.is_empty())
+                        .unwrap_or_else(|| key.rsplit('/').next().unwrap_or(key)),
+                };
+                PathBuf::from(name)
+            }
+        };
+
+        let mut skipped = 0;
+        let mut downloads = Vec::new();
+        for (rel, size) in &files {
+            let Some(target) = local_path_under(&root, rel) else {
+                // Keys like "../x" would land outside the destination
+                skipped += 1;
+                continue;
+            };
+            let source = &source;
+            let client = Arc::clone(state.s3_client());
+            downloads.push(async move {
+                let written = match source {
+                    Source::S3 { bucket, key } => {
+                        let key = match key.trim_end_matches('/') {
+                            "" => rel.clone(),
+                            prefix => format!("{prefix}/{rel}"),
+                        };
+                        client.download_file(bucket, &key, *size, &target).await
+                    }
+                    Source::Archive {
+                        bucket,
+                        key,
+                        archive_type,
+                        index,
+                        path,
+                    } => {
+                        let entry = match path.as_str() {
+                            "" => rel.clone(),
+                            path => format!("{path}/{rel}"),
+                        };
+                        let handler = handler_for(archive_type)
+                            .ok_or_else(|| anyhow!("Archive type not yet supported"))?;
+                        let bytes = handler
+                            .extract_file(&client, bucket, key, index, &entry)
+                            .await?;
+                        write_file(&target, &bytes).await
+                    }
+                };
+                written.map(|written| (target, written))
+            });
+        }
+
+        // Archive entries are read compressed, so only S3 downloads get a byte total
+        let progress = match &source {
+            Source::S3 { .. } => create_progress_bar(
+                &format!("Downloading {} files", downloads.len()),
+                files.iter().filter_map(|(_, size)| *size).sum(),
+                state.s3_client().metrics(),
+                out.is_terminal(),
+            ),
+            Source::Archive { .. } => {
+                create_spinner(&format!("Extracting {} files...", downloads.len()))
+            }
+        };
+
+        let total = downloads.len();
+        let mut results = stream::iter(downloads).buffer_unordered(concurrency);
+        let (mut count, mut bytes) = (0, 0);
+        let mut failures: Vec<anyhow::Error> = Vec::new();
+        while let Some(result) = results.next().await {
+            match result {
+                Ok((target, written)) => {
+                    count += 1;
+                    bytes += written;
+                    // A closed pipe only stops the listing; the downloads carry on
+                    progress.suspend(|| -> Result<()> {
+                        print_line!(out, "{}", target.display());
+                        Ok(())
+                    })?;
+                }
+                Err(e) => failures.push(e),
+            }
+        }
+        progress.finish_and_clear();
+
+        print_line!(
+            out,
+            "Downloaded {count} files ({}) to {}",
+            humansize::format_size(bytes, humansize::BINARY),
+            root.display()
+        );
+        if skipped > 0 {
+            print_line!(
+                out,
+                "Skipped {skipped} entries whose names would escape the destination"
+            );
+        }
+
+        match failures.first() {
+            None => Ok(()),
+            Some(first) => Err(anyhow!(
+                "{} of {total} downloads failed (first: {first:#})",
+                failures.len()
+            )),
+        }
+    }
+
+    /// Extract one archive entry to a local file
+    async fn extract_to(
+        state: &ShellState,
+        bucket: &str,
+        key: &str,
+        archive_type: &ArchiveType,
+        index: &ArchiveIndex,
+        entry: &str,
+        target: &Path,
+    ) -> Result<u64> {
+        let handler =
+            handler_for(archive_type).ok_or_else(|| anyhow!("Archive type not yet supported"))?;
+        let bytes = handler
+            .extract_file(state.s3_client(), bucket, key, index, entry)
+            .await?;
+        write_file(target, &bytes).await
+    }
+}
+
+/// Write a downloaded file, creating parent directories as needed
+async fn write_file(target: &Path, bytes: &[u8]) -> Result<u64> {
+    if let Some(dir) = target.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    tokio::fs::write(target, bytes)
+        .await
+        .with_context(|| format!("Failed to write {}", target.display()))?;
+    Ok(bytes.len() as u64)
+}
@@ -0,0 +1,40 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+
+use super::output::{Output, print_line};
+use super::{Command, ShellState};
+
+pub struct HistoryCommand;
+
+#[async_trait]
+impl Command for HistoryCommand {
+    fn name(&self) -> &str {
+        "history"
+    }
+
+    fn usage(&self) -> &str {
+        "history [N] - List the last N (default all) commands; re-run with !N, !-N, !! or !prefix"
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        let entries = state.history().entries();
+        let count = match args {
+            [] => entries.len(),
+            [n] => n
+                .parse::<usize>()
+                .map_err(|_| anyhow!("history: {n}: numeric argument required"))?,
+            _ => return Err(anyhow!("Usage: {}", self.usage())),
+        };
+
+        let start = entries.len().saturating_sub(count);
+        for (i, entry) in entries.iter().enumerate().skip(start) {
+            print_line!(out, "{:>5}  {}", i + 1, entry);
+        }
+        Ok(())
+    }
+}
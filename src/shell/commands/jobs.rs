@@ -0,0 +1,109 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+
+use super::output::{Output, print_bytes, print_line};
+use super::{Command, ShellState};
+
+pub struct JobsCommand;
+
+#[async_trait]
+impl Command for JobsCommand {
+    fn name(&self) -> &str {
+        "jobs"
+    }
+
+    fn usage(&self) -> &str {
+        "jobs - List background jobs (start one with CMD &) and their progress"
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        if !args.is_empty() {
+            return Err(anyhow!("Usage: {}", self.usage()));
+        }
+        for job in state.jobs().iter() {
+            let status = if job.is_finished() { "Done" } else { "Running" };
+            let secs = job.elapsed().as_secs();
+            print_line!(
+                out,
+                "[{}] {status:<8} {}:{:02}  {}  - {}",
+                job.id,
+                secs / 60,
+                secs % 60,
+                job.line,
+                job.progress()
+            );
+        }
+        Ok(())
+    }
+}
+
+pub struct FgCommand;
+
+#[async_trait]
+impl Command for FgCommand {
+    fn name(&self) -> &str {
+        "fg"
+    }
+
+    fn usage(&self) -> &str {
+        "fg [%N] - Wait for a background job (default the latest) and print its output"
+    }
+
+    fn interactive(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        if args.len() > 1 {
+            return Err(anyhow!("Usage: {}", self.usage()));
+        }
+        let job = state.jobs_mut().get_mut(args.first().map(String::as_str))?;
+        eprintln!("{}", job.line);
+        let result = job.wait().await;
+        let (id, output) = (job.id, job.output());
+        state.jobs_mut().remove(id);
+        print_bytes!(out, &output?);
+        result
+    }
+}
+
+pub struct KillCommand;
+
+#[async_trait]
+impl Command for KillCommand {
+    fn name(&self) -> &str {
+        "kill"
+    }
+
+    fn usage(&self) -> &str {
+        "kill %N - Stop a background job"
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        let [spec] = args else {
+            return Err(anyhow!("Usage: {}", self.usage()));
+        };
+        let job = state.jobs_mut().get_mut(Some(spec))?;
+        job.kill();
+        let id = job.id;
+        if let Some(job) = state.jobs_mut().remove(id) {
+            print_line!(out, "[{id}] Killed  {}", job.line);
+        }
+        Ok(())
+    }
+}
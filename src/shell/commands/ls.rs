@@ -1,16 +1,16 @@
 use anyhow::{Result, anyhow};
 use async_trait::async_trait;
 use colored::*;
-use std::sync::Arc;
+use serde_json::{Value, json};
 
-use super::output::print_line;
+use super::output::{Output, print_line};
 use super::{Command, ShellState};
-use crate::archive::ArchiveHandler;
-#[cfg(feature = "parquet")]
-use crate::archive::ParquetHandler;
-use crate::archive::tar::TarHandler;
-use crate::archive::zip::ZipHandler;
-use crate::vfs::{ArchiveType, VfsNode};
+use crate::archive::{handler_for, zip};
+use crate::s3::client::{ListObjectsResult, ObjectInfo};
+use crate::shell::CompletionEntry;
+use crate::shell::theme::{FileKind, Theme};
+use crate::ui::create_spinner;
+use crate::vfs::{ArchiveEntry, VfsNode};
 
 pub struct LsCommand;
 
@@ -21,20 +21,75 @@ impl Command for LsCommand {
     }
 
     fn usage(&self) -> &str {
-        "ls [OPTIONS] - List directory contents"
+        "ls [-l] [--owner] [--content-type] [--storage-class CLASS] [--versions] [--json] [--summarize [-R]] [--no-cache] [PATH] - List directory contents"
     }
 
-    async fn execute(&self, state: &mut ShellState, args: &[String]) -> Result<()> {
+    fn flags(&self) -> &[(&str, &str)] {
+        &[
+            ("-l", "Long format with sizes and modification times"),
+            ("--owner", "Long format with object owners"),
+            ("--content-type", "Long format with Content-Type"),
+            (
+                "--storage-class CLASS",
+                "Only objects in CLASS (e.g. GLACIER)",
+            ),
+            ("--versions", "List every object version"),
+            ("--json", "One JSON object per entry"),
+            ("--summarize", "Print object count and total size"),
+            (
+                "-r, -R",
+                "With --summarize, include everything under the prefix",
+            ),
+            (
+                "--no-cache",
+                "List from S3 even if the listing was fetched recently",
+            ),
+        ]
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
         // Parse flags and path
-        let mut _recursive = false;
+        let mut recursive = false;
         let mut long_format = false;
+        let mut versions = false;
+        let mut json = false;
+        let mut summarize = false;
+        let mut content_type = false;
+        let mut owner = false;
+        let mut no_cache = false;
+        let mut storage_class: Option<String> = None;
         let mut path_arg: Option<String> = None;
 
-        for arg in args {
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
             if arg == "-R" || arg == "-r" {
-                _recursive = true;
+                recursive = true;
             } else if arg == "-l" {
                 long_format = true;
+            } else if arg == "--versions" {
+                versions = true;
+            } else if arg == "--json" {
+                json = true;
+            } else if arg == "--summarize" {
+                summarize = true;
+            } else if arg == "--content-type" {
+                long_format = true;
+                content_type = true;
+            } else if arg == "--no-cache" {
+                no_cache = true;
+            } else if arg == "--owner" {
+                long_format = true;
+                owner = true;
+            } else if arg == "--storage-class" {
+                let class = iter
+                    .next()
+                    .ok_or_else(|| anyhow!("--storage-class requires a value"))?;
+                storage_class = Some(class.to_uppercase());
             } else if !arg.starts_with('-') {
                 path_arg = Some(arg.clone());
                 break; // Only take the first non-flag argument
@@ -57,45 +112,124 @@ impl Command for LsCommand {
                 let node = if parent_path.is_empty() {
                     state.current_node().clone()
                 } else {
-                    self.resolve_path(state, parent_path).await?
+                    state
+                        .resolver()
+                        .resolve(state.current_node(), parent_path)
+                        .await?
                 };
 
                 (node, Some(pattern.to_string()))
             } else {
-                (self.resolve_path(state, &path).await?, None)
+                (
+                    state
+                        .resolver()
+                        .resolve(state.current_node(), &path)
+                        .await?,
+                    None,
+                )
             }
         } else {
             (state.current_node().clone(), None)
         };
 
+        if recursive && !summarize {
+            return Err(anyhow!("-R only applies with --summarize"));
+        }
+
+        if storage_class.is_some()
+            && !matches!(target_node, VfsNode::Bucket { .. } | VfsNode::Prefix { .. })
+        {
+            return Err(anyhow!(
+                "--storage-class only applies to buckets and prefixes"
+            ));
+        }
+
+        if no_cache {
+            let listings = state.s3_client().listing_cache();
+            match &target_node {
+                VfsNode::Bucket { name } => listings.remove(name, ""),
+                VfsNode::Prefix { bucket, prefix } => listings.remove(bucket, prefix),
+                _ => {}
+            }
+        }
+
+        if versions {
+            return self
+                .list_versions(state, out, &target_node, &filter_pattern, json)
+                .await;
+        }
+
+        // (object count, total bytes) for --summarize
+        let mut summary: Option<(usize, u64)> = None;
+
         match &target_node {
             VfsNode::Root => {
                 // List S3 buckets
                 let buckets = state.s3_client().list_buckets().await?;
 
-                if long_format {
-                    print_line!("{:<30} CREATED", "NAME");
-                    print_line!("{}", "-".repeat(60));
+                if json {
+                    for bucket in buckets {
+                        let record = json!({
+                            "type": "bucket",
+                            "name": bucket.name,
+                            "created": bucket.creation_date,
+                        });
+                        print_line!(out, "{record}");
+                    }
+                } else if long_format {
+                    print_line!(out, "{:<30} CREATED", "NAME");
+                    print_line!(out, "{}", "-".repeat(60));
                     for bucket in buckets {
                         let created = bucket.creation_date.unwrap_or_else(|| "-".to_string());
-                        print_line!("{:<30} {}", bucket.name.blue().bold(), created);
+                        print_line!(
+                            out,
+                            "{:<30} {}",
+                            state.theme().paint_kind(&bucket.name, FileKind::Dir),
+                            created
+                        );
                     }
                 } else {
                     for bucket in buckets {
-                        print_line!("{}/", bucket.name.blue().bold());
+                        print_line!(
+                            out,
+                            "{}/",
+                            state.theme().paint_kind(&bucket.name, FileKind::Dir)
+                        );
                     }
                 }
             }
 
             VfsNode::Bucket { name } => {
                 // List objects in bucket (top level)
-                let result = state.s3_client().list_objects(name, "", Some("/")).await?;
-
-                if long_format {
-                    print_line!("{:<50} {:>12} MODIFIED", "NAME", "SIZE");
-                    print_line!("{}", "-".repeat(80));
+                let mut result = if owner {
+                    state
+                        .s3_client()
+                        .list_objects_with_owner(name, "", Some("/"))
+                        .await?
+                } else {
+                    state.s3_client().list_objects(name, "", Some("/")).await?
+                };
+                if let Some(class) = &storage_class {
+                    Self::filter_storage_class(&mut result, class);
+                } else if filter_pattern.is_none() {
+                    state.update_completions(&target_node, CompletionEntry::from_listing(&result));
+                }
 
-                    // Print prefixes (directories)
+                if json {
+                    Self::print_json_listing(out, &result, &filter_pattern)?;
+                } else if long_format {
+                    Self::print_long_listing(
+                        state,
+                        out,
+                        name,
+                        &result,
+                        &filter_pattern,
+                        owner,
+                        content_type,
+                    )
+                    .await?;
+                } else {
+                    // Print prefixes
                     for prefix in &result.prefixes {
                         let display_name = prefix
                             .trim_end_matches('/')
@@ -104,9 +238,9 @@ impl Command for LsCommand {
                             .unwrap_or(prefix);
                         if Self::should_display(display_name, &filter_pattern) {
                             print_line!(
-                                "{:<50} {:>12} -",
-                                format!("{display_name}/").blue().bold(),
-                                "-"
+                                out,
+                                "{}/",
+                                state.theme().paint_kind(display_name, FileKind::Dir)
                             );
                         }
                     }
@@ -115,57 +249,68 @@ impl Command for LsCommand {
                     for obj in &result.objects {
                         let display_name = obj.key.rsplit('/').next().unwrap_or(&obj.key);
                         if Self::should_display(display_name, &filter_pattern) {
-                            let modified = obj.last_modified.as_deref().unwrap_or("-");
-                            print_line!(
-                                "{:<50} {:>12} {}",
-                                display_name,
-                                humansize::format_size(obj.size, humansize::BINARY),
-                                modified
-                            );
-                        }
-                    }
-                } else {
-                    // Print prefixes
-                    for prefix in &result.prefixes {
-                        let display_name = prefix
-                            .trim_end_matches('/')
-                            .rsplit('/')
-                            .next()
-                            .unwrap_or(prefix);
-                        if Self::should_display(display_name, &filter_pattern) {
-                            print_line!("{}/", display_name.blue().bold());
+                            print_line!(out, "{}", state.theme().paint(display_name));
                         }
                     }
+                }
 
-                    // Print objects
-                    for obj in &result.objects {
-                        let display_name = obj.key.rsplit('/').next().unwrap_or(&obj.key);
-                        if Self::should_display(display_name, &filter_pattern) {
-                            print_line!("{display_name}");
-                        }
-                    }
+                if summarize {
+                    summary = Some(
+                        Self::summarize_listing(
+                            state,
+                            name,
+                            "",
+                            &result,
+                            recursive,
+                            &filter_pattern,
+                            storage_class.as_deref(),
+                        )
+                        .await?,
+                    );
                 }
             }
 
             VfsNode::Prefix { bucket, prefix } => {
                 // List objects with this prefix
-                let result = state
-                    .s3_client()
-                    .list_objects(bucket, prefix, Some("/"))
-                    .await?;
-
-                if long_format {
-                    print_line!("{:<50} {:>12} MODIFIED", "NAME", "SIZE");
-                    print_line!("{}", "-".repeat(80));
+                let mut result = if owner {
+                    state
+                        .s3_client()
+                        .list_objects_with_owner(bucket, prefix, Some("/"))
+                        .await?
+                } else {
+                    state
+                        .s3_client()
+                        .list_objects(bucket, prefix, Some("/"))
+                        .await?
+                };
+                if let Some(class) = &storage_class {
+                    Self::filter_storage_class(&mut result, class);
+                } else if filter_pattern.is_none() {
+                    state.update_completions(&target_node, CompletionEntry::from_listing(&result));
+                }
 
-                    // Print prefixes (directories)
+                if json {
+                    Self::print_json_listing(out, &result, &filter_pattern)?;
+                } else if long_format {
+                    Self::print_long_listing(
+                        state,
+                        out,
+                        bucket,
+                        &result,
+                        &filter_pattern,
+                        owner,
+                        content_type,
+                    )
+                    .await?;
+                } else {
+                    // Print prefixes
                     for p in &result.prefixes {
                         let display_name = p.trim_end_matches('/').rsplit('/').next().unwrap_or(p);
                         if Self::should_display(display_name, &filter_pattern) {
                             print_line!(
-                                "{:<50} {:>12} -",
-                                format!("{display_name}/").blue().bold(),
-                                "-"
+                                out,
+                                "{}/",
+                                state.theme().paint_kind(display_name, FileKind::Dir)
                             );
                         }
                     }
@@ -174,126 +319,49 @@ impl Command for LsCommand {
                     for obj in &result.objects {
                         let display_name = obj.key.rsplit('/').next().unwrap_or(&obj.key);
                         if Self::should_display(display_name, &filter_pattern) {
-                            let modified = obj.last_modified.as_deref().unwrap_or("-");
-                            print_line!(
-                                "{:<50} {:>12} {}",
-                                display_name,
-                                humansize::format_size(obj.size, humansize::BINARY),
-                                modified
-                            );
-                        }
-                    }
-                } else {
-                    // Print prefixes
-                    for p in &result.prefixes {
-                        let display_name = p.trim_end_matches('/').rsplit('/').next().unwrap_or(p);
-                        if Self::should_display(display_name, &filter_pattern) {
-                            print_line!("{}/", display_name.blue().bold());
+                            print_line!(out, "{}", state.theme().paint(display_name));
                         }
                     }
+                }
 
-                    // Print objects
-                    for obj in &result.objects {
-                        let display_name = obj.key.rsplit('/').next().unwrap_or(&obj.key);
-                        if Self::should_display(display_name, &filter_pattern) {
-                            print_line!("{display_name}");
-                        }
-                    }
+                if summarize {
+                    summary = Some(
+                        Self::summarize_listing(
+                            state,
+                            bucket,
+                            prefix,
+                            &result,
+                            recursive,
+                            &filter_pattern,
+                            storage_class.as_deref(),
+                        )
+                        .await?,
+                    );
                 }
             }
 
-            VfsNode::Archive {
-                parent,
-                archive_type,
-                index,
-            } => {
+            VfsNode::Archive { archive_type, .. } => {
                 // List contents of archive at root
-                let idx = if let Some(i) = index {
-                    Arc::clone(i)
-                } else {
-                    // Build index
-                    let (bucket, key) = match parent.as_ref() {
-                        VfsNode::Object { bucket, key, .. } => (bucket, key),
-                        _ => return Err(anyhow!("Invalid archive parent")),
-                    };
-
-                    let cache_key = format!("s3://{bucket}/{key}");
-                    if let Some(cached) = state.cache().get(&cache_key) {
-                        cached
-                    } else {
-                        let built = match archive_type {
-                            ArchiveType::Zip => {
-                                let handler = ZipHandler::new();
-                                handler.build_index(state.s3_client(), bucket, key).await?
-                            }
-                            ArchiveType::Tar | ArchiveType::TarGz | ArchiveType::TarBz2 => {
-                                let handler = TarHandler::new(archive_type.clone());
-                                handler.build_index(state.s3_client(), bucket, key).await?
-                            }
-                            #[cfg(feature = "parquet")]
-                            ArchiveType::Parquet => {
-                                let handler = ParquetHandler::new();
-                                handler.build_index(state.s3_client(), bucket, key).await?
-                            }
-                            _ => return Err(anyhow!("Archive type not yet supported")),
-                        };
-                        let arc = Arc::new(built);
-                        state.cache().put(cache_key, Arc::clone(&arc));
-                        arc
-                    }
-                };
+                let idx = state.resolver().index(&target_node).await?;
 
                 // List entries at root
-                let entries: Vec<_> = match archive_type {
-                    ArchiveType::Zip => {
-                        let handler = ZipHandler::new();
-                        handler.list_entries(&idx, "")
-                    }
-                    ArchiveType::Tar | ArchiveType::TarGz | ArchiveType::TarBz2 => {
-                        let handler = TarHandler::new(archive_type.clone());
-                        handler.list_entries(&idx, "")
-                    }
-                    #[cfg(feature = "parquet")]
-                    ArchiveType::Parquet => {
-                        let handler = ParquetHandler::new();
-                        handler.list_entries(&idx, "")
-                    }
-                    _ => return Err(anyhow!("Archive type not yet supported")),
-                };
-
-                if long_format {
-                    print_line!("{:<50} {:>12}", "NAME", "SIZE");
-                    print_line!("{}", "-".repeat(65));
+                let handler = handler_for(archive_type)
+                    .ok_or_else(|| anyhow!("Archive type not yet supported"))?;
+                let entries = handler.list_entries(&idx, "");
 
-                    for entry in entries {
-                        let base_name = entry
-                            .path
-                            .trim_end_matches('/')
-                            .rsplit('/')
-                            .next()
-                            .unwrap_or(&entry.path);
-                        if !Self::should_display(base_name, &filter_pattern) {
-                            continue;
-                        }
-
-                        let display_name = if entry.is_dir {
-                            format!("{base_name}/")
-                        } else {
-                            base_name.to_string()
-                        };
-
-                        let size_str = if entry.is_dir {
-                            "-".to_string()
-                        } else {
-                            humansize::format_size(entry.size, humansize::BINARY)
-                        };
+                if summarize {
+                    summary = Some(Self::summarize_entries(&entries, &filter_pattern));
+                }
 
-                        if entry.is_dir {
-                            print_line!("{:<50} {:>12}", display_name.blue().bold(), size_str);
-                        } else {
-                            print_line!("{display_name:<50} {size_str:>12}");
-                        }
-                    }
+                if json {
+                    Self::print_json_entries(out, &entries, &filter_pattern)?;
+                } else if long_format {
+                    Self::print_archive_long_listing(
+                        state.theme(),
+                        out,
+                        &entries,
+                        &filter_pattern,
+                    )?;
                 } else {
                     for entry in entries {
                         let base_name = entry
@@ -312,11 +380,13 @@ impl Command for LsCommand {
                             base_name.to_string()
                         };
 
-                        if entry.is_dir {
-                            print_line!("{}", display_name.blue().bold());
-                        } else {
-                            print_line!("{display_name}");
-                        }
+                        print_line!(
+                            out,
+                            "{}",
+                            state
+                                .theme()
+                                .paint_kind(&display_name, Self::entry_kind(entry))
+                        );
                     }
                 }
             }
@@ -331,104 +401,32 @@ impl Command for LsCommand {
                     return Err(anyhow!("Not a directory"));
                 }
 
-                // Get archive type
                 let archive_type = match archive.as_ref() {
-                    VfsNode::Archive { archive_type, .. } => archive_type.clone(),
+                    VfsNode::Archive { archive_type, .. } => archive_type,
                     _ => return Err(anyhow!("Not an archive")),
                 };
 
                 // Get archive index
-                let idx = match archive.as_ref() {
-                    VfsNode::Archive { index: Some(i), .. } => Arc::clone(i),
-                    VfsNode::Archive {
-                        parent,
-                        archive_type,
-                        ..
-                    } => {
-                        let (bucket, key) = match parent.as_ref() {
-                            VfsNode::Object { bucket, key, .. } => (bucket, key),
-                            _ => return Err(anyhow!("Invalid archive parent")),
-                        };
-
-                        let cache_key = format!("s3://{bucket}/{key}");
-                        if let Some(cached) = state.cache().get(&cache_key) {
-                            cached
-                        } else {
-                            let built = match archive_type {
-                                ArchiveType::Zip => {
-                                    let handler = ZipHandler::new();
-                                    handler.build_index(state.s3_client(), bucket, key).await?
-                                }
-                                ArchiveType::Tar | ArchiveType::TarGz | ArchiveType::TarBz2 => {
-                                    let handler = TarHandler::new(archive_type.clone());
-                                    handler.build_index(state.s3_client(), bucket, key).await?
-                                }
-                                #[cfg(feature = "parquet")]
-                                ArchiveType::Parquet => {
-                                    let handler = ParquetHandler::new();
-                                    handler.build_index(state.s3_client(), bucket, key).await?
-                                }
-                                _ => return Err(anyhow!("Archive type not yet supported")),
-                            };
-                            let arc = Arc::new(built);
-                            state.cache().put(cache_key, Arc::clone(&arc));
-                            arc
-                        }
-                    }
-                    _ => return Err(anyhow!("Not an archive")),
-                };
+                let idx = state.resolver().index(archive).await?;
 
                 // List entries at this path
-                let entries: Vec<_> = match &archive_type {
-                    ArchiveType::Zip => {
-                        let handler = ZipHandler::new();
-                        handler.list_entries(&idx, path)
-                    }
-                    ArchiveType::Tar | ArchiveType::TarGz | ArchiveType::TarBz2 => {
-                        let handler = TarHandler::new(archive_type.clone());
-                        handler.list_entries(&idx, path)
-                    }
-                    #[cfg(feature = "parquet")]
-                    ArchiveType::Parquet => {
-                        let handler = ParquetHandler::new();
-                        handler.list_entries(&idx, path)
-                    }
-                    _ => return Err(anyhow!("Archive type not yet supported")),
-                };
-
-                if long_format {
-                    print_line!("{:<50} {:>12}", "NAME", "SIZE");
-                    print_line!("{}", "-".repeat(65));
+                let handler = handler_for(archive_type)
+                    .ok_or_else(|| anyhow!("Archive type not yet supported"))?;
+                let entries = handler.list_entries(&idx, path);
 
-                    for entry in entries {
-                        let full_path = &entry.path;
-                        let base_name = full_path
-                            .trim_end_matches('/')
-                            .rsplit('/')
-                            .next()
-                            .unwrap_or(full_path);
-                        if !Self::should_display(base_name, &filter_pattern) {
-                            continue;
-                        }
-
-                        let display_name = if entry.is_dir {
-                            format!("{base_name}/")
-                        } else {
-                            base_name.to_string()
-                        };
-
-                        let size_str = if entry.is_dir {
-                            "-".to_string()
-                        } else {
-                            humansize::format_size(entry.size, humansize::BINARY)
-                        };
+                if summarize {
+                    summary = Some(Self::summarize_entries(&entries, &filter_pattern));
+                }
 
-                        if entry.is_dir {
-                            print_line!("{:<50} {:>12}", display_name.blue().bold(), size_str);
-                        } else {
-                            print_line!("{display_name:<50} {size_str:>12}");
-                        }
-                    }
+                if json {
+                    Self::print_json_entries(out, &entries, &filter_pattern)?;
+                } else if long_format {
+                    Self::print_archive_long_listing(
+                        state.theme(),
+                        out,
+                        &entries,
+                        &filter_pattern,
+                    )?;
                 } else {
                     for entry in entries {
                         let full_path = &entry.path;
@@ -447,11 +445,13 @@ impl Command for LsCommand {
                             base_name.to_string()
                         };
 
-                        if entry.is_dir {
-                            print_line!("{}", display_name.blue().bold());
-                        } else {
-                            print_line!("{display_name}");
-                        }
+                        print_line!(
+                            out,
+                            "{}",
+                            state
+                                .theme()
+                                .paint_kind(&display_name, Self::entry_kind(entry))
+                        );
                     }
                 }
             }
@@ -461,179 +461,480 @@ impl Command for LsCommand {
             }
         }
 
+        if let Some((count, bytes)) = summary {
+            if json {
+                let record = json!({ "type": "summary", "objects": count, "bytes": bytes });
+                print_line!(out, "{record}");
+            } else {
+                print_line!(out);
+                print_line!(
+                    out,
+                    "{count} objects, {} total",
+                    humansize::format_size(bytes, humansize::BINARY)
+                );
+            }
+        }
+
         Ok(())
     }
 }
 
 impl LsCommand {
-    /// Resolve a path (absolute or relative) to a VFS node
-    async fn resolve_path(&self, state: &ShellState, path: &str) -> Result<VfsNode> {
-        // Start from root for absolute paths, current for relative
-        let mut current = if path.starts_with('/') {
-            VfsNode::Root
-        } else {
-            state.current_node().clone()
+    /// List every version of the objects in a bucket or prefix, or of a single object
+    async fn list_versions(
+        &self,
+        state: &ShellState,
+        out: &mut Output,
+        node: &VfsNode,
+        filter_pattern: &Option<String>,
+        json: bool,
+    ) -> Result<()> {
+        let (bucket, prefix, exact_key) = match node {
+            VfsNode::Bucket { name } => (name, String::new(), None),
+            VfsNode::Prefix { bucket, prefix } => (bucket, prefix.clone(), None),
+            VfsNode::Object { bucket, key, .. } => (bucket, key.clone(), Some(key)),
+            _ => {
+                return Err(anyhow!(
+                    "--versions only applies to buckets, prefixes, and objects"
+                ));
+            }
         };
 
-        // Split path into segments
-        let segments: Vec<&str> = path
-            .trim_matches('/')
-            .split('/')
-            .filter(|s| !s.is_empty() && *s != ".")
-            .collect();
+        // A single object is listed without a delimiter so only its own versions match
+        let delimiter = exact_key.is_none().then_some("/");
+        let result = state
+            .s3_client()
+            .list_object_versions(bucket, &prefix, delimiter)
+            .await?;
+
+        if json {
+            for p in &result.prefixes {
+                let name = p.trim_end_matches('/').rsplit('/').next().unwrap_or(p);
+                if Self::should_display(name, filter_pattern) {
+                    let record = json!({ "type": "dir", "name": name, "key": p });
+                    print_line!(out, "{record}");
+                }
+            }
+            for version in &result.versions {
+                if exact_key.is_some_and(|key| *key != version.key) {
+                    continue;
+                }
+                let name = version.key.rsplit('/').next().unwrap_or(&version.key);
+                if !Self::should_display(name, filter_pattern) {
+                    continue;
+                }
+                let record = json!({
+                    "type": if version.is_delete_marker { "delete_marker" } else { "file" },
+                    "name": name,
+                    "key": version.key,
+                    "version_id": version.version_id,
+                    "size": version.size,
+                    "last_modified": version.last_modified,
+                    "is_latest": version.is_latest,
+                });
+                print_line!(out, "{record}");
+            }
+            return Ok(());
+        }
 
-        // If path is just "/", return root
-        if path == "/" || segments.is_empty() {
-            return Ok(VfsNode::Root);
+        print_line!(
+            out,
+            "{:<40} {:<34} {:>12} {:<22}",
+            "NAME",
+            "VERSION ID",
+            "SIZE",
+            "MODIFIED"
+        );
+        print_line!(out, "{}", "-".repeat(120));
+
+        for p in &result.prefixes {
+            let display_name = p.trim_end_matches('/').rsplit('/').next().unwrap_or(p);
+            if Self::should_display(display_name, filter_pattern) {
+                print_line!(
+                    out,
+                    "{:<40} {:<34} {:>12} -",
+                    state
+                        .theme()
+                        .paint_kind(&format!("{display_name}/"), FileKind::Dir),
+                    "-",
+                    "-"
+                );
+            }
         }
 
-        // Process each segment
-        for segment in segments {
-            current = if segment == ".." {
-                self.navigate_up(&current)?
+        for version in &result.versions {
+            if exact_key.is_some_and(|key| *key != version.key) {
+                continue;
+            }
+            let display_name = version.key.rsplit('/').next().unwrap_or(&version.key);
+            if !Self::should_display(display_name, filter_pattern) {
+                continue;
+            }
+
+            let size = if version.is_delete_marker {
+                "-".to_string()
+            } else {
+                humansize::format_size(version.size, humansize::BINARY)
+            };
+            let mut flags = Vec::new();
+            if version.is_latest {
+                flags.push("latest");
+            }
+            if version.is_delete_marker {
+                flags.push("delete marker");
+            }
+            let flags = if flags.is_empty() {
+                String::new()
             } else {
-                self.navigate_to_segment(state, &current, segment).await?
+                format!(" ({})", flags.join(", "))
             };
+
+            print_line!(
+                out,
+                "{:<40} {:<34} {:>12} {:<22}{}",
+                state.theme().paint(display_name),
+                version.version_id,
+                size,
+                version.last_modified.as_deref().unwrap_or("-"),
+                flags.dimmed()
+            );
         }
 
-        Ok(current)
+        Ok(())
     }
 
-    /// Navigate up one level
-    fn navigate_up(&self, current: &VfsNode) -> Result<VfsNode> {
-        match current {
-            VfsNode::Root => Ok(VfsNode::Root),
-            VfsNode::Bucket { .. } => Ok(VfsNode::Root),
-            VfsNode::Prefix { bucket, prefix } => {
-                if prefix.trim_end_matches('/').contains('/') {
-                    let parent_prefix = prefix.trim_end_matches('/').rsplit_once('/').unwrap().0;
-                    Ok(VfsNode::Prefix {
-                        bucket: bucket.clone(),
-                        prefix: format!("{parent_prefix}/"),
-                    })
-                } else {
-                    Ok(VfsNode::Bucket {
-                        name: bucket.clone(),
-                    })
-                }
+    /// Count and total the objects for --summarize: the listed ones, or with -R everything
+    /// under the prefix (which may take many list requests)
+    async fn summarize_listing(
+        state: &ShellState,
+        bucket: &str,
+        prefix: &str,
+        result: &ListObjectsResult,
+        recursive: bool,
+        filter_pattern: &Option<String>,
+        storage_class: Option<&str>,
+    ) -> Result<(usize, u64)> {
+        let include = |obj: &ObjectInfo| {
+            let name = obj.key.rsplit('/').next().unwrap_or(&obj.key);
+            Self::should_display(name, filter_pattern)
+                && storage_class.is_none_or(|class| obj.effective_storage_class() == class)
+        };
+
+        if recursive {
+            let spinner = create_spinner("Summarizing...");
+            let summary = state
+                .s3_client()
+                .summarize_prefix(bucket, prefix, include)
+                .await;
+            spinner.finish_and_clear();
+            return summary;
+        }
+
+        Ok(result
+            .objects
+            .iter()
+            .filter(|obj| include(obj))
+            .fold((0, 0), |(count, bytes), obj| (count + 1, bytes + obj.size)))
+    }
+
+    /// Keep only objects in a storage class; prefixes have none, so they're dropped too
+    fn filter_storage_class(result: &mut ListObjectsResult, class: &str) {
+        result.prefixes.clear();
+        result
+            .objects
+            .retain(|obj| obj.effective_storage_class() == class);
+    }
+
+    /// Count and total the files among archive entries for --summarize
+    fn summarize_entries(
+        entries: &[&ArchiveEntry],
+        filter_pattern: &Option<String>,
+    ) -> (usize, u64) {
+        entries
+            .iter()
+            .filter(|entry| !entry.is_dir)
+            .filter(|entry| {
+                let name = entry.path.rsplit('/').next().unwrap_or(&entry.path);
+                Self::should_display(name, filter_pattern)
+            })
+            .fold((0, 0), |(count, bytes), entry| {
+                (count + 1, bytes + entry.size)
+            })
+    }
+
+    /// Print a bucket or prefix listing in long format
+    /// With `owner`, the listing was made with FetchOwner and each object's owner is shown.
+    /// With `content_type`, each shown object is HEADed (concurrently, and cached by
+    /// ETag) to add its Content-Type.
+    async fn print_long_listing(
+        state: &ShellState,
+        out: &mut Output,
+        bucket: &str,
+        result: &ListObjectsResult,
+        filter_pattern: &Option<String>,
+        owner: bool,
+        content_type: bool,
+    ) -> Result<()> {
+        let objects: Vec<&ObjectInfo> = result
+            .objects
+            .iter()
+            .filter(|obj| {
+                let name = obj.key.rsplit('/').next().unwrap_or(&obj.key);
+                Self::should_display(name, filter_pattern)
+            })
+            .collect();
+        let metadata = if content_type {
+            state
+                .s3_client()
+                .head_listed_objects(bucket, &objects)
+                .await
+        } else {
+            Vec::new()
+        };
+
+        // Storage class, then the optional owner and Content-Type columns
+        let mut columns = vec![("STORAGE CLASS", 19)];
+        if owner {
+            columns.push(("OWNER", 24));
+        }
+        if content_type {
+            columns.push(("CONTENT-TYPE", 0));
+        }
+        let header: Vec<&str> = columns.iter().map(|(title, _)| *title).collect();
+        print_line!(
+            out,
+            "{:<50} {:>12} {:<20} {}",
+            "NAME",
+            "SIZE",
+            "MODIFIED",
+            Self::join_columns(&columns, &header)
+        );
+        let width = 100 + if owner { 25 } else { 0 } + if content_type { 20 } else { 0 };
+        print_line!(out, "{}", "-".repeat(width));
+
+        // Print prefixes (directories)
+        for prefix in &result.prefixes {
+            let display_name = prefix
+                .trim_end_matches('/')
+                .rsplit('/')
+                .next()
+                .unwrap_or(prefix);
+            if Self::should_display(display_name, filter_pattern) {
+                print_line!(
+                    out,
+                    "{:<50} {:>12} {:<20} -",
+                    state
+                        .theme()
+                        .paint_kind(&format!("{display_name}/"), FileKind::Dir),
+                    "-",
+                    "-"
+                );
             }
-            VfsNode::Archive { parent, .. } => Ok(*parent.clone()),
-            VfsNode::ArchiveEntry { archive, path, .. } => {
-                if path.trim_end_matches('/').contains('/') {
-                    let parent_path = path
-                        .trim_end_matches('/')
-                        .rsplit_once('/')
-                        .unwrap()
-                        .0
-                        .to_string();
-                    Ok(VfsNode::ArchiveEntry {
-                        archive: archive.clone(),
-                        path: parent_path,
-                        size: 0,
-                        is_dir: true,
-                    })
+        }
+
+        // Print objects
+        for (i, obj) in objects.iter().enumerate() {
+            let display_name = obj.key.rsplit('/').next().unwrap_or(&obj.key);
+            let modified = obj.last_modified.as_deref().unwrap_or("-");
+            let size = humansize::format_size(obj.size, humansize::BINARY);
+            let mut cells = vec![obj.effective_storage_class()];
+            if owner {
+                cells.push(obj.owner.as_deref().unwrap_or("-"));
+            }
+            if content_type {
+                cells.push(
+                    metadata[i]
+                        .as_ref()
+                        .and_then(|m| m.content_type.as_deref())
+                        .unwrap_or("-"),
+                );
+            }
+            print_line!(
+                out,
+                "{:<50} {:>12} {:<20} {}",
+                state.theme().paint(display_name),
+                size,
+                modified,
+                Self::join_columns(&columns, &cells)
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Pad each cell to its column's width, leaving the last one unpadded
+    fn join_columns(columns: &[(&str, usize)], cells: &[&str]) -> String {
+        let last = cells.len().saturating_sub(1);
+        cells
+            .iter()
+            .zip(columns)
+            .enumerate()
+            .map(|(i, (cell, (_, width)))| {
+                if i == last {
+                    cell.to_string()
                 } else {
-                    Ok(*archive.clone())
+                    format!("{cell:<width$}")
                 }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Print a bucket or prefix listing as JSON Lines, one record per prefix or object
+    fn print_json_listing(
+        out: &mut Output,
+        result: &ListObjectsResult,
+        filter_pattern: &Option<String>,
+    ) -> Result<()> {
+        for prefix in &result.prefixes {
+            let name = prefix
+                .trim_end_matches('/')
+                .rsplit('/')
+                .next()
+                .unwrap_or(prefix);
+            if Self::should_display(name, filter_pattern) {
+                let record = json!({ "type": "dir", "name": name, "key": prefix });
+                print_line!(out, "{record}");
             }
-            VfsNode::Object { bucket, key, .. } => {
-                if key.contains('/') {
-                    let parent_prefix = key.rsplit_once('/').unwrap().0;
-                    Ok(VfsNode::Prefix {
-                        bucket: bucket.clone(),
-                        prefix: format!("{parent_prefix}/"),
-                    })
-                } else {
-                    Ok(VfsNode::Bucket {
-                        name: bucket.clone(),
-                    })
+        }
+
+        for obj in &result.objects {
+            let name = obj.key.rsplit('/').next().unwrap_or(&obj.key);
+            if Self::should_display(name, filter_pattern) {
+                let mut record = json!({
+                    "type": "file",
+                    "name": name,
+                    "key": obj.key,
+                    "size": obj.size,
+                    "last_modified": obj.last_modified,
+                    "storage_class": obj.effective_storage_class(),
+                });
+                if let Some(owner) = &obj.owner {
+                    record["owner"] = json!(owner);
                 }
+                print_line!(out, "{record}");
             }
         }
+
+        Ok(())
     }
 
-    /// Navigate to a single segment from current node
-    async fn navigate_to_segment(
-        &self,
-        state: &ShellState,
-        current: &VfsNode,
-        segment: &str,
-    ) -> Result<VfsNode> {
-        match current {
-            VfsNode::Root => Ok(VfsNode::Bucket {
-                name: segment.to_string(),
-            }),
-            VfsNode::Bucket { name } => {
-                if let Ok(metadata) = state.s3_client().head_object(name, segment).await {
-                    return Ok(VfsNode::Object {
-                        bucket: name.clone(),
-                        key: segment.to_string(),
-                        size: metadata.size,
-                    });
-                }
-                Ok(VfsNode::Prefix {
-                    bucket: name.clone(),
-                    prefix: format!("{segment}/"),
-                })
+    /// Print archive entries with their size, modification time, permissions, and owner
+    /// Formats fill in what they record; the rest show as "-".
+    fn print_archive_long_listing(
+        theme: &Theme,
+        out: &mut Output,
+        entries: &[&ArchiveEntry],
+        filter_pattern: &Option<String>,
+    ) -> Result<()> {
+        // Zips record how each file was compressed
+        let compressed = entries.iter().any(|entry| entry.compression().is_some());
+        let packed_header = if compressed {
+            format!(" {:>12} {:>5} {:<9}", "PACKED", "SAVED", "METHOD")
+        } else {
+            String::new()
+        };
+        print_line!(
+            out,
+            "{:<50} {:>12}{packed_header} {:<19} {:<10} OWNER",
+            "NAME",
+            "SIZE",
+            "MODIFIED",
+            "MODE"
+        );
+        print_line!(out, "{}", "-".repeat(100 + packed_header.len()));
+
+        for entry in entries {
+            let path = entry.path.trim_end_matches('/');
+            let base_name = path.rsplit('/').next().unwrap_or(path);
+            if !Self::should_display(base_name, filter_pattern) {
+                continue;
             }
-            VfsNode::Prefix { bucket, prefix } => {
-                let full_key = format!("{prefix}{segment}");
-                if let Ok(metadata) = state.s3_client().head_object(bucket, &full_key).await {
-                    return Ok(VfsNode::Object {
-                        bucket: bucket.clone(),
-                        key: full_key,
-                        size: metadata.size,
-                    });
-                }
-                Ok(VfsNode::Prefix {
-                    bucket: bucket.clone(),
-                    prefix: format!("{full_key}/"),
-                })
+
+            let size = if entry.is_dir {
+                "-".to_string()
+            } else {
+                humansize::format_size(entry.size, humansize::BINARY)
+            };
+            let packed = match entry.compression() {
+                Some((packed, method)) => format!(
+                    " {:>12} {:>5} {:<9}",
+                    humansize::format_size(packed, humansize::BINARY),
+                    zip::space_saved(entry.size, packed),
+                    zip::method_name(method)
+                ),
+                None if compressed => format!(" {:>12} {:>5} {:<9}", "-", "-", "-"),
+                None => String::new(),
+            };
+            let attrs = &entry.attrs;
+            let details = format!(
+                "{size:>12}{packed} {:<19} {:<10} {}",
+                attrs.modified().as_deref().unwrap_or("-"),
+                attrs.permissions(entry.is_dir).as_deref().unwrap_or("-"),
+                attrs.owner().as_deref().unwrap_or("-")
+            );
+
+            let name = if entry.is_dir {
+                format!("{base_name}/")
+            } else {
+                base_name.to_string()
+            };
+            print_line!(
+                out,
+                "{:<50} {details}",
+                theme.paint_kind(&name, Self::entry_kind(entry))
+            );
+        }
+
+        Ok(())
+    }
+
+    /// How an archive entry is colored: directories and symlinks by type, files by name
+    fn entry_kind(entry: &ArchiveEntry) -> FileKind {
+        if entry.is_dir {
+            FileKind::Dir
+        } else if entry.attrs.symlink {
+            FileKind::Symlink
+        } else {
+            FileKind::of(&entry.path)
+        }
+    }
+
+    /// Print archive entries as JSON Lines
+    fn print_json_entries(
+        out: &mut Output,
+        entries: &[&ArchiveEntry],
+        filter_pattern: &Option<String>,
+    ) -> Result<()> {
+        for entry in entries {
+            let path = entry.path.trim_end_matches('/');
+            let name = path.rsplit('/').next().unwrap_or(path);
+            if !Self::should_display(name, filter_pattern) {
+                continue;
             }
-            VfsNode::Archive { index, .. } => {
-                if let Some(idx) = index
-                    && let Some(entry) = idx.find_entry(segment)
-                    && entry.is_dir
-                {
-                    // Store the path without trailing slash for consistency
-                    let clean_path = entry.path.trim_end_matches('/').to_string();
-                    return Ok(VfsNode::ArchiveEntry {
-                        archive: Box::new(current.clone()),
-                        path: clean_path,
-                        size: entry.size,
-                        is_dir: true,
-                    });
-                }
-                Err(anyhow!("Path not found in archive: {segment}"))
+            let mut record: Value = if entry.is_dir {
+                json!({ "type": "dir", "name": name, "path": path })
+            } else {
+                json!({ "type": "file", "name": name, "path": path, "size": entry.size })
+            };
+            if let Some((packed, method)) = entry.compression() {
+                record["compressed_size"] = json!(packed);
+                record["compression"] = json!(zip::method_name(method));
             }
-            VfsNode::ArchiveEntry { archive, path, .. } => {
-                if let VfsNode::Archive {
-                    index: Some(idx), ..
-                } = archive.as_ref()
-                {
-                    let target_path = if path.is_empty() {
-                        segment.to_string()
-                    } else {
-                        format!("{}/{}", path.trim_end_matches('/'), segment)
-                    };
-
-                    if let Some(entry) = idx.find_entry(&target_path)
-                        && entry.is_dir
-                    {
-                        // Store the path without trailing slash for consistency
-                        let clean_path = entry.path.trim_end_matches('/').to_string();
-                        return Ok(VfsNode::ArchiveEntry {
-                            archive: archive.clone(),
-                            path: clean_path,
-                            size: entry.size,
-                            is_dir: true,
-                        });
-                    }
-                }
-                Err(anyhow!("Path not found"))
+            let attrs = &entry.attrs;
+            if let Some(modified) = attrs.modified() {
+                record["modified"] = json!(modified);
+            }
+            if let Some(mode) = attrs.mode {
+                record["mode"] = json!(format!("{mode:o}"));
+            }
+            if let Some(owner) = attrs.owner() {
+                record["owner"] = json!(owner);
             }
-            VfsNode::Object { .. } => Err(anyhow!("Cannot navigate into a file")),
+            print_line!(out, "{record}");
         }
+
+        Ok(())
     }
 
     /// Check if a filename should be displayed given an optional filter pattern
@@ -645,7 +946,7 @@ impl LsCommand {
     }
 
     /// Match a filename against a simple wildcard pattern (* and ?)
-    fn matches_pattern(filename: &str, pattern: &str) -> bool {
+    pub(crate) fn matches_pattern(filename: &str, pattern: &str) -> bool {
         let mut name_chars = filename.chars().peekable();
         let mut pattern_chars = pattern.chars().peekable();
 
@@ -683,3 +984,155 @@ impl LsCommand {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::s3::client::ObjectInfo;
+
+    #[test]
+    fn test_summarize_entries() {
+        let entries = [
+            ArchiveEntry::physical("data/".to_string(), 0, 0, true),
+            ArchiveEntry::physical("data/a.csv".to_string(), 0, 1000, false),
+            ArchiveEntry::physical("data/b.json".to_string(), 0, 24, false),
+        ];
+        let entries: Vec<&ArchiveEntry> = entries.iter().collect();
+
+        assert_eq!(LsCommand::summarize_entries(&entries, &None), (2, 1024));
+        assert_eq!(
+            LsCommand::summarize_entries(&entries, &Some("*.csv".to_string())),
+            (1, 1000)
+        );
+    }
+
+    #[test]
+    fn test_print_json_listing() {
+        let result = ListObjectsResult {
+            prefixes: vec!["logs/2024/".to_string()],
+            objects: vec![ObjectInfo {
+                key: "logs/app.json".to_string(),
+                size: 42,
+                last_modified: None,
+                etag: None,
+                storage_class: Some("GLACIER".to_string()),
+                owner: None,
+            }],
+        };
+
+        let mut out = Output::capture();
+        LsCommand::print_json_listing(&mut out, &result, &None).unwrap();
+        let text = String::from_utf8(out.captured().unwrap().to_vec()).unwrap();
+        let records: Vec<Value> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(
+            records,
+            vec![
+                json!({"type": "dir", "name": "2024", "key": "logs/2024/"}),
+                json!({
+                    "type": "file",
+                    "name": "app.json",
+                    "key": "logs/app.json",
+                    "size": 42,
+                    "last_modified": null,
+                    "storage_class": "GLACIER",
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_archive_long_listing_compression() {
+        let packed = ArchiveEntry::zip_entry("docs/a.txt".to_string(), 1000, false, 0, 250, 8, 0);
+        let stored = ArchiveEntry::zip_entry("docs/b.txt".to_string(), 10, false, 0, 10, 0, 0);
+        let mut out = Output::capture();
+        LsCommand::print_archive_long_listing(
+            &Theme::default(),
+            &mut out,
+            &[&packed, &stored],
+            &None,
+        )
+        .unwrap();
+        let listing = String::from_utf8(out.captured().unwrap().to_vec()).unwrap();
+        let lines: Vec<&str> = listing.lines().collect();
+        assert!(lines[0].contains("PACKED SAVED METHOD"));
+        let columns = |line: &str| {
+            line.split_whitespace()
+                .skip(1)
+                .take(6)
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            columns(lines[2]),
+            ["1000", "B", "250", "B", "75%", "deflate"]
+        );
+        assert_eq!(columns(lines[3]), ["10", "B", "10", "B", "0%", "stored"]);
+    }
+
+    #[tokio::test]
+    async fn test_listing_cache() {
+        let mock = crate::s3::MockS3Client::new().with_object("b", "a.txt", "a");
+        let mut state = ShellState::with_client(std::sync::Arc::new(mock.client()))
+            .await
+            .unwrap();
+        let mut ls = async |args: &[&str]| {
+            let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+            let mut out = Output::capture();
+            LsCommand
+                .execute(&mut state, &args, &mut out)
+                .await
+                .unwrap();
+            String::from_utf8(out.captured().unwrap().to_vec()).unwrap()
+        };
+        assert_eq!(ls(&["/b"]).await, "a.txt\n");
+
+        // A change made elsewhere shows up once the listing is fetched again
+        let _ = mock.with_object("b", "b.txt", "b");
+        assert_eq!(ls(&["/b"]).await, "a.txt\n");
+        assert_eq!(ls(&["--no-cache", "/b"]).await, "a.txt\nb.txt\n");
+    }
+
+    #[tokio::test]
+    async fn test_storage_class_filter() {
+        let mock = crate::s3::MockS3Client::new()
+            .with_object("b", "logs/a.log", vec![0; 5])
+            .with_object("b", "logs/b.log", vec![0; 10])
+            .with_object("b", "logs/c.log", vec![0; 20])
+            .with_object("b", "logs/sub/d.log", vec![0; 40])
+            .with_object("b", "logs/sub/e.log", vec![0; 80])
+            .with_storage_class("b", "logs/b.log", "GLACIER")
+            .with_storage_class("b", "logs/c.log", "GLACIER")
+            .with_storage_class("b", "logs/sub/d.log", "GLACIER");
+        let mut state = ShellState::with_client(std::sync::Arc::new(mock.client()))
+            .await
+            .unwrap();
+        let mut ls = async |line: &str| {
+            let mut out = Output::capture();
+            state.execute_to(line, &mut out).await.unwrap();
+            String::from_utf8(out.captured().unwrap().to_vec()).unwrap()
+        };
+
+        assert_eq!(
+            ls("ls --storage-class glacier /b/logs").await,
+            "b.log\nc.log\n"
+        );
+        assert_eq!(
+            ls("ls --storage-class glacier --summarize /b/logs").await,
+            "b.log\nc.log\n\n2 objects, 30 B total\n"
+        );
+        assert!(
+            ls("ls --storage-class glacier --summarize -R /b/logs")
+                .await
+                .ends_with("\n3 objects, 70 B total\n")
+        );
+        assert!(
+            ls("ls --storage-class standard --summarize -R /b/logs")
+                .await
+                .ends_with("\n2 objects, 85 B total\n")
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+
+use super::output::{Output, print_line};
+use super::{Command, ShellState, resolve_object};
+
+pub struct MetaCommand;
+
+#[async_trait]
+impl Command for MetaCommand {
+    fn name(&self) -> &str {
+        "meta"
+    }
+
+    fn usage(&self) -> &str {
+        "meta FILE [-s KEY=VALUE]... [-n|--dry-run] - Show or set user metadata (x-amz-meta-*)"
+    }
+
+    fn flags(&self) -> &[(&str, &str)] {
+        &[
+            ("-s, --set KEY=VALUE", "Set a metadata key (repeatable)"),
+            (
+                "-n, --dry-run",
+                "Show the change without copying the object",
+            ),
+        ]
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        let mut updates = Vec::new();
+        let mut dry_run = false;
+        let mut positional = Vec::new();
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "-s" | "--set" => {
+                    let pair = iter
+                        .next()
+                        .ok_or_else(|| anyhow!("{arg} requires KEY=VALUE"))?;
+                    updates.push(Self::parse_pair(pair)?);
+                }
+                "-n" | "--dry-run" => dry_run = true,
+                _ => positional.push(arg),
+            }
+        }
+
+        let [path] = positional.as_slice() else {
+            return Err(anyhow!("Usage: {}", self.usage()));
+        };
+
+        let (bucket, key, _) = resolve_object(state, path).await?;
+        if !updates.is_empty() && !dry_run {
+            state
+                .s3_client()
+                .check_writable("update metadata of", &bucket, &key)?;
+        }
+        let mut metadata = state
+            .s3_client()
+            .head_object(&bucket, &key)
+            .await?
+            .user_metadata;
+
+        if !updates.is_empty() {
+            // Metadata can only be replaced as a whole, so merge onto the current set
+            metadata.extend(updates);
+            if dry_run {
+                print_line!(
+                    out,
+                    "(dry run) metadata of s3://{bucket}/{key} would become:"
+                );
+            } else {
+                state
+                    .s3_client()
+                    .replace_user_metadata(&bucket, &key, &metadata)
+                    .await?;
+            }
+        }
+
+        if metadata.is_empty() {
+            print_line!(out, "(no user metadata)");
+            return Ok(());
+        }
+
+        let width = metadata.keys().map(|k| k.len()).max().unwrap_or(0);
+        for (name, value) in &metadata {
+            print_line!(out, "{name:<width$}  {value}");
+        }
+
+        Ok(())
+    }
+}
+
+impl MetaCommand {
+    /// Parse a KEY=VALUE pair; keys are lowercased like S3 stores them
+    fn parse_pair(pair: &str) -> Result<(String, String)> {
+        let (name, value) = pair
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Expected KEY=VALUE, got: {pair}"))?;
+        let name = name.trim().trim_start_matches("x-amz-meta-").to_lowercase();
+        if name.is_empty() {
+            return Err(anyhow!("Metadata key cannot be empty: {pair}"));
+        }
+        Ok((name, value.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pair() {
+        assert_eq!(
+            MetaCommand::parse_pair("Owner=data-team").unwrap(),
+            ("owner".to_string(), "data-team".to_string())
+        );
+        assert_eq!(
+            MetaCommand::parse_pair("x-amz-meta-query=a=b").unwrap(),
+            ("query".to_string(), "a=b".to_string())
+        );
+        assert!(MetaCommand::parse_pair("novalue").is_err());
+        assert!(MetaCommand::parse_pair("=value").is_err());
+    }
+}
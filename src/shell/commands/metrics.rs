@@ -0,0 +1,143 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+
+use super::output::{Output, print_line};
+use super::{Command, ShellState};
+use crate::s3::MetricsSummary;
+
+pub struct MetricsCommand;
+
+#[async_trait]
+impl Command for MetricsCommand {
+    fn name(&self) -> &str {
+        "metrics"
+    }
+
+    fn usage(&self) -> &str {
+        "metrics [show|per-command|reset] - Show the S3 requests and bytes used by recent commands"
+    }
+
+    fn records_metrics(&self) -> bool {
+        false
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        match args.first().map(String::as_str) {
+            None | Some("show") => {
+                match state.metrics_log().back() {
+                    Some((line, summary)) => {
+                        print_line!(out, "Last command: {line}");
+                        for detail in describe(summary) {
+                            print_line!(out, "  {detail}");
+                        }
+                    }
+                    None => print_line!(out, "No commands recorded yet"),
+                }
+                print_line!(out, "Session:");
+                for detail in describe(state.session_metrics()) {
+                    print_line!(out, "  {detail}");
+                }
+            }
+            Some("per-command") => {
+                print_line!(
+                    out,
+                    "{:>8}  {:>10}  {:>10}  {:>8}  COMMAND",
+                    "REQUESTS",
+                    "DOWN",
+                    "UP",
+                    "TIME"
+                );
+                for (line, summary) in state.metrics_log() {
+                    print_line!(
+                        out,
+                        "{:>8}  {:>10}  {:>10}  {:>7.2}s  {line}",
+                        summary.requests(),
+                        format_bytes(summary.downloaded),
+                        format_bytes(summary.uploaded),
+                        summary.elapsed.as_secs_f64()
+                    );
+                }
+            }
+            Some("reset") => {
+                state.reset_metrics();
+                print_line!(out, "Metrics reset");
+            }
+            Some(_) => return Err(anyhow!("Usage: {}", self.usage())),
+        }
+        Ok(())
+    }
+}
+
+/// Lines describing a summary: requests by operation, bytes, and time
+fn describe(summary: &MetricsSummary) -> Vec<String> {
+    let operations = summary
+        .operations
+        .iter()
+        .map(|(operation, count)| format!("{operation} {count}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut requests = match operations.as_str() {
+        "" => "0".to_string(),
+        operations => format!("{} ({operations})", summary.requests()),
+    };
+    match summary.retries {
+        0 => {}
+        1 => requests.push_str(", 1 retry"),
+        n => requests.push_str(&format!(", {n} retries")),
+    }
+    vec![
+        format!("Requests:   {requests}"),
+        format!("Downloaded: {}", format_bytes(summary.downloaded)),
+        format!("Uploaded:   {}", format_bytes(summary.uploaded)),
+        format!("Time:       {:.2}s", summary.elapsed.as_secs_f64()),
+    ]
+}
+
+fn format_bytes(bytes: u64) -> String {
+    humansize::format_size(bytes, humansize::DECIMAL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::time::Duration;
+
+    #[test]
+    fn test_describe() {
+        let summary = MetricsSummary {
+            operations: BTreeMap::from([
+                ("GetObject".to_string(), 1),
+                ("ListObjectsV2".to_string(), 2),
+            ]),
+            retries: 0,
+            downloaded: 1500,
+            uploaded: 0,
+            elapsed: Duration::from_millis(250),
+        };
+        assert_eq!(
+            describe(&summary),
+            [
+                "Requests:   3 (GetObject 1, ListObjectsV2 2)",
+                "Downloaded: 1.50 kB",
+                "Uploaded:   0 B",
+                "Time:       0.25s",
+            ]
+        );
+        assert_eq!(describe(&MetricsSummary::default())[0], "Requests:   0");
+
+        let retried = MetricsSummary {
+            retries: 2,
+            ..summary
+        };
+        assert_eq!(
+            describe(&retried)[0],
+            "Requests:   3 (GetObject 1, ListObjectsV2 2), 2 retries"
+        );
+    }
+}
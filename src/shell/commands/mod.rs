@@ -1,12 +1,48 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use async_trait::async_trait;
 
+pub mod alias;
+pub mod bucket;
 pub mod cat;
 pub mod cd;
+pub mod checksum;
+pub mod compare;
+pub mod config;
+pub mod cost;
+pub mod diff;
+pub mod edit;
+pub mod file;
+pub mod foreach;
+pub mod get;
+pub mod history;
+pub mod jobs;
 pub mod ls;
+pub mod meta;
+pub mod metrics;
+pub mod open;
 pub mod output;
+pub mod profile;
+pub mod provider;
+#[cfg(feature = "parquet")]
+pub mod query;
+pub mod refresh;
+pub mod search;
+pub mod select;
+pub mod set;
+pub mod stat;
+pub mod strings;
+pub mod sync;
+pub mod tags;
+pub mod tee;
+pub mod watch;
+pub mod wc;
+pub mod whoami;
+pub mod xxd;
+pub mod zcat;
 
 use super::ShellState;
+use crate::vfs::VfsNode;
+use output::Output;
 
 /// Trait for shell commands
 #[async_trait]
@@ -17,6 +53,159 @@ pub trait Command: Send + Sync {
     /// Get command usage help
     fn usage(&self) -> &str;
 
+    /// Flags and what they do, listed by `help NAME` and `NAME --help`
+    fn flags(&self) -> &[(&str, &str)] {
+        &[]
+    }
+
     /// Execute the command
-    async fn execute(&self, state: &mut ShellState, args: &[String]) -> Result<()>;
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        _out: &mut Output,
+    ) -> Result<()>;
+
+    /// Whether the command waits on the user, so the `timeout` setting doesn't apply
+    fn interactive(&self) -> bool {
+        false
+    }
+
+    /// Whether `s3://` arguments reach the command as written rather than as shell paths,
+    /// for commands that need to tell S3 locations from local ones
+    fn keeps_s3_urls(&self) -> bool {
+        false
+    }
+
+    /// Whether everything after ` -- ` reaches the command as one argument, pipes and
+    /// redirections included, for commands that run it as a command line themselves
+    fn takes_command_line(&self) -> bool {
+        false
+    }
+
+    /// Whether the command's S3 usage is logged for `metrics`; commands that report
+    /// on the log opt out so they don't push out the command being inspected
+    fn records_metrics(&self) -> bool {
+        true
+    }
+}
+
+/// Map a '/'-separated relative path (an S3 key suffix or archive entry) under a local directory
+/// Returns None for paths that would escape it, such as keys containing "..".
+pub(crate) fn local_path_under(root: &std::path::Path, rel: &str) -> Option<std::path::PathBuf> {
+    use std::path::{Component, Path};
+
+    let mut path = root.to_path_buf();
+    for part in rel
+        .split('/')
+        .filter(|part| !part.is_empty() && *part != ".")
+    {
+        // Rejects "..", and on Windows drive prefixes and backslash-separated segments
+        let mut components = Path::new(part).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(_)), None) => path.push(part),
+            _ => return None,
+        }
+    }
+    (path != root).then_some(path)
+}
+
+/// Resolve a path to an S3 object's bucket, key, and size
+/// Used by commands that operate on S3 objects directly rather than archive entries
+pub(crate) async fn resolve_object(
+    state: &ShellState,
+    path: &str,
+) -> Result<(String, String, u64)> {
+    match state.resolver().resolve(state.current_node(), path).await? {
+        VfsNode::Object {
+            bucket, key, size, ..
+        } => Ok((bucket, key, size)),
+        VfsNode::Prefix { .. } => Err(anyhow!("File not found: {path}")),
+        _ => Err(not_an_object(path)),
+    }
+}
+
+/// Resolve a path to the bucket and key of an S3 object that may not exist yet,
+/// such as an upload destination or an older version of a deleted object
+pub(crate) async fn resolve_object_key(state: &ShellState, path: &str) -> Result<(String, String)> {
+    let (dir, name) = match path.rsplit_once('/') {
+        Some(("", name)) => ("/", name),
+        Some((dir, name)) => (dir, name),
+        None => (".", path),
+    };
+    if matches!(name, "" | "." | "..") {
+        return Err(not_an_object(path));
+    }
+    match state.resolver().resolve(state.current_node(), dir).await? {
+        VfsNode::Bucket { name: bucket } => Ok((bucket, name.to_string())),
+        VfsNode::Prefix { bucket, prefix } => Ok((bucket, format!("{prefix}{name}"))),
+        _ => Err(not_an_object(path)),
+    }
+}
+
+/// The error for a path that names a bucket, prefix, or archive entry where an object is needed
+pub(crate) fn not_an_object(path: &str) -> anyhow::Error {
+    anyhow!("Not an S3 object: {path}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_local_path_under() {
+        let root = Path::new("out");
+        assert_eq!(
+            local_path_under(root, "a/b.txt"),
+            Some(root.join("a").join("b.txt"))
+        );
+        assert_eq!(
+            local_path_under(root, "./a//b.txt"),
+            Some(root.join("a").join("b.txt"))
+        );
+        assert_eq!(local_path_under(root, "../etc/passwd"), None);
+        assert_eq!(local_path_under(root, "a/../../b"), None);
+        assert_eq!(local_path_under(root, ""), None);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_object() {
+        let mock = crate::s3::MockS3Client::demo();
+        let mut state = ShellState::with_client(std::sync::Arc::new(mock.client()))
+            .await
+            .unwrap();
+        state
+            .execute_to("cd /demo-bucket/logs/2024-06-01", &mut Output::capture())
+            .await
+            .unwrap();
+
+        let (bucket, key, size) = resolve_object(&state, "../../././README.md").await.unwrap();
+        assert_eq!(
+            (bucket.as_str(), key.as_str()),
+            ("demo-bucket", "README.md")
+        );
+        assert_eq!(
+            size,
+            mock.object("demo-bucket", "README.md").unwrap().len() as u64
+        );
+
+        for (path, error) in [
+            ("../missing.txt", "File not found: ../missing.txt"),
+            ("..", "File not found: .."),
+            ("/demo-bucket", "Not an S3 object: /demo-bucket"),
+        ] {
+            let message = resolve_object(&state, path).await.unwrap_err().to_string();
+            assert_eq!(message, error);
+        }
+
+        // Targets that don't exist yet keep their name under the resolved directory
+        let (_, key) = resolve_object_key(&state, "../new.txt").await.unwrap();
+        assert_eq!(key, "logs/new.txt");
+        let (_, key) = resolve_object_key(&state, "/demo-bucket/a.txt")
+            .await
+            .unwrap();
+        assert_eq!(key, "a.txt");
+        assert!(resolve_object_key(&state, "..").await.is_err());
+    }
 }
@@ -0,0 +1,109 @@
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use std::process::{Command as Process, Stdio};
+use std::time::Duration;
+
+use super::output::{Output, print_line};
+use super::{Command, ShellState, resolve_object};
+use crate::shell::settings::parse_timeout;
+
+/// How long presigned URLs stay valid unless --expires says otherwise
+const DEFAULT_EXPIRY: Duration = Duration::from_secs(300);
+
+pub struct OpenCommand;
+
+#[async_trait]
+impl Command for OpenCommand {
+    fn name(&self) -> &str {
+        "open"
+    }
+
+    fn usage(&self) -> &str {
+        "open [--expires SECS] [--print] FILE - Open an object in the browser via a presigned URL"
+    }
+
+    fn flags(&self) -> &[(&str, &str)] {
+        &[
+            ("--expires SECS", "URL lifetime in seconds (default 300)"),
+            ("-p, --print", "Print the URL instead of opening a browser"),
+        ]
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        let mut expires_in = DEFAULT_EXPIRY;
+        let mut print = false;
+        let mut positional = Vec::new();
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--expires" => {
+                    let raw = iter
+                        .next()
+                        .ok_or_else(|| anyhow!("--expires requires a value"))?;
+                    expires_in = parse_timeout(raw)?
+                        .ok_or_else(|| anyhow!("--expires must be greater than zero"))?;
+                }
+                "--print" | "-p" => print = true,
+                _ => positional.push(arg),
+            }
+        }
+
+        let [path] = positional.as_slice() else {
+            return Err(anyhow!("Usage: {}", self.usage()));
+        };
+
+        let (bucket, key, _) = resolve_object(state, path).await?;
+        let url = state
+            .s3_client()
+            .presign_get_object(&bucket, &key, expires_in)
+            .await?;
+
+        if print {
+            print_line!(out, "{url}");
+            return Ok(());
+        }
+
+        let status = opener(&url)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .context("Failed to launch the default opener")?;
+        if !status.success() {
+            // Headless sessions have no browser; the URL is still useful
+            print_line!(out, "{url}");
+            return Err(anyhow!("The default opener failed; URL printed above"));
+        }
+        print_line!(
+            out,
+            "Opened {path} (link valid for {}s)",
+            expires_in.as_secs()
+        );
+
+        Ok(())
+    }
+}
+
+/// The platform's "open with the default application" command
+fn opener(url: &str) -> Process {
+    if cfg!(target_os = "macos") {
+        let mut cmd = Process::new("open");
+        cmd.arg(url);
+        cmd
+    } else if cfg!(windows) {
+        // Not `cmd /C start`, which would split the URL at each '&'
+        let mut cmd = Process::new("rundll32");
+        cmd.arg("url.dll,FileProtocolHandler").arg(url);
+        cmd
+    } else {
+        let mut cmd = Process::new("xdg-open");
+        cmd.arg(url);
+        cmd
+    }
+}
@@ -1,17 +1,119 @@
 //! Output utilities for shell commands with BrokenPipe handling.
 //!
+//! Commands write to an [`Output`] sink rather than the process's stdout, so the shell can
+//! point them at a terminal, a pipeline, a file, or an in-memory buffer.
+//!
 //! These macros handle the common case where output is piped to a command like `head`
 //! that closes the pipe early. Instead of erroring, we gracefully return Ok(()).
 
+use std::fs::File;
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::process::ChildStdin;
+
+/// Destination for a command's output
+pub enum Output {
+    /// s3sh's own stdout (usually the terminal)
+    Stdout(io::Stdout),
+    /// Stdin of the shell running the right-hand side of a pipe
+    Pipe(BufWriter<ChildStdin>),
+    /// Local file from `>` or `>>`
+    File(BufWriter<File>),
+    /// In-memory buffer (e.g. for tests)
+    Capture(Vec<u8>),
+    /// Everything written goes to the first output and is copied to the second (`| tee`)
+    Tee(Box<Output>, Box<Output>),
+}
+
+impl Output {
+    pub fn stdout() -> Self {
+        Output::Stdout(io::stdout())
+    }
+
+    pub fn pipe(stdin: ChildStdin) -> Self {
+        Output::Pipe(BufWriter::new(stdin))
+    }
+
+    pub fn file(file: File) -> Self {
+        Output::File(BufWriter::new(file))
+    }
+
+    pub fn capture() -> Self {
+        Output::Capture(Vec::new())
+    }
+
+    pub fn tee(output: Output, copy: Output) -> Self {
+        Output::Tee(Box::new(output), Box::new(copy))
+    }
+
+    /// Whether output goes straight to a terminal (not a pipe, file, or buffer)
+    pub fn is_terminal(&self) -> bool {
+        match self {
+            Output::Stdout(stdout) => stdout.is_terminal(),
+            _ => false,
+        }
+    }
+
+    /// Everything written so far, if this is a capture buffer
+    pub fn captured(&self) -> Option<&[u8]> {
+        match self {
+            Output::Capture(buf) => Some(buf),
+            _ => None,
+        }
+    }
+
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            Output::Stdout(w) => w,
+            Output::Pipe(w) => w,
+            Output::File(w) => w,
+            Output::Capture(w) => w,
+            Output::Tee(..) => unreachable!("tee writes to both of its outputs"),
+        }
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            // Partial writes would leave the two copies out of step
+            Output::Tee(..) => self.write_all(buf).map(|()| buf.len()),
+            _ => self.writer().write(buf),
+        }
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        match self {
+            Output::Tee(output, copy) => {
+                copy.write_all(buf)?;
+                output.write_all(buf)
+            }
+            _ => self.writer().write_all(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::Tee(output, copy) => {
+                copy.flush()?;
+                output.flush()
+            }
+            _ => self.writer().flush(),
+        }
+    }
+}
+
 /// Print with newline, handling BrokenPipe gracefully.
 ///
 /// Returns `Ok(())` early if BrokenPipe is encountered (e.g., when piped to `head`).
 /// Propagates other IO errors.
 #[macro_export]
 macro_rules! print_line {
-    ($($arg:tt)*) => {{
+    ($out:expr) => {
+        $crate::print_line!($out, "")
+    };
+    ($out:expr, $($arg:tt)*) => {{
         use std::io::Write;
-        match writeln!(std::io::stdout(), $($arg)*) {
+        match writeln!($out, $($arg)*) {
             Ok(()) => {}
             Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => {
                 return Ok(());
@@ -27,9 +129,27 @@ macro_rules! print_line {
 /// Propagates other IO errors.
 #[macro_export]
 macro_rules! print_str {
-    ($($arg:tt)*) => {{
+    ($out:expr, $($arg:tt)*) => {{
+        use std::io::Write;
+        match write!($out, $($arg)*) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => {
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }};
+}
+
+/// Write raw bytes, handling BrokenPipe gracefully.
+///
+/// Returns `Ok(())` early if BrokenPipe is encountered (e.g., when piped to `head`).
+/// Propagates other IO errors.
+#[macro_export]
+macro_rules! print_bytes {
+    ($out:expr, $bytes:expr) => {{
         use std::io::Write;
-        match write!(std::io::stdout(), $($arg)*) {
+        match $out.write_all($bytes) {
             Ok(()) => {}
             Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => {
                 return Ok(());
@@ -39,5 +159,38 @@ macro_rules! print_str {
     }};
 }
 
+pub use print_bytes;
 pub use print_line;
 pub use print_str;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn greet(out: &mut Output, name: &str) -> anyhow::Result<()> {
+        print_str!(out, "hello ");
+        print_line!(out, "{name}");
+        print_bytes!(out, b"bye");
+        print_line!(out);
+        Ok(())
+    }
+
+    #[test]
+    fn test_capture_output() {
+        let mut out = Output::capture();
+        greet(&mut out, "s3").unwrap();
+        assert_eq!(out.captured(), Some(b"hello s3\nbye\n".as_slice()));
+        assert!(Output::stdout().captured().is_none());
+    }
+
+    #[test]
+    fn test_tee_output() {
+        let mut out = Output::tee(Output::capture(), Output::capture());
+        greet(&mut out, "s3").unwrap();
+        let Output::Tee(output, copy) = out else {
+            unreachable!()
+        };
+        assert_eq!(output.captured(), Some(b"hello s3\nbye\n".as_slice()));
+        assert_eq!(copy.captured(), output.captured());
+    }
+}
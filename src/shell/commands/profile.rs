@@ -0,0 +1,88 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use colored::*;
+use std::sync::Arc;
+
+use super::output::{Output, print_line};
+use super::{Command, ShellState};
+use crate::providers::list_profiles;
+use crate::s3::S3Client;
+use crate::vfs::VfsNode;
+
+pub struct ProfileCommand;
+
+#[async_trait]
+impl Command for ProfileCommand {
+    fn name(&self) -> &str {
+        "profile"
+    }
+
+    fn usage(&self) -> &str {
+        "profile [NAME] - List AWS profiles or switch to one"
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        let s3_client = state.s3_client();
+        let provider_name = s3_client.provider_name().unwrap_or("aws").to_string();
+        let config = s3_client
+            .provider_config()
+            .cloned()
+            .ok_or_else(|| anyhow!("Profiles require a shell started from a provider"))?;
+
+        let current = config
+            .profile
+            .clone()
+            .or_else(|| std::env::var("AWS_PROFILE").ok())
+            .unwrap_or_else(|| "default".to_string());
+        let profiles = list_profiles();
+
+        let Some(name) = args.first() else {
+            if profiles.is_empty() {
+                print_line!(
+                    out,
+                    "(no profiles found in ~/.aws/config or ~/.aws/credentials)"
+                );
+            }
+            for profile in &profiles {
+                if *profile == current {
+                    print_line!(out, "* {}", profile.green().bold());
+                } else {
+                    print_line!(out, "  {profile}");
+                }
+            }
+            return Ok(());
+        };
+
+        if config.anonymous {
+            return Err(anyhow!(
+                "The {provider_name} provider uses anonymous access; profiles don't apply"
+            ));
+        }
+        if !profiles.contains(name) {
+            return Err(anyhow!("Unknown profile: {name}"));
+        }
+
+        let mut config = config;
+        config.profile = Some(name.clone());
+
+        let client = S3Client::from_provider(&provider_name, config).await?;
+        state.replace_client(Arc::new(client)).await;
+
+        // Buckets visible to the old profile may not exist for the new one
+        state.set_current_node(VfsNode::Root);
+
+        print_line!(
+            out,
+            "Switched to profile {} (region {})",
+            name.bold(),
+            state.s3_client().region()
+        );
+
+        Ok(())
+    }
+}
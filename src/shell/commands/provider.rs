@@ -0,0 +1,88 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use colored::*;
+use std::sync::Arc;
+
+use super::output::{Output, print_line};
+use super::{Command, ShellState};
+use crate::providers::ProviderRegistry;
+use crate::s3::S3Client;
+use crate::vfs::VfsNode;
+
+pub struct ProviderCommand;
+
+#[async_trait]
+impl Command for ProviderCommand {
+    fn name(&self) -> &str {
+        "provider"
+    }
+
+    fn usage(&self) -> &str {
+        "provider [list|use NAME] - List S3 providers and mounted buckets, or switch provider"
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        let registry = ProviderRegistry::new();
+        let current = state
+            .s3_client()
+            .provider_name()
+            .unwrap_or("aws")
+            .to_string();
+
+        match args
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .as_slice()
+        {
+            [] | ["list"] => {
+                for name in registry.list() {
+                    let description = registry.get(name).map(|p| p.description()).unwrap_or("");
+                    if name == current {
+                        print_line!(out, "* {:12} {}", name.green().bold(), description);
+                    } else {
+                        print_line!(out, "  {:12} {}", name, description);
+                    }
+                }
+                let mounts = state.s3_client().mounts();
+                if !mounts.is_empty() {
+                    print_line!(out, "");
+                    print_line!(out, "Mounted buckets:");
+                    for mount in mounts {
+                        let provider = mount.client.provider_name().unwrap_or("-");
+                        print_line!(out, "  {:12} {provider}", mount.pattern);
+                    }
+                }
+                Ok(())
+            }
+            ["use", name] => {
+                let provider = registry
+                    .get(name)
+                    .ok_or_else(|| anyhow!("Unknown provider: {name}"))?;
+                let mut config = provider.build_config().await?;
+                state.config().apply_to_provider(&mut config);
+                let endpoint = config.endpoint_url.clone();
+
+                let client = S3Client::from_provider(provider.name(), config).await?;
+                state.replace_client(Arc::new(client)).await;
+
+                // Buckets from the old provider don't exist on the new one
+                state.set_current_node(VfsNode::Root);
+
+                match endpoint {
+                    Some(endpoint) => {
+                        print_line!(out, "Switched to provider {} ({endpoint})", name.bold())
+                    }
+                    None => print_line!(out, "Switched to provider {}", name.bold()),
+                }
+                Ok(())
+            }
+            _ => Err(anyhow!("Usage: {}", self.usage())),
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use datafusion::arrow::util::pretty::pretty_format_batches;
+use std::sync::Arc;
+
+use super::output::{Output, print_line};
+use super::{Command, ShellState};
+use crate::archive::parquet::ParquetHandler;
+use crate::vfs::{ArchiveIndex, ArchiveType, VfsNode};
+
+pub struct QueryCommand;
+
+#[async_trait]
+impl Command for QueryCommand {
+    fn name(&self) -> &str {
+        "query"
+    }
+
+    fn usage(&self) -> &str {
+        "query \"SQL\" - Run SQL against the current Parquet file (table name: this)"
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        if args.is_empty() {
+            return Err(anyhow!("Usage: {}", self.usage()));
+        }
+
+        // Allow unquoted queries: query SELECT * FROM this LIMIT 5
+        let sql = args.join(" ");
+
+        let index = Self::current_parquet_index(state)?;
+        let batches = ParquetHandler::new().query(&index, &sql).await?;
+
+        if batches.iter().all(|b| b.num_rows() == 0) {
+            print_line!(out, "(0 rows)");
+            return Ok(());
+        }
+
+        print_line!(out, "{}", pretty_format_batches(&batches)?);
+
+        Ok(())
+    }
+}
+
+impl QueryCommand {
+    /// Find the index of the Parquet file the user is currently inside
+    fn current_parquet_index(state: &ShellState) -> Result<Arc<ArchiveIndex>> {
+        let mut node = state.current_node();
+
+        // Walk out of virtual column directories to the Parquet file itself
+        while let VfsNode::ArchiveEntry { archive, .. } = node {
+            node = archive;
+        }
+
+        match node {
+            VfsNode::Archive {
+                archive_type: ArchiveType::Parquet,
+                index: Some(index),
+                ..
+            } => Ok(Arc::clone(index)),
+            _ => Err(anyhow!(
+                "query must be run inside a Parquet file (cd into one first)"
+            )),
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+
+use super::output::{Output, print_line};
+use super::{Command, ShellState};
+use crate::vfs::VfsNode;
+
+pub struct RefreshCommand;
+
+#[async_trait]
+impl Command for RefreshCommand {
+    fn name(&self) -> &str {
+        "refresh"
+    }
+
+    fn usage(&self) -> &str {
+        "refresh [--archives] [PATH] - Forget cached listings under a directory and list it again, after changes made elsewhere"
+    }
+
+    fn flags(&self) -> &[(&str, &str)] {
+        &[(
+            "-a, --archives",
+            "Also drop archive indexes, so archives are indexed again when entered",
+        )]
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        let mut archives = false;
+        let mut path = None;
+        for arg in args {
+            match arg.as_str() {
+                "-a" | "--archives" => archives = true,
+                _ if path.is_none() && !arg.starts_with('-') => path = Some(arg),
+                _ => return Err(anyhow!("Usage: {}", self.usage())),
+            }
+        }
+        let node = match path {
+            None => state.current_node().clone(),
+            Some(path) => state.resolver().resolve(state.current_node(), path).await?,
+        };
+
+        // Everything cached under the directory, since any of it may have changed
+        let listings = state.s3_client().listing_cache();
+        let completions = state.completion_cache();
+        let archive_key = match &node {
+            VfsNode::Root => {
+                listings.clear();
+                completions.clear();
+                None
+            }
+            VfsNode::Bucket { name } => {
+                listings.invalidate(name, "");
+                completions.invalidate(name, "");
+                Some(format!("s3://{name}/"))
+            }
+            VfsNode::Prefix { bucket, prefix } => {
+                listings.invalidate(bucket, prefix);
+                completions.invalidate(bucket, prefix);
+                Some(format!("s3://{bucket}/{prefix}"))
+            }
+            // An archive's own listing comes from its index
+            _ => archive_object(&node).map(|(bucket, key)| format!("s3://{bucket}/{key}")),
+        };
+        let dropped = match (archives, &archive_key) {
+            (false, _) => 0,
+            (true, None) => state.cache().remove_where(|_| true),
+            (true, Some(under)) if under.ends_with('/') => state
+                .cache()
+                .remove_where(|key| key.starts_with(under.as_str())),
+            (true, Some(archive)) => state.cache().remove_where(|key| key == archive),
+        };
+
+        let count = state.completion_cache().reload(&node).await?;
+        let entries = if count == 1 { "entry" } else { "entries" };
+        let indexes = match dropped {
+            0 => String::new(),
+            1 => ", 1 archive index dropped".to_string(),
+            n => format!(", {n} archive indexes dropped"),
+        };
+        print_line!(out, "{}: {count} {entries}{indexes}", node.path());
+        Ok(())
+    }
+}
+
+/// Bucket and key of the archive a node is in
+fn archive_object(node: &VfsNode) -> Option<(&str, &str)> {
+    match node {
+        VfsNode::Archive { parent, .. } => match parent.as_ref() {
+            VfsNode::Object { bucket, key, .. } => Some((bucket, key)),
+            _ => None,
+        },
+        VfsNode::ArchiveEntry { archive, .. } => archive_object(archive),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::s3::MockS3Client;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_refresh() {
+        let client = MockS3Client::new()
+            .with_object("b", "logs/app.log", "x")
+            .with_object("b", "notes.txt", "hello");
+        let mut state = ShellState::with_client(Arc::new(client.client()))
+            .await
+            .unwrap();
+        state
+            .execute_to("cd /b/logs", &mut Output::capture())
+            .await
+            .unwrap();
+
+        let mut out = Output::capture();
+        RefreshCommand
+            .execute(&mut state, &[], &mut out)
+            .await
+            .unwrap();
+        assert_eq!(out.captured().unwrap(), b"/b/logs: 1 entry\n");
+        let entries = state.completion_cache().get_entries("/b/logs").unwrap();
+        assert_eq!(entries[0].name, "app.log");
+
+        let mut out = Output::capture();
+        RefreshCommand
+            .execute(&mut state, &["/b".to_string()], &mut out)
+            .await
+            .unwrap();
+        assert_eq!(out.captured().unwrap(), b"/b: 2 entries\n");
+    }
+
+    #[tokio::test]
+    async fn test_refresh_archives() {
+        let mut state = ShellState::with_client(Arc::new(MockS3Client::demo().client()))
+            .await
+            .unwrap();
+        let mut run = async |line: &str| {
+            let mut out = Output::capture();
+            state.execute_to(line, &mut out).await.unwrap();
+            String::from_utf8(out.captured().unwrap().to_vec()).unwrap()
+        };
+        run("cd /demo-bucket/backups/nightly.tar.gz").await;
+        run("cd /demo-bucket/logs").await;
+        assert_eq!(run("refresh ..").await, "/demo-bucket: 4 entries\n");
+        assert_eq!(
+            run("refresh --archives /demo-bucket").await,
+            "/demo-bucket: 4 entries, 1 archive index dropped\n"
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use colored::*;
+use regex::{Regex, RegexBuilder};
+
+use super::output::{Output, print_line};
+use super::{Command, ShellState};
+use crate::vfs::VfsNode;
+
+pub struct SearchCommand;
+
+#[async_trait]
+impl Command for SearchCommand {
+    fn name(&self) -> &str {
+        "search"
+    }
+
+    fn usage(&self) -> &str {
+        "search [-E|--regex] [-i] PATTERN [DIR] - Find entries in an archive by name (glob or regex)"
+    }
+
+    fn flags(&self) -> &[(&str, &str)] {
+        &[
+            ("-E, --regex", "Treat PATTERN as a regex instead of a glob"),
+            ("-i, --ignore-case", "Match case-insensitively"),
+        ]
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        let mut regex = false;
+        let mut ignore_case = false;
+        let mut positional = Vec::new();
+        for arg in args {
+            match arg.as_str() {
+                "-E" | "--regex" => regex = true,
+                "-i" | "--ignore-case" => ignore_case = true,
+                _ => positional.push(arg.as_str()),
+            }
+        }
+
+        let (pattern, dir) = match positional.as_slice() {
+            [pattern] => (*pattern, None),
+            [pattern, dir] => (*pattern, Some(*dir)),
+            _ => return Err(anyhow!("Usage: {}", self.usage())),
+        };
+        let matcher = Matcher::new(pattern, regex, ignore_case)?;
+
+        // Search the current directory, or one named by the argument
+        let resolver = state.resolver();
+        let node = match dir {
+            Some(dir) => resolver.resolve_dir(state.current_node(), dir).await?,
+            None => state.current_node().clone(),
+        };
+        let (archive, root) = match &node {
+            VfsNode::Archive { .. } => (&node, ""),
+            VfsNode::ArchiveEntry { archive, path, .. } => (archive.as_ref(), path.as_str()),
+            _ => return Err(anyhow!("search only works inside an archive")),
+        };
+
+        // The index is usually already cached, so this doesn't touch S3
+        let index = resolver.index(archive).await?;
+        let root = root.trim_end_matches('/');
+        let mut matches: Vec<_> = index
+            .entries
+            .values()
+            .filter_map(|entry| {
+                let path = entry.path.trim_end_matches('/');
+                let rel = match root {
+                    "" => path,
+                    root => path.strip_prefix(root)?.strip_prefix('/')?,
+                };
+                (!rel.is_empty() && matcher.is_match(rel)).then_some((rel, entry))
+            })
+            .collect();
+        matches.sort_by_key(|(rel, _)| *rel);
+
+        for (rel, entry) in matches {
+            if entry.is_dir {
+                print_line!(out, "{:>12}  {}", "-", format!("{rel}/").blue().bold());
+            } else {
+                let size = humansize::format_size(entry.size, humansize::BINARY);
+                print_line!(out, "{size:>12}  {rel}");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Matches entry paths (relative to the search directory) against a pattern
+/// Patterns without a '/' match the entry's name, like `find -name`; patterns with one
+/// match the whole path.
+struct Matcher {
+    regex: Regex,
+    whole_path: bool,
+}
+
+impl Matcher {
+    fn new(pattern: &str, regex: bool, ignore_case: bool) -> Result<Self> {
+        let (source, whole_path) = if regex {
+            (pattern.to_string(), pattern.contains('/'))
+        } else {
+            (
+                format!("^{}$", glob_to_regex(pattern)),
+                pattern.contains('/'),
+            )
+        };
+        let regex = RegexBuilder::new(&source)
+            .case_insensitive(ignore_case)
+            .build()
+            .map_err(|e| anyhow!("Invalid pattern: {e}"))?;
+        Ok(Matcher { regex, whole_path })
+    }
+
+    fn is_match(&self, path: &str) -> bool {
+        let target = if self.whole_path {
+            path
+        } else {
+            path.rsplit('/').next().unwrap_or(path)
+        };
+        self.regex.is_match(target)
+    }
+}
+
+/// Translate a glob (`*`, `**`, `?`) into a regular expression
+/// `*` and `?` stay within one path segment; `**` crosses segments.
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::new();
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                regex.push_str(".*");
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matcher() {
+        let name = Matcher::new("*.csv", false, false).unwrap();
+        assert!(name.is_match("data/2024/a.csv"));
+        assert!(!name.is_match("data/a.csv.gz"));
+
+        let path = Matcher::new("data/*/a.csv", false, false).unwrap();
+        assert!(path.is_match("data/2024/a.csv"));
+        assert!(!path.is_match("data/2024/01/a.csv"));
+        assert!(
+            Matcher::new("data/**.csv", false, false)
+                .unwrap()
+                .is_match("data/2024/01/a.csv")
+        );
+
+        let regex = Matcher::new(r"^part-\d+", true, true).unwrap();
+        assert!(regex.is_match("out/PART-0001.parquet"));
+        assert!(!regex.is_match("out/summary.txt"));
+        assert!(Matcher::new("(", true, false).is_err());
+    }
+}
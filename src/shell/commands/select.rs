@@ -0,0 +1,117 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+
+use super::output::{Output, print_bytes};
+use super::{Command, ShellState, resolve_object};
+use crate::s3::{SelectEvent, SelectFormat, SelectRequest};
+
+pub struct SelectCommand;
+
+#[async_trait]
+impl Command for SelectCommand {
+    fn name(&self) -> &str {
+        "select"
+    }
+
+    fn usage(&self) -> &str {
+        "select [--format csv|tsv|json|jsonl|parquet] [--no-header] [--json] \"SQL\" FILE - Query an object with S3 Select"
+    }
+
+    fn flags(&self) -> &[(&str, &str)] {
+        &[
+            (
+                "-f, --format csv|tsv|json|jsonl|parquet",
+                "Input format (guessed from the extension)",
+            ),
+            ("--no-header", "CSV or TSV input has no header row"),
+            ("--json", "Print records as JSON"),
+            ("--csv", "Print records as CSV"),
+        ]
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        let mut format: Option<SelectFormat> = None;
+        let mut csv_header = true;
+        let mut output_json: Option<bool> = None;
+        let mut positional = Vec::new();
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--format" | "-f" => {
+                    let name = iter
+                        .next()
+                        .ok_or_else(|| anyhow!("--format requires a value"))?;
+                    format = Some(
+                        SelectFormat::parse(name)
+                            .ok_or_else(|| anyhow!("Unknown input format: {name}"))?,
+                    );
+                }
+                "--no-header" => csv_header = false,
+                "--json" => output_json = Some(true),
+                "--csv" => output_json = Some(false),
+                _ => positional.push(arg),
+            }
+        }
+
+        let (expression, path) = match positional.as_slice() {
+            [expression, path] => (expression.to_string(), path.as_str()),
+            _ => return Err(anyhow!("Usage: {}", self.usage())),
+        };
+
+        let (bucket, key, _) = resolve_object(state, path).await?;
+
+        let input_format = match format.or_else(|| SelectFormat::from_key(&key)) {
+            Some(f) => f,
+            None => {
+                return Err(anyhow!(
+                    "Cannot detect format of {key}; pass --format csv|tsv|json|jsonl|parquet"
+                ));
+            }
+        };
+
+        // Default to echoing the input style: JSON in, JSON out
+        let output_json = output_json.unwrap_or(matches!(
+            input_format,
+            SelectFormat::Json | SelectFormat::JsonLines
+        ));
+
+        let request = SelectRequest {
+            expression,
+            input_format,
+            csv_header,
+            output_json,
+        };
+
+        let mut stream = state
+            .s3_client()
+            .select_object_content(&bucket, &key, &request)
+            .await?;
+
+        while let Some(event) = stream.next_event().await? {
+            match event {
+                SelectEvent::Records(bytes) => print_bytes!(out, &bytes),
+                SelectEvent::Stats {
+                    bytes_scanned,
+                    bytes_processed,
+                    bytes_returned,
+                } => {
+                    eprintln!(
+                        "Scanned {}, processed {}, returned {}",
+                        humansize::format_size(bytes_scanned, humansize::BINARY),
+                        humansize::format_size(bytes_processed, humansize::BINARY),
+                        humansize::format_size(bytes_returned, humansize::BINARY)
+                    );
+                }
+                SelectEvent::End => break,
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+
+use super::output::{Output, print_line};
+use super::{Command, ShellState};
+use crate::shell::settings::{Settings, format_bool, parse_bool};
+
+pub struct SetCommand;
+
+#[async_trait]
+impl Command for SetCommand {
+    fn name(&self) -> &str {
+        "set"
+    }
+
+    fn usage(&self) -> &str {
+        "set [KEY [VALUE]] - Show or change shell settings"
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        match args {
+            [] => {
+                for key in Settings::KEYS {
+                    print_line!(out, "{key} = {}", state.settings().get(key)?);
+                }
+                print_line!(out, "anonymous = {}", format_bool(state.anonymous()));
+            }
+            // Anonymous access belongs to the client, which has to be rebuilt to change it
+            [key] if key == "anonymous" => {
+                print_line!(out, "{key} = {}", format_bool(state.anonymous()));
+            }
+            [key, value] if key == "anonymous" => {
+                state.set_anonymous(parse_bool(value)?).await?;
+            }
+            [key] => {
+                print_line!(out, "{key} = {}", state.settings().get(key)?);
+            }
+            [key, value] => {
+                state.settings_mut().set(key, value)?;
+            }
+            _ => return Err(anyhow!("Usage: set [KEY [VALUE]]")),
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,213 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use serde_json::json;
+
+use super::output::{Output, print_line};
+use super::{Command, ShellState, resolve_object, resolve_object_key};
+use crate::archive::{ArchiveFormat, format_for_name, zip};
+use crate::vfs::{ArchiveEntry, VfsNode};
+
+pub struct StatCommand;
+
+#[async_trait]
+impl Command for StatCommand {
+    fn name(&self) -> &str {
+        "stat"
+    }
+
+    fn usage(&self) -> &str {
+        "stat [--version-id ID] [--json] FILE - Show object metadata"
+    }
+
+    fn flags(&self) -> &[(&str, &str)] {
+        &[
+            ("--version-id ID", "Show an older version of the object"),
+            ("--json", "Print the metadata as JSON"),
+        ]
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        let mut version_id: Option<&String> = None;
+        let mut json = false;
+        let mut positional = Vec::new();
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--version-id" => {
+                    version_id = Some(
+                        iter.next()
+                            .ok_or_else(|| anyhow!("--version-id requires a value"))?,
+                    )
+                }
+                "--json" => json = true,
+                _ => positional.push(arg),
+            }
+        }
+
+        let [path] = positional.as_slice() else {
+            return Err(anyhow!("Usage: {}", self.usage()));
+        };
+
+        // Files inside archives are described from the archive's index
+        if Self::in_archive(state, path) {
+            if version_id.is_some() {
+                return Err(anyhow!("--version-id only applies to S3 objects"));
+            }
+            return Self::stat_entry(state, out, path, json).await;
+        }
+
+        // An older version may be all that's left of a deleted object
+        let (bucket, key) = match version_id {
+            Some(_) => resolve_object_key(state, path).await?,
+            None => {
+                let (bucket, key, _) = resolve_object(state, path).await?;
+                (bucket, key)
+            }
+        };
+        let metadata = state
+            .s3_client()
+            .head_object_version(&bucket, &key, version_id.map(String::as_str))
+            .await?;
+
+        if json {
+            let record = json!({
+                "bucket": bucket,
+                "key": key,
+                "size": metadata.size,
+                "last_modified": metadata.last_modified,
+                "content_type": metadata.content_type,
+                "etag": metadata.etag,
+                "version_id": metadata.version_id,
+                "storage_class": metadata.storage_class,
+                "metadata": metadata.user_metadata,
+            });
+            print_line!(out, "{record}");
+            return Ok(());
+        }
+
+        print_line!(out, "  Object: s3://{bucket}/{key}");
+        print_line!(
+            out,
+            "    Size: {} ({} bytes)",
+            humansize::format_size(metadata.size, humansize::BINARY),
+            metadata.size
+        );
+        print_line!(
+            out,
+            "Modified: {}",
+            metadata.last_modified.as_deref().unwrap_or("-")
+        );
+        print_line!(
+            out,
+            "    Type: {}",
+            metadata.content_type.as_deref().unwrap_or("-")
+        );
+        print_line!(out, "    ETag: {}", metadata.etag.as_deref().unwrap_or("-"));
+        print_line!(
+            out,
+            " Version: {}",
+            metadata
+                .version_id
+                .as_deref()
+                .unwrap_or("- (versioning not enabled)")
+        );
+
+        Ok(())
+    }
+}
+
+impl StatCommand {
+    /// Whether a path leads into an archive: it's relative to a directory inside one,
+    /// or runs through an archive file
+    fn in_archive(state: &ShellState, path: &str) -> bool {
+        let from_archive = !path.starts_with('/')
+            && matches!(
+                state.current_node(),
+                VfsNode::Archive { .. } | VfsNode::ArchiveEntry { .. }
+            );
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        from_archive
+            || segments
+                .iter()
+                .rev()
+                .skip(1)
+                .any(|segment| format_for_name(segment).is_some_and(ArchiveFormat::is_navigable))
+    }
+
+    /// Show the size, time, permissions, and owner an archive records for an entry
+    async fn stat_entry(
+        state: &ShellState,
+        out: &mut Output,
+        path: &str,
+        json: bool,
+    ) -> Result<()> {
+        let resolver = state.resolver();
+        let node = resolver.resolve(state.current_node(), path).await?;
+        let VfsNode::ArchiveEntry {
+            archive,
+            path: entry_path,
+            size,
+            is_dir,
+        } = &node
+        else {
+            return Err(anyhow!("Not a file in an archive: {path}"));
+        };
+        let index = resolver.index(archive).await?;
+        let entry = index.find_entry(entry_path);
+        let attrs = entry.map(|entry| entry.attrs.clone()).unwrap_or_default();
+        let compression = entry.and_then(ArchiveEntry::compression);
+
+        if json {
+            let record = json!({
+                "path": node.path(),
+                "size": size,
+                "is_dir": is_dir,
+                "modified": attrs.modified(),
+                "mode": attrs.mode.map(|mode| format!("{mode:o}")),
+                "owner": attrs.owner(),
+                "compressed_size": compression.map(|(packed, _)| packed),
+                "compression": compression.map(|(_, method)| zip::method_name(method)),
+            });
+            print_line!(out, "{record}");
+            return Ok(());
+        }
+
+        print_line!(out, "   Entry: {}", node.path());
+        print_line!(
+            out,
+            "    Size: {} ({size} bytes)",
+            humansize::format_size(*size, humansize::BINARY)
+        );
+        if let Some((packed, method)) = compression {
+            print_line!(
+                out,
+                "  Packed: {} ({packed} bytes, {} saved)",
+                humansize::format_size(packed, humansize::BINARY),
+                zip::space_saved(*size, packed)
+            );
+            print_line!(out, "  Method: {}", zip::method_name(method));
+        }
+        print_line!(
+            out,
+            "Modified: {}",
+            attrs.modified().as_deref().unwrap_or("-")
+        );
+        print_line!(
+            out,
+            "    Mode: {}",
+            match (attrs.permissions(*is_dir), attrs.mode) {
+                (Some(text), Some(mode)) => format!("{text} ({mode:04o})"),
+                _ => "-".to_string(),
+            }
+        );
+        print_line!(out, "   Owner: {}", attrs.owner().as_deref().unwrap_or("-"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use futures::TryStreamExt;
+
+use super::output::{Output, print_line};
+use super::{Command, ShellState};
+use crate::vfs::VfsNode;
+
+/// Shortest run printed by default, as in GNU strings
+const DEFAULT_MIN_LEN: usize = 4;
+
+pub struct StringsCommand;
+
+#[async_trait]
+impl Command for StringsCommand {
+    fn name(&self) -> &str {
+        "strings"
+    }
+
+    fn usage(&self) -> &str {
+        "strings [-n MINLEN] FILE - Print printable text runs in a binary object"
+    }
+
+    fn flags(&self) -> &[(&str, &str)] {
+        &[("-n, --bytes MINLEN", "Shortest run to print (default 4)")]
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        let mut min_len = DEFAULT_MIN_LEN;
+        let mut paths = Vec::new();
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "-n" | "--bytes" => {
+                    min_len = iter
+                        .next()
+                        .ok_or_else(|| anyhow!("{arg} requires a value"))?
+                        .parse()
+                        .ok()
+                        .filter(|&n| n > 0)
+                        .ok_or_else(|| anyhow!("{arg} expects a positive number"))?;
+                }
+                _ => paths.push(arg),
+            }
+        }
+
+        let [path] = paths.as_slice() else {
+            return Err(anyhow!("Usage: {}", self.usage()));
+        };
+
+        let (bucket, key, size) = match state.resolver().resolve(state.current_node(), path).await?
+        {
+            VfsNode::Object {
+                bucket, key, size, ..
+            } => (bucket, key, size),
+            VfsNode::Prefix { .. } => return Err(anyhow!("File not found: {path}")),
+            _ => return Err(anyhow!("Not an S3 object: {path}")),
+        };
+
+        // Stream chunk by chunk; runs that span chunk boundaries are carried over
+        let mut chunks = state
+            .s3_client()
+            .get_object_chunks(&bucket, &key, Some(size))
+            .await?;
+        let mut scanner = StringScanner::new(min_len);
+        while let Some(chunk) = chunks.try_next().await? {
+            for text in scanner.feed(&chunk) {
+                // Returning on BrokenPipe drops the stream, ending the download early
+                print_line!(out, "{text}");
+            }
+        }
+        if let Some(text) = scanner.finish() {
+            print_line!(out, "{text}");
+        }
+
+        Ok(())
+    }
+}
+
+/// Finds runs of printable ASCII and UTF-8 text in a byte stream
+struct StringScanner {
+    min_len: usize,
+    /// Bytes of the current run
+    run: Vec<u8>,
+    /// Characters in the current run
+    chars: usize,
+    /// Start of an incomplete UTF-8 sequence in `run`, and how many bytes it still needs
+    partial: Option<(usize, usize)>,
+}
+
+impl StringScanner {
+    fn new(min_len: usize) -> Self {
+        StringScanner {
+            min_len,
+            run: Vec::new(),
+            chars: 0,
+            partial: None,
+        }
+    }
+
+    /// Scan the next chunk, returning the runs it completed
+    fn feed(&mut self, chunk: &[u8]) -> Vec<String> {
+        let mut found = Vec::new();
+        for &byte in chunk {
+            if let Some((start, needed)) = self.partial {
+                if byte & 0xc0 == 0x80 {
+                    self.run.push(byte);
+                    if needed > 1 {
+                        self.partial = Some((start, needed - 1));
+                        continue;
+                    }
+                    self.partial = None;
+                    // Complete sequences that decode to control characters end the run
+                    let printable = std::str::from_utf8(&self.run[start..])
+                        .ok()
+                        .and_then(|s| s.chars().next())
+                        .is_some_and(|c| !c.is_control());
+                    if printable {
+                        self.chars += 1;
+                    } else {
+                        self.run.truncate(start);
+                        found.extend(self.end_run());
+                    }
+                    continue;
+                }
+                // Broken sequence: drop it and look at this byte fresh
+                self.run.truncate(start);
+                self.partial = None;
+                found.extend(self.end_run());
+            }
+
+            match byte {
+                b' '..=b'~' | b'\t' => {
+                    self.run.push(byte);
+                    self.chars += 1;
+                }
+                // Lead bytes of 2, 3, and 4 byte sequences
+                0xc2..=0xf4 => {
+                    let needed = match byte {
+                        0xc2..=0xdf => 1,
+                        0xe0..=0xef => 2,
+                        _ => 3,
+                    };
+                    self.partial = Some((self.run.len(), needed));
+                    self.run.push(byte);
+                }
+                _ => found.extend(self.end_run()),
+            }
+        }
+        found
+    }
+
+    /// Flush the run still open at the end of the stream
+    fn finish(&mut self) -> Option<String> {
+        if let Some((start, _)) = self.partial.take() {
+            self.run.truncate(start);
+        }
+        self.end_run()
+    }
+
+    fn end_run(&mut self) -> Option<String> {
+        let long_enough = self.chars >= self.min_len;
+        self.chars = 0;
+        let run = std::mem::take(&mut self.run);
+        long_enough.then(|| String::from_utf8_lossy(&run).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(chunks: &[&[u8]], min_len: usize) -> Vec<String> {
+        let mut scanner = StringScanner::new(min_len);
+        let mut found: Vec<String> = chunks.iter().flat_map(|c| scanner.feed(c)).collect();
+        found.extend(scanner.finish());
+        found
+    }
+
+    #[test]
+    fn test_string_scanner() {
+        assert_eq!(
+            scan(&[b"\x00\x01hello\x00ab\x00world\tfoo\xff"], 4),
+            vec!["hello", "world\tfoo"]
+        );
+        // Runs and UTF-8 sequences split across chunks
+        assert_eq!(
+            scan(&[b"\x00caf\xc3", b"\xa9 au lait\x00xy"], 4),
+            vec!["caf\u{e9} au lait"]
+        );
+        assert_eq!(scan(&[b"abc\x00", b"abcd"], 4), vec!["abcd"]);
+        assert_eq!(scan(&[b"ab\x00abc"], 2), vec!["ab", "abc"]);
+        // A lead byte without its continuation ends the run
+        assert_eq!(scan(&[b"text\xe2\x82more"], 4), vec!["text", "more"]);
+        // C1 control characters aren't printable
+        assert_eq!(scan(&[b"left\xc2\x85right"], 4), vec!["left", "right"]);
+    }
+}
@@ -0,0 +1,486 @@
+use anyhow::{Context, Result, anyhow};
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::checksum::local_file_matches_etag;
+use super::output::{Output, print_line};
+use super::{Command, ShellState, local_path_under};
+use crate::s3::S3Client;
+use crate::ui::{create_progress_bar, create_spinner};
+
+/// Transfers running at once unless --concurrency says otherwise
+const DEFAULT_CONCURRENCY: usize = 8;
+
+pub struct SyncCommand;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Direction {
+    Upload,
+    Download,
+}
+
+/// A file on either side, keyed by its path relative to the sync root ("a/b.txt")
+#[derive(Debug, Clone)]
+struct Entry {
+    size: u64,
+    /// S3 ETag (remote side only)
+    etag: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Action {
+    Copy(String),
+    Delete(String),
+}
+
+#[async_trait]
+impl Command for SyncCommand {
+    fn name(&self) -> &str {
+        "sync"
+    }
+
+    fn usage(&self) -> &str {
+        "sync [--delete] [--dry-run] [--concurrency N] SRC DST - Sync a local directory with an s3:// prefix (either way)"
+    }
+
+    fn flags(&self) -> &[(&str, &str)] {
+        &[
+            ("--delete", "Remove files in DST that aren't in SRC"),
+            ("-n, --dry-run", "Show what would be copied or deleted"),
+            ("-j, --concurrency N", "Parallel transfers (default 8)"),
+        ]
+    }
+
+    fn keeps_s3_urls(&self) -> bool {
+        true
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        let mut delete = false;
+        let mut dry_run = false;
+        let mut concurrency = DEFAULT_CONCURRENCY;
+        let mut positional = Vec::new();
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--delete" => delete = true,
+                "--dry-run" | "-n" => dry_run = true,
+                "--concurrency" | "-j" => {
+                    concurrency = iter
+                        .next()
+                        .and_then(|n| n.parse().ok())
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| anyhow!("--concurrency requires a positive number"))?;
+                }
+                _ => positional.push(arg.as_str()),
+            }
+        }
+
+        let [src, dst] = positional.as_slice() else {
+            return Err(anyhow!("Usage: {}", self.usage()));
+        };
+        let (direction, local, url) = match (src.strip_prefix("s3://"), dst.strip_prefix("s3://")) {
+            (None, Some(url)) => (Direction::Upload, *src, url),
+            (Some(url), None) => (Direction::Download, *dst, url),
+            _ => {
+                return Err(anyhow!(
+                    "One side of sync must be a local directory and the other an s3:// URL"
+                ));
+            }
+        };
+
+        let (bucket, prefix) = url.split_once('/').unwrap_or((url, ""));
+        if bucket.is_empty() {
+            return Err(anyhow!("Invalid S3 URL: s3://{url}"));
+        }
+        let prefix = match prefix.trim_matches('/') {
+            "" => String::new(),
+            prefix => format!("{prefix}/"),
+        };
+        let root = PathBuf::from(local);
+        if direction == Direction::Upload && !root.is_dir() {
+            return Err(anyhow!("Not a directory: {local}"));
+        }
+        if direction == Direction::Upload && !dry_run {
+            state
+                .s3_client()
+                .check_writable("sync to", bucket, &prefix)
+                .map_err(|e| anyhow!("{e}; --dry-run shows what would change"))?;
+        }
+
+        let client = Arc::clone(state.s3_client());
+
+        let spinner = create_spinner("Comparing...");
+        let planned = async {
+            let local_entries = walk_local(&root)?;
+            let remote_entries = list_remote(&client, bucket, &prefix).await?;
+            let planned = plan(direction, &root, &local_entries, &remote_entries, delete).await?;
+            Ok::<_, anyhow::Error>((planned, local_entries, remote_entries))
+        }
+        .await;
+        spinner.finish_and_clear();
+        let ((actions, unchanged), local_entries, remote_entries) = planned?;
+
+        let local_path = |rel: &str| {
+            rel.split('/')
+                .fold(root.clone(), |path, part| path.join(part))
+        };
+        let remote_url = |rel: &str| format!("s3://{bucket}/{prefix}{rel}");
+        let describe = |action: &Action| match (action, direction) {
+            (Action::Copy(rel), Direction::Upload) => format!(
+                "upload: {} to {}",
+                local_path(rel).display(),
+                remote_url(rel)
+            ),
+            (Action::Copy(rel), Direction::Download) => format!(
+                "download: {} to {}",
+                remote_url(rel),
+                local_path(rel).display()
+            ),
+            (Action::Delete(rel), Direction::Upload) => format!("delete: {}", remote_url(rel)),
+            (Action::Delete(rel), Direction::Download) => {
+                format!("delete: {}", local_path(rel).display())
+            }
+        };
+
+        if dry_run {
+            for action in &actions {
+                print_line!(out, "(dry run) {}", describe(action));
+            }
+            print_line!(
+                out,
+                "{} to transfer, {} to delete, {unchanged} unchanged",
+                actions
+                    .iter()
+                    .filter(|a| matches!(a, Action::Copy(_)))
+                    .count(),
+                actions
+                    .iter()
+                    .filter(|a| matches!(a, Action::Delete(_)))
+                    .count(),
+            );
+            return Ok(());
+        }
+
+        // Build the transfers up front: a closure inside the stream trips up async_trait's Send check
+        let transfers: Vec<_> = actions
+            .iter()
+            .map(|action| {
+                let client = Arc::clone(&client);
+                let bucket = bucket.to_string();
+                let (Action::Copy(rel) | Action::Delete(rel)) = action;
+                let path = local_path(rel);
+                let key = format!("{prefix}{rel}");
+                let size = remote_entries.get(rel).map(|entry| entry.size);
+                async move {
+                    let bytes = match (action, direction) {
+                        (Action::Copy(_), Direction::Upload) => {
+                            client.upload_file(&bucket, &key, &path).await?;
+                            std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0)
+                        }
+                        (Action::Copy(_), Direction::Download) => {
+                            client.download_file(&bucket, &key, size, &path).await?
+                        }
+                        (Action::Delete(_), Direction::Upload) => {
+                            client.delete_object(&bucket, &key).await?;
+                            0
+                        }
+                        (Action::Delete(_), Direction::Download) => {
+                            tokio::fs::remove_file(&path)
+                                .await
+                                .with_context(|| format!("Failed to delete {}", path.display()))?;
+                            0
+                        }
+                    };
+                    Ok::<_, anyhow::Error>((action, bytes))
+                }
+            })
+            .collect();
+        let mut results = stream::iter(transfers).buffer_unordered(concurrency);
+
+        let source_entries = match direction {
+            Direction::Upload => &local_entries,
+            Direction::Download => &remote_entries,
+        };
+        let progress = create_progress_bar(
+            "Syncing",
+            actions
+                .iter()
+                .filter_map(|action| match action {
+                    Action::Copy(rel) => source_entries.get(rel).map(|entry| entry.size),
+                    Action::Delete(_) => None,
+                })
+                .sum(),
+            client.metrics(),
+            out.is_terminal(),
+        );
+
+        let (mut copied, mut deleted, mut bytes) = (0, 0, 0);
+        let mut failures: Vec<anyhow::Error> = Vec::new();
+        while let Some(result) = results.next().await {
+            match result {
+                Ok((action, size)) => {
+                    match action {
+                        Action::Copy(_) => copied += 1,
+                        Action::Delete(_) => deleted += 1,
+                    }
+                    bytes += size;
+                    // A closed pipe only stops the listing; the transfers carry on
+                    progress.suspend(|| -> Result<()> {
+                        print_line!(out, "{}", describe(action));
+                        Ok(())
+                    })?;
+                }
+                Err(e) => failures.push(e),
+            }
+        }
+        progress.finish_and_clear();
+        if direction == Direction::Upload && copied + deleted > 0 {
+            state.completion_cache().invalidate(bucket, &prefix);
+        }
+
+        print_line!(
+            out,
+            "{copied} transferred ({}), {deleted} deleted, {unchanged} unchanged",
+            humansize::format_size(bytes, humansize::BINARY)
+        );
+
+        match failures.first() {
+            None => Ok(()),
+            Some(first) => Err(anyhow!(
+                "{} of {} operations failed (first: {first:#})",
+                failures.len(),
+                actions.len()
+            )),
+        }
+    }
+}
+
+/// Every file below a local directory (a missing directory is empty)
+fn walk_local(root: &Path) -> Result<BTreeMap<String, Entry>> {
+    let mut entries = BTreeMap::new();
+    if !root.exists() {
+        return Ok(entries);
+    }
+
+    let mut dirs = vec![(root.to_path_buf(), String::new())];
+    while let Some((dir, rel_dir)) = dirs.pop() {
+        let listing =
+            std::fs::read_dir(&dir).with_context(|| format!("Cannot read {}", dir.display()))?;
+        for item in listing {
+            let item = item.with_context(|| format!("Cannot read {}", dir.display()))?;
+            let name = item.file_name().to_string_lossy().into_owned();
+            let rel = format!("{rel_dir}{name}");
+            // Follows symlinks, like a plain copy would
+            let metadata = std::fs::metadata(item.path())
+                .with_context(|| format!("Cannot read {}", item.path().display()))?;
+            if metadata.is_dir() {
+                dirs.push((item.path(), format!("{rel}/")));
+            } else if metadata.is_file() {
+                entries.insert(
+                    rel,
+                    Entry {
+                        size: metadata.len(),
+                        etag: None,
+                    },
+                );
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Every object below a prefix, keyed relative to it
+/// "Folder" marker objects and keys that don't map to a local path are skipped.
+async fn list_remote(
+    client: &S3Client,
+    bucket: &str,
+    prefix: &str,
+) -> Result<BTreeMap<String, Entry>> {
+    Ok(client
+        .list_objects_recursive(bucket, prefix)
+        .await?
+        .into_iter()
+        .filter_map(|obj| {
+            let rel = obj.key.strip_prefix(prefix)?;
+            // Keys like "../x" can't be mirrored locally without escaping the directory
+            if rel.ends_with('/') || local_path_under(Path::new("."), rel).is_none() {
+                return None;
+            }
+            Some((
+                rel.to_string(),
+                Entry {
+                    size: obj.size,
+                    etag: obj.etag,
+                },
+            ))
+        })
+        .collect())
+}
+
+/// Decide what to copy and delete, returning the actions and the number of unchanged files
+/// Files of equal size are compared by ETag against the local content; when the ETag can't be
+/// reproduced (an unknown multipart part size) equal sizes count as unchanged.
+async fn plan(
+    direction: Direction,
+    root: &Path,
+    local: &BTreeMap<String, Entry>,
+    remote: &BTreeMap<String, Entry>,
+    delete: bool,
+) -> Result<(Vec<Action>, usize)> {
+    let (source, dest) = match direction {
+        Direction::Upload => (local, remote),
+        Direction::Download => (remote, local),
+    };
+    let mut actions = Vec::new();
+    let mut unchanged = 0;
+
+    for (rel, entry) in source {
+        let changed = match dest.get(rel) {
+            None => true,
+            Some(other) if other.size != entry.size => true,
+            Some(_) => match remote.get(rel).and_then(|e| e.etag.as_deref()) {
+                Some(etag) => {
+                    let path = rel.split('/').fold(root.to_path_buf(), |p, s| p.join(s));
+                    local_file_matches_etag(&path, entry.size, etag).await? == Some(false)
+                }
+                None => false,
+            },
+        };
+        if changed {
+            actions.push(Action::Copy(rel.clone()));
+        } else {
+            unchanged += 1;
+        }
+    }
+
+    if delete {
+        actions.extend(
+            dest.keys()
+                .filter(|rel| !source.contains_key(*rel))
+                .map(|rel| Action::Delete(rel.clone())),
+        );
+    }
+
+    Ok((actions, unchanged))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(size: u64, etag: Option<&str>) -> Entry {
+        Entry {
+            size,
+            etag: etag.map(String::from),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_plan_upload() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("same.txt"), "hello world").unwrap();
+        std::fs::write(dir.path().join("edited.txt"), "hello there").unwrap();
+        std::fs::write(dir.path().join("sub").join("new.txt"), "new").unwrap();
+
+        let local = walk_local(dir.path()).unwrap();
+        assert_eq!(
+            local.keys().collect::<Vec<_>>(),
+            ["edited.txt", "same.txt", "sub/new.txt"]
+        );
+
+        let md5 = "\"5eb63bbbe01eeed093cb22bb8f5acdc3\"";
+        let remote = BTreeMap::from([
+            ("same.txt".to_string(), entry(11, Some(md5))),
+            ("edited.txt".to_string(), entry(11, Some(md5))),
+            ("gone.txt".to_string(), entry(1, None)),
+        ]);
+
+        let (actions, unchanged) = plan(Direction::Upload, dir.path(), &local, &remote, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            actions,
+            [
+                Action::Copy("edited.txt".to_string()),
+                Action::Copy("sub/new.txt".to_string())
+            ]
+        );
+        assert_eq!(unchanged, 1);
+
+        let (actions, _) = plan(Direction::Upload, dir.path(), &local, &remote, true)
+            .await
+            .unwrap();
+        assert_eq!(
+            actions.last(),
+            Some(&Action::Delete("gone.txt".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_plan_download_into_missing_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("missing");
+        let local = walk_local(&root).unwrap();
+        let remote = BTreeMap::from([("a/b.txt".to_string(), entry(3, Some("\"x\"")))]);
+
+        let (actions, unchanged) = plan(Direction::Download, &root, &local, &remote, true)
+            .await
+            .unwrap();
+        assert_eq!(actions, [Action::Copy("a/b.txt".to_string())]);
+        assert_eq!(unchanged, 0);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_and_read_only() {
+        let mock = crate::s3::MockS3Client::new().with_object("b", "gone.txt", "old");
+        let mut state = ShellState::with_client(Arc::new(mock.client()))
+            .await
+            .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("new.txt"), "new").unwrap();
+        let line = format!("sync --delete {} s3://b/", dir.path().display());
+        let writes = |mock: &crate::s3::MockS3Client| {
+            mock.take_requests()
+                .into_iter()
+                .filter(|request| matches!(request.method.as_str(), "PUT" | "DELETE" | "POST"))
+                .count()
+        };
+
+        // A dry run plans the upload and delete without sending either
+        let mut out = Output::capture();
+        state
+            .execute_to(&format!("{line} --dry-run"), &mut out)
+            .await
+            .unwrap();
+        let printed = String::from_utf8(out.captured().unwrap().to_vec()).unwrap();
+        assert!(printed.contains("(dry run) upload:"));
+        assert!(printed.contains("(dry run) delete: s3://b/gone.txt"));
+        assert_eq!(writes(&mock), 0);
+
+        // Read-only mode refuses the real thing before comparing anything
+        state
+            .execute_to("set readonly on", &mut Output::capture())
+            .await
+            .unwrap();
+        let err = state
+            .execute_to(&line, &mut Output::capture())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().starts_with("Read-only mode"));
+        assert_eq!(writes(&mock), 0);
+        assert!(mock.object("b", "gone.txt").is_some());
+        assert!(mock.object("b", "new.txt").is_none());
+    }
+}
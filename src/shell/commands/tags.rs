@@ -0,0 +1,61 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use serde_json::{Map, Value};
+
+use super::output::{Output, print_line};
+use super::{Command, ShellState, resolve_object};
+
+pub struct TagsCommand;
+
+#[async_trait]
+impl Command for TagsCommand {
+    fn name(&self) -> &str {
+        "tags"
+    }
+
+    fn usage(&self) -> &str {
+        "tags [--json] FILE - Show object tags"
+    }
+
+    fn flags(&self) -> &[(&str, &str)] {
+        &[("--json", "Print the tags as JSON")]
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        let json = args.iter().any(|a| a == "--json");
+        let args: Vec<&String> = args.iter().filter(|a| *a != "--json").collect();
+        let [path] = args.as_slice() else {
+            return Err(anyhow!("Usage: {}", self.usage()));
+        };
+
+        let (bucket, key, _) = resolve_object(state, path).await?;
+        let tags = state.s3_client().get_object_tags(&bucket, &key).await?;
+
+        if json {
+            // Tags as one object, e.g. {"env":"prod","team":"data"}
+            let record: Map<String, Value> = tags
+                .into_iter()
+                .map(|(tag, value)| (tag, Value::String(value)))
+                .collect();
+            print_line!(out, "{}", Value::Object(record));
+            return Ok(());
+        }
+
+        if tags.is_empty() {
+            print_line!(out, "(no tags)");
+            return Ok(());
+        }
+
+        let width = tags.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
+        for (tag, value) in &tags {
+            print_line!(out, "{tag:<width$}  {value}");
+        }
+
+        Ok(())
+    }
+}
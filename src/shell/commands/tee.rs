@@ -0,0 +1,124 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+
+use super::output::Output;
+use super::{Command, ShellState};
+
+/// `CMD | tee FILE` copies CMD's output to FILE as it's printed
+/// The shell handles the pipe itself (see `ShellState::execute_with_tee`); run on its own,
+/// tee has nothing to read.
+pub struct TeeCommand;
+
+/// Where `tee` writes its copy
+#[derive(Debug, PartialEq)]
+pub(crate) struct TeeOptions {
+    pub path: String,
+    /// Append to a local file instead of truncating it
+    pub append: bool,
+    /// FILE is an S3 path (`--remote`, or an `s3://` URL), uploaded once the command finishes
+    pub remote: bool,
+}
+
+impl TeeOptions {
+    pub(crate) fn parse(args: &[String]) -> Result<Self> {
+        let mut append = false;
+        let mut remote = false;
+        let mut paths = Vec::new();
+        for arg in args {
+            match arg.as_str() {
+                "-a" | "--append" => append = true,
+                "--remote" => remote = true,
+                _ => paths.push(arg),
+            }
+        }
+
+        let [path] = paths.as_slice() else {
+            return Err(anyhow!("Usage: {}", TeeCommand.usage()));
+        };
+        // The shell hands tee its arguments as written, so s3:// URLs arrive untranslated
+        let path = match path.strip_prefix("s3://") {
+            Some(rest) => {
+                remote = true;
+                format!("/{rest}")
+            }
+            None => path.to_string(),
+        };
+        if append && remote {
+            return Err(anyhow!("--append can't be combined with --remote"));
+        }
+        Ok(TeeOptions {
+            path,
+            append,
+            remote,
+        })
+    }
+}
+
+#[async_trait]
+impl Command for TeeCommand {
+    fn name(&self) -> &str {
+        "tee"
+    }
+
+    fn usage(&self) -> &str {
+        "tee [-a|--append] [--remote] FILE - Copy piped output (CMD | tee FILE) to a local file or S3 object"
+    }
+
+    fn flags(&self) -> &[(&str, &str)] {
+        &[
+            ("-a, --append", "Append to FILE instead of overwriting it"),
+            (
+                "--remote",
+                "FILE is an S3 path; the copy is uploaded when CMD finishes",
+            ),
+        ]
+    }
+
+    async fn execute(
+        &self,
+        _state: &mut ShellState,
+        _args: &[String],
+        _out: &mut Output,
+    ) -> Result<()> {
+        Err(anyhow!(
+            "tee copies another command's output; use it after a pipe: CMD | tee FILE"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            TeeOptions::parse(&args(&["-a", "out.txt"])).unwrap(),
+            TeeOptions {
+                path: "out.txt".to_string(),
+                append: true,
+                remote: false,
+            }
+        );
+        assert!(
+            TeeOptions::parse(&args(&["--remote", "/bucket/copy.txt"]))
+                .unwrap()
+                .remote
+        );
+        assert_eq!(
+            TeeOptions::parse(&args(&["s3://bucket/copy.txt"])).unwrap(),
+            TeeOptions {
+                path: "/bucket/copy.txt".to_string(),
+                append: false,
+                remote: true,
+            }
+        );
+        assert!(TeeOptions::parse(&args(&[])).is_err());
+        assert!(TeeOptions::parse(&args(&["a", "b"])).is_err());
+        assert!(TeeOptions::parse(&args(&["--append", "--remote", "a"])).is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use colored::*;
+use std::time::Duration;
+
+use super::compare::{Change, CompareCommand, compare, list};
+use super::output::{Output, print_line};
+use super::{Command, ShellState};
+use crate::shell::settings::parse_duration;
+
+/// Time between listings unless --interval says otherwise
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(10);
+
+pub struct WatchCommand;
+
+#[async_trait]
+impl Command for WatchCommand {
+    fn name(&self) -> &str {
+        "watch"
+    }
+
+    fn usage(&self) -> &str {
+        "watch [-r] [--interval 10s] [PATH] - Print keys added, removed, or modified under a prefix until Ctrl-C"
+    }
+
+    fn flags(&self) -> &[(&str, &str)] {
+        &[
+            ("-r, --recursive", "Watch everything under the prefix"),
+            (
+                "-n, --interval DURATION",
+                "Time between listings (default 10s)",
+            ),
+        ]
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        let mut recursive = false;
+        let mut interval = DEFAULT_INTERVAL;
+        let mut paths = Vec::new();
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "-r" | "-R" | "--recursive" => recursive = true,
+                "-n" | "--interval" => {
+                    let value = iter
+                        .next()
+                        .ok_or_else(|| anyhow!("{arg} requires a value"))?;
+                    interval = parse_duration(value)?;
+                    if interval.is_zero() {
+                        return Err(anyhow!("{arg} must be greater than zero"));
+                    }
+                }
+                _ => paths.push(arg.as_str()),
+            }
+        }
+        let path = match paths.as_slice() {
+            [] => ".",
+            [path] => path,
+            _ => return Err(anyhow!("Usage: {}", self.usage())),
+        };
+
+        let (bucket, prefix) = CompareCommand::resolve_prefix(state, path).await?;
+        let client = state.s3_client();
+        let mut previous = list(client, &bucket, &prefix, recursive).await?;
+        eprintln!(
+            "Watching /{bucket}/{prefix} ({} objects) every {}s; Ctrl-C to stop",
+            previous.len(),
+            interval.as_secs_f64()
+        );
+
+        // Runs until the shell cancels the command on Ctrl-C
+        loop {
+            tokio::time::sleep(interval).await;
+
+            // A failed listing (say, a dropped connection) is retried on the next tick
+            let current = match list(client, &bucket, &prefix, recursive).await {
+                Ok(current) => current,
+                Err(e) => {
+                    eprintln!("{} {e}", "Warning:".yellow().bold());
+                    continue;
+                }
+            };
+
+            let time = chrono::Local::now().format("%H:%M:%S");
+            for (rel, change) in compare(&previous, &current) {
+                let line = match change {
+                    Change::OnlyInB => {
+                        let size = humansize::format_size(current[&rel].size, humansize::BINARY);
+                        format!("+ {rel} ({size})").green()
+                    }
+                    Change::OnlyInA => format!("- {rel}").red(),
+                    Change::SizeDiffers(_, size) => {
+                        let size = humansize::format_size(size, humansize::BINARY);
+                        format!("~ {rel} ({size})").yellow()
+                    }
+                    Change::EtagDiffers => format!("~ {rel}").yellow(),
+                };
+                print_line!(out, "[{time}] {line}");
+            }
+            previous = current;
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use tokio::io::AsyncReadExt;
+
+use super::output::{Output, print_line};
+use super::zcat::open_decompressed;
+use super::{Command, ShellState, resolve_object};
+
+/// Read buffer for counting
+const CHUNK_SIZE: usize = 64 * 1024;
+
+pub struct WcCommand;
+
+#[async_trait]
+impl Command for WcCommand {
+    fn name(&self) -> &str {
+        "wc"
+    }
+
+    fn usage(&self) -> &str {
+        "wc [-l] [-w] [-c] FILE... - Count lines, words, and bytes (.gz/.bz2/.zst decompressed)"
+    }
+
+    fn flags(&self) -> &[(&str, &str)] {
+        &[
+            ("-l", "Count lines"),
+            ("-w", "Count words"),
+            ("-c", "Count bytes"),
+        ]
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        let mut lines = false;
+        let mut words = false;
+        let mut bytes = false;
+        let mut paths = Vec::new();
+        for arg in args {
+            match arg.as_str() {
+                "-l" => lines = true,
+                "-w" => words = true,
+                "-c" => bytes = true,
+                _ => paths.push(arg),
+            }
+        }
+        if paths.is_empty() {
+            return Err(anyhow!("Usage: {}", self.usage()));
+        }
+        // Like wc, no flags means all three
+        if !lines && !words && !bytes {
+            (lines, words, bytes) = (true, true, true);
+        }
+
+        let format = |counts: &Counts, name: &str| {
+            let mut line = String::new();
+            for (shown, count) in [
+                (lines, counts.lines),
+                (words, counts.words),
+                (bytes, counts.bytes),
+            ] {
+                if shown {
+                    line.push_str(&format!("{count:>8} "));
+                }
+            }
+            line.push_str(name);
+            line
+        };
+
+        let mut total = Counts::default();
+        for path in &paths {
+            let (bucket, key, size) = resolve_object(state, path).await?;
+            let mut reader = open_decompressed(state.s3_client(), &bucket, &key, size).await?;
+
+            let mut counts = Counts::default();
+            let mut buf = vec![0u8; CHUNK_SIZE];
+            loop {
+                let n = reader.read(&mut buf).await?;
+                if n == 0 {
+                    break;
+                }
+                counts.update(&buf[..n]);
+            }
+
+            print_line!(out, "{}", format(&counts, path));
+            total.add(&counts);
+        }
+
+        if paths.len() > 1 {
+            print_line!(out, "{}", format(&total, "total"));
+        }
+
+        Ok(())
+    }
+}
+
+/// Running line, word, and byte counts over a stream of chunks
+#[derive(Debug, Default)]
+struct Counts {
+    lines: u64,
+    words: u64,
+    bytes: u64,
+    /// Whether the last byte seen was inside a word, so words can span chunks
+    in_word: bool,
+}
+
+impl Counts {
+    fn update(&mut self, chunk: &[u8]) {
+        self.bytes += chunk.len() as u64;
+        for &byte in chunk {
+            if byte == b'\n' {
+                self.lines += 1;
+            }
+            if byte.is_ascii_whitespace() {
+                self.in_word = false;
+            } else if !self.in_word {
+                self.in_word = true;
+                self.words += 1;
+            }
+        }
+    }
+
+    fn add(&mut self, other: &Counts) {
+        self.lines += other.lines;
+        self.words += other.words;
+        self.bytes += other.bytes;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts() {
+        let mut counts = Counts::default();
+        counts.update(b"{\"id\": 1}\n{\"id\"");
+        counts.update(b": 2}\n  trailing");
+        assert_eq!((counts.lines, counts.words, counts.bytes), (2, 5, 30));
+    }
+}
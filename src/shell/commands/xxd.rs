@@ -0,0 +1,177 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+
+use super::output::{Output, print_line};
+use super::{Command, ShellState};
+use crate::shell::settings::parse_size;
+use crate::vfs::VfsNode;
+
+/// Bytes shown when no --length is given
+const DEFAULT_LENGTH: u64 = 1024;
+
+/// Bytes per dump line
+const LINE_WIDTH: usize = 16;
+
+pub struct XxdCommand;
+
+#[async_trait]
+impl Command for XxdCommand {
+    fn name(&self) -> &str {
+        "xxd"
+    }
+
+    fn usage(&self) -> &str {
+        "xxd [-s|--offset N] [-l|--length M] FILE - Hex dump a byte range of an object"
+    }
+
+    fn flags(&self) -> &[(&str, &str)] {
+        &[
+            (
+                "-s, --offset N",
+                "Start at byte N (negative counts from the end)",
+            ),
+            ("-l, --length M", "Dump M bytes (default 1024)"),
+        ]
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        let mut offset: Option<&String> = None;
+        let mut length: Option<u64> = None;
+        let mut paths = Vec::new();
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "-s" | "--offset" => {
+                    offset = Some(
+                        iter.next()
+                            .ok_or_else(|| anyhow!("{arg} requires a value"))?,
+                    )
+                }
+                "-l" | "--length" => {
+                    let value = iter
+                        .next()
+                        .ok_or_else(|| anyhow!("{arg} requires a value"))?;
+                    length = Some(parse_offset(value)?);
+                }
+                _ => paths.push(arg),
+            }
+        }
+
+        let [path] = paths.as_slice() else {
+            return Err(anyhow!("Usage: {}", self.usage()));
+        };
+
+        let (bucket, key, size) = match state.resolver().resolve(state.current_node(), path).await?
+        {
+            VfsNode::Object {
+                bucket, key, size, ..
+            } => (bucket, key, size),
+            VfsNode::Prefix { .. } => return Err(anyhow!("File not found: {path}")),
+            _ => return Err(anyhow!("Not an S3 object: {path}")),
+        };
+
+        // A negative offset counts back from the end, like `xxd -s -N`
+        let offset = match offset {
+            Some(value) => match value.strip_prefix('-') {
+                Some(back) => size.saturating_sub(parse_offset(back)?),
+                None => parse_offset(value)?,
+            },
+            None => 0,
+        };
+        if offset >= size {
+            // Nothing to show past the end (or in an empty object)
+            return Ok(());
+        }
+        let length = length.unwrap_or(DEFAULT_LENGTH).min(size - offset);
+        if length == 0 {
+            return Ok(());
+        }
+
+        // Only the requested range is fetched
+        let bytes = state
+            .s3_client()
+            .get_object_range(&bucket, &key, offset, length)
+            .await?;
+        for line in hex_dump(offset, &bytes) {
+            print_line!(out, "{line}");
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse an offset or length: decimal with an optional size unit, or hex with 0x
+fn parse_offset(value: &str) -> Result<u64> {
+    match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16).map_err(|_| anyhow!("Invalid offset: {value}")),
+        None => parse_size(value),
+    }
+}
+
+/// Format bytes starting at `offset` as a canonical hex+ASCII dump (like `hexdump -C`)
+pub(crate) fn hex_dump(offset: u64, bytes: &[u8]) -> Vec<String> {
+    bytes
+        .chunks(LINE_WIDTH)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mut hex = String::new();
+            for j in 0..LINE_WIDTH {
+                // An extra space splits the two halves of the line
+                if j == LINE_WIDTH / 2 {
+                    hex.push(' ');
+                }
+                match chunk.get(j) {
+                    Some(byte) => hex.push_str(&format!("{byte:02x} ")),
+                    None => hex.push_str("   "),
+                }
+            }
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            let line_offset = offset + (i * LINE_WIDTH) as u64;
+            format!("{line_offset:08x}  {hex} |{ascii}|")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(parse_offset("512").unwrap(), 512);
+        assert_eq!(parse_offset("0x1f0").unwrap(), 0x1f0);
+        assert_eq!(parse_offset("4KiB").unwrap(), 4096);
+        assert!(parse_offset("0xzz").is_err());
+    }
+
+    #[test]
+    fn test_hex_dump() {
+        let lines = hex_dump(0x100, b"Hello, world!\n\x00\x01PK");
+        assert_eq!(
+            lines,
+            vec![
+                "00000100  48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 0a 00 01  |Hello, world!...|",
+                "00000110  50 4b                                             |PK|",
+            ]
+        );
+        assert!(hex_dump(0, b"").is_empty());
+    }
+}
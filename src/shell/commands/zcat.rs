@@ -0,0 +1,249 @@
+use anyhow::{Result, anyhow};
+use async_compression::tokio::bufread::{BzDecoder, GzipDecoder, ZstdDecoder};
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use regex::RegexBuilder;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio_util::io::StreamReader;
+
+use super::output::{Output, print_bytes, print_line};
+use super::{Command, ShellState, resolve_object};
+use crate::s3::S3Client;
+
+/// Read buffer for decompressed output
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Compression formats zcat and zgrep understand
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Codec {
+    Gzip,
+    Bzip2,
+    Zstd,
+}
+
+impl Codec {
+    fn from_key(key: &str) -> Option<Self> {
+        let key = key.to_lowercase();
+        if key.ends_with(".gz") || key.ends_with(".tgz") {
+            Some(Codec::Gzip)
+        } else if key.ends_with(".bz2") || key.ends_with(".tbz2") {
+            Some(Codec::Bzip2)
+        } else if key.ends_with(".zst") || key.ends_with(".zstd") {
+            Some(Codec::Zstd)
+        } else {
+            None
+        }
+    }
+
+    fn sniff(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(&[0x1f, 0x8b]) {
+            Some(Codec::Gzip)
+        } else if bytes.starts_with(b"BZh") {
+            Some(Codec::Bzip2)
+        } else if bytes.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Codec::Zstd)
+        } else {
+            None
+        }
+    }
+}
+
+/// Stream an object, decompressing it on the fly
+/// The codec comes from the key's extension, or else the first bytes; anything
+/// unrecognized is passed through as-is, like `zcat -f`.
+pub(super) async fn open_decompressed<'a>(
+    client: &'a S3Client,
+    bucket: &'a str,
+    key: &'a str,
+    size: u64,
+) -> Result<Box<dyn AsyncBufRead + Unpin + Send + 'a>> {
+    let chunks = client.get_object_chunks(bucket, key, Some(size)).await?;
+    let mut reader = BufReader::new(StreamReader::new(chunks.map_err(std::io::Error::other)));
+
+    let codec = match Codec::from_key(key) {
+        Some(codec) => Some(codec),
+        None => Codec::sniff(reader.fill_buf().await?),
+    };
+
+    Ok(match codec {
+        // Concatenated gzip members are common in rotated logs
+        Some(Codec::Gzip) => {
+            let mut decoder = GzipDecoder::new(reader);
+            decoder.multiple_members(true);
+            Box::new(BufReader::new(decoder))
+        }
+        Some(Codec::Bzip2) => {
+            let mut decoder = BzDecoder::new(reader);
+            decoder.multiple_members(true);
+            Box::new(BufReader::new(decoder))
+        }
+        Some(Codec::Zstd) => {
+            let mut decoder = ZstdDecoder::new(reader);
+            decoder.multiple_members(true);
+            Box::new(BufReader::new(decoder))
+        }
+        None => Box::new(reader),
+    })
+}
+
+pub struct ZcatCommand;
+
+#[async_trait]
+impl Command for ZcatCommand {
+    fn name(&self) -> &str {
+        "zcat"
+    }
+
+    fn usage(&self) -> &str {
+        "zcat FILE... - Display .gz, .bz2, or .zst objects decompressed"
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        if args.is_empty() {
+            return Err(anyhow!("Usage: {}", self.usage()));
+        }
+
+        for path in args {
+            let (bucket, key, size) = resolve_object(state, path).await?;
+
+            let mut reader = open_decompressed(state.s3_client(), &bucket, &key, size).await?;
+            let mut buf = vec![0u8; CHUNK_SIZE];
+            loop {
+                let n = reader.read(&mut buf).await?;
+                if n == 0 {
+                    break;
+                }
+                // Returning on BrokenPipe drops the reader, ending the download early
+                print_bytes!(out, &buf[..n]);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub struct ZgrepCommand;
+
+#[async_trait]
+impl Command for ZgrepCommand {
+    fn name(&self) -> &str {
+        "zgrep"
+    }
+
+    fn usage(&self) -> &str {
+        "zgrep [-i] [-v] [-n] [-c] PATTERN FILE... - Search compressed objects for a regex"
+    }
+
+    fn flags(&self) -> &[(&str, &str)] {
+        &[
+            ("-i", "Match case-insensitively"),
+            ("-v", "Print lines that don't match"),
+            ("-n", "Prefix lines with their line number"),
+            ("-c", "Print only a count of matching lines"),
+        ]
+    }
+
+    async fn execute(
+        &self,
+        state: &mut ShellState,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        let mut ignore_case = false;
+        let mut invert = false;
+        let mut line_numbers = false;
+        let mut count = false;
+        let mut positional = Vec::new();
+        for arg in args {
+            match arg.as_str() {
+                "-i" => ignore_case = true,
+                "-v" => invert = true,
+                "-n" => line_numbers = true,
+                "-c" => count = true,
+                _ => positional.push(arg),
+            }
+        }
+
+        let [pattern, paths @ ..] = positional.as_slice() else {
+            return Err(anyhow!("Usage: {}", self.usage()));
+        };
+        if paths.is_empty() {
+            return Err(anyhow!("Usage: {}", self.usage()));
+        }
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(ignore_case)
+            .build()
+            .map_err(|e| anyhow!("Invalid pattern: {e}"))?;
+
+        // Like grep, name the file on each line once there's more than one
+        let show_names = paths.len() > 1;
+
+        for path in paths {
+            let (bucket, key, size) = resolve_object(state, path).await?;
+            let prefix = if show_names {
+                format!("{path}:")
+            } else {
+                String::new()
+            };
+
+            let mut reader = open_decompressed(state.s3_client(), &bucket, &key, size).await?;
+            let mut line = Vec::new();
+            let mut line_number = 0u64;
+            let mut matches = 0u64;
+            loop {
+                line.clear();
+                if reader.read_until(b'\n', &mut line).await? == 0 {
+                    break;
+                }
+                line_number += 1;
+
+                let text = String::from_utf8_lossy(&line);
+                let text = text.trim_end_matches(['\n', '\r']);
+                if regex.is_match(text) == invert {
+                    continue;
+                }
+                matches += 1;
+                if count {
+                    continue;
+                }
+                if line_numbers {
+                    print_line!(out, "{prefix}{line_number}:{text}");
+                } else {
+                    print_line!(out, "{prefix}{text}");
+                }
+            }
+
+            if count {
+                print_line!(out, "{prefix}{matches}");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codec_detection() {
+        assert_eq!(Codec::from_key("logs/app.log.GZ"), Some(Codec::Gzip));
+        assert_eq!(Codec::from_key("dump.bz2"), Some(Codec::Bzip2));
+        assert_eq!(Codec::from_key("events.json.zst"), Some(Codec::Zstd));
+        assert_eq!(Codec::from_key("app.log"), None);
+
+        assert_eq!(Codec::sniff(&[0x1f, 0x8b, 8]), Some(Codec::Gzip));
+        assert_eq!(Codec::sniff(b"BZh91AY"), Some(Codec::Bzip2));
+        assert_eq!(
+            Codec::sniff(&[0x28, 0xb5, 0x2f, 0xfd, 0]),
+            Some(Codec::Zstd)
+        );
+        assert_eq!(Codec::sniff(b"plain text"), None);
+    }
+}
@@ -1,43 +1,157 @@
+use anyhow::{Result, anyhow};
+use colored::Colorize;
 use rustyline::Context;
 use rustyline::completion::{Completer, Pair};
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 
-use crate::archive::ArchiveHandler;
+use crate::archive::{ArchiveFormat, format_for_name, handler_for};
 use crate::cache::ArchiveCache;
 use crate::s3::S3Client;
-use crate::vfs::{ArchiveType, VfsNode};
-
-#[cfg(feature = "parquet")]
-use crate::archive::ParquetHandler;
-use crate::archive::{tar::TarHandler, zip::ZipHandler};
+use crate::s3::client::ListObjectsResult;
+use crate::vfs::{PathResolver, VfsNode};
 
 /// Entry in completion cache with metadata
 #[derive(Clone, Debug)]
 pub struct CompletionEntry {
     pub name: String,
     pub is_dir: bool,
+    /// Size of a file, shown next to its candidate
+    pub size: Option<u64>,
 }
 
 impl CompletionEntry {
+    /// Entries of one level of a bucket listing: prefixes as directories, then objects
+    pub fn from_listing(result: &ListObjectsResult) -> Vec<CompletionEntry> {
+        let prefixes = result.prefixes.iter().map(|prefix| CompletionEntry {
+            name: prefix
+                .trim_end_matches('/')
+                .rsplit('/')
+                .next()
+                .unwrap_or(prefix)
+                .to_string(),
+            is_dir: true,
+            size: None,
+        });
+        let objects = result.objects.iter().map(|obj| CompletionEntry {
+            name: obj.key.rsplit('/').next().unwrap_or(&obj.key).to_string(),
+            is_dir: false,
+            size: Some(obj.size),
+        });
+        prefixes.chain(objects).collect()
+    }
+
+    /// A rustyline candidate: directories complete with a trailing '/' so the next TAB
+    /// continues into them, and files show their size
+    fn pair(&self, dir_path: &str) -> Pair {
+        let name = if self.is_dir {
+            format!("{}/", self.name)
+        } else {
+            self.name.clone()
+        };
+        let display = match self.size {
+            Some(size) if !self.is_dir => {
+                format!(
+                    "{name} ({})",
+                    humansize::format_size(size, humansize::BINARY)
+                )
+            }
+            _ => name.clone(),
+        };
+        Pair {
+            display,
+            replacement: format!("{dir_path}{name}"),
+        }
+    }
+
     /// Check if this entry can be navigated into with cd
     /// Returns true for directories and supported archive files
     pub fn is_navigable(&self) -> bool {
-        self.is_dir || ArchiveType::from_path(&self.name).is_some()
+        self.is_dir || format_for_name(&self.name).is_some_and(ArchiveFormat::is_navigable)
     }
 }
 
+/// How long a directory's listing is reused for completion before it's fetched again
+pub const DEFAULT_COMPLETION_TTL: Duration = Duration::from_secs(60);
+
+/// How the typed part of a path is matched against names
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MatchMode {
+    /// Names starting with what's typed
+    #[default]
+    Prefix,
+    /// Also names containing it, or its letters in order, case-insensitively
+    Fuzzy,
+}
+
+impl MatchMode {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "prefix" => Ok(MatchMode::Prefix),
+            "fuzzy" => Ok(MatchMode::Fuzzy),
+            _ => Err(anyhow!(
+                "Unknown completion mode: {value} (expected prefix or fuzzy)"
+            )),
+        }
+    }
+
+    /// How well `name` matches `typed`, best first; None when it doesn't match
+    /// An exact prefix always ranks first, then a prefix in another case, then
+    /// substrings by how early they start, then scattered letters by how spread out they are.
+    fn rank(self, name: &str, typed: &str) -> Option<(u8, usize)> {
+        if name.starts_with(typed) {
+            return Some((0, 0));
+        }
+        if self == MatchMode::Prefix {
+            return None;
+        }
+        let (name, typed) = (name.to_lowercase(), typed.to_lowercase());
+        if name.starts_with(&typed) {
+            return Some((1, 0));
+        }
+        if let Some(i) = name.find(&typed) {
+            return Some((2, i));
+        }
+        let mut positions = Vec::with_capacity(typed.len());
+        let mut chars = name.char_indices();
+        for wanted in typed.chars() {
+            let (i, _) = chars.find(|(_, c)| *c == wanted)?;
+            positions.push(i);
+        }
+        let spread = positions.last()? - positions.first()?;
+        Some((3, spread))
+    }
+}
+
+impl std::fmt::Display for MatchMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatchMode::Prefix => write!(f, "prefix"),
+            MatchMode::Fuzzy => write!(f, "fuzzy"),
+        }
+    }
+}
+
+/// A directory's entries and when they were listed
+type Listing = (Instant, Vec<CompletionEntry>);
+
 /// Cache of available completions for different paths
 #[derive(Clone)]
 pub struct CompletionCache {
     /// Cached entries by path (path -> entries with metadata)
-    entries: Arc<RwLock<HashMap<String, Vec<CompletionEntry>>>>,
+    entries: Arc<RwLock<HashMap<String, Listing>>>,
+    /// Age after which a path's entries are listed again
+    ttl: Arc<RwLock<Duration>>,
+    /// How typed paths are matched
+    match_mode: Arc<RwLock<MatchMode>>,
     /// Available commands
     commands: Vec<String>,
     /// Current VFS node
     current_node: Arc<RwLock<VfsNode>>,
-    /// S3 client for lazy loading
-    s3_client: Arc<S3Client>,
+    /// S3 client for lazy loading (swappable when the shell switches credentials)
+    s3_client: Arc<RwLock<Arc<S3Client>>>,
     /// Archive cache for accessing archive indexes
     archive_cache: ArchiveCache,
 }
@@ -46,16 +160,58 @@ impl CompletionCache {
     pub fn new(s3_client: Arc<S3Client>, archive_cache: ArchiveCache) -> Self {
         CompletionCache {
             entries: Arc::new(RwLock::new(HashMap::new())),
+            ttl: Arc::new(RwLock::new(DEFAULT_COMPLETION_TTL)),
+            match_mode: Arc::new(RwLock::new(MatchMode::default())),
             commands: vec![
                 "ls".to_string(),
                 "cd".to_string(),
                 "cat".to_string(),
+                "zcat".to_string(),
+                "zgrep".to_string(),
+                "xxd".to_string(),
+                "strings".to_string(),
+                "wc".to_string(),
+                "diff".to_string(),
+                "compare".to_string(),
+                "watch".to_string(),
+                "select".to_string(),
+                "stat".to_string(),
+                "search".to_string(),
+                "tags".to_string(),
+                "meta".to_string(),
+                "file".to_string(),
+                "checksum".to_string(),
+                "open".to_string(),
+                "edit".to_string(),
+                "get".to_string(),
+                "sync".to_string(),
+                "tee".to_string(),
+                "foreach".to_string(),
+                "jobs".to_string(),
+                "fg".to_string(),
+                "kill".to_string(),
+                "refresh".to_string(),
+                "whoami".to_string(),
+                "mb".to_string(),
+                "rb".to_string(),
+                "bucket".to_string(),
+                "profile".to_string(),
+                "provider".to_string(),
+                "alias".to_string(),
+                "config".to_string(),
+                "unalias".to_string(),
+                "history".to_string(),
+                "metrics".to_string(),
+                "cost".to_string(),
+                #[cfg(feature = "parquet")]
+                "query".to_string(),
                 "pwd".to_string(),
+                "set".to_string(),
                 "help".to_string(),
                 "exit".to_string(),
             ],
             current_node: Arc::new(RwLock::new(VfsNode::Root)),
-            s3_client,
+            s3_client: Arc::new(RwLock::new(s3_client)),
             archive_cache,
         }
     }
@@ -79,16 +235,68 @@ impl CompletionCache {
     /// Update the cached entries for a specific path
     pub fn update_entries(&self, path: String, entries: Vec<CompletionEntry>) {
         if let Ok(mut cache) = self.entries.write() {
-            cache.insert(path, entries);
+            cache.insert(path, (Instant::now(), entries));
         }
     }
 
-    /// Get cached entries for a path
+    /// Get cached entries for a path, unless they're older than the TTL
     pub fn get_entries(&self, path: &str) -> Option<Vec<CompletionEntry>> {
-        self.entries
-            .read()
-            .ok()
-            .and_then(|cache| cache.get(path).cloned())
+        let ttl = self.ttl();
+        self.entries.read().ok().and_then(|cache| {
+            let (listed, entries) = cache.get(path)?;
+            (listed.elapsed() < ttl).then(|| entries.clone())
+        })
+    }
+
+    /// How long entries are reused
+    pub fn ttl(&self) -> Duration {
+        *self.ttl.read().unwrap()
+    }
+
+    pub fn set_ttl(&self, ttl: Duration) {
+        *self.ttl.write().unwrap() = ttl;
+    }
+
+    /// How typed paths are matched
+    pub fn match_mode(&self) -> MatchMode {
+        *self.match_mode.read().unwrap()
+    }
+
+    pub fn set_match_mode(&self, mode: MatchMode) {
+        *self.match_mode.write().unwrap() = mode;
+    }
+
+    /// Drop the listings a change to `s3://bucket/key` makes stale: the directories
+    /// containing it and, for a prefix, everything below it
+    pub fn invalidate(&self, bucket: &str, key: &str) {
+        let target = format!("/{bucket}/{}", key.trim_end_matches('/'));
+        let target = target.trim_end_matches('/');
+        if let Ok(mut cache) = self.entries.write() {
+            cache.retain(|path, _| {
+                let stale = path == target
+                    || target.starts_with(&format!("{path}/"))
+                    || path.starts_with(&format!("{target}/"))
+                    || path.starts_with(&format!("{target}@"));
+                !stale
+            });
+        }
+    }
+
+    /// List a directory again and cache its entries, returning how many there are
+    pub async fn reload(&self, node: &VfsNode) -> Result<usize> {
+        let s3_client = self.s3_client();
+        match node {
+            VfsNode::Bucket { name } => s3_client.listing_cache().remove(name, ""),
+            VfsNode::Prefix { bucket, prefix } => s3_client.listing_cache().remove(bucket, prefix),
+            _ => {}
+        }
+        let entries =
+            ShellCompleter::fetch_entries_async_static(&s3_client, &self.archive_cache, node, "")
+                .await
+                .map_err(|_| anyhow!("Failed to list {}", node.path()))?;
+        let count = entries.len();
+        self.update_entries(Self::key_for(node), entries);
+        Ok(count)
     }
 
     /// Get available commands
@@ -97,14 +305,56 @@ impl CompletionCache {
     }
 
     /// Get S3 client
-    pub fn s3_client(&self) -> &Arc<S3Client> {
-        &self.s3_client
+    pub fn s3_client(&self) -> Arc<S3Client> {
+        Arc::clone(&self.s3_client.read().unwrap())
+    }
+
+    /// Replace the S3 client used for future completions
+    pub fn set_s3_client(&self, s3_client: Arc<S3Client>) {
+        *self.s3_client.write().unwrap() = s3_client;
+    }
+
+    /// Drop all cached completion entries
+    pub fn clear(&self) {
+        if let Ok(mut cache) = self.entries.write() {
+            cache.clear();
+        }
     }
 
     /// Get archive cache
     pub fn archive_cache(&self) -> &ArchiveCache {
         &self.archive_cache
     }
+
+    /// Cache key (path string) of a directory node
+    pub fn key_for(node: &VfsNode) -> String {
+        match node {
+            VfsNode::Root => "/".to_string(),
+            VfsNode::Bucket { name } => format!("/{name}"),
+            VfsNode::Prefix { bucket, prefix } => {
+                format!("/{}/{}", bucket, prefix.trim_end_matches('/'))
+            }
+            VfsNode::Archive { parent, .. } => {
+                // Archive root - use the parent object's path
+                match parent.as_ref() {
+                    VfsNode::Object { bucket, key, .. } => {
+                        format!("/{}/{}@archive", bucket, key)
+                    }
+                    _ => "/".to_string(),
+                }
+            }
+            VfsNode::ArchiveEntry { archive, path, .. } => {
+                // Entry within archive - append path to archive key
+                let archive_key = Self::key_for(archive.as_ref());
+                if path.is_empty() {
+                    archive_key
+                } else {
+                    format!("{}/{}", archive_key, path.trim_end_matches('/'))
+                }
+            }
+            _ => "/".to_string(),
+        }
+    }
 }
 
 /// Tab completion helper for the shell
@@ -131,7 +381,8 @@ impl ShellCompleter {
     }
 
     /// Complete a path (file or directory)
-    fn complete_path(&self, path: &str, command: &str) -> Vec<Pair> {
+    /// Without `fetch`, only directories already in the cache are used, so nothing blocks.
+    fn complete_path(&self, path: &str, command: &str, fetch: bool) -> Vec<Pair> {
         // Determine which directory we're completing in
         let (dir_path, file_prefix) = if path.contains('/') {
             // Multi-segment path like "movies/" or "movies/id"
@@ -144,140 +395,122 @@ impl ShellCompleter {
             ("", path)
         };
 
-        // Get the cache key for this directory
-        let cache_key = self.get_cache_key_for_path(dir_path);
-
-        // Try to get cached entries
-        let entries = if let Some(cached) = self.cache.get_entries(&cache_key) {
-            cached
-        } else {
-            // Not cached - fetch it lazily
-            match self.fetch_entries_for_path(dir_path) {
-                Ok(entries) => {
-                    // Cache for future use
-                    self.cache
-                        .update_entries(cache_key.clone(), entries.clone());
-                    entries
-                }
-                Err(_) => return Vec::new(),
-            }
+        let Some(entries) = self.entries_for_path(dir_path, fetch) else {
+            return Vec::new();
         };
 
-        // Filter and format completions
-        entries
+        // Filter by command (cd only shows directories and archives) and match, best first
+        let mode = self.cache.match_mode();
+        let mut matches: Vec<_> = entries
             .into_iter()
-            .filter(|entry| {
-                // Filter by prefix
-                if !entry.name.starts_with(file_prefix) {
-                    return false;
-                }
-                // Filter by command: cd only shows directories and archives
-                if command == "cd" && !entry.is_navigable() {
-                    return false;
-                }
-                true
-            })
-            .map(|entry| {
-                let replacement = if dir_path.is_empty() {
-                    entry.name.clone()
-                } else {
-                    format!("{}{}", dir_path, entry.name)
-                };
-                Pair {
-                    display: entry.name,
-                    replacement,
-                }
-            })
+            .filter(|entry| command != "cd" || entry.is_navigable())
+            .filter_map(|entry| Some((mode.rank(&entry.name, file_prefix)?, entry)))
+            .collect();
+        matches.sort_by_key(|(rank, _)| *rank);
+        matches
+            .into_iter()
+            .map(|(_, entry)| entry.pair(dir_path))
             .collect()
     }
 
-    /// Get cache key for a path relative to current location
-    fn get_cache_key_for_path(&self, rel_path: &str) -> String {
-        let current = self.cache.get_current_node();
-
-        if rel_path.is_empty() {
-            // Current directory
-            return self.node_to_cache_key(&current);
+    /// Entries of a directory relative to the current location, from the cache or (with
+    /// `fetch`) listed and cached
+    fn entries_for_path(&self, dir_path: &str, fetch: bool) -> Option<Vec<CompletionEntry>> {
+        let cache_key = self.get_cache_key_for_path(dir_path);
+        if let Some(cached) = self.cache.get_entries(&cache_key) {
+            return Some(cached);
         }
-
-        // Handle relative paths
-        if rel_path.starts_with("..") {
-            // Parent directory - compute from current
-            return self.get_parent_cache_key(&current, rel_path);
+        if !fetch {
+            return None;
         }
+        let entries = self.fetch_entries_for_path(dir_path).ok()?;
+        self.cache.update_entries(cache_key, entries.clone());
+        Some(entries)
+    }
 
-        // Child path - append to current
-        let current_key = self.node_to_cache_key(&current);
-        if current_key == "/" {
-            format!("/{}", rel_path.trim_matches('/'))
-        } else {
-            format!("{}{}", current_key.trim_end_matches('/'), rel_path)
+    /// Completions for the text before the cursor, with where they start
+    fn candidates(&self, line: &str, fetch: bool) -> (usize, Vec<Pair>) {
+        // If line is empty or only whitespace, don't complete
+        if line.trim().is_empty() {
+            return (0, Vec::new());
         }
-    }
 
-    /// Convert VfsNode to a cache key (path string)
-    fn node_to_cache_key(&self, node: &VfsNode) -> String {
-        match node {
-            VfsNode::Root => "/".to_string(),
-            VfsNode::Bucket { name } => format!("/{name}"),
-            VfsNode::Prefix { bucket, prefix } => {
-                format!("/{}/{}", bucket, prefix.trim_end_matches('/'))
-            }
-            VfsNode::Archive { parent, .. } => {
-                // Archive root - use the parent object's path
-                match parent.as_ref() {
-                    VfsNode::Object { bucket, key, .. } => {
-                        format!("/{}/{}@archive", bucket, key)
-                    }
-                    _ => "/".to_string(),
-                }
-            }
-            VfsNode::ArchiveEntry { archive, path, .. } => {
-                // Entry within archive - append path to archive key
-                let archive_key = self.node_to_cache_key(archive.as_ref());
-                if path.is_empty() {
-                    archive_key
-                } else {
-                    format!("{}/{}", archive_key, path.trim_end_matches('/'))
-                }
-            }
-            _ => "/".to_string(),
+        // Split into words
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        // If we're on the first word, complete commands
+        if words.len() == 1 && !line.ends_with(char::is_whitespace) {
+            let completions = self.complete_command(words[0]);
+            let start = line.len() - words[0].len();
+            return (start, completions);
         }
-    }
 
-    /// Get parent directory cache key
-    fn get_parent_cache_key(&self, current: &VfsNode, rel_path: &str) -> String {
-        let mut key = self.node_to_cache_key(current);
+        // Otherwise, complete paths
+        // The path is everything after the command
+        let path_start = line.find(char::is_whitespace).unwrap_or(0);
+        let path = line[path_start..].trim_start();
 
-        // Count how many levels up to go
-        let up_count = rel_path.chars().filter(|&c| c == '.').count() / 2;
+        // Get the command name for filtering
+        let command = words[0];
 
-        for _ in 0..up_count {
-            if let Some(pos) = key.trim_end_matches('/').rfind('/') {
-                if pos == 0 {
-                    key = "/".to_string();
-                } else {
-                    key = key[..pos].to_string();
-                }
-            }
+        if path.is_empty() {
+            // Just completed command, show all entries for current directory
+            let entries = self.entries_for_path("", fetch).unwrap_or_default();
+            let completions = entries
+                .into_iter()
+                .filter(|entry| {
+                    // Filter by command: cd only shows directories and archives
+                    if command == "cd" && !entry.is_navigable() {
+                        return false;
+                    }
+                    true
+                })
+                .map(|entry| entry.pair(""))
+                .collect();
+            return (line.len(), completions);
         }
 
-        // Add any remaining path after ../
-        if let Some(suffix) = rel_path
-            .strip_prefix("../")
-            .or_else(|| rel_path.strip_prefix(".."))
-            && !suffix.is_empty()
-        {
-            key = format!("{}/{}", key.trim_end_matches('/'), suffix);
+        let completions = self.complete_path(path, command, fetch);
+        let start = line.len() - path.split_whitespace().last().unwrap_or("").len();
+        (start, completions)
+    }
+
+    /// The rest of the most likely completion of the word before the cursor: the only
+    /// candidate, or what every candidate shares
+    /// Hints come from cached listings only, so typing never waits on S3.
+    fn hint_for(&self, line: &str) -> Option<String> {
+        if line.is_empty() || line.ends_with(char::is_whitespace) {
+            return None;
         }
+        let (start, candidates) = self.candidates(line, false);
+        let typed = &line[start..];
+        let mut replacements = candidates
+            .iter()
+            .map(|pair| pair.replacement.as_str())
+            .filter(|replacement| replacement.starts_with(typed));
+        let first = replacements.next()?;
+        let shared = replacements.fold(first, |shared, replacement| {
+            let len = shared
+                .char_indices()
+                .zip(replacement.chars())
+                .find(|((_, a), b)| a != b)
+                .map_or(shared.len().min(replacement.len()), |((i, _), _)| i);
+            &shared[..len]
+        });
+        let hint = shared.get(typed.len()..)?;
+        (!hint.is_empty()).then(|| hint.to_string())
+    }
 
-        key
+    /// Cache key of a directory given relative to the current location, or absolute
+    fn get_cache_key_for_path(&self, path: &str) -> String {
+        let current = self.cache.get_current_node();
+        CompletionCache::key_for(&Self::resolve_target_node_static(&current, path))
     }
 
     /// Fetch entries for a path (blocks on async S3 call)
     fn fetch_entries_for_path(&self, rel_path: &str) -> Result<Vec<CompletionEntry>, ()> {
         let current = self.cache.get_current_node();
-        let s3_client = self.cache.s3_client().clone();
+        let s3_client = self.cache.s3_client();
         let archive_cache = self.cache.archive_cache().clone();
         let rel_path = rel_path.to_string();
 
@@ -322,6 +555,7 @@ impl ShellCompleter {
                     .map(|b| CompletionEntry {
                         name: b.name,
                         is_dir: true,
+                        size: None,
                     })
                     .collect())
             }
@@ -331,30 +565,7 @@ impl ShellCompleter {
                     .list_objects(name, "", Some("/"))
                     .await
                     .map_err(|_| ())?;
-                let mut entries = Vec::new();
-
-                // Prefixes are directories
-                for prefix in result.prefixes {
-                    let name = prefix
-                        .trim_end_matches('/')
-                        .rsplit('/')
-                        .next()
-                        .unwrap_or(&prefix);
-                    entries.push(CompletionEntry {
-                        name: name.to_string(),
-                        is_dir: true,
-                    });
-                }
-                // Objects are files
-                for obj in result.objects {
-                    let name = obj.key.rsplit('/').next().unwrap_or(&obj.key);
-                    entries.push(CompletionEntry {
-                        name: name.to_string(),
-                        is_dir: false,
-                    });
-                }
-
-                Ok(entries)
+                Ok(CompletionEntry::from_listing(&result))
             }
             VfsNode::Prefix {
                 ref bucket,
@@ -365,26 +576,7 @@ impl ShellCompleter {
                     .list_objects(bucket, prefix, Some("/"))
                     .await
                     .map_err(|_| ())?;
-                let mut entries = Vec::new();
-
-                // Prefixes are directories
-                for pfx in result.prefixes {
-                    let name = pfx.trim_end_matches('/').rsplit('/').next().unwrap_or(&pfx);
-                    entries.push(CompletionEntry {
-                        name: name.to_string(),
-                        is_dir: true,
-                    });
-                }
-                // Objects are files
-                for obj in result.objects {
-                    let name = obj.key.rsplit('/').next().unwrap_or(&obj.key);
-                    entries.push(CompletionEntry {
-                        name: name.to_string(),
-                        is_dir: false,
-                    });
-                }
-
-                Ok(entries)
+                Ok(CompletionEntry::from_listing(&result))
             }
             VfsNode::Archive {
                 ref parent,
@@ -405,22 +597,10 @@ impl ShellCompleter {
                 };
 
                 // Get the appropriate handler and list entries
-                let handler_entries: Vec<_> = match archive_type {
-                    ArchiveType::Zip => {
-                        let handler = ZipHandler::new();
-                        handler.list_entries(&archive_index, "")
-                    }
-                    ArchiveType::Tar | ArchiveType::TarGz | ArchiveType::TarBz2 => {
-                        let handler = TarHandler::new(archive_type.clone());
-                        handler.list_entries(&archive_index, "")
-                    }
-                    #[cfg(feature = "parquet")]
-                    ArchiveType::Parquet => {
-                        let handler = ParquetHandler::new();
-                        handler.list_entries(&archive_index, "")
-                    }
-                    _ => return Ok(Vec::new()),
+                let Some(handler) = handler_for(archive_type) else {
+                    return Ok(Vec::new());
                 };
+                let handler_entries = handler.list_entries(&archive_index, "");
 
                 // Convert ArchiveEntry to CompletionEntry
                 Ok(handler_entries
@@ -428,11 +608,13 @@ impl ShellCompleter {
                     .map(|entry| CompletionEntry {
                         name: entry
                             .path
+                            .trim_end_matches('/')
                             .rsplit('/')
                             .next()
                             .unwrap_or(&entry.path)
                             .to_string(),
                         is_dir: entry.is_dir,
+                        size: (!entry.is_dir).then_some(entry.size),
                     })
                     .collect())
             }
@@ -467,22 +649,10 @@ impl ShellCompleter {
                 };
 
                 // Get the appropriate handler and list entries at this path
-                let handler_entries: Vec<_> = match archive_type {
-                    ArchiveType::Zip => {
-                        let handler = ZipHandler::new();
-                        handler.list_entries(&archive_index, path)
-                    }
-                    ArchiveType::Tar | ArchiveType::TarGz | ArchiveType::TarBz2 => {
-                        let handler = TarHandler::new(archive_type.clone());
-                        handler.list_entries(&archive_index, path)
-                    }
-                    #[cfg(feature = "parquet")]
-                    ArchiveType::Parquet => {
-                        let handler = ParquetHandler::new();
-                        handler.list_entries(&archive_index, path)
-                    }
-                    _ => return Ok(Vec::new()),
+                let Some(handler) = handler_for(archive_type) else {
+                    return Ok(Vec::new());
                 };
+                let handler_entries = handler.list_entries(&archive_index, path);
 
                 // Convert ArchiveEntry to CompletionEntry
                 Ok(handler_entries
@@ -490,11 +660,13 @@ impl ShellCompleter {
                     .map(|entry| CompletionEntry {
                         name: entry
                             .path
+                            .trim_end_matches('/')
                             .rsplit('/')
                             .next()
                             .unwrap_or(&entry.path)
                             .to_string(),
                         is_dir: entry.is_dir,
+                        size: (!entry.is_dir).then_some(entry.size),
                     })
                     .collect())
             }
@@ -502,70 +674,22 @@ impl ShellCompleter {
         }
     }
 
-    /// Resolve target node from current + relative path (static version)
-    fn resolve_target_node_static(current: &VfsNode, rel_path: &str) -> VfsNode {
-        if rel_path.is_empty() {
-            return current.clone();
-        }
-
-        // Handle parent navigation
-        if rel_path.starts_with("..") {
-            let parent = Self::navigate_up_static(current);
-            let remaining = rel_path
-                .strip_prefix("../")
-                .or_else(|| rel_path.strip_prefix(".."))
-                .unwrap_or("");
-            if remaining.is_empty() {
-                return parent;
-            }
-            // Continue from parent with remaining path
-            return Self::resolve_child_node_static(&parent, remaining.trim_end_matches('/'));
-        }
-
-        // Navigate to child
-        Self::resolve_child_node_static(current, rel_path.trim_end_matches('/'))
-    }
-
-    /// Navigate up one level (static version)
-    fn navigate_up_static(current: &VfsNode) -> VfsNode {
-        match current {
-            VfsNode::Root => VfsNode::Root,
-            VfsNode::Bucket { .. } => VfsNode::Root,
-            VfsNode::Prefix { bucket, prefix } => {
-                if prefix.trim_end_matches('/').contains('/') {
-                    let parent_prefix = prefix.trim_end_matches('/').rsplit_once('/').unwrap().0;
-                    VfsNode::Prefix {
-                        bucket: bucket.clone(),
-                        prefix: format!("{parent_prefix}/"),
-                    }
-                } else {
-                    VfsNode::Bucket {
-                        name: bucket.clone(),
-                    }
-                }
-            }
-            VfsNode::Archive { parent, .. } => *parent.clone(),
-            VfsNode::ArchiveEntry { archive, path, .. } => {
-                // Go up within the archive
-                if path.trim_end_matches('/').contains('/') {
-                    let parent_path = path
-                        .trim_end_matches('/')
-                        .rsplit_once('/')
-                        .unwrap()
-                        .0
-                        .to_string();
-                    VfsNode::ArchiveEntry {
-                        archive: archive.clone(),
-                        path: parent_path,
-                        size: 0,
-                        is_dir: true,
-                    }
-                } else {
-                    *archive.clone()
-                }
-            }
-            _ => current.clone(),
+    /// Resolve the directory a completion path points into: absolute paths start at the
+    /// bucket list, others at the current node
+    fn resolve_target_node_static(current: &VfsNode, path: &str) -> VfsNode {
+        let mut node = if path.starts_with('/') {
+            VfsNode::Root
+        } else {
+            current.clone()
+        };
+        for segment in path.split('/') {
+            node = match segment {
+                "" | "." => node,
+                ".." => PathResolver::parent(&node),
+                name => Self::resolve_child_node_static(&node, name),
+            };
         }
+        node
     }
 
     /// Resolve child node (static version)
@@ -619,79 +743,323 @@ impl Completer for ShellCompleter {
         pos: usize,
         _ctx: &Context<'_>,
     ) -> rustyline::Result<(usize, Vec<Pair>)> {
-        let line = &line[..pos];
+        Ok(self.candidates(&line[..pos], true))
+    }
+}
 
-        // If line is empty or only whitespace, don't complete
-        if line.trim().is_empty() {
-            return Ok((0, Vec::new()));
+/// How a piece of a typed command line is highlighted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    /// A known command word, at the start or after `|`, `&&`, or `||`
+    Command,
+    /// A quoted string, to the end of the line if unterminated
+    Quoted,
+    Plain,
+}
+
+/// Split a command line into consecutive highlighted pieces
+fn tokenize<'l>(commands: &[String], line: &'l str) -> Vec<(Token, &'l str)> {
+    let mut tokens: Vec<(Token, &str)> = Vec::new();
+    let mut expect_command = true;
+    let mut pos = 0;
+    while let Some(c) = line[pos..].chars().next() {
+        let rest = &line[pos..];
+        let (token, len) = match c {
+            '\'' | '"' => {
+                expect_command = false;
+                let end = closing_quote(rest).map_or(rest.len(), |i| i + 1);
+                (Token::Quoted, end)
+            }
+            '&' | '|' => {
+                expect_command = true;
+                let operator = rest.starts_with("&&") || rest.starts_with("||");
+                (Token::Plain, if operator { 2 } else { 1 })
+            }
+            c if c.is_whitespace() => (Token::Plain, c.len_utf8()),
+            _ => {
+                let end = word_end(rest);
+                let word = &rest[..end];
+                let known = expect_command && commands.iter().any(|command| command == word);
+                expect_command = false;
+                (if known { Token::Command } else { Token::Plain }, end)
+            }
+        };
+        // Runs of plain text are kept together
+        let end = pos + len;
+        match tokens.last_mut() {
+            Some((Token::Plain, text)) if token == Token::Plain => {
+                *text = &line[pos - text.len()..end];
+            }
+            _ => tokens.push((token, &line[pos..end])),
         }
+        pos = end;
+    }
+    tokens
+}
 
-        // Split into words
-        let words: Vec<&str> = line.split_whitespace().collect();
+/// Color a command line as it's typed: command words green, quoted strings yellow
+fn highlight_line(commands: &[String], line: &str) -> String {
+    tokenize(commands, line)
+        .into_iter()
+        .map(|(token, text)| match token {
+            Token::Command => text.green().bold().to_string(),
+            Token::Quoted => text.yellow().to_string(),
+            Token::Plain => text.to_string(),
+        })
+        .collect()
+}
 
-        if words.is_empty() {
-            return Ok((0, Vec::new()));
+/// Index of the quote closing the one `text` starts with (backslashes escape in "...")
+fn closing_quote(text: &str) -> Option<usize> {
+    let quote = text.chars().next()?;
+    let mut escaped = false;
+    for (i, c) in text.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quote == '"' => escaped = true,
+            c if c == quote => return Some(i),
+            _ => {}
         }
+    }
+    None
+}
 
-        // If we're on the first word, complete commands
-        if words.len() == 1 && !line.ends_with(char::is_whitespace) {
-            let completions = self.complete_command(words[0]);
-            let start = line.len() - words[0].len();
-            return Ok((start, completions));
+/// Length of the unquoted word `text` starts with (backslashes escape the next character)
+fn word_end(text: &str) -> usize {
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            c if c.is_whitespace() || "'\"|&".contains(c) => return i,
+            _ => {}
         }
+    }
+    text.len()
+}
 
-        // Otherwise, complete paths
-        // The path is everything after the command
-        let path_start = line.find(char::is_whitespace).unwrap_or(0);
-        let path = line[path_start..].trim_start();
-
-        // Get the command name for filtering
-        let command = words[0];
+impl rustyline::Helper for ShellCompleter {}
 
-        if path.is_empty() {
-            // Just completed command, show all entries for current directory
-            let current = self.cache.get_current_node();
-            let cache_key = self.node_to_cache_key(&current);
-
-            let entries = if let Some(cached) = self.cache.get_entries(&cache_key) {
-                cached
-            } else {
-                // Try to fetch entries for current directory
-                match self.fetch_entries_for_path("") {
-                    Ok(entries) => {
-                        self.cache.update_entries(cache_key, entries.clone());
-                        entries
-                    }
-                    Err(_) => Vec::new(),
-                }
-            };
+impl rustyline::highlight::Highlighter for ShellCompleter {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        Cow::Owned(highlight_line(&self.cache.get_commands(), line))
+    }
 
-            let completions = entries
-                .into_iter()
-                .filter(|entry| {
-                    // Filter by command: cd only shows directories and archives
-                    if command == "cd" && !entry.is_navigable() {
-                        return false;
-                    }
-                    true
-                })
-                .map(|entry| Pair {
-                    display: entry.name.clone(),
-                    replacement: entry.name,
-                })
-                .collect();
-            return Ok((pos, completions));
-        }
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        Cow::Owned(hint.dimmed().to_string())
+    }
 
-        let completions = self.complete_path(path, command);
-        let start = pos - path.split_whitespace().last().unwrap_or("").len();
-        Ok((start, completions))
+    fn highlight_char(&self, _line: &str, _pos: usize, _forced: bool) -> bool {
+        // Any keystroke can start or end a quote or command word
+        true
     }
 }
 
-impl rustyline::Helper for ShellCompleter {}
-impl rustyline::highlight::Highlighter for ShellCompleter {}
 impl rustyline::hint::Hinter for ShellCompleter {
     type Hint = String;
+
+    /// Shown greyed out after the cursor; the right arrow accepts it
+    fn hint(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> Option<String> {
+        if pos < line.len() {
+            return None;
+        }
+        self.hint_for(line)
+    }
 }
+
 impl rustyline::validate::Validator for ShellCompleter {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::s3::MockS3Client;
+
+    fn completer() -> ShellCompleter {
+        let cache =
+            CompletionCache::new(Arc::new(MockS3Client::new().client()), ArchiveCache::new(1));
+        cache.set_current_node(VfsNode::Bucket {
+            name: "data".to_string(),
+        });
+        let entry = |name: &str, is_dir| CompletionEntry {
+            name: name.to_string(),
+            is_dir,
+            size: (!is_dir).then_some(2048),
+        };
+        cache.update_entries(
+            "/data".to_string(),
+            vec![
+                entry("logs", true),
+                entry("report-2024.csv", false),
+                entry("report-2025.csv", false),
+            ],
+        );
+        ShellCompleter::new(cache)
+    }
+
+    #[test]
+    fn test_hint() {
+        let completer = completer();
+        assert_eq!(completer.hint_for("whoa").as_deref(), Some("mi"));
+        assert_eq!(completer.hint_for("cat l").as_deref(), Some("ogs/"));
+        // Only what every match shares
+        assert_eq!(completer.hint_for("cat r").as_deref(), Some("eport-202"));
+        assert_eq!(completer.hint_for("cat report-2024.csv"), None);
+        assert_eq!(completer.hint_for("cat "), None);
+        // Uncached directories aren't listed while typing
+        assert_eq!(completer.hint_for("cat logs/a"), None);
+    }
+
+    #[test]
+    fn test_candidates() {
+        let completer = completer();
+        let shown = |line: &str| {
+            let (_, pairs) = completer.candidates(line, false);
+            pairs
+                .into_iter()
+                .map(|pair| (pair.display, pair.replacement))
+                .collect::<Vec<_>>()
+        };
+        // Directories end in '/' so the next TAB continues into them
+        assert_eq!(
+            shown("cd l"),
+            vec![("logs/".to_string(), "logs/".to_string())]
+        );
+        assert_eq!(
+            shown("cat report-2024"),
+            vec![(
+                "report-2024.csv (2 KiB)".to_string(),
+                "report-2024.csv".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_absolute_paths() {
+        let completer = completer();
+        let dir = |name: &str| CompletionEntry {
+            name: name.to_string(),
+            is_dir: true,
+            size: None,
+        };
+        completer
+            .cache
+            .update_entries("/".to_string(), vec![dir("data"), dir("other")]);
+        completer
+            .cache
+            .update_entries("/other".to_string(), vec![dir("prefix")]);
+        completer
+            .cache
+            .update_entries("/data/logs".to_string(), vec![dir("2024")]);
+
+        let replacements = |line: &str| {
+            let (start, pairs) = completer.candidates(line, false);
+            let replacements: Vec<_> = pairs.into_iter().map(|pair| pair.replacement).collect();
+            (start, replacements)
+        };
+        assert_eq!(replacements("cat /ot"), (4, vec!["/other/".to_string()]));
+        assert_eq!(
+            replacements("cat /other/pr"),
+            (4, vec!["/other/prefix/".to_string()])
+        );
+        assert_eq!(replacements("cd ../o"), (3, vec!["../other/".to_string()]));
+        assert_eq!(
+            replacements("ls logs/2"),
+            (3, vec!["logs/2024/".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_expiry_and_invalidation() {
+        let cache = completer().cache;
+        let dir = |name: &str| CompletionEntry {
+            name: name.to_string(),
+            is_dir: true,
+            size: None,
+        };
+        for path in [
+            "/",
+            "/data/logs",
+            "/data/logs/2024",
+            "/data/other",
+            "/data/a.zip@archive",
+        ] {
+            cache.update_entries(path.to_string(), vec![dir("x")]);
+        }
+
+        // A new key is stale for the directories above it, not beside it
+        cache.invalidate("data", "logs/2024/app.log");
+        let cached = |path: &str| cache.get_entries(path).is_some();
+        assert!(!cached("/data") && !cached("/data/logs") && !cached("/data/logs/2024"));
+        assert!(cached("/") && cached("/data/other") && cached("/data/a.zip@archive"));
+        // Replacing an archive drops its contents
+        cache.invalidate("data", "a.zip");
+        assert!(!cached("/data/a.zip@archive"));
+
+        cache.set_ttl(Duration::ZERO);
+        assert!(!cached("/"));
+    }
+
+    #[test]
+    fn test_fuzzy_matching() {
+        let fuzzy = MatchMode::Fuzzy;
+        assert_eq!(fuzzy.rank("events", "ev"), Some((0, 0)));
+        assert_eq!(fuzzy.rank("Events", "ev"), Some((1, 0)));
+        assert_eq!(fuzzy.rank("2024-events-archive", "ev"), Some((2, 5)));
+        assert_eq!(fuzzy.rank("e-x-v", "ev"), Some((3, 4)));
+        assert_eq!(fuzzy.rank("archive", "ev"), None);
+        assert_eq!(MatchMode::Prefix.rank("2024-events-archive", "ev"), None);
+        assert!(MatchMode::parse("Fuzzy").is_ok() && MatchMode::parse("glob").is_err());
+
+        let completer = completer();
+        let dir = |name: &str| CompletionEntry {
+            name: name.to_string(),
+            is_dir: true,
+            size: None,
+        };
+        completer.cache.update_entries(
+            "/data".to_string(),
+            vec![dir("2024-events-archive"), dir("reviews"), dir("events")],
+        );
+        let replacements = |line: &str| {
+            let (_, pairs) = completer.candidates(line, false);
+            pairs
+                .into_iter()
+                .map(|pair| pair.replacement)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(replacements("cd ev"), vec!["events/"]);
+        completer.cache.set_match_mode(MatchMode::Fuzzy);
+        // The exact prefix still comes first
+        assert_eq!(
+            replacements("cd ev"),
+            vec!["events/", "reviews/", "2024-events-archive/"]
+        );
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let commands = vec!["cat".to_string(), "ls".to_string()];
+        assert_eq!(
+            tokenize(&commands, "cat 'a b' | grep \"x\\\"\" && ls x"),
+            vec![
+                (Token::Command, "cat"),
+                (Token::Plain, " "),
+                (Token::Quoted, "'a b'"),
+                (Token::Plain, " | grep "),
+                (Token::Quoted, "\"x\\\"\""),
+                (Token::Plain, " && "),
+                (Token::Command, "ls"),
+                (Token::Plain, " x"),
+            ]
+        );
+        // Unknown commands and arguments named like commands stay plain
+        assert_eq!(
+            tokenize(&commands, "nope ls \"open"),
+            vec![(Token::Plain, "nope ls "), (Token::Quoted, "\"open")]
+        );
+        assert_eq!(
+            tokenize(&commands, "cat a\\ b"),
+            vec![(Token::Command, "cat"), (Token::Plain, " a\\ b")]
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use colored::Colorize;
+
+/// Lines of unchanged context shown around each change
+const CONTEXT: usize = 3;
+
+/// Above this many line comparisons the middle of the files is shown as replaced wholesale
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op<'a> {
+    Equal(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+/// Render a unified diff of two texts ("--- old", "+++ new", "@@" hunks)
+/// Returns None when the texts are identical.
+pub fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> Option<String> {
+    if old == new {
+        return None;
+    }
+
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = diff_lines(&old_lines, &new_lines);
+
+    let mut diff = format!(
+        "{}\n{}\n",
+        format!("--- {old_name}").bold(),
+        format!("+++ {new_name}").bold()
+    );
+    let changed: Vec<usize> = (0..ops.len())
+        .filter(|&i| !matches!(ops[i], Op::Equal(_)))
+        .collect();
+    if changed.is_empty() {
+        // Only line endings differ
+        diff.push_str("(line endings or trailing newline differ)\n");
+        return Some(diff);
+    }
+
+    // Group changes whose context windows touch into hunks
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &i in &changed {
+        let start = i.saturating_sub(CONTEXT);
+        let end = (i + CONTEXT + 1).min(ops.len());
+        match hunks.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    for (start, end) in hunks {
+        // Line numbers where the hunk starts in each file
+        let old_start = 1 + ops[..start]
+            .iter()
+            .filter(|op| !matches!(op, Op::Insert(_)))
+            .count();
+        let new_start = 1 + ops[..start]
+            .iter()
+            .filter(|op| !matches!(op, Op::Delete(_)))
+            .count();
+        let hunk = &ops[start..end];
+        let old_len = hunk
+            .iter()
+            .filter(|op| !matches!(op, Op::Insert(_)))
+            .count();
+        let new_len = hunk
+            .iter()
+            .filter(|op| !matches!(op, Op::Delete(_)))
+            .count();
+
+        diff.push_str(
+            &format!("@@ -{old_start},{old_len} +{new_start},{new_len} @@")
+                .cyan()
+                .to_string(),
+        );
+        diff.push('\n');
+        for op in hunk {
+            let line = match op {
+                Op::Equal(line) => format!(" {line}"),
+                Op::Delete(line) => format!("-{line}").red().to_string(),
+                Op::Insert(line) => format!("+{line}").green().to_string(),
+            };
+            diff.push_str(&line);
+            diff.push('\n');
+        }
+    }
+
+    Some(diff)
+}
+
+/// Line-level edit script: common prefix/suffix, then a longest-common-subsequence on the rest
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Op<'a>> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut ops: Vec<Op> = old[..prefix].iter().map(|line| Op::Equal(line)).collect();
+
+    if old_mid.len().saturating_mul(new_mid.len()) > MAX_LCS_CELLS {
+        ops.extend(old_mid.iter().map(|line| Op::Delete(line)));
+        ops.extend(new_mid.iter().map(|line| Op::Insert(line)));
+    } else {
+        // lcs[i][j] = length of the LCS of old_mid[i..] and new_mid[j..]
+        let (n, m) = (old_mid.len(), new_mid.len());
+        let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i][j] = if old_mid[i] == new_mid[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && old_mid[i] == new_mid[j] {
+                ops.push(Op::Equal(old_mid[i]));
+                i += 1;
+                j += 1;
+            } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+                // Deletions before insertions, as diff -u prints them
+                ops.push(Op::Delete(old_mid[i]));
+                i += 1;
+            } else {
+                ops.push(Op::Insert(new_mid[j]));
+                j += 1;
+            }
+        }
+    }
+
+    ops.extend(old[old.len() - suffix..].iter().map(|line| Op::Equal(line)));
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        colored::control::set_override(false);
+
+        assert_eq!(unified_diff("a\nb\n", "a\nb\n", "old", "new"), None);
+
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+        let new = "1\n2\n3\n4\nfive\n6\n7\n8\n9\n10\n11\n";
+        let diff = unified_diff(old, new, "a.txt", "a.txt (edited)").unwrap();
+        assert_eq!(
+            diff,
+            "--- a.txt\n+++ a.txt (edited)\n\
+             @@ -2,9 +2,10 @@\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n 9\n 10\n+11\n"
+        );
+    }
+
+    #[test]
+    fn test_separate_hunks() {
+        colored::control::set_override(false);
+
+        let old: String = (1..=20).map(|n| format!("{n}\n")).collect();
+        let new = old
+            .replacen("\n2\n", "\ntwo\n", 1)
+            .replacen("\n19\n", "\nnineteen\n", 1);
+        let diff = unified_diff(&old, &new, "old", "new").unwrap();
+        assert!(diff.contains("@@ -1,5 +1,5 @@\n 1\n-2\n+two\n"));
+        assert!(diff.contains("@@ -16,5 +16,5 @@\n 16\n 17\n 18\n-19\n+nineteen\n 20\n"));
+    }
+}
@@ -0,0 +1,152 @@
+use anyhow::{Result, anyhow};
+
+/// Number of entries kept when config.toml doesn't set `history_size`
+pub const DEFAULT_HISTORY_SIZE: usize = 1000;
+
+/// In-shell copy of the line editor's history, for `history` and `!N`/`!!` expansion
+/// main keeps it in step with rustyline, which owns the history file.
+#[derive(Debug, Clone)]
+pub struct History {
+    entries: Vec<String>,
+    max_size: usize,
+    /// Skip a line identical to the previous entry
+    ignore_dups: bool,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            max_size: DEFAULT_HISTORY_SIZE,
+            ignore_dups: true,
+        }
+    }
+}
+
+impl History {
+    /// Maximum number of entries kept
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    /// Whether consecutive duplicates are skipped
+    pub fn ignore_dups(&self) -> bool {
+        self.ignore_dups
+    }
+
+    /// Change the size limit, dropping the oldest entries if needed
+    pub fn set_max_size(&mut self, max_size: usize) {
+        self.max_size = max_size;
+        self.trim();
+    }
+
+    pub fn set_ignore_dups(&mut self, ignore_dups: bool) {
+        self.ignore_dups = ignore_dups;
+    }
+
+    /// All entries, oldest first (entry N is `entries()[N - 1]`)
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Record a line, returning whether it was added (blank lines and repeats are skipped)
+    pub fn add(&mut self, line: &str) -> bool {
+        if line.trim().is_empty() {
+            return false;
+        }
+        if self.ignore_dups && self.entries.last().is_some_and(|last| last == line) {
+            return false;
+        }
+        self.entries.push(line.to_string());
+        self.trim();
+        true
+    }
+
+    fn trim(&mut self) {
+        if self.entries.len() > self.max_size {
+            let excess = self.entries.len() - self.max_size;
+            self.entries.drain(..excess);
+        }
+    }
+
+    /// Expand a leading history reference, like bash's `!!`, `!N`, `!-N` and `!prefix`
+    /// Anything after the reference is kept, so `!! | grep foo` works. Returns None when the
+    /// line doesn't start with a reference.
+    pub fn expand(&self, line: &str) -> Result<Option<String>> {
+        let trimmed = line.trim_start();
+        let Some(reference) = trimmed.strip_prefix('!') else {
+            return Ok(None);
+        };
+        let end = reference
+            .find(char::is_whitespace)
+            .unwrap_or(reference.len());
+        let (event, rest) = reference.split_at(end);
+        if event.is_empty() {
+            return Ok(None);
+        }
+
+        let entry = if event == "!" {
+            self.entries.last()
+        } else if let Ok(n) = event.parse::<i64>() {
+            let index = if n < 0 {
+                self.entries.len().checked_sub(n.unsigned_abs() as usize)
+            } else {
+                (n as usize).checked_sub(1)
+            };
+            index.and_then(|i| self.entries.get(i))
+        } else {
+            self.entries
+                .iter()
+                .rev()
+                .find(|entry| entry.starts_with(event))
+        };
+
+        entry
+            .map(|entry| Some(format!("{entry}{rest}")))
+            .ok_or_else(|| anyhow!("!{event}: event not found"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_skips_duplicates_and_trims() {
+        let mut history = History::default();
+        assert!(history.add("ls"));
+        assert!(!history.add("ls"));
+        assert!(!history.add("  "));
+        assert!(history.add("cd bucket"));
+
+        history.set_ignore_dups(false);
+        assert!(history.add("cd bucket"));
+
+        history.set_max_size(2);
+        assert_eq!(history.entries(), ["cd bucket", "cd bucket"]);
+    }
+
+    #[test]
+    fn test_expand() {
+        let mut history = History::default();
+        history.add("cd my-bucket");
+        history.add("ls -l");
+        history.add("cat a.txt");
+
+        assert_eq!(history.expand("ls").unwrap(), None);
+        assert_eq!(history.expand("!!").unwrap().as_deref(), Some("cat a.txt"));
+        assert_eq!(
+            history.expand("!1").unwrap().as_deref(),
+            Some("cd my-bucket")
+        );
+        assert_eq!(history.expand("!-2").unwrap().as_deref(), Some("ls -l"));
+        assert_eq!(history.expand("!ls").unwrap().as_deref(), Some("ls -l"));
+        assert_eq!(
+            history.expand("!! | grep x").unwrap().as_deref(),
+            Some("cat a.txt | grep x")
+        );
+        assert!(history.expand("!0").is_err());
+        assert!(history.expand("!9").is_err());
+        assert!(history.expand("!nope").is_err());
+    }
+}
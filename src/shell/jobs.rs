@@ -0,0 +1,196 @@
+use anyhow::{Context, Result, anyhow};
+use futures::FutureExt;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tempfile::NamedTempFile;
+use tokio::task::JoinHandle;
+
+use super::{Output, ShellState};
+use crate::ui::JOB_STATUS;
+
+/// A command line running in the background (`CMD &`)
+/// Its output is buffered in a temporary file until the job is brought to the
+/// foreground or reported done, so it doesn't interleave with the prompt.
+pub struct Job {
+    pub id: usize,
+    pub line: String,
+    started: Instant,
+    /// Message of the job's latest spinner or progress bar
+    status: Arc<Mutex<String>>,
+    output: NamedTempFile,
+    handle: JoinHandle<Result<()>>,
+}
+
+impl Job {
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// Time since the job started
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// What the job is doing, e.g. "Downloading 120 files (37 lines of output)"
+    pub fn progress(&self) -> String {
+        let lines = self
+            .output()
+            .map(|output| output.iter().filter(|b| **b == b'\n').count())
+            .unwrap_or(0);
+        let lines = match lines {
+            1 => "1 line of output".to_string(),
+            n => format!("{n} lines of output"),
+        };
+        match self.status.lock().unwrap().as_str() {
+            "" => lines,
+            status => format!("{status} ({lines})"),
+        }
+    }
+
+    /// Everything the job has written so far
+    pub fn output(&self) -> Result<Vec<u8>> {
+        std::fs::read(self.output.path()).context("Failed to read the job's output")
+    }
+
+    /// Wait for the job to finish and return its result
+    /// If the wait is abandoned (Ctrl-C), the job keeps running and stays in the table.
+    pub async fn wait(&mut self) -> Result<()> {
+        join_result((&mut self.handle).await)
+    }
+
+    /// Stop the job at its next await point
+    pub fn kill(&self) {
+        self.handle.abort();
+    }
+}
+
+fn join_result(result: Result<Result<()>, tokio::task::JoinError>) -> Result<()> {
+    match result {
+        Ok(result) => result,
+        Err(e) if e.is_cancelled() => Err(anyhow!("Killed")),
+        Err(e) => Err(anyhow!("Job failed: {e}")),
+    }
+}
+
+/// Background jobs, numbered from 1 in the order they were started
+#[derive(Default)]
+pub struct Jobs {
+    jobs: Vec<Job>,
+}
+
+impl Jobs {
+    /// Run a command line on a copy of the shell in the background
+    pub fn spawn(&mut self, line: &str, mut worker: ShellState) -> Result<usize> {
+        let output = NamedTempFile::new().context("Failed to create a file for job output")?;
+        let mut out = Output::file(output.reopen()?);
+        let status = Arc::new(Mutex::new(String::new()));
+
+        let command = line.to_string();
+        let run = async move {
+            let result = worker.execute_line(&command, &mut out).await;
+            out.flush()?;
+            result
+        };
+        let handle = tokio::spawn(JOB_STATUS.scope(Arc::clone(&status), run));
+
+        // Numbers are reused once every job has been collected, like in sh
+        let id = self.jobs.last().map_or(1, |job| job.id + 1);
+        self.jobs.push(Job {
+            id,
+            line: line.to_string(),
+            started: Instant::now(),
+            status,
+            output,
+            handle,
+        });
+        Ok(id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Job> {
+        self.jobs.iter()
+    }
+
+    /// Jobs that haven't finished yet
+    pub fn running(&self) -> usize {
+        self.jobs.iter().filter(|job| !job.is_finished()).count()
+    }
+
+    /// A job by `%N` or `N`, or the most recent one
+    pub fn get_mut(&mut self, spec: Option<&str>) -> Result<&mut Job> {
+        let Some(spec) = spec else {
+            return self
+                .jobs
+                .last_mut()
+                .ok_or_else(|| anyhow!("No current job"));
+        };
+        let id: usize = spec
+            .strip_prefix('%')
+            .unwrap_or(spec)
+            .parse()
+            .map_err(|_| anyhow!("Invalid job: {spec} (use %N)"))?;
+        self.jobs
+            .iter_mut()
+            .find(|job| job.id == id)
+            .ok_or_else(|| anyhow!("No such job: %{id}"))
+    }
+
+    pub fn remove(&mut self, id: usize) -> Option<Job> {
+        let index = self.jobs.iter().position(|job| job.id == id)?;
+        Some(self.jobs.remove(index))
+    }
+
+    /// Take the jobs that have finished, with their results
+    pub fn take_finished(&mut self) -> Vec<(Job, Result<()>)> {
+        let (finished, running) = std::mem::take(&mut self.jobs)
+            .into_iter()
+            .partition(|job: &Job| job.is_finished());
+        self.jobs = running;
+        finished
+            .into_iter()
+            .map(|mut job| {
+                let result = (&mut job.handle)
+                    .now_or_never()
+                    .map_or_else(|| Err(anyhow!("Job still running")), join_result);
+                (job, result)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::s3::MockS3Client;
+
+    #[tokio::test]
+    async fn test_jobs() {
+        let state = ShellState::with_client(Arc::new(MockS3Client::demo().client()))
+            .await
+            .unwrap();
+        let mut jobs = Jobs::default();
+        let first = jobs
+            .spawn("cat /demo-bucket/README.md", state.worker())
+            .unwrap();
+        let second = jobs
+            .spawn("cat /demo-bucket/missing", state.worker())
+            .unwrap();
+        assert_eq!((first, second), (1, 2));
+        assert!(jobs.get_mut(Some("%3")).is_err());
+        assert_eq!(jobs.get_mut(None).unwrap().id, 2);
+
+        let job = jobs.get_mut(Some("%1")).unwrap();
+        job.wait().await.unwrap();
+        assert!(job.output().unwrap().starts_with(b"# Demo bucket"));
+        assert_eq!(job.progress(), "3 lines of output");
+        assert!(jobs.remove(1).is_some());
+
+        while jobs.running() > 0 {
+            tokio::task::yield_now().await;
+        }
+        let finished = jobs.take_finished();
+        assert_eq!(finished.len(), 1);
+        assert!(finished[0].1.is_err());
+        assert_eq!(jobs.iter().count(), 0);
+    }
+}
@@ -1,22 +1,119 @@
 pub mod commands;
 pub mod completion;
+pub mod diff;
+pub mod history;
+pub mod jobs;
+pub mod prompt;
+pub mod settings;
+pub mod theme;
 
-use anyhow::{Result, anyhow};
-use std::collections::HashMap;
+use anyhow::{Context, Result, anyhow};
+use colored::Colorize;
+use std::collections::{HashMap, VecDeque};
 use std::io::Write;
+use std::path::PathBuf;
 use std::process::{Command as ProcessCommand, Stdio};
 use std::sync::Arc;
 
 use crate::cache::ArchiveCache;
-use crate::s3::S3Client;
-use crate::vfs::{VfsNode, VirtualPath};
+use crate::config::Config;
+use crate::providers::ProviderConfig;
+use crate::s3::{MetricsSummary, Mount, S3Client};
+use crate::vfs::{PathResolver, VfsNode};
 use commands::Command;
-pub use completion::{CompletionCache, ShellCompleter};
+pub use commands::output::Output;
+use commands::output::print_line;
+use commands::tee::TeeOptions;
+pub use completion::{CompletionCache, CompletionEntry, ShellCompleter};
+pub use history::History;
+pub use jobs::Jobs;
+pub use settings::Settings;
+use theme::Theme;
+
+/// How a command in an `&&`/`||` list depends on the status of the one before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Chain {
+    /// `&&`: run only if the previous command succeeded
+    And,
+    /// `||`: run only if the previous command failed
+    Or,
+}
+
+/// Whether a command asked the shell to exit (`exit` or `quit`)
+fn is_exit(result: &Result<()>) -> bool {
+    matches!(result, Err(e) if e.to_string() == "exit")
+}
+
+/// Expand a leading `~/` in a local path like a shell would
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/").zip(dirs::home_dir()) {
+        Some((rest, home)) => home.join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Output redirection parsed from a command line
+struct Redirect {
+    /// Local file receiving the command's stdout
+    path: PathBuf,
+    /// `>>` appends instead of truncating
+    append: bool,
+}
+
+/// Shell process running the right-hand side of a pipe
+/// It is killed if the command is abandoned (Ctrl-C or a timeout) before it finishes,
+/// so e.g. a pager doesn't fight the prompt for the terminal.
+struct PipelineChild(Option<std::process::Child>);
+
+impl PipelineChild {
+    /// Start `pipeline` in the platform shell (`sh -c`, or `cmd /C` on Windows)
+    fn spawn(pipeline: &str) -> Result<Self> {
+        #[cfg(windows)]
+        let mut shell = {
+            let mut shell = ProcessCommand::new("cmd");
+            shell.arg("/C");
+            shell
+        };
+        #[cfg(not(windows))]
+        let mut shell = {
+            let mut shell = ProcessCommand::new("sh");
+            shell.arg("-c");
+            shell
+        };
+
+        let child = shell
+            .arg(pipeline)
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|e| anyhow!("Failed to spawn shell: {e}"))?;
+        Ok(Self(Some(child)))
+    }
+
+    /// Wait for the shell to exit and return its status code
+    fn wait(mut self) -> Result<Option<i32>> {
+        let mut child = self.0.take().expect("pipeline child already waited for");
+        let status = child
+            .wait()
+            .map_err(|e| anyhow!("Failed to wait for child: {e}"))?;
+        Ok(status.code())
+    }
+}
+
+impl Drop for PipelineChild {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.0.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
 
 /// Shell state - tracks current location and provides command execution
 pub struct ShellState {
     /// Current node in the virtual filesystem
     current_node: VfsNode,
+    /// Node before the last cd, for `cd -`
+    previous_node: Option<VfsNode>,
     /// S3 client
     s3_client: Arc<S3Client>,
     /// Archive cache
@@ -25,8 +122,45 @@ pub struct ShellState {
     completion_cache: CompletionCache,
     /// Registered commands
     commands: HashMap<String, Arc<dyn Command>>,
+    /// Runtime settings
+    settings: Settings,
+    /// Exit status of the last pipeline's shell command (None if the line had no pipe)
+    pipe_status: Option<i32>,
+    /// Exit status of the last command, for `$?` and `&&`/`||`
+    last_status: i32,
+    /// User configuration (aliases), saved back when changed
+    config: Config,
+    /// Command history, for `history` and `!N`/`!!`
+    history: History,
+    /// S3 usage of recent commands, oldest first, for `metrics`
+    metrics_log: VecDeque<(String, MetricsSummary)>,
+    /// S3 usage of every command since the session started (or `metrics reset`)
+    session_metrics: MetricsSummary,
+    /// Command lines running in the background
+    jobs: Jobs,
+    /// `exit` was refused once because jobs were running; the next one goes through
+    exit_warned: bool,
+    /// Listing colors, from the config's `[theme]` table
+    theme: Theme,
 }
 
+/// Number of commands whose S3 usage is kept for `metrics per-command`
+const METRICS_LOG_SIZE: usize = 100;
+
+/// Commands handled by the shell itself rather than the registry, with their usage
+const BUILTINS: &[(&str, &str)] = &[
+    ("pwd", "pwd - Print working directory"),
+    (
+        "help",
+        "help [COMMAND] - List commands, or show one command's usage and flags",
+    ),
+    ("exit", "exit - Exit the shell (also quit)"),
+    (
+        "echo",
+        "echo [WORD]... - Print words, e.g. `echo $?` for the last command's exit status",
+    ),
+];
+
 impl ShellState {
     /// Create a new shell state
     pub async fn new() -> Result<Self> {
@@ -41,16 +175,67 @@ impl ShellState {
 
         let mut state = ShellState {
             current_node: VfsNode::Root,
+            previous_node: None,
             s3_client,
             cache,
             completion_cache,
             commands: HashMap::new(),
+            settings: Settings::default(),
+            pipe_status: None,
+            last_status: 0,
+            config: Config::default(),
+            history: History::default(),
+            metrics_log: VecDeque::new(),
+            session_metrics: MetricsSummary::default(),
+            jobs: Jobs::default(),
+            exit_warned: false,
+            theme: Theme::default(),
         };
 
         // Register commands
         state.register_command(Arc::new(commands::ls::LsCommand));
         state.register_command(Arc::new(commands::cd::CdCommand));
         state.register_command(Arc::new(commands::cat::CatCommand));
+        state.register_command(Arc::new(commands::zcat::ZcatCommand));
+        state.register_command(Arc::new(commands::zcat::ZgrepCommand));
+        state.register_command(Arc::new(commands::xxd::XxdCommand));
+        state.register_command(Arc::new(commands::strings::StringsCommand));
+        state.register_command(Arc::new(commands::wc::WcCommand));
+        state.register_command(Arc::new(commands::diff::DiffCommand));
+        state.register_command(Arc::new(commands::compare::CompareCommand));
+        state.register_command(Arc::new(commands::watch::WatchCommand));
+        state.register_command(Arc::new(commands::select::SelectCommand));
+        state.register_command(Arc::new(commands::stat::StatCommand));
+        state.register_command(Arc::new(commands::search::SearchCommand));
+        state.register_command(Arc::new(commands::tags::TagsCommand));
+        state.register_command(Arc::new(commands::meta::MetaCommand));
+        state.register_command(Arc::new(commands::file::FileCommand));
+        state.register_command(Arc::new(commands::checksum::ChecksumCommand));
+        state.register_command(Arc::new(commands::open::OpenCommand));
+        state.register_command(Arc::new(commands::edit::EditCommand));
+        state.register_command(Arc::new(commands::get::GetCommand));
+        state.register_command(Arc::new(commands::sync::SyncCommand));
+        state.register_command(Arc::new(commands::foreach::ForeachCommand));
+        state.register_command(Arc::new(commands::tee::TeeCommand));
+        state.register_command(Arc::new(commands::whoami::WhoamiCommand));
+        state.register_command(Arc::new(commands::bucket::MbCommand));
+        state.register_command(Arc::new(commands::bucket::RbCommand));
+        state.register_command(Arc::new(commands::bucket::BucketCommand));
+        state.register_command(Arc::new(commands::profile::ProfileCommand));
+        state.register_command(Arc::new(commands::provider::ProviderCommand));
+        #[cfg(feature = "parquet")]
+        state.register_command(Arc::new(commands::query::QueryCommand));
+        state.register_command(Arc::new(commands::set::SetCommand));
+        state.register_command(Arc::new(commands::config::ConfigCommand));
+        state.register_command(Arc::new(commands::alias::AliasCommand));
+        state.register_command(Arc::new(commands::alias::UnaliasCommand));
+        state.register_command(Arc::new(commands::history::HistoryCommand));
+        state.register_command(Arc::new(commands::metrics::MetricsCommand));
+        state.register_command(Arc::new(commands::cost::CostCommand));
+        state.register_command(Arc::new(commands::jobs::JobsCommand));
+        state.register_command(Arc::new(commands::jobs::FgCommand));
+        state.register_command(Arc::new(commands::jobs::KillCommand));
+        state.register_command(Arc::new(commands::refresh::RefreshCommand));
 
         Ok(state)
     }
@@ -64,13 +249,44 @@ impl ShellState {
     ) -> Self {
         ShellState {
             current_node,
+            previous_node: None,
             s3_client,
             cache,
             completion_cache,
             commands: HashMap::new(),
+            settings: Settings::default(),
+            pipe_status: None,
+            last_status: 0,
+            config: Config::default(),
+            history: History::default(),
+            metrics_log: VecDeque::new(),
+            session_metrics: MetricsSummary::default(),
+            jobs: Jobs::default(),
+            exit_warned: false,
+            theme: Theme::default(),
         }
     }
 
+    /// A copy of the shell for running command lines alongside it
+    /// It has its own working directory (starting at this one) and exit status, but
+    /// shares the client, caches, commands, and config. Sharing the client means sharing
+    /// its metrics, so requests made while both run count toward the shell's command too,
+    /// and the settings the shell applies to the client before each command (transfer,
+    /// retry, read-only, extract limits) take effect in the worker as well.
+    pub(crate) fn worker(&self) -> ShellState {
+        let mut worker = ShellState::from_components(
+            self.current_node.clone(),
+            Arc::clone(&self.s3_client),
+            self.cache.clone(),
+            CompletionCache::new(Arc::clone(&self.s3_client), self.cache.clone()),
+        );
+        worker.commands = self.commands.clone();
+        worker.settings = self.settings.clone();
+        worker.config = self.config.clone();
+        worker.theme = self.theme.clone();
+        worker
+    }
+
     /// Register a command
     fn register_command(&mut self, command: Arc<dyn Command>) {
         self.commands.insert(command.name().to_string(), command);
@@ -81,89 +297,346 @@ impl ShellState {
         self.register_command(command);
     }
 
-    /// Execute a command line
+    /// Execute a command line, printing to stdout
     pub async fn execute(&mut self, line: &str) -> Result<()> {
-        let line = line.trim();
+        self.execute_to(line, &mut Output::stdout()).await
+    }
+
+    /// Execute a command line, giving up after the `timeout` setting if one is set
+    /// Output goes to `out` unless the line pipes or redirects it elsewhere.
+    #[tracing::instrument(level = "debug", skip(self, out))]
+    pub async fn execute_to(&mut self, line: &str, out: &mut Output) -> Result<()> {
+        // Count this command's requests from here, so a timeout can say how far it got.
+        // Background jobs share the counters, so they're only cleared while none runs.
+        let metrics = self.s3_client.metrics().cloned();
+        let start = metrics.as_ref().map(|metrics| {
+            if self.jobs.running() == 0 {
+                metrics.reset();
+                metrics.start_operation();
+            }
+            metrics.summary()
+        });
+        self.s3_client.set_transfer_options(self.settings.transfer);
+        self.s3_client.set_retry_policy(self.settings.retry);
+        self.s3_client.set_read_only(self.settings.read_only);
+        self.s3_client
+            .set_extract_limits(self.settings.extract_limits);
+        self.s3_client
+            .set_inventory_locations(self.config.inventories());
+        self.s3_client
+            .listing_cache()
+            .set_ttl(self.settings.listing_ttl);
+
+        // Commands that wait on the user (e.g. an editor) aren't subject to the timeout
+        let timeout = self
+            .settings
+            .timeout
+            .filter(|_| !self.command_for(line).is_some_and(|c| c.interactive()));
+        let result = match timeout {
+            None => self.execute_line(line, out).await,
+            Some(timeout) => {
+                match tokio::time::timeout(timeout, self.execute_line(line, out)).await {
+                    Ok(result) => result,
+                    Err(_) => {
+                        let progress = metrics
+                            .as_ref()
+                            .zip(start.as_ref())
+                            .map(|(m, start)| {
+                                let summary = m.summary().since(start);
+                                format!(
+                                    " ({} transferred in {} completed requests)",
+                                    humansize::format_size(
+                                        summary.downloaded + summary.uploaded,
+                                        humansize::DECIMAL
+                                    ),
+                                    summary.requests()
+                                )
+                            })
+                            .unwrap_or_default();
+                        Err(anyhow!("Timed out after {}s{progress}", timeout.as_secs()))
+                    }
+                }
+            }
+        };
+
+        if let Some((metrics, start)) = metrics.as_ref().zip(start.as_ref())
+            && self.command_for(line).is_none_or(|c| c.records_metrics())
+        {
+            self.record_metrics(line.trim(), metrics.summary().since(start));
+        }
+        if let Err(e) = &result {
+            tracing::debug!("command failed: {e:#}");
+        }
+        if !is_exit(&result) {
+            self.record_status(&result);
+        }
+        result
+    }
+
+    /// Exit status of the last command line: 0 on success, 1 if the command failed, or
+    /// the status of the piped shell command if that failed (e.g. grep with no matches)
+    pub fn last_status(&self) -> i32 {
+        self.last_status
+    }
+
+    /// Set the status `$?` reports, e.g. 130 after a command is interrupted
+    pub fn set_last_status(&mut self, status: i32) {
+        self.last_status = status;
+    }
+
+    fn record_status(&mut self, result: &Result<()>) {
+        self.last_status = match result {
+            Ok(()) => self.pipe_status.unwrap_or(0),
+            Err(_) => 1,
+        };
+    }
+
+    /// The registered command a line runs, after alias expansion
+    fn command_for(&self, line: &str) -> Option<&Arc<dyn Command>> {
+        let line = self.expand_alias(line.trim());
+        line.split_whitespace()
+            .next()
+            .and_then(|name| self.commands.get(name))
+    }
+
+    /// Log a command's S3 usage for the `metrics` command
+    fn record_metrics(&mut self, line: &str, summary: MetricsSummary) {
+        if line.is_empty() {
+            return;
+        }
+        self.session_metrics.add(&summary);
+        if self.metrics_log.len() == METRICS_LOG_SIZE {
+            self.metrics_log.pop_front();
+        }
+        self.metrics_log.push_back((line.to_string(), summary));
+    }
+
+    /// S3 usage of recent commands, oldest first
+    pub fn metrics_log(&self) -> &VecDeque<(String, MetricsSummary)> {
+        &self.metrics_log
+    }
+
+    /// S3 usage of every command since the session started or was reset
+    pub fn session_metrics(&self) -> &MetricsSummary {
+        &self.session_metrics
+    }
+
+    /// Forget all recorded S3 usage
+    pub fn reset_metrics(&mut self) {
+        self.metrics_log.clear();
+        self.session_metrics = MetricsSummary::default();
+    }
+
+    /// Execute a command line without a time limit
+    /// Commands joined by `&&` or `||` run depending on the status of the one before.
+    async fn execute_line(&mut self, line: &str, out: &mut Output) -> Result<()> {
+        if let Some(line) = Self::split_background(line) {
+            let id = self.jobs.spawn(line, self.worker())?;
+            print_line!(out, "[{id}] {line}");
+            return Ok(());
+        }
+
+        let mut result = Ok(());
+        for (chain, command) in Self::split_chain(line)? {
+            let skip = match chain {
+                Some(Chain::And) => self.last_status != 0,
+                Some(Chain::Or) => self.last_status == 0,
+                None => false,
+            };
+            if skip {
+                continue;
+            }
+            // A failure followed by another command (`a || b`) is reported here,
+            // since only the last result is returned
+            if let Err(e) = result {
+                eprintln!("{} {e}", "Error:".red().bold());
+            }
+            result = self.execute_command(&command, out).await;
+            if is_exit(&result) {
+                return result;
+            }
+            self.record_status(&result);
+        }
+        result
+    }
+
+    /// Execute one command of a line, with any pipe or redirection
+    async fn execute_command(&mut self, line: &str, out: &mut Output) -> Result<()> {
+        let line = self.expand_status(line.trim());
+        let line = self.expand_alias(&line);
+        let line = line.as_str();
+        self.pipe_status = None;
         if line.is_empty() {
             return Ok(());
         }
 
+        // Commands like foreach run the text after ` -- ` themselves, pipes and all
+        if let Some(command) = line
+            .split_whitespace()
+            .next()
+            .and_then(|name| self.commands.get(name))
+            .filter(|command| command.takes_command_line())
+            .cloned()
+            && let Some(i) = Self::find_unquoted(line, " -- ")
+        {
+            let mut args = Self::parse_command_line(&line[..i])?;
+            args.remove(0);
+            args.push("--".to_string());
+            args.push(line[i + 4..].trim().to_string());
+            let result = command.execute(self, &args, out).await;
+            let _ = out.flush();
+            return result;
+        }
+
         // Check if there's a pipe in the command
         let (command_part, pipeline_part) = Self::split_pipeline(line);
 
         if let Some(pipeline) = pipeline_part {
-            // Execute command with output piped to shell
-            self.execute_with_pipe(&command_part, &pipeline).await
-        } else {
+            // `| tee` runs here rather than in sh, so the copy can go to S3
+            let (tee, rest) = Self::split_pipeline(&pipeline);
+            if tee.split_whitespace().next() == Some("tee") {
+                let tee_args = Self::parse_command_line(&tee)?;
+                return self
+                    .execute_with_tee(&command_part, &tee_args[1..], rest.as_deref(), out)
+                    .await;
+            }
+            // Execute command with output piped to shell (which handles any redirection itself)
+            return self.execute_with_pipe(&command_part, &pipeline).await;
+        }
+
+        match Self::split_redirect(line)? {
+            (command, Some(redirect)) => self.execute_with_redirect(&command, &redirect).await,
             // Normal execution
-            self.execute_internal(line).await
+            (_, None) => {
+                let result = self.execute_internal(line, out).await;
+                let _ = out.flush();
+                result
+            }
         }
     }
 
-    /// Execute a command with its output piped to a shell command
-    #[cfg(unix)]
-    async fn execute_with_pipe(&mut self, command: &str, pipeline: &str) -> Result<()> {
-        use std::os::unix::io::AsRawFd;
+    /// Replace a leading alias name with its expansion (one level, so aliases can't loop)
+    /// Expansion happens before pipes are split, so aliases may contain pipes and redirections
+    fn expand_alias(&self, line: &str) -> String {
+        let (word, rest) = line
+            .split_once(char::is_whitespace)
+            .map_or((line, ""), |(word, rest)| (word, rest));
 
-        // Spawn shell process with the pipeline
-        let mut child = ProcessCommand::new("sh")
-            .arg("-c")
-            .arg(pipeline)
-            .stdin(Stdio::piped())
-            .spawn()
-            .map_err(|e| anyhow!("Failed to spawn shell: {e}"))?;
+        match self.config.alias(word) {
+            Some(expansion) if rest.is_empty() => expansion.to_string(),
+            Some(expansion) => format!("{expansion} {rest}"),
+            None => line.to_string(),
+        }
+    }
 
-        let child_stdin = child
-            .stdin
-            .take()
-            .ok_or_else(|| anyhow!("Failed to open stdin"))?;
-        let child_fd = child_stdin.as_raw_fd();
-        let stdout_fd = std::io::stdout().as_raw_fd();
+    /// Execute a command with its output written to a local file (`>` or `>>`)
+    async fn execute_with_redirect(&mut self, command: &str, redirect: &Redirect) -> Result<()> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(redirect.append)
+            .truncate(!redirect.append)
+            .open(&redirect.path)
+            .map_err(|e| anyhow!("Cannot write to {}: {e}", redirect.path.display()))?;
+
+        let mut out = Output::file(file);
+        let result = self.execute_internal(command, &mut out).await;
+        out.flush()
+            .map_err(|e| anyhow!("Cannot write to {}: {e}", redirect.path.display()))?;
+        result
+    }
+
+    /// Execute a command with its output copied to a local file or S3 object (`| tee FILE`)
+    /// The output also goes on to `out`, or to the rest of the pipeline after tee.
+    async fn execute_with_tee(
+        &mut self,
+        command: &str,
+        tee_args: &[String],
+        rest: Option<&str>,
+        out: &mut Output,
+    ) -> Result<()> {
+        let options = TeeOptions::parse(tee_args)?;
+        // Check the destination before running the command, so its output isn't lost
+        let (copy, object) = if options.remote {
+            let (bucket, key) = commands::resolve_object_key(self, &options.path).await?;
+            self.s3_client.check_writable("upload to", &bucket, &key)?;
+            (Output::capture(), Some((bucket, key)))
+        } else {
+            let path = expand_home(&options.path);
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .write(true)
+                .append(options.append)
+                .truncate(!options.append)
+                .open(&path)
+                .map_err(|e| anyhow!("Cannot write to {}: {e}", path.display()))?;
+            (Output::file(file), None)
+        };
 
-        // Duplicate stdout and redirect to pipe, with proper cleanup on error
-        // SAFETY: All libc calls operate on valid file descriptors obtained from Rust types
-        let saved_stdout = unsafe { libc::dup(stdout_fd) };
-        if saved_stdout < 0 {
-            drop(child_stdin);
-            child.kill().ok();
-            return Err(anyhow!("Failed to duplicate stdout"));
+        let mut child = rest.map(PipelineChild::spawn).transpose()?;
+        let output = match &mut child {
+            Some(child) => Output::pipe(
+                child
+                    .0
+                    .as_mut()
+                    .and_then(|c| c.stdin.take())
+                    .ok_or_else(|| anyhow!("Failed to open stdin"))?,
+            ),
+            None => std::mem::replace(out, Output::capture()),
+        };
+        let mut tee = Output::tee(output, copy);
+        let result = self.execute_internal(command, &mut tee).await;
+        let Output::Tee(mut output, mut copy) = tee else {
+            unreachable!("built as a tee");
+        };
+        // The reader after tee may be gone already (e.g. `| tee log | head`)
+        let _ = output.flush();
+        match child {
+            Some(child) => {
+                drop(output);
+                self.pipe_status = child.wait()?;
+            }
+            None => *out = *output,
         }
+        result?;
 
-        if unsafe { libc::dup2(child_fd, stdout_fd) } < 0 {
-            unsafe { libc::close(saved_stdout) };
-            drop(child_stdin);
-            child.kill().ok();
-            return Err(anyhow!("Failed to redirect stdout"));
+        copy.flush()
+            .map_err(|e| anyhow!("Cannot write to {}: {e}", options.path))?;
+        if let (Some((bucket, key)), Output::Capture(body)) = (object, *copy) {
+            self.s3_client
+                .put_object(&bucket, &key, body.into())
+                .await?;
+            self.completion_cache.invalidate(&bucket, &key);
         }
+        Ok(())
+    }
 
-        // Execute the command (writes to the redirected stdout)
-        let result = self.execute_internal(command).await;
+    /// Execute a command with its output piped to a shell command
+    /// The output is written straight into the child's stdin, so this works on every platform.
+    async fn execute_with_pipe(&mut self, command: &str, pipeline: &str) -> Result<()> {
+        let mut child = PipelineChild::spawn(pipeline)?;
+        let child_stdin = child
+            .0
+            .as_mut()
+            .and_then(|c| c.stdin.take())
+            .ok_or_else(|| anyhow!("Failed to open stdin"))?;
 
-        // Restore stdout and clean up
-        let _ = std::io::stdout().flush();
-        // SAFETY: Restoring saved_stdout to stdout_fd, then closing the duplicate
-        unsafe {
-            libc::dup2(saved_stdout, stdout_fd);
-            libc::close(saved_stdout);
-        }
+        // Execute the command with its output going to the pipe
+        let mut out = Output::pipe(child_stdin);
+        let result = self.execute_internal(command, &mut out).await;
 
-        // Close the pipe to signal EOF, then wait for the child
-        drop(child_stdin);
-        child
-            .wait()
-            .map_err(|e| anyhow!("Failed to wait for child: {e}"))?;
+        // Flush and close the pipe to signal EOF, then wait for the child
+        // (the reader may be gone already, e.g. `| head`)
+        let _ = out.flush();
+        drop(out);
+        self.pipe_status = child.wait()?;
 
-        // Return the command's result (child exit status is intentionally ignored)
+        // Return the command's result; the child's status is only reported via pipe_status()
         result
     }
 
-    #[cfg(not(unix))]
-    async fn execute_with_pipe(&mut self, _command: &str, _pipeline: &str) -> Result<()> {
-        Err(anyhow!("Pipe support is only available on Unix systems"))
-    }
-
     /// Internal execute for normal (non-piped) commands
-    async fn execute_internal(&mut self, line: &str) -> Result<()> {
+    async fn execute_internal(&mut self, line: &str, out: &mut Output) -> Result<()> {
         // Parse command line respecting quotes
         let parts = Self::parse_command_line(line)?;
 
@@ -172,33 +645,105 @@ impl ShellState {
         }
 
         let cmd_name = &parts[0];
-        let args = &parts[1..];
+        // s3://bucket/key URLs are accepted anywhere a path is
+        let keep_urls = self
+            .commands
+            .get(cmd_name)
+            .is_some_and(|command| command.keeps_s3_urls());
+        let args: Vec<String> = parts[1..]
+            .iter()
+            .map(|arg| match arg.strip_prefix("s3://") {
+                Some(rest) if !keep_urls => format!("/{rest}"),
+                _ => arg.clone(),
+            })
+            .collect();
+        let args = args.as_slice();
+
+        // `NAME --help` shows the same page as `help NAME`
+        if args.iter().any(|arg| arg == "--help") && self.usage_of(cmd_name).is_some() {
+            return self.print_command_help(cmd_name, out);
+        }
 
         // Check for built-in commands first
         match cmd_name.as_str() {
             "exit" | "quit" => {
+                // Jobs die with the shell, so make sure that's intended
+                if self.jobs.running() > 0 && !self.exit_warned {
+                    self.exit_warned = true;
+                    return Err(anyhow!(
+                        "There are running jobs (see `jobs`); exit again to stop them"
+                    ));
+                }
                 return Err(anyhow!("exit"));
             }
             "help" => {
-                self.print_help();
-                return Ok(());
+                return match args.first() {
+                    Some(name) => self.print_command_help(name, out),
+                    None => self.print_help(out),
+                };
             }
             "pwd" => {
-                println!("{}", self.current_path());
+                print_line!(out, "{}", self.current_path());
+                return Ok(());
+            }
+            "echo" => {
+                print_line!(out, "{}", parts[1..].join(" "));
                 return Ok(());
             }
             _ => {}
         }
 
-        // Look up command
-        if let Some(command) = self.commands.get(cmd_name) {
+        self.run_command(cmd_name, args, out).await
+    }
+
+    /// Exit status of the shell side of the last executed pipeline, if the line had one
+    pub fn pipe_status(&self) -> Option<i32> {
+        self.pipe_status
+    }
+
+    /// Run a registered command with already-parsed arguments
+    /// Bypasses quoting and pipes, so arguments may contain any characters
+    pub async fn run_command(
+        &mut self,
+        name: &str,
+        args: &[String],
+        out: &mut Output,
+    ) -> Result<()> {
+        if let Some(command) = self.commands.get(name) {
             let cmd = Arc::clone(command);
-            cmd.execute(self, args).await
+            cmd.execute(self, args, out).await
         } else {
-            Err(anyhow!("Unknown command: {cmd_name}"))
+            match self.suggest_command(name) {
+                Some(suggestion) => Err(anyhow!(
+                    "Unknown command: {name} (did you mean '{suggestion}'?)"
+                )),
+                None => Err(anyhow!("Unknown command: {name}")),
+            }
         }
     }
 
+    /// The command, built-in, or alias closest to a mistyped name, if any is close enough
+    /// Transpositions count as one edit, so `sl` suggests `ls`.
+    fn suggest_command(&self, name: &str) -> Option<String> {
+        let max_distance = (name.chars().count() / 3).max(1);
+        let mut candidates: Vec<String> = self
+            .commands
+            .keys()
+            .cloned()
+            .chain(BUILTINS.iter().map(|(builtin, _)| builtin.to_string()))
+            .chain(std::iter::once("quit".to_string()))
+            .chain(self.config.aliases().into_keys())
+            .collect();
+        // Sorted so ties are broken the same way every time
+        candidates.sort();
+        candidates
+            .into_iter()
+            .map(|candidate| (strsim::damerau_levenshtein(name, &candidate), candidate))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, candidate)| candidate)
+    }
+
     /// Get the current node
     pub fn current_node(&self) -> &VfsNode {
         &self.current_node
@@ -210,82 +755,487 @@ impl ShellState {
         self.completion_cache.set_current_node(node);
     }
 
+    /// Move to a new node, remembering the current one for `cd -`
+    pub fn change_dir(&mut self, node: VfsNode) {
+        self.previous_node = Some(self.current_node.clone());
+        self.set_current_node(node);
+    }
+
+    /// Get the node before the last directory change
+    pub fn previous_node(&self) -> Option<&VfsNode> {
+        self.previous_node.as_ref()
+    }
+
     /// Get the S3 client
     pub fn s3_client(&self) -> &Arc<S3Client> {
         &self.s3_client
     }
 
+    /// Swap in a new S3 client (e.g. after switching profile)
+    /// Archive indexes and completions built with the old client are dropped, and the
+    /// mount table is rebuilt on top of the new client.
+    pub async fn replace_client(&mut self, s3_client: Arc<S3Client>) {
+        self.completion_cache.set_s3_client(Arc::clone(&s3_client));
+        self.completion_cache.clear();
+        self.s3_client = s3_client;
+        self.cache.clear();
+        self.previous_node = None;
+        self.remount().await;
+    }
+
+    /// Build the `[mounts]` table from the configuration and route those buckets to it
+    /// Mounts that can't be built are left out, and the first failure is returned.
+    pub async fn mount_buckets(&mut self) -> Result<()> {
+        let registry = crate::providers::ProviderRegistry::new();
+        let mut mounts = Vec::new();
+        let mut first_error = None;
+        for entry in self.config.mounts() {
+            let mount = async {
+                let provider = registry
+                    .get(&entry.provider)
+                    .ok_or_else(|| anyhow!("Unknown provider: {}", entry.provider))?;
+                let mut config = provider.build_config().await?;
+                self.config.apply_to_provider(&mut config);
+                if let Some(profile) = &entry.profile {
+                    config.profile = Some(profile.clone());
+                }
+                if let Some(region) = &entry.region {
+                    config.default_region = Some(region.clone());
+                }
+                self.s3_client.mount(provider.name(), config).await
+            };
+            match mount.await {
+                Ok(client) => mounts.push(Mount {
+                    pattern: entry.pattern,
+                    client: Arc::new(client),
+                }),
+                Err(e) => {
+                    first_error.get_or_insert_with(|| {
+                        e.context(format!("Failed to mount {}", entry.pattern))
+                    });
+                }
+            }
+        }
+        self.s3_client.set_mounts(mounts);
+        self.completion_cache.clear();
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// Rebuild the mounts for a new client; failures were already reported at startup
+    async fn remount(&mut self) {
+        if let Err(e) = self.mount_buckets().await {
+            tracing::debug!("{e:#}");
+        }
+    }
+
+    /// Rebuild the S3 client from its provider so expired credentials are re-read
+    /// SSO profiles go through `aws sso login` first. The current directory and
+    /// caches are kept, except Parquet indexes whose object store holds the old keys.
+    pub async fn reload_credentials(&mut self) -> Result<()> {
+        let (Some(name), Some(config)) = (
+            self.s3_client.provider_name().map(str::to_string),
+            self.s3_client.provider_config().cloned(),
+        ) else {
+            return Err(anyhow!(
+                "Credentials can only be reloaded for provider-based shells"
+            ));
+        };
+
+        let profile = config
+            .profile
+            .clone()
+            .or_else(|| std::env::var("AWS_PROFILE").ok())
+            .unwrap_or_else(|| "default".to_string());
+        if crate::providers::profile_uses_sso(&profile) {
+            let status = ProcessCommand::new("aws")
+                .args(["sso", "login", "--profile", &profile])
+                .status()
+                .context("Failed to run `aws sso login` (is the AWS CLI installed?)")?;
+            if !status.success() {
+                return Err(anyhow!("`aws sso login --profile {profile}` failed"));
+            }
+        }
+
+        self.rebuild_client(&name, config).await
+    }
+
+    /// Whether requests go out unsigned
+    pub fn anonymous(&self) -> bool {
+        self.s3_client
+            .provider_config()
+            .is_some_and(|config| config.anonymous)
+    }
+
+    /// Rebuild the S3 client to send unsigned requests, or to sign them again
+    /// The profile or keys the shell started with are kept for switching back.
+    pub async fn set_anonymous(&mut self, anonymous: bool) -> Result<()> {
+        let (Some(name), Some(mut config)) = (
+            self.s3_client.provider_name().map(str::to_string),
+            self.s3_client.provider_config().cloned(),
+        ) else {
+            return Err(anyhow!(
+                "Anonymous access can only be changed for provider-based shells"
+            ));
+        };
+        if config.anonymous == anonymous {
+            return Ok(());
+        }
+        config.anonymous = anonymous;
+        self.rebuild_client(&name, config).await
+    }
+
+    /// Swap in a client built from new provider settings, keeping the current directory
+    /// and caches except Parquet indexes, whose object store holds the old credentials
+    async fn rebuild_client(&mut self, name: &str, config: ProviderConfig) -> Result<()> {
+        let s3_client = Arc::new(S3Client::from_provider(name, config).await?);
+        self.completion_cache.set_s3_client(Arc::clone(&s3_client));
+        self.s3_client = s3_client;
+        #[cfg(feature = "parquet")]
+        self.cache.retain(|index| index.parquet_store.is_none());
+        self.remount().await;
+
+        Ok(())
+    }
+
+    /// Get the user configuration
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Get mutable access to the user configuration
+    pub fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
+    }
+
+    /// Replace the user configuration (e.g. with one loaded from disk at startup)
+    pub fn set_config(&mut self, config: Config) {
+        self.config = config;
+        self.apply_config();
+    }
+
+    /// Apply the configuration's session-level values (settings, cache size, history, color,
+    /// theme)
+    /// Startup-only values (provider, region) are read by main
+    pub fn apply_config(&mut self) {
+        if let Some(confirm) = self.config.confirm() {
+            self.settings.confirm = confirm;
+        }
+        if let Some(threshold) = self.config.archive_threshold() {
+            self.settings.archive_threshold = threshold;
+        }
+        if let Some(timeout) = self.config.timeout() {
+            self.settings.timeout = timeout;
+        }
+        if let Some(part_size) = self.config.part_size() {
+            self.settings.transfer.part_size = part_size;
+        }
+        if let Some(concurrency) = self.config.part_concurrency() {
+            self.settings.transfer.concurrency = concurrency;
+        }
+        if let Some(concurrency) = self.config.list_concurrency() {
+            self.settings.transfer.list_concurrency = concurrency;
+        }
+        if let Some(read_ahead) = self.config.read_ahead() {
+            self.settings.transfer.read_ahead = read_ahead;
+        }
+        if let Some(bandwidth) = self.config.bandwidth() {
+            self.settings.transfer.bandwidth = bandwidth;
+        }
+        if let Some(max_size) = self.config.max_extract_size() {
+            self.settings.extract_limits.max_size = max_size;
+        }
+        if let Some(max_ratio) = self.config.max_compression_ratio() {
+            self.settings.extract_limits.max_ratio = max_ratio;
+        }
+        if let Some(attempts) = self.config.retry_attempts() {
+            self.settings.retry.max_attempts = attempts;
+        }
+        if let Some(backoff) = self.config.retry_backoff() {
+            self.settings.retry.initial_backoff = backoff;
+        }
+        if let Some(retry_on) = self.config.retry_on() {
+            self.settings.retry.retry_on = retry_on;
+        }
+        if let Some(ttl) = self.config.listing_ttl() {
+            self.settings.listing_ttl = ttl;
+        }
+        if let Some(read_only) = self.config.read_only() {
+            self.settings.read_only = read_only;
+        }
+        if let Some(size) = self.config.cache_size() {
+            self.cache.resize(size);
+        }
+        self.completion_cache.set_ttl(
+            self.config
+                .completion_ttl()
+                .unwrap_or(completion::DEFAULT_COMPLETION_TTL),
+        );
+        self.completion_cache
+            .set_match_mode(self.config.completion_mode().unwrap_or_default());
+        self.history.set_max_size(
+            self.config
+                .history_size()
+                .unwrap_or(history::DEFAULT_HISTORY_SIZE),
+        );
+        self.history
+            .set_ignore_dups(self.config.history_dedup().unwrap_or(true));
+        if let Some(color) = self.config.color() {
+            crate::ui::set_color(Some(color));
+        }
+        self.theme = Theme::from_config(&self.config);
+    }
+
+    /// Listing colors
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Get the command history
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Get mutable access to the command history
+    pub fn history_mut(&mut self) -> &mut History {
+        &mut self.history
+    }
+
     /// Get the cache
     pub fn cache(&self) -> &ArchiveCache {
         &self.cache
     }
 
+    /// A path resolver for this session; it asks before large archive scans when
+    /// `confirm` is on
+    pub fn resolver(&self) -> PathResolver {
+        PathResolver::new(Arc::clone(&self.s3_client), self.cache.clone()).confirm_above(
+            self.settings
+                .confirm
+                .then_some(self.settings.archive_threshold),
+        )
+    }
+
     /// Get the completion cache
     pub fn completion_cache(&self) -> &CompletionCache {
         &self.completion_cache
     }
 
-    /// Update completion cache with current directory entries
-    pub fn update_completions(&self, path: String, entry_names: Vec<String>) {
-        // Convert string names to CompletionEntry
-        // We don't have is_dir info from cd, but the lazy loader will fetch accurate data
-        let entries: Vec<_> = entry_names
-            .into_iter()
-            .map(|name| completion::CompletionEntry {
-                name,
-                is_dir: true, // Assume dirs for now, will be corrected on lazy load
-            })
-            .collect();
-        self.completion_cache.update_entries(path, entries);
+    /// Get the shell settings
+    pub fn settings(&self) -> &Settings {
+        &self.settings
     }
 
-    /// Get the current virtual path
-    pub fn current_path(&self) -> VirtualPath {
-        Self::node_to_path(&self.current_node)
+    /// Get mutable access to the shell settings
+    pub fn settings_mut(&mut self) -> &mut Settings {
+        &mut self.settings
     }
 
-    /// Convert a VFS node to a virtual path
-    fn node_to_path(node: &VfsNode) -> VirtualPath {
-        match node {
-            VfsNode::Root => VirtualPath::parse("/"),
-            VfsNode::Bucket { name } => VirtualPath::parse(&format!("/{name}")),
-            VfsNode::Prefix { bucket, prefix } => {
-                VirtualPath::parse(&format!("/{}/{}", bucket, prefix.trim_end_matches('/')))
-            }
-            VfsNode::Object { bucket, key, .. } => VirtualPath::parse(&format!("/{bucket}/{key}")),
-            VfsNode::Archive { parent, .. } => Self::node_to_path(parent),
-            VfsNode::ArchiveEntry { archive, path, .. } => {
-                let archive_path = Self::node_to_path(archive);
-                archive_path.join(path)
+    /// Remember a directory's listing for tab completion, so completing in it
+    /// doesn't list it again
+    pub fn update_completions(&self, node: &VfsNode, entries: Vec<CompletionEntry>) {
+        self.completion_cache
+            .update_entries(CompletionCache::key_for(node), entries);
+    }
+
+    /// Command lines running in the background
+    pub fn jobs(&self) -> &Jobs {
+        &self.jobs
+    }
+
+    pub fn jobs_mut(&mut self) -> &mut Jobs {
+        &mut self.jobs
+    }
+
+    /// Get the current path, as shown by the prompt and `pwd`
+    pub fn current_path(&self) -> String {
+        self.current_node.path()
+    }
+
+    /// Print help message
+    /// Commands come from the registry, so everything registered is listed.
+    fn print_help(&self, out: &mut Output) -> Result<()> {
+        let mut commands: Vec<_> = self.commands.values().collect();
+        commands.sort_by(|a, b| a.name().cmp(b.name()));
+        let usages = commands
+            .iter()
+            .map(|command| (command.name(), command.usage()))
+            .chain(BUILTINS.iter().copied());
+
+        print_line!(out, "Available commands:");
+        for (name, usage) in usages {
+            let description = usage.split_once(" - ").map_or("", |(_, d)| d);
+            print_line!(out, "  {name:<10} {description}");
+        }
+        print_line!(out);
+        print_line!(
+            out,
+            "Run `help COMMAND` or `COMMAND --help` for its usage and flags."
+        );
+        print_line!(out);
+        print_line!(out, "Pipe support:");
+        print_line!(out, "  You can pipe command output to external tools:");
+        print_line!(out, "  ls | grep pattern");
+        print_line!(out, "  cat file.json | jq .");
+        print_line!(out, "  cat large.log | less");
+        Ok(())
+    }
+
+    /// The usage line of a registered or built-in command
+    fn usage_of(&self, name: &str) -> Option<&str> {
+        let name = if name == "quit" { "exit" } else { name };
+        match self.commands.get(name) {
+            Some(command) => Some(command.usage()),
+            None => BUILTINS
+                .iter()
+                .find(|(builtin, _)| *builtin == name)
+                .map(|(_, usage)| *usage),
+        }
+    }
+
+    /// Print one command's synopsis, description, and flags table
+    fn print_command_help(&self, name: &str, out: &mut Output) -> Result<()> {
+        let usage = self
+            .usage_of(name)
+            .ok_or_else(|| anyhow!("Unknown command: {name}"))?;
+        let (synopsis, description) = usage.split_once(" - ").unwrap_or((usage, ""));
+        print_line!(out, "Usage: {synopsis}");
+        if !description.is_empty() {
+            print_line!(out);
+            print_line!(out, "{description}");
+        }
+
+        let flags = self.commands.get(name).map_or(&[][..], |c| c.flags());
+        if !flags.is_empty() {
+            let width = flags.iter().map(|(flag, _)| flag.len()).max().unwrap_or(0);
+            print_line!(out);
+            print_line!(out, "Flags:");
+            for (flag, description) in flags {
+                print_line!(out, "  {flag:<width$}  {description}");
             }
         }
+        Ok(())
     }
 
-    /// Print help message
-    fn print_help(&self) {
-        println!("Available commands:");
-        println!("  ls [OPTIONS]   - List contents");
-        println!("  cd PATH        - Change directory");
-        println!("  cat FILE       - Display file contents");
-        println!("  pwd            - Print working directory");
-        println!("  help           - Show this help");
-        println!("  exit/quit      - Exit the shell");
-        println!();
-        println!("Pipe support:");
-        println!("  You can pipe command output to external tools:");
-        println!("  ls | grep pattern");
-        println!("  cat file.json | jq .");
-        println!("  cat large.log | less");
-    }
-
-    /// Get the prompt string
+    /// Get the prompt string, from the config's `prompt` template
     pub fn prompt(&self) -> String {
-        format!("s3sh:{} $ ", self.current_path())
+        let template = self.config.prompt().unwrap_or(prompt::DEFAULT_PROMPT);
+        let provider = self.s3_client.provider_config();
+        let context = prompt::PromptContext {
+            provider: self.s3_client.provider_name().unwrap_or("aws"),
+            profile: provider.and_then(|p| p.profile.as_deref()),
+            region: self.s3_client.region(),
+            node: &self.current_node,
+            read_only: self.settings.read_only,
+        };
+        prompt::render(template, &context)
     }
 
     /// Split command line on first unquoted pipe character
     /// Returns (command, Some(pipeline)) or (command, None)
     fn split_pipeline(line: &str) -> (String, Option<String>) {
+        match Self::find_unquoted(line, "|") {
+            Some(i) => (
+                line[..i].trim().to_string(),
+                Some(line[i + 1..].trim().to_string()),
+            ),
+            None => (line.to_string(), None),
+        }
+    }
+
+    /// The command line before a trailing unquoted `&`, which runs it in the background
+    fn split_background(line: &str) -> Option<&str> {
+        let line = line.trim_end();
+        let mut last = None;
+        let mut offset = 0;
+        while let Some(i) = Self::find_unquoted(&line[offset..], "&") {
+            last = Some(offset + i);
+            offset += i + 1;
+        }
+        let i = last.filter(|i| *i + 1 == line.len())?;
+        // `&&` is a list operator, not a background marker
+        let command = line[..i].trim_end();
+        (!line[..i].ends_with('&') && !command.is_empty()).then_some(command)
+    }
+
+    /// Split a line on unquoted `&&` and `||`
+    /// Each command comes with the operator before it (None for the first).
+    fn split_chain(line: &str) -> Result<Vec<(Option<Chain>, String)>> {
+        let mut commands = Vec::new();
+        let mut chain = None;
+        let mut rest = line;
+        loop {
+            let next = [("&&", Chain::And), ("||", Chain::Or)]
+                .into_iter()
+                .filter_map(|(op, kind)| Some((Self::find_unquoted(rest, op)?, kind)))
+                .min_by_key(|(i, _)| *i);
+            let command = match next {
+                Some((i, _)) => &rest[..i],
+                None => rest,
+            };
+            if command.trim().is_empty() && (chain.is_some() || next.is_some()) {
+                return Err(anyhow!("Syntax error: empty command in && / || list"));
+            }
+            commands.push((chain, command.trim().to_string()));
+            match next {
+                Some((i, kind)) => {
+                    chain = Some(kind);
+                    rest = &rest[i + 2..];
+                }
+                None => return Ok(commands),
+            }
+        }
+    }
+
+    /// Replace `$?` outside single quotes with the last command's exit status
+    fn expand_status(&self, line: &str) -> String {
+        let mut expanded = String::with_capacity(line.len());
+        let mut in_single_quote = false;
+        let mut in_double_quote = false;
+        let mut chars = line.chars().peekable();
+        while let Some(ch) = chars.next() {
+            match ch {
+                // Keep escapes for the argument parser, but don't expand `\$?`
+                '\\' if !in_single_quote => {
+                    expanded.push(ch);
+                    expanded.extend(chars.next());
+                    continue;
+                }
+                '\'' if !in_double_quote => in_single_quote = !in_single_quote,
+                '"' if !in_single_quote => in_double_quote = !in_double_quote,
+                '$' if !in_single_quote && chars.peek() == Some(&'?') => {
+                    chars.next();
+                    expanded.push_str(&self.last_status.to_string());
+                    continue;
+                }
+                _ => {}
+            }
+            expanded.push(ch);
+        }
+        expanded
+    }
+
+    /// Split off an unquoted `> FILE` or `>> FILE` redirection
+    fn split_redirect(line: &str) -> Result<(String, Option<Redirect>)> {
+        let Some(i) = Self::find_unquoted(line, ">") else {
+            return Ok((line.to_string(), None));
+        };
+
+        let append = line[i + 1..].starts_with('>');
+        let target = &line[i + if append { 2 } else { 1 }..];
+        let [path] = Self::parse_command_line(target)?
+            .try_into()
+            .map_err(|_| anyhow!("Redirection needs exactly one file name"))?;
+
+        let path = expand_home(&path);
+
+        Ok((
+            line[..i].trim().to_string(),
+            Some(Redirect { path, append }),
+        ))
+    }
+
+    /// Find the first occurrence of `target` outside quotes and escapes
+    fn find_unquoted(line: &str, target: &str) -> Option<usize> {
         let mut in_single_quote = false;
         let mut in_double_quote = false;
         let mut escape_next = false;
@@ -306,18 +1256,14 @@ impl ShellState {
                 '"' if !in_single_quote => {
                     in_double_quote = !in_double_quote;
                 }
-                '|' if !in_single_quote && !in_double_quote => {
-                    // Found first unquoted pipe
-                    let command = line[..i].trim().to_string();
-                    let pipeline = line[i + 1..].trim().to_string();
-                    return (command, Some(pipeline));
+                _ if !in_single_quote && !in_double_quote && line[i..].starts_with(target) => {
+                    return Some(i);
                 }
                 _ => {}
             }
         }
 
-        // No pipe found
-        (line.to_string(), None)
+        None
     }
 
     /// Parse command line respecting quotes (both single and double)
@@ -373,3 +1319,269 @@ impl ShellState {
         Ok(args)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_redirect() {
+        let (command, redirect) = ShellState::split_redirect("cat big.json > local.json").unwrap();
+        let redirect = redirect.unwrap();
+        assert_eq!(command, "cat big.json");
+        assert_eq!(redirect.path, PathBuf::from("local.json"));
+        assert!(!redirect.append);
+
+        let (command, redirect) = ShellState::split_redirect("ls -l >> 'my listing.txt'").unwrap();
+        let redirect = redirect.unwrap();
+        assert_eq!(command, "ls -l");
+        assert_eq!(redirect.path, PathBuf::from("my listing.txt"));
+        assert!(redirect.append);
+
+        // Quoted operators are left alone
+        let (command, redirect) =
+            ShellState::split_redirect("query \"SELECT * FROM this WHERE x > 5\"").unwrap();
+        assert!(redirect.is_none());
+        assert_eq!(command, "query \"SELECT * FROM this WHERE x > 5\"");
+
+        assert!(ShellState::split_redirect("ls >").is_err());
+        assert!(ShellState::split_redirect("ls > a b").is_err());
+    }
+
+    async fn run(state: &mut ShellState, line: &str) -> Result<String> {
+        let mut out = Output::capture();
+        state.execute_to(line, &mut out).await?;
+        Ok(String::from_utf8_lossy(out.captured().unwrap()).into_owned())
+    }
+
+    #[tokio::test]
+    async fn test_help() {
+        let mut state = ShellState::with_client(Arc::new(crate::s3::MockS3Client::new().client()))
+            .await
+            .unwrap();
+
+        // Every registered command is listed, along with the built-ins
+        let help = run(&mut state, "help").await.unwrap();
+        for name in state
+            .commands
+            .keys()
+            .map(String::as_str)
+            .chain(["pwd", "exit"])
+        {
+            assert!(help.contains(&format!("  {name} ")), "{name} missing");
+        }
+
+        let cd = run(&mut state, "help cd").await.unwrap();
+        assert!(cd.starts_with("Usage: cd [-y|--yes] PATH\n"));
+        assert!(cd.contains("Flags:\n  -y, --yes  Skip the confirmation"));
+        assert_eq!(run(&mut state, "cd --help").await.unwrap(), cd);
+
+        assert!(
+            run(&mut state, "help pwd")
+                .await
+                .unwrap()
+                .starts_with("Usage: pwd\n")
+        );
+        assert!(run(&mut state, "help nope").await.is_err());
+    }
+
+    #[test]
+    fn test_split_chain() {
+        let chain = ShellState::split_chain("cd logs && ls | grep x || echo 'a && b'").unwrap();
+        assert_eq!(
+            chain,
+            vec![
+                (None, "cd logs".to_string()),
+                (Some(Chain::And), "ls | grep x".to_string()),
+                (Some(Chain::Or), "echo 'a && b'".to_string()),
+            ]
+        );
+        assert_eq!(
+            ShellState::split_chain("").unwrap(),
+            vec![(None, String::new())]
+        );
+        assert!(ShellState::split_chain("ls &&").is_err());
+        assert!(ShellState::split_chain("|| ls").is_err());
+    }
+
+    #[test]
+    fn test_split_background() {
+        assert_eq!(
+            ShellState::split_background("get -r logs/ . &"),
+            Some("get -r logs/ .")
+        );
+        assert_eq!(
+            ShellState::split_background("cat a && cat b&  "),
+            Some("cat a && cat b")
+        );
+        assert_eq!(ShellState::split_background("cat a && cat b"), None);
+        assert_eq!(ShellState::split_background("echo 'a &'"), None);
+        assert_eq!(ShellState::split_background("echo a \\&"), None);
+        assert_eq!(ShellState::split_background("ls &&"), None);
+        assert_eq!(ShellState::split_background(" &"), None);
+    }
+
+    #[tokio::test]
+    async fn test_background_jobs() {
+        let mut state = ShellState::with_client(Arc::new(crate::s3::MockS3Client::demo().client()))
+            .await
+            .unwrap();
+        assert_eq!(
+            run(&mut state, "cat /demo-bucket/README.md &")
+                .await
+                .unwrap(),
+            "[1] cat /demo-bucket/README.md\n"
+        );
+        run(&mut state, "cat /demo-bucket/missing &").await.unwrap();
+        assert!(run(&mut state, "jobs").await.unwrap().contains("[2] "));
+
+        // Exiting with jobs running takes a second try
+        if state.jobs().running() > 0 {
+            assert_ne!(
+                run(&mut state, "exit").await.unwrap_err().to_string(),
+                "exit"
+            );
+        }
+        assert_eq!(
+            run(&mut state, "exit").await.unwrap_err().to_string(),
+            "exit"
+        );
+
+        // Without %N, fg takes the latest job, which failed
+        assert!(run(&mut state, "fg").await.is_err());
+        assert!(
+            run(&mut state, "fg %1")
+                .await
+                .unwrap()
+                .starts_with("# Demo bucket")
+        );
+        assert!(run(&mut state, "fg %1").await.is_err());
+        assert_eq!(state.jobs().iter().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_exit_status() {
+        let mut state = ShellState::with_client(Arc::new(crate::s3::MockS3Client::demo().client()))
+            .await
+            .unwrap();
+        assert_eq!(run(&mut state, "echo $?").await.unwrap(), "0\n");
+
+        assert!(run(&mut state, "cat /demo-bucket/missing").await.is_err());
+        assert_eq!(state.last_status(), 1);
+        assert_eq!(
+            run(&mut state, "echo $? \"$?\" '$?' \\$?").await.unwrap(),
+            "1 1 $? $?\n"
+        );
+        assert_eq!(state.last_status(), 0);
+
+        // && runs on success, || on failure; the line's status is the last command's
+        let out = run(&mut state, "cd /demo-bucket && echo in || echo out")
+            .await
+            .unwrap();
+        assert_eq!(out, "in\n");
+        assert_eq!(state.current_path(), "/demo-bucket");
+        let out = run(&mut state, "cd missing && echo in || echo $?")
+            .await
+            .unwrap();
+        assert_eq!(out, "1\n");
+        assert!(run(&mut state, "cd missing && echo in").await.is_err());
+        assert_eq!(state.last_status(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_tee() {
+        let mock = crate::s3::MockS3Client::demo();
+        let mut state = ShellState::with_client(Arc::new(mock.client()))
+            .await
+            .unwrap();
+        state
+            .execute_to("cd /demo-bucket", &mut Output::capture())
+            .await
+            .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let local = dir.path().join("copy.md");
+        let line = format!("cat README.md | tee {}", local.display());
+        let printed = run(&mut state, &line).await.unwrap();
+        assert!(printed.starts_with("# Demo bucket"));
+        assert_eq!(std::fs::read_to_string(&local).unwrap(), printed);
+
+        let printed = run(&mut state, "cat README.md | tee --remote copies/readme.md")
+            .await
+            .unwrap();
+        assert_eq!(
+            mock.object("demo-bucket", "copies/readme.md").unwrap(),
+            printed.as_bytes()
+        );
+
+        // Relative targets are resolved like any other path
+        state
+            .execute_to("cd data", &mut Output::capture())
+            .await
+            .unwrap();
+        run(
+            &mut state,
+            "cat ../README.md | tee --remote ../copies/./up.md",
+        )
+        .await
+        .unwrap();
+        assert!(mock.object("demo-bucket", "copies/up.md").is_some());
+        assert!(
+            mock.object("demo-bucket", "data/../copies/./up.md")
+                .is_none()
+        );
+        state
+            .execute_to("cd ..", &mut Output::capture())
+            .await
+            .unwrap();
+
+        // Read-only mode is checked before the command runs
+        state.settings.read_only = true;
+        assert!(
+            run(&mut state, "cat README.md | tee --remote copy.md")
+                .await
+                .is_err()
+        );
+        assert!(mock.object("demo-bucket", "copy.md").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_command_suggestions() {
+        let mut state = ShellState::with_client(Arc::new(crate::s3::MockS3Client::new().client()))
+            .await
+            .unwrap();
+        state.config.set_alias("lsl", "ls -l");
+
+        let suggest = |state: &ShellState, name: &str| state.suggest_command(name);
+        assert_eq!(suggest(&state, "sl").as_deref(), Some("ls"));
+        assert_eq!(suggest(&state, "chekcsum").as_deref(), Some("checksum"));
+        assert_eq!(suggest(&state, "hlep").as_deref(), Some("help"));
+        assert_eq!(suggest(&state, "lsll").as_deref(), Some("lsl"));
+        assert_eq!(suggest(&state, "frobnicate"), None);
+
+        let message = run(&mut state, "sl -l").await.unwrap_err().to_string();
+        assert_eq!(message, "Unknown command: sl (did you mean 'ls'?)");
+    }
+
+    #[tokio::test]
+    async fn test_ls_fills_completions() {
+        let client = crate::s3::MockS3Client::new()
+            .with_object("b", "logs/app.log", "x")
+            .with_object("b", "notes.txt", "hello");
+        let mut state = ShellState::with_client(Arc::new(client.client()))
+            .await
+            .unwrap();
+        run(&mut state, "cd /b").await.unwrap();
+        run(&mut state, "ls").await.unwrap();
+
+        let entries = state.completion_cache().get_entries("/b").unwrap();
+        let entries: Vec<_> = entries
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.is_dir, entry.size))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![("logs", true, None), ("notes.txt", false, Some(5))]
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use anyhow::{Result, anyhow};
+
+use crate::vfs::VfsNode;
+
+/// Prompt used when the config doesn't set one
+pub const DEFAULT_PROMPT: &str = "s3sh{ro}:{path} $ ";
+
+/// Segments a prompt template can use
+pub const SEGMENTS: &[&str] = &[
+    "provider", "profile", "region", "bucket", "path", "archive", "ro",
+];
+
+/// What the prompt's segments expand to
+pub struct PromptContext<'a> {
+    pub provider: &'a str,
+    pub profile: Option<&'a str>,
+    pub region: &'a str,
+    pub node: &'a VfsNode,
+    pub read_only: bool,
+}
+
+/// Expand `{segment}`s in a prompt template; `{{` and `}}` are literal braces
+/// Unknown segments are left as written (`config prompt` refuses them).
+pub fn render(template: &str, context: &PromptContext) -> String {
+    let mut prompt = String::with_capacity(template.len() + 32);
+    let mut rest = template;
+    while let Some(i) = rest.find(['{', '}']) {
+        prompt.push_str(&rest[..i]);
+        let tail = &rest[i..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            prompt.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        match tail[1..].find('}').filter(|_| tail.starts_with('{')) {
+            Some(end) => {
+                let segment = &tail[1..end + 1];
+                match expand(segment, context) {
+                    Some(value) => prompt.push_str(&value),
+                    None => prompt.push_str(&tail[..end + 2]),
+                }
+                rest = &tail[end + 2..];
+            }
+            None => {
+                prompt.push_str(&tail[..1]);
+                rest = &tail[1..];
+            }
+        }
+    }
+    prompt.push_str(rest);
+    prompt
+}
+
+/// Check that a template only uses known segments
+pub fn validate(template: &str) -> Result<()> {
+    let mut rest = template;
+    while let Some(i) = rest.find('{') {
+        let tail = &rest[i..];
+        if let Some(escaped) = tail.strip_prefix("{{") {
+            rest = escaped;
+            continue;
+        }
+        let end = tail
+            .find('}')
+            .ok_or_else(|| anyhow!("Unclosed '{{' in prompt (use {{{{ for a literal brace)"))?;
+        let segment = &tail[1..end];
+        let (name, arg) = segment.split_once(':').unwrap_or((segment, ""));
+        let known = SEGMENTS.contains(&name);
+        let valid_arg = arg.is_empty() || (name == "path" && arg.parse::<usize>().is_ok());
+        if !known || !valid_arg {
+            return Err(anyhow!(
+                "Unknown prompt segment {{{segment}}} (expected one of: {})",
+                SEGMENTS.join(", ")
+            ));
+        }
+        rest = &tail[end + 1..];
+    }
+    Ok(())
+}
+
+fn expand(segment: &str, context: &PromptContext) -> Option<String> {
+    let (name, arg) = segment.split_once(':').unwrap_or((segment, ""));
+    if !arg.is_empty() && name != "path" {
+        return None;
+    }
+    Some(match name {
+        "provider" => context.provider.to_string(),
+        "profile" => context.profile.unwrap_or_default().to_string(),
+        "region" => context.region.to_string(),
+        "bucket" => bucket(context.node).unwrap_or_default().to_string(),
+        "path" if arg.is_empty() => context.node.path(),
+        "path" => truncate_path(&context.node.path(), arg.parse().ok()?),
+        "archive" => archive(context.node)
+            .map(|name| format!(" ({name})"))
+            .unwrap_or_default(),
+        "ro" if context.read_only => "(ro)".to_string(),
+        "ro" => String::new(),
+        _ => return None,
+    })
+}
+
+/// The last `keep` segments of a path, with "…" standing in for the rest
+fn truncate_path(path: &str, keep: usize) -> String {
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    if keep == 0 || segments.len() <= keep {
+        return path.to_string();
+    }
+    format!("…/{}", segments[segments.len() - keep..].join("/"))
+}
+
+fn bucket(node: &VfsNode) -> Option<&str> {
+    match node {
+        VfsNode::Root => None,
+        VfsNode::Bucket { name } => Some(name),
+        VfsNode::Prefix { bucket, .. } | VfsNode::Object { bucket, .. } => Some(bucket),
+        VfsNode::Archive { parent, .. } => bucket(parent),
+        VfsNode::ArchiveEntry { archive, .. } => bucket(archive),
+    }
+}
+
+/// Name of the innermost archive the node is in
+fn archive(node: &VfsNode) -> Option<String> {
+    let archive = match node {
+        VfsNode::Archive { .. } => node,
+        VfsNode::ArchiveEntry { archive, .. } => archive,
+        _ => return None,
+    };
+    let path = archive.path();
+    path.rsplit('/').next().map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::ArchiveType;
+
+    fn context(node: &VfsNode) -> PromptContext<'_> {
+        PromptContext {
+            provider: "aws",
+            profile: Some("work"),
+            region: "us-east-1",
+            node,
+            read_only: false,
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let node = VfsNode::Prefix {
+            bucket: "logs".to_string(),
+            prefix: "2024/06/01/".to_string(),
+        };
+        let ctx = context(&node);
+        assert_eq!(render(DEFAULT_PROMPT, &ctx), "s3sh:/logs/2024/06/01 $ ");
+        assert_eq!(
+            render("{provider}:{profile}@{region} {bucket} {path:2}> ", &ctx),
+            "aws:work@us-east-1 logs …/06/01> "
+        );
+        assert_eq!(
+            render("{path:9} {{x}} {nope}", &ctx),
+            "/logs/2024/06/01 {x} {nope}"
+        );
+
+        let archive = VfsNode::Archive {
+            parent: Box::new(VfsNode::Object {
+                bucket: "b".to_string(),
+                key: "backups/site.zip".to_string(),
+                size: 0,
+                version_id: None,
+            }),
+            archive_type: ArchiveType::Zip,
+            index: None,
+        };
+        let entry = VfsNode::ArchiveEntry {
+            archive: Box::new(archive),
+            path: "docs/".to_string(),
+            size: 0,
+            is_dir: true,
+        };
+        let ctx = PromptContext {
+            read_only: true,
+            ..context(&entry)
+        };
+        assert_eq!(
+            render("{path:1}{archive}{ro} $ ", &ctx),
+            "…/docs (site.zip)(ro) $ "
+        );
+        assert_eq!(render("{archive}", &context(&VfsNode::Root)), "");
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate(DEFAULT_PROMPT).is_ok());
+        assert!(validate("{provider}:{path:3}{{}}> ").is_ok());
+        assert!(validate("{pwd}> ").is_err());
+        assert!(validate("{region:2}> ").is_err());
+        assert!(validate("{path> ").is_err());
+    }
+}
@@ -0,0 +1,426 @@
+use anyhow::{Result, anyhow};
+use std::time::Duration;
+
+use crate::archive::ExtractLimits;
+use crate::s3::listing::DEFAULT_LISTING_TTL;
+use crate::s3::retry::RetryOn;
+use crate::s3::{RetryPolicy, TransferOptions};
+
+/// Default size above which `cd` asks before streaming an archive (1 GB)
+pub const DEFAULT_ARCHIVE_THRESHOLD: u64 = 1_000_000_000;
+
+/// Smallest allowed part size; smaller parts only add request overhead
+pub const MIN_PART_SIZE: u64 = 1 << 20;
+
+/// Runtime settings that control shell behavior
+#[derive(Debug, Clone)]
+pub struct Settings {
+    /// Ask for confirmation before potentially expensive operations
+    pub confirm: bool,
+    /// Archives that must be streamed in full are confirmed above this size
+    pub archive_threshold: u64,
+    /// Abandon a command that runs longer than this (None = wait forever)
+    pub timeout: Option<Duration>,
+    /// How large downloads are split into concurrent ranged GETs
+    pub transfer: TransferOptions,
+    /// How failed S3 calls are retried
+    pub retry: RetryPolicy,
+    /// Refuse anything that would modify S3 (uploads, deletes, metadata changes)
+    pub read_only: bool,
+    /// Zip bomb limits for files decompressed out of archives
+    pub extract_limits: ExtractLimits,
+    /// How long directory listings are reused (zero = always list again)
+    pub listing_ttl: Duration,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            confirm: true,
+            archive_threshold: DEFAULT_ARCHIVE_THRESHOLD,
+            timeout: None,
+            transfer: TransferOptions::default(),
+            retry: RetryPolicy::default(),
+            read_only: false,
+            extract_limits: ExtractLimits::default(),
+            listing_ttl: DEFAULT_LISTING_TTL,
+        }
+    }
+}
+
+impl Settings {
+    /// Names of all settings, in display order
+    pub const KEYS: &'static [&'static str] = &[
+        "confirm",
+        "archive_threshold",
+        "timeout",
+        "part_size",
+        "part_concurrency",
+        "list_concurrency",
+        "read_ahead",
+        "bandwidth",
+        "retry_attempts",
+        "retry_backoff",
+        "retry_on",
+        "readonly",
+        "max_extract_size",
+        "max_compression_ratio",
+        "listing_ttl",
+    ];
+
+    /// Get a setting value formatted for display
+    pub fn get(&self, key: &str) -> Result<String> {
+        match key {
+            "confirm" => Ok(format_bool(self.confirm)),
+            "archive_threshold" => Ok(humansize::format_size(
+                self.archive_threshold,
+                humansize::DECIMAL,
+            )),
+            "timeout" => Ok(self
+                .timeout
+                .map_or_else(|| "off".to_string(), |t| format!("{}s", t.as_secs()))),
+            "part_size" => Ok(humansize::format_size(
+                self.transfer.part_size,
+                humansize::BINARY,
+            )),
+            "part_concurrency" => Ok(self.transfer.concurrency.to_string()),
+            "list_concurrency" => Ok(self.transfer.list_concurrency.to_string()),
+            "read_ahead" => Ok(match self.transfer.read_ahead {
+                0 => "off".to_string(),
+                size => humansize::format_size(size, humansize::BINARY),
+            }),
+            "bandwidth" => Ok(self.transfer.bandwidth.map_or_else(
+                || "off".to_string(),
+                |rate| format!("{}/s", humansize::format_size(rate, humansize::DECIMAL)),
+            )),
+            "retry_attempts" => Ok(self.retry.max_attempts.to_string()),
+            "retry_backoff" => Ok(format_duration(self.retry.initial_backoff)),
+            "retry_on" => Ok(self.retry.retry_on.to_string()),
+            "readonly" => Ok(format_bool(self.read_only)),
+            "max_extract_size" => Ok(self.extract_limits.max_size.map_or_else(
+                || "off".to_string(),
+                |size| humansize::format_size(size, humansize::BINARY),
+            )),
+            "max_compression_ratio" => Ok(self
+                .extract_limits
+                .max_ratio
+                .map_or_else(|| "off".to_string(), |ratio| format!("{ratio}:1"))),
+            "listing_ttl" => Ok(format_duration(self.listing_ttl)),
+            _ => Err(anyhow!("Unknown setting: {key}")),
+        }
+    }
+
+    /// Update a setting from its string representation
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "confirm" => self.confirm = parse_bool(value)?,
+            "archive_threshold" => self.archive_threshold = parse_size(value)?,
+            "timeout" => self.timeout = parse_timeout(value)?,
+            "part_size" => self.transfer.part_size = parse_part_size(value)?,
+            "part_concurrency" => self.transfer.concurrency = parse_concurrency(value)?,
+            "list_concurrency" => self.transfer.list_concurrency = parse_concurrency(value)?,
+            "read_ahead" => self.transfer.read_ahead = parse_read_ahead(value)?,
+            "bandwidth" => self.transfer.bandwidth = parse_bandwidth(value)?,
+            "retry_attempts" => self.retry.max_attempts = parse_attempts(value)?,
+            "retry_backoff" => self.retry.initial_backoff = parse_duration(value)?,
+            "retry_on" => self.retry.retry_on = RetryOn::parse(value)?,
+            "readonly" => self.read_only = parse_bool(value)?,
+            "max_extract_size" => self.extract_limits.max_size = parse_extract_size(value)?,
+            "max_compression_ratio" => {
+                self.extract_limits.max_ratio = parse_compression_ratio(value)?
+            }
+            "listing_ttl" => self.listing_ttl = parse_duration(value)?,
+            _ => return Err(anyhow!("Unknown setting: {key}")),
+        }
+        Ok(())
+    }
+
+    /// Check whether streaming an archive of this size needs confirmation
+    pub fn needs_archive_confirmation(&self, size: u64) -> bool {
+        self.confirm && size > self.archive_threshold
+    }
+}
+
+/// Format a boolean setting as on/off
+pub fn format_bool(value: bool) -> String {
+    if value { "on" } else { "off" }.to_string()
+}
+
+/// Parse an on/off style boolean
+pub fn parse_bool(value: &str) -> Result<bool> {
+    match value.to_lowercase().as_str() {
+        "on" | "true" | "yes" | "1" => Ok(true),
+        "off" | "false" | "no" | "0" => Ok(false),
+        _ => Err(anyhow!("Invalid boolean value: {value} (expected on/off)")),
+    }
+}
+
+/// Parse a timeout in seconds ("120" or "120s"); "off" or 0 disables it
+pub fn parse_timeout(value: &str) -> Result<Option<Duration>> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("off") {
+        return Ok(None);
+    }
+    let seconds: u64 = value
+        .strip_suffix('s')
+        .unwrap_or(value)
+        .parse()
+        .map_err(|_| anyhow!("Invalid timeout: {value} (expected seconds or off)"))?;
+    Ok((seconds > 0).then(|| Duration::from_secs(seconds)))
+}
+
+/// Parse the part size for ranged downloads (at least 1 MiB)
+pub fn parse_part_size(value: &str) -> Result<u64> {
+    let size = parse_size(value)?;
+    if size < MIN_PART_SIZE {
+        return Err(anyhow!("Part size must be at least 1MiB: {value}"));
+    }
+    Ok(size)
+}
+
+/// Parse a number of concurrent requests (1 = sequential)
+pub fn parse_concurrency(value: &str) -> Result<usize> {
+    value
+        .trim()
+        .parse()
+        .ok()
+        .filter(|n| *n > 0)
+        .ok_or_else(|| anyhow!("Invalid concurrency: {value} (expected a positive number)"))
+}
+
+/// Parse the read-ahead chunk limit (a size like "16MiB"); "off" or 0 disables it
+pub fn parse_read_ahead(value: &str) -> Result<u64> {
+    if value.trim().eq_ignore_ascii_case("off") {
+        return Ok(0);
+    }
+    parse_size(value)
+}
+
+/// Parse a bandwidth limit like "10MB/s" or "500KiB"; "off" or 0 removes it
+pub fn parse_bandwidth(value: &str) -> Result<Option<u64>> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("off") {
+        return Ok(None);
+    }
+    let rate = value.strip_suffix("/s").unwrap_or(value);
+    let rate = parse_size(rate)
+        .map_err(|_| anyhow!("Invalid bandwidth: {value} (expected e.g. 10MB/s or off)"))?;
+    Ok((rate > 0).then_some(rate))
+}
+
+/// Parse the largest file extracted from an archive, like "4GiB"; "off" or 0 removes the limit
+pub fn parse_extract_size(value: &str) -> Result<Option<u64>> {
+    if value.trim().eq_ignore_ascii_case("off") {
+        return Ok(None);
+    }
+    let size = parse_size(value)?;
+    Ok((size > 0).then_some(size))
+}
+
+/// Parse the largest compression ratio, like "5000" or "5000:1"; "off" or 0 removes the limit
+pub fn parse_compression_ratio(value: &str) -> Result<Option<u64>> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("off") {
+        return Ok(None);
+    }
+    let ratio: u64 = value
+        .strip_suffix(":1")
+        .unwrap_or(value)
+        .parse()
+        .map_err(|_| anyhow!("Invalid compression ratio: {value} (expected e.g. 5000 or off)"))?;
+    Ok((ratio > 0).then_some(ratio))
+}
+
+/// Parse the number of attempts per S3 call (1 = never retry)
+pub fn parse_attempts(value: &str) -> Result<u32> {
+    value
+        .trim()
+        .parse()
+        .ok()
+        .filter(|n| *n > 0)
+        .ok_or_else(|| anyhow!("Invalid attempts: {value} (expected a positive number)"))
+}
+
+/// Parse a duration: milliseconds ("500ms") or seconds ("2s" or "2")
+pub fn parse_duration(value: &str) -> Result<Duration> {
+    let value = value.trim();
+    let invalid = || anyhow!("Invalid duration: {value} (expected e.g. 500ms or 2s)");
+    if let Some(ms) = value.strip_suffix("ms") {
+        return ms
+            .trim()
+            .parse()
+            .map(Duration::from_millis)
+            .map_err(|_| invalid());
+    }
+    let seconds: f64 = value
+        .strip_suffix('s')
+        .unwrap_or(value)
+        .trim()
+        .parse()
+        .map_err(|_| invalid())?;
+    if !seconds.is_finite() || seconds < 0.0 {
+        return Err(invalid());
+    }
+    Ok(Duration::from_secs_f64(seconds))
+}
+
+/// Format a duration the way parse_duration reads it
+fn format_duration(duration: Duration) -> String {
+    if duration.subsec_millis() == 0 {
+        format!("{}s", duration.as_secs())
+    } else {
+        format!("{}ms", duration.as_millis())
+    }
+}
+
+/// Parse a human-readable size like "500MB", "4.2GiB", or "1024"
+pub fn parse_size(value: &str) -> Result<u64> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+
+    let number: f64 = number
+        .parse()
+        .map_err(|_| anyhow!("Invalid size: {value}"))?;
+
+    let multiplier: u64 = match unit.trim().to_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1_000,
+        "m" | "mb" => 1_000_000,
+        "g" | "gb" => 1_000_000_000,
+        "t" | "tb" => 1_000_000_000_000,
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        "tib" => 1 << 40,
+        _ => return Err(anyhow!("Invalid size unit in: {value}")),
+    };
+
+    Ok((number * multiplier as f64) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024").unwrap(), 1024);
+        assert_eq!(parse_size("500MB").unwrap(), 500_000_000);
+        assert_eq!(parse_size("4.2 GB").unwrap(), 4_200_000_000);
+        assert_eq!(parse_size("1GiB").unwrap(), 1 << 30);
+        assert_eq!(parse_size("10k").unwrap(), 10_000);
+        assert!(parse_size("lots").is_err());
+        assert!(parse_size("10XB").is_err());
+    }
+
+    #[test]
+    fn test_parse_bool() {
+        assert!(parse_bool("on").unwrap());
+        assert!(parse_bool("TRUE").unwrap());
+        assert!(!parse_bool("off").unwrap());
+        assert!(parse_bool("maybe").is_err());
+    }
+
+    #[test]
+    fn test_timeout_setting() {
+        let mut settings = Settings::default();
+        assert_eq!(settings.get("timeout").unwrap(), "off");
+
+        settings.set("timeout", "120").unwrap();
+        assert_eq!(settings.timeout, Some(Duration::from_secs(120)));
+        assert_eq!(settings.get("timeout").unwrap(), "120s");
+
+        settings.set("timeout", "0").unwrap();
+        assert_eq!(settings.timeout, None);
+        assert!(settings.set("timeout", "soon").is_err());
+    }
+
+    #[test]
+    fn test_retry_settings() {
+        let mut settings = Settings::default();
+        assert_eq!(settings.get("retry_attempts").unwrap(), "3");
+        assert_eq!(settings.get("retry_backoff").unwrap(), "1s");
+
+        settings.set("retry_attempts", "5").unwrap();
+        settings.set("retry_backoff", "250ms").unwrap();
+        settings.set("retry_on", "throttling").unwrap();
+        assert_eq!(settings.retry.max_attempts, 5);
+        assert_eq!(settings.retry.initial_backoff, Duration::from_millis(250));
+        assert_eq!(settings.get("retry_backoff").unwrap(), "250ms");
+        assert_eq!(settings.get("retry_on").unwrap(), "throttling");
+
+        assert_eq!(parse_duration("2").unwrap(), Duration::from_secs(2));
+        assert!(settings.set("retry_attempts", "0").is_err());
+        assert!(settings.set("retry_backoff", "soon").is_err());
+    }
+
+    #[test]
+    fn test_archive_confirmation() {
+        let mut settings = Settings::default();
+        assert!(!settings.needs_archive_confirmation(1_000));
+        assert!(settings.needs_archive_confirmation(4_200_000_000));
+
+        settings.set("archive_threshold", "5GB").unwrap();
+        assert!(!settings.needs_archive_confirmation(4_200_000_000));
+
+        settings.set("confirm", "off").unwrap();
+        assert!(!settings.needs_archive_confirmation(u64::MAX));
+    }
+
+    #[test]
+    fn test_transfer_settings() {
+        let mut settings = Settings::default();
+        assert_eq!(settings.get("part_size").unwrap(), "8 MiB");
+
+        settings.set("part_size", "16MiB").unwrap();
+        settings.set("part_concurrency", "4").unwrap();
+        settings.set("list_concurrency", "2").unwrap();
+        assert_eq!(settings.transfer.part_size, 16 << 20);
+        assert_eq!(settings.transfer.concurrency, 4);
+        assert_eq!(settings.transfer.list_concurrency, 2);
+
+        assert!(settings.set("part_size", "8").is_err());
+        assert!(settings.set("part_concurrency", "0").is_err());
+
+        assert_eq!(settings.get("read_ahead").unwrap(), "8 MiB");
+        settings.set("read_ahead", "off").unwrap();
+        assert_eq!(settings.transfer.read_ahead, 0);
+        assert_eq!(settings.get("read_ahead").unwrap(), "off");
+
+        assert_eq!(settings.get("bandwidth").unwrap(), "off");
+        settings.set("bandwidth", "10MB/s").unwrap();
+        assert_eq!(settings.transfer.bandwidth, Some(10_000_000));
+        assert_eq!(settings.get("bandwidth").unwrap(), "10 MB/s");
+        settings.set("bandwidth", "0").unwrap();
+        assert_eq!(settings.transfer.bandwidth, None);
+        assert!(settings.set("bandwidth", "fast").is_err());
+    }
+
+    #[test]
+    fn test_extract_limit_settings() {
+        let mut settings = Settings::default();
+        assert_eq!(settings.get("max_extract_size").unwrap(), "1 GiB");
+        assert_eq!(settings.get("max_compression_ratio").unwrap(), "1000:1");
+
+        settings.set("max_extract_size", "8GiB").unwrap();
+        settings.set("max_compression_ratio", "20000:1").unwrap();
+        assert_eq!(settings.extract_limits.max_size, Some(8 << 30));
+        assert_eq!(settings.extract_limits.max_ratio, Some(20_000));
+
+        settings.set("max_extract_size", "off").unwrap();
+        settings.set("max_compression_ratio", "0").unwrap();
+        assert_eq!(settings.extract_limits, ExtractLimits::UNLIMITED);
+        assert_eq!(settings.get("max_compression_ratio").unwrap(), "off");
+        assert!(settings.set("max_compression_ratio", "lots").is_err());
+    }
+
+    #[test]
+    fn test_unknown_setting() {
+        let mut settings = Settings::default();
+        assert!(settings.set("nope", "1").is_err());
+        assert!(settings.get("nope").is_err());
+    }
+}
@@ -0,0 +1,324 @@
+use anyhow::{Result, anyhow};
+use colored::{Color, ColoredString, Colorize};
+use std::collections::BTreeMap;
+
+use crate::config::Config;
+
+/// What a listed name is, for picking its color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    Dir,
+    Archive,
+    /// Tabular and structured data: parquet, CSV, JSON, Avro, ...
+    Data,
+    Image,
+    Symlink,
+    File,
+}
+
+/// Suffixes of each kind, checked in this order so `.tar.gz` is an archive
+const KINDS: &[(FileKind, &[&str])] = &[
+    (
+        FileKind::Archive,
+        &[
+            ".tar", ".tgz", ".tar.gz", ".tbz", ".tbz2", ".tar.bz2", ".zip", ".gz", ".bz2", ".zst",
+            ".xz", ".7z", ".rar", ".jar", ".whl",
+        ],
+    ),
+    (
+        FileKind::Data,
+        &[
+            ".parquet", ".csv", ".tsv", ".json", ".jsonl", ".ndjson", ".avro", ".orc", ".arrow",
+            ".feather", ".xlsx", ".xls",
+        ],
+    ),
+    (
+        FileKind::Image,
+        &[
+            ".png", ".jpg", ".jpeg", ".gif", ".bmp", ".svg", ".webp", ".tif", ".tiff", ".ico",
+            ".heic", ".avif",
+        ],
+    ),
+];
+
+impl FileKind {
+    /// Kind of a file from its name
+    pub fn of(name: &str) -> Self {
+        let name = name.to_lowercase();
+        KINDS
+            .iter()
+            .find(|(_, suffixes)| suffixes.iter().any(|suffix| name.ends_with(suffix)))
+            .map_or(FileKind::File, |(kind, _)| *kind)
+    }
+}
+
+/// A color and text attributes, written like "bold bright blue" or "#ff8800 underline"
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Style {
+    color: Option<Color>,
+    bold: bool,
+    dimmed: bool,
+    italic: bool,
+    underline: bool,
+}
+
+impl Style {
+    const fn new(color: Color, bold: bool) -> Self {
+        Style {
+            color: Some(color),
+            bold,
+            dimmed: false,
+            italic: false,
+            underline: false,
+        }
+    }
+
+    /// Parse a style; "none" (or an empty string) leaves text as is
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut style = Style::default();
+        let mut words = spec.split_whitespace();
+        while let Some(word) = words.next() {
+            let color = match word.to_lowercase().as_str() {
+                "none" => continue,
+                "bold" => {
+                    style.bold = true;
+                    continue;
+                }
+                "dim" | "dimmed" => {
+                    style.dimmed = true;
+                    continue;
+                }
+                "italic" => {
+                    style.italic = true;
+                    continue;
+                }
+                "underline" => {
+                    style.underline = true;
+                    continue;
+                }
+                "bright" => {
+                    let name = words.next().unwrap_or_default();
+                    format!("bright {name}").parse().ok()
+                }
+                hex if hex.starts_with('#') => parse_hex(hex),
+                name => name.parse().ok(),
+            };
+            style.color =
+                Some(color.ok_or_else(|| anyhow!("Unknown color or attribute in {spec:?}"))?);
+        }
+        Ok(style)
+    }
+
+    /// Apply the style, unless colors are off (`--no-color`, `NO_COLOR`, or `color = off`)
+    pub fn paint(&self, text: &str) -> ColoredString {
+        let mut painted = match self.color {
+            Some(color) => text.color(color),
+            None => text.normal(),
+        };
+        if self.bold {
+            painted = painted.bold();
+        }
+        if self.dimmed {
+            painted = painted.dimmed();
+        }
+        if self.italic {
+            painted = painted.italic();
+        }
+        if self.underline {
+            painted = painted.underline();
+        }
+        painted
+    }
+}
+
+/// "#rrggbb" as a 24-bit color
+fn parse_hex(hex: &str) -> Option<Color> {
+    let digits = hex.strip_prefix('#').filter(|d| d.len() == 6)?;
+    let channel = |i: usize| u8::from_str_radix(digits.get(i..i + 2)?, 16).ok();
+    Some(Color::TrueColor {
+        r: channel(0)?,
+        g: channel(2)?,
+        b: channel(4)?,
+    })
+}
+
+/// Colors for `ls`, by file kind with per-extension overrides, in the spirit of LS_COLORS
+#[derive(Debug, Clone)]
+pub struct Theme {
+    dir: Style,
+    archive: Style,
+    data: Style,
+    image: Style,
+    symlink: Style,
+    file: Style,
+    /// `*.ext` patterns from the config as lowercase suffixes, longest first
+    extensions: Vec<(String, Style)>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            dir: Style::new(Color::Blue, true),
+            archive: Style::new(Color::Red, true),
+            data: Style::new(Color::Yellow, false),
+            image: Style::new(Color::Magenta, false),
+            symlink: Style::new(Color::Cyan, true),
+            file: Style::default(),
+            extensions: Vec::new(),
+        }
+    }
+}
+
+impl Theme {
+    /// The default theme with the config's `[theme]` table applied
+    /// Entries that don't parse keep their default, like other invalid settings.
+    pub fn from_config(config: &Config) -> Self {
+        Self::from_entries(&config.theme())
+    }
+
+    fn from_entries(entries: &BTreeMap<String, String>) -> Self {
+        let mut theme = Theme::default();
+        for (key, spec) in entries {
+            let Ok(style) = Style::parse(spec) else {
+                continue;
+            };
+            match key.strip_prefix('*') {
+                Some(suffix) if !suffix.is_empty() => {
+                    theme.extensions.push((suffix.to_lowercase(), style));
+                }
+                _ => {
+                    if let Some(slot) = theme.slot(key) {
+                        *slot = style;
+                    }
+                }
+            }
+        }
+        theme
+            .extensions
+            .sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        theme
+    }
+
+    fn slot(&mut self, key: &str) -> Option<&mut Style> {
+        Some(match key {
+            "dir" => &mut self.dir,
+            "archive" => &mut self.archive,
+            "data" => &mut self.data,
+            "image" => &mut self.image,
+            "symlink" => &mut self.symlink,
+            "file" => &mut self.file,
+            _ => return None,
+        })
+    }
+
+    /// Style for a name of the given kind; `*.ext` patterns apply to everything but directories
+    pub fn style(&self, name: &str, kind: FileKind) -> Style {
+        if kind != FileKind::Dir {
+            let lower = name.to_lowercase();
+            if let Some((_, style)) = self
+                .extensions
+                .iter()
+                .find(|(suffix, _)| lower.ends_with(suffix.as_str()))
+            {
+                return *style;
+            }
+        }
+        match kind {
+            FileKind::Dir => self.dir,
+            FileKind::Archive => self.archive,
+            FileKind::Data => self.data,
+            FileKind::Image => self.image,
+            FileKind::Symlink => self.symlink,
+            FileKind::File => self.file,
+        }
+    }
+
+    /// Color a name as it's listed: directories (with their trailing '/') as `dir`,
+    /// files by their extension
+    pub fn paint(&self, name: &str) -> ColoredString {
+        let kind = if name.ends_with('/') {
+            FileKind::Dir
+        } else {
+            FileKind::of(name)
+        };
+        self.paint_kind(name, kind)
+    }
+
+    /// Color a name of a kind that isn't evident from it, like a symlink
+    pub fn paint_kind(&self, name: &str, kind: FileKind) -> ColoredString {
+        self.style(name, kind).paint(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_kind() {
+        assert_eq!(FileKind::of("backup.tar.gz"), FileKind::Archive);
+        assert_eq!(FileKind::of("events.json.gz"), FileKind::Archive);
+        assert_eq!(FileKind::of("part-0001.PARQUET"), FileKind::Data);
+        assert_eq!(FileKind::of("cat.jpeg"), FileKind::Image);
+        assert_eq!(FileKind::of("README"), FileKind::File);
+    }
+
+    #[test]
+    fn test_style_parse() {
+        assert_eq!(
+            Style::parse("bold bright blue").unwrap(),
+            Style {
+                color: Some(Color::BrightBlue),
+                bold: true,
+                ..Style::default()
+            }
+        );
+        assert_eq!(
+            Style::parse("#ff8800 underline").unwrap().color,
+            Some(Color::TrueColor {
+                r: 0xff,
+                g: 0x88,
+                b: 0
+            })
+        );
+        assert_eq!(Style::parse("none").unwrap(), Style::default());
+        assert!(Style::parse("blinking").is_err());
+        assert!(Style::parse("#ff88").is_err());
+    }
+
+    #[test]
+    fn test_theme() {
+        let entries: BTreeMap<String, String> = [
+            ("data", "green"),
+            ("*.log", "dim"),
+            ("*.tar.gz", "cyan"),
+            ("*.gz", "purple"),
+            ("image", "not a color"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        let theme = Theme::from_entries(&entries);
+
+        assert_eq!(
+            theme.style("a.csv", FileKind::Data).color,
+            Some(Color::Green)
+        );
+        assert!(theme.style("app.log", FileKind::File).dimmed);
+        // The longest pattern wins
+        assert_eq!(
+            theme.style("b.tar.gz", FileKind::Archive).color,
+            Some(Color::Cyan)
+        );
+        assert_eq!(
+            theme.style("c.gz", FileKind::Archive).color,
+            Some(Color::Magenta)
+        );
+        // Patterns don't apply to directories, and bad entries keep the default
+        assert_eq!(theme.style("logs.gz/", FileKind::Dir), Theme::default().dir);
+        assert_eq!(
+            theme.style("cat.png", FileKind::Image),
+            Theme::default().image
+        );
+    }
+}
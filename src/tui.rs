@@ -0,0 +1,390 @@
+//! Dual-pane terminal browser (`s3sh --tui`).
+//!
+//! The left pane lists the working directory of a [`Session`]; the right pane previews
+//! the selected entry as text or a hex dump, or shows a Parquet file's schema. Enter
+//! opens directories and archives, and `d` downloads the selected file to the local
+//! working directory.
+
+use anyhow::{Result, anyhow};
+use bytes::Bytes;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::path::Path;
+
+use crate::archive::format_for_name;
+use crate::session::{Entry, EntryKind, Session};
+use crate::shell::commands::local_path_under;
+use crate::shell::commands::xxd::hex_dump;
+use crate::ui::looks_binary;
+use crate::vfs::VfsNode;
+
+/// Bytes of an object fetched for its preview
+const PREVIEW_BYTES: u64 = 64 * 1024;
+
+/// Lines the preview scrolls by for PageUp/PageDown
+const SCROLL_PAGE: u16 = 20;
+
+/// Run the browser until the user quits, restoring the terminal afterwards
+pub async fn run(session: Session) -> Result<()> {
+    let mut app = App::new(session);
+    app.reload().await;
+
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal).await;
+    ratatui::restore();
+    result
+}
+
+/// How file contents are shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PreviewMode {
+    /// Text, falling back to hex for binary content
+    Auto,
+    Hex,
+}
+
+struct App {
+    session: Session,
+    entries: Vec<Entry>,
+    list: ListState,
+    mode: PreviewMode,
+    /// Contents of the selected file, fetched once per selection
+    preview: Option<Result<Bytes, String>>,
+    /// Preview text that isn't file contents (schemas, hints)
+    note: Vec<String>,
+    scroll: u16,
+    status: String,
+}
+
+impl App {
+    fn new(session: Session) -> Self {
+        App {
+            session,
+            entries: Vec::new(),
+            list: ListState::default(),
+            mode: PreviewMode::Auto,
+            preview: None,
+            note: Vec::new(),
+            scroll: 0,
+            status: String::new(),
+        }
+    }
+
+    async fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            // Reading the terminal blocks, so keep it off the runtime's worker
+            let Event::Key(key) = tokio::task::block_in_place(event::read)? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if !self.handle_key(key).await {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Act on a key press; returns false to quit
+    async fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Down | KeyCode::Char('j') => self.select(1).await,
+            KeyCode::Up | KeyCode::Char('k') => self.select(-1).await,
+            KeyCode::Home | KeyCode::Char('g') => self.select(isize::MIN).await,
+            KeyCode::End | KeyCode::Char('G') => self.select(isize::MAX).await,
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => self.open().await,
+            KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => self.cd("..").await,
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_add(SCROLL_PAGE),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(SCROLL_PAGE),
+            KeyCode::Char('x') => {
+                self.mode = match self.mode {
+                    PreviewMode::Auto => PreviewMode::Hex,
+                    PreviewMode::Hex => PreviewMode::Auto,
+                };
+                self.scroll = 0;
+            }
+            KeyCode::Char('r') => self.reload().await,
+            KeyCode::Char('d') => self.download().await,
+            _ => {}
+        }
+        true
+    }
+
+    fn selected(&self) -> Option<&Entry> {
+        self.entries.get(self.list.selected()?)
+    }
+
+    /// List the working directory again, keeping the selection where possible
+    async fn reload(&mut self) {
+        let selected = self.selected().map(|e| e.name.clone());
+        match self.session.ls(".").await {
+            Ok(mut entries) => {
+                entries.sort_by(|a, b| is_dir(b).cmp(&is_dir(a)).then_with(|| a.name.cmp(&b.name)));
+                self.entries = entries;
+                self.status.clear();
+            }
+            Err(e) => {
+                self.entries.clear();
+                self.status = format!("Error: {e:#}");
+            }
+        }
+        let index = selected
+            .and_then(|name| self.entries.iter().position(|e| e.name == name))
+            .or((!self.entries.is_empty()).then_some(0));
+        self.list.select(index);
+        self.load_preview().await;
+    }
+
+    /// Move the selection by `delta` entries, clamped to the listing
+    async fn select(&mut self, delta: isize) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let current = self.list.selected().unwrap_or(0) as isize;
+        let last = self.entries.len() as isize - 1;
+        let next = current.saturating_add(delta).clamp(0, last) as usize;
+        if Some(next) != self.list.selected() {
+            self.list.select(Some(next));
+            self.load_preview().await;
+        }
+    }
+
+    async fn open(&mut self) {
+        let Some(entry) = self.selected() else {
+            return;
+        };
+        if is_dir(entry) {
+            let name = entry.name.clone();
+            self.cd(&name).await;
+        }
+    }
+
+    async fn cd(&mut self, path: &str) {
+        // Coming back up selects the directory just left
+        let left = self.session.cwd().path();
+        match self.session.cd(path).await {
+            Ok(()) => {
+                self.list.select(None);
+                self.reload().await;
+                if path == ".."
+                    && let Some(index) = self.entries.iter().position(|e| e.path == left)
+                {
+                    self.list.select(Some(index));
+                    self.load_preview().await;
+                }
+            }
+            Err(e) => self.status = format!("Error: {e:#}"),
+        }
+    }
+
+    /// Fetch what the preview pane shows for the selected entry
+    async fn load_preview(&mut self) {
+        self.preview = None;
+        self.note.clear();
+        self.scroll = 0;
+        let Some(entry) = self.selected().cloned() else {
+            return;
+        };
+
+        match entry.kind {
+            EntryKind::Bucket | EntryKind::Directory => {
+                self.note = vec!["Directory — press Enter to open".to_string()];
+            }
+            EntryKind::File if is_dir(&entry) => {
+                // Parquet files describe themselves with a virtual _schema.txt
+                match self
+                    .session
+                    .read(&format!("{}/_schema.txt", entry.path))
+                    .await
+                {
+                    Ok(schema) => {
+                        self.note = String::from_utf8_lossy(&schema)
+                            .lines()
+                            .map(String::from)
+                            .collect();
+                    }
+                    Err(_) => self.note = vec!["Archive — press Enter to open".to_string()],
+                }
+            }
+            EntryKind::File => {
+                let bytes = self.session.read_head(&entry.path, PREVIEW_BYTES).await;
+                self.preview = Some(bytes.map_err(|e| format!("{e:#}")));
+            }
+        }
+    }
+
+    /// Save the selected file under its own name in the local working directory
+    async fn download(&mut self) {
+        let Some(entry) = self.selected().cloned() else {
+            return;
+        };
+        if is_dir(&entry) {
+            self.status = format!("Not a file: {}", entry.name);
+            return;
+        }
+        self.status = match self.save(&entry).await {
+            Ok((path, written)) => format!(
+                "Downloaded {} to {} ({})",
+                entry.name,
+                path.display(),
+                humansize::format_size(written, humansize::BINARY)
+            ),
+            Err(e) => format!("Error: {e:#}"),
+        };
+    }
+
+    async fn save(&self, entry: &Entry) -> Result<(std::path::PathBuf, u64)> {
+        let path = local_path_under(Path::new("."), &entry.name).ok_or_else(|| {
+            anyhow!(
+                "Refusing to write outside the current directory: {}",
+                entry.name
+            )
+        })?;
+        match self.session.resolve(&entry.path).await? {
+            VfsNode::Object {
+                bucket, key, size, ..
+            } => {
+                let written = self
+                    .session
+                    .client()
+                    .download_file(&bucket, &key, Some(size), &path)
+                    .await?;
+                Ok((path, written))
+            }
+            _ => {
+                let bytes = self.session.read(&entry.path).await?;
+                tokio::fs::write(&path, &bytes).await?;
+                Ok((path, bytes.len() as u64))
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .areas(body);
+
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::styled(
+                    " s3sh ",
+                    Style::new().bold().fg(Color::Black).bg(Color::Cyan),
+                ),
+                Span::raw(" "),
+                Span::styled(self.session.cwd().path(), Style::new().bold()),
+            ])),
+            header,
+        );
+
+        let items: Vec<ListItem> = self.entries.iter().map(list_item).collect();
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" {} entries ", self.entries.len())),
+            )
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, left, &mut self.list);
+
+        let title = match (self.selected(), self.mode) {
+            (Some(entry), PreviewMode::Hex) => format!(" {} (hex) ", entry.name),
+            (Some(entry), PreviewMode::Auto) => format!(" {} ", entry.name),
+            (None, _) => " Preview ".to_string(),
+        };
+        let lines: Vec<Line> = self.preview_lines().into_iter().map(Line::from).collect();
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(Block::default().borders(Borders::ALL).title(title))
+                .scroll((self.scroll, 0)),
+            right,
+        );
+
+        let footer_text = if self.status.is_empty() {
+            "↑↓ move  Enter open  ← up  PgUp/PgDn scroll  x hex  d download  r refresh  q quit"
+                .to_string()
+        } else {
+            self.status.clone()
+        };
+        frame.render_widget(
+            Paragraph::new(footer_text).style(Style::new().fg(Color::DarkGray)),
+            footer,
+        );
+    }
+
+    fn preview_lines(&self) -> Vec<String> {
+        match &self.preview {
+            None => self.note.clone(),
+            Some(Err(e)) => vec![format!("Error: {e}")],
+            Some(Ok(bytes)) => match self.mode {
+                PreviewMode::Auto if !looks_binary(bytes) => String::from_utf8_lossy(bytes)
+                    .lines()
+                    .map(|line| line.replace('\t', "    "))
+                    .collect(),
+                _ => hex_dump(0, bytes),
+            },
+        }
+    }
+}
+
+/// Whether an entry opens like a directory (buckets, prefixes, and navigable archives)
+fn is_dir(entry: &Entry) -> bool {
+    match entry.kind {
+        EntryKind::Bucket | EntryKind::Directory => true,
+        EntryKind::File => format_for_name(&entry.name).is_some_and(|f| f.is_navigable()),
+    }
+}
+
+fn list_item(entry: &Entry) -> ListItem<'static> {
+    if is_dir(entry) {
+        let style = match entry.kind {
+            EntryKind::File => Style::new().fg(Color::Magenta),
+            _ => Style::new().fg(Color::Blue).bold(),
+        };
+        return ListItem::new(Line::styled(format!("{}/", entry.name), style));
+    }
+    let size = entry
+        .size
+        .map(|size| humansize::format_size(size, humansize::BINARY))
+        .unwrap_or_default();
+    ListItem::new(Line::from(vec![
+        Span::raw(entry.name.clone()),
+        Span::styled(format!("  {size}"), Style::new().fg(Color::DarkGray)),
+    ]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, kind: EntryKind) -> Entry {
+        Entry {
+            name: name.to_string(),
+            path: format!("/bucket/{name}"),
+            kind,
+            size: Some(1),
+            modified: None,
+        }
+    }
+
+    #[test]
+    fn test_is_dir() {
+        assert!(is_dir(&entry("logs", EntryKind::Directory)));
+        assert!(is_dir(&entry("my-bucket", EntryKind::Bucket)));
+        assert!(is_dir(&entry("backup.tar.gz", EntryKind::File)));
+        assert!(!is_dir(&entry("notes.txt", EntryKind::File)));
+        assert!(!is_dir(&entry("data.gz", EntryKind::File)));
+    }
+}
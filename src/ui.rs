@@ -1,9 +1,67 @@
-use indicatif::{ProgressBar, ProgressStyle};
+use anyhow::{Result, anyhow};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::io::{BufRead, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+use crate::s3::S3Metrics;
+
+tokio::task_local! {
+    /// Set while a background job runs: spinners and progress bars aren't drawn over
+    /// the prompt, and the latest one's message is kept here for `jobs`
+    pub static JOB_STATUS: Arc<Mutex<String>>;
+}
+
+/// Whether the current task is a background job
+fn in_background() -> bool {
+    JOB_STATUS.try_with(|_| ()).is_ok()
+}
+
+/// Record what a background job is doing, if this is one
+fn set_job_status(message: &str) {
+    let _ = JOB_STATUS.try_with(|status| *status.lock().unwrap() = message.to_string());
+}
+
+/// Set by --no-color, which wins over the config file for the whole session
+static COLOR_DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Turn colored output off for good (--no-color)
+pub fn disable_color() {
+    COLOR_DISABLED.store(true, Ordering::Relaxed);
+    set_color(Some(false));
+}
+
+/// Apply the `color` setting to text and progress bars (None = color on terminals)
+/// --no-color and a non-empty NO_COLOR (https://no-color.org) take precedence over it.
+pub fn set_color(color: Option<bool>) {
+    let no_color = COLOR_DISABLED.load(Ordering::Relaxed)
+        || std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    let color = if no_color { Some(false) } else { color };
+    match color {
+        Some(color) => {
+            colored::control::set_override(color);
+            console::set_colors_enabled(color);
+            console::set_colors_enabled_stderr(color);
+        }
+        None => {
+            colored::control::unset_override();
+            console::set_colors_enabled(console::Term::stdout().features().colors_supported());
+            console::set_colors_enabled_stderr(
+                console::Term::stderr().features().colors_supported(),
+            );
+        }
+    }
+}
+
 /// Create a spinner with a cyan color and custom message
 pub fn create_spinner(message: &str) -> ProgressBar {
-    let spinner = ProgressBar::new_spinner();
+    set_job_status(message);
+    let spinner = if in_background() {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new_spinner()
+    };
     spinner.set_style(
         ProgressStyle::default_spinner()
             .template("{spinner:.cyan} {msg}")
@@ -13,3 +71,95 @@ pub fn create_spinner(message: &str) -> ProgressBar {
     spinner.enable_steady_tick(Duration::from_millis(100));
     spinner
 }
+
+/// Create a progress bar for a transfer of `total` bytes, with throughput and ETA
+/// The position follows the client's metrics, so every byte the command moves counts
+/// (without metrics it stays at zero). Nothing is drawn unless `visible` and stderr is
+/// a terminal, so piped or redirected output stays clean.
+pub fn create_progress_bar(
+    message: &str,
+    total: u64,
+    metrics: Option<&Arc<S3Metrics>>,
+    visible: bool,
+) -> ProgressBar {
+    set_job_status(message);
+    let target = if visible && !in_background() && std::io::stderr().is_terminal() {
+        ProgressDrawTarget::stderr()
+    } else {
+        ProgressDrawTarget::hidden()
+    };
+    let bar = ProgressBar::with_draw_target(Some(total), target);
+    bar.set_style(
+        ProgressStyle::default_bar()
+            .template(
+                "{spinner:.cyan} {msg} [{bar:30.cyan/blue}] {bytes}/{total_bytes} ({binary_bytes_per_sec}, ETA {eta})",
+            )
+            .unwrap()
+            .progress_chars("=> "),
+    );
+    bar.set_message(message.to_string());
+    bar.enable_steady_tick(Duration::from_millis(100));
+
+    if let Some(metrics) = metrics {
+        // Poll until the bar is finished or dropped
+        let metrics = Arc::clone(metrics);
+        let start = metrics.transferred_bytes();
+        let weak = bar.downgrade();
+        tokio::spawn(async move {
+            while let Some(bar) = weak.upgrade().filter(|bar| !bar.is_finished()) {
+                bar.set_position(metrics.transferred_bytes().saturating_sub(start));
+                drop(bar);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        });
+    }
+    bar
+}
+
+/// Ask a yes/no question on the terminal, defaulting to "no"
+/// Returns false when stdin is closed (e.g. in non-interactive scripts)
+pub fn confirm(message: &str) -> Result<bool> {
+    // The prompt owns the terminal while a job runs in the background
+    if in_background() {
+        return Err(anyhow!(
+            "Can't ask \"{message}\" in a background job; run the command in the foreground"
+        ));
+    }
+    eprint!("{message} [y/N] ");
+    std::io::stderr().flush()?;
+
+    let mut answer = String::new();
+    if std::io::stdin().lock().read_line(&mut answer)? == 0 {
+        eprintln!();
+        return Ok(false);
+    }
+
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Whether bytes look binary: they have NUL bytes or aren't UTF-8 (ignoring a
+/// character cut off at the end, as when previewing the start of a file)
+pub fn looks_binary(bytes: &[u8]) -> bool {
+    if bytes.contains(&0) {
+        return true;
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(_) => false,
+        Err(e) => e.error_len().is_some(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_looks_binary() {
+        assert!(!looks_binary(b"hello\nworld\n"));
+        assert!(!looks_binary(b""));
+        assert!(looks_binary(b"PK\x03\x04\x00\x00"));
+        assert!(looks_binary(b"\xff\xfe plain"));
+        // A multi-byte character split by the preview range is still text
+        assert!(!looks_binary(&"café".as_bytes()[..4]));
+    }
+}
@@ -1,7 +1,11 @@
 pub mod node;
 pub mod path;
+pub mod resolver;
 
 #[cfg(feature = "parquet")]
 pub use node::ParquetEntryHandler;
-pub use node::{ArchiveEntry, ArchiveIndex, ArchiveType, EntryType, VfsNode};
+pub use node::{
+    ArchiveEntry, ArchiveIndex, ArchiveType, EntryAttrs, EntryType, JsonEntryHandler, VfsNode,
+};
 pub use path::VirtualPath;
+pub use resolver::PathResolver;
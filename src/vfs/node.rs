@@ -1,5 +1,7 @@
 use std::sync::Arc;
 
+use crate::s3::http::is_http_origin;
+
 /// Represents different types of archives we can navigate into
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ArchiveType {
@@ -7,39 +9,28 @@ pub enum ArchiveType {
     TarGz,
     TarBz2,
     Zip,
+    Xlsx,
     Gz,
     Bz2,
+    Json,
     #[cfg(feature = "parquet")]
     Parquet,
 }
 
 impl ArchiveType {
-    /// Detect archive type from file extension
+    /// Detect archive type from file extension (see [`crate::archive::FORMATS`])
     pub fn from_path(path: &str) -> Option<Self> {
-        let path_lower = path.to_lowercase();
-        if path_lower.ends_with(".tar.gz") || path_lower.ends_with(".tgz") {
-            return Some(ArchiveType::TarGz);
-        }
-        if path_lower.ends_with(".tar.bz2") || path_lower.ends_with(".tbz2") {
-            return Some(ArchiveType::TarBz2);
-        }
-        if path_lower.ends_with(".tar") {
-            return Some(ArchiveType::Tar);
-        }
-        if path_lower.ends_with(".zip") {
-            return Some(ArchiveType::Zip);
-        }
-        if path_lower.ends_with(".gz") {
-            return Some(ArchiveType::Gz);
-        }
-        if path_lower.ends_with(".bz2") {
-            return Some(ArchiveType::Bz2);
-        }
-        #[cfg(feature = "parquet")]
-        if path_lower.ends_with(".parquet") {
-            return Some(ArchiveType::Parquet);
-        }
-        None
+        crate::archive::format_for_name(path).map(|format| format.archive_type.clone())
+    }
+
+    /// Whether indexing requires streaming the entire object
+    /// Tar formats have no central directory, so every header must be read,
+    /// and JSON must be parsed in full to find its records
+    pub fn requires_full_scan(&self) -> bool {
+        matches!(
+            self,
+            ArchiveType::Tar | ArchiveType::TarGz | ArchiveType::TarBz2 | ArchiveType::Json
+        )
     }
 }
 
@@ -59,28 +50,114 @@ pub enum EntryType {
         /// CRC-32 checksum for data integrity verification
         crc32: u32,
     },
+    /// Excel worksheet, stored as an XML part inside the workbook zip
+    XlsxSheet {
+        /// Path of the worksheet XML within the zip
+        part: String,
+        /// Position of the sheet in the workbook's tab order
+        position: usize,
+    },
+    /// Virtual entry for JSON/NDJSON files
+    JsonVirtual { handler: JsonEntryHandler },
     /// Virtual entry for Parquet files
     #[cfg(feature = "parquet")]
     ParquetVirtual { handler: ParquetEntryHandler },
 }
 
+#[derive(Debug, Clone)]
+pub enum JsonEntryHandler {
+    /// _schema.txt virtual file
+    Schema,
+    /// Values of one top-level field across all records, in columns/
+    Column { name: String },
+    /// A single record or object value, read by byte range
+    Value { offset: u64, length: u64 },
+}
+
 #[cfg(feature = "parquet")]
 #[derive(Debug, Clone)]
 pub enum ParquetEntryHandler {
     /// _schema.txt virtual file
     Schema,
+    /// _head.csv virtual file with the first rows of all flat columns
+    Head,
     /// Column statistics file in stats/
     ColumnStats {
         column_index: usize,
         column_name: String,
     },
     /// Column data file in columns/
+    /// field_path holds child indices into struct columns (empty for top-level columns)
     ColumnData {
         column_index: usize,
         column_name: String,
+        field_path: Vec<usize>,
+    },
+    /// Overview of every row group in row_groups/_summary.txt
+    RowGroupSummary,
+    /// Overview of one row group in row_groups/NNN/_info.txt
+    RowGroupInfo { row_group: usize },
+    /// Column chunk details in row_groups/NNN/
+    /// column_index is the leaf column index, so nested columns are included
+    RowGroupColumn {
+        row_group: usize,
+        column_index: usize,
+        column_name: String,
     },
 }
 
+/// File attributes an archive records for an entry; formats fill in what they have
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EntryAttrs {
+    /// Modification time, seconds since the Unix epoch
+    pub mtime: Option<i64>,
+    /// Unix permission bits, like 0o644
+    pub mode: Option<u32>,
+    pub uid: Option<u64>,
+    pub gid: Option<u64>,
+    /// Owner user and group names (tar)
+    pub user: Option<String>,
+    pub group: Option<String>,
+    /// The entry is a symbolic link (tar, and zips made on Unix)
+    pub symlink: bool,
+}
+
+impl EntryAttrs {
+    /// Modification time as "2024-01-31 12:00:00"
+    pub fn modified(&self) -> Option<String> {
+        let time = chrono::DateTime::from_timestamp(self.mtime?, 0)?;
+        Some(time.format("%Y-%m-%d %H:%M:%S").to_string())
+    }
+
+    /// Permissions as `ls -l` shows them, like "drwxr-xr-x"
+    pub fn permissions(&self, is_dir: bool) -> Option<String> {
+        let mode = self.mode?;
+        let mut text = String::with_capacity(10);
+        text.push(match (is_dir, self.symlink) {
+            (true, _) => 'd',
+            (false, true) => 'l',
+            (false, false) => '-',
+        });
+        for shift in [6, 3, 0] {
+            let bits = (mode >> shift) & 0o7;
+            text.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+            text.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+            text.push(if bits & 0o1 != 0 { 'x' } else { '-' });
+        }
+        Some(text)
+    }
+
+    /// Owner as "user:group", falling back to numeric IDs
+    pub fn owner(&self) -> Option<String> {
+        let user = self.user.clone().or_else(|| Some(self.uid?.to_string()))?;
+        let group = self.group.clone().or_else(|| Some(self.gid?.to_string()));
+        Some(match group {
+            Some(group) => format!("{user}:{group}"),
+            None => user,
+        })
+    }
+}
+
 /// Archive index entry - cached metadata about files in an archive
 #[derive(Debug, Clone)]
 pub struct ArchiveEntry {
@@ -88,6 +165,8 @@ pub struct ArchiveEntry {
     pub size: u64,
     pub is_dir: bool,
     pub entry_type: EntryType,
+    /// Timestamps, permissions, and ownership, where the format records them
+    pub attrs: EntryAttrs,
 }
 
 impl ArchiveEntry {
@@ -98,6 +177,7 @@ impl ArchiveEntry {
             size,
             is_dir,
             entry_type: EntryType::Physical { offset },
+            attrs: EntryAttrs::default(),
         }
     }
 
@@ -121,6 +201,29 @@ impl ArchiveEntry {
                 compression_method,
                 crc32,
             },
+            attrs: EntryAttrs::default(),
+        }
+    }
+
+    /// Create an Excel worksheet entry
+    pub fn xlsx_sheet(path: String, size: u64, part: String, position: usize) -> Self {
+        Self {
+            path,
+            size,
+            is_dir: false,
+            entry_type: EntryType::XlsxSheet { part, position },
+            attrs: EntryAttrs::default(),
+        }
+    }
+
+    /// Create a virtual JSON entry
+    pub fn json_virtual(path: String, size: u64, is_dir: bool, handler: JsonEntryHandler) -> Self {
+        Self {
+            path,
+            size,
+            is_dir,
+            entry_type: EntryType::JsonVirtual { handler },
+            attrs: EntryAttrs::default(),
         }
     }
 
@@ -137,8 +240,27 @@ impl ArchiveEntry {
             size,
             is_dir,
             entry_type: EntryType::ParquetVirtual { handler },
+            attrs: EntryAttrs::default(),
         }
     }
+
+    /// Compressed size and compression method of a file in a zip
+    pub fn compression(&self) -> Option<(u64, u16)> {
+        match self.entry_type {
+            EntryType::ZipEntry {
+                compressed_size,
+                compression_method,
+                ..
+            } if !self.is_dir => Some((compressed_size, compression_method)),
+            _ => None,
+        }
+    }
+
+    /// Attach the attributes the archive records for this entry
+    pub fn with_attrs(mut self, attrs: EntryAttrs) -> Self {
+        self.attrs = attrs;
+        self
+    }
 }
 
 /// Archive index - maps file paths to their metadata
@@ -149,6 +271,9 @@ pub struct ArchiveIndex {
     /// Cached object_store client for Parquet files (avoids reloading credentials)
     #[cfg(feature = "parquet")]
     pub parquet_store: Option<std::sync::Arc<dyn object_store::ObjectStore>>,
+    /// Parsed Parquet footer (metadata + Arrow schema), read once when the index is built
+    #[cfg(feature = "parquet")]
+    pub parquet_metadata: Option<parquet::arrow::arrow_reader::ArrowReaderMetadata>,
 }
 
 impl ArchiveIndex {
@@ -179,6 +304,8 @@ pub enum VfsNode {
         bucket: String,
         key: String,
         size: u64,
+        /// A specific object version, or None for the latest
+        version_id: Option<String>,
     },
 
     /// An archive file that can be navigated into
@@ -221,4 +348,92 @@ impl VfsNode {
     pub fn is_navigable(&self) -> bool {
         self.is_listable()
     }
+
+    /// The canonical path of this node, as shown by the prompt and `pwd`
+    /// Archives keep their file name, so entries read `/bucket/key.tar.gz/inner/dir`;
+    /// files opened by URL start with the URL instead of a bucket.
+    pub fn path(&self) -> String {
+        match self {
+            VfsNode::Root => "/".to_string(),
+            VfsNode::Bucket { name } => format!("/{name}"),
+            VfsNode::Prefix { bucket, prefix } => {
+                format!("/{bucket}/{}", prefix.trim_end_matches('/'))
+            }
+            VfsNode::Object { bucket, key, .. } if is_http_origin(bucket) => {
+                format!("{bucket}/{key}")
+            }
+            VfsNode::Object { bucket, key, .. } => format!("/{bucket}/{key}"),
+            VfsNode::Archive { parent, .. } => parent.path(),
+            VfsNode::ArchiveEntry { archive, path, .. } => {
+                let archive_path = archive.path();
+                match path.trim_matches('/') {
+                    "" => archive_path,
+                    path => format!("{archive_path}/{path}"),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path() {
+        let object = VfsNode::Object {
+            bucket: "b".to_string(),
+            key: "backups/data.tar.gz".to_string(),
+            size: 0,
+            version_id: None,
+        };
+        let archive = VfsNode::Archive {
+            parent: Box::new(object),
+            archive_type: ArchiveType::TarGz,
+            index: None,
+        };
+        assert_eq!(archive.path(), "/b/backups/data.tar.gz");
+
+        let entry = VfsNode::ArchiveEntry {
+            archive: Box::new(archive),
+            path: "configs/app/".to_string(),
+            size: 0,
+            is_dir: true,
+        };
+        assert_eq!(entry.path(), "/b/backups/data.tar.gz/configs/app");
+
+        // An archive nested inside another one
+        let nested = VfsNode::ArchiveEntry {
+            archive: Box::new(VfsNode::Archive {
+                parent: Box::new(entry),
+                archive_type: ArchiveType::Zip,
+                index: None,
+            }),
+            path: "x".to_string(),
+            size: 0,
+            is_dir: false,
+        };
+        assert_eq!(nested.path(), "/b/backups/data.tar.gz/configs/app/x");
+
+        let remote = VfsNode::Archive {
+            parent: Box::new(VfsNode::Object {
+                bucket: "https://example.com".to_string(),
+                key: "files/a.zip".to_string(),
+                size: 0,
+                version_id: None,
+            }),
+            archive_type: ArchiveType::Zip,
+            index: None,
+        };
+        assert_eq!(remote.path(), "https://example.com/files/a.zip");
+        assert_eq!(
+            VfsNode::Prefix {
+                bucket: "b".to_string(),
+                prefix: "logs/2024/".to_string()
+            }
+            .path(),
+            "/b/logs/2024"
+        );
+        assert_eq!(VfsNode::Root.path(), "/");
+    }
 }
@@ -0,0 +1,452 @@
+//! Path resolution shared by the commands that take a path (cd, ls, cat, ...).
+//!
+//! Paths are absolute (from the bucket list) or relative to a node, and may run through
+//! `.`, `..`, archives, and directories inside archives. Archive objects met along the
+//! way are opened, building their index (or reusing a cached one) as needed.
+
+use anyhow::{Result, anyhow};
+use std::sync::Arc;
+
+use super::{ArchiveIndex, VfsNode};
+use crate::archive::{
+    ArchiveFormat, SNIFF_LEN, format_for_mime, format_for_name, handler_for, sniff,
+};
+use crate::cache::ArchiveCache;
+use crate::s3::S3Client;
+use crate::s3::http::is_http_origin;
+use crate::ui::{confirm, create_progress_bar, create_spinner};
+
+/// Resolves paths to VFS nodes against S3 and the archive index cache
+pub struct PathResolver {
+    client: Arc<S3Client>,
+    cache: ArchiveCache,
+    /// Archives that must be streamed in full ask before indexing above this size
+    confirm_above: Option<u64>,
+}
+
+impl PathResolver {
+    pub fn new(client: Arc<S3Client>, cache: ArchiveCache) -> Self {
+        PathResolver {
+            client,
+            cache,
+            confirm_above: None,
+        }
+    }
+
+    /// Ask before indexing full-scan archives larger than this (None = never ask)
+    pub fn confirm_above(mut self, threshold: Option<u64>) -> Self {
+        self.confirm_above = threshold;
+        self
+    }
+
+    /// Resolve a path, absolute or relative to `from`
+    /// An archive named by the last segment is left as an object; use
+    /// [`resolve_dir`](Self::resolve_dir) to open it.
+    pub async fn resolve(&self, from: &VfsNode, path: &str) -> Result<VfsNode> {
+        let mut node = if path.starts_with('/') {
+            VfsNode::Root
+        } else {
+            from.clone()
+        };
+
+        for segment in path.split('/').filter(|s| !s.is_empty() && *s != ".") {
+            node = if segment == ".." {
+                Self::parent(&node)
+            } else {
+                // Boxed to keep the future small; child recurses through archives
+                Box::pin(self.child(&node, segment)).await?
+            };
+        }
+
+        Ok(node)
+    }
+
+    /// Resolve a path that must name a directory (a bucket, prefix, archive, or
+    /// directory inside an archive), opening an archive at the end of it
+    pub async fn resolve_dir(&self, from: &VfsNode, path: &str) -> Result<VfsNode> {
+        let node = self.resolve(from, path).await?;
+
+        // Names that aren't objects resolve to prefixes; only real ones (non-empty) count
+        if let VfsNode::Prefix { bucket, prefix } = &node
+            && !self.client.prefix_exists(bucket, prefix).await?
+        {
+            return Err(anyhow!("No such file or directory: {path}"));
+        }
+
+        let node = self.open_archive(node).await?;
+        if !node.is_navigable() {
+            return Err(anyhow!("Not a directory: {path}"));
+        }
+        Ok(node)
+    }
+
+    /// The directory containing a node
+    /// The parent of an archive is the directory holding the archive file.
+    pub fn parent(node: &VfsNode) -> VfsNode {
+        match node {
+            VfsNode::Root | VfsNode::Bucket { .. } => VfsNode::Root,
+
+            VfsNode::Prefix { bucket, prefix } => {
+                parent_of_key(bucket, prefix.trim_end_matches('/'))
+            }
+
+            // HTTP(S) files have no listable parent
+            VfsNode::Object { bucket, .. } if is_http_origin(bucket) => VfsNode::Root,
+
+            VfsNode::Object { bucket, key, .. } => parent_of_key(bucket, key),
+
+            VfsNode::Archive { parent, .. } => Self::parent(parent),
+
+            VfsNode::ArchiveEntry { archive, path, .. } => {
+                match path.trim_end_matches('/').rsplit_once('/') {
+                    Some((parent_path, _)) => {
+                        // Take the directory's size from the index when it has an entry;
+                        // tar archives often leave intermediate directories implicit
+                        let size = match archive.as_ref() {
+                            VfsNode::Archive {
+                                index: Some(index), ..
+                            } => index.find_entry(parent_path).map_or(0, |entry| entry.size),
+                            _ => 0,
+                        };
+                        VfsNode::ArchiveEntry {
+                            archive: archive.clone(),
+                            path: parent_path.to_string(),
+                            size,
+                            is_dir: true,
+                        }
+                    }
+                    None => *archive.clone(),
+                }
+            }
+        }
+    }
+
+    /// The node named `name` inside a directory
+    /// Inside buckets and prefixes, a name that isn't an object is taken as a prefix.
+    pub async fn child(&self, node: &VfsNode, name: &str) -> Result<VfsNode> {
+        match node {
+            VfsNode::Root => Ok(VfsNode::Bucket {
+                name: name.to_string(),
+            }),
+
+            VfsNode::Bucket { name: bucket } => Ok(self.s3_child(bucket, "", name).await),
+
+            VfsNode::Prefix { bucket, prefix } => Ok(self.s3_child(bucket, prefix, name).await),
+
+            VfsNode::Object { key, .. } => {
+                let archive = self.open_archive(node.clone()).await?;
+                if !archive.is_navigable() {
+                    return Err(anyhow!("Not a directory: {key}"));
+                }
+                Box::pin(self.child(&archive, name)).await
+            }
+
+            VfsNode::Archive { .. } => self.archive_child(node, "", name).await,
+
+            VfsNode::ArchiveEntry {
+                archive,
+                path,
+                is_dir,
+                ..
+            } => {
+                if !is_dir {
+                    return Err(anyhow!("Not a directory: {path}"));
+                }
+                self.archive_child(archive, path, name).await
+            }
+        }
+    }
+
+    /// Turn an archive object into an archive node with its index; other nodes pass through
+    pub async fn open_archive(&self, node: VfsNode) -> Result<VfsNode> {
+        let VfsNode::Object {
+            bucket, key, size, ..
+        } = &node
+        else {
+            return Ok(node);
+        };
+        let format = match format_for_name(key) {
+            Some(format) => Some(format),
+            None => self.detect_format(bucket, key, *size).await?,
+        };
+        let Some(format) = format.filter(|format| format.is_navigable()) else {
+            return Ok(node);
+        };
+        let archive_type = format.archive_type.clone();
+
+        let mut archive = VfsNode::Archive {
+            parent: Box::new(node),
+            archive_type,
+            index: None,
+        };
+        let index = self.index(&archive).await?;
+        if let VfsNode::Archive { index: slot, .. } = &mut archive {
+            *slot = Some(index);
+        }
+        Ok(archive)
+    }
+
+    /// The index of an archive node, from the node itself, the cache, or built from S3
+    pub async fn index(&self, archive: &VfsNode) -> Result<Arc<ArchiveIndex>> {
+        let VfsNode::Archive {
+            parent,
+            archive_type,
+            index,
+        } = archive
+        else {
+            return Err(anyhow!("Not an archive"));
+        };
+        if let Some(index) = index {
+            return Ok(Arc::clone(index));
+        }
+        let VfsNode::Object {
+            bucket, key, size, ..
+        } = parent.as_ref()
+        else {
+            return Err(anyhow!("Invalid archive parent"));
+        };
+
+        // Reuse a previously built index without touching S3 again
+        let cache_key = format!("s3://{bucket}/{key}");
+        if let Some(index) = self.cache.get(&cache_key) {
+            return Ok(index);
+        }
+
+        let handler = handler_for(archive_type)
+            .ok_or_else(|| anyhow!("Archive type not yet supported: {archive_type:?}"))?;
+        let filename = key.rsplit('/').next().unwrap_or(key);
+
+        // Streaming formats download the whole object to build an index
+        if archive_type.requires_full_scan()
+            && self
+                .confirm_above
+                .is_some_and(|threshold| *size > threshold)
+        {
+            let message = format!(
+                "Indexing {filename} will download ~{}. Continue?",
+                humansize::format_size(*size, humansize::DECIMAL)
+            );
+            if !confirm(&message)? {
+                return Err(anyhow!("Cancelled: {filename} was not indexed"));
+            }
+        }
+
+        // Show progress while building the index; full scans report bytes read
+        let spinner = if archive_type.requires_full_scan() {
+            create_progress_bar(
+                &format!("Building index for {filename}"),
+                *size,
+                self.client.metrics(),
+                true,
+            )
+        } else {
+            create_spinner(&format!("Building index for {filename}..."))
+        };
+        let index = handler.build_index(&self.client, bucket, key).await;
+        spinner.finish_and_clear();
+
+        let index = Arc::new(index?);
+        self.cache.put(cache_key, Arc::clone(&index));
+        Ok(index)
+    }
+
+    /// The format of an object without a known extension, from its content type or
+    /// failing that its first bytes
+    async fn detect_format(
+        &self,
+        bucket: &str,
+        key: &str,
+        size: u64,
+    ) -> Result<Option<&'static ArchiveFormat>> {
+        let metadata = self.client.head_object(bucket, key).await?;
+        if let Some(format) = metadata.content_type.as_deref().and_then(format_for_mime) {
+            return Ok(Some(format));
+        }
+        if size == 0 {
+            return Ok(None);
+        }
+        let head = self
+            .client
+            .get_object_range(bucket, key, 0, size.min(SNIFF_LEN))
+            .await?;
+        Ok(sniff(&head))
+    }
+
+    /// An object if `prefix` + `name` exists as one, otherwise a prefix
+    async fn s3_child(&self, bucket: &str, prefix: &str, name: &str) -> VfsNode {
+        let key = format!("{prefix}{name}");
+        match self.client.head_object(bucket, &key).await {
+            Ok(metadata) => VfsNode::Object {
+                bucket: bucket.to_string(),
+                key,
+                size: metadata.size,
+                version_id: None,
+            },
+            Err(_) => VfsNode::Prefix {
+                bucket: bucket.to_string(),
+                prefix: format!("{key}/"),
+            },
+        }
+    }
+
+    /// The entry named `name` in directory `dir` of an archive
+    async fn archive_child(&self, archive: &VfsNode, dir: &str, name: &str) -> Result<VfsNode> {
+        let index = self.index(archive).await?;
+        let target = match dir.trim_end_matches('/') {
+            "" => name.to_string(),
+            dir => format!("{dir}/{name}"),
+        };
+        // Archives may leave directories implicit, present only in their entries' paths
+        let (path, size, is_dir) = match index.find_entry(&target) {
+            Some(entry) => (
+                entry.path.trim_end_matches('/').to_string(),
+                entry.size,
+                entry.is_dir,
+            ),
+            None if index
+                .entries
+                .keys()
+                .any(|path| path.starts_with(&format!("{target}/"))) =>
+            {
+                (target, 0, true)
+            }
+            None => return Err(anyhow!("Path not found in archive: {target}")),
+        };
+
+        // Keep the index on the archive node so later lookups don't rebuild it
+        let archive = match archive {
+            VfsNode::Archive {
+                parent,
+                archive_type,
+                index: None,
+            } => VfsNode::Archive {
+                parent: parent.clone(),
+                archive_type: archive_type.clone(),
+                index: Some(Arc::clone(&index)),
+            },
+            archive => archive.clone(),
+        };
+
+        // Store the path without trailing slash for consistency
+        Ok(VfsNode::ArchiveEntry {
+            archive: Box::new(archive),
+            path,
+            size,
+            is_dir,
+        })
+    }
+}
+
+/// The prefix (or bucket) holding a key
+fn parent_of_key(bucket: &str, key: &str) -> VfsNode {
+    match key.rsplit_once('/') {
+        Some((parent, _)) => VfsNode::Prefix {
+            bucket: bucket.to_string(),
+            prefix: format!("{parent}/"),
+        },
+        None => VfsNode::Bucket {
+            name: bucket.to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::{ArchiveEntry, ArchiveType};
+    use std::collections::HashMap;
+
+    fn path_of(node: &VfsNode) -> String {
+        match node {
+            VfsNode::Root => "/".to_string(),
+            VfsNode::Bucket { name } => format!("/{name}"),
+            VfsNode::Prefix { bucket, prefix } => format!("/{bucket}/{prefix}"),
+            VfsNode::Object { bucket, key, .. } => format!("/{bucket}/{key}"),
+            VfsNode::Archive { parent, .. } => format!("{}!", path_of(parent)),
+            VfsNode::ArchiveEntry { archive, path, .. } => format!("{}{path}", path_of(archive)),
+        }
+    }
+
+    #[test]
+    fn test_parent() {
+        let object = VfsNode::Object {
+            bucket: "b".to_string(),
+            key: "logs/2024/x.tar".to_string(),
+            size: 0,
+            version_id: None,
+        };
+        assert_eq!(path_of(&PathResolver::parent(&object)), "/b/logs/2024/");
+
+        let prefix = PathResolver::parent(&PathResolver::parent(&object));
+        assert_eq!(path_of(&prefix), "/b/logs/");
+        assert_eq!(path_of(&PathResolver::parent(&prefix)), "/b");
+
+        let archive = VfsNode::Archive {
+            parent: Box::new(object),
+            archive_type: ArchiveType::Tar,
+            index: None,
+        };
+        let entry = VfsNode::ArchiveEntry {
+            archive: Box::new(archive.clone()),
+            path: "a/b/c".to_string(),
+            size: 0,
+            is_dir: true,
+        };
+        assert_eq!(
+            path_of(&PathResolver::parent(&entry)),
+            "/b/logs/2024/x.tar!a/b"
+        );
+        assert_eq!(path_of(&PathResolver::parent(&archive)), "/b/logs/2024/");
+
+        let top = VfsNode::ArchiveEntry {
+            archive: Box::new(archive),
+            path: "a".to_string(),
+            size: 0,
+            is_dir: true,
+        };
+        assert_eq!(path_of(&PathResolver::parent(&top)), "/b/logs/2024/x.tar!");
+    }
+
+    #[test]
+    fn test_parent_in_nested_archive_dirs() {
+        let mut entries = HashMap::new();
+        entries.insert(
+            "a/b/".to_string(),
+            ArchiveEntry::physical("a/b/".to_string(), 0, 3, true),
+        );
+        let archive = VfsNode::Archive {
+            parent: Box::new(VfsNode::Object {
+                bucket: "b".to_string(),
+                key: "x.tar".to_string(),
+                size: 0,
+                version_id: None,
+            }),
+            archive_type: ArchiveType::Tar,
+            index: Some(Arc::new(ArchiveIndex {
+                entries,
+                metadata: HashMap::new(),
+                #[cfg(feature = "parquet")]
+                parquet_store: None,
+                #[cfg(feature = "parquet")]
+                parquet_metadata: None,
+            })),
+        };
+        let entry = VfsNode::ArchiveEntry {
+            archive: Box::new(archive),
+            path: "a/b/c".to_string(),
+            size: 0,
+            is_dir: true,
+        };
+
+        // a/b/c -> a/b (found in the index) -> a (implicit) -> archive root
+        let up = PathResolver::parent(&entry);
+        assert!(
+            matches!(&up, VfsNode::ArchiveEntry { path, size: 3, is_dir: true, .. } if path == "a/b")
+        );
+        let up = PathResolver::parent(&up);
+        assert!(
+            matches!(&up, VfsNode::ArchiveEntry { path, size: 0, is_dir: true, .. } if path == "a")
+        );
+        assert!(matches!(PathResolver::parent(&up), VfsNode::Archive { .. }));
+    }
+}
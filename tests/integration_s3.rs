@@ -6,7 +6,7 @@ use std::sync::Arc;
 use s3sh::cache::ArchiveCache;
 use s3sh::s3::S3Client;
 use s3sh::shell::commands::{Command, cat::CatCommand, cd::CdCommand, ls::LsCommand};
-use s3sh::shell::{CompletionCache, ShellState};
+use s3sh::shell::{CompletionCache, Output, ShellState};
 use s3sh::vfs::VfsNode;
 
 /// Test bucket name
@@ -172,7 +172,11 @@ async fn test_cd_command() {
     // Test: cd into bucket
     let cd_cmd = CdCommand;
     cd_cmd
-        .execute(&mut shell, &[TEST_BUCKET.to_string()])
+        .execute(
+            &mut shell,
+            &[TEST_BUCKET.to_string()],
+            &mut Output::capture(),
+        )
         .await
         .expect("Failed to cd into bucket");
 
@@ -186,7 +190,7 @@ async fn test_cd_command() {
 
     // Test: cd into prefix (directory)
     cd_cmd
-        .execute(&mut shell, &["dir".to_string()])
+        .execute(&mut shell, &["dir".to_string()], &mut Output::capture())
         .await
         .expect("Failed to cd into dir");
 
@@ -201,7 +205,7 @@ async fn test_cd_command() {
 
     // Test: cd back to root
     cd_cmd
-        .execute(&mut shell, &["/".to_string()])
+        .execute(&mut shell, &["/".to_string()], &mut Output::capture())
         .await
         .expect("Failed to cd to root");
 
@@ -229,18 +233,30 @@ async fn test_cat_command() {
     // Navigate to bucket
     let cd_cmd = CdCommand;
     cd_cmd
-        .execute(&mut shell, &[TEST_BUCKET.to_string()])
+        .execute(
+            &mut shell,
+            &[TEST_BUCKET.to_string()],
+            &mut Output::capture(),
+        )
         .await
         .expect("Failed to cd into bucket");
 
     // Test: cat a simple file
     let cat_cmd = CatCommand;
-    let result = cat_cmd.execute(&mut shell, &["test.txt".to_string()]).await;
+    let mut out = Output::capture();
+    let result = cat_cmd
+        .execute(&mut shell, &["test.txt".to_string()], &mut out)
+        .await;
     assert!(result.is_ok(), "Failed to cat test.txt");
+    assert_eq!(out.captured(), Some(b"Hello from S3!\n".as_slice()));
 
     // Test: cat a nested file with absolute path
     let result = cat_cmd
-        .execute(&mut shell, &[format!("/{TEST_BUCKET}/dir/nested.txt")])
+        .execute(
+            &mut shell,
+            &[format!("/{TEST_BUCKET}/dir/nested.txt")],
+            &mut Output::capture(),
+        )
         .await;
     assert!(
         result.is_ok(),
@@ -266,13 +282,21 @@ async fn test_cd_into_archive() {
     // Navigate to bucket
     let cd_cmd = CdCommand;
     cd_cmd
-        .execute(&mut shell, &[TEST_BUCKET.to_string()])
+        .execute(
+            &mut shell,
+            &[TEST_BUCKET.to_string()],
+            &mut Output::capture(),
+        )
         .await
         .expect("Failed to cd into bucket");
 
     // Test: cd into tar.gz archive
     let result = cd_cmd
-        .execute(&mut shell, &["test.tar.gz".to_string()])
+        .execute(
+            &mut shell,
+            &["test.tar.gz".to_string()],
+            &mut Output::capture(),
+        )
         .await;
     assert!(result.is_ok(), "Failed to cd into test.tar.gz archive");
 
@@ -303,7 +327,11 @@ async fn test_cd_parent_from_archive() {
     // Navigate to bucket
     let cd_cmd = CdCommand;
     cd_cmd
-        .execute(&mut shell, &[TEST_BUCKET.to_string()])
+        .execute(
+            &mut shell,
+            &[TEST_BUCKET.to_string()],
+            &mut Output::capture(),
+        )
         .await
         .expect("Failed to cd into bucket");
 
@@ -317,7 +345,11 @@ async fn test_cd_parent_from_archive() {
 
     // Test: cd into tar.gz archive
     cd_cmd
-        .execute(&mut shell, &["test.tar.gz".to_string()])
+        .execute(
+            &mut shell,
+            &["test.tar.gz".to_string()],
+            &mut Output::capture(),
+        )
         .await
         .expect("Failed to cd into test.tar.gz archive");
 
@@ -331,7 +363,7 @@ async fn test_cd_parent_from_archive() {
 
     // Test: cd .. from archive root should go back to bucket
     cd_cmd
-        .execute(&mut shell, &["..".to_string()])
+        .execute(&mut shell, &["..".to_string()], &mut Output::capture())
         .await
         .expect("Failed to cd .. from archive root");
 
@@ -345,13 +377,17 @@ async fn test_cd_parent_from_archive() {
 
     // Navigate back into archive and then into a subdirectory
     cd_cmd
-        .execute(&mut shell, &["test.tar.gz".to_string()])
+        .execute(
+            &mut shell,
+            &["test.tar.gz".to_string()],
+            &mut Output::capture(),
+        )
         .await
         .expect("Failed to cd into test.tar.gz archive");
 
     // cd into app directory within archive
     cd_cmd
-        .execute(&mut shell, &["app".to_string()])
+        .execute(&mut shell, &["app".to_string()], &mut Output::capture())
         .await
         .expect("Failed to cd into app directory in archive");
 
@@ -366,7 +402,7 @@ async fn test_cd_parent_from_archive() {
 
     // Test: cd .. from archive subdirectory should go back to archive root
     cd_cmd
-        .execute(&mut shell, &["..".to_string()])
+        .execute(&mut shell, &["..".to_string()], &mut Output::capture())
         .await
         .expect("Failed to cd .. from archive subdirectory");
 
@@ -380,7 +416,7 @@ async fn test_cd_parent_from_archive() {
 
     // Test: cd .. again from archive root should go back to bucket
     cd_cmd
-        .execute(&mut shell, &["..".to_string()])
+        .execute(&mut shell, &["..".to_string()], &mut Output::capture())
         .await
         .expect("Failed to cd .. from archive root (second time)");
 
@@ -412,7 +448,11 @@ async fn test_pipe_support() {
     // Navigate to bucket
     let cd_cmd = CdCommand;
     cd_cmd
-        .execute(&mut shell, &[TEST_BUCKET.to_string()])
+        .execute(
+            &mut shell,
+            &[TEST_BUCKET.to_string()],
+            &mut Output::capture(),
+        )
         .await
         .expect("Failed to cd into bucket");
 
@@ -431,3 +471,119 @@ async fn test_pipe_support() {
     let result = shell.execute("pwd").await;
     assert!(result.is_ok(), "Non-piped command should still work");
 }
+
+#[tokio::test]
+#[ignore] // Run with: cargo test -- --ignored --test-threads=1
+async fn test_object_versions() {
+    let client = create_localstack_client().await;
+
+    // Verify localstack is running
+    let resp = client.list_buckets().send().await;
+    assert!(resp.is_ok(), "Failed to connect to Localstack S3");
+
+    // Versioned bucket with two writes to the same key
+    let bucket = "test-versions-bucket";
+    client
+        .create_bucket()
+        .bucket(bucket)
+        .send()
+        .await
+        .expect("Failed to create versioned bucket");
+    client
+        .put_bucket_versioning()
+        .bucket(bucket)
+        .versioning_configuration(
+            aws_sdk_s3::types::VersioningConfiguration::builder()
+                .status(aws_sdk_s3::types::BucketVersioningStatus::Enabled)
+                .build(),
+        )
+        .send()
+        .await
+        .expect("Failed to enable versioning");
+    for body in ["v1\n", "v2\n"] {
+        client
+            .put_object()
+            .bucket(bucket)
+            .key("notes.txt")
+            .body(Bytes::from(body).into())
+            .send()
+            .await
+            .expect("Failed to upload notes.txt");
+    }
+
+    let s3_client = S3Client::from_client(client, "us-east-1".to_string());
+
+    let result = s3_client
+        .list_object_versions(bucket, "notes.txt", None)
+        .await
+        .expect("Failed to list object versions");
+    assert_eq!(result.versions.len(), 2);
+    assert!(result.versions[0].is_latest);
+
+    // The older version is still readable by ID
+    let oldest = &result.versions[1];
+    let bytes = s3_client
+        .get_object_version(bucket, "notes.txt", Some(&oldest.version_id))
+        .await
+        .expect("Failed to get older version");
+    assert_eq!(&bytes[..], b"v1\n");
+
+    let metadata = s3_client
+        .head_object(bucket, "notes.txt")
+        .await
+        .expect("Failed to head notes.txt");
+    assert_eq!(
+        metadata.version_id.as_deref(),
+        Some(result.versions[0].version_id.as_str())
+    );
+}
+
+#[tokio::test]
+#[ignore] // Run with: cargo test -- --ignored --test-threads=1
+async fn test_session() {
+    use s3sh::Session;
+    use s3sh::session::EntryKind;
+
+    let client = create_localstack_client().await;
+    setup_test_bucket(&client).await;
+
+    let s3_client = Arc::new(S3Client::from_client(client, "us-east-1".to_string()));
+    let mut session = Session::new(s3_client);
+
+    // Buckets at the root
+    let buckets = session.ls("/").await.expect("Failed to list buckets");
+    assert!(
+        buckets
+            .iter()
+            .any(|e| e.name == TEST_BUCKET && e.kind == EntryKind::Bucket)
+    );
+
+    // Objects and prefixes in a bucket
+    session.cd(TEST_BUCKET).await.expect("Failed to cd");
+    let entries = session.ls(".").await.expect("Failed to list bucket");
+    let dir = entries.iter().find(|e| e.name == "dir").unwrap();
+    assert_eq!(dir.kind, EntryKind::Directory);
+    let file = entries.iter().find(|e| e.name == "test.txt").unwrap();
+    assert_eq!(file.path, format!("/{TEST_BUCKET}/test.txt"));
+    assert_eq!(file.size, Some(15));
+
+    // Archives list and read like directories
+    let entries = session.ls("test.tar.gz/app").await.unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(
+        entries[0].path,
+        format!("/{TEST_BUCKET}/test.tar.gz/app/nested.txt")
+    );
+    let bytes = session.read(&entries[0].path).await.unwrap();
+    assert_eq!(&bytes[..], b"Nested file content\n");
+    assert_eq!(
+        &session.read("test.txt").await.unwrap()[..],
+        b"Hello from S3!\n"
+    );
+
+    assert!(matches!(
+        session.resolve("test.tar.gz").await.unwrap(),
+        VfsNode::Object { .. }
+    ));
+    assert!(session.read("dir").await.is_err());
+}
@@ -16,7 +16,7 @@ use std::time::{Duration, Instant};
 use s3sh::cache::ArchiveCache;
 use s3sh::s3::{S3Client, S3Metrics};
 use s3sh::shell::commands::{Command, cat::CatCommand, cd::CdCommand, ls::LsCommand};
-use s3sh::shell::{CompletionCache, ShellState};
+use s3sh::shell::{CompletionCache, Output, ShellState};
 use s3sh::vfs::VfsNode;
 
 /// Test configuration from environment
@@ -199,7 +199,11 @@ async fn test_perf_cd_into_zip() {
     // Navigate to bucket
     let cd_cmd = CdCommand;
     cd_cmd
-        .execute(&mut shell, &[config.bucket.clone()])
+        .execute(
+            &mut shell,
+            std::slice::from_ref(&config.bucket),
+            &mut Output::capture(),
+        )
         .await
         .expect("Failed to cd into bucket");
 
@@ -211,7 +215,11 @@ async fn test_perf_cd_into_zip() {
 
     // CD into zip archive
     cd_cmd
-        .execute(&mut shell, &[zip_key.clone()])
+        .execute(
+            &mut shell,
+            std::slice::from_ref(zip_key),
+            &mut Output::capture(),
+        )
         .await
         .expect("Failed to cd into zip archive");
 
@@ -278,7 +286,11 @@ async fn test_perf_cd_into_targz() {
     // Navigate to bucket
     let cd_cmd = CdCommand;
     cd_cmd
-        .execute(&mut shell, &[config.bucket.clone()])
+        .execute(
+            &mut shell,
+            std::slice::from_ref(&config.bucket),
+            &mut Output::capture(),
+        )
         .await
         .expect("Failed to cd into bucket");
 
@@ -290,7 +302,11 @@ async fn test_perf_cd_into_targz() {
 
     // CD into tar.gz archive
     cd_cmd
-        .execute(&mut shell, &[targz_key.clone()])
+        .execute(
+            &mut shell,
+            std::slice::from_ref(targz_key),
+            &mut Output::capture(),
+        )
         .await
         .expect("Failed to cd into tar.gz archive");
 
@@ -344,14 +360,22 @@ async fn test_func_zip_cd_ls() {
     // cd into bucket
     let cd_cmd = CdCommand;
     cd_cmd
-        .execute(&mut shell, &[config.bucket.clone()])
+        .execute(
+            &mut shell,
+            std::slice::from_ref(&config.bucket),
+            &mut Output::capture(),
+        )
         .await
         .expect("Failed to cd into bucket");
 
     // cd into zip
     metrics.reset();
     cd_cmd
-        .execute(&mut shell, &[zip_key.clone()])
+        .execute(
+            &mut shell,
+            std::slice::from_ref(zip_key),
+            &mut Output::capture(),
+        )
         .await
         .expect("Failed to cd into zip archive");
 
@@ -368,7 +392,7 @@ async fn test_func_zip_cd_ls() {
     metrics.reset();
     let ls_cmd = LsCommand;
     ls_cmd
-        .execute(&mut shell, &[])
+        .execute(&mut shell, &[], &mut Output::capture())
         .await
         .expect("Failed to ls in archive");
 
@@ -387,7 +411,7 @@ async fn test_func_zip_cd_ls() {
 
     // cd .. back out
     cd_cmd
-        .execute(&mut shell, &["..".to_string()])
+        .execute(&mut shell, &["..".to_string()], &mut Output::capture())
         .await
         .expect("Failed to cd ..");
 
@@ -426,14 +450,22 @@ async fn test_func_targz_cd_ls() {
     // cd into bucket
     let cd_cmd = CdCommand;
     cd_cmd
-        .execute(&mut shell, &[config.bucket.clone()])
+        .execute(
+            &mut shell,
+            std::slice::from_ref(&config.bucket),
+            &mut Output::capture(),
+        )
         .await
         .expect("Failed to cd into bucket");
 
     // cd into tar.gz
     metrics.reset();
     cd_cmd
-        .execute(&mut shell, &[targz_key.clone()])
+        .execute(
+            &mut shell,
+            std::slice::from_ref(targz_key),
+            &mut Output::capture(),
+        )
         .await
         .expect("Failed to cd into tar.gz archive");
 
@@ -450,7 +482,7 @@ async fn test_func_targz_cd_ls() {
     metrics.reset();
     let ls_cmd = LsCommand;
     ls_cmd
-        .execute(&mut shell, &[])
+        .execute(&mut shell, &[], &mut Output::capture())
         .await
         .expect("Failed to ls in archive");
 
@@ -469,7 +501,7 @@ async fn test_func_targz_cd_ls() {
 
     // cd .. back out
     cd_cmd
-        .execute(&mut shell, &["..".to_string()])
+        .execute(&mut shell, &["..".to_string()], &mut Output::capture())
         .await
         .expect("Failed to cd ..");
 
@@ -512,21 +544,29 @@ async fn test_func_archive_navigation_roundtrip() {
 
     // cd bucket
     cd_cmd
-        .execute(&mut shell, &[config.bucket.clone()])
+        .execute(
+            &mut shell,
+            std::slice::from_ref(&config.bucket),
+            &mut Output::capture(),
+        )
         .await
         .expect("cd bucket");
     assert!(matches!(shell.current_node(), VfsNode::Bucket { .. }));
 
     // cd archive
     cd_cmd
-        .execute(&mut shell, &[archive_key.clone()])
+        .execute(
+            &mut shell,
+            std::slice::from_ref(archive_key),
+            &mut Output::capture(),
+        )
         .await
         .expect("cd archive");
     assert!(matches!(shell.current_node(), VfsNode::Archive { .. }));
 
     // cd .. (back to bucket or prefix, depending on archive location)
     cd_cmd
-        .execute(&mut shell, &["..".to_string()])
+        .execute(&mut shell, &["..".to_string()], &mut Output::capture())
         .await
         .expect("cd ..");
     assert!(
@@ -540,7 +580,7 @@ async fn test_func_archive_navigation_roundtrip() {
 
     // cd / (back to root)
     cd_cmd
-        .execute(&mut shell, &["/".to_string()])
+        .execute(&mut shell, &["/".to_string()], &mut Output::capture())
         .await
         .expect("cd /");
     assert!(matches!(shell.current_node(), VfsNode::Root));
@@ -572,13 +612,21 @@ async fn test_metrics_bytes_tracking() {
     // Navigate to archive
     let cd_cmd = CdCommand;
     cd_cmd
-        .execute(&mut shell, &[config.bucket.clone()])
+        .execute(
+            &mut shell,
+            std::slice::from_ref(&config.bucket),
+            &mut Output::capture(),
+        )
         .await
         .unwrap();
 
     metrics.reset();
     cd_cmd
-        .execute(&mut shell, &[archive_key.clone()])
+        .execute(
+            &mut shell,
+            std::slice::from_ref(archive_key),
+            &mut Output::capture(),
+        )
         .await
         .unwrap();
 